@@ -1,7 +1,7 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
-use super::query::CliDirection;
+use super::query::{CliDirection, CliGraphFormat};
 use super::ui::OutputFormat;
 
 /// Defines the top-level interface for the Firm CLI with clap.
@@ -35,7 +35,32 @@ pub enum FirmCliCommand {
     /// Initialize a new Firm workspace with default schemas and files.
     Init,
     /// Build workspace and entity graph.
-    Build,
+    Build {
+        /// Warn about path fields that point at missing files
+        #[arg(long)]
+        check_paths: bool,
+        /// Also check paths outside the workspace (implies --check-paths)
+        #[arg(long)]
+        allow_external_paths: bool,
+        /// Also write the built graph to this file, for other tools or caching
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Format of the graph written to --output
+        #[arg(long, value_enum, default_value_t = CliGraphFormat::Json, requires = "output")]
+        graph_format: CliGraphFormat,
+        /// Only build entities of these types into --output (comma-separated, e.g. task,project)
+        #[arg(long, value_delimiter = ',', requires = "output")]
+        only_type: Vec<String>,
+        /// Leave out entities of these types from --output (comma-separated, e.g. file_asset)
+        #[arg(long, value_delimiter = ',', requires = "output")]
+        exclude_type: Vec<String>,
+        /// Run the validation scripts that schemas declare (scripts can run any program)
+        #[arg(long)]
+        allow_scripts: bool,
+        /// Seconds a validation script may run for each entity
+        #[arg(long, default_value_t = 10, requires = "allow_scripts")]
+        script_timeout: u64,
+    },
     /// Get an entity or schema.
     Get {
         /// Entity type (e.g. person, organization) or "schema"
@@ -58,6 +83,12 @@ pub enum FirmCliCommand {
         #[arg(short, long)]
         direction: Option<CliDirection>,
     },
+    /// Inspect the shape of the entity graph.
+    Graph {
+        /// List entities that neither reference nor are referenced by other entities
+        #[arg(long)]
+        orphans: bool,
+    },
     /// Adds a new entity to a file in the workspace. If type, id or fields are not provided, this is done interactively.
     Add {
         /// Target firm file.
@@ -68,20 +99,85 @@ pub enum FirmCliCommand {
         /// Entity ID for non-interactive mode (e.g., john_doe)
         #[arg(long)]
         id: Option<String>,
+        /// Entity to copy fields from, overridden by --field (e.g., task.fix_bug). Implies its type
+        #[arg(long, value_name = "ENTITY_ID")]
+        from: Option<String>,
         /// Field for non-interactive mode (can be repeated). Format: --field <field_name> <value>
         #[arg(long = "field", num_args = 2, value_names = ["FIELD_NAME", "VALUE"])]
         fields: Vec<String>,
-        /// List declaration for non-interactive mode (can be repeated). Format: --list <field_name> <item_type>
+        /// List item type for non-interactive mode, when the schema doesn't declare one (can be repeated). Format: --list <field_name> <item_type>
         #[arg(long = "list", num_args = 2, value_names = ["FIELD_NAME", "ITEM_TYPE"])]
         lists: Vec<String>,
         /// List value for non-interactive mode (can be repeated). Format: --list-value <field_name> <value>
         #[arg(long = "list-value", num_args = 2, value_names = ["FIELD_NAME", "VALUE"])]
         list_values: Vec<String>,
+        /// Show the generated DSL and target file without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Query entities in the workspace using a query language.
     Query {
-        /// Query string (e.g., "from task | where is_completed == false | limit 5")
-        query: String,
+        /// Query string (e.g., "from task | where is_completed == false | limit 5"), or - for stdin
+        #[arg(required_unless_present = "file")]
+        query: Option<String>,
+        /// Read queries from a file (or - for stdin), separated by blank lines
+        #[arg(long, short, conflicts_with = "query")]
+        file: Option<PathBuf>,
+        /// Reject fields that are not declared in the schema of the queried entity type
+        #[arg(long)]
+        strict: bool,
+        /// Show the query as it was understood, and how many entities each stage left
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Run a query saved in the workspace by name, or list the saved queries.
+    Run {
+        /// Name of the saved query (e.g., "open_tasks"). Leave out to list the saved queries
+        name: Option<String>,
+        /// Reject fields that are not declared in the schema of the queried entity type
+        #[arg(long)]
+        strict: bool,
+        /// Show the query as it was understood, and how many entities each stage left
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Check the workspace for build warnings and broken references.
+    Check {
+        /// Only check for references to entities or fields that don't exist
+        #[arg(long)]
+        refs: bool,
+        /// Also warn about references to entities of another type than their field expects
+        #[arg(long)]
+        strict_refs: bool,
+        /// Only check entities of these types (comma-separated, e.g. task,project)
+        #[arg(long, value_delimiter = ',')]
+        only_type: Vec<String>,
+        /// Leave out entities of these types (comma-separated, e.g. file_asset)
+        #[arg(long, value_delimiter = ',')]
+        exclude_type: Vec<String>,
+        /// Run the validation scripts that schemas declare (scripts can run any program)
+        #[arg(long)]
+        allow_scripts: bool,
+        /// Seconds a validation script may run for each entity
+        #[arg(long, default_value_t = 10, requires = "allow_scripts")]
+        script_timeout: u64,
+    },
+    /// Suggest fixes for problems in the workspace and apply them.
+    Fix {
+        /// Apply every suggested fix without asking
+        #[arg(long)]
+        yes: bool,
+    },
+    /// List TODO, FIXME and NOTE comments in the workspace.
+    Todos {
+        /// Comment marker to look for instead of the defaults (can be repeated)
+        #[arg(long = "marker")]
+        markers: Vec<String>,
+    },
+    /// Inspect the workspace schemas.
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommand,
     },
     /// Find the source file for an entity or schema.
     Source {
@@ -91,5 +187,22 @@ pub enum FirmCliCommand {
         target_id: String,
     },
     /// Start the MCP server (stdio transport).
-    Mcp,
+    Mcp {
+        /// Only offer tools that don't change the workspace
+        #[arg(long)]
+        read_only: bool,
+        /// Most entities the query, get, list and related tools return (0 for no limit)
+        #[arg(long, env = "FIRM_MCP_MAX_RESPONSE_ENTITIES")]
+        max_response_entities: Option<usize>,
+        /// Most bytes the query, get, list and related tools return (0 for no limit)
+        #[arg(long, env = "FIRM_MCP_MAX_RESPONSE_BYTES")]
+        max_response_bytes: Option<usize>,
+    },
+}
+
+/// Defines the subcommands of `firm schema`.
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum SchemaCommand {
+    /// Compare workspace schemas against the built-in defaults.
+    Diff,
 }
@@ -1,21 +1,24 @@
+use chrono::FixedOffset;
 use convert_case::{Case, Casing};
 use firm_core::graph::EntityGraph;
-use firm_core::{Entity, EntitySchema, FieldId, FieldType, FieldValue, compose_entity_id};
+use firm_core::{
+    Entity, EntityId, EntitySchema, FieldId, FieldType, FieldValue, compose_entity_id,
+    decompose_entity_id,
+};
 use firm_lang::generate::generate_dsl;
-use firm_lang::parser::dsl::ParsedValue;
-use firm_lang::workspace::Workspace;
+use firm_lang::parser::dsl::{ParsedValue, local_timezone};
+use firm_lang::workspace::{AddConfig, Workspace, path_relocation};
 use inquire::{Confirm, Select, Text};
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use super::{build_workspace, field_prompt::prompt_for_field_value, load_workspace_files};
 use crate::errors::CliError;
-use crate::files::load_current_graph;
+use crate::files::{load_current_graph, load_workspace_config, lock_workspace, value_format};
 use crate::ui::{self, OutputFormat};
 
-pub const GENERATED_DIR_NAME: &str = "generated";
 pub const FIRM_EXTENSION: &str = "firm";
 
 /// Wrapper for EntitySchema that customizes Display for Inquire prompts.
@@ -27,25 +30,34 @@ impl<'a> std::fmt::Display for InquireSchema<'a> {
 }
 
 /// Add a new entity and generate DSL for it.
-/// If type, id, or fields are provided, uses non-interactive mode.
+/// If type, id, fields, or an entity to copy from are provided, uses non-interactive mode.
 pub fn add_entity(
     workspace_path: &PathBuf,
     to_file: Option<PathBuf>,
     entity_type: Option<String>,
     entity_id: Option<String>,
+    from: Option<String>,
     fields: Vec<String>,
     lists: Vec<String>,
     list_values: Vec<String>,
+    dry_run: bool,
     output_format: OutputFormat,
 ) -> Result<(), CliError> {
     // Check if we're in non-interactive mode
     let is_non_interactive = entity_type.is_some()
         || entity_id.is_some()
+        || from.is_some()
         || !fields.is_empty()
         || !lists.is_empty()
         || !list_values.is_empty();
 
     if is_non_interactive {
+        // The type can come from the entity to copy from
+        let entity_type = entity_type.or_else(|| {
+            from.as_deref()
+                .map(|from| decompose_entity_id(from).0.to_string())
+        });
+
         // Validate that both type and id are provided
         if entity_type.is_none() || entity_id.is_none() {
             ui::error("Non-interactive mode requires both --type and --id arguments");
@@ -57,33 +69,40 @@ pub fn add_entity(
             to_file,
             entity_type.unwrap(),
             entity_id.unwrap(),
+            from,
             fields,
             lists,
             list_values,
+            dry_run,
             output_format,
         );
     }
 
     // Otherwise, use interactive mode
-    add_entity_interactive(workspace_path, to_file, output_format)
+    add_entity_interactive(workspace_path, to_file, dry_run, output_format)
 }
 
 /// Add a new entity non-interactively using CLI arguments.
+///
+/// With an entity to copy from, its fields and tags are the starting point,
+/// and the given fields override them.
 fn add_entity_non_interactive(
     workspace_path: &PathBuf,
     to_file: Option<PathBuf>,
     entity_type: String,
     entity_id: String,
+    from: Option<String>,
     fields: Vec<String>,
     lists: Vec<String>,
     list_values: Vec<String>,
+    dry_run: bool,
     output_format: OutputFormat,
 ) -> Result<(), CliError> {
     // Load the pre-built graph and build workspace for schemas
     let graph = load_current_graph(workspace_path)?;
     let mut workspace = Workspace::new();
     load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
-    let build = build_workspace(workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
 
     // Find the schema for the given type
     let schema = build
@@ -134,8 +153,32 @@ fn add_entity_non_interactive(
     }
 
     // Compute the generated file path early so we can use it for path parsing
-    let generated_file_path =
-        compute_dsl_path(workspace_path, to_file.clone(), entity_type.clone());
+    let config = load_workspace_config(workspace_path)?;
+    let timezone = config.timezone.offset().unwrap_or_else(local_timezone);
+    let generated_file_path = compute_dsl_path(
+        workspace_path,
+        to_file.clone(),
+        &config.add,
+        &entity_type,
+        &sanitized_id,
+    );
+
+    // Copy the fields of the entity to start from (--from type.id)
+    if let Some(from) = from {
+        let source = graph.get_entity(&EntityId::new(&from)).ok_or_else(|| {
+            ui::error(&format!("Entity '{}' to copy from not found", from));
+            CliError::InputError
+        })?;
+        if source.entity_type != schema.entity_type {
+            ui::error(&format!(
+                "Can't copy '{}' into a '{}' entity, since it's a '{}'",
+                from, entity_type, source.entity_type
+            ));
+            return Err(CliError::InputError);
+        }
+
+        entity = copy_entity_fields(source, entity, schema, workspace_path, &generated_file_path);
+    }
 
     // Process regular fields (--field field_name value)
     for chunk in fields.chunks(2) {
@@ -143,6 +186,13 @@ fn add_entity_non_interactive(
             let field_name = chunk[0].as_str();
             let field_value_str = chunk[1].as_str();
             let field_id = FieldId::new(field_name);
+            if schema.is_computed(&field_id) {
+                ui::error(&format!(
+                    "Field '{}' of '{}' is computed from the entity's other fields and can't be set",
+                    field_name, entity_type
+                ));
+                return Err(CliError::InputError);
+            }
 
             // Find the field in the schema to get its expected type
             let schema_field = schema.fields.get(&field_id).ok_or_else(|| {
@@ -174,6 +224,7 @@ fn add_entity_non_interactive(
                 field_value_str,
                 expected_type,
                 &generated_file_path,
+                timezone,
             )?;
             let field_value: FieldValue = parsed_value.try_into().map_err(|_| {
                 ui::error(&format!(
@@ -183,12 +234,15 @@ fn add_entity_non_interactive(
                 CliError::InputError
             })?;
 
-            entity = entity.with_field(field_id, field_value);
+            entity.set_field(field_id, field_value);
         }
     }
 
-    // Process list fields (--list field_name item_type and --list-value field_name value)
-    for (list_field_name, item_type_str) in &list_types {
+    // Process list fields (--list-value field_name value, with --list field_name item_type
+    // when the schema doesn't declare the item type)
+    let list_field_names: std::collections::BTreeSet<&String> =
+        list_types.keys().chain(list_value_groups.keys()).collect();
+    for list_field_name in list_field_names {
         let field_id = FieldId::new(list_field_name);
 
         // Validate field exists in schema
@@ -223,8 +277,29 @@ fn add_entity_non_interactive(
             return Err(CliError::InputError);
         }
 
-        // Parse the item type
-        let item_field_type = parse_field_type(item_type_str)?;
+        // The schema's item type comes first, and a declared one has to agree with it
+        let declared_item_type = list_types
+            .get(list_field_name)
+            .map(|item_type_str| parse_field_type(item_type_str))
+            .transpose()?;
+        let item_field_type = match (schema_field.item_type(), declared_item_type) {
+            (Some(schema_item_type), Some(declared)) if &declared != schema_item_type => {
+                ui::error(&format!(
+                    "List field '{}' holds {} items in the schema, not {}",
+                    list_field_name, schema_item_type, declared
+                ));
+                return Err(CliError::InputError);
+            }
+            (Some(schema_item_type), _) => schema_item_type.clone(),
+            (None, Some(declared)) => declared,
+            (None, None) => {
+                ui::error(&format!(
+                    "No item type for list '{}' (use --list {} <item_type>)",
+                    list_field_name, list_field_name
+                ));
+                return Err(CliError::InputError);
+            }
+        };
 
         // Get the values for this list
         let values = list_value_groups.get(list_field_name).ok_or_else(|| {
@@ -242,6 +317,7 @@ fn add_entity_non_interactive(
                 value_str.as_str(),
                 &item_field_type,
                 &generated_file_path,
+                timezone,
             )?;
             parsed_items.push(item);
         }
@@ -263,7 +339,7 @@ fn add_entity_non_interactive(
             CliError::InputError
         })?;
 
-        entity = entity.with_field(field_id, field_value);
+        entity.set_field(field_id, field_value);
     }
 
     // Validate entity against schema
@@ -275,28 +351,41 @@ fn add_entity_non_interactive(
         CliError::InputError
     })?;
 
-    // Generate and write DSL
+    // Generate and write DSL, with enum values in their declared casing
+    schema.canonicalize(&mut entity);
     let generated_dsl = generate_dsl(&[entity.clone()]);
 
+    if dry_run {
+        ui::success("Entity passed validation");
+        return preview_dsl(entity, generated_dsl, generated_file_path, output_format);
+    }
+
     ui::info(&format!(
         "Writing generated DSL to file {}",
         generated_file_path.display()
     ));
 
-    write_dsl(entity, generated_dsl, generated_file_path, output_format)
+    write_dsl(
+        workspace_path,
+        entity,
+        generated_dsl,
+        generated_file_path,
+        output_format,
+    )
 }
 
 /// Interactively add a new entity and generate DSL for it.
 fn add_entity_interactive(
     workspace_path: &PathBuf,
     to_file: Option<PathBuf>,
+    dry_run: bool,
     output_format: OutputFormat,
 ) -> Result<(), CliError> {
     ui::header("Adding new entity");
     let graph = load_current_graph(workspace_path)?;
     let mut workspace = Workspace::new();
     load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
-    let build = build_workspace(workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
 
     // Let user choose entity type from built-in and custom schemas
     let mut sorted_schemas = build.schemas.clone();
@@ -316,9 +405,19 @@ fn add_entity_interactive(
     let entity_id = compute_unique_entity_id(&graph, &chosen_type_str, chosen_id);
 
     // Create initial entity and collect required fields
-    let mut entity = Entity::new(entity_id.into(), chosen_schema.entity_type.to_owned());
+    let mut entity = Entity::new(
+        entity_id.clone().into(),
+        chosen_schema.entity_type.to_owned(),
+    );
     let arc_graph = Arc::new(graph.clone());
-    let generated_file_path = compute_dsl_path(workspace_path, to_file, chosen_type_str);
+    let config = load_workspace_config(workspace_path)?;
+    let generated_file_path = compute_dsl_path(
+        workspace_path,
+        to_file,
+        &config.add,
+        &chosen_type_str,
+        &entity_id,
+    );
     entity = prompt_required_fields(
         &chosen_schema,
         entity.clone(),
@@ -343,15 +442,36 @@ fn add_entity_interactive(
         )?;
     }
 
-    // Generate and write the resulting DSL
+    // Generate and write the resulting DSL, with enum values in their declared casing
+    chosen_schema.canonicalize(&mut entity);
     let generated_dsl = generate_dsl(&[entity.clone()]);
 
+    if dry_run {
+        // Prompts only ask for valid values, but report the result like non-interactive mode
+        match chosen_schema.validate(&entity) {
+            Ok(()) => ui::success("Entity passed validation"),
+            Err(errors) => {
+                ui::warning("Entity validation failed:");
+                for error in errors {
+                    ui::warning(&format!("  - {}", error.message));
+                }
+            }
+        }
+        return preview_dsl(entity, generated_dsl, generated_file_path, output_format);
+    }
+
     ui::info(&format!(
         "Writing generated DSL to file {}",
         generated_file_path.display()
     ));
 
-    write_dsl(entity, generated_dsl, generated_file_path, output_format)
+    write_dsl(
+        workspace_path,
+        entity,
+        generated_dsl,
+        generated_file_path,
+        output_format,
+    )
 }
 
 /// Prompts for each required field in an entity schema and writes it to the entity.
@@ -372,9 +492,7 @@ fn prompt_required_fields(
     for (field_id, field) in required_fields {
         if let Some(value) = prompt_for_field_value(
             field_id,
-            field.expected_type(),
-            field.is_required(),
-            field.allowed_values(),
+            field,
             Arc::clone(arc_graph),
             source_path,
             workspace_path,
@@ -404,9 +522,7 @@ fn prompt_optional_fields(
     for (field_id, field) in optional_fields {
         if let Some(value) = prompt_for_field_value(
             field_id,
-            field.expected_type(),
-            field.is_required(),
-            field.allowed_values(),
+            field,
             Arc::clone(&graph),
             source_path,
             workspace_path,
@@ -418,6 +534,29 @@ fn prompt_optional_fields(
     Ok(entity)
 }
 
+/// Copies the fields and tags of a source entity to a new entity.
+///
+/// Paths in the graph are relative to the workspace root, so they're
+/// relocated to be relative to the file the new entity is written to.
+fn copy_entity_fields(
+    source: &Entity,
+    entity: Entity,
+    schema: &EntitySchema,
+    workspace_path: &Path,
+    target_path: &Path,
+) -> Entity {
+    let relocate_path = path_relocation(workspace_path, target_path);
+
+    let mut entity = entity.with_tags(source.tags.iter().cloned());
+    for (field_id, value) in &source.fields {
+        // Computed fields are set when the new entity is built
+        if !schema.is_computed(field_id) {
+            entity.set_field(field_id.clone(), value.map_paths(&relocate_path));
+        }
+    }
+    entity
+}
+
 /// Sanitize a string to be a valid entity ID.
 /// - Filters for only alphanumeric characters, underscores, dashes, and whitespace
 /// - Converts to snake_case
@@ -458,32 +597,65 @@ fn compute_unique_entity_id(
 
 /// Get the target path to write DSL to by:
 /// - Using a custom path, if provided
-/// - Generating a path from default settings
+/// - Generating a path from the workspace config's add target
 fn compute_dsl_path(
     workspace_path: &PathBuf,
     to_file: Option<PathBuf>,
-    chosen_type_str: String,
+    add_config: &AddConfig,
+    chosen_type_str: &str,
+    entity_id: &str,
 ) -> PathBuf {
-    
-
     match to_file {
         Some(file_path) => workspace_path
             .join(file_path)
             .with_extension(FIRM_EXTENSION),
-        None => workspace_path
-            .join(GENERATED_DIR_NAME)
-            .join(&chosen_type_str)
-            .with_extension(FIRM_EXTENSION),
+        None => workspace_path.join(add_config.target_path(chosen_type_str, entity_id)),
     }
 }
 
+/// Shows the DSL that would be written and where, without touching any file.
+fn preview_dsl(
+    entity: Entity,
+    generated_dsl: String,
+    target_path: PathBuf,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    match output_format {
+        OutputFormat::Pretty => {
+            ui::info(&format!(
+                "Dry run: would write to file {}",
+                target_path.display()
+            ));
+            ui::raw_output(generated_dsl.trim_end());
+        }
+        OutputFormat::Json => {
+            #[derive(serde::Serialize)]
+            struct DryRunResult {
+                path: PathBuf,
+                dsl: String,
+                entity: serde_json::Value,
+            }
+
+            ui::json_output(&DryRunResult {
+                path: target_path,
+                dsl: generated_dsl,
+                entity: entity.to_json(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
 /// Writes the DSL to a file and outputs the generated entity.
 fn write_dsl(
+    workspace_path: &Path,
     entity: Entity,
     generated_dsl: String,
     target_path: PathBuf,
     output_format: OutputFormat,
 ) -> Result<(), CliError> {
+    let _lock = lock_workspace(workspace_path)?;
     if let Some(parent) = target_path.parent() {
         fs::create_dir_all(parent).map_err(|_| CliError::FileError)?;
     }
@@ -494,8 +666,10 @@ fn write_dsl(
                 ui::success(&format!("Generated DSL for '{}'", &entity.id));
 
                 match output_format {
-                    OutputFormat::Pretty => ui::pretty_output_entity_single(&entity),
-                    OutputFormat::Json => ui::json_output(&entity),
+                    OutputFormat::Pretty => {
+                        ui::pretty_output_entity_single(&entity, &value_format(workspace_path)?)
+                    }
+                    OutputFormat::Json => ui::json_output_entity_single(&entity),
                 }
                 Ok(())
             }
@@ -511,25 +685,23 @@ fn write_dsl(
     }
 }
 
-/// Parses a field type string into a FieldType enum.
+/// Parses a list item type string into a FieldType enum, ignoring case.
 fn parse_field_type(type_str: &str) -> Result<FieldType, CliError> {
-    match type_str.to_lowercase().as_str() {
-        "string" => Ok(FieldType::String),
-        "integer" => Ok(FieldType::Integer),
-        "float" => Ok(FieldType::Float),
-        "boolean" => Ok(FieldType::Boolean),
-        "currency" => Ok(FieldType::Currency),
-        "reference" => Ok(FieldType::Reference),
-        "datetime" => Ok(FieldType::DateTime),
-        "path" => Ok(FieldType::Path),
-        "enum" => Ok(FieldType::Enum),
-        _ => {
+    match type_str.to_lowercase().parse() {
+        Ok(FieldType::List) | Err(_) => {
             ui::error(&format!(
-                "Unknown field type '{}'. Valid types: string, integer, float, boolean, currency, reference, datetime, path, enum",
-                type_str
+                "Unknown field type '{}'. Valid types: {}",
+                type_str,
+                FieldType::NAMES
+                    .iter()
+                    .filter(|name| **name != "list")
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ")
             ));
             Err(CliError::InputError)
         }
+        Ok(field_type) => Ok(field_type),
     }
 }
 
@@ -538,6 +710,7 @@ fn parse_field_value_from_string(
     value_str: &str,
     expected_type: &FieldType,
     source_path: &PathBuf,
+    timezone: FixedOffset,
 ) -> Result<ParsedValue, CliError> {
     match expected_type {
         FieldType::Boolean => ParsedValue::parse_boolean(value_str),
@@ -547,7 +720,8 @@ fn parse_field_value_from_string(
         FieldType::Reference => ParsedValue::parse_reference(value_str),
         FieldType::DateTime => {
             // Try parsing as datetime first, then as date
-            ParsedValue::parse_datetime(value_str).or_else(|_| ParsedValue::parse_date(value_str))
+            ParsedValue::parse_datetime_in(value_str, timezone)
+                .or_else(|_| ParsedValue::parse_date_in(value_str, timezone))
         }
         FieldType::Enum => ParsedValue::parse_enum(value_str),
         FieldType::Path => {
@@ -583,7 +757,7 @@ fn parse_field_value_from_string(
             Ok(ParsedValue::Path(relative_to_source))
         }
         FieldType::List => {
-            ui::error("List fields must be specified using --list and --list-value flags");
+            ui::error("List fields must be specified using --list-value flags");
             return Err(CliError::InputError);
         }
     }
@@ -1,29 +1,85 @@
-use firm_core::graph::{EntityGraph, GraphError};
-use firm_lang::workspace::{Workspace, WorkspaceBuild, WorkspaceError};
+use firm_core::graph::{EntityGraph, GraphError, GraphFormat};
+use firm_lang::workspace::{
+    PathCheckOptions, ScriptOptions, TypeFilter, Workspace, WorkspaceBuild, WorkspaceError,
+};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::errors::CliError;
-use crate::files::save_graph_with_backup;
+use crate::files::{load_workspace_config, save_graph, save_graph_with_backup};
 use crate::ui::{self};
 
 /// Builds the selected workspace and saves the resulting entity graph.
-pub fn build_and_save_graph(workspace_path: &PathBuf) -> Result<(), CliError> {
+///
+/// If path check options are given, path fields are also checked for missing files.
+/// If an output file is given, the graph is also written there in its format.
+/// Only entities of the types included by the type filter are built, and a
+/// filtered graph is only written to the output file, so the saved graph
+/// other commands load stays complete.
+/// Validation scripts only run if script options are given.
+pub fn build_and_save_graph(
+    workspace_path: &PathBuf,
+    path_check: Option<&PathCheckOptions>,
+    output: Option<&(PathBuf, GraphFormat)>,
+    type_filter: &TypeFilter,
+    scripts: Option<&ScriptOptions>,
+) -> Result<(), CliError> {
     ui::header("Building graph");
 
     // First load and build the workspace from DSL
-    let mut workspace = Workspace::new();
+    let config = load_workspace_config(workspace_path)?;
+    let mut workspace = Workspace::new()
+        .with_timezone(config.timezone.offset())
+        .with_type_filter(type_filter.clone())
+        .with_validation_scripts(scripts.cloned());
+    report_type_filter(type_filter);
     load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
-    let build = build_workspace(workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
+
+    if let Some(options) = path_check {
+        check_workspace_paths(&workspace, options);
+    }
 
     // Then build and save the entity graph
     let graph = build_graph(&build).map_err(|_| CliError::BuildError)?;
-    save_graph_with_backup(workspace_path, &graph).map_err(|_| CliError::BuildError)?;
+    if type_filter.is_unrestricted() {
+        save_graph_with_backup(workspace_path, &graph).map_err(|_| CliError::BuildError)?;
+    }
+    if let Some((output_path, format)) = output {
+        save_graph(output_path, &graph, *format).map_err(|_| CliError::BuildError)?;
+    }
 
     ui::success("Graph was built and saved");
 
     Ok(())
 }
 
+/// Creates the type filter for the --only-type and --exclude-type options.
+pub fn type_filter(only_types: Vec<String>, exclude_types: Vec<String>) -> TypeFilter {
+    let filter = TypeFilter::new().excluding(exclude_types);
+    if only_types.is_empty() {
+        filter
+    } else {
+        filter.only(only_types)
+    }
+}
+
+/// Creates the script options for the --allow-scripts and --script-timeout options.
+pub fn script_options(allow_scripts: bool, timeout_secs: u64) -> Option<ScriptOptions> {
+    allow_scripts.then(|| ScriptOptions {
+        timeout: Duration::from_secs(timeout_secs),
+    })
+}
+
+/// Tells the user when only some entity types are used.
+///
+/// References to the left out types aren't reported as broken.
+pub fn report_type_filter(type_filter: &TypeFilter) {
+    if !type_filter.is_unrestricted() {
+        ui::info("Only some entity types are included; references to the others are skipped");
+    }
+}
+
 /// Loads files in the workspace with progress indicator.
 pub fn load_workspace_files(
     path: &PathBuf,
@@ -48,8 +104,30 @@ pub fn load_workspace_files(
     }
 }
 
+/// Checks path fields in a workspace and reports missing files.
+pub fn check_workspace_paths(workspace: &Workspace, options: &PathCheckOptions) {
+    let check = workspace.check_paths(options);
+    for warning in &check.warnings {
+        ui::warning(&warning.to_string());
+    }
+
+    let summary = format!(
+        "Checked {} path(s), {} missing",
+        check.checked,
+        check.warnings.len()
+    );
+    if check.skipped_external > 0 {
+        ui::info(&format!(
+            "{} ({} outside the workspace skipped)",
+            summary, check.skipped_external
+        ));
+    } else {
+        ui::info(&summary);
+    }
+}
+
 /// Builds a workspace with progress indicator.
-pub fn build_workspace(mut workspace: Workspace) -> Result<WorkspaceBuild, WorkspaceError> {
+pub fn build_workspace(workspace: &mut Workspace) -> Result<WorkspaceBuild, WorkspaceError> {
     let progress = ui::progress_bar(workspace.num_files().try_into().unwrap());
 
     match workspace.build_with_progress(|total, curent, phase| {
@@ -59,6 +137,9 @@ pub fn build_workspace(mut workspace: Workspace) -> Result<WorkspaceBuild, Works
     }) {
         Ok(build) => {
             progress.finish_with_message("Workspace built successfully");
+            for warning in &build.warnings {
+                ui::warning(&warning.to_string());
+            }
             Ok(build)
         }
         Err(e) => {
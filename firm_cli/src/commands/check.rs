@@ -0,0 +1,145 @@
+use firm_lang::workspace::{BrokenReferenceKind, ScriptOptions, TypeFilter, Workspace};
+use std::path::PathBuf;
+
+use super::{load_workspace_files, report_type_filter};
+use crate::errors::CliError;
+use crate::ui::{self, OutputFormat};
+
+/// Checks the workspace for problems that don't stop a build.
+///
+/// Reports build warnings (unless only references are checked) and broken references.
+/// Fails if any reference points at an entity or field that doesn't exist.
+/// With strict references, references to entities of the wrong type are also
+/// reported, as warnings that don't fail the check.
+/// Only entities of the types included by the type filter are checked, and
+/// validation scripts only run if script options are given.
+pub fn check_workspace(
+    workspace_path: &PathBuf,
+    refs_only: bool,
+    strict_refs: bool,
+    type_filter: &TypeFilter,
+    scripts: Option<&ScriptOptions>,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    ui::header("Checking workspace");
+
+    let mut workspace = Workspace::new()
+        .with_type_filter(type_filter.clone())
+        .with_validation_scripts(scripts.cloned());
+    report_type_filter(type_filter);
+    load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
+
+    let spinner = ui::spinner("Building workspace");
+    let build = workspace.build().map_err(|e| {
+        spinner.finish_and_clear();
+        ui::error_with_details("Failed to build workspace", &e.to_string());
+        CliError::BuildError
+    })?;
+    spinner.finish_with_message("Workspace built successfully");
+
+    let warnings: Vec<String> = if refs_only {
+        Vec::new()
+    } else {
+        build.warnings.iter().map(|w| w.to_string()).collect()
+    };
+    let broken = workspace.check_references(&build);
+    let mismatched = if strict_refs {
+        workspace.check_reference_types(&build)
+    } else {
+        Vec::new()
+    };
+
+    match output_format {
+        OutputFormat::Pretty => {
+            for warning in &warnings {
+                ui::warning(warning);
+            }
+            for broken_reference in &broken {
+                ui::warning(&broken_reference.to_string());
+            }
+            for mismatched_reference in &mismatched {
+                ui::warning(&mismatched_reference.to_string());
+            }
+        }
+        OutputFormat::Json => {
+            #[derive(serde::Serialize)]
+            struct BrokenReferenceResult {
+                path: PathBuf,
+                line: usize,
+                entity: String,
+                field: String,
+                reference: String,
+                problem: &'static str,
+            }
+
+            #[derive(serde::Serialize)]
+            struct MismatchedReferenceResult {
+                path: PathBuf,
+                line: usize,
+                entity: String,
+                field: String,
+                reference: String,
+                expected_type: String,
+                inferred: bool,
+            }
+
+            #[derive(serde::Serialize)]
+            struct CheckResult {
+                #[serde(skip_serializing_if = "Option::is_none")]
+                warnings: Option<Vec<String>>,
+                broken_references: Vec<BrokenReferenceResult>,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                mismatched_references: Option<Vec<MismatchedReferenceResult>>,
+            }
+
+            let broken_references = broken
+                .iter()
+                .map(|b| BrokenReferenceResult {
+                    path: b.path.clone(),
+                    line: b.line,
+                    entity: b.entity_id.to_string(),
+                    field: b.field.to_string(),
+                    reference: b.reference.to_string(),
+                    problem: match b.kind {
+                        BrokenReferenceKind::MissingEntity => "missing_entity",
+                        BrokenReferenceKind::MissingField => "missing_field",
+                    },
+                })
+                .collect();
+
+            let mismatched_references = mismatched
+                .iter()
+                .map(|m| MismatchedReferenceResult {
+                    path: m.path.clone(),
+                    line: m.line,
+                    entity: m.entity_id.to_string(),
+                    field: m.field.to_string(),
+                    reference: m.reference.to_string(),
+                    expected_type: m.expected_type.to_string(),
+                    inferred: m.inferred,
+                })
+                .collect();
+
+            ui::json_output(&CheckResult {
+                warnings: (!refs_only).then_some(warnings),
+                broken_references,
+                mismatched_references: strict_refs.then_some(mismatched_references),
+            });
+        }
+    }
+
+    if !mismatched.is_empty() {
+        ui::warning(&format!(
+            "Found {} reference(s) to the wrong entity type",
+            mismatched.len()
+        ));
+    }
+
+    if broken.is_empty() {
+        ui::success("No broken references found");
+        Ok(())
+    } else {
+        ui::error(&format!("Found {} broken reference(s)", broken.len()));
+        Err(CliError::BuildError)
+    }
+}
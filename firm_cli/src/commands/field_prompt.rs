@@ -2,7 +2,9 @@ use chrono::{FixedOffset, Local, NaiveTime, TimeZone, Timelike};
 use console::style;
 use convert_case::{Case, Casing};
 use firm_core::{
-    FieldId, FieldType, FieldValue, ReferenceValue, compose_entity_id, graph::EntityGraph,
+    FieldId, FieldType, FieldValue, ReferenceValue, compose_entity_id,
+    graph::EntityGraph,
+    schema::{FieldMode, FieldSchema},
 };
 use inquire::{Confirm, CustomType, DateSelect, Select, Text, validator::Validation};
 use iso_currency::{Currency, IntoEnumIterator};
@@ -21,17 +23,15 @@ pub const SKIP_PROMPT_FRAGMENT: &str = " (esc to skip)";
 /// Interactive prompt for a field value, applying relevant prompt configurations depending on the field type.
 pub fn prompt_for_field_value(
     field_id: &FieldId,
-    field_type: &FieldType,
-    is_required: bool,
-    allowed_values: Option<&Vec<String>>,
+    field: &FieldSchema,
     entity_graph: Arc<EntityGraph>,
     source_path: &PathBuf,
     workspace_dir: &PathBuf,
 ) -> Result<Option<FieldValue>, CliError> {
-    let skippable = !is_required;
+    let skippable = !field.is_required();
     let field_id_prompt = field_id.as_str().to_case(Case::Sentence);
 
-    match field_type {
+    match field.expected_type() {
         FieldType::Boolean => bool_prompt(skippable, &field_id_prompt),
         FieldType::String => string_prompt(skippable, &field_id_prompt),
         FieldType::Integer => int_prompt(skippable, &field_id_prompt),
@@ -43,6 +43,7 @@ pub fn prompt_for_field_value(
         FieldType::List => list_prompt(
             skippable,
             &field_id_prompt,
+            field,
             Arc::clone(&entity_graph),
             source_path,
             workspace_dir,
@@ -54,7 +55,7 @@ pub fn prompt_for_field_value(
             source_path,
             workspace_dir.clone(),
         ),
-        FieldType::Enum => enum_prompt(skippable, &field_id_prompt, allowed_values),
+        FieldType::Enum => enum_prompt(skippable, &field_id_prompt, field.allowed_values()),
     }
 }
 
@@ -361,15 +362,64 @@ fn get_reference_suggestions(
 
 /// Prompt for a list field.
 /// Lists must have homogeneous types.
-/// User can select a valid type, then iteratively inputs values to it.
+/// Items use the type the schema declares, or the user selects a valid type,
+/// then iteratively inputs values to it. Each item is parsed and validated as
+/// that type, like a field of its own.
 fn list_prompt(
     skippable: bool,
     field_id_prompt: &String,
+    field: &FieldSchema,
     entity_graph: Arc<EntityGraph>,
     source_path: &PathBuf,
     workspace_dir: &PathBuf,
 ) -> Result<Option<FieldValue>, CliError> {
-    // Ask for the item type
+    let item_type = match field.item_type() {
+        Some(item_type) => item_type.clone(),
+        None => match select_item_type(skippable, field_id_prompt)? {
+            Some(item_type) => item_type,
+            None => return Ok(None),
+        },
+    };
+
+    // Items are always skippable, so the user can skip to finish
+    let item_field = match (&item_type, field.allowed_values()) {
+        (FieldType::Enum, Some(allowed_values)) => {
+            FieldSchema::new_enum(FieldMode::Optional, 0, allowed_values.clone())
+        }
+        _ => FieldSchema::new(item_type, FieldMode::Optional, 0),
+    };
+
+    // Collect items until user skips
+    let mut items = Vec::new();
+    let mut item_index = 1;
+    loop {
+        let item_field_id = FieldId::new(format!("item_{}", item_index));
+        match prompt_for_field_value(
+            &item_field_id,
+            &item_field,
+            Arc::clone(&entity_graph),
+            source_path,
+            workspace_dir,
+        )? {
+            Some(value) => {
+                items.push(value);
+                item_index += 1;
+            }
+            None => {
+                // User skipped, finish the list
+                break;
+            }
+        }
+    }
+
+    Ok(Some(FieldValue::List(items)))
+}
+
+/// Asks for the item type of a list whose schema doesn't declare one.
+fn select_item_type(
+    skippable: bool,
+    field_id_prompt: &String,
+) -> Result<Option<FieldType>, CliError> {
     let item_types = vec![
         FieldType::String,
         FieldType::Integer,
@@ -401,33 +451,7 @@ fn list_prompt(
             .map_err(|_| CliError::InputError)?
     };
 
-    // Collect items until user skips
-    let mut items = Vec::new();
-    let mut item_index = 1;
-    loop {
-        // Prompt for each item (always treat as skippable so user can skip to finish)
-        let item_field_id = FieldId::new(format!("item_{}", item_index));
-        match prompt_for_field_value(
-            &item_field_id,
-            &item_type,
-            false,
-            None,
-            Arc::clone(&entity_graph),
-            source_path,
-            workspace_dir,
-        )? {
-            Some(value) => {
-                items.push(value);
-                item_index += 1;
-            }
-            None => {
-                // User skipped, finish the list
-                break;
-            }
-        }
-    }
-
-    Ok(Some(FieldValue::List(items)))
+    Ok(Some(item_type))
 }
 
 /// Prompts for a date field.
@@ -0,0 +1,109 @@
+use firm_lang::workspace::{SuggestedFix, TextEdit, Workspace, apply_edits};
+use inquire::Confirm;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::load_workspace_files;
+use crate::errors::CliError;
+use crate::files::lock_workspace;
+use crate::ui::{self, OutputFormat};
+
+/// Suggests fixes for problems in the workspace and applies the accepted ones.
+///
+/// Each fix is confirmed interactively unless `apply_all` is set.
+/// With JSON output, fixes are listed and only applied when `apply_all` is set.
+pub fn fix_workspace(
+    workspace_path: &PathBuf,
+    apply_all: bool,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    ui::header("Looking for fixes");
+
+    let mut workspace = Workspace::new();
+    load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
+
+    let fixes = workspace.suggest_fixes();
+    if fixes.is_empty() {
+        ui::success("No fixes to suggest");
+        if let OutputFormat::Json = output_format {
+            ui::json_output(&Vec::<FixResult>::new());
+        }
+        return Ok(());
+    }
+
+    let accepted: Vec<&SuggestedFix> = match output_format {
+        OutputFormat::Pretty => {
+            let mut accepted = Vec::new();
+            for fix in &fixes {
+                ui::info(&fix.to_string());
+                let apply = apply_all
+                    || Confirm::new("Apply this fix?")
+                        .with_default(true)
+                        .prompt()
+                        .map_err(|_| CliError::InputError)?;
+
+                if apply {
+                    accepted.push(fix);
+                }
+            }
+            accepted
+        }
+        OutputFormat::Json => {
+            let results: Vec<FixResult> = fixes
+                .iter()
+                .map(|fix| FixResult {
+                    path: fix.path.clone(),
+                    line: fix.line,
+                    message: fix.message.clone(),
+                    applied: apply_all,
+                })
+                .collect();
+            ui::json_output(&results);
+
+            if apply_all {
+                fixes.iter().collect()
+            } else {
+                Vec::new()
+            }
+        }
+    };
+
+    // Edits are grouped per file so each file is rewritten once
+    let mut edits_by_file: BTreeMap<&PathBuf, Vec<TextEdit>> = BTreeMap::new();
+    for fix in &accepted {
+        edits_by_file
+            .entry(&fix.path)
+            .or_default()
+            .extend(fix.edits.iter().cloned());
+    }
+
+    let _lock = lock_workspace(workspace_path)?;
+    for (path, edits) in edits_by_file {
+        let source = fs::read_to_string(path).map_err(|e| {
+            ui::error_with_details("Couldn't read file", &e.to_string());
+            CliError::FileError
+        })?;
+
+        fs::write(path, apply_edits(&source, &edits)).map_err(|e| {
+            ui::error_with_details("Couldn't write to file", &e.to_string());
+            CliError::FileError
+        })?;
+    }
+
+    ui::success(&format!(
+        "Applied {} of {} suggested fix(es)",
+        accepted.len(),
+        fixes.len()
+    ));
+    Ok(())
+}
+
+/// A suggested fix in JSON output.
+#[derive(serde::Serialize)]
+struct FixResult {
+    path: PathBuf,
+    line: usize,
+    message: String,
+    applied: bool,
+}
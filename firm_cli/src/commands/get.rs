@@ -1,10 +1,12 @@
-use firm_core::compose_entity_id;
+use firm_core::{
+    EntityType, compose_entity_id, decompose_entity_id, did_you_mean, suggest_similar,
+};
 use firm_lang::workspace::Workspace;
 use std::path::PathBuf;
 
 use super::{build_workspace, load_workspace_files};
 use crate::errors::CliError;
-use crate::files::load_current_graph;
+use crate::files::{entities_for_output, load_current_graph, value_format};
 use crate::ui::{self, OutputFormat};
 
 /// Gets an entity or schema by type and ID/name.
@@ -40,9 +42,12 @@ fn get_entity(
                 entity_type, entity_id
             ));
 
+            let rendered = entities_for_output(workspace_path, &[entity])?;
             match output_format {
-                ui::OutputFormat::Pretty => ui::pretty_output_entity_single(entity),
-                ui::OutputFormat::Json => ui::json_output(entity),
+                ui::OutputFormat::Pretty => {
+                    ui::pretty_output_entity_single(&rendered[0], &value_format(workspace_path)?)
+                }
+                ui::OutputFormat::Json => ui::json_output_entity_single(&rendered[0]),
             }
             Ok(())
         }
@@ -51,6 +56,18 @@ fn get_entity(
                 "Couldn't find '{}' entity with ID '{}'",
                 entity_type, entity_id
             ));
+
+            let existing_ids = graph
+                .list_by_type(&EntityType::new(&entity_type))
+                .into_iter()
+                .map(|entity| decompose_entity_id(entity.id.as_str()).1.to_string());
+            let suggestions: Vec<String> = suggest_similar(&entity_id, existing_ids)
+                .iter()
+                .map(|similar_id| compose_entity_id(&entity_type, similar_id).to_string())
+                .collect();
+            if let Some(question) = did_you_mean(&suggestions) {
+                ui::info(&question);
+            }
             Err(CliError::QueryError)
         }
     }
@@ -65,7 +82,7 @@ fn get_schema(
     ui::header("Getting schema");
     let mut workspace = Workspace::new();
     load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
-    let build = build_workspace(workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
 
     // Find the schema by name
     let schema = build
@@ -85,6 +102,11 @@ fn get_schema(
         }
         None => {
             ui::error(&format!("Schema '{}' not found in workspace", schema_name));
+
+            let existing_names = build.schemas.iter().map(|s| s.entity_type.as_str());
+            if let Some(question) = did_you_mean(&suggest_similar(&schema_name, existing_names)) {
+                ui::info(&question);
+            }
             Err(CliError::QueryError)
         }
     }
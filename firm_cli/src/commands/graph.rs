@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use crate::errors::CliError;
+use crate::files::load_current_graph;
+use crate::ui::{self, OutputFormat};
+
+/// Inspects the shape of the entity graph.
+pub fn inspect_graph(
+    workspace_path: &PathBuf,
+    orphans: bool,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    if !orphans {
+        ui::error("Choose what to look for in the graph, like --orphans");
+        return Err(CliError::InputError);
+    }
+
+    list_orphans(workspace_path, output_format)
+}
+
+/// Lists the IDs of entities that neither reference nor are referenced by other entities.
+fn list_orphans(workspace_path: &PathBuf, output_format: OutputFormat) -> Result<(), CliError> {
+    ui::header("Finding disconnected entities");
+    let graph = load_current_graph(workspace_path)?;
+
+    let orphans = graph.orphans();
+    let ids: Vec<&str> = orphans.iter().map(|e| e.id.as_str()).collect();
+
+    ui::success(&format!(
+        "Found {} entities without references to or from other entities",
+        ids.len()
+    ));
+
+    ui::list_output(&ids, output_format);
+
+    Ok(())
+}
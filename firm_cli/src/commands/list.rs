@@ -48,7 +48,7 @@ fn list_schemas(workspace_path: &PathBuf, output_format: OutputFormat) -> Result
     ui::header("Listing schemas");
     let mut workspace = Workspace::new();
     load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
-    let build = build_workspace(workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
 
     let names: Vec<&str> = build
         .schemas
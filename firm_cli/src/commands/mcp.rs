@@ -3,13 +3,31 @@
 use std::path::Path;
 
 use firm_mcp::FirmMcpServer;
+use firm_mcp::tools::ResponseLimits;
 
 use crate::errors::CliError;
+use crate::files::load_workspace_config;
 use crate::ui;
 
 /// Start the MCP server on stdio.
-pub fn serve(workspace_path: &Path) -> Result<(), CliError> {
+///
+/// A read-only server only offers tools that don't change the workspace.
+/// Response limits left out keep their defaults, and a limit of 0 lifts it.
+pub fn serve(
+    workspace_path: &Path,
+    read_only: bool,
+    max_response_entities: Option<usize>,
+    max_response_bytes: Option<usize>,
+) -> Result<(), CliError> {
     ui::debug("Starting MCP server...");
+    let config = load_workspace_config(workspace_path)?;
+
+    let defaults = ResponseLimits::default();
+    let limit = |max: Option<usize>, default| max.map_or(default, |max| (max > 0).then_some(max));
+    let response_limits = ResponseLimits {
+        max_entities: limit(max_response_entities, defaults.max_entities),
+        max_bytes: limit(max_response_bytes, defaults.max_bytes),
+    };
 
     // Create a tokio runtime for the async MCP server
     let rt = tokio::runtime::Runtime::new().map_err(|e| {
@@ -19,10 +37,13 @@ pub fn serve(workspace_path: &Path) -> Result<(), CliError> {
 
     rt.block_on(async {
         // Create the MCP server
-        let server = FirmMcpServer::new(workspace_path.to_path_buf()).map_err(|e| {
-            ui::error_with_details("Failed to load workspace", &e.to_string());
-            CliError::BuildError
-        })?;
+        let server = FirmMcpServer::with_config(workspace_path.to_path_buf(), config)
+            .map_err(|e| {
+                ui::error_with_details("Failed to load workspace", &e.to_string());
+                CliError::BuildError
+            })?
+            .with_read_only(read_only)
+            .with_response_limits(response_limits);
 
         // Serve over stdio (blocks until connection closes)
         server.serve_stdio().await.map_err(|e| {
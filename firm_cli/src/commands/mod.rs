@@ -1,19 +1,34 @@
 mod add;
 mod build;
+mod check;
 mod field_prompt;
+mod fix;
 mod get;
+mod graph;
 mod init;
 mod list;
 pub mod mcp;
 mod query;
 mod related;
+mod run;
+mod schema;
 mod source;
+mod todos;
 
 pub use add::add_entity;
-pub use build::{build_and_save_graph, build_workspace, load_workspace_files};
+pub use build::{
+    build_and_save_graph, build_workspace, load_workspace_files, report_type_filter,
+    script_options, type_filter,
+};
+pub use check::check_workspace;
+pub use fix::fix_workspace;
 pub use get::get_item;
+pub use graph::inspect_graph;
 pub use init::init_workspace;
 pub use list::list_items;
-pub use query::query_entities;
+pub use query::{QueryInput, query_entities};
 pub use related::get_related_entities;
+pub use run::run_named_query;
+pub use schema::diff_schemas;
 pub use source::find_item_source;
+pub use todos::list_todos;
@@ -1,54 +1,274 @@
+use std::fs;
+use std::io;
 use std::path::PathBuf;
 
-use firm_core::graph::{Query, QueryResult};
+use chrono::FixedOffset;
+use firm_core::graph::{EntityGraph, Query, QueryResult};
+use firm_core::{Entity, EntitySchema};
 use firm_lang::parser::query::parse_query;
+use firm_lang::workspace::Workspace;
+use serde_json::json;
 
+use super::{build_workspace, load_workspace_files};
 use crate::errors::CliError;
-use crate::files::load_current_graph;
+use crate::files::{entities_for_output, load_current_graph, load_workspace_config};
 use crate::ui::{self, OutputFormat};
 
-/// Executes a query against the workspace entity graph.
+/// Where the queries to run come from.
+#[derive(Debug, Clone)]
+pub enum QueryInput {
+    /// A single query given on the command line.
+    Inline(String),
+    /// A file of queries separated by blank lines.
+    File(PathBuf),
+    /// Queries separated by blank lines, piped on stdin.
+    Stdin,
+}
+
+/// A query read from a file or stdin, with the line it starts on.
+struct QueryEntry {
+    line: usize,
+    text: String,
+}
+
+/// Executes queries against the workspace entity graph.
+/// In strict mode, field references are validated against the workspace schemas first.
+/// With explain, the understood query and the entities left after each stage are shown too.
+///
+/// Queries from a file or stdin are each shown with a header, and every query
+/// runs even if an earlier one fails. In JSON mode, their results are output
+/// together as a list of `{"query", "line", "result"}` objects.
 pub fn query_entities(
     workspace_path: &PathBuf,
-    query_string: String,
+    input: QueryInput,
+    strict: bool,
+    explain: bool,
     output_format: OutputFormat,
 ) -> Result<(), CliError> {
-    ui::header("Executing query");
+    let (source_name, queries) = read_queries(&input)?;
+    if queries.is_empty() {
+        ui::error(&format!("No queries found in {}", source_name));
+        return Err(CliError::QueryError);
+    }
+
+    let labeled = !matches!(input, QueryInput::Inline(_));
+    if labeled {
+        ui::header(&format!(
+            "Executing {} query(s) from {}",
+            queries.len(),
+            source_name
+        ));
+    } else {
+        ui::header("Executing query");
+    }
     let graph = load_current_graph(workspace_path)?;
 
+    // Check date-only conditions in the workspace's default timezone
+    let config = load_workspace_config(workspace_path)?;
+    let timezone = config.timezone.offset();
+    let value_format = config.display.value_format();
+
+    // Validate field references against schemas in strict mode
+    let schemas = if strict {
+        let mut workspace = Workspace::new();
+        load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
+        let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
+        Some(build.schemas)
+    } else {
+        None
+    };
+
+    let mut json_results = Vec::new();
+    let mut failures = 0;
+    for (index, entry) in queries.iter().enumerate() {
+        if labeled {
+            ui::header(&format!(
+                "\nQuery {} (line {}): {}",
+                index + 1,
+                entry.line,
+                single_line(&entry.text)
+            ));
+        }
+
+        let result = run_query(&graph, &entry.text, schemas.as_deref(), timezone, explain);
+        let result = match result {
+            Ok(result) => result,
+            Err(message) => {
+                if labeled {
+                    ui::error(&format!(
+                        "Query {} at line {} of {} failed: {}",
+                        index + 1,
+                        entry.line,
+                        source_name,
+                        message
+                    ));
+                    json_results.push(json!({
+                        "query": entry.text,
+                        "line": entry.line,
+                        "error": message,
+                    }));
+                } else {
+                    ui::error(&message);
+                }
+                failures += 1;
+                continue;
+            }
+        };
+
+        // Output results
+        match result {
+            QueryResult::Entities(entities) => {
+                ui::success(&format!("Query returned {} entities", entities.len()));
+                let entities = entities_for_output(workspace_path, &entities)?;
+                let entities: Vec<&Entity> = entities.iter().collect();
+                match output_format {
+                    OutputFormat::Pretty => ui::pretty_output_entity_list(&entities, &value_format),
+                    OutputFormat::Json if labeled => json_results.push(json!({
+                        "query": entry.text,
+                        "line": entry.line,
+                        "result": entities.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
+                    })),
+                    OutputFormat::Json => ui::json_output_entity_list(&entities),
+                }
+            }
+            QueryResult::Aggregation(agg_result) => match output_format {
+                OutputFormat::Pretty => ui::raw_output(&value_format.aggregation(&agg_result)),
+                OutputFormat::Json if labeled => json_results.push(json!({
+                    "query": entry.text,
+                    "line": entry.line,
+                    "result": agg_result,
+                })),
+                OutputFormat::Json => ui::json_output(&agg_result),
+            },
+        }
+    }
+
+    if labeled && output_format == OutputFormat::Json {
+        ui::json_output(&json_results);
+    }
+
+    if failures > 0 {
+        if labeled {
+            ui::error(&format!(
+                "{} of {} query(s) failed",
+                failures,
+                queries.len()
+            ));
+        }
+        return Err(CliError::QueryError);
+    }
+
+    Ok(())
+}
+
+/// Parses, checks and executes a query, returning a description of what failed.
+fn run_query<'a>(
+    graph: &'a EntityGraph,
+    query_string: &str,
+    schemas: Option<&[EntitySchema]>,
+    timezone: Option<FixedOffset>,
+    explain: bool,
+) -> Result<QueryResult<'a>, String> {
     // Parse the query
-    let parsed_query = parse_query(&query_string).map_err(|e| {
-        ui::error(&format!("Failed to parse query: {}", e));
-        CliError::QueryError
-    })?;
+    let parsed_query =
+        parse_query(query_string).map_err(|e| format!("Failed to parse query: {}", e))?;
 
     // Convert to executable query
-    let query: Query = parsed_query.try_into().map_err(|e| {
-        ui::error(&format!("Failed to convert query: {}", e));
-        CliError::QueryError
-    })?;
+    let mut query: Query = parsed_query
+        .try_into()
+        .map_err(|e| format!("Failed to convert query: {}", e))?;
+
+    if let Some(timezone) = timezone {
+        query = query.with_timezone(timezone);
+    }
+
+    if let Some(schemas) = schemas {
+        query
+            .validate(schemas)
+            .map_err(|e| format!("Query validation failed: {}", e))?;
+    }
 
     // Execute the query
     ui::debug("Executing query");
-    let result = query.execute(&graph).map_err(|e| {
-        ui::error(&format!("Query execution failed: {}", e));
-        CliError::QueryError
-    })?;
-
-    // Output results
-    match result {
-        QueryResult::Entities(entities) => {
-            ui::success(&format!("Query returned {} entities", entities.len()));
-            match output_format {
-                OutputFormat::Pretty => ui::pretty_output_entity_list(&entities),
-                OutputFormat::Json => ui::json_output(&entities),
+    let result = if explain {
+        query.execute_explained(graph).map(|(result, explanation)| {
+            ui::info(&format!("Query plan:\n{}", explanation));
+            result
+        })
+    } else {
+        query.execute(graph)
+    };
+    result.map_err(|e| format!("Query execution failed: {}", e))
+}
+
+/// Reads the queries to run, with a name for where they came from.
+fn read_queries(input: &QueryInput) -> Result<(String, Vec<QueryEntry>), CliError> {
+    match input {
+        QueryInput::Inline(query) => Ok((
+            "the command line".to_string(),
+            vec![QueryEntry {
+                line: 1,
+                text: query.clone(),
+            }],
+        )),
+        QueryInput::File(path) => {
+            let source = fs::read_to_string(path).map_err(|e| {
+                ui::error(&format!(
+                    "Failed to read query file '{}': {}",
+                    path.display(),
+                    e
+                ));
+                CliError::FileError
+            })?;
+            Ok((format!("'{}'", path.display()), split_queries(&source)))
+        }
+        QueryInput::Stdin => {
+            let source = io::read_to_string(io::stdin()).map_err(|e| {
+                ui::error(&format!("Failed to read queries from stdin: {}", e));
+                CliError::InputError
+            })?;
+            Ok(("stdin".to_string(), split_queries(&source)))
+        }
+    }
+}
+
+/// Splits text into queries separated by blank lines.
+///
+/// A query can span several lines. Lines starting with `//` are comments,
+/// so queries in a file can be described.
+fn split_queries(source: &str) -> Vec<QueryEntry> {
+    let mut queries = Vec::new();
+    let mut current: Option<QueryEntry> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") {
+            continue;
+        }
+        if trimmed.is_empty() {
+            queries.extend(current.take());
+            continue;
+        }
+
+        match &mut current {
+            Some(entry) => {
+                entry.text.push('\n');
+                entry.text.push_str(trimmed);
+            }
+            None => {
+                current = Some(QueryEntry {
+                    line: index + 1,
+                    text: trimmed.to_string(),
+                })
             }
         }
-        QueryResult::Aggregation(agg_result) => match output_format {
-            OutputFormat::Pretty => ui::raw_output(&agg_result.to_string()),
-            OutputFormat::Json => ui::json_output(&agg_result),
-        },
     }
 
-    Ok(())
+    queries.extend(current);
+    queries
+}
+
+/// Shows a query spanning several lines on one line.
+fn single_line(query: &str) -> String {
+    query.lines().collect::<Vec<_>>().join(" ")
 }
@@ -1,8 +1,8 @@
-use firm_core::compose_entity_id;
+use firm_core::{Entity, compose_entity_id};
 use std::path::PathBuf;
 
 use crate::errors::CliError;
-use crate::files::load_current_graph;
+use crate::files::{entities_for_output, load_current_graph, value_format};
 use crate::query::CliDirection;
 use crate::ui::{self, OutputFormat};
 
@@ -34,9 +34,13 @@ pub fn get_related_entities(
                 entity_id
             ));
 
+            let entities = entities_for_output(workspace_path, &entities)?;
+            let entities: Vec<&Entity> = entities.iter().collect();
             match output_format {
-                OutputFormat::Pretty => ui::pretty_output_entity_list(&entities),
-                OutputFormat::Json => ui::json_output(&entities),
+                OutputFormat::Pretty => {
+                    ui::pretty_output_entity_list(&entities, &value_format(workspace_path)?)
+                }
+                OutputFormat::Json => ui::json_output_entity_list(&entities),
             }
 
             Ok(())
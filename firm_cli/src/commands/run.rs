@@ -0,0 +1,97 @@
+use firm_lang::workspace::Workspace;
+use serde_json::json;
+use std::path::PathBuf;
+
+use super::{QueryInput, build_workspace, load_workspace_files, query_entities};
+use crate::errors::CliError;
+use crate::ui::{self, OutputFormat};
+
+/// Runs a query saved in the workspace by its name.
+/// Without a name, lists the saved queries instead.
+pub fn run_named_query(
+    workspace_path: &PathBuf,
+    name: Option<String>,
+    strict: bool,
+    explain: bool,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    let mut workspace = Workspace::new();
+    load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
+
+    let relative = |path: &PathBuf| {
+        workspace
+            .relative_path(path)
+            .unwrap_or_else(|| path.display().to_string())
+    };
+
+    let Some(name) = name else {
+        match output_format {
+            OutputFormat::Pretty => {
+                if build.queries.is_empty() {
+                    ui::success("No saved queries found");
+                    return Ok(());
+                }
+
+                for query in &build.queries {
+                    let description = query
+                        .description
+                        .as_ref()
+                        .map(|description| format!(" - {}", description))
+                        .unwrap_or_default();
+                    ui::raw_output(&format!(
+                        "{}{} ({}:{})\n  {}",
+                        query.name,
+                        description,
+                        relative(&query.path),
+                        query.line,
+                        query.query.trim()
+                    ));
+                }
+                ui::info(&format!("\nFound {} saved query(s)", build.queries.len()));
+            }
+            OutputFormat::Json => {
+                let queries: Vec<_> = build
+                    .queries
+                    .iter()
+                    .map(|query| {
+                        json!({
+                            "name": query.name,
+                            "query": query.query,
+                            "description": query.description,
+                            "path": relative(&query.path),
+                            "line": query.line,
+                        })
+                    })
+                    .collect();
+                ui::json_output(&queries);
+            }
+        }
+        return Ok(());
+    };
+
+    let Some(query) = build.named_query(&name) else {
+        let names: Vec<&str> = build.queries.iter().map(|q| q.name.as_str()).collect();
+        let available = if names.is_empty() {
+            "there are no saved queries".to_string()
+        } else {
+            format!("saved queries: {}", names.join(", "))
+        };
+        ui::error_with_details(&format!("No query named '{}'", name), &available);
+        return Err(CliError::QueryError);
+    };
+
+    ui::info(&format!(
+        "Running '{}' from {}:{}",
+        query.name,
+        relative(&query.path),
+        query.line
+    ));
+    query_entities(
+        workspace_path,
+        QueryInput::Inline(query.query.clone()),
+        strict,
+        explain,
+        output_format,
+    )
+}
@@ -0,0 +1,89 @@
+use firm_core::FieldType;
+use firm_core::schema::{FieldChange, FieldDiff, SchemaDiff};
+use firm_lang::defaults::all_default_schemas;
+use firm_lang::workspace::Workspace;
+use std::path::PathBuf;
+
+use super::{build_workspace, load_workspace_files};
+use crate::errors::CliError;
+use crate::ui::{self, OutputFormat};
+
+/// Compares workspace schemas against the built-in defaults they share a name with.
+///
+/// Schemas that only exist in the workspace, or only in the defaults, are skipped.
+pub fn diff_schemas(workspace_path: &PathBuf, output_format: OutputFormat) -> Result<(), CliError> {
+    ui::header("Comparing schemas with the defaults");
+    let mut workspace = Workspace::new();
+    load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
+    let build = build_workspace(&mut workspace).map_err(|_| CliError::BuildError)?;
+
+    let diffs: Vec<SchemaDiff> = all_default_schemas()
+        .iter()
+        .filter_map(|default| {
+            build
+                .schemas
+                .iter()
+                .find(|schema| schema.entity_type == default.entity_type)
+                .map(|schema| schema.diff(default))
+        })
+        .collect();
+
+    match output_format {
+        OutputFormat::Pretty => {
+            let changed: Vec<&SchemaDiff> = diffs.iter().filter(|diff| !diff.is_empty()).collect();
+            for diff in &changed {
+                ui::raw_output(&format!("\n{}", diff.entity_type));
+                for field in &diff.fields {
+                    ui::raw_output(&format!("  {}", describe_field_diff(field)));
+                }
+            }
+
+            ui::info(&format!(
+                "\n{} of {} default schema(s) in this workspace differ from the defaults",
+                changed.len(),
+                diffs.len()
+            ));
+        }
+        OutputFormat::Json => ui::json_output(&diffs),
+    }
+
+    Ok(())
+}
+
+/// Describes a field difference on one line, marked with +, - or ~.
+fn describe_field_diff(field: &FieldDiff) -> String {
+    match field {
+        FieldDiff::Added { field, field_type } => format!("+ {} ({})", field, field_type),
+        FieldDiff::Removed { field, field_type } => format!("- {} ({})", field, field_type),
+        FieldDiff::Changed { field, changes } => {
+            let changes: Vec<String> = changes.iter().map(describe_field_change).collect();
+            format!("~ {}: {}", field, changes.join(", "))
+        }
+        FieldDiff::ComputedAdded { field, expression } => format!("+ {} = {}", field, expression),
+        FieldDiff::ComputedRemoved { field, expression } => format!("- {} = {}", field, expression),
+    }
+}
+
+fn describe_field_change(change: &FieldChange) -> String {
+    match change {
+        FieldChange::Type { from, to } => format!("type {} -> {}", from, to),
+        FieldChange::ItemType { from, to } => {
+            let describe = |item_type: &Option<FieldType>| {
+                item_type
+                    .as_ref()
+                    .map_or("any".to_string(), |item_type| item_type.to_string())
+            };
+            format!("item type {} -> {}", describe(from), describe(to))
+        }
+        FieldChange::Required { from, to } => format!("required {} -> {}", from, to),
+        FieldChange::AllowedValues { added, removed } => {
+            let values: Vec<String> = added
+                .iter()
+                .map(|value| format!("+{}", value))
+                .chain(removed.iter().map(|value| format!("-{}", value)))
+                .collect();
+            format!("allowed values {}", values.join(" "))
+        }
+        FieldChange::Expression { from, to } => format!("expression {} -> {}", from, to),
+    }
+}
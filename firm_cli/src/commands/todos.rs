@@ -0,0 +1,86 @@
+use firm_lang::workspace::{Annotation, DEFAULT_ANNOTATION_MARKERS, Workspace};
+use std::path::PathBuf;
+
+use super::load_workspace_files;
+use crate::errors::CliError;
+use crate::ui::{self, OutputFormat};
+
+/// Lists TODO-style comments in the workspace, grouped by file.
+///
+/// Uses the default markers (TODO, FIXME, NOTE) unless others are given.
+pub fn list_todos(
+    workspace_path: &PathBuf,
+    markers: Vec<String>,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    let mut workspace = Workspace::new();
+    load_workspace_files(workspace_path, &mut workspace).map_err(|_| CliError::BuildError)?;
+
+    let markers: Vec<&str> = if markers.is_empty() {
+        DEFAULT_ANNOTATION_MARKERS.to_vec()
+    } else {
+        markers.iter().map(String::as_str).collect()
+    };
+    let annotations = workspace.annotations(&markers);
+
+    let relative = |annotation: &Annotation| {
+        workspace
+            .relative_path(&annotation.path)
+            .unwrap_or_else(|| annotation.path.display().to_string())
+    };
+
+    match output_format {
+        OutputFormat::Pretty => {
+            if annotations.is_empty() {
+                ui::success("No annotations found");
+                return Ok(());
+            }
+
+            // Annotations come in path order, so a header is printed whenever the file changes
+            let mut current_path = None;
+            for annotation in &annotations {
+                if current_path != Some(&annotation.path) {
+                    ui::raw_output(&format!("\n{}", relative(annotation)));
+                    current_path = Some(&annotation.path);
+                }
+
+                let owner = annotation
+                    .owner
+                    .as_ref()
+                    .map(|owner| format!(" ({})", owner))
+                    .unwrap_or_default();
+                ui::raw_output(&format!(
+                    "  {}: {} {}{}",
+                    annotation.line, annotation.marker, annotation.text, owner
+                ));
+            }
+
+            ui::info(&format!("\nFound {} annotation(s)", annotations.len()));
+        }
+        OutputFormat::Json => {
+            #[derive(serde::Serialize)]
+            struct AnnotationResult {
+                path: String,
+                line: usize,
+                marker: String,
+                text: String,
+                owner: Option<String>,
+            }
+
+            let results: Vec<AnnotationResult> = annotations
+                .iter()
+                .map(|annotation| AnnotationResult {
+                    path: relative(annotation),
+                    line: annotation.line,
+                    marker: annotation.marker.clone(),
+                    text: annotation.text.clone(),
+                    owner: annotation.owner.as_ref().map(|owner| owner.to_string()),
+                })
+                .collect();
+
+            ui::json_output(&results);
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,10 @@
-use firm_core::graph::EntityGraph;
-use std::{env, fs, path::PathBuf};
+use firm_core::{Entity, ValueFormat};
+use firm_core::graph::{EntityGraph, GraphError, GraphFormat};
+use firm_lang::workspace::{DEFAULT_LOCK_TIMEOUT, WorkspaceConfig, WorkspaceLock};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use super::errors::CliError;
 use super::ui::{self};
@@ -60,6 +65,36 @@ pub fn save_graph_with_backup(
     Ok(())
 }
 
+/// Saves an entity graph to a file in a format, creating its directory if needed.
+pub fn save_graph(path: &Path, graph: &EntityGraph, format: GraphFormat) -> Result<(), CliError> {
+    let serialized_graph = graph.to_bytes(format).map_err(|e| {
+        let details = match e {
+            GraphError::SerializationError(reason) => reason,
+            e => format!("{:?}", e),
+        };
+        ui::error_with_details("Failed to serialize graph", &details);
+        CliError::FileError
+    })?;
+
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        fs::create_dir_all(parent).map_err(|e| {
+            ui::error_with_details("Failed to create graph directory", &e.to_string());
+            CliError::FileError
+        })?;
+    }
+
+    if let Err(e) = fs::write(path, serialized_graph) {
+        ui::error_with_details("Failed to write graph file", &e.to_string());
+        return Err(CliError::FileError);
+    }
+
+    ui::info(&format!("Graph written to {}", path.display()));
+    Ok(())
+}
+
 /// Loads an entity graph from the workspace root.
 pub fn load_current_graph(workspace_path: &PathBuf) -> Result<EntityGraph, CliError> {
     let current_graph_path = workspace_path.join(CURRENT_GRAPH_NAME);
@@ -91,3 +126,48 @@ pub fn load_current_graph(workspace_path: &PathBuf) -> Result<EntityGraph, CliEr
 
     Ok(graph)
 }
+
+/// Converts entities to the workspace's default timezone for output, if its config asks for it.
+pub fn entities_for_output(
+    workspace_path: &Path,
+    entities: &[&Entity],
+) -> Result<Vec<Entity>, CliError> {
+    let timezone = load_workspace_config(workspace_path)?
+        .timezone
+        .render_offset();
+
+    Ok(entities
+        .iter()
+        .map(|entity| match &timezone {
+            Some(timezone) => entity.in_timezone(timezone),
+            None => (*entity).clone(),
+        })
+        .collect())
+}
+
+/// Gets the format the workspace's config asks values to be shown in.
+pub fn value_format(workspace_path: &Path) -> Result<ValueFormat, CliError> {
+    Ok(load_workspace_config(workspace_path)?
+        .display
+        .value_format())
+}
+
+/// Loads the workspace config, falling back to the defaults if there's no config file.
+pub fn load_workspace_config(workspace_path: &Path) -> Result<WorkspaceConfig, CliError> {
+    ui::debug("Loading workspace config");
+    WorkspaceConfig::load(workspace_path).map_err(|e| {
+        ui::error_with_details("Failed to load workspace config", &e.to_string());
+        CliError::FileError
+    })
+}
+
+/// Takes the workspace lock before writing source files, waiting for other writers to finish.
+///
+/// The lock is released when the returned guard is dropped.
+pub fn lock_workspace(workspace_path: &Path) -> Result<WorkspaceLock, CliError> {
+    ui::debug("Locking workspace for writing");
+    WorkspaceLock::acquire(workspace_path, DEFAULT_LOCK_TIMEOUT).map_err(|e| {
+        ui::error_with_details("Couldn't lock the workspace", &e.to_string());
+        CliError::FileError
+    })
+}
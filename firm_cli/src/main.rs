@@ -13,10 +13,11 @@ mod query;
 mod ui;
 
 use clap::Parser;
+use firm_lang::workspace::{PathCheckOptions, TypeFilter};
 use std::process::ExitCode;
 
-use cli::{FirmCli, FirmCliCommand};
-use commands::build_and_save_graph;
+use cli::{FirmCli, FirmCliCommand, SchemaCommand};
+use commands::{QueryInput, build_and_save_graph};
 use files::get_workspace_path;
 
 fn main() -> ExitCode {
@@ -38,14 +39,18 @@ fn main() -> ExitCode {
     let skip_build = cli.cached
         || matches!(
             cli.command,
-            FirmCliCommand::Build
+            FirmCliCommand::Build { .. }
                 | FirmCliCommand::Init
                 | FirmCliCommand::Source { .. }
-                | FirmCliCommand::Mcp
+                | FirmCliCommand::Check { .. }
+                | FirmCliCommand::Fix { .. }
+                | FirmCliCommand::Todos { .. }
+                | FirmCliCommand::Schema { .. }
+                | FirmCliCommand::Mcp { .. }
         );
 
     if !skip_build {
-        match build_and_save_graph(&workspace_path) {
+        match build_and_save_graph(&workspace_path, None, None, &TypeFilter::new(), None) {
             Ok(_) => (),
             Err(_) => return ExitCode::FAILURE,
         }
@@ -54,7 +59,30 @@ fn main() -> ExitCode {
     // Handle CLI subcommands
     let result = match cli.command {
         FirmCliCommand::Init => commands::init_workspace(&workspace_path),
-        FirmCliCommand::Build => build_and_save_graph(&workspace_path),
+        FirmCliCommand::Build {
+            check_paths,
+            allow_external_paths,
+            output,
+            graph_format,
+            only_type,
+            exclude_type,
+            allow_scripts,
+            script_timeout,
+        } => {
+            let path_check = (check_paths || allow_external_paths).then_some(PathCheckOptions {
+                allow_external_paths,
+            });
+            let output = output.map(|path| (path, graph_format.into()));
+            let type_filter = commands::type_filter(only_type, exclude_type);
+            let scripts = commands::script_options(allow_scripts, script_timeout);
+            build_and_save_graph(
+                &workspace_path,
+                path_check.as_ref(),
+                output.as_ref(),
+                &type_filter,
+                scripts.as_ref(),
+            )
+        }
         FirmCliCommand::Get {
             target_type,
             target_id,
@@ -73,31 +101,90 @@ fn main() -> ExitCode {
             direction,
             cli.format,
         ),
+        FirmCliCommand::Graph { orphans } => {
+            commands::inspect_graph(&workspace_path, orphans, cli.format)
+        }
         FirmCliCommand::Add {
             to_file,
             r#type,
             id,
+            from,
             fields,
             lists,
             list_values,
+            dry_run,
         } => commands::add_entity(
             &workspace_path,
             to_file,
             r#type,
             id,
+            from,
             fields,
             lists,
             list_values,
+            dry_run,
             cli.format,
         ),
-        FirmCliCommand::Query { query } => {
-            commands::query_entities(&workspace_path, query, cli.format)
+        FirmCliCommand::Query {
+            query,
+            file,
+            strict,
+            explain,
+        } => {
+            let input = match (query, file) {
+                (Some(query), _) if query == "-" => QueryInput::Stdin,
+                (Some(query), _) => QueryInput::Inline(query),
+                (None, Some(file)) if file.as_os_str() == "-" => QueryInput::Stdin,
+                (None, Some(file)) => QueryInput::File(file),
+                (None, None) => unreachable!("clap requires a query or a file"),
+            };
+            commands::query_entities(&workspace_path, input, strict, explain, cli.format)
+        }
+        FirmCliCommand::Run {
+            name,
+            strict,
+            explain,
+        } => commands::run_named_query(&workspace_path, name, strict, explain, cli.format),
+        FirmCliCommand::Check {
+            refs,
+            strict_refs,
+            only_type,
+            exclude_type,
+            allow_scripts,
+            script_timeout,
+        } => {
+            let type_filter = commands::type_filter(only_type, exclude_type);
+            let scripts = commands::script_options(allow_scripts, script_timeout);
+            commands::check_workspace(
+                &workspace_path,
+                refs,
+                strict_refs,
+                &type_filter,
+                scripts.as_ref(),
+                cli.format,
+            )
         }
+        FirmCliCommand::Fix { yes } => commands::fix_workspace(&workspace_path, yes, cli.format),
+        FirmCliCommand::Todos { markers } => {
+            commands::list_todos(&workspace_path, markers, cli.format)
+        }
+        FirmCliCommand::Schema { command } => match command {
+            SchemaCommand::Diff => commands::diff_schemas(&workspace_path, cli.format),
+        },
         FirmCliCommand::Source {
             target_type,
             target_id,
         } => commands::find_item_source(&workspace_path, target_type, target_id, cli.format),
-        FirmCliCommand::Mcp => commands::mcp::serve(&workspace_path),
+        FirmCliCommand::Mcp {
+            read_only,
+            max_response_entities,
+            max_response_bytes,
+        } => commands::mcp::serve(
+            &workspace_path,
+            read_only,
+            max_response_entities,
+            max_response_bytes,
+        ),
     };
 
     result.map_or(ExitCode::FAILURE, |_| ExitCode::SUCCESS)
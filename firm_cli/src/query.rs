@@ -1,5 +1,5 @@
 use clap::ValueEnum;
-use firm_core::graph::Direction;
+use firm_core::graph::{Direction, GraphFormat};
 
 /// Wraps the underlying graph direction enum, allowing it to be used by clap.
 #[derive(Clone, Debug, ValueEnum, PartialEq)]
@@ -16,3 +16,19 @@ impl From<CliDirection> for Direction {
         }
     }
 }
+
+/// Wraps the graph formats, allowing them to be used by clap.
+#[derive(Clone, Debug, ValueEnum, PartialEq)]
+pub enum CliGraphFormat {
+    Json,
+    Bincode,
+}
+
+impl From<CliGraphFormat> for GraphFormat {
+    fn from(format: CliGraphFormat) -> GraphFormat {
+        match format {
+            CliGraphFormat::Json => GraphFormat::Json,
+            CliGraphFormat::Bincode => GraphFormat::Bincode,
+        }
+    }
+}
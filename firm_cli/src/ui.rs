@@ -1,6 +1,6 @@
 use clap::ValueEnum;
 use console::Style;
-use firm_core::{Entity, EntitySchema};
+use firm_core::{Entity, EntitySchema, ValueFormat};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::{fmt, time::Duration};
 
@@ -94,15 +94,15 @@ impl fmt::Display for OutputFormat {
     }
 }
 
-/// Outputs a single entity in pretty format.
-pub fn pretty_output_entity_single(entity: &Entity) {
-    println!("\n{}", entity);
+/// Outputs a single entity in pretty format, with values in the given format.
+pub fn pretty_output_entity_single(entity: &Entity, value_format: &ValueFormat) {
+    println!("\n{}", value_format.entity(entity));
 }
 
-/// Outputs a list of entities in pretty format.
-pub fn pretty_output_entity_list(entities: &Vec<&Entity>) {
+/// Outputs a list of entities in pretty format, with values in the given format.
+pub fn pretty_output_entity_list(entities: &Vec<&Entity>, value_format: &ValueFormat) {
     for (i, entity) in entities.iter().enumerate() {
-        pretty_output_entity_single(entity);
+        pretty_output_entity_single(entity, value_format);
 
         // Add a separator after each entity, except for the last one.
         if i < entities.len() - 1 {
@@ -123,6 +123,17 @@ pub fn json_output<T: serde::Serialize>(data: &T) {
     }
 }
 
+/// Outputs a single entity in its canonical json format.
+pub fn json_output_entity_single(entity: &Entity) {
+    json_output(&entity.to_json());
+}
+
+/// Outputs a list of entities in their canonical json format.
+pub fn json_output_entity_list(entities: &[&Entity]) {
+    let json: Vec<_> = entities.iter().map(|entity| entity.to_json()).collect();
+    json_output(&json);
+}
+
 /// Outputs a list of strings (one per line for pretty, array for JSON).
 pub fn list_output(items: &[&str], format: OutputFormat) {
     match format {
@@ -1,15 +1,31 @@
+use chrono::{DateTime, FixedOffset};
 use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use super::{EntityId, EntityType, FieldId, FieldValue};
 
+/// The field holding an entity's tags, unless its schema declares a field by that name.
+pub const TAGS_FIELD_NAME: &str = "tags";
+
 /// Represents a business entity in the Firm graph.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entity {
     pub id: EntityId,
     pub entity_type: EntityType,
     pub fields: Vec<(FieldId, FieldValue)>,
+    /// Fields that were explicitly cleared, as opposed to never set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unset_fields: Vec<FieldId>,
+    /// When the entity was first written, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<FixedOffset>>,
+    /// When the entity was last changed, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<FixedOffset>>,
+    /// Free-form labels that apply across entity types, kept apart from schema fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl Entity {
@@ -19,6 +35,10 @@ impl Entity {
             id,
             entity_type,
             fields: Vec::new(),
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         }
     }
 
@@ -31,6 +51,52 @@ impl Entity {
         self
     }
 
+    /// Builder method to set when a new entity was created and last modified.
+    pub fn with_timestamps(
+        mut self,
+        created: Option<DateTime<FixedOffset>>,
+        modified: Option<DateTime<FixedOffset>>,
+    ) -> Self {
+        self.created = created;
+        self.modified = modified;
+        self
+    }
+
+    /// Builder method to add tags to a new entity.
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Moves a `tags` field holding a list of strings to the entity's tags.
+    ///
+    /// Returns whether the field was moved. Other `tags` values are left as a field.
+    pub fn extract_tags(&mut self) -> bool {
+        let tags_id = FieldId::new(TAGS_FIELD_NAME);
+        let Some(FieldValue::List(items)) = self.get_field(&tags_id) else {
+            return false;
+        };
+
+        let tags: Option<Vec<String>> = items
+            .iter()
+            .map(|item| match item {
+                FieldValue::String(tag) => Some(tag.clone()),
+                _ => None,
+            })
+            .collect();
+        let Some(tags) = tags else {
+            return false;
+        };
+
+        self.fields.retain(|(field_id, _)| *field_id != tags_id);
+        self.tags = tags;
+        true
+    }
+
     /// Try to get a entity field value for a given field ID.
     pub fn get_field(&self, id: &FieldId) -> Option<&FieldValue> {
         self.fields
@@ -38,6 +104,58 @@ impl Entity {
             .find(|(field_id, _)| field_id == id)
             .map(|(_, field_value)| field_value)
     }
+
+    /// Builder method to mark a field as explicitly unset on a new entity.
+    pub fn with_unset_field(mut self, id: FieldId) -> Self {
+        self.unset_field(&id);
+        self
+    }
+
+    /// Sets a field value, replacing any existing value in place.
+    ///
+    /// Clears the field's unset mark, if it had one.
+    pub fn set_field<V>(&mut self, id: FieldId, value: V)
+    where
+        V: Into<FieldValue>,
+    {
+        self.unset_fields.retain(|unset_id| *unset_id != id);
+
+        let value = value.into();
+        match self.fields.iter_mut().find(|(field_id, _)| *field_id == id) {
+            Some((_, existing)) => *existing = value,
+            None => self.fields.push((id, value)),
+        }
+    }
+
+    /// Removes a field value and marks the field as explicitly unset.
+    ///
+    /// Returns the removed value, if the field had one.
+    pub fn unset_field(&mut self, id: &FieldId) -> Option<FieldValue> {
+        if !self.unset_fields.contains(id) {
+            self.unset_fields.push(id.clone());
+        }
+
+        let index = self.fields.iter().position(|(field_id, _)| field_id == id)?;
+        Some(self.fields.remove(index).1)
+    }
+
+    /// Checks whether a field was explicitly unset, rather than never set.
+    pub fn is_unset(&self, id: &FieldId) -> bool {
+        self.unset_fields.contains(id)
+    }
+
+    /// Copies the entity with its datetime fields and timestamps shown in a timezone.
+    pub fn in_timezone(&self, timezone: &FixedOffset) -> Entity {
+        let mut entity = self.clone();
+        for (_, value) in &mut entity.fields {
+            *value = value.in_timezone(timezone);
+        }
+        entity.created = self.created.map(|created| created.with_timezone(timezone));
+        entity.modified = self
+            .modified
+            .map(|modified| modified.with_timezone(timezone));
+        entity
+    }
 }
 
 impl fmt::Display for Entity {
@@ -86,6 +204,64 @@ mod tests {
         assert_eq!(person.get_field(&FieldId::new("nonexistant")), None);
     }
 
+    #[test]
+    fn test_entity_set_field_replaces_in_place() {
+        let mut person = Entity::new(EntityId::new("john_doe"), EntityType::new("person"))
+            .with_field(FieldId::new("name"), "John")
+            .with_field(FieldId::new("email"), "john@example.com");
+
+        person.set_field(FieldId::new("name"), "John Doe");
+
+        assert_eq!(
+            person.fields,
+            vec![
+                (FieldId::new("name"), FieldValue::from("John Doe")),
+                (FieldId::new("email"), FieldValue::from("john@example.com")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entity_unset_field() {
+        let mut person = Entity::new(EntityId::new("john_doe"), EntityType::new("person"))
+            .with_field(FieldId::new("email"), "john@example.com");
+
+        let removed = person.unset_field(&FieldId::new("email"));
+
+        assert_eq!(removed, Some(FieldValue::from("john@example.com")));
+        assert_eq!(person.get_field(&FieldId::new("email")), None);
+        assert!(person.is_unset(&FieldId::new("email")));
+        assert!(!person.is_unset(&FieldId::new("phone")));
+    }
+
+    #[test]
+    fn test_entity_set_field_clears_unset() {
+        let mut person = Entity::new(EntityId::new("john_doe"), EntityType::new("person"))
+            .with_unset_field(FieldId::new("email"));
+        assert!(person.is_unset(&FieldId::new("email")));
+
+        person.set_field(FieldId::new("email"), "john@example.com");
+
+        assert!(!person.is_unset(&FieldId::new("email")));
+        assert!(person.get_field(&FieldId::new("email")).is_some());
+    }
+
+    #[test]
+    fn test_entity_unset_fields_serialization() {
+        let person = Entity::new(EntityId::new("john_doe"), EntityType::new("person"));
+        let json = serde_json::to_string(&person).unwrap();
+        assert!(!json.contains("unset_fields"));
+
+        // Entities saved before unset fields existed still load
+        let loaded: Entity = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, person);
+
+        let person = person.with_unset_field(FieldId::new("email"));
+        let json = serde_json::to_string(&person).unwrap();
+        let loaded: Entity = serde_json::from_str(&json).unwrap();
+        assert!(loaded.is_unset(&FieldId::new("email")));
+    }
+
     #[test]
     fn test_entity_different_types() {
         let person = Entity::new(EntityId::new("john_doe"), EntityType::new("person"));
@@ -94,4 +270,27 @@ mod tests {
         assert_eq!(person.entity_type, EntityType::new("person"));
         assert_eq!(organization.entity_type, EntityType::new("organization"));
     }
+
+    #[test]
+    fn test_entity_extract_tags() {
+        let tags = FieldValue::List(vec![
+            FieldValue::String("urgent".to_string()),
+            FieldValue::String("q3".to_string()),
+        ]);
+        let mut task = Entity::new(EntityId::new("fix_bug"), EntityType::new("task"))
+            .with_field(FieldId::new("name"), "Fix bug")
+            .with_field(FieldId::new(TAGS_FIELD_NAME), tags);
+
+        assert!(task.extract_tags());
+        assert_eq!(task.tags, vec!["urgent", "q3"]);
+        assert_eq!(task.get_field(&FieldId::new(TAGS_FIELD_NAME)), None);
+        assert_eq!(task.fields.len(), 1);
+
+        // Lists of other values stay a regular field
+        let numbers = FieldValue::List(vec![FieldValue::Integer(1)]);
+        let mut task = Entity::new(EntityId::new("fix_bug"), EntityType::new("task"))
+            .with_field(FieldId::new(TAGS_FIELD_NAME), numbers);
+        assert!(!task.extract_tags());
+        assert!(task.tags.is_empty());
+    }
 }
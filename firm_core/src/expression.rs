@@ -0,0 +1,360 @@
+//! Arithmetic expressions over an entity's fields, used by computed fields.
+
+use std::fmt;
+
+use iso_currency::Currency;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::{Entity, FieldId, FieldValue};
+
+/// An arithmetic operator.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Subtract => write!(f, "-"),
+            Operator::Multiply => write!(f, "*"),
+            Operator::Divide => write!(f, "/"),
+        }
+    }
+}
+
+/// An arithmetic expression over numbers and an entity's numeric fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expression {
+    Integer(i64),
+    Float(f64),
+    Field(FieldId),
+    Negate(Box<Expression>),
+    Binary {
+        operator: Operator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+}
+
+impl Expression {
+    /// Shorthand for creating a binary expression.
+    pub fn binary(operator: Operator, left: Expression, right: Expression) -> Self {
+        Expression::Binary {
+            operator,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    /// Evaluates the expression with the entity's field values.
+    ///
+    /// Integers stay integers when added, subtracted or multiplied, and
+    /// division gives a float. Currency amounts can be added to and subtracted
+    /// from amounts in the same currency, and multiplied or divided by numbers.
+    /// Dividing two amounts in the same currency gives a float.
+    ///
+    /// Returns None if a field is missing or isn't a number or currency, if the
+    /// values can't be combined (like amounts in different currencies), or if
+    /// the result is undefined (like dividing by zero).
+    pub fn evaluate(&self, entity: &Entity) -> Option<FieldValue> {
+        self.evaluate_number(entity)
+            .and_then(Number::into_field_value)
+    }
+
+    /// Gets the fields the expression uses, in the order they appear.
+    pub fn fields(&self) -> Vec<&FieldId> {
+        match self {
+            Expression::Integer(_) | Expression::Float(_) => Vec::new(),
+            Expression::Field(field_id) => vec![field_id],
+            Expression::Negate(inner) => inner.fields(),
+            Expression::Binary { left, right, .. } => {
+                let mut fields = left.fields();
+                fields.extend(right.fields());
+                fields
+            }
+        }
+    }
+
+    fn evaluate_number(&self, entity: &Entity) -> Option<Number> {
+        match self {
+            Expression::Integer(value) => Some(Number::Integer(*value)),
+            Expression::Float(value) => Some(Number::Float(*value)),
+            Expression::Field(field_id) => match entity.get_field(field_id)? {
+                FieldValue::Integer(value) => Some(Number::Integer(*value)),
+                FieldValue::Float(value) => Some(Number::Float(*value)),
+                FieldValue::Currency { amount, currency } => {
+                    Some(Number::Currency(*amount, *currency))
+                }
+                _ => None,
+            },
+            Expression::Negate(inner) => match inner.evaluate_number(entity)? {
+                Number::Integer(value) => value.checked_neg().map(Number::Integer),
+                Number::Float(value) => Some(Number::Float(-value)),
+                Number::Currency(amount, currency) => Some(Number::Currency(-amount, currency)),
+            },
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => apply(
+                *operator,
+                left.evaluate_number(entity)?,
+                right.evaluate_number(entity)?,
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Integer(value) => write!(f, "{}", value),
+            Expression::Float(value) => write!(f, "{:?}", value),
+            Expression::Field(field_id) => write!(f, "{}", field_id),
+            Expression::Negate(inner) => write!(f, "-{}", inner),
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => write!(f, "({} {} {})", left, operator, right),
+        }
+    }
+}
+
+/// A value while evaluating an expression.
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+    Currency(Decimal, Currency),
+}
+
+impl Number {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Number::Integer(value) => Some(*value as f64),
+            Number::Float(value) => Some(*value),
+            Number::Currency(..) => None,
+        }
+    }
+
+    fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Number::Integer(value) => Some(Decimal::from(*value)),
+            Number::Float(value) => Decimal::from_f64(*value),
+            Number::Currency(..) => None,
+        }
+    }
+
+    /// Converts the result to a field value, leaving out undefined floats.
+    fn into_field_value(self) -> Option<FieldValue> {
+        match self {
+            Number::Integer(value) => Some(FieldValue::Integer(value)),
+            Number::Float(value) if value.is_finite() => Some(FieldValue::Float(value)),
+            Number::Float(_) => None,
+            Number::Currency(amount, currency) => Some(FieldValue::Currency { amount, currency }),
+        }
+    }
+}
+
+/// Applies an operator to two values, if they can be combined.
+fn apply(operator: Operator, left: Number, right: Number) -> Option<Number> {
+    use Number::*;
+    use Operator::*;
+
+    match (operator, left, right) {
+        (Add, Integer(a), Integer(b)) => a.checked_add(b).map(Integer),
+        (Subtract, Integer(a), Integer(b)) => a.checked_sub(b).map(Integer),
+        (Multiply, Integer(a), Integer(b)) => a.checked_mul(b).map(Integer),
+
+        (Add | Subtract, Currency(a, a_currency), Currency(b, b_currency))
+            if a_currency == b_currency =>
+        {
+            let amount = match operator {
+                Add => a.checked_add(b)?,
+                _ => a.checked_sub(b)?,
+            };
+            Some(Currency(amount, a_currency))
+        }
+        (Divide, Currency(a, a_currency), Currency(b, b_currency))
+            if a_currency == b_currency && !b.is_zero() =>
+        {
+            a.checked_div(b)?.to_f64().map(Float)
+        }
+        (Multiply, Currency(amount, currency), factor)
+        | (Multiply, factor, Currency(amount, currency)) => Some(Currency(
+            amount.checked_mul(factor.as_decimal()?)?,
+            currency,
+        )),
+        (Divide, Currency(amount, currency), divisor) => {
+            let divisor = divisor.as_decimal()?;
+            if divisor.is_zero() {
+                return None;
+            }
+            Some(Currency(amount.checked_div(divisor)?, currency))
+        }
+        (_, Currency(..), _) | (_, _, Currency(..)) => None,
+
+        (operator, left, right) => {
+            let (a, b) = (left.as_f64()?, right.as_f64()?);
+            match operator {
+                Add => Some(Float(a + b)),
+                Subtract => Some(Float(a - b)),
+                Multiply => Some(Float(a * b)),
+                Divide if b == 0.0 => None,
+                Divide => Some(Float(a / b)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityId, EntityType};
+    use std::str::FromStr;
+
+    fn field(name: &str) -> Expression {
+        Expression::Field(FieldId::new(name))
+    }
+
+    fn goal() -> Entity {
+        Entity::new(EntityId::new("goal"), EntityType::new("goal"))
+            .with_field(FieldId::new("start_value"), 10)
+            .with_field(FieldId::new("current_value"), 25)
+            .with_field(FieldId::new("target_value"), 40)
+            .with_field(FieldId::new("weight"), 0.5)
+            .with_field(
+                FieldId::new("budget"),
+                FieldValue::Currency {
+                    amount: Decimal::from_str("100.50").unwrap(),
+                    currency: Currency::EUR,
+                },
+            )
+            .with_field(
+                FieldId::new("spent"),
+                FieldValue::Currency {
+                    amount: Decimal::from_str("20.10").unwrap(),
+                    currency: Currency::EUR,
+                },
+            )
+            .with_field(
+                FieldId::new("fee"),
+                FieldValue::Currency {
+                    amount: Decimal::from(5),
+                    currency: Currency::USD,
+                },
+            )
+            .with_field(FieldId::new("name"), "Launch")
+    }
+
+    #[test]
+    fn test_evaluate_numbers() {
+        let entity = goal();
+
+        // (current_value - start_value) / (target_value - start_value)
+        let progress = Expression::binary(
+            Operator::Divide,
+            Expression::binary(
+                Operator::Subtract,
+                field("current_value"),
+                field("start_value"),
+            ),
+            Expression::binary(
+                Operator::Subtract,
+                field("target_value"),
+                field("start_value"),
+            ),
+        );
+        assert_eq!(progress.evaluate(&entity), Some(FieldValue::Float(0.5)));
+        assert_eq!(
+            progress.fields(),
+            vec![
+                &FieldId::new("current_value"),
+                &FieldId::new("start_value"),
+                &FieldId::new("target_value"),
+                &FieldId::new("start_value"),
+            ]
+        );
+
+        // Integers stay integers, unless mixed with floats
+        let sum = Expression::binary(Operator::Add, field("start_value"), Expression::Integer(2));
+        assert_eq!(sum.evaluate(&entity), Some(FieldValue::Integer(12)));
+        let scaled = Expression::binary(Operator::Multiply, field("start_value"), field("weight"));
+        assert_eq!(scaled.evaluate(&entity), Some(FieldValue::Float(5.0)));
+        let negated = Expression::Negate(Box::new(field("start_value")));
+        assert_eq!(negated.evaluate(&entity), Some(FieldValue::Integer(-10)));
+    }
+
+    #[test]
+    fn test_evaluate_currency() {
+        let entity = goal();
+
+        let remaining = Expression::binary(Operator::Subtract, field("budget"), field("spent"));
+        assert_eq!(
+            remaining.evaluate(&entity),
+            Some(FieldValue::Currency {
+                amount: Decimal::from_str("80.40").unwrap(),
+                currency: Currency::EUR,
+            })
+        );
+
+        let doubled =
+            Expression::binary(Operator::Multiply, Expression::Integer(2), field("spent"));
+        assert_eq!(
+            doubled.evaluate(&entity),
+            Some(FieldValue::Currency {
+                amount: Decimal::from_str("40.20").unwrap(),
+                currency: Currency::EUR,
+            })
+        );
+
+        let share = Expression::binary(Operator::Divide, field("spent"), field("budget"));
+        assert!(matches!(
+            share.evaluate(&entity),
+            Some(FieldValue::Float(_))
+        ));
+
+        // Amounts in different currencies, or amounts and plain numbers, don't add up
+        let mixed = Expression::binary(Operator::Add, field("budget"), field("fee"));
+        assert_eq!(mixed.evaluate(&entity), None);
+        let plain = Expression::binary(Operator::Add, field("budget"), Expression::Integer(1));
+        assert_eq!(plain.evaluate(&entity), None);
+    }
+
+    #[test]
+    fn test_evaluate_undefined() {
+        let entity = goal();
+
+        // Missing and non-numeric fields
+        let missing = Expression::binary(Operator::Add, field("start_value"), field("nothing"));
+        assert_eq!(missing.evaluate(&entity), None);
+        let text = Expression::binary(Operator::Add, field("start_value"), field("name"));
+        assert_eq!(text.evaluate(&entity), None);
+
+        // Division by zero and overflow
+        let by_zero = Expression::binary(
+            Operator::Divide,
+            field("start_value"),
+            Expression::binary(
+                Operator::Subtract,
+                field("start_value"),
+                Expression::Integer(10),
+            ),
+        );
+        assert_eq!(by_zero.evaluate(&entity), None);
+        let overflow = Expression::binary(
+            Operator::Multiply,
+            Expression::Integer(i64::MAX),
+            Expression::Integer(2),
+        );
+        assert_eq!(overflow.evaluate(&entity), None);
+    }
+}
@@ -1,10 +1,11 @@
 use std::fmt;
+use std::str::FromStr;
 
 use chrono::{DateTime, FixedOffset};
 use iso_currency::Currency;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::{EntityId, FieldId};
 
@@ -40,6 +41,68 @@ impl fmt::Display for FieldType {
     }
 }
 
+impl FieldType {
+    /// The type names written in the DSL, like `type = "string"`.
+    pub const NAMES: [&'static str; 10] = [
+        "string",
+        "integer",
+        "float",
+        "boolean",
+        "currency",
+        "reference",
+        "datetime",
+        "path",
+        "enum",
+        "list",
+    ];
+
+    /// Whether a schema can declare this as the item type of a list field.
+    ///
+    /// Lists and enums can't be items, as the schema has nowhere to put their
+    /// own item type or allowed values.
+    pub fn is_item_type(&self) -> bool {
+        !matches!(self, FieldType::List | FieldType::Enum)
+    }
+}
+
+/// Parses a type name as written in the DSL. Names are lowercase.
+impl FromStr for FieldType {
+    type Err = UnknownFieldType;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "boolean" => Ok(FieldType::Boolean),
+            "string" => Ok(FieldType::String),
+            "integer" => Ok(FieldType::Integer),
+            "float" => Ok(FieldType::Float),
+            "currency" => Ok(FieldType::Currency),
+            "reference" => Ok(FieldType::Reference),
+            "list" => Ok(FieldType::List),
+            "datetime" => Ok(FieldType::DateTime),
+            "path" => Ok(FieldType::Path),
+            "enum" => Ok(FieldType::Enum),
+            _ => Err(UnknownFieldType(name.to_string())),
+        }
+    }
+}
+
+/// A type name that isn't one of the [`FieldType::NAMES`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownFieldType(pub String);
+
+impl fmt::Display for UnknownFieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown field type '{}'. Valid types: {}",
+            self.0,
+            FieldType::NAMES.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownFieldType {}
+
 /// The supported reference types: to an entity or an entity field.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ReferenceValue {
@@ -122,6 +185,37 @@ impl FieldValue {
     pub fn is_type(&self, expected: &FieldType) -> bool {
         &self.get_type() == expected
     }
+
+    /// Converts datetime values to a timezone, including inside lists.
+    ///
+    /// The instant stays the same, but the calendar date and time of day are
+    /// the ones in the timezone. Other values are returned as they are.
+    pub fn in_timezone(&self, timezone: &FixedOffset) -> FieldValue {
+        match self {
+            FieldValue::DateTime(value) => FieldValue::DateTime(value.with_timezone(timezone)),
+            FieldValue::List(items) => FieldValue::List(
+                items
+                    .iter()
+                    .map(|item| item.in_timezone(timezone))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Maps path values, including inside lists.
+    ///
+    /// Useful for moving values between files, since relative paths are
+    /// written relative to the file they're in. Other values are returned as they are.
+    pub fn map_paths(&self, map_path: &dyn Fn(&Path) -> PathBuf) -> FieldValue {
+        match self {
+            FieldValue::Path(path) => FieldValue::Path(map_path(path)),
+            FieldValue::List(items) => {
+                FieldValue::List(items.iter().map(|item| item.map_paths(map_path)).collect())
+            }
+            other => other.clone(),
+        }
+    }
 }
 
 /// Convert from bool to FieldValue.
@@ -185,6 +279,27 @@ mod tests {
     use super::*;
     use std::env::current_dir;
 
+    #[test]
+    fn test_field_type_from_str() {
+        for name in FieldType::NAMES {
+            let field_type: FieldType = name.parse().unwrap();
+            assert_eq!(field_type.to_string().to_lowercase(), name);
+        }
+
+        assert_eq!(
+            "text".parse::<FieldType>(),
+            Err(UnknownFieldType("text".to_string()))
+        );
+        assert!("String".parse::<FieldType>().is_err());
+    }
+
+    #[test]
+    fn test_field_type_is_item_type() {
+        assert!(FieldType::Reference.is_item_type());
+        assert!(!FieldType::List.is_item_type());
+        assert!(!FieldType::Enum.is_item_type());
+    }
+
     #[test]
     fn test_field_value_get_type() {
         let string_value = FieldValue::String("test".to_string());
@@ -433,4 +548,41 @@ mod tests {
         let deserialized: FieldValue = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized, field);
     }
+
+    #[test]
+    fn test_field_value_in_timezone() {
+        use chrono::{FixedOffset, TimeZone};
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let late = utc.with_ymd_and_hms(2025, 9, 30, 20, 0, 0).unwrap();
+
+        let converted = FieldValue::List(vec![FieldValue::DateTime(late), FieldValue::Integer(1)])
+            .in_timezone(&tokyo);
+        let FieldValue::List(items) = converted else {
+            panic!("Expected a list");
+        };
+        let FieldValue::DateTime(in_tokyo) = &items[0] else {
+            panic!("Expected a datetime");
+        };
+        assert_eq!(*in_tokyo, late);
+        assert_eq!(in_tokyo.to_string(), "2025-10-01 05:00:00 +09:00");
+        assert_eq!(items[1], FieldValue::Integer(1));
+    }
+
+    #[test]
+    fn test_field_value_map_paths() {
+        let moved = FieldValue::List(vec![
+            FieldValue::Path(PathBuf::from("./docs/spec.pdf")),
+            FieldValue::String("./docs/spec.pdf".to_string()),
+        ])
+        .map_paths(&|path| Path::new("..").join(path));
+
+        assert_eq!(
+            moved,
+            FieldValue::List(vec![
+                FieldValue::Path(PathBuf::from(".././docs/spec.pdf")),
+                FieldValue::String("./docs/spec.pdf".to_string()),
+            ])
+        );
+    }
 }
@@ -0,0 +1,265 @@
+//! Formatting of values for people reading reports.
+//!
+//! `Display` gives the raw form of values, which is what machine paths like
+//! JSON output and generated DSL rely on. A `ValueFormat` renders values,
+//! entities and aggregation results for reading instead: currency amounts with
+//! their symbol and the currency's minor-unit decimals, and datetimes in a
+//! chosen format. The default `ValueFormat` renders the raw form.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset};
+use convert_case::{Case, Casing};
+use iso_currency::Currency;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::graph::{AggregateValue, AggregationResult};
+use crate::{Entity, FieldValue};
+
+/// The placeholder symbol of currencies that don't have one.
+const GENERIC_CURRENCY_SYMBOL: &str = "¤";
+
+/// How currency amounts are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CurrencyFormat {
+    /// The amount as written followed by the currency code, like `100.5 USD`.
+    #[default]
+    Code,
+    /// The currency symbol and the amount with the currency's minor-unit
+    /// decimals, like `$100.50`.
+    Symbol,
+}
+
+/// Options for showing values to people.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueFormat {
+    pub currency: CurrencyFormat,
+    /// A strftime-style format for datetimes, like `%Y-%m-%d %H:%M`.
+    ///
+    /// Datetimes are shown in their raw form without one.
+    pub date_format: Option<String>,
+}
+
+impl ValueFormat {
+    /// Checks that a date format only uses known strftime specifiers.
+    pub fn is_valid_date_format(format: &str) -> bool {
+        !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+    }
+
+    /// Formats a field value, including the items of lists.
+    pub fn value(&self, value: &FieldValue) -> String {
+        match value {
+            FieldValue::Currency { amount, currency }
+                if self.currency == CurrencyFormat::Symbol =>
+            {
+                with_symbol(amount, currency)
+            }
+            FieldValue::DateTime(value) => self.datetime(value),
+            FieldValue::List(items) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| self.value(item))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Formats a datetime with the date format, if there's a valid one.
+    pub fn datetime(&self, value: &DateTime<FixedOffset>) -> String {
+        match &self.date_format {
+            Some(format) if Self::is_valid_date_format(format) => value.format(format).to_string(),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Formats the value of a numeric aggregation.
+    pub fn aggregate_value(&self, value: &AggregateValue) -> String {
+        match value {
+            AggregateValue::Currency { amount, currency }
+                if self.currency == CurrencyFormat::Symbol =>
+            {
+                with_symbol(amount, currency)
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Formats an entity as its ID followed by a line per field.
+    pub fn entity(&self, entity: &Entity) -> String {
+        let mut text = format!("{}\n\n", entity.id);
+        for (field_id, value) in &entity.fields {
+            text.push_str(&format!(
+                "{}: {}\n",
+                field_id.as_str().to_case(Case::Sentence),
+                self.value(value)
+            ));
+        }
+        text
+    }
+
+    /// Formats an aggregation result, with a line per row or group.
+    pub fn aggregation(&self, result: &AggregationResult) -> String {
+        let cell = |value: &Option<FieldValue>| match value {
+            Some(value) => self.value(value),
+            None => "-".to_string(),
+        };
+
+        match result {
+            AggregationResult::Count(n) => n.to_string(),
+            AggregationResult::Sum(value) => self.aggregate_value(value),
+            AggregationResult::Average(value) => value.to_string(),
+            AggregationResult::Median(value) => value.to_string(),
+            AggregationResult::Select { columns, rows } => {
+                let mut text = format!("{}\n", columns.join("\t"));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(cell).collect();
+                    text.push_str(&format!("{}\n", cells.join("\t")));
+                }
+                text
+            }
+            AggregationResult::Grouped {
+                field,
+                aggregate,
+                groups,
+            } => {
+                let mut text = format!("{}\t{}\n", field, aggregate);
+                for (key, result) in groups {
+                    text.push_str(&format!("{}\t{}\n", cell(key), self.aggregation(result)));
+                }
+                text
+            }
+        }
+    }
+}
+
+/// Formats an amount with the currency's symbol, rounded to its minor unit.
+///
+/// Symbols made of letters, like `kr`, follow the amount.
+fn with_symbol(amount: &Decimal, currency: &Currency) -> String {
+    let amount = match currency.exponent() {
+        Some(exponent) => {
+            let decimals = u32::from(exponent);
+            let mut rounded =
+                amount.round_dp_with_strategy(decimals, RoundingStrategy::MidpointAwayFromZero);
+            rounded.rescale(decimals);
+            rounded
+        }
+        None => *amount,
+    };
+
+    let sign = if amount.is_sign_negative() && !amount.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+    let digits = amount.abs();
+    let symbol = currency.symbol().to_string();
+    if symbol == GENERIC_CURRENCY_SYMBOL {
+        format!("{}{} {}", sign, digits, currency.code())
+    } else if symbol.chars().any(char::is_alphabetic) {
+        format!("{}{} {}", sign, digits, symbol)
+    } else {
+        format!("{}{}{}", sign, symbol, digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityId, EntityType, FieldId};
+    use chrono::TimeZone;
+
+    fn symbols() -> ValueFormat {
+        ValueFormat {
+            currency: CurrencyFormat::Symbol,
+            date_format: None,
+        }
+    }
+
+    fn money(amount: &str, currency: Currency) -> FieldValue {
+        FieldValue::Currency {
+            amount: amount.parse().unwrap(),
+            currency,
+        }
+    }
+
+    #[test]
+    fn test_currency_uses_minor_unit_decimals() {
+        assert_eq!(symbols().value(&money("100.5", Currency::USD)), "$100.50");
+        assert_eq!(symbols().value(&money("-3", Currency::EUR)), "-€3.00");
+        assert_eq!(symbols().value(&money("1500.4", Currency::JPY)), "¥1500");
+    }
+
+    #[test]
+    fn test_default_format_is_raw() {
+        let value = money("100.5", Currency::USD);
+        assert_eq!(ValueFormat::default().value(&value), value.to_string());
+
+        let entity = Entity::new(EntityId::new("invoice.one"), EntityType::new("invoice"))
+            .with_field(FieldId::new("amount_due"), value)
+            .with_field(FieldId::new("paid"), false);
+        assert_eq!(ValueFormat::default().entity(&entity), entity.to_string());
+
+        let grouped = AggregationResult::Grouped {
+            field: "status".to_string(),
+            aggregate: "sum".to_string(),
+            groups: vec![
+                (
+                    Some(FieldValue::Enum("open".to_string())),
+                    AggregationResult::Sum(AggregateValue::Currency {
+                        amount: Decimal::new(1005, 1),
+                        currency: Currency::USD,
+                    }),
+                ),
+                (None, AggregationResult::Count(2)),
+            ],
+        };
+        assert_eq!(
+            ValueFormat::default().aggregation(&grouped),
+            grouped.to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_entity_and_aggregation() {
+        let entity = Entity::new(EntityId::new("invoice.one"), EntityType::new("invoice"))
+            .with_field(FieldId::new("amount_due"), money("100.5", Currency::USD));
+        assert_eq!(
+            symbols().entity(&entity),
+            "invoice.one\n\nAmount due: $100.50\n"
+        );
+
+        let sum = AggregationResult::Sum(AggregateValue::Currency {
+            amount: Decimal::new(25, 1),
+            currency: Currency::GBP,
+        });
+        assert_eq!(symbols().aggregation(&sum), "£2.50");
+    }
+
+    #[test]
+    fn test_date_format() {
+        let value = FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2025, 9, 30, 14, 5, 0)
+            .unwrap();
+        let format = ValueFormat {
+            currency: CurrencyFormat::Code,
+            date_format: Some("%d.%m.%Y %H:%M".to_string()),
+        };
+
+        assert_eq!(
+            format.value(&FieldValue::List(vec![FieldValue::DateTime(value)])),
+            "[30.09.2025 14:05]"
+        );
+        // An invalid format falls back to the raw form rather than failing
+        let invalid = ValueFormat {
+            date_format: Some("%Q".to_string()),
+            ..format
+        };
+        assert_eq!(invalid.datetime(&value), value.to_string());
+        assert!(!ValueFormat::is_valid_date_format("%Q"));
+        assert!(ValueFormat::is_valid_date_format("%Y-%m-%d"));
+    }
+}
@@ -110,6 +110,63 @@ impl EntityGraph {
         }
     }
 
+    /// Gets the relationships of an entity, each with the entity at the other end.
+    ///
+    /// Each relationship comes with its direction: outgoing when this entity holds
+    /// the referencing field, incoming when the other entity does. Relationships
+    /// are ordered by the other entity's ID, then by field.
+    pub fn get_relationships(
+        &self,
+        id: &EntityId,
+        direction: Option<Direction>,
+    ) -> Option<Vec<(&Entity, &Relationship, Direction)>> {
+        let node_index = *self.entity_map.get(id)?;
+
+        let directions = match direction {
+            Some(direction) => vec![direction],
+            None => vec![Direction::Outgoing, Direction::Incoming],
+        };
+
+        let mut relationships = Vec::new();
+        for direction in directions {
+            relationships.extend(
+                self.graph
+                    .edges_directed(node_index, direction)
+                    .map(|edge| {
+                        let other = match direction {
+                            Direction::Outgoing => edge.target(),
+                            Direction::Incoming => edge.source(),
+                        };
+                        (&self.graph[other], edge.weight(), direction)
+                    }),
+            );
+        }
+
+        relationships.sort_by(|a, b| (&a.0.id, a.1.from_field()).cmp(&(&b.0.id, b.1.from_field())));
+        Some(relationships)
+    }
+
+    /// Gets the entities that neither reference nor are referenced by other entities.
+    ///
+    /// These are often forgotten drafts. An entity's references to itself don't
+    /// connect it to anything. Entities are ordered by ID.
+    pub fn orphans(&self) -> Vec<&Entity> {
+        let mut orphans: Vec<&Entity> = self
+            .graph
+            .node_indices()
+            .filter(|&node_index| {
+                self.graph
+                    .edges_directed(node_index, Direction::Outgoing)
+                    .chain(self.graph.edges_directed(node_index, Direction::Incoming))
+                    .all(|edge| edge.source() == edge.target())
+            })
+            .map(|node_index| &self.graph[node_index])
+            .collect();
+
+        orphans.sort_by_key(|entity| &entity.id);
+        orphans
+    }
+
     /// Searches for a field reference on a given entity by traversing the graph
     fn search_field_reference(
         &self,
@@ -661,4 +718,42 @@ mod tests {
         let non_existing = graph.get_related(&EntityId::new("non_existing"), None);
         assert!(non_existing.is_none());
     }
+
+    #[test]
+    fn test_orphans() {
+        let mut graph = EntityGraph::new();
+
+        let organization = Entity::new(EntityId::new("megacorp"), EntityType::new("organization"))
+            .with_field(FieldId::new("name"), "MegaCorp Inc.");
+        let employee = Entity::new(EntityId::new("john_doe"), EntityType::new("person"))
+            .with_field(
+                FieldId::new("employer"),
+                FieldValue::Reference(ReferenceValue::Entity(EntityId::new("megacorp"))),
+            );
+        let loner = Entity::new(EntityId::new("jane_smith"), EntityType::new("person"))
+            .with_field(FieldId::new("name"), "Jane Smith");
+        let self_referencing = Entity::new(EntityId::new("bob"), EntityType::new("person"))
+            .with_field(
+                FieldId::new("manager"),
+                FieldValue::Reference(ReferenceValue::Entity(EntityId::new("bob"))),
+            );
+        let broken = Entity::new(EntityId::new("alice"), EntityType::new("person")).with_field(
+            FieldId::new("employer"),
+            FieldValue::Reference(ReferenceValue::Entity(EntityId::new("missing"))),
+        );
+
+        graph
+            .add_entities(vec![
+                organization,
+                employee,
+                loner,
+                self_referencing,
+                broken,
+            ])
+            .unwrap();
+        graph.build();
+
+        let orphan_ids: Vec<&str> = graph.orphans().iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(orphan_ids, vec!["alice", "bob", "jane_smith"]);
+    }
 }
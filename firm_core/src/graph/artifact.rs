@@ -0,0 +1,250 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use iso_currency::Currency;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{EntityGraph, GraphError};
+use crate::{Entity, EntityId, EntityType, FieldId, FieldValue, ReferenceValue};
+
+/// The version of the binary graph layout, bumped when it changes.
+const BINCODE_VERSION: u32 = 1;
+
+/// The formats a built graph can be saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// The graph's JSON serialization, readable by other tools.
+    Json,
+    /// A compact binary encoding of the graph's entities, for caching.
+    Bincode,
+}
+
+/// The binary layout of a graph: its entities, from which relationships are rebuilt.
+#[derive(Serialize, Deserialize)]
+struct StoredGraph {
+    version: u32,
+    entities: Vec<StoredEntity>,
+}
+
+/// An entity with every field written out, since the binary encoding
+/// isn't self-describing and can't skip empty fields like JSON does.
+#[derive(Serialize, Deserialize)]
+struct StoredEntity {
+    id: EntityId,
+    entity_type: EntityType,
+    fields: Vec<(FieldId, StoredValue)>,
+    unset_fields: Vec<FieldId>,
+    created: Option<DateTime<FixedOffset>>,
+    modified: Option<DateTime<FixedOffset>>,
+    tags: Vec<String>,
+}
+
+/// A field value with its currency amount read back as a string, since
+/// decimals otherwise need a self-describing format to be read.
+#[derive(Serialize, Deserialize)]
+enum StoredValue {
+    Boolean(bool),
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Currency {
+        #[serde(with = "rust_decimal::serde::str")]
+        amount: Decimal,
+        currency: Currency,
+    },
+    Reference(ReferenceValue),
+    List(Vec<StoredValue>),
+    DateTime(DateTime<FixedOffset>),
+    Path(PathBuf),
+    Enum(String),
+}
+
+impl EntityGraph {
+    /// Serializes the graph in a format.
+    pub fn to_bytes(&self, format: GraphFormat) -> Result<Vec<u8>, GraphError> {
+        match format {
+            GraphFormat::Json => {
+                serde_json::to_vec(self).map_err(|e| GraphError::SerializationError(e.to_string()))
+            }
+            GraphFormat::Bincode => {
+                let stored = StoredGraph {
+                    version: BINCODE_VERSION,
+                    entities: self
+                        .graph
+                        .raw_nodes()
+                        .iter()
+                        .map(|node| StoredEntity::from(node.weight.clone()))
+                        .collect(),
+                };
+                bincode::serde::encode_to_vec(&stored, bincode::config::standard())
+                    .map_err(|e| GraphError::SerializationError(e.to_string()))
+            }
+        }
+    }
+
+    /// Deserializes a graph saved in a format.
+    ///
+    /// Graphs saved as bincode have their relationships rebuilt.
+    pub fn from_bytes(bytes: &[u8], format: GraphFormat) -> Result<Self, GraphError> {
+        match format {
+            GraphFormat::Json => serde_json::from_slice(bytes)
+                .map_err(|e| GraphError::SerializationError(e.to_string())),
+            GraphFormat::Bincode => {
+                let (stored, _): (StoredGraph, usize) =
+                    bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+                        .map_err(|e| GraphError::SerializationError(e.to_string()))?;
+                if stored.version != BINCODE_VERSION {
+                    return Err(GraphError::SerializationError(format!(
+                        "unsupported graph version {}",
+                        stored.version
+                    )));
+                }
+
+                let mut graph = EntityGraph::new();
+                graph.add_entities(stored.entities.into_iter().map(Entity::from).collect())?;
+                graph.build();
+                Ok(graph)
+            }
+        }
+    }
+}
+
+impl From<Entity> for StoredEntity {
+    fn from(entity: Entity) -> Self {
+        Self {
+            id: entity.id,
+            entity_type: entity.entity_type,
+            fields: entity
+                .fields
+                .into_iter()
+                .map(|(field_id, value)| (field_id, value.into()))
+                .collect(),
+            unset_fields: entity.unset_fields,
+            created: entity.created,
+            modified: entity.modified,
+            tags: entity.tags,
+        }
+    }
+}
+
+impl From<StoredEntity> for Entity {
+    fn from(stored: StoredEntity) -> Self {
+        Self {
+            id: stored.id,
+            entity_type: stored.entity_type,
+            fields: stored
+                .fields
+                .into_iter()
+                .map(|(field_id, value)| (field_id, value.into()))
+                .collect(),
+            unset_fields: stored.unset_fields,
+            created: stored.created,
+            modified: stored.modified,
+            tags: stored.tags,
+        }
+    }
+}
+
+impl From<FieldValue> for StoredValue {
+    fn from(value: FieldValue) -> Self {
+        match value {
+            FieldValue::Boolean(value) => StoredValue::Boolean(value),
+            FieldValue::String(value) => StoredValue::String(value),
+            FieldValue::Integer(value) => StoredValue::Integer(value),
+            FieldValue::Float(value) => StoredValue::Float(value),
+            FieldValue::Currency { amount, currency } => StoredValue::Currency { amount, currency },
+            FieldValue::Reference(value) => StoredValue::Reference(value),
+            FieldValue::List(items) => {
+                StoredValue::List(items.into_iter().map(StoredValue::from).collect())
+            }
+            FieldValue::DateTime(value) => StoredValue::DateTime(value),
+            FieldValue::Path(value) => StoredValue::Path(value),
+            FieldValue::Enum(value) => StoredValue::Enum(value),
+        }
+    }
+}
+
+impl From<StoredValue> for FieldValue {
+    fn from(value: StoredValue) -> Self {
+        match value {
+            StoredValue::Boolean(value) => FieldValue::Boolean(value),
+            StoredValue::String(value) => FieldValue::String(value),
+            StoredValue::Integer(value) => FieldValue::Integer(value),
+            StoredValue::Float(value) => FieldValue::Float(value),
+            StoredValue::Currency { amount, currency } => FieldValue::Currency { amount, currency },
+            StoredValue::Reference(value) => FieldValue::Reference(value),
+            StoredValue::List(items) => {
+                FieldValue::List(items.into_iter().map(FieldValue::from).collect())
+            }
+            StoredValue::DateTime(value) => FieldValue::DateTime(value),
+            StoredValue::Path(value) => FieldValue::Path(value),
+            StoredValue::Enum(value) => FieldValue::Enum(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_graph() -> EntityGraph {
+        let manager = Entity::new(EntityId::new("person.jane"), EntityType::new("person"))
+            .with_field(FieldId::new("name"), "Jane")
+            .with_field(
+                FieldId::new("salary"),
+                FieldValue::Currency {
+                    amount: Decimal::new(150_000, 0),
+                    currency: Currency::from_code("EUR").unwrap(),
+                },
+            );
+        let mut report = Entity::new(EntityId::new("person.john"), EntityType::new("person"))
+            .with_field(
+                FieldId::new("manager"),
+                FieldValue::Reference(ReferenceValue::Entity(EntityId::new("person.jane"))),
+            )
+            .with_tags(["new"]);
+        report.created = DateTime::parse_from_rfc3339("2025-01-15T17:00:00+03:00").ok();
+
+        let mut graph = EntityGraph::new();
+        graph.add_entities(vec![manager, report]).unwrap();
+        graph.build();
+        graph
+    }
+
+    #[test]
+    fn test_graph_round_trips_in_every_format() {
+        let graph = create_graph();
+
+        for format in [GraphFormat::Json, GraphFormat::Bincode] {
+            let bytes = graph.to_bytes(format).unwrap();
+            let loaded = EntityGraph::from_bytes(&bytes, format).unwrap();
+
+            let john = EntityId::new("person.john");
+            assert_eq!(
+                loaded.get_entity(&john),
+                graph.get_entity(&john),
+                "{:?}",
+                format
+            );
+            assert_eq!(
+                loaded.get_entity(&EntityId::new("person.jane")),
+                graph.get_entity(&EntityId::new("person.jane"))
+            );
+            assert_eq!(
+                loaded.get_related(&john, None).map(|related| related.len()),
+                Some(1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_bincode_rejects_other_data() {
+        let json = create_graph().to_bytes(GraphFormat::Json).unwrap();
+
+        assert!(matches!(
+            EntityGraph::from_bytes(&json, GraphFormat::Bincode),
+            Err(GraphError::SerializationError(_))
+        ));
+    }
+}
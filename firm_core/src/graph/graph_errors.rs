@@ -11,4 +11,6 @@ pub enum GraphError {
     NotAFieldReference,
     NotAnEntityReference,
     GraphNotBuilt,
+    /// The graph couldn't be saved or loaded, with the reason.
+    SerializationError(String),
 }
@@ -7,9 +7,11 @@ use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 mod access;
+mod artifact;
 mod graph_errors;
 mod query;
 
+pub use artifact::GraphFormat;
 pub use graph_errors::GraphError;
 pub use petgraph::Direction;
 pub use query::*;
@@ -28,6 +30,16 @@ pub enum Relationship {
     },
 }
 
+impl Relationship {
+    /// Gets the field on the referencing entity that holds the reference.
+    pub fn from_field(&self) -> &FieldId {
+        match self {
+            Relationship::EntityReference { from_field }
+            | Relationship::FieldReference { from_field, .. } => from_field,
+        }
+    }
+}
+
 /// The entity graph tracks all Firm entities and their relationships.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityGraph {
@@ -74,11 +86,13 @@ impl EntityGraph {
             return Err(GraphError::EntityAlreadyExists(entity.id));
         }
 
-        let node_index = self.graph.add_node(entity.clone());
-        self.entity_map.insert(entity.id.clone(), node_index);
+        let id = entity.id.clone();
+        let entity_type = entity.entity_type.clone();
+        let node_index = self.graph.add_node(entity);
+        self.entity_map.insert(id, node_index);
 
         self.entity_type_map
-            .entry(entity.entity_type)
+            .entry(entity_type)
             .or_default()
             .push(node_index);
 
@@ -94,6 +108,14 @@ impl EntityGraph {
         Ok(())
     }
 
+    /// Takes the entities out of the graph, in the order they were added.
+    ///
+    /// Lets a new graph reuse the entities that haven't changed instead of copying them.
+    pub fn into_entities(self) -> Vec<Entity> {
+        let (nodes, _) = self.graph.into_nodes_edges();
+        nodes.into_iter().map(|node| node.weight).collect()
+    }
+
     /// Builds relationships for all entities in the graph.
     ///
     /// Note: We always clear the edges and build from scratch.
@@ -279,6 +301,18 @@ mod tests {
         assert_basic_graph_structure(&graph);
     }
 
+    #[test]
+    fn test_into_entities_keeps_added_order() {
+        let (mut graph, organization, person) = setup_basic_graph();
+
+        graph
+            .add_entities(vec![person.clone(), organization.clone()])
+            .unwrap();
+        graph.build();
+
+        assert_eq!(graph.into_entities(), vec![person, organization]);
+    }
+
     #[test]
     fn test_add_duplicate_entity_returns_error() {
         let mut graph = EntityGraph::new();
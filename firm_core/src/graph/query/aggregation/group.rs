@@ -0,0 +1,235 @@
+//! Grouped aggregation: run an aggregation once per distinct field value
+
+use std::cmp::Ordering;
+
+use super::super::QueryError;
+use super::super::filter::FieldRef;
+use super::super::order::compare_field_values;
+use super::super::types::{AggregateValue, Aggregation, AggregationResult, SortDirection};
+use crate::{Entity, FieldValue};
+
+pub fn execute(
+    field: &FieldRef,
+    aggregation: &Aggregation,
+    aggregate_order: Option<&SortDirection>,
+    entities: &[&Entity],
+) -> Result<AggregationResult, QueryError> {
+    if let Aggregation::Select(_) = aggregation {
+        return Err(QueryError::InvalidAggregation {
+            message: "Cannot select fields in a grouped query. Use count, sum, average or median."
+                .to_string(),
+        });
+    }
+
+    // Collect entities per distinct value, keeping the first-seen order within each group
+    let mut members: Vec<(Option<FieldValue>, Vec<&Entity>)> = Vec::new();
+    for entity in entities {
+        let key = group_key(entity, field);
+        match members.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, group)) => group.push(entity),
+            None => members.push((key, vec![entity])),
+        }
+    }
+
+    // Groups are ordered by their value, with entities missing the field last
+    members.sort_by(|(a, _), (b, _)| match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_field_values(a, b),
+    });
+
+    let mut groups = Vec::with_capacity(members.len());
+    for (key, group) in members {
+        groups.push((key, aggregation.execute(&group)?));
+    }
+
+    // Sorting is stable, so groups with equal aggregates stay in value order
+    if let Some(direction) = aggregate_order {
+        groups.sort_by(|(_, a), (_, b)| {
+            let ordering = aggregate_as_f64(a).total_cmp(&aggregate_as_f64(b));
+            match direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+    }
+
+    Ok(AggregationResult::Grouped {
+        field: field.to_string(),
+        aggregate: aggregation.name().to_string(),
+        groups,
+    })
+}
+
+/// Get the value an entity is grouped by, if it has one.
+fn group_key(entity: &Entity, field: &FieldRef) -> Option<FieldValue> {
+    match field {
+        FieldRef::Metadata(metadata) => metadata.value(entity),
+        FieldRef::Regular(field_id) => entity.get_field(field_id).cloned(),
+    }
+}
+
+/// Get a numeric aggregate as a float for ordering groups.
+fn aggregate_as_f64(result: &AggregationResult) -> f64 {
+    match result {
+        AggregationResult::Count(n) => *n as f64,
+        AggregationResult::Sum(AggregateValue::Integer(n)) => *n as f64,
+        AggregationResult::Sum(AggregateValue::Float(n)) => *n,
+        AggregationResult::Sum(AggregateValue::Currency { amount, .. }) => {
+            use rust_decimal::prelude::ToPrimitive;
+            amount.to_f64().unwrap_or(0.0)
+        }
+        AggregationResult::Average(n) | AggregationResult::Median(n) => *n,
+        AggregationResult::Select { .. } | AggregationResult::Grouped { .. } => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::filter::MetadataField;
+    use super::*;
+    use crate::{EntityId, EntityType, FieldId};
+
+    fn make_entities() -> Vec<Entity> {
+        vec![
+            Entity::new(EntityId::new("t1"), EntityType::new("task"))
+                .with_field(FieldId::new("status"), FieldValue::Enum("open".to_string()))
+                .with_field(FieldId::new("hours"), FieldValue::Integer(2)),
+            Entity::new(EntityId::new("t2"), EntityType::new("task"))
+                .with_field(FieldId::new("status"), FieldValue::Enum("done".to_string()))
+                .with_field(FieldId::new("hours"), FieldValue::Integer(5)),
+            Entity::new(EntityId::new("t3"), EntityType::new("task"))
+                .with_field(FieldId::new("status"), FieldValue::Enum("open".to_string()))
+                .with_field(FieldId::new("hours"), FieldValue::Integer(1)),
+            Entity::new(EntityId::new("t4"), EntityType::new("task"))
+                .with_field(FieldId::new("hours"), FieldValue::Integer(3)),
+        ]
+    }
+
+    fn status() -> FieldRef {
+        FieldRef::Regular(FieldId::new("status"))
+    }
+
+    fn enum_key(value: &str) -> Option<FieldValue> {
+        Some(FieldValue::Enum(value.to_string()))
+    }
+
+    #[test]
+    fn test_group_count_orders_by_value() {
+        let entities = make_entities();
+        let refs: Vec<&Entity> = entities.iter().collect();
+        let result = execute(&status(), &Aggregation::Count(None), None, &refs).unwrap();
+
+        assert_eq!(
+            result,
+            AggregationResult::Grouped {
+                field: "status".to_string(),
+                aggregate: "count".to_string(),
+                groups: vec![
+                    (enum_key("done"), AggregationResult::Count(1)),
+                    (enum_key("open"), AggregationResult::Count(2)),
+                    (None, AggregationResult::Count(1)),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_group_count_ordered_by_aggregate_descending() {
+        let entities = make_entities();
+        let refs: Vec<&Entity> = entities.iter().collect();
+        let result = execute(
+            &status(),
+            &Aggregation::Count(None),
+            Some(&SortDirection::Descending),
+            &refs,
+        )
+        .unwrap();
+
+        let AggregationResult::Grouped { groups, .. } = result else {
+            panic!("Expected grouped result");
+        };
+        let keys: Vec<Option<FieldValue>> = groups.into_iter().map(|(key, _)| key).collect();
+        // Ties keep value order, with the missing value last
+        assert_eq!(keys, vec![enum_key("open"), enum_key("done"), None]);
+    }
+
+    #[test]
+    fn test_group_sum_ordered_by_aggregate_ascending() {
+        let entities = make_entities();
+        let refs: Vec<&Entity> = entities.iter().collect();
+        let hours = FieldRef::Regular(FieldId::new("hours"));
+        let result = execute(
+            &status(),
+            &Aggregation::Sum(hours),
+            Some(&SortDirection::Ascending),
+            &refs,
+        )
+        .unwrap();
+
+        let AggregationResult::Grouped { groups, .. } = result else {
+            panic!("Expected grouped result");
+        };
+        assert_eq!(
+            groups,
+            vec![
+                (enum_key("open"), AggregationResult::Sum(AggregateValue::Integer(3))),
+                (None, AggregationResult::Sum(AggregateValue::Integer(3))),
+                (enum_key("done"), AggregationResult::Sum(AggregateValue::Integer(5))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_metadata_type() {
+        let entities = make_entities();
+        let refs: Vec<&Entity> = entities.iter().collect();
+        let result = execute(
+            &FieldRef::Metadata(MetadataField::Type),
+            &Aggregation::Count(None),
+            None,
+            &refs,
+        )
+        .unwrap();
+
+        let AggregationResult::Grouped { field, groups, .. } = result else {
+            panic!("Expected grouped result");
+        };
+        assert_eq!(field, "@type");
+        assert_eq!(
+            groups,
+            vec![(
+                Some(FieldValue::String("task".to_string())),
+                AggregationResult::Count(4)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_group_select_is_an_error() {
+        let entities = make_entities();
+        let refs: Vec<&Entity> = entities.iter().collect();
+        let result = execute(&status(), &Aggregation::Select(vec![status()]), None, &refs);
+
+        assert!(matches!(
+            result,
+            Err(QueryError::InvalidAggregation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_group_empty_set() {
+        let refs: Vec<&Entity> = vec![];
+        let result = execute(&status(), &Aggregation::Count(None), None, &refs).unwrap();
+
+        assert_eq!(
+            result,
+            AggregationResult::Grouped {
+                field: "status".to_string(),
+                aggregate: "count".to_string(),
+                groups: vec![],
+            }
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 mod average;
 mod count;
+pub(super) mod group;
 mod median;
 mod select;
 mod sum;
@@ -22,6 +23,17 @@ impl Aggregation {
             Aggregation::Median(field) => median::execute(field, entities),
         }
     }
+
+    /// The keyword for this aggregation in the query language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregation::Select(_) => "select",
+            Aggregation::Count(_) => "count",
+            Aggregation::Sum(_) => "sum",
+            Aggregation::Average(_) => "average",
+            Aggregation::Median(_) => "median",
+        }
+    }
 }
 
 /// Require that the field is a regular field (not metadata) for numeric aggregations.
@@ -1,6 +1,6 @@
 //! Select aggregation: extract specific field values from entities
 
-use super::super::filter::{FieldRef, MetadataField};
+use super::super::filter::FieldRef;
 use super::super::types::AggregationResult;
 use super::super::QueryError;
 use crate::{Entity, FieldValue};
@@ -9,14 +9,7 @@ pub fn execute(
     fields: &[FieldRef],
     entities: &[&Entity],
 ) -> Result<AggregationResult, QueryError> {
-    let columns: Vec<String> = fields
-        .iter()
-        .map(|f| match f {
-            FieldRef::Metadata(MetadataField::Id) => "@id".to_string(),
-            FieldRef::Metadata(MetadataField::Type) => "@type".to_string(),
-            FieldRef::Regular(field_id) => field_id.as_str().to_string(),
-        })
-        .collect();
+    let columns: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
 
     let rows: Vec<Vec<Option<FieldValue>>> = entities
         .iter()
@@ -24,12 +17,7 @@ pub fn execute(
             fields
                 .iter()
                 .map(|field| match field {
-                    FieldRef::Metadata(MetadataField::Id) => {
-                        Some(FieldValue::String(entity.id.to_string()))
-                    }
-                    FieldRef::Metadata(MetadataField::Type) => {
-                        Some(FieldValue::String(entity.entity_type.to_string()))
-                    }
+                    FieldRef::Metadata(metadata) => metadata.value(entity),
                     FieldRef::Regular(field_id) => entity.get_field(field_id).cloned(),
                 })
                 .collect()
@@ -41,6 +29,7 @@ pub fn execute(
 
 #[cfg(test)]
 mod tests {
+    use super::super::super::filter::MetadataField;
     use super::*;
     use crate::{Entity, EntityId, EntityType, FieldId, FieldValue};
 
@@ -8,6 +8,10 @@ use chrono::{DateTime, FixedOffset};
 const SUPPORTED_OPS: [&str; 6] = ["==", "!=", ">", "<", ">=", "<="];
 
 /// Compare a datetime field value against a filter
+///
+/// Full datetimes compare as instants. Date-only filters like `2025-09-30`
+/// compare the calendar date of the value in its own offset, so queries with
+/// a timezone convert values to it first (see `Query::with_timezone`).
 pub fn compare_datetime(
     field_value: &FieldValue,
     operator: &FilterOperator,
@@ -12,6 +12,10 @@ mod types;
 // Re-export types
 pub use types::*;
 
+use std::fmt;
+
+use chrono::FixedOffset;
+
 use super::QueryError;
 use crate::{Entity, FieldId, FieldValue};
 
@@ -35,13 +39,31 @@ impl FilterCondition {
 
     /// Check if an entity matches this condition
     pub fn matches(&self, entity: &Entity) -> Result<bool, QueryError> {
+        self.matches_in(entity, None)
+    }
+
+    /// Check if an entity matches this condition, with datetimes converted to a timezone
+    ///
+    /// Only date-only comparisons change: they check the calendar date in the
+    /// timezone rather than in each value's own offset.
+    pub fn matches_in(
+        &self,
+        entity: &Entity,
+        timezone: Option<&FixedOffset>,
+    ) -> Result<bool, QueryError> {
         match &self.field {
-            FieldRef::Metadata(metadata) => self.matches_metadata(entity, metadata),
-            FieldRef::Regular(field_id) => self.matches_field(entity, field_id),
+            FieldRef::Metadata(metadata) => self.matches_metadata(entity, metadata, timezone),
+            FieldRef::Regular(field_id) => self.matches_field(entity, field_id, timezone),
         }
     }
 }
 
+impl fmt::Display for FilterCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.operator, self.value)
+    }
+}
+
 /// A compound filter condition combining multiple conditions with a logical operator
 #[derive(Debug, Clone, PartialEq)]
 pub struct CompoundFilterCondition {
@@ -68,10 +90,19 @@ impl CompoundFilterCondition {
 
     /// Check if an entity matches this compound condition
     pub fn matches(&self, entity: &Entity) -> Result<bool, QueryError> {
+        self.matches_in(entity, None)
+    }
+
+    /// Check if an entity matches this compound condition, with datetimes converted to a timezone
+    pub fn matches_in(
+        &self,
+        entity: &Entity,
+        timezone: Option<&FixedOffset>,
+    ) -> Result<bool, QueryError> {
         let results: Result<Vec<bool>, QueryError> = self
             .conditions
             .iter()
-            .map(|c| c.matches(entity))
+            .map(|c| c.matches_in(entity, timezone))
             .collect();
 
         Ok(match self.combinator {
@@ -81,28 +112,75 @@ impl CompoundFilterCondition {
     }
 }
 
+impl fmt::Display for CompoundFilterCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = match self.combinator {
+            Combinator::And => " and ",
+            Combinator::Or => " or ",
+        };
+        let conditions: Vec<String> = self.conditions.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", conditions.join(separator))
+    }
+}
+
 impl FilterCondition {
 
     fn matches_metadata(
         &self,
         entity: &Entity,
         metadata: &MetadataField,
+        timezone: Option<&FixedOffset>,
     ) -> Result<bool, QueryError> {
         // Create a synthetic FieldValue for metadata comparisons
-        let field_value = match metadata {
-            MetadataField::Type => FieldValue::String(entity.entity_type.to_string()),
-            MetadataField::Id => FieldValue::String(entity.id.to_string()),
+        let field_value = match metadata.value(entity) {
+            Some(value) => value,
+            None => return Ok(false), // Timestamp isn't known, condition fails
+        };
+        let field_value = match timezone {
+            Some(timezone) => field_value.in_timezone(timezone),
+            None => field_value,
         };
-        string::compare_string(&field_value, &self.operator, &self.value)
+
+        match metadata {
+            MetadataField::Type | MetadataField::Id => {
+                string::compare_string(&field_value, &self.operator, &self.value)
+            }
+            MetadataField::Created | MetadataField::Modified => {
+                datetime::compare_datetime(&field_value, &self.operator, &self.value)
+            }
+            MetadataField::Tags => match (&self.operator, &self.value) {
+                // A tag matches as a whole, so "urgent" doesn't match "not_urgent"
+                (FilterOperator::Contains, FilterValue::String(tag)) => Ok(entity
+                    .tags
+                    .iter()
+                    .any(|entity_tag| entity_tag.eq_ignore_ascii_case(tag))),
+                _ => list::compare_list(&field_value, &self.operator, &self.value),
+            },
+        }
     }
 
-    fn matches_field(&self, entity: &Entity, field_id: &FieldId) -> Result<bool, QueryError> {
+    fn matches_field(
+        &self,
+        entity: &Entity,
+        field_id: &FieldId,
+        timezone: Option<&FixedOffset>,
+    ) -> Result<bool, QueryError> {
         // Get the field value from the entity
         let field_value = match entity.get_field(field_id) {
             Some(value) => value,
             None => return Ok(false), // Field doesn't exist, condition fails
         };
 
+        // Date-only comparisons use the calendar date in the query's timezone
+        let converted;
+        let field_value = match timezone {
+            Some(timezone) => {
+                converted = field_value.in_timezone(timezone);
+                &converted
+            }
+            None => field_value,
+        };
+
         // Compare based on field value type - now we pass the FieldValue directly
         match field_value {
             FieldValue::String(_) | FieldValue::Enum(_) | FieldValue::Path(_) => {
@@ -265,4 +343,45 @@ mod tests {
 
         assert!(condition.matches(&entity).unwrap());
     }
+
+    #[test]
+    fn test_metadata_timestamp_condition() {
+        let modified = "2025-03-01T12:00:00+00:00".parse().unwrap();
+        let entity = make_test_entity("Alice", 30, true).with_timestamps(None, Some(modified));
+        let condition = FilterCondition::new(
+            FieldRef::Metadata(MetadataField::Modified),
+            FilterOperator::GreaterThan,
+            FilterValue::DateTime("2025-01-01".to_string()),
+        );
+        assert!(condition.matches(&entity).unwrap());
+
+        // Entities without a known timestamp don't match
+        let condition = FilterCondition::new(
+            FieldRef::Metadata(MetadataField::Created),
+            FilterOperator::LessThan,
+            FilterValue::DateTime("2025-01-01".to_string()),
+        );
+        assert!(!condition.matches(&entity).unwrap());
+    }
+
+    #[test]
+    fn test_metadata_tags_condition() {
+        let entity = make_test_entity("Alice", 30, true).with_tags(["urgent", "Q3"]);
+        let tags_contain = |tag: &str| {
+            FilterCondition::new(
+                FieldRef::Metadata(MetadataField::Tags),
+                FilterOperator::Contains,
+                FilterValue::String(tag.to_string()),
+            )
+        };
+
+        assert!(tags_contain("urgent").matches(&entity).unwrap());
+        assert!(tags_contain("q3").matches(&entity).unwrap());
+        assert!(!tags_contain("urg").matches(&entity).unwrap());
+        assert!(
+            !tags_contain("urgent")
+                .matches(&make_test_entity("Bob", 40, false))
+                .unwrap()
+        );
+    }
 }
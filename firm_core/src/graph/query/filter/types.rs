@@ -1,6 +1,8 @@
 //! Filter type definitions
 
-use crate::FieldId;
+use std::fmt;
+
+use crate::{Entity, FieldId, FieldValue};
 
 /// Logical operator for combining multiple filter conditions
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -13,17 +15,58 @@ pub enum Combinator {
 /// Reference to a field (either metadata or regular field)
 #[derive(Debug, Clone, PartialEq)]
 pub enum FieldRef {
-    /// Metadata field like @type or @id
+    /// Metadata field like @type, @id or @modified
     Metadata(MetadataField),
     /// Regular entity field
     Regular(FieldId),
 }
 
+impl fmt::Display for FieldRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldRef::Metadata(MetadataField::Type) => write!(f, "@type"),
+            FieldRef::Metadata(MetadataField::Id) => write!(f, "@id"),
+            FieldRef::Metadata(MetadataField::Created) => write!(f, "@created"),
+            FieldRef::Metadata(MetadataField::Modified) => write!(f, "@modified"),
+            FieldRef::Metadata(MetadataField::Tags) => write!(f, "@tags"),
+            FieldRef::Regular(field_id) => write!(f, "{}", field_id),
+        }
+    }
+}
+
 /// Metadata fields that can be queried
 #[derive(Debug, Clone, PartialEq)]
 pub enum MetadataField {
     Type,
     Id,
+    /// When the entity was first written
+    Created,
+    /// When the entity was last changed
+    Modified,
+    /// The entity's free-form tags, as a list of strings
+    Tags,
+}
+
+impl MetadataField {
+    /// Gets an entity's metadata value, if it has one.
+    ///
+    /// Timestamps are missing when they couldn't be read from the entity's source.
+    pub fn value(&self, entity: &Entity) -> Option<FieldValue> {
+        match self {
+            MetadataField::Type => Some(FieldValue::String(entity.entity_type.to_string())),
+            MetadataField::Id => Some(FieldValue::String(entity.id.to_string())),
+            MetadataField::Created => entity.created.map(FieldValue::DateTime),
+            MetadataField::Modified => entity.modified.map(FieldValue::DateTime),
+            MetadataField::Tags => Some(FieldValue::List(
+                entity
+                    .tags
+                    .iter()
+                    .cloned()
+                    .map(FieldValue::String)
+                    .collect(),
+            )),
+        }
+    }
 }
 
 /// Comparison operators for filtering
@@ -41,6 +84,23 @@ pub enum FilterOperator {
     In,
 }
 
+impl fmt::Display for FilterOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterOperator::Equal => write!(f, "=="),
+            FilterOperator::NotEqual => write!(f, "!="),
+            FilterOperator::GreaterThan => write!(f, ">"),
+            FilterOperator::LessThan => write!(f, "<"),
+            FilterOperator::GreaterOrEqual => write!(f, ">="),
+            FilterOperator::LessOrEqual => write!(f, "<="),
+            FilterOperator::Contains => write!(f, "contains"),
+            FilterOperator::StartsWith => write!(f, "startswith"),
+            FilterOperator::EndsWith => write!(f, "endswith"),
+            FilterOperator::In => write!(f, "in"),
+        }
+    }
+}
+
 /// Values used in filter conditions
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterValue {
@@ -56,6 +116,26 @@ pub enum FilterValue {
     List(Vec<FilterValue>),
 }
 
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterValue::String(s) => write!(f, "{:?}", s),
+            FilterValue::Integer(n) => write!(f, "{}", n),
+            FilterValue::Float(n) => write!(f, "{}", n),
+            FilterValue::Boolean(b) => write!(f, "{}", b),
+            FilterValue::Currency { amount, code } => write!(f, "{} {}", amount, code),
+            FilterValue::DateTime(dt) => write!(f, "{}", dt),
+            FilterValue::Reference(r) => write!(f, "{}", r),
+            FilterValue::Path(p) => write!(f, "path{:?}", p),
+            FilterValue::Enum(e) => write!(f, "enum{:?}", e),
+            FilterValue::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
 impl FilterValue {
     /// Returns the type name of this filter value for error messages
     pub fn type_name(&self) -> &'static str {
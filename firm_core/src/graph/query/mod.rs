@@ -4,6 +4,7 @@
 //! - Filter conditions for matching entities
 //! - Query operations (where, related, order, limit)
 //! - Query execution against the entity graph
+//! - Optional schema validation of field references
 
 mod aggregation;
 mod filter;
@@ -11,6 +12,7 @@ mod order;
 mod query_errors;
 mod related;
 mod types;
+mod validation;
 
 // Re-export all public types
 pub use filter::*;
@@ -41,6 +41,15 @@ pub fn compare_entities_by_field(
                         .to_lowercase()
                         .cmp(&b.id.as_str().to_lowercase())
                 }
+                MetadataField::Created | MetadataField::Modified | MetadataField::Tags => {
+                    // Unknown timestamps sort to end, like missing fields
+                    match (metadata.value(a), metadata.value(b)) {
+                        (None, None) => Ordering::Equal,
+                        (None, Some(_)) => Ordering::Greater,
+                        (Some(_), None) => Ordering::Less,
+                        (Some(a_val), Some(b_val)) => compare_field_values(&a_val, &b_val),
+                    }
+                }
             }
         }
     };
@@ -53,7 +62,7 @@ pub fn compare_entities_by_field(
 }
 
 /// Compare two field values for sorting
-fn compare_field_values(a: &FieldValue, b: &FieldValue) -> std::cmp::Ordering {
+pub(super) fn compare_field_values(a: &FieldValue, b: &FieldValue) -> std::cmp::Ordering {
     use FieldValue::*;
     use std::cmp::Ordering;
 
@@ -820,4 +829,28 @@ mod tests {
         );
         assert_eq!(result, std::cmp::Ordering::Greater); // Case-insensitive: zebra > apple
     }
+
+    // Metadata: @modified tests
+    #[test]
+    fn test_order_metadata_modified_missing_sorts_last() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let older = Entity::new(EntityId::new("older"), EntityType::new("test")).with_timestamps(
+            None,
+            Some(tz.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+        );
+        let newer = Entity::new(EntityId::new("newer"), EntityType::new("test")).with_timestamps(
+            None,
+            Some(tz.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+        );
+        let unknown = Entity::new(EntityId::new("unknown"), EntityType::new("test"));
+        let modified = FieldRef::Metadata(MetadataField::Modified);
+
+        let result =
+            compare_entities_by_field(&older, &newer, &modified, &SortDirection::Descending);
+        assert_eq!(result, std::cmp::Ordering::Greater); // newer comes first
+
+        let result =
+            compare_entities_by_field(&unknown, &older, &modified, &SortDirection::Ascending);
+        assert_eq!(result, std::cmp::Ordering::Greater);
+    }
 }
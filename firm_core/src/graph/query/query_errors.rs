@@ -29,6 +29,11 @@ pub enum QueryError {
     InvalidAggregation {
         message: String,
     },
+    /// Field is not declared in the schema for the queried entity type (strict mode)
+    UnknownField {
+        field: String,
+        entity_type: String,
+    },
 }
 
 impl fmt::Display for QueryError {
@@ -82,6 +87,17 @@ impl fmt::Display for QueryError {
             QueryError::InvalidAggregation { message } => {
                 write!(f, "Invalid aggregation: {}", message)
             }
+            QueryError::UnknownField { field, entity_type } => {
+                if entity_type == "*" {
+                    write!(f, "Field '{}' is not declared in any schema", field)
+                } else {
+                    write!(
+                        f,
+                        "Field '{}' is not declared in the schema for '{}'",
+                        field, entity_type
+                    )
+                }
+            }
         }
     }
 }
@@ -1,11 +1,44 @@
 //! Related entity traversal for queries
 
-use crate::graph::EntityGraph;
-use crate::{Entity, EntityId, EntityType};
+use super::EntitySelector;
+use crate::graph::{Direction, EntityGraph};
+use crate::{Entity, EntityId, FieldId};
+use serde::Serialize;
 use std::collections::HashSet;
 
 const MAX_DEGREES: usize = 5;
 
+/// Which end of an edge holds the referencing field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeDirection {
+    /// `from_entity` has a field referencing `entity`.
+    Outgoing,
+    /// `entity` has a field referencing `from_entity`.
+    Incoming,
+}
+
+/// An edge followed while traversing to a related entity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelatedEdge {
+    /// The entity the edge leads to.
+    pub entity: EntityId,
+    /// The field holding the reference.
+    pub via_field: FieldId,
+    /// The entity the edge was followed from.
+    pub from_entity: EntityId,
+    pub direction: EdgeDirection,
+    /// The number of hops from the starting entities to `entity`.
+    pub degree: usize,
+}
+
+/// Related entities, along with the edges that connect them.
+#[derive(Debug, Clone)]
+pub struct RelatedEntities<'a> {
+    pub entities: Vec<&'a Entity>,
+    pub edges: Vec<RelatedEdge>,
+}
+
 /// Get related entities by traversing the graph up to N degrees
 ///
 /// This function starts with a set of entities and traverses relationships
@@ -16,7 +49,7 @@ const MAX_DEGREES: usize = 5;
 /// * `graph` - The entity graph to traverse
 /// * `starting_entities` - The initial set of entities to start from
 /// * `degrees` - Number of relationship hops to traverse (max 3)
-/// * `entity_type_filter` - Optional filter to only return entities of a specific type
+/// * `entity_types` - Only return entities matching one of these selectors, or every entity when empty
 ///
 /// # Returns
 /// A deduplicated vector of all entities found within the specified degrees,
@@ -25,37 +58,79 @@ pub fn get_related_entities<'a>(
     graph: &'a EntityGraph,
     starting_entities: Vec<&'a Entity>,
     degrees: usize,
-    entity_type_filter: Option<&EntityType>,
+    entity_types: &[EntitySelector],
 ) -> Vec<&'a Entity> {
-    // Cap degrees at MAX_DEGREES
-    let degrees = degrees.min(MAX_DEGREES);
-
     if degrees < 1 {
         return starting_entities;
     }
 
+    get_related_with_edges(graph, starting_entities, degrees, None, entity_types).entities
+}
+
+/// Get related entities like `get_related_entities`, recording how they're connected
+///
+/// Each entity found is returned with every edge that reached it at the level it
+/// was first found, so all shortest connections to the starting entities can be
+/// explained. Edges between entities that were already found are left out.
+///
+/// # Arguments
+/// * `direction` - Only follow outgoing or incoming references, or both when None
+/// * `entity_types` - Only return entities matching one of these selectors, or every
+///   entity when empty. Edges through entities of other types are kept, since they
+///   explain the connection
+///
+/// # Returns
+/// The entities found, including the starting entities and ordered by ID, and
+/// the edges ordered by degree, then entity and field.
+pub fn get_related_with_edges<'a>(
+    graph: &'a EntityGraph,
+    starting_entities: Vec<&'a Entity>,
+    degrees: usize,
+    direction: Option<Direction>,
+    entity_types: &[EntitySelector],
+) -> RelatedEntities<'a> {
+    // Cap degrees at MAX_DEGREES
+    let degrees = degrees.min(MAX_DEGREES);
+
     // Track all entities we've seen (including starting entities)
     let mut all_entities: HashSet<&EntityId> = starting_entities.iter().map(|e| &e.id).collect();
 
     // Current level starts with the starting entities
-    let mut current_level: HashSet<&EntityId> = starting_entities.iter().map(|e| &e.id).collect();
+    let mut current_level: HashSet<&EntityId> = all_entities.clone();
+    let mut edges = Vec::new();
 
     // Traverse N degrees
-    for _ in 0..degrees {
+    for degree in 1..=degrees {
         let mut next_level = HashSet::new();
 
-        // For each entity in the current level, get its related entities
+        // For each entity in the current level, follow its edges to entities not seen before
         for entity_id in &current_level {
-            if let Some(related) = graph.get_related(entity_id, None) {
-                for entity in related {
-                    // Add to next level for further traversal
-                    next_level.insert(&entity.id);
-                    // Add to overall collection
-                    all_entities.insert(&entity.id);
+            let Some(relationships) = graph.get_relationships(entity_id, direction) else {
+                continue;
+            };
+
+            for (entity, relationship, edge_direction) in relationships {
+                if all_entities.contains(&entity.id) {
+                    continue;
                 }
+
+                next_level.insert(&entity.id);
+                edges.push(RelatedEdge {
+                    entity: entity.id.clone(),
+                    via_field: relationship.from_field().clone(),
+                    from_entity: (*entity_id).clone(),
+                    direction: match edge_direction {
+                        Direction::Outgoing => EdgeDirection::Outgoing,
+                        Direction::Incoming => EdgeDirection::Incoming,
+                    },
+                    degree,
+                });
             }
         }
 
+        // Only mark entities as seen after the level, so every edge reaching them is kept
+        all_entities.extend(next_level.iter().copied());
+
         // Move to the next level
         current_level = next_level;
 
@@ -66,17 +141,31 @@ pub fn get_related_entities<'a>(
     }
 
     // Convert entity IDs back to entity references
-    let mut result: Vec<&Entity> = all_entities
+    let mut entities: Vec<&Entity> = all_entities
         .iter()
         .filter_map(|id| graph.get_entity(id))
         .collect();
 
-    // Apply entity type filter if specified
-    if let Some(filter_type) = entity_type_filter {
-        result.retain(|e| &e.entity_type == filter_type);
+    // Apply the entity type filter if specified
+    if !entity_types.is_empty() {
+        entities.retain(|e| {
+            entity_types
+                .iter()
+                .any(|selector| selector.matches(&e.entity_type))
+        });
     }
 
-    result
+    entities.sort_by_key(|entity| &entity.id);
+    edges.sort_by(|a, b| {
+        (a.degree, &a.entity, &a.via_field, &a.from_entity).cmp(&(
+            b.degree,
+            &b.entity,
+            &b.via_field,
+            &b.from_entity,
+        ))
+    });
+
+    RelatedEntities { entities, edges }
 }
 
 #[cfg(test)]
@@ -167,7 +256,7 @@ mod tests {
         let graph = create_test_graph_linear();
         let person = graph.get_entity(&EntityId::new("person1")).unwrap();
 
-        let result = get_related_entities(&graph, vec![person], 0, None);
+        let result = get_related_entities(&graph, vec![person], 0, &[]);
 
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].id, EntityId::new("person1"));
@@ -178,7 +267,7 @@ mod tests {
         let graph = create_test_graph_linear();
         let person = graph.get_entity(&EntityId::new("person1")).unwrap();
 
-        let result = get_related_entities(&graph, vec![person], 1, None);
+        let result = get_related_entities(&graph, vec![person], 1, &[]);
 
         // Should include person1 and task1
         assert_eq!(result.len(), 2);
@@ -192,7 +281,7 @@ mod tests {
         let graph = create_test_graph_linear();
         let person = graph.get_entity(&EntityId::new("person1")).unwrap();
 
-        let result = get_related_entities(&graph, vec![person], 2, None);
+        let result = get_related_entities(&graph, vec![person], 2, &[]);
 
         // Should include person1, task1, and project1
         assert_eq!(result.len(), 3);
@@ -207,19 +296,46 @@ mod tests {
         let graph = create_test_graph_linear();
         let person = graph.get_entity(&EntityId::new("person1")).unwrap();
 
-        let result = get_related_entities(&graph, vec![person], 2, Some(&EntityType::new("task")));
+        let result = get_related_entities(
+            &graph,
+            vec![person],
+            2,
+            &[EntitySelector::Type(EntityType::new("task"))],
+        );
 
         // Should only include task1
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].id, EntityId::new("task1"));
     }
 
+    #[test]
+    fn test_related_with_several_entity_types() {
+        let graph = create_test_graph_linear();
+        let person = graph.get_entity(&EntityId::new("person1")).unwrap();
+
+        let result = get_related_entities(
+            &graph,
+            vec![person],
+            2,
+            &[
+                EntitySelector::Type(EntityType::new("task")),
+                EntitySelector::TypeWithSubtypes(EntityType::new("project")),
+            ],
+        );
+
+        let ids: Vec<&EntityId> = result.iter().map(|e| &e.id).collect();
+        assert_eq!(
+            ids,
+            vec![&EntityId::new("project1"), &EntityId::new("task1")]
+        );
+    }
+
     #[test]
     fn test_related_complex_graph() {
         let graph = create_test_graph_complex();
         let person1 = graph.get_entity(&EntityId::new("person1")).unwrap();
 
-        let result = get_related_entities(&graph, vec![person1], 2, None);
+        let result = get_related_entities(&graph, vec![person1], 2, &[]);
 
         // person1 -> task1, task2
         // task1 -> project1
@@ -240,7 +356,7 @@ mod tests {
         let person1 = graph.get_entity(&EntityId::new("person1")).unwrap();
         let person2 = graph.get_entity(&EntityId::new("person2")).unwrap();
 
-        let result = get_related_entities(&graph, vec![person1, person2], 1, None);
+        let result = get_related_entities(&graph, vec![person1, person2], 1, &[]);
 
         // person1 -> task1, task2
         // person2 -> task2
@@ -262,10 +378,84 @@ mod tests {
             &graph,
             vec![person],
             2,
-            Some(&EntityType::new("organization")),
+            &[EntitySelector::Type(EntityType::new("organization"))],
         );
 
         // No organizations in the graph
         assert_eq!(result.len(), 0);
     }
+
+    #[test]
+    fn test_related_with_edges_explains_connections() {
+        let graph = create_test_graph_complex();
+        let person1 = graph.get_entity(&EntityId::new("person1")).unwrap();
+
+        let result = get_related_with_edges(&graph, vec![person1], 2, None, &[]);
+
+        assert_eq!(result.entities.len(), 5);
+        assert_eq!(
+            result.edges,
+            vec![
+                RelatedEdge {
+                    entity: EntityId::new("task1"),
+                    via_field: FieldId::new("assignee"),
+                    from_entity: EntityId::new("person1"),
+                    direction: EdgeDirection::Incoming,
+                    degree: 1,
+                },
+                RelatedEdge {
+                    entity: EntityId::new("task2"),
+                    via_field: FieldId::new("assignee"),
+                    from_entity: EntityId::new("person1"),
+                    direction: EdgeDirection::Incoming,
+                    degree: 1,
+                },
+                RelatedEdge {
+                    entity: EntityId::new("person2"),
+                    via_field: FieldId::new("reviewer"),
+                    from_entity: EntityId::new("task2"),
+                    direction: EdgeDirection::Outgoing,
+                    degree: 2,
+                },
+                RelatedEdge {
+                    entity: EntityId::new("project1"),
+                    via_field: FieldId::new("task1"),
+                    from_entity: EntityId::new("task1"),
+                    direction: EdgeDirection::Incoming,
+                    degree: 2,
+                },
+                RelatedEdge {
+                    entity: EntityId::new("project1"),
+                    via_field: FieldId::new("task2"),
+                    from_entity: EntityId::new("task2"),
+                    direction: EdgeDirection::Incoming,
+                    degree: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_related_with_edges_follows_direction() {
+        let graph = create_test_graph_linear();
+        let task = graph.get_entity(&EntityId::new("task1")).unwrap();
+
+        let result = get_related_with_edges(&graph, vec![task], 2, Some(Direction::Outgoing), &[]);
+
+        assert_eq!(
+            result.edges,
+            vec![RelatedEdge {
+                entity: EntityId::new("person1"),
+                via_field: FieldId::new("assignee"),
+                from_entity: EntityId::new("task1"),
+                direction: EdgeDirection::Outgoing,
+                degree: 1,
+            }]
+        );
+        let ids: Vec<&EntityId> = result.entities.iter().map(|e| &e.id).collect();
+        assert_eq!(
+            ids,
+            vec![&EntityId::new("person1"), &EntityId::new("task1")]
+        );
+    }
 }
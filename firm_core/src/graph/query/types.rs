@@ -2,13 +2,16 @@
 
 use std::fmt;
 
+use chrono::FixedOffset;
+
 use iso_currency::Currency;
 use rust_decimal::Decimal;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
 use super::QueryError;
 use super::filter::{CompoundFilterCondition, FieldRef};
 use super::order::compare_entities_by_field;
+use crate::json::optional_to_json;
 use crate::{Entity, EntityType, FieldValue};
 
 /// Sort direction
@@ -21,6 +24,15 @@ pub enum SortDirection {
 }
 
 
+impl fmt::Display for SortDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortDirection::Ascending => write!(f, "asc"),
+            SortDirection::Descending => write!(f, "desc"),
+        }
+    }
+}
+
 /// Terminal aggregation that transforms the query result set
 #[derive(Debug, Clone)]
 pub enum Aggregation {
@@ -36,6 +48,22 @@ pub enum Aggregation {
     Median(FieldRef),
 }
 
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aggregation::Select(fields) => {
+                let fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+                write!(f, "select {}", fields.join(", "))
+            }
+            Aggregation::Count(None) => write!(f, "count"),
+            Aggregation::Count(Some(field))
+            | Aggregation::Sum(field)
+            | Aggregation::Average(field)
+            | Aggregation::Median(field) => write!(f, "{} {}", self.name(), field),
+        }
+    }
+}
+
 /// The result of executing a query
 #[derive(Debug)]
 pub enum QueryResult<'a> {
@@ -45,12 +73,53 @@ pub enum QueryResult<'a> {
     Aggregation(AggregationResult),
 }
 
+/// How a query ran, for understanding why it returns what it does
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct QueryExplanation {
+    /// The query as it was understood, in query syntax
+    pub query: String,
+    /// Each stage in execution order, before any aggregation
+    pub stages: Vec<QueryStage>,
+}
+
+/// A stage of query execution and what it left
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryStage {
+    /// The stage in query syntax, like `where priority > 5`
+    pub operation: String,
+    /// The number of entities left after the stage
+    pub entities: usize,
+}
+
+impl fmt::Display for QueryExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.query)?;
+        let width = self
+            .stages
+            .iter()
+            .map(|stage| stage.operation.len())
+            .max()
+            .unwrap_or(0);
+        for stage in &self.stages {
+            writeln!(
+                f,
+                "  {:width$}  {} entities",
+                stage.operation,
+                stage.entities,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Result of an aggregation operation
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum AggregationResult {
     /// Rows of field values from a select query
     Select {
         columns: Vec<String>,
+        #[serde(serialize_with = "serialize_rows")]
         rows: Vec<Vec<Option<FieldValue>>>,
     },
     /// A count result
@@ -61,6 +130,36 @@ pub enum AggregationResult {
     Average(f64),
     /// A median result
     Median(f64),
+    /// One aggregation result per distinct value of the grouped field
+    Grouped {
+        field: String,
+        aggregate: String,
+        #[serde(serialize_with = "serialize_groups")]
+        groups: Vec<(Option<FieldValue>, AggregationResult)>,
+    },
+}
+
+/// Serializes select rows with values in their canonical JSON encoding.
+fn serialize_rows<S: Serializer>(
+    rows: &[Vec<Option<FieldValue>>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        rows.iter()
+            .map(|row| row.iter().map(optional_to_json).collect::<Vec<_>>()),
+    )
+}
+
+/// Serializes groups with their keys in their canonical JSON encoding.
+fn serialize_groups<S: Serializer>(
+    groups: &[(Option<FieldValue>, AggregationResult)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        groups
+            .iter()
+            .map(|(key, result)| (optional_to_json(key), result)),
+    )
 }
 
 impl fmt::Display for AggregationResult {
@@ -84,6 +183,20 @@ impl fmt::Display for AggregationResult {
                 }
                 Ok(())
             }
+            AggregationResult::Grouped {
+                field,
+                aggregate,
+                groups,
+            } => {
+                writeln!(f, "{}\t{}", field, aggregate)?;
+                for (key, result) in groups {
+                    match key {
+                        Some(val) => writeln!(f, "{}\t{}", val, result)?,
+                        None => writeln!(f, "-\t{}", result)?,
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -116,7 +229,12 @@ impl fmt::Display for AggregateValue {
 pub struct Query {
     pub from: EntitySelector,
     pub operations: Vec<QueryOperation>,
+    pub group_by: Option<FieldRef>,
     pub aggregation: Option<Aggregation>,
+    pub aggregate_order: Option<SortDirection>,
+    pub aggregate_limit: Option<usize>,
+    /// The timezone date-only conditions are compared in, instead of each value's own offset.
+    pub timezone: Option<FixedOffset>,
 }
 
 impl Query {
@@ -125,7 +243,11 @@ impl Query {
         Self {
             from,
             operations: Vec::new(),
+            group_by: None,
             aggregation: None,
+            aggregate_order: None,
+            aggregate_limit: None,
+            timezone: None,
         }
     }
 
@@ -135,20 +257,71 @@ impl Query {
         self
     }
 
+    /// Group the entities by a field before aggregating
+    pub fn with_group_by(mut self, field: FieldRef) -> Self {
+        self.group_by = Some(field);
+        self
+    }
+
     /// Set the terminal aggregation for the query
     pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
         self.aggregation = Some(aggregation);
         self
     }
 
+    /// Order grouped results by their aggregate value
+    pub fn with_aggregate_order(mut self, direction: SortDirection) -> Self {
+        self.aggregate_order = Some(direction);
+        self
+    }
+
+    /// Limit the number of groups in grouped results
+    pub fn with_aggregate_limit(mut self, limit: usize) -> Self {
+        self.aggregate_limit = Some(limit);
+        self
+    }
+
+    /// Compare datetimes with date-only conditions in a timezone
+    ///
+    /// Without one, `due == 2025-09-30` checks the date in each value's own
+    /// offset, so the same instant can fall on different days.
+    pub fn with_timezone(mut self, timezone: FixedOffset) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
     /// Execute the query against an entity graph
     pub fn execute<'a>(
         &self,
         graph: &'a crate::graph::EntityGraph,
     ) -> Result<QueryResult<'a>, QueryError> {
+        self.run(graph, None)
+    }
+
+    /// Execute the query, recording how many entities each stage leaves
+    pub fn execute_explained<'a>(
+        &self,
+        graph: &'a crate::graph::EntityGraph,
+    ) -> Result<(QueryResult<'a>, QueryExplanation), QueryError> {
+        let mut explanation = QueryExplanation {
+            query: self.to_string(),
+            stages: Vec::new(),
+        };
+        let result = self.run(graph, Some(&mut explanation))?;
+        Ok((result, explanation))
+    }
+
+    fn run<'a>(
+        &self,
+        graph: &'a crate::graph::EntityGraph,
+        mut explanation: Option<&mut QueryExplanation>,
+    ) -> Result<QueryResult<'a>, QueryError> {
+        self.check_grouping()?;
+
         // Start by selecting entities based on the "from" clause
         let mut entities = match &self.from {
-            EntitySelector::Type(entity_type) => {
+            // Schemas can't extend other schemas yet, so a type's subtypes are just itself
+            EntitySelector::Type(entity_type) | EntitySelector::TypeWithSubtypes(entity_type) => {
                 // Check if the entity type exists in the graph
                 let all_types = graph.get_all_entity_types();
                 if !all_types.contains(entity_type) {
@@ -168,6 +341,12 @@ impl Query {
                     .collect()
             }
         };
+        if let Some(explanation) = explanation.as_deref_mut() {
+            explanation.stages.push(QueryStage {
+                operation: format!("from {}", self.from),
+                entities: entities.len(),
+            });
+        }
 
         // Apply each operation in sequence
         for operation in &self.operations {
@@ -175,7 +354,7 @@ impl Query {
                 QueryOperation::Where(condition) => {
                     let mut filtered = Vec::new();
                     for e in entities {
-                        if condition.matches(e)? {
+                        if condition.matches_in(e, self.timezone.as_ref())? {
                             filtered.push(e);
                         }
                     }
@@ -189,24 +368,87 @@ impl Query {
                 QueryOperation::Limit(n) => entities.into_iter().take(*n).collect(),
                 QueryOperation::Related {
                     degrees,
-                    entity_type,
-                } => super::related::get_related_entities(
-                    graph,
-                    entities,
-                    *degrees,
-                    entity_type.as_ref(),
-                ),
+                    entity_types,
+                } => super::related::get_related_entities(graph, entities, *degrees, entity_types),
             };
+            if let Some(explanation) = explanation.as_deref_mut() {
+                explanation.stages.push(QueryStage {
+                    operation: operation.to_string(),
+                    entities: entities.len(),
+                });
+            }
         }
 
-        // Apply terminal aggregation if present
-        match &self.aggregation {
-            None => Ok(QueryResult::Entities(entities)),
-            Some(aggregation) => {
+        // Apply terminal aggregation if present, per group when grouping
+        match (&self.group_by, &self.aggregation) {
+            (_, None) => Ok(QueryResult::Entities(entities)),
+            (None, Some(aggregation)) => {
                 let result = aggregation.execute(&entities)?;
                 Ok(QueryResult::Aggregation(result))
             }
+            (Some(field), Some(aggregation)) => {
+                let mut result = super::aggregation::group::execute(
+                    field,
+                    aggregation,
+                    self.aggregate_order.as_ref(),
+                    &entities,
+                )?;
+
+                // Keep the first groups, after any ordering by aggregate
+                if let (Some(limit), AggregationResult::Grouped { groups, .. }) =
+                    (self.aggregate_limit, &mut result)
+                {
+                    groups.truncate(limit);
+                }
+                Ok(QueryResult::Aggregation(result))
+            }
+        }
+    }
+
+    /// Check that grouping, and ordering or limiting groups, are used together.
+    pub(super) fn check_grouping(&self) -> Result<(), QueryError> {
+        if self.group_by.is_some() && self.aggregation.is_none() {
+            return Err(QueryError::InvalidAggregation {
+                message: "A group clause must be followed by an aggregation, e.g.: group status | count".to_string(),
+            });
+        }
+
+        if self.aggregate_order.is_some() && self.group_by.is_none() {
+            return Err(QueryError::InvalidAggregation {
+                message: "Ordering by an aggregate requires a group clause, e.g.: group status | count | order count desc".to_string(),
+            });
+        }
+
+        // Limiting a single value (or select rows) after aggregating is always a mistake
+        if self.aggregate_limit.is_some() && self.group_by.is_none() {
+            return Err(QueryError::InvalidAggregation {
+                message: "A limit after an aggregation requires a group clause. To limit entities, put limit before the aggregation, e.g.: limit 5 | select name".to_string(),
+            });
         }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "from {}", self.from)?;
+        for operation in &self.operations {
+            write!(f, " | {}", operation)?;
+        }
+        if let Some(field) = &self.group_by {
+            write!(f, " | group {}", field)?;
+        }
+        if let Some(aggregation) = &self.aggregation {
+            write!(f, " | {}", aggregation)?;
+            if let Some(direction) = &self.aggregate_order {
+                write!(f, " | order {} {}", aggregation.name(), direction)?;
+            }
+        }
+        if let Some(limit) = self.aggregate_limit {
+            write!(f, " | limit {}", limit)?;
+        }
+        Ok(())
     }
 }
 
@@ -215,10 +457,35 @@ impl Query {
 pub enum EntitySelector {
     /// Select entities of a specific type
     Type(EntityType),
+    /// Select entities of a type and of every type that extends it
+    TypeWithSubtypes(EntityType),
     /// Select all entities (wildcard)
     All,
 }
 
+impl EntitySelector {
+    /// Checks whether entities of a type are selected.
+    pub fn matches(&self, entity_type: &EntityType) -> bool {
+        match self {
+            // Schemas can't extend other schemas yet, so a type's subtypes are just itself
+            EntitySelector::Type(selected) | EntitySelector::TypeWithSubtypes(selected) => {
+                selected == entity_type
+            }
+            EntitySelector::All => true,
+        }
+    }
+}
+
+impl fmt::Display for EntitySelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntitySelector::Type(entity_type) => write!(f, "{}", entity_type),
+            EntitySelector::TypeWithSubtypes(entity_type) => write!(f, "{}+", entity_type),
+            EntitySelector::All => write!(f, "*"),
+        }
+    }
+}
+
 /// Operations that can be applied to entity collections
 #[derive(Debug, Clone)]
 pub enum QueryOperation {
@@ -227,7 +494,8 @@ pub enum QueryOperation {
     /// Traverse to related entities
     Related {
         degrees: usize,
-        entity_type: Option<EntityType>,
+        /// Only keep entities matching one of these selectors, or every entity when empty
+        entity_types: Vec<EntitySelector>,
     },
     /// Sort entities by a field (or metadata)
     Order {
@@ -238,6 +506,36 @@ pub enum QueryOperation {
     Limit(usize),
 }
 
+impl fmt::Display for QueryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryOperation::Where(condition) => write!(f, "where {}", condition),
+            QueryOperation::Related {
+                degrees,
+                entity_types,
+            } => {
+                write!(f, "related")?;
+                if *degrees != 1 {
+                    write!(f, "({})", degrees)?;
+                }
+                match entity_types.as_slice() {
+                    [] => Ok(()),
+                    [entity_type] => write!(f, " {}", entity_type),
+                    entity_types => {
+                        let entity_types: Vec<String> =
+                            entity_types.iter().map(ToString::to_string).collect();
+                        write!(f, " [{}]", entity_types.join(", "))
+                    }
+                }
+            }
+            QueryOperation::Order { field, direction } => {
+                write!(f, "order {} {}", field, direction)
+            }
+            QueryOperation::Limit(n) => write!(f, "limit {}", n),
+        }
+    }
+}
+
 /// Compare two entities by a specific field for sorting
 #[cfg(test)]
 mod tests {
@@ -290,6 +588,24 @@ mod tests {
         assert!(results.iter().any(|e| e.id == EntityId::new("person2")));
     }
 
+    #[test]
+    fn test_query_from_type_with_subtypes() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::TypeWithSubtypes(EntityType::new("person")));
+        assert_eq!(query.to_string(), "from person+");
+
+        // Without schema inheritance, a type has no subtypes to add
+        let results = unwrap_entities(query.execute(&graph).unwrap());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|e| e.entity_type.as_str() == "person"));
+
+        let query = Query::new(EntitySelector::TypeWithSubtypes(EntityType::new("animal")));
+        assert!(matches!(
+            query.execute(&graph),
+            Err(QueryError::UnknownEntityType { .. })
+        ));
+    }
+
     #[test]
     fn test_query_from_all() {
         let graph = create_test_graph();
@@ -317,6 +633,38 @@ mod tests {
         assert_eq!(results[0].id, EntityId::new("task2"));
     }
 
+    #[test]
+    fn test_query_date_only_conditions_use_timezone() {
+        use chrono::TimeZone;
+
+        // Due late on the 30th in UTC, which is already the 1st in Tokyo
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let due = utc.with_ymd_and_hms(2025, 9, 30, 20, 0, 0).unwrap();
+        let mut graph = crate::graph::EntityGraph::new();
+        graph
+            .add_entities(vec![
+                Entity::new(EntityId::new("task1"), EntityType::new("task"))
+                    .with_field(FieldId::new("due"), due),
+            ])
+            .unwrap();
+        graph.build();
+
+        let query = Query::new(EntitySelector::Type(EntityType::new("task"))).with_operation(
+            QueryOperation::Where(super::super::CompoundFilterCondition::single(
+                super::super::FilterCondition::new(
+                    super::super::FieldRef::Regular(FieldId::new("due")),
+                    super::super::FilterOperator::Equal,
+                    super::super::FilterValue::DateTime("2025-10-01".to_string()),
+                ),
+            )),
+        );
+        assert!(unwrap_entities(query.execute(&graph).unwrap()).is_empty());
+
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let query = query.with_timezone(tokyo);
+        assert_eq!(unwrap_entities(query.execute(&graph).unwrap()).len(), 1);
+    }
+
     #[test]
     fn test_query_with_limit() {
         let graph = create_test_graph();
@@ -345,6 +693,47 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn test_query_execute_explained() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::Type(EntityType::new("person")))
+            .with_operation(QueryOperation::Where(
+                super::super::CompoundFilterCondition::single(
+                    super::super::FilterCondition::new(
+                        super::super::FieldRef::Regular(FieldId::new("name")),
+                        super::super::FilterOperator::StartsWith,
+                        super::super::FilterValue::String("A".to_string()),
+                    ),
+                ),
+            ))
+            .with_operation(QueryOperation::Order {
+                field: super::super::FieldRef::Regular(FieldId::new("age")),
+                direction: SortDirection::Descending,
+            })
+            .with_aggregation(Aggregation::Count(None));
+
+        let (result, explanation) = query.execute_explained(&graph).unwrap();
+        assert_eq!(unwrap_aggregation(result), AggregationResult::Count(1));
+        assert_eq!(
+            explanation.query,
+            "from person | where name startswith \"A\" | order age desc | count"
+        );
+
+        let stages: Vec<(&str, usize)> = explanation
+            .stages
+            .iter()
+            .map(|stage| (stage.operation.as_str(), stage.entities))
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                ("from person", 2),
+                ("where name startswith \"A\"", 1),
+                ("order age desc", 1),
+            ]
+        );
+    }
+
     #[test]
     fn test_query_unknown_entity_type() {
         let graph = create_test_graph();
@@ -375,6 +764,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_aggregation_results_serialize_values_as_canonical_json() {
+        let select = AggregationResult::Select {
+            columns: vec!["name".to_string(), "status".to_string()],
+            rows: vec![vec![
+                Some(FieldValue::String("Alice".to_string())),
+                Some(FieldValue::Enum("active".to_string())),
+            ]],
+        };
+        assert_eq!(
+            serde_json::to_value(&select).unwrap(),
+            serde_json::json!({"Select": {
+                "columns": ["name", "status"],
+                "rows": [["Alice", {"enum": "active"}]],
+            }})
+        );
+
+        let grouped = AggregationResult::Grouped {
+            field: "age".to_string(),
+            aggregate: "count".to_string(),
+            groups: vec![
+                (Some(FieldValue::Integer(30)), AggregationResult::Count(2)),
+                (None, AggregationResult::Count(1)),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_value(&grouped).unwrap()["Grouped"]["groups"],
+            serde_json::json!([[30, {"Count": 2}], [null, {"Count": 1}]])
+        );
+    }
+
     #[test]
     fn test_query_with_count_aggregation() {
         let graph = create_test_graph();
@@ -409,4 +829,94 @@ mod tests {
         let result = query.execute(&graph).unwrap();
         assert!(matches!(result, QueryResult::Entities(_)));
     }
+
+    #[test]
+    fn test_query_with_grouped_count_ordered_by_aggregate() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::All)
+            .with_group_by(super::super::FieldRef::Metadata(
+                super::super::MetadataField::Type,
+            ))
+            .with_aggregation(Aggregation::Count(None))
+            .with_aggregate_order(SortDirection::Descending);
+        let result = unwrap_aggregation(query.execute(&graph).unwrap());
+
+        assert_eq!(
+            result,
+            AggregationResult::Grouped {
+                field: "@type".to_string(),
+                aggregate: "count".to_string(),
+                groups: vec![
+                    (
+                        Some(FieldValue::String("person".to_string())),
+                        AggregationResult::Count(2)
+                    ),
+                    (
+                        Some(FieldValue::String("task".to_string())),
+                        AggregationResult::Count(2)
+                    ),
+                ],
+            }
+        );
+        assert_eq!(result.to_string(), "@type\tcount\nperson\t2\ntask\t2\n");
+    }
+
+    #[test]
+    fn test_query_aggregate_order_requires_group() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::Type(EntityType::new("person")))
+            .with_aggregation(Aggregation::Count(None))
+            .with_aggregate_order(SortDirection::Descending);
+
+        assert!(matches!(
+            query.execute(&graph),
+            Err(QueryError::InvalidAggregation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_query_group_requires_aggregation() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::Type(EntityType::new("person")))
+            .with_group_by(super::super::FieldRef::Regular(FieldId::new("age")));
+
+        assert!(matches!(
+            query.execute(&graph),
+            Err(QueryError::InvalidAggregation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_query_with_grouped_count_limited() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::All)
+            .with_group_by(super::super::FieldRef::Regular(FieldId::new("name")))
+            .with_aggregation(Aggregation::Count(None))
+            .with_aggregate_order(SortDirection::Descending)
+            .with_aggregate_limit(1);
+        let result = unwrap_aggregation(query.execute(&graph).unwrap());
+
+        // Tasks have no name, so their group is the largest
+        assert_eq!(
+            result,
+            AggregationResult::Grouped {
+                field: "name".to_string(),
+                aggregate: "count".to_string(),
+                groups: vec![(None, AggregationResult::Count(2))],
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_aggregate_limit_requires_group() {
+        let graph = create_test_graph();
+        let query = Query::new(EntitySelector::Type(EntityType::new("person")))
+            .with_aggregation(Aggregation::Count(None))
+            .with_aggregate_limit(1);
+
+        assert!(matches!(
+            query.execute(&graph),
+            Err(QueryError::InvalidAggregation { .. })
+        ));
+    }
 }
@@ -0,0 +1,311 @@
+//! Schema-aware validation of queries before execution
+//!
+//! Query execution is lenient: referencing a field that no entity has simply
+//! matches nothing (or sorts everything equal). Validation is an opt-in strict
+//! check that every referenced field is declared in the relevant schema.
+
+use super::QueryError;
+use super::filter::FieldRef;
+use super::types::{Aggregation, EntitySelector, Query, QueryOperation};
+use crate::{EntitySchema, EntityType, FieldId};
+
+/// Label used for the entity type when a query is not narrowed to a single type
+const ANY_ENTITY_TYPE: &str = "*";
+
+impl Query {
+    /// Validate field references in the query against the workspace schemas.
+    ///
+    /// Fields used by `where`, `order`, `group` and the terminal aggregation must be
+    /// declared in the schema of the entity type being queried at that point.
+    /// After a `related` step without a type filter (or when querying `*`),
+    /// a field is accepted if any schema declares it, and after one filtering
+    /// to several types, if any of their schemas declares it. Grouping clauses are
+    /// also checked for being used together, like they are on execution.
+    pub fn validate(&self, schemas: &[EntitySchema]) -> Result<(), QueryError> {
+        self.check_grouping()?;
+
+        let mut current_types = selected_types(schemas, std::slice::from_ref(&self.from))?;
+
+        for operation in &self.operations {
+            match operation {
+                QueryOperation::Where(condition) => {
+                    for filter in &condition.conditions {
+                        validate_field(schemas, &current_types, &filter.field)?;
+                    }
+                }
+                QueryOperation::Order { field, .. } => {
+                    validate_field(schemas, &current_types, field)?;
+                }
+                QueryOperation::Related { entity_types, .. } => {
+                    current_types = selected_types(schemas, entity_types)?;
+                }
+                QueryOperation::Limit(_) => {}
+            }
+        }
+
+        if let Some(field) = &self.group_by {
+            validate_field(schemas, &current_types, field)?;
+        }
+
+        if let Some(aggregation) = &self.aggregation {
+            let fields: Vec<&FieldRef> = match aggregation {
+                Aggregation::Select(fields) => fields.iter().collect(),
+                Aggregation::Count(field) => field.iter().collect(),
+                Aggregation::Sum(field)
+                | Aggregation::Average(field)
+                | Aggregation::Median(field) => vec![field],
+            };
+
+            for field in fields {
+                validate_field(schemas, &current_types, field)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the schema for an entity type, or report the available types.
+fn find_schema<'a>(
+    schemas: &'a [EntitySchema],
+    entity_type: &EntityType,
+) -> Result<&'a EntitySchema, QueryError> {
+    schemas
+        .iter()
+        .find(|schema| &schema.entity_type == entity_type)
+        .ok_or_else(|| QueryError::UnknownEntityType {
+            requested: entity_type.to_string(),
+            available: schemas.iter().map(|s| s.entity_type.to_string()).collect(),
+        })
+}
+
+/// Find the entity types selected by some selectors, checking that each has a schema.
+///
+/// Returns no types when any type can be selected, like for `*`.
+fn selected_types(
+    schemas: &[EntitySchema],
+    selectors: &[EntitySelector],
+) -> Result<Vec<EntityType>, QueryError> {
+    let mut entity_types = Vec::new();
+    for selector in selectors {
+        match selector {
+            EntitySelector::Type(entity_type) | EntitySelector::TypeWithSubtypes(entity_type) => {
+                find_schema(schemas, entity_type)?;
+                entity_types.push(entity_type.clone());
+            }
+            EntitySelector::All => return Ok(Vec::new()),
+        }
+    }
+    Ok(entity_types)
+}
+
+/// Check that a field reference is declared for one of the current entity types.
+///
+/// With no current types, a field declared by any schema is accepted.
+fn validate_field(
+    schemas: &[EntitySchema],
+    entity_types: &[EntityType],
+    field: &FieldRef,
+) -> Result<(), QueryError> {
+    let field_id = match field {
+        FieldRef::Metadata(_) => return Ok(()),
+        FieldRef::Regular(field_id) => field_id,
+    };
+
+    let is_declared = if entity_types.is_empty() {
+        schemas
+            .iter()
+            .any(|schema| declares_field(schema, field_id))
+    } else {
+        let mut is_declared = false;
+        for entity_type in entity_types {
+            is_declared |= declares_field(find_schema(schemas, entity_type)?, field_id);
+        }
+        is_declared
+    };
+
+    if is_declared {
+        Ok(())
+    } else {
+        Err(QueryError::UnknownField {
+            field: field_id.to_string(),
+            entity_type: if entity_types.is_empty() {
+                ANY_ENTITY_TYPE.to_string()
+            } else {
+                let entity_types: Vec<String> =
+                    entity_types.iter().map(ToString::to_string).collect();
+                entity_types.join(", ")
+            },
+        })
+    }
+}
+
+fn declares_field(schema: &EntitySchema, field_id: &FieldId) -> bool {
+    schema.fields.contains_key(field_id) || schema.is_computed(field_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldType;
+    use crate::graph::query::{
+        CompoundFilterCondition, FilterCondition, FilterOperator, FilterValue, MetadataField,
+        SortDirection,
+    };
+    use crate::schema::EntitySchema;
+
+    fn create_test_schemas() -> Vec<EntitySchema> {
+        vec![
+            EntitySchema::new(EntityType::new("person"))
+                .with_required_field(FieldId::new("name"), FieldType::String)
+                .with_optional_field(FieldId::new("age"), FieldType::Integer),
+            EntitySchema::new(EntityType::new("task"))
+                .with_required_field(FieldId::new("name"), FieldType::String)
+                .with_optional_field(FieldId::new("priority"), FieldType::Integer),
+        ]
+    }
+
+    fn where_field(field: &str) -> QueryOperation {
+        QueryOperation::Where(CompoundFilterCondition::single(FilterCondition::new(
+            FieldRef::Regular(FieldId::new(field)),
+            FilterOperator::Equal,
+            FilterValue::Integer(1),
+        )))
+    }
+
+    fn order_by(field: FieldRef) -> QueryOperation {
+        QueryOperation::Order {
+            field,
+            direction: SortDirection::Ascending,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_declared_fields() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("task")))
+            .with_operation(where_field("priority"))
+            .with_operation(order_by(FieldRef::Regular(FieldId::new("name"))))
+            .with_aggregation(Aggregation::Sum(FieldRef::Regular(FieldId::new(
+                "priority",
+            ))));
+
+        assert_eq!(query.validate(&create_test_schemas()), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_accepts_metadata_fields() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("task")))
+            .with_operation(order_by(FieldRef::Metadata(MetadataField::Id)));
+
+        assert_eq!(query.validate(&create_test_schemas()), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_order_field() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("task")))
+            .with_operation(order_by(FieldRef::Regular(FieldId::new("due_date"))));
+
+        assert_eq!(
+            query.validate(&create_test_schemas()),
+            Err(QueryError::UnknownField {
+                field: "due_date".to_string(),
+                entity_type: "task".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_field_from_other_schema() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("task")))
+            .with_operation(where_field("age"));
+
+        assert!(matches!(
+            query.validate(&create_test_schemas()),
+            Err(QueryError::UnknownField { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_select_field() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("person"))).with_aggregation(
+            Aggregation::Select(vec![
+                FieldRef::Metadata(MetadataField::Id),
+                FieldRef::Regular(FieldId::new("email")),
+            ]),
+        );
+
+        assert_eq!(
+            query.validate(&create_test_schemas()),
+            Err(QueryError::UnknownField {
+                field: "email".to_string(),
+                entity_type: "person".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_follows_related_type() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("person")))
+            .with_operation(QueryOperation::Related {
+                degrees: 1,
+                entity_types: vec![EntitySelector::Type(EntityType::new("task"))],
+            })
+            .with_operation(where_field("priority"));
+
+        assert_eq!(query.validate(&create_test_schemas()), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_related_to_several_types() {
+        let related = QueryOperation::Related {
+            degrees: 2,
+            entity_types: vec![
+                EntitySelector::Type(EntityType::new("person")),
+                EntitySelector::TypeWithSubtypes(EntityType::new("task")),
+            ],
+        };
+
+        // A field of either type is accepted
+        let query = Query::new(EntitySelector::Type(EntityType::new("task")))
+            .with_operation(related.clone())
+            .with_operation(where_field("age"))
+            .with_operation(where_field("priority"));
+        assert_eq!(query.validate(&create_test_schemas()), Ok(()));
+
+        let query = Query::new(EntitySelector::Type(EntityType::new("task")))
+            .with_operation(related)
+            .with_operation(where_field("email"));
+        assert_eq!(
+            query.validate(&create_test_schemas()),
+            Err(QueryError::UnknownField {
+                field: "email".to_string(),
+                entity_type: "person, task".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_wildcard_accepts_field_from_any_schema() {
+        let query = Query::new(EntitySelector::All).with_operation(where_field("age"));
+        assert_eq!(query.validate(&create_test_schemas()), Ok(()));
+
+        let query = Query::new(EntitySelector::All).with_operation(where_field("missing"));
+        assert_eq!(
+            query.validate(&create_test_schemas()),
+            Err(QueryError::UnknownField {
+                field: "missing".to_string(),
+                entity_type: "*".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_entity_type() {
+        let query = Query::new(EntitySelector::Type(EntityType::new("tasks")));
+
+        assert!(matches!(
+            query.validate(&create_test_schemas()),
+            Err(QueryError::UnknownEntityType { .. })
+        ));
+    }
+}
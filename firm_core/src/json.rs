@@ -0,0 +1,578 @@
+//! Canonical JSON encoding of field values and entities
+//!
+//! This is the shape field values take in tool input and structured output,
+//! meant to be read and written by hand. It's separate from the serde derive
+//! on `FieldValue`, which tags every value with its variant and is used to
+//! store the graph.
+//!
+//! - Booleans, strings, integers and floats are plain JSON values
+//! - Currencies are `{"amount": "100.50", "code": "USD"}`
+//! - Datetimes are RFC 3339 strings, like `"2025-01-15T17:00:00+03:00"`
+//! - References are strings, like `"person.john"` or `"person.john.email"`
+//! - Paths are strings
+//! - Enum values are tagged, like `{"enum": "active"}`
+//! - Lists are arrays of their items
+//!
+//! Entities are `{"id", "type", "fields"}` objects with their fields by name,
+//! plus tags and timestamps when they have them.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use chrono::DateTime;
+use iso_currency::Currency;
+use rust_decimal::Decimal;
+use serde_json::{Map, Value, json};
+
+use crate::{Entity, EntityId, FieldId, FieldType, FieldValue, ReferenceValue, compose_entity_id};
+
+const AMOUNT_KEY: &str = "amount";
+const CODE_KEY: &str = "code";
+const ENUM_KEY: &str = "enum";
+
+/// Why a JSON value can't be read as a field value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldJsonError {
+    /// The JSON value has the wrong shape for the field type.
+    Mismatch { expected: FieldType, found: Value },
+    /// The JSON value has the right shape, but not a valid value.
+    Invalid {
+        expected: FieldType,
+        message: String,
+    },
+    /// A list has items, but no item type to read them as.
+    MissingItemType,
+    /// The item type given with a list disagrees with the schema's.
+    ItemTypeConflict {
+        schema: FieldType,
+        declared: FieldType,
+    },
+    /// A value for a named field can't be read.
+    Field {
+        field: String,
+        error: Box<FieldJsonError>,
+    },
+}
+
+impl FieldJsonError {
+    /// Gets the error behind any field names.
+    pub fn cause(&self) -> &FieldJsonError {
+        match self {
+            FieldJsonError::Field { error, .. } => error.cause(),
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for FieldJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldJsonError::Mismatch { expected, found } => write!(
+                f,
+                "Expected {} for field type {}, got {}",
+                expected_shape(expected),
+                expected,
+                found
+            ),
+            FieldJsonError::Invalid { expected, message } => write!(
+                f,
+                "Invalid {} value: {}. Expected {}",
+                expected,
+                message,
+                expected_shape(expected)
+            ),
+            FieldJsonError::MissingItemType => {
+                write!(f, "A list needs an item type to read its items")
+            }
+            FieldJsonError::ItemTypeConflict { schema, declared } => write!(
+                f,
+                "The list holds {} items in the schema, but {} items were given",
+                schema, declared
+            ),
+            FieldJsonError::Field { field, error } => write!(f, "Field '{}': {}", field, error),
+        }
+    }
+}
+
+impl std::error::Error for FieldJsonError {}
+
+/// What a JSON value is read for, besides its field type.
+#[derive(Clone, Copy, Default)]
+pub struct FieldJsonContext<'a> {
+    /// The field the value is for, named in errors.
+    pub field: Option<&'a str>,
+    /// The item type of lists, from the field's schema.
+    pub item_type: Option<&'a FieldType>,
+    /// An item type given with the value, used when the schema has none.
+    pub declared_item_type: Option<&'a FieldType>,
+    /// Maps paths as they're read, like from the workspace root to the file
+    /// the value is written to.
+    pub map_path: Option<&'a dyn Fn(&Path) -> PathBuf>,
+}
+
+impl FieldValue {
+    /// Encodes the value as canonical JSON.
+    pub fn to_json(&self) -> Value {
+        match self {
+            FieldValue::Boolean(b) => json!(b),
+            FieldValue::String(s) => json!(s),
+            FieldValue::Integer(n) => json!(n),
+            FieldValue::Float(n) => json!(n),
+            FieldValue::Currency { amount, currency } => json!({
+                AMOUNT_KEY: amount.to_string(),
+                CODE_KEY: currency.code(),
+            }),
+            FieldValue::Reference(reference) => json!(reference.to_string()),
+            FieldValue::List(items) => Value::Array(items.iter().map(Self::to_json).collect()),
+            FieldValue::DateTime(datetime) => json!(datetime.to_rfc3339()),
+            FieldValue::Path(path) => json!(path.to_string_lossy()),
+            FieldValue::Enum(value) => json!({ ENUM_KEY: value }),
+        }
+    }
+
+    /// Reads a value of a field type from canonical JSON.
+    ///
+    /// JSON doesn't say which field type a value has, so it's given, and the
+    /// context has the item type for lists. Some shorthands are accepted too:
+    /// enum values as plain strings, currency amounts as numbers, and
+    /// currencies as strings like `"100.50 USD"`.
+    pub fn try_from_json(
+        value: &Value,
+        field_type: &FieldType,
+        context: &FieldJsonContext,
+    ) -> Result<FieldValue, FieldJsonError> {
+        read_json(value, field_type, context).map_err(|error| match context.field {
+            Some(field) => FieldJsonError::Field {
+                field: field.to_string(),
+                error: Box::new(error),
+            },
+            None => error,
+        })
+    }
+}
+
+impl Entity {
+    /// Encodes the entity as canonical JSON, with its fields by name.
+    ///
+    /// Tags and timestamps are included when the entity has them.
+    pub fn to_json(&self) -> Value {
+        let fields: Map<String, Value> = self
+            .fields
+            .iter()
+            .map(|(field_id, value)| (field_id.to_string(), value.to_json()))
+            .collect();
+
+        let mut json = json!({
+            "id": self.id.as_str(),
+            "type": self.entity_type.as_str(),
+            "fields": fields,
+        });
+        if !self.tags.is_empty() {
+            json["tags"] = json!(self.tags);
+        }
+        if let Some(created) = self.created {
+            json["created"] = json!(created.to_rfc3339());
+        }
+        if let Some(modified) = self.modified {
+            json["modified"] = json!(modified.to_rfc3339());
+        }
+        json
+    }
+}
+
+fn read_json(
+    value: &Value,
+    field_type: &FieldType,
+    context: &FieldJsonContext,
+) -> Result<FieldValue, FieldJsonError> {
+    let mismatch = || FieldJsonError::Mismatch {
+        expected: field_type.clone(),
+        found: value.clone(),
+    };
+    let invalid = |message: String| FieldJsonError::Invalid {
+        expected: field_type.clone(),
+        message,
+    };
+
+    match (field_type, value) {
+        (FieldType::Boolean, Value::Bool(b)) => Ok(FieldValue::Boolean(*b)),
+        (FieldType::String, Value::String(s)) => Ok(FieldValue::String(s.clone())),
+        (FieldType::Integer, Value::Number(n)) => {
+            n.as_i64().map(FieldValue::Integer).ok_or_else(mismatch)
+        }
+        (FieldType::Float, Value::Number(n)) => {
+            n.as_f64().map(FieldValue::Float).ok_or_else(mismatch)
+        }
+        (FieldType::Currency, Value::Object(object)) => {
+            let amount = match object.get(AMOUNT_KEY) {
+                Some(Value::String(amount)) => amount.clone(),
+                Some(Value::Number(amount)) => amount.to_string(),
+                _ => return Err(mismatch()),
+            };
+            let Some(Value::String(code)) = object.get(CODE_KEY) else {
+                return Err(mismatch());
+            };
+            parse_currency(&amount, code).map_err(invalid)
+        }
+        (FieldType::Currency, Value::String(s)) => {
+            match s.split_whitespace().collect::<Vec<_>>()[..] {
+                [amount, code] => parse_currency(amount, code).map_err(invalid),
+                _ => Err(mismatch()),
+            }
+        }
+        (FieldType::Reference, Value::String(s)) => parse_reference(s).map_err(invalid),
+        (FieldType::List, Value::Array(items)) => {
+            if items.is_empty() {
+                return Ok(FieldValue::List(Vec::new()));
+            }
+
+            // The schema's item type comes first, and a declared one has to agree with it
+            let item_type = match (context.item_type, context.declared_item_type) {
+                (Some(schema), Some(declared)) if schema != declared => {
+                    return Err(FieldJsonError::ItemTypeConflict {
+                        schema: schema.clone(),
+                        declared: declared.clone(),
+                    });
+                }
+                (Some(item_type), _) | (None, Some(item_type)) => item_type,
+                (None, None) => return Err(FieldJsonError::MissingItemType),
+            };
+            let item_context = FieldJsonContext {
+                field: None,
+                item_type: None,
+                declared_item_type: None,
+                map_path: context.map_path,
+            };
+            items
+                .iter()
+                .map(|item| read_json(item, item_type, &item_context))
+                .collect::<Result<_, _>>()
+                .map(FieldValue::List)
+        }
+        (FieldType::DateTime, Value::String(s)) => DateTime::parse_from_rfc3339(s)
+            .map(FieldValue::DateTime)
+            .map_err(|_| invalid(format!("'{}' is not an RFC 3339 datetime", s))),
+        (FieldType::Path, Value::String(s)) => {
+            let path = Path::new(s);
+            Ok(FieldValue::Path(match context.map_path {
+                Some(map_path) => map_path(path),
+                None => path.to_path_buf(),
+            }))
+        }
+        (FieldType::Enum, Value::String(s)) => Ok(FieldValue::Enum(s.clone())),
+        (FieldType::Enum, Value::Object(object)) => match tagged_enum(object) {
+            Some(value) => Ok(FieldValue::Enum(value.to_string())),
+            None => Err(mismatch()),
+        },
+        _ => Err(mismatch()),
+    }
+}
+
+/// Encodes an optional field value as canonical JSON, with `null` when missing.
+pub fn optional_to_json(value: &Option<FieldValue>) -> Value {
+    value.as_ref().map_or(Value::Null, FieldValue::to_json)
+}
+
+/// Describes the JSON a field type is read from, for error messages.
+fn expected_shape(field_type: &FieldType) -> &'static str {
+    match field_type {
+        FieldType::Boolean => "a boolean",
+        FieldType::String => "a string",
+        FieldType::Integer => "an integer",
+        FieldType::Float => "a number",
+        FieldType::Currency => {
+            "an object like {\"amount\": \"100.50\", \"code\": \"USD\"} or a string like \"100.50 USD\""
+        }
+        FieldType::Reference => "a string like \"person.john\" or \"person.john.email\"",
+        FieldType::List => "an array",
+        FieldType::DateTime => {
+            "an RFC 3339 string like \"2025-01-15T17:00:00+03:00\" or \"2025-01-15T00:00:00Z\""
+        }
+        FieldType::Path => "a string",
+        FieldType::Enum => "a string or an object like {\"enum\": \"active\"}",
+    }
+}
+
+fn parse_currency(amount: &str, code: &str) -> Result<FieldValue, String> {
+    let amount = Decimal::from_str_exact(amount)
+        .map_err(|_| format!("'{}' is not a decimal amount", amount))?;
+    let currency =
+        Currency::from_code(code).ok_or_else(|| format!("'{}' is not a currency code", code))?;
+    Ok(FieldValue::Currency { amount, currency })
+}
+
+/// Reads `type.id` as an entity reference and `type.id.field` as a field reference.
+fn parse_reference(reference: &str) -> Result<FieldValue, String> {
+    let reference = match reference.split('.').collect::<Vec<_>>()[..] {
+        [entity_type, id] if !entity_type.is_empty() && !id.is_empty() => {
+            ReferenceValue::Entity(EntityId::new(reference))
+        }
+        [entity_type, id, field] if ![entity_type, id, field].contains(&"") => {
+            ReferenceValue::Field(compose_entity_id(entity_type, id), FieldId::new(field))
+        }
+        _ => {
+            return Err(format!(
+                "'{}' is not a reference like type.id or type.id.field",
+                reference
+            ));
+        }
+    };
+    Ok(FieldValue::Reference(reference))
+}
+
+/// Gets the value of an `{"enum": "value"}` object.
+fn tagged_enum(object: &Map<String, Value>) -> Option<&str> {
+    match object.get(ENUM_KEY) {
+        Some(Value::String(value)) if object.len() == 1 => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EntityType;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn read(value: &Value, field_type: &FieldType) -> Result<FieldValue, FieldJsonError> {
+        FieldValue::try_from_json(value, field_type, &FieldJsonContext::default())
+    }
+
+    fn list_of(item_type: Option<&FieldType>) -> FieldJsonContext<'_> {
+        FieldJsonContext {
+            item_type,
+            ..FieldJsonContext::default()
+        }
+    }
+
+    fn round_trip(value: FieldValue, item_type: Option<&FieldType>) {
+        let json = value.to_json();
+        let decoded =
+            FieldValue::try_from_json(&json, &value.get_type(), &list_of(item_type)).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn test_scalar_encodings() {
+        assert_eq!(FieldValue::Boolean(true).to_json(), json!(true));
+        assert_eq!(FieldValue::Integer(42).to_json(), json!(42));
+        assert_eq!(FieldValue::Float(1.5).to_json(), json!(1.5));
+        assert_eq!(FieldValue::String("Jane".into()).to_json(), json!("Jane"));
+        assert_eq!(
+            FieldValue::Path(PathBuf::from("./docs/plan.md")).to_json(),
+            json!("./docs/plan.md")
+        );
+        assert_eq!(
+            FieldValue::Enum("active".into()).to_json(),
+            json!({"enum": "active"})
+        );
+    }
+
+    #[test]
+    fn test_currency_encoding() {
+        let value = FieldValue::Currency {
+            amount: Decimal::new(10050, 2),
+            currency: Currency::USD,
+        };
+        assert_eq!(value.to_json(), json!({"amount": "100.50", "code": "USD"}));
+        round_trip(value.clone(), None);
+
+        // A number amount and the DSL-like string shorthand are read too
+        let number = json!({"amount": 100.50, "code": "USD"});
+        let decoded = read(&number, &FieldType::Currency).unwrap();
+        assert_eq!(decoded.to_string(), "100.5 USD");
+        let string = json!("100.50 USD");
+        assert_eq!(read(&string, &FieldType::Currency).unwrap(), value);
+    }
+
+    #[test]
+    fn test_datetime_encoding() {
+        let offset = FixedOffset::east_opt(3 * 3600).unwrap();
+        let value = FieldValue::DateTime(offset.with_ymd_and_hms(2025, 1, 15, 17, 0, 0).unwrap());
+        assert_eq!(value.to_json(), json!("2025-01-15T17:00:00+03:00"));
+        round_trip(value, None);
+
+        let error = read(&json!("2025-01-15"), &FieldType::DateTime).unwrap_err();
+        assert!(matches!(error, FieldJsonError::Invalid { .. }));
+    }
+
+    #[test]
+    fn test_reference_encoding() {
+        let entity = FieldValue::Reference(ReferenceValue::Entity(EntityId::new("person.john")));
+        assert_eq!(entity.to_json(), json!("person.john"));
+        round_trip(entity, None);
+
+        let field = FieldValue::Reference(ReferenceValue::Field(
+            EntityId::new("person.john"),
+            FieldId::new("email"),
+        ));
+        assert_eq!(field.to_json(), json!("person.john.email"));
+        round_trip(field, None);
+
+        assert!(read(&json!("john"), &FieldType::Reference).is_err());
+    }
+
+    #[test]
+    fn test_enum_accepts_plain_strings() {
+        let value = FieldValue::Enum("active".into());
+        round_trip(value.clone(), None);
+        assert_eq!(read(&json!("active"), &FieldType::Enum).unwrap(), value);
+    }
+
+    #[test]
+    fn test_list_encoding() {
+        let value = FieldValue::List(vec![
+            FieldValue::String("urgent".into()),
+            FieldValue::String("frontend".into()),
+        ]);
+        assert_eq!(value.to_json(), json!(["urgent", "frontend"]));
+        round_trip(value, Some(&FieldType::String));
+
+        // Items can only be read with an item type, unless there are none
+        let items = json!(["urgent"]);
+        assert_eq!(
+            read(&items, &FieldType::List),
+            Err(FieldJsonError::MissingItemType)
+        );
+        assert_eq!(
+            read(&json!([]), &FieldType::List).unwrap(),
+            FieldValue::List(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_mismatched_shapes() {
+        let error = read(&json!(1.5), &FieldType::Integer).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected an integer for field type Integer, got 1.5"
+        );
+        assert!(read(&json!("true"), &FieldType::Boolean).is_err());
+        assert!(read(&json!(["a"]), &FieldType::String).is_err());
+    }
+
+    #[test]
+    fn test_list_item_types_from_context() {
+        let items = json!([1, 2]);
+        let schema = FieldType::Integer;
+        let declared = FieldType::String;
+
+        // A declared item type is used when the schema has none
+        let context = FieldJsonContext {
+            declared_item_type: Some(&schema),
+            ..FieldJsonContext::default()
+        };
+        assert_eq!(
+            FieldValue::try_from_json(&items, &FieldType::List, &context).unwrap(),
+            FieldValue::List(vec![FieldValue::Integer(1), FieldValue::Integer(2)])
+        );
+
+        // And has to agree with the schema's otherwise
+        let context = FieldJsonContext {
+            field: Some("points"),
+            item_type: Some(&schema),
+            declared_item_type: Some(&declared),
+            ..FieldJsonContext::default()
+        };
+        let error = FieldValue::try_from_json(&items, &FieldType::List, &context).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Field 'points': The list holds Integer items in the schema, but String items were given"
+        );
+        assert!(matches!(
+            error.cause(),
+            FieldJsonError::ItemTypeConflict { .. }
+        ));
+    }
+
+    #[test]
+    fn test_paths_are_mapped_in_lists() {
+        let map_path = |path: &Path| Path::new("..").join(path);
+        let item_type = FieldType::Path;
+        let context = FieldJsonContext {
+            item_type: Some(&item_type),
+            map_path: Some(&map_path),
+            ..FieldJsonContext::default()
+        };
+
+        let value = FieldValue::try_from_json(&json!("a.md"), &FieldType::Path, &context).unwrap();
+        assert_eq!(value, FieldValue::Path(PathBuf::from("../a.md")));
+        let value =
+            FieldValue::try_from_json(&json!(["b.md"]), &FieldType::List, &context).unwrap();
+        assert_eq!(
+            value,
+            FieldValue::List(vec![FieldValue::Path(PathBuf::from("../b.md"))])
+        );
+    }
+
+    #[test]
+    fn test_entity_to_json() {
+        let entity = Entity::new(EntityId::new("task.review"), EntityType::new("task"))
+            .with_field(FieldId::new("done"), FieldValue::Boolean(false))
+            .with_field(
+                FieldId::new("title"),
+                FieldValue::String("Review".to_string()),
+            )
+            .with_field(FieldId::new("priority"), FieldValue::Integer(2))
+            .with_field(FieldId::new("estimate"), FieldValue::Float(1.5))
+            .with_field(
+                FieldId::new("budget"),
+                FieldValue::Currency {
+                    amount: Decimal::new(10050, 2),
+                    currency: Currency::EUR,
+                },
+            )
+            .with_field(
+                FieldId::new("owner"),
+                FieldValue::Reference(ReferenceValue::Entity(EntityId::new("person.jane"))),
+            )
+            .with_field(
+                FieldId::new("contact"),
+                FieldValue::Reference(ReferenceValue::Field(
+                    EntityId::new("person.jane"),
+                    FieldId::new("email"),
+                )),
+            )
+            .with_field(
+                FieldId::new("labels"),
+                FieldValue::List(vec![
+                    FieldValue::String("a".to_string()),
+                    FieldValue::String("b".to_string()),
+                ]),
+            )
+            .with_field(
+                FieldId::new("due"),
+                FieldValue::DateTime(
+                    DateTime::parse_from_rfc3339("2025-01-15T17:00:00+03:00").unwrap(),
+                ),
+            )
+            .with_field(
+                FieldId::new("spec"),
+                FieldValue::Path(PathBuf::from("docs/spec.md")),
+            )
+            .with_field(FieldId::new("status"), FieldValue::Enum("Open".to_string()))
+            .with_tags(["urgent"]);
+
+        assert_eq!(
+            entity.to_json(),
+            json!({
+                "id": "task.review",
+                "type": "task",
+                "fields": {
+                    "done": false,
+                    "title": "Review",
+                    "priority": 2,
+                    "estimate": 1.5,
+                    "budget": { "amount": "100.50", "code": "EUR" },
+                    "owner": "person.jane",
+                    "contact": "person.jane.email",
+                    "labels": ["a", "b"],
+                    "due": "2025-01-15T17:00:00+03:00",
+                    "spec": "docs/spec.md",
+                    "status": { "enum": "Open" },
+                },
+                "tags": ["urgent"],
+            })
+        );
+    }
+}
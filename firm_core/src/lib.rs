@@ -4,12 +4,20 @@
 //! business entities, their associated data and their relationships.
 
 pub mod entity;
+pub mod expression;
 pub mod field;
+pub mod format;
 pub mod graph;
 pub mod id;
+pub mod json;
 pub mod schema;
+pub mod suggest;
 
-pub use entity::Entity;
+pub use entity::{Entity, TAGS_FIELD_NAME};
+pub use expression::Expression;
 pub use field::{FieldType, FieldValue, ReferenceValue};
+pub use format::{CurrencyFormat, ValueFormat};
+pub use json::{FieldJsonContext, FieldJsonError};
 pub use id::{EntityId, EntityType, FieldId, compose_entity_id, decompose_entity_id};
 pub use schema::EntitySchema;
+pub use suggest::{closest, did_you_mean, suggest_similar};
@@ -0,0 +1,346 @@
+use serde::Serialize;
+
+use super::{EntitySchema, FieldSchema};
+use crate::{EntityType, FieldId, FieldType};
+
+/// A difference in one property of a field that both schemas declare.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "property", rename_all = "snake_case")]
+pub enum FieldChange {
+    Type {
+        from: FieldType,
+        to: FieldType,
+    },
+    ItemType {
+        from: Option<FieldType>,
+        to: Option<FieldType>,
+    },
+    Required {
+        from: bool,
+        to: bool,
+    },
+    AllowedValues {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// The expression of a computed field, as written in each schema.
+    Expression {
+        from: String,
+        to: String,
+    },
+}
+
+/// How a field differs between two schemas.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FieldDiff {
+    /// The field is only in the compared schema.
+    Added {
+        field: FieldId,
+        field_type: FieldType,
+    },
+    /// The field is only in the base schema.
+    Removed {
+        field: FieldId,
+        field_type: FieldType,
+    },
+    /// The field is in both schemas, with different definitions.
+    Changed {
+        field: FieldId,
+        changes: Vec<FieldChange>,
+    },
+    /// The computed field is only in the compared schema.
+    ComputedAdded { field: FieldId, expression: String },
+    /// The computed field is only in the base schema.
+    ComputedRemoved { field: FieldId, expression: String },
+}
+
+/// The field differences between two schemas for the same entity type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaDiff {
+    pub entity_type: EntityType,
+    pub fields: Vec<FieldDiff>,
+}
+
+impl SchemaDiff {
+    /// Checks whether the schemas declare the same fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+impl EntitySchema {
+    /// Compares this schema's fields against a base schema.
+    ///
+    /// Added fields are the ones only this schema declares. Field order, format,
+    /// reference targets and case sensitivity aren't compared. Computed fields
+    /// are compared by their expressions. Differences are listed in this
+    /// schema's field order, then its computed fields, followed by removed
+    /// fields and computed fields in the base's order.
+    pub fn diff(&self, base: &EntitySchema) -> SchemaDiff {
+        let mut fields = Vec::new();
+
+        for (field_id, field_schema) in self.ordered_fields() {
+            match base.fields.get(field_id) {
+                None => fields.push(FieldDiff::Added {
+                    field: field_id.clone(),
+                    field_type: field_schema.field_type.clone(),
+                }),
+                Some(base_schema) => {
+                    let changes = field_changes(base_schema, field_schema);
+                    if !changes.is_empty() {
+                        fields.push(FieldDiff::Changed {
+                            field: field_id.clone(),
+                            changes,
+                        });
+                    }
+                }
+            }
+        }
+
+        for (field_id, computed) in self.ordered_computed_fields() {
+            match base.computed.get(field_id) {
+                None => fields.push(FieldDiff::ComputedAdded {
+                    field: field_id.clone(),
+                    expression: computed.source.clone(),
+                }),
+                Some(base_computed) => {
+                    if base_computed.expression != computed.expression {
+                        fields.push(FieldDiff::Changed {
+                            field: field_id.clone(),
+                            changes: vec![FieldChange::Expression {
+                                from: base_computed.source.clone(),
+                                to: computed.source.clone(),
+                            }],
+                        });
+                    }
+                }
+            }
+        }
+
+        for (field_id, base_schema) in base.ordered_fields() {
+            if !self.fields.contains_key(field_id) {
+                fields.push(FieldDiff::Removed {
+                    field: field_id.clone(),
+                    field_type: base_schema.field_type.clone(),
+                });
+            }
+        }
+
+        for (field_id, base_computed) in base.ordered_computed_fields() {
+            if !self.computed.contains_key(field_id) {
+                fields.push(FieldDiff::ComputedRemoved {
+                    field: field_id.clone(),
+                    expression: base_computed.source.clone(),
+                });
+            }
+        }
+
+        SchemaDiff {
+            entity_type: self.entity_type.clone(),
+            fields,
+        }
+    }
+}
+
+/// Finds the property changes from one field definition to another.
+fn field_changes(from: &FieldSchema, to: &FieldSchema) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    if from.field_type != to.field_type {
+        changes.push(FieldChange::Type {
+            from: from.field_type.clone(),
+            to: to.field_type.clone(),
+        });
+    }
+
+    if from.item_type != to.item_type {
+        changes.push(FieldChange::ItemType {
+            from: from.item_type.clone(),
+            to: to.item_type.clone(),
+        });
+    }
+
+    if from.is_required() != to.is_required() {
+        changes.push(FieldChange::Required {
+            from: from.is_required(),
+            to: to.is_required(),
+        });
+    }
+
+    let from_values = from.allowed_values().map(Vec::as_slice).unwrap_or_default();
+    let to_values = to.allowed_values().map(Vec::as_slice).unwrap_or_default();
+    let added: Vec<String> = to_values
+        .iter()
+        .filter(|value| !from_values.contains(value))
+        .cloned()
+        .collect();
+    let removed: Vec<String> = from_values
+        .iter()
+        .filter(|value| !to_values.contains(value))
+        .cloned()
+        .collect();
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(FieldChange::AllowedValues { added, removed });
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::FieldMode;
+
+    fn base() -> EntitySchema {
+        EntitySchema::new(EntityType::new("project"))
+            .with_required_field(FieldId::new("name"), FieldType::String)
+            .with_optional_field(FieldId::new("budget"), FieldType::Currency)
+            .with_optional_enum(
+                FieldId::new("status"),
+                vec!["planning".to_string(), "active".to_string()],
+            )
+    }
+
+    #[test]
+    fn test_diff_identical_schemas_is_empty() {
+        assert!(base().diff(&base()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_added_and_removed_fields() {
+        let custom = EntitySchema::new(EntityType::new("project"))
+            .with_required_field(FieldId::new("name"), FieldType::String)
+            .with_optional_enum(
+                FieldId::new("status"),
+                vec!["planning".to_string(), "active".to_string()],
+            )
+            .with_optional_field(FieldId::new("repository"), FieldType::Path);
+
+        let diff = custom.diff(&base());
+        assert_eq!(
+            diff.fields,
+            vec![
+                FieldDiff::Added {
+                    field: FieldId::new("repository"),
+                    field_type: FieldType::Path,
+                },
+                FieldDiff::Removed {
+                    field: FieldId::new("budget"),
+                    field_type: FieldType::Currency,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_changed_fields() {
+        let custom = EntitySchema::new(EntityType::new("project"))
+            .with_required_field(FieldId::new("name"), FieldType::String)
+            .with_required_field(FieldId::new("budget"), FieldType::Float)
+            .with_optional_enum(
+                FieldId::new("status"),
+                vec!["active".to_string(), "archived".to_string()],
+            );
+
+        let diff = custom.diff(&base());
+        assert_eq!(
+            diff.fields,
+            vec![
+                FieldDiff::Changed {
+                    field: FieldId::new("budget"),
+                    changes: vec![
+                        FieldChange::Type {
+                            from: FieldType::Currency,
+                            to: FieldType::Float,
+                        },
+                        FieldChange::Required {
+                            from: false,
+                            to: true,
+                        },
+                    ],
+                },
+                FieldDiff::Changed {
+                    field: FieldId::new("status"),
+                    changes: vec![FieldChange::AllowedValues {
+                        added: vec!["archived".to_string()],
+                        removed: vec!["planning".to_string()],
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_changed_item_type() {
+        let list_field = |item_type: Option<FieldType>| {
+            let field_schema = FieldSchema::new(FieldType::List, FieldMode::Optional, 0);
+            let field_schema = match item_type {
+                Some(item_type) => field_schema.with_item_type(item_type),
+                None => field_schema,
+            };
+            EntitySchema::new(EntityType::new("project"))
+                .add_field_schema(FieldId::new("urls"), field_schema)
+        };
+
+        let diff = list_field(Some(FieldType::String)).diff(&list_field(None));
+        assert_eq!(
+            diff.fields,
+            vec![FieldDiff::Changed {
+                field: FieldId::new("urls"),
+                changes: vec![FieldChange::ItemType {
+                    from: None,
+                    to: Some(FieldType::String),
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_computed_fields() {
+        use crate::expression::{Expression, Operator};
+
+        let field = |name: &str| Expression::Field(FieldId::new(name));
+        let with_computed = |schema: EntitySchema, name: &str, operator, source: &str| {
+            schema.with_computed_field(
+                FieldId::new(name),
+                Expression::binary(operator, field("budget"), Expression::Integer(2)),
+                source,
+            )
+        };
+        let base = with_computed(base(), "half", Operator::Divide, "budget / 2");
+        let base = with_computed(base, "double", Operator::Multiply, "budget * 2");
+
+        // Same expression written differently isn't a change
+        let custom = with_computed(self::base(), "half", Operator::Divide, "budget/2");
+        let custom = with_computed(custom, "total", Operator::Add, "budget + 2");
+        let custom = with_computed(custom, "double", Operator::Add, "budget + 2");
+
+        let diff = custom.diff(&base);
+        assert_eq!(
+            diff.fields,
+            vec![
+                FieldDiff::ComputedAdded {
+                    field: FieldId::new("total"),
+                    expression: "budget + 2".to_string(),
+                },
+                FieldDiff::Changed {
+                    field: FieldId::new("double"),
+                    changes: vec![FieldChange::Expression {
+                        from: "budget * 2".to_string(),
+                        to: "budget + 2".to_string(),
+                    }],
+                },
+            ]
+        );
+
+        let diff = base.diff(&custom);
+        assert_eq!(
+            diff.fields[1],
+            FieldDiff::ComputedRemoved {
+                field: FieldId::new("total"),
+                expression: "budget + 2".to_string(),
+            }
+        );
+    }
+}
@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt::Display};
 
-use crate::{EntityType, FieldId, FieldType};
+use crate::{Entity, EntityType, Expression, FieldId, FieldType, FieldValue};
 
+mod diff;
 mod validation;
 mod validation_errors;
 
+pub use diff::{FieldChange, FieldDiff, SchemaDiff};
 pub use validation::ValidationResult;
 pub use validation_errors::{ValidationError, ValidationErrorType};
 
@@ -23,6 +25,20 @@ pub struct FieldSchema {
     pub field_mode: FieldMode,
     pub order: usize,
     pub allowed_values: Option<Vec<String>>,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Whether enum values must match the allowed values exactly, rather than ignoring case.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Whether currency amounts may have more decimals than the currency's minor unit.
+    #[serde(default)]
+    pub extra_precision: bool,
+    /// The type every item of a list field must have, if the schema declares one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<FieldType>,
+    /// The entity type a reference field should point at, if the schema declares one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<EntityType>,
 }
 
 impl FieldSchema {
@@ -32,24 +48,66 @@ impl FieldSchema {
             field_mode,
             order,
             allowed_values: None,
+            format: None,
+            case_sensitive: false,
+            extra_precision: false,
+            item_type: None,
+            target: None,
         }
     }
 
-    /// Creates a new enum field schema with allowed values (normalized to lowercase).
+    /// Creates a new enum field schema with allowed values.
+    ///
+    /// Values are trimmed but keep their declared casing, which is the casing
+    /// entity values are canonicalized to.
     pub fn new_enum(field_mode: FieldMode, order: usize, allowed_values: Vec<String>) -> Self {
-        let normalized_values: Vec<String> = allowed_values
-            .iter()
-            .map(|v| v.trim().to_lowercase())
-            .collect();
+        let normalized_values: Vec<String> =
+            allowed_values.iter().map(|v| v.trim().to_string()).collect();
 
         FieldSchema {
             field_type: FieldType::Enum,
             field_mode,
             order,
             allowed_values: Some(normalized_values),
+            format: None,
+            case_sensitive: false,
+            extra_precision: false,
+            item_type: None,
+            target: None,
         }
     }
 
+    /// Sets a format hint for the field's content (e.g. "firm-query").
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    /// Makes enum values match the allowed values exactly, rather than ignoring case.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Lets currency amounts have more decimals than the currency's minor unit,
+    /// like fractions of a cent in exchange rates.
+    pub fn with_extra_precision(mut self, extra_precision: bool) -> Self {
+        self.extra_precision = extra_precision;
+        self
+    }
+
+    /// Sets the type every item of a list field must have.
+    pub fn with_item_type(mut self, item_type: FieldType) -> Self {
+        self.item_type = Some(item_type);
+        self
+    }
+
+    /// Sets the entity type a reference field should point at.
+    pub fn with_target(mut self, target: EntityType) -> Self {
+        self.target = Some(target);
+        self
+    }
+
     /// Get the expected field type.
     pub fn expected_type(&self) -> &FieldType {
         &self.field_type
@@ -64,6 +122,61 @@ impl FieldSchema {
     pub fn allowed_values(&self) -> Option<&Vec<String>> {
         self.allowed_values.as_ref()
     }
+
+    /// Get the type of a list field's items, if declared.
+    pub fn item_type(&self) -> Option<&FieldType> {
+        self.item_type.as_ref()
+    }
+
+    /// Get the entity type a reference field should point at, if declared.
+    pub fn target(&self) -> Option<&EntityType> {
+        self.target.as_ref()
+    }
+
+    /// Get the format hint for the field's content, if any.
+    pub fn format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+
+    /// Finds the allowed value an enum value matches, in its declared casing.
+    ///
+    /// Surrounding whitespace is ignored. Case is ignored too, unless the field
+    /// is case-sensitive. Returns None for values that aren't allowed.
+    pub fn canonical_enum_value(&self, value: &str) -> Option<&str> {
+        let value = value.trim();
+        self.allowed_values()?
+            .iter()
+            .find(|allowed| {
+                if self.case_sensitive {
+                    allowed.as_str() == value
+                } else {
+                    allowed.to_lowercase() == value.to_lowercase()
+                }
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Defines a field whose value is computed from an entity's other fields.
+///
+/// Computed fields are read-only: they're set when an entity is built, and
+/// left unset if the expression can't be evaluated for the entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComputedField {
+    pub expression: Expression,
+    /// The expression as written in the schema.
+    pub source: String,
+    pub order: usize,
+}
+
+impl ComputedField {
+    pub fn new(expression: Expression, source: impl Into<String>, order: usize) -> Self {
+        ComputedField {
+            expression,
+            source: source.into(),
+            order,
+        }
+    }
 }
 
 /// Defines the schema for an entity type.
@@ -71,6 +184,9 @@ impl FieldSchema {
 pub struct EntitySchema {
     pub entity_type: EntityType,
     pub fields: HashMap<FieldId, FieldSchema>,
+    /// Fields computed from the entity's other fields when it's built.
+    #[serde(default)]
+    pub computed: HashMap<FieldId, ComputedField>,
     insertion_order: u16,
 }
 
@@ -80,6 +196,7 @@ impl EntitySchema {
         Self {
             entity_type,
             fields: HashMap::new(),
+            computed: HashMap::new(),
             insertion_order: 0,
         }
     }
@@ -126,6 +243,19 @@ impl EntitySchema {
         )
     }
 
+    /// Builder method to add a computed field preserving insertion order.
+    pub fn with_computed_field(
+        mut self,
+        id: FieldId,
+        expression: Expression,
+        source: impl Into<String>,
+    ) -> Self {
+        let order = self.next_order();
+        self.computed
+            .insert(id, ComputedField::new(expression, source, order));
+        self
+    }
+
     /// Builder method to add common metadata fields to the schema.
     pub fn with_metadata(self) -> Self {
         self.with_raw_field(
@@ -142,6 +272,49 @@ impl EntitySchema {
         )
     }
 
+    /// Rewrites an entity's enum values to the casing declared in the schema.
+    ///
+    /// Values that aren't allowed are left as they are, for validation to report.
+    pub fn canonicalize(&self, entity: &mut Entity) {
+        for (field_id, value) in &mut entity.fields {
+            let Some(field_schema) = self.fields.get(field_id) else {
+                continue;
+            };
+
+            if let FieldValue::Enum(enum_value) = value
+                && let Some(canonical) = field_schema.canonical_enum_value(enum_value)
+            {
+                *enum_value = canonical.to_string();
+            }
+        }
+    }
+
+    /// Sets an entity's computed fields from its other fields.
+    ///
+    /// Fields are computed in the order they're declared, so a computed field
+    /// can use the ones before it. A field whose expression can't be evaluated,
+    /// like when a field it uses is missing, is left unset.
+    pub fn compute(&self, entity: &mut Entity) {
+        for (field_id, computed) in self.ordered_computed_fields() {
+            entity.fields.retain(|(id, _)| id != field_id);
+            if let Some(value) = computed.expression.evaluate(entity) {
+                entity.fields.push((field_id.clone(), value));
+            }
+        }
+    }
+
+    /// Check if a field is computed rather than written.
+    pub fn is_computed(&self, field_id: &FieldId) -> bool {
+        self.computed.contains_key(field_id)
+    }
+
+    /// Get computed fields sorted by their order.
+    pub fn ordered_computed_fields(&self) -> Vec<(&FieldId, &ComputedField)> {
+        let mut ordered: Vec<_> = self.computed.iter().collect();
+        ordered.sort_by_key(|&(_, computed)| computed.order);
+        ordered
+    }
+
     /// Get schema fields sorted by their order.
     pub fn ordered_fields(&self) -> Vec<(&FieldId, &FieldSchema)> {
         let mut ordered: Vec<_> = self.fields.iter().collect();
@@ -151,7 +324,7 @@ impl EntitySchema {
 
     /// Gets the next order for a field, preserving insertion order.
     fn next_order(&self) -> usize {
-        self.fields.len()
+        self.fields.len() + self.computed.len()
     }
 }
 
@@ -162,10 +335,30 @@ impl Display for EntitySchema {
         for (field_id, field_schema) in &self.ordered_fields() {
             writeln!(f, "\n{}", field_id)?;
             writeln!(f, "- Type: {}", field_schema.expected_type())?;
+            if let Some(item_type) = field_schema.item_type() {
+                writeln!(f, "- Item type: {}", item_type)?;
+            }
             writeln!(f, "- Required: {}", field_schema.is_required())?;
             if let Some(allowed_values) = field_schema.allowed_values() {
                 writeln!(f, "- Allowed values: {}", allowed_values.join(", "))?;
             }
+            if field_schema.case_sensitive {
+                writeln!(f, "- Case-sensitive: true")?;
+            }
+            if field_schema.extra_precision {
+                writeln!(f, "- Extra precision: true")?;
+            }
+            if let Some(format) = field_schema.format() {
+                writeln!(f, "- Format: {}", format)?;
+            }
+            if let Some(target) = field_schema.target() {
+                writeln!(f, "- Target: {}", target)?;
+            }
+        }
+
+        for (field_id, computed) in &self.ordered_computed_fields() {
+            writeln!(f, "\n{}", field_id)?;
+            writeln!(f, "- Computed: {}", computed.source)?;
         }
 
         Ok(())
@@ -175,6 +368,7 @@ impl Display for EntitySchema {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::EntityId;
 
     #[test]
     fn test_schema_create_new() {
@@ -191,4 +385,106 @@ mod tests {
         assert_eq!(email_field.field_type, FieldType::String);
         assert_eq!(email_field.field_mode, FieldMode::Optional);
     }
+
+    #[test]
+    fn test_schema_field_format() {
+        let schema = EntitySchema::new(EntityType::new("report")).with_raw_field(
+            FieldId::new("query"),
+            FieldSchema::new(FieldType::String, FieldMode::Required, 0).with_format("firm-query"),
+        );
+
+        let query_field = &schema.fields[&FieldId::new("query")];
+        assert_eq!(query_field.format(), Some("firm-query"));
+        assert!(schema.to_string().contains("- Format: firm-query"));
+    }
+
+    #[test]
+    fn test_schema_compute_fields() {
+        use crate::expression::Operator;
+
+        let field = |name: &str| Expression::Field(FieldId::new(name));
+        let schema = EntitySchema::new(EntityType::new("goal"))
+            .with_required_field(FieldId::new("current_value"), FieldType::Integer)
+            .with_optional_field(FieldId::new("target_value"), FieldType::Integer)
+            .with_computed_field(
+                FieldId::new("progress"),
+                Expression::binary(
+                    Operator::Divide,
+                    field("current_value"),
+                    field("target_value"),
+                ),
+                "current_value / target_value",
+            )
+            .with_computed_field(
+                FieldId::new("percent"),
+                Expression::binary(
+                    Operator::Multiply,
+                    field("progress"),
+                    Expression::Integer(100),
+                ),
+                "progress * 100",
+            );
+        assert!(schema.is_computed(&FieldId::new("progress")));
+        assert!(!schema.is_computed(&FieldId::new("current_value")));
+        assert!(schema.to_string().contains("- Computed: progress * 100"));
+
+        // Later computed fields can use earlier ones
+        let mut entity = Entity::new(EntityId::new("launch"), EntityType::new("goal"))
+            .with_field(FieldId::new("current_value"), 1)
+            .with_field(FieldId::new("target_value"), 4);
+        schema.compute(&mut entity);
+        assert_eq!(
+            entity.get_field(&FieldId::new("progress")),
+            Some(&FieldValue::Float(0.25))
+        );
+        assert_eq!(
+            entity.get_field(&FieldId::new("percent")),
+            Some(&FieldValue::Float(25.0))
+        );
+
+        // Missing fields leave the computed fields unset
+        let mut entity = Entity::new(EntityId::new("launch"), EntityType::new("goal"))
+            .with_field(FieldId::new("current_value"), 1);
+        schema.compute(&mut entity);
+        assert_eq!(entity.get_field(&FieldId::new("progress")), None);
+        assert_eq!(entity.get_field(&FieldId::new("percent")), None);
+    }
+
+    #[test]
+    fn test_canonical_enum_value() {
+        let field = FieldSchema::new_enum(
+            FieldMode::Required,
+            0,
+            vec![" Active ".to_string(), "On Hold".to_string()],
+        );
+        assert_eq!(field.canonical_enum_value("active"), Some("Active"));
+        assert_eq!(field.canonical_enum_value("  ON HOLD "), Some("On Hold"));
+        assert_eq!(field.canonical_enum_value("closed"), None);
+
+        let field = field.with_case_sensitive(true);
+        assert_eq!(field.canonical_enum_value("Active"), Some("Active"));
+        assert_eq!(field.canonical_enum_value("active"), None);
+    }
+
+    #[test]
+    fn test_schema_canonicalize_enum_values() {
+        let schema = EntitySchema::new(EntityType::new("account"))
+            .with_required_field(FieldId::new("name"), FieldType::String)
+            .with_required_enum(FieldId::new("status"), vec!["Active".to_string()]);
+
+        let mut entity = Entity::new(EntityId::new("acme"), EntityType::new("account"))
+            .with_field(FieldId::new("name"), "active")
+            .with_field(FieldId::new("status"), FieldValue::Enum("ACTIVE".to_string()));
+        schema.canonicalize(&mut entity);
+
+        // Only enum values are rewritten
+        assert_eq!(
+            entity.get_field(&FieldId::new("status")),
+            Some(&FieldValue::Enum("Active".to_string()))
+        );
+        assert_eq!(
+            entity.get_field(&FieldId::new("name")),
+            Some(&FieldValue::String("active".to_string()))
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use log::debug;
 
 use super::{EntitySchema, ValidationError};
-use crate::Entity;
+use crate::{Entity, EntityId, FieldId, FieldValue};
 
 pub type ValidationResult = Result<(), Vec<ValidationError>>;
 
@@ -37,11 +37,30 @@ impl EntitySchema {
                             expected_type,
                             &field_value.get_type(),
                         ));
+                    } else if let crate::field::FieldValue::List(items) = field_value {
+                        // For list fields, validate each item against the item type
+                        if let Some(item_type) = field_schema.item_type() {
+                            for (index, item) in items.iter().enumerate() {
+                                if !item.is_type(item_type) {
+                                    errors.push(ValidationError::mismatched_list_item_type(
+                                        &entity.id,
+                                        field_name,
+                                        index,
+                                        item_type,
+                                        &item.get_type(),
+                                    ));
+                                }
+                            }
+                        }
+                        if !field_schema.extra_precision {
+                            errors.extend(items.iter().filter_map(|item| {
+                                excess_precision_error(&entity.id, field_name, item)
+                            }));
+                        }
                     } else if let crate::field::FieldValue::Enum(value) = field_value {
                         // For enum fields, validate against allowed values
                         if let Some(allowed_values) = field_schema.allowed_values() {
-                            let normalized_value = value.trim().to_lowercase();
-                            if !allowed_values.contains(&normalized_value) {
+                            if field_schema.canonical_enum_value(value).is_none() {
                                 errors.push(ValidationError::invalid_enum_value(
                                     &entity.id,
                                     field_name,
@@ -57,17 +76,35 @@ impl EntitySchema {
                                 &[],
                             ));
                         }
+                    } else if !field_schema.extra_precision
+                        && let Some(error) =
+                            excess_precision_error(&entity.id, field_name, field_value)
+                    {
+                        errors.push(error);
                     }
                 }
                 // Entity does not have the field: Check if it's required
                 None => {
                     if field_schema.is_required() {
-                        errors.push(ValidationError::missing_field(&entity.id, field_name));
+                        if entity.is_unset(field_name) {
+                            errors.push(ValidationError::unset_required_field(
+                                &entity.id, field_name,
+                            ));
+                        } else {
+                            errors.push(ValidationError::missing_field(&entity.id, field_name));
+                        }
                     }
                 }
             }
         }
 
+        // Computed fields are set when the entity is built, not written
+        for field_name in self.computed.keys() {
+            if entity.get_field(field_name).is_some() {
+                errors.push(ValidationError::computed_field_set(&entity.id, field_name));
+            }
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -81,15 +118,41 @@ impl EntitySchema {
     }
 }
 
+/// Checks that a currency amount has no more decimals than the currency's minor unit.
+///
+/// Trailing zeros don't count, so `100.500 USD` is as precise as `100.50 USD`.
+/// Currencies without a minor unit, like gold, take any number of decimals.
+fn excess_precision_error(
+    entity_id: &EntityId,
+    field_id: &FieldId,
+    value: &FieldValue,
+) -> Option<ValidationError> {
+    let FieldValue::Currency { amount, currency } = value else {
+        return None;
+    };
+    let allowed = u32::from(currency.exponent()?);
+    let decimals = amount.normalize().scale();
+    (decimals > allowed).then(|| {
+        ValidationError::excess_currency_precision(
+            entity_id,
+            field_id,
+            &value.to_string(),
+            decimals,
+            allowed,
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::ValidationErrorType;
+    use crate::schema::{FieldMode, FieldSchema, ValidationErrorType};
     use crate::{
         EntityId, EntityType, FieldId,
         field::{FieldType, FieldValue},
     };
     use assert_matches::assert_matches;
+    use iso_currency::Currency;
 
     #[test]
     fn test_validate_ok() {
@@ -151,6 +214,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_error_unset_required_field() {
+        let schema = EntitySchema::new(EntityType::new("person"))
+            .with_required_field(FieldId::new("name"), FieldType::String)
+            .with_optional_field(FieldId::new("email"), FieldType::String);
+
+        let entity = Entity::new(EntityId::new("test_person"), EntityType::new("person"))
+            .with_unset_field(FieldId::new("name"))
+            .with_unset_field(FieldId::new("email"));
+
+        let errors = schema.validate(&entity).unwrap_err();
+
+        // Unsetting an optional field is fine
+        assert_eq!(errors.len(), 1);
+        assert_matches!(
+            &errors[0].error_type,
+            ValidationErrorType::UnsetRequiredField { required } if required == &FieldId::new("name")
+        );
+    }
+
     #[test]
     fn test_validate_error_mismatched_field_types() {
         let schema = EntitySchema::new(EntityType::new("person"))
@@ -175,6 +258,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_list_items_against_item_type() {
+        let mut schema = EntitySchema::new(EntityType::new("project"));
+        schema.fields.insert(
+            FieldId::new("urls"),
+            FieldSchema::new(FieldType::List, FieldMode::Required, 0)
+                .with_item_type(FieldType::String),
+        );
+
+        let valid = Entity::new(EntityId::new("test_project"), EntityType::new("project"))
+            .with_field(
+                FieldId::new("urls"),
+                FieldValue::List(vec![FieldValue::String("https://firm.dev".to_string())]),
+            );
+        assert!(schema.validate(&valid).is_ok());
+
+        let invalid = Entity::new(EntityId::new("test_project"), EntityType::new("project"))
+            .with_field(
+                FieldId::new("urls"),
+                FieldValue::List(vec![
+                    FieldValue::String("https://firm.dev".to_string()),
+                    FieldValue::Integer(42),
+                ]),
+            );
+        let errors = schema.validate(&invalid).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_matches!(
+            &errors[0].error_type,
+            ValidationErrorType::MismatchedListItemType { index: 1, expected, actual }
+            if expected == &FieldType::String && actual == &FieldType::Integer
+        );
+    }
+
     #[test]
     fn test_validate_enum_with_valid_value() {
         let schema = EntitySchema::new(EntityType::new("account")).with_required_enum(
@@ -213,6 +329,46 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_validate_enum_with_declared_casing() {
+        let schema = EntitySchema::new(EntityType::new("account")).with_required_enum(
+            FieldId::new("status"),
+            vec!["Active".to_string(), "Closed".to_string()],
+        );
+
+        let entity = Entity::new(EntityId::new("test_account"), EntityType::new("account"))
+            .with_field(
+                FieldId::new("status"),
+                FieldValue::Enum("active".to_string()),
+            );
+
+        let result = schema.validate(&entity);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_case_sensitive_enum_rejects_other_casing() {
+        let mut schema = EntitySchema::new(EntityType::new("account"));
+        schema.fields.insert(
+            FieldId::new("status"),
+            FieldSchema::new_enum(FieldMode::Required, 0, vec!["Active".to_string()])
+                .with_case_sensitive(true),
+        );
+
+        let entity = Entity::new(EntityId::new("test_account"), EntityType::new("account"))
+            .with_field(
+                FieldId::new("status"),
+                FieldValue::Enum("active".to_string()),
+            );
+
+        let result = schema.validate(&entity);
+        assert!(result.is_err());
+        assert_matches!(
+            &result.unwrap_err()[0].error_type,
+            ValidationErrorType::InvalidEnumValue { actual, .. } if actual == "active"
+        );
+    }
+
     #[test]
     fn test_validate_enum_with_whitespace_trimmed() {
         let schema = EntitySchema::new(EntityType::new("account")).with_required_enum(
@@ -299,4 +455,79 @@ mod tests {
             ValidationErrorType::InvalidEnumValue { actual, .. } if actual == "invalid"
         );
     }
+
+    #[test]
+    fn test_validate_error_computed_field_set() {
+        let schema = EntitySchema::new(EntityType::new("goal"))
+            .with_required_field(FieldId::new("current_value"), FieldType::Integer)
+            .with_computed_field(FieldId::new("doubled"), crate::Expression::Integer(2), "2");
+
+        let entity = Entity::new(EntityId::new("launch"), EntityType::new("goal"))
+            .with_field(FieldId::new("current_value"), 1)
+            .with_field(FieldId::new("doubled"), 2);
+
+        let errors = schema.validate(&entity).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_matches!(
+            &errors[0].error_type,
+            ValidationErrorType::ComputedFieldSet { computed } if computed.as_str() == "doubled"
+        );
+    }
+
+    #[test]
+    fn test_validate_currency_precision() {
+        let mut schema = EntitySchema::new(EntityType::new("invoice"));
+        schema.fields.insert(
+            FieldId::new("amount"),
+            FieldSchema::new(FieldType::Currency, FieldMode::Required, 0),
+        );
+        schema.fields.insert(
+            FieldId::new("fees"),
+            FieldSchema::new(FieldType::List, FieldMode::Optional, 1)
+                .with_item_type(FieldType::Currency),
+        );
+        let money = |amount: &str, currency| FieldValue::Currency {
+            amount: amount.parse().unwrap(),
+            currency,
+        };
+        let invoice = |amount: FieldValue, fees: Vec<FieldValue>| {
+            Entity::new(EntityId::new("march"), EntityType::new("invoice"))
+                .with_field(FieldId::new("amount"), amount)
+                .with_field(FieldId::new("fees"), FieldValue::List(fees))
+        };
+
+        // Trailing zeros don't add precision
+        let valid = invoice(
+            money("100.500", Currency::USD),
+            vec![money("1500", Currency::JPY)],
+        );
+        assert!(schema.validate(&valid).is_ok());
+
+        let invalid = invoice(
+            money("100.555", Currency::USD),
+            vec![money("1500.5", Currency::JPY)],
+        );
+        let errors = schema.validate(&invalid).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(|error| matches!(
+            error.error_type,
+            ValidationErrorType::ExcessCurrencyPrecision {
+                decimals: 3,
+                allowed: 2
+            }
+        ) && error.message.contains("100.555 USD")));
+        assert!(errors.iter().any(|error| matches!(
+            error.error_type,
+            ValidationErrorType::ExcessCurrencyPrecision {
+                decimals: 1,
+                allowed: 0
+            }
+        )));
+
+        // Fields can opt into extra precision
+        for field_schema in schema.fields.values_mut() {
+            field_schema.extra_precision = true;
+        }
+        assert!(schema.validate(&invalid).is_ok());
+    }
 }
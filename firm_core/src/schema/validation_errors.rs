@@ -10,16 +10,28 @@ pub enum ValidationErrorType {
     },
     /// The entity is missing a required field.
     MissingRequiredField { required: FieldId },
+    /// The entity explicitly unset a required field.
+    UnsetRequiredField { required: FieldId },
     /// The entity has a field whose type did not match the schema.
     MismatchedFieldType {
         expected: FieldType,
         actual: FieldType,
     },
+    /// An item of a list field did not match the item type in the schema.
+    MismatchedListItemType {
+        index: usize,
+        expected: FieldType,
+        actual: FieldType,
+    },
     /// The enum field has a value that is not in the allowed values.
     InvalidEnumValue {
         actual: String,
         allowed: Vec<String>,
     },
+    /// The entity set a field that the schema computes.
+    ComputedFieldSet { computed: FieldId },
+    /// A currency amount has more decimals than the currency's minor unit.
+    ExcessCurrencyPrecision { decimals: u32, allowed: u32 },
 }
 
 /// Information about an error encountered while validating a schema.
@@ -67,6 +79,21 @@ impl ValidationError {
         }
     }
 
+    /// Shorthand for creating an explicitly unset required field error.
+    pub fn unset_required_field(entity_id: &EntityId, field_id: &FieldId) -> Self {
+        Self {
+            entity_id: Some(entity_id.clone()),
+            field: Some(field_id.clone()),
+            message: format!(
+                "Required field '{}' for entity '{}' can't be unset",
+                field_id, entity_id
+            ),
+            error_type: ValidationErrorType::UnsetRequiredField {
+                required: field_id.clone(),
+            },
+        }
+    }
+
     /// Shorthand for creating a mismatched field type error.
     pub fn mismatched_field_type(
         entity_id: &EntityId,
@@ -88,6 +115,33 @@ impl ValidationError {
         }
     }
 
+    /// Shorthand for creating a mismatched list item type error.
+    pub fn mismatched_list_item_type(
+        entity_id: &EntityId,
+        field_id: &FieldId,
+        index: usize,
+        expected: &FieldType,
+        actual: &FieldType,
+    ) -> Self {
+        Self {
+            entity_id: Some(entity_id.clone()),
+            field: Some(field_id.clone()),
+            message: format!(
+                "Expected item {} of list field '{}' for entity '{}' to be of type '{}' but it was '{}'",
+                index + 1,
+                field_id,
+                entity_id,
+                expected,
+                actual
+            ),
+            error_type: ValidationErrorType::MismatchedListItemType {
+                index,
+                expected: expected.clone(),
+                actual: actual.clone(),
+            },
+        }
+    }
+
     /// Shorthand for creating an invalid enum value error.
     pub fn invalid_enum_value(
         entity_id: &EntityId,
@@ -111,4 +165,39 @@ impl ValidationError {
             },
         }
     }
+
+    /// Shorthand for creating an error for setting a computed field.
+    pub fn computed_field_set(entity_id: &EntityId, field_id: &FieldId) -> Self {
+        Self {
+            entity_id: Some(entity_id.clone()),
+            field: Some(field_id.clone()),
+            message: format!(
+                "Field '{}' for entity '{}' is computed by its schema and can't be set",
+                field_id, entity_id
+            ),
+            error_type: ValidationErrorType::ComputedFieldSet {
+                computed: field_id.clone(),
+            },
+        }
+    }
+
+    /// Shorthand for creating an error for a currency amount with too many decimals.
+    pub fn excess_currency_precision(
+        entity_id: &EntityId,
+        field_id: &FieldId,
+        amount: &str,
+        decimals: u32,
+        allowed: u32,
+    ) -> Self {
+        Self {
+            entity_id: Some(entity_id.clone()),
+            field: Some(field_id.clone()),
+            message: format!(
+                "Currency amount '{}' in field '{}' for entity '{}' has {} decimals but the currency has {}. \
+                 Set 'extra_precision = true' on the schema field to allow more",
+                amount, field_id, entity_id, decimals, allowed
+            ),
+            error_type: ValidationErrorType::ExcessCurrencyPrecision { decimals, allowed },
+        }
+    }
 }
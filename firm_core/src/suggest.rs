@@ -0,0 +1,131 @@
+//! Suggestions for mistyped names, like entity IDs that don't exist.
+
+/// The most suggestions to give for a mistyped name.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// The most edits a suggestion can be from the mistyped name.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// Finds the candidates closest to a mistyped name, by edit distance.
+///
+/// Names are compared ignoring case. A candidate can be at most one edit per
+/// three characters of the name away (and at least one edit for short names),
+/// up to three edits. The closest candidates come first, ties in name order,
+/// and at most `MAX_SUGGESTIONS` are returned.
+pub fn suggest_similar<I, S>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let max_distance = (name.chars().count() / 3).clamp(1, MAX_SUGGESTION_DISTANCE);
+
+    by_distance(name, candidates)
+        .into_iter()
+        .filter(|(distance, _)| *distance <= max_distance)
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Finds the `count` candidates closest to a name by edit distance, however far off they are.
+///
+/// Unlike `suggest_similar`, this always returns candidates when there are
+/// any, for listing what's there instead of guessing at a typo.
+pub fn closest<I, S>(name: &str, candidates: I, count: usize) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    by_distance(name, candidates)
+        .into_iter()
+        .take(count)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Pairs unique candidates with their edit distance from a name ignoring case, closest first.
+fn by_distance<I, S>(name: &str, candidates: I) -> Vec<(usize, String)>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let name = name.to_lowercase();
+    let mut matches: Vec<(usize, String)> = candidates
+        .into_iter()
+        .map(|candidate| candidate.as_ref().to_string())
+        .map(|candidate| {
+            let distance = strsim::levenshtein(&name, &candidate.to_lowercase());
+            (distance, candidate)
+        })
+        .collect();
+
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches
+}
+
+/// Formats suggestions as a question to add to an error, like "Did you mean 'a' or 'b'?".
+///
+/// Returns None if there are no suggestions.
+pub fn did_you_mean(suggestions: &[String]) -> Option<String> {
+    let quoted: Vec<String> = suggestions
+        .iter()
+        .map(|suggestion| format!("'{}'", suggestion))
+        .collect();
+
+    match quoted.as_slice() {
+        [] => None,
+        [only] => Some(format!("Did you mean {}?", only)),
+        [rest @ .., last] => Some(format!("Did you mean {} or {}?", rest.join(", "), last)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_similar() {
+        let ids = ["john_doe", "jane_doe", "jon_snow", "acme", "John_Doe"];
+
+        assert_eq!(
+            suggest_similar("jon_doe", ids),
+            vec!["John_Doe", "john_doe", "jane_doe"]
+        );
+        assert_eq!(suggest_similar("acne", ids), vec!["acme"]);
+        assert!(suggest_similar("someone_else", ids).is_empty());
+        assert!(suggest_similar("jon_doe", Vec::<String>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_closest() {
+        let ids = ["john_doe", "jane_doe", "acme", "john_doe", "globex"];
+
+        assert_eq!(closest("jon_doe", ids, 2), vec!["john_doe", "jane_doe"]);
+        // Far-off candidates are still listed, closest first
+        assert_eq!(
+            closest("someone_else", ids, 5),
+            vec!["jane_doe", "john_doe", "acme", "globex"]
+        );
+        assert!(closest("jon_doe", Vec::<String>::new(), 5).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_similar_limits_and_deduplicates() {
+        let ids = ["ab", "ac", "ad", "ae", "ab"];
+        assert_eq!(suggest_similar("aa", ids), vec!["ab", "ac", "ad"]);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean(&[]), None);
+        assert_eq!(
+            did_you_mean(&["a".to_string()]),
+            Some("Did you mean 'a'?".to_string())
+        );
+        assert_eq!(
+            did_you_mean(&["a".to_string(), "b".to_string(), "c".to_string()]),
+            Some("Did you mean 'a', 'b' or 'c'?".to_string())
+        );
+    }
+}
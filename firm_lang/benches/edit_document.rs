@@ -0,0 +1,50 @@
+//! Benchmarks reparsing a large document after a keystroke.
+//!
+//! Compares editing an open document, which reparses incrementally, with
+//! parsing the whole text again. Run with `cargo bench -p firm_lang`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use firm_lang::parser::dsl::{Document, TextChange, parse_source};
+use tree_sitter::Point;
+
+const NUM_ENTITIES: usize = 1000;
+
+/// Creates a document of a few thousand lines.
+fn create_source() -> String {
+    let mut source = String::new();
+    for index in 0..NUM_ENTITIES {
+        source.push_str(&format!(
+            "task task_{} {{\n    name = \"Task {}\"\n    priority = {}\n    is_completed = false\n}}\n\n",
+            index, index, index
+        ));
+    }
+    source
+}
+
+fn bench_edit_document(c: &mut Criterion) {
+    let source = create_source();
+    // Inside the name of an entity in the middle of the document
+    let row = NUM_ENTITIES / 2 * 6 + 1;
+    let position = Point::new(row, 16);
+
+    c.bench_function("keystroke_incremental", |b| {
+        let mut document = Document::new(source.clone(), None).unwrap();
+        b.iter(|| {
+            document
+                .apply_changes(&[TextChange::replace(position, position, "x")])
+                .unwrap();
+        })
+    });
+
+    c.bench_function("keystroke_full_reparse", |b| {
+        let mut source = source.clone();
+        let offset = source.match_indices('\n').nth(row - 1).unwrap().0 + 1 + 16;
+        b.iter(|| {
+            source.insert(offset, 'x');
+            parse_source(source.clone(), None).unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_edit_document);
+criterion_main!(benches);
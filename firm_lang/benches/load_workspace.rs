@@ -0,0 +1,79 @@
+//! Benchmarks loading and building a synthetic workspace.
+//!
+//! Run with `cargo bench -p firm_lang`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use firm_lang::workspace::Workspace;
+use tempfile::TempDir;
+
+const NUM_FILES: usize = 400;
+const ENTITIES_PER_FILE: usize = 10;
+
+/// Creates a workspace with one schema file and many entity files.
+fn create_workspace() -> TempDir {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+
+    let schema = r#"schema task {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+    field {
+        name = "priority"
+        type = "integer"
+        required = false
+    }
+    field {
+        name = "is_completed"
+        type = "boolean"
+        required = false
+    }
+}
+"#;
+    fs::write(dir.path().join("schemas.firm"), schema).expect("Failed to write schema");
+
+    for file_index in 0..NUM_FILES {
+        let mut content = String::new();
+        for entity_index in 0..ENTITIES_PER_FILE {
+            content.push_str(&format!(
+                "task task_{}_{} {{\n    name = \"Task {} {}\"\n    priority = {}\n    is_completed = false\n}}\n\n",
+                file_index, entity_index, file_index, entity_index, entity_index
+            ));
+        }
+
+        let sub_dir = dir.path().join(format!("group_{}", file_index % 20));
+        fs::create_dir_all(&sub_dir).expect("Failed to create dir");
+        fs::write(sub_dir.join(format!("tasks_{}.firm", file_index)), content)
+            .expect("Failed to write file");
+    }
+
+    dir
+}
+
+fn bench_load_workspace(c: &mut Criterion) {
+    let dir = create_workspace();
+    let path = PathBuf::from(dir.path());
+
+    c.bench_function("load_directory", |b| {
+        b.iter(|| {
+            let mut workspace = Workspace::new();
+            workspace.load_directory(&path).unwrap();
+            workspace
+        })
+    });
+
+    c.bench_function("load_and_build", |b| {
+        b.iter(|| {
+            let mut workspace = Workspace::new();
+            workspace.load_directory(&path).unwrap();
+            workspace.build().unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_load_workspace);
+criterion_main!(benches);
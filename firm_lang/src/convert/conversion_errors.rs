@@ -1,5 +1,9 @@
 use std::fmt;
 
+use firm_core::field::FieldType;
+
+use crate::parser::dsl::ValueParseError;
+
 /// Errors that can occur when converting a parsed entity.
 #[derive(Debug)]
 pub enum EntityConversionError {
@@ -7,6 +11,10 @@ pub enum EntityConversionError {
     MissingEntityId,
     MissingFieldId,
     InvalidFieldValue,
+    UnparsableFieldValue {
+        field: String,
+        error: ValueParseError,
+    },
 }
 
 impl fmt::Display for EntityConversionError {
@@ -24,6 +32,9 @@ impl fmt::Display for EntityConversionError {
             EntityConversionError::InvalidFieldValue => {
                 write!(f, "Entity field contains an invalid value")
             }
+            EntityConversionError::UnparsableFieldValue { field, error } => {
+                write!(f, "Entity field '{}' could not be parsed: {}", field, error)
+            }
         }
     }
 }
@@ -36,6 +47,13 @@ pub enum SchemaConversionError {
     MissingFieldType,
     UnknownFieldType(String),
     InvalidFieldDefinition,
+    MissingAllowedValues { field: String, line: usize },
+    AmbiguousAllowedValue { field: String, line: usize, value: String },
+    ItemTypeOnNonList { field: String, line: usize },
+    UnsupportedItemType { field: String, line: usize, item_type: FieldType },
+    MissingExpression { field: String, line: usize },
+    InvalidExpression { field: String, line: usize, message: String },
+    ComputedFieldConflict { field: String, line: usize },
 }
 
 impl fmt::Display for SchemaConversionError {
@@ -56,6 +74,63 @@ impl fmt::Display for SchemaConversionError {
             SchemaConversionError::InvalidFieldDefinition => {
                 write!(f, "Schema field definition is invalid")
             }
+            SchemaConversionError::MissingAllowedValues { field, line } => {
+                write!(
+                    f,
+                    "Enum field '{}' at line {} needs a non-empty list of allowed_values",
+                    field, line
+                )
+            }
+            SchemaConversionError::AmbiguousAllowedValue { field, line, value } => {
+                write!(
+                    f,
+                    "Enum field '{}' at line {} allows '{}' more than once, ignoring case",
+                    field, line, value
+                )
+            }
+            SchemaConversionError::ItemTypeOnNonList { field, line } => {
+                write!(
+                    f,
+                    "Field '{}' at line {} declares an item_type, but only list fields have items",
+                    field, line
+                )
+            }
+            SchemaConversionError::UnsupportedItemType {
+                field,
+                line,
+                item_type,
+            } => {
+                write!(
+                    f,
+                    "List field '{}' at line {} can't hold {} items",
+                    field, line, item_type
+                )
+            }
+            SchemaConversionError::MissingExpression { field, line } => {
+                write!(
+                    f,
+                    "Computed field '{}' at line {} needs an expr",
+                    field, line
+                )
+            }
+            SchemaConversionError::InvalidExpression {
+                field,
+                line,
+                message,
+            } => {
+                write!(
+                    f,
+                    "Computed field '{}' at line {} has an invalid expr: {}",
+                    field, line, message
+                )
+            }
+            SchemaConversionError::ComputedFieldConflict { field, line } => {
+                write!(
+                    f,
+                    "Computed field '{}' at line {} has the same name as another field",
+                    field, line
+                )
+            }
         }
     }
 }
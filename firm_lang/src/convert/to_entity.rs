@@ -1,38 +1,52 @@
+use chrono::FixedOffset;
 use firm_core::{Entity, FieldId, FieldValue, ReferenceValue, compose_entity_id};
 
 use super::EntityConversionError;
-use crate::parser::dsl::{ParsedEntity, ParsedValue};
+use crate::parser::dsl::{ParsedEntity, ParsedValue, local_timezone};
 
 /// Converts a ParsedEntity to an Entity.
+///
+/// Dates and datetimes without a timezone are in the machine's local timezone.
 impl TryFrom<&ParsedEntity<'_>> for Entity {
     type Error = EntityConversionError;
 
     fn try_from(parsed: &ParsedEntity) -> Result<Self, EntityConversionError> {
-        let entity_type_str = parsed
-            .entity_type()
-            .ok_or(EntityConversionError::MissingEntityType)?;
+        convert_entity_in(parsed, local_timezone())
+    }
+}
 
-        let entity_id = parsed.id().ok_or(EntityConversionError::MissingEntityId)?;
-        let composite_id = compose_entity_id(entity_type_str, entity_id);
-        let mut entity = Entity::new(composite_id, entity_type_str.into());
+/// Converts a ParsedEntity to an Entity, with a timezone for dates and
+/// datetimes that don't give one.
+pub fn convert_entity_in(
+    parsed: &ParsedEntity,
+    timezone: FixedOffset,
+) -> Result<Entity, EntityConversionError> {
+    let entity_type_str = parsed
+        .entity_type()
+        .ok_or(EntityConversionError::MissingEntityType)?;
 
-        for field in parsed.fields() {
-            let field_id = field.id().ok_or(EntityConversionError::MissingFieldId)?;
-            let parsed_value = field
-                .value()
-                .map_err(|_| EntityConversionError::InvalidFieldValue)?;
+    let entity_id = parsed.id().ok_or(EntityConversionError::MissingEntityId)?;
+    let composite_id = compose_entity_id(entity_type_str, entity_id);
+    let mut entity = Entity::new(composite_id, entity_type_str.into());
 
-            let field_value: FieldValue = parsed_value
-                .try_into()
-                .map_err(|_| EntityConversionError::InvalidFieldValue)?;
+    for field in parsed.fields() {
+        let field_id = field.id().ok_or(EntityConversionError::MissingFieldId)?;
+        let parsed_value = field.value_in(timezone).map_err(|error| {
+            EntityConversionError::UnparsableFieldValue {
+                field: field_id.to_string(),
+                error,
+            }
+        })?;
 
-            entity
-                .fields
-                .push((FieldId(field_id.to_string()), field_value));
-        }
+        let field_value: FieldValue = parsed_value
+            .try_into()
+            .map_err(|_| EntityConversionError::InvalidFieldValue)?;
 
-        Ok(entity)
+        // A field assigned more than once keeps its first position but takes the last value
+        entity.set_field(FieldId(field_id.to_string()), field_value);
     }
+
+    Ok(entity)
 }
 
 /// Converts a ParsedValue to a FieldValue.
@@ -34,12 +34,7 @@ impl TryFrom<ParsedQuery> for Query {
 
     fn try_from(parsed: ParsedQuery) -> Result<Self, Self::Error> {
         // Convert the "from" clause
-        let from = match parsed.from.selector {
-            ParsedEntitySelector::Type(type_str) => {
-                EntitySelector::Type(EntityType::new(&type_str))
-            }
-            ParsedEntitySelector::Wildcard => EntitySelector::All,
-        };
+        let from = convert_selector(parsed.from.selector);
 
         let mut query = Query::new(from);
 
@@ -49,10 +44,32 @@ impl TryFrom<ParsedQuery> for Query {
             query = query.with_operation(operation);
         }
 
+        // Convert optional grouping
+        if let Some(field) = parsed.group_by {
+            query = query.with_group_by(convert_field(field));
+        }
+
         // Convert optional aggregation
         if let Some(parsed_agg) = parsed.aggregation {
             let aggregation = convert_aggregation(parsed_agg)?;
+
+            // Ordering names the aggregate it sorts by, so it must match the query's aggregation
+            if let Some(order) = parsed.aggregate_order {
+                if order.aggregate != aggregation.name() {
+                    return Err(QueryConversionError::UnsupportedOperation(format!(
+                        "Cannot order by '{}' in a query that aggregates with '{}'",
+                        order.aggregate,
+                        aggregation.name()
+                    )));
+                }
+                query = query.with_aggregate_order(convert_direction(order.direction));
+            }
+
             query = query.with_aggregation(aggregation);
+
+            if let Some(limit) = parsed.aggregate_limit {
+                query = query.with_aggregate_limit(limit);
+            }
         }
 
         Ok(query)
@@ -75,7 +92,7 @@ fn convert_operation(parsed: ParsedOperation) -> Result<QueryOperation, QueryCon
         }
         ParsedOperation::Limit(n) => Ok(QueryOperation::Limit(n)),
         ParsedOperation::Order { field, direction } => convert_order(field, direction),
-        ParsedOperation::Related { degree, selector } => convert_related(degree, selector),
+        ParsedOperation::Related { degree, selectors } => convert_related(degree, selectors),
     }
 }
 
@@ -107,20 +124,35 @@ fn convert_order(
     })
 }
 
+fn convert_selector(parsed: ParsedEntitySelector) -> EntitySelector {
+    match parsed {
+        ParsedEntitySelector::Type(type_str) => EntitySelector::Type(EntityType::new(&type_str)),
+        ParsedEntitySelector::TypeWithSubtypes(type_str) => {
+            EntitySelector::TypeWithSubtypes(EntityType::new(&type_str))
+        }
+        ParsedEntitySelector::Wildcard => EntitySelector::All,
+    }
+}
+
 fn convert_related(
     degree: Option<usize>,
-    selector: Option<ParsedEntitySelector>,
+    selectors: Vec<ParsedEntitySelector>,
 ) -> Result<QueryOperation, QueryConversionError> {
     // Default to 1 degree if not specified
     let degrees = degree.unwrap_or(1);
-    let entity_type = selector.and_then(|sel| match sel {
-        ParsedEntitySelector::Type(type_str) => Some(EntityType::new(&type_str)),
-        ParsedEntitySelector::Wildcard => None,
-    });
+
+    let entity_types: Vec<EntitySelector> = selectors.into_iter().map(convert_selector).collect();
+
+    // A wildcard keeps every type, like no filter at all
+    let entity_types = if entity_types.contains(&EntitySelector::All) {
+        Vec::new()
+    } else {
+        entity_types
+    };
 
     Ok(QueryOperation::Related {
         degrees,
-        entity_type,
+        entity_types,
     })
 }
 
@@ -147,6 +179,9 @@ fn convert_field(parsed: ParsedField) -> FieldRef {
             let metadata = match name.as_str() {
                 "type" => MetadataField::Type,
                 "id" => MetadataField::Id,
+                "created" => MetadataField::Created,
+                "modified" => MetadataField::Modified,
+                "tags" => MetadataField::Tags,
                 _ => MetadataField::Type, // Default fallback
             };
             FieldRef::Metadata(metadata)
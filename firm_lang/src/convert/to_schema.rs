@@ -6,6 +6,7 @@ use firm_core::{
 
 use super::SchemaConversionError;
 use crate::parser::dsl::ParsedSchema;
+use crate::parser::query::parse_expression;
 
 /// Converts a ParsedSchema to an EntitySchema.
 impl TryFrom<&ParsedSchema<'_>> for EntitySchema {
@@ -37,39 +38,116 @@ impl TryFrom<&ParsedSchema<'_>> for EntitySchema {
             };
 
             let field_schema = if field_type == FieldType::Enum {
-                // For enum fields, check if allowed values are provided
-                if let Some(allowed_values) = field.allowed_values() {
-                    FieldSchema::new_enum(field_mode, order, allowed_values)
-                } else {
-                    // Enum without allowed values - treat as regular field
-                    FieldSchema::new(field_type, field_mode, order)
+                // Enum fields must list their allowed values, or any value would pass
+                match field.allowed_values() {
+                    Some(allowed_values) if !allowed_values.is_empty() => {
+                        // Filters compare enum values ignoring case, so they must differ by more
+                        if let Some(value) = find_case_duplicate(&allowed_values) {
+                            return Err(SchemaConversionError::AmbiguousAllowedValue {
+                                field: field_name,
+                                line: field.line(),
+                                value,
+                            });
+                        }
+
+                        FieldSchema::new_enum(field_mode, order, allowed_values)
+                            .with_case_sensitive(field.case_sensitive())
+                    }
+                    _ => {
+                        return Err(SchemaConversionError::MissingAllowedValues {
+                            field: field_name,
+                            line: field.line(),
+                        });
+                    }
                 }
             } else {
                 FieldSchema::new(field_type, field_mode, order)
             };
 
+            let field_schema = match field.item_type() {
+                Some(_) if field_schema.field_type != FieldType::List => {
+                    return Err(SchemaConversionError::ItemTypeOnNonList {
+                        field: field_name,
+                        line: field.line(),
+                    });
+                }
+                Some(item_type) => {
+                    let item_type = convert_field_type(&item_type)?;
+                    if !item_type.is_item_type() {
+                        return Err(SchemaConversionError::UnsupportedItemType {
+                            field: field_name,
+                            line: field.line(),
+                            item_type,
+                        });
+                    }
+                    field_schema.with_item_type(item_type)
+                }
+                None => field_schema,
+            };
+
+            let field_schema = match field.format() {
+                Some(format) => field_schema.with_format(format),
+                None => field_schema,
+            };
+
+            let field_schema = match field.target() {
+                Some(target) => field_schema.with_target(EntityType::new(target)),
+                None => field_schema,
+            };
+            let field_schema = field_schema.with_extra_precision(field.extra_precision());
+
             schema.fields.insert(FieldId(field_name), field_schema);
         }
 
+        // Computed fields come after the written ones, in the order they're declared
+        for computed in parsed.computed_fields() {
+            let field_name = computed
+                .name()
+                .map_err(|_| SchemaConversionError::MissingFieldName)?;
+            let field_id = FieldId(field_name.clone());
+            if schema.fields.contains_key(&field_id) || schema.is_computed(&field_id) {
+                return Err(SchemaConversionError::ComputedFieldConflict {
+                    field: field_name,
+                    line: computed.line(),
+                });
+            }
+
+            let source =
+                computed
+                    .expression()
+                    .ok_or_else(|| SchemaConversionError::MissingExpression {
+                        field: field_name.clone(),
+                        line: computed.line(),
+                    })?;
+            let expression = parse_expression(&source).map_err(|e| {
+                SchemaConversionError::InvalidExpression {
+                    field: field_name.clone(),
+                    line: computed.line(),
+                    message: e.to_string(),
+                }
+            })?;
+
+            schema = schema.with_computed_field(field_id, expression, source);
+        }
+
         Ok(schema)
     }
 }
 
+/// Finds an allowed value that only differs by case from an earlier one.
+fn find_case_duplicate(allowed_values: &[String]) -> Option<String> {
+    allowed_values.iter().enumerate().find_map(|(index, value)| {
+        let lowercase = value.trim().to_lowercase();
+        allowed_values[..index]
+            .iter()
+            .any(|earlier| earlier.trim().to_lowercase() == lowercase)
+            .then(|| value.clone())
+    })
+}
+
 /// Converts a field type string to a FieldType enum.
 fn convert_field_type(type_str: &str) -> Result<FieldType, SchemaConversionError> {
-    match type_str {
-        "boolean" => Ok(FieldType::Boolean),
-        "string" => Ok(FieldType::String),
-        "integer" => Ok(FieldType::Integer),
-        "float" => Ok(FieldType::Float),
-        "currency" => Ok(FieldType::Currency),
-        "reference" => Ok(FieldType::Reference),
-        "list" => Ok(FieldType::List),
-        "datetime" => Ok(FieldType::DateTime),
-        "path" => Ok(FieldType::Path),
-        "enum" => Ok(FieldType::Enum),
-        _ => Err(SchemaConversionError::UnknownFieldType(
-            type_str.to_string(),
-        )),
-    }
+    type_str
+        .parse()
+        .map_err(|_| SchemaConversionError::UnknownFieldType(type_str.to_string()))
 }
@@ -1,8 +1,11 @@
-use firm_core::{Entity, decompose_entity_id};
+use firm_core::{Entity, FieldValue, TAGS_FIELD_NAME, decompose_entity_id};
 
 use super::{GeneratorOptions, from_field};
 
 /// Generate DSL for a single entity.
+///
+/// The DSL has no null value, so explicitly unset fields are left out,
+/// the same as fields that were never set.
 pub fn generate_entity(entity: &Entity, options: &GeneratorOptions) -> String {
     let mut output = String::new();
     let (_, entity_id) = decompose_entity_id(&entity.id.0);
@@ -27,12 +30,26 @@ pub fn generate_entity(entity: &Entity, options: &GeneratorOptions) -> String {
 }
 
 /// Generate DSL for all fields for an entity.
+///
+/// Tags are written last, as a `tags` list.
 fn generate_entity_fields(entity: &Entity, options: &GeneratorOptions) -> Vec<String> {
-    let fields: Vec<(String, &firm_core::FieldValue)> = entity
+    let tags = FieldValue::List(
+        entity
+            .tags
+            .iter()
+            .cloned()
+            .map(FieldValue::String)
+            .collect(),
+    );
+
+    let mut fields: Vec<(String, &FieldValue)> = entity
         .fields
         .iter()
         .map(|(field_id, field_value)| (field_id.0.clone(), field_value))
         .collect();
+    if !entity.tags.is_empty() {
+        fields.push((TAGS_FIELD_NAME.to_string(), &tags));
+    }
 
     // Generate each field
     fields
@@ -63,6 +80,10 @@ mod tests {
             id: EntityId("person.john_doe".to_string()),
             entity_type: EntityType::new("person"),
             fields,
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let result = generate_entity(&entity, &GeneratorOptions::default());
@@ -96,6 +117,10 @@ mod tests {
             id: EntityId("organization.acme_corp".to_string()),
             entity_type: EntityType::new("organization"),
             fields,
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let result = generate_entity(&entity, &GeneratorOptions::default());
@@ -135,6 +160,10 @@ mod tests {
             id: EntityId("person.jane_smith".to_string()),
             entity_type: EntityType::new("person"),
             fields,
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let result = generate_entity(&entity, &GeneratorOptions::default());
@@ -167,6 +196,10 @@ mod tests {
             id: EntityId("task.code_review".to_string()),
             entity_type: EntityType::new("task"),
             fields,
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let result = generate_entity(&entity, &GeneratorOptions::default());
@@ -196,6 +229,10 @@ mod tests {
             id: EntityId("person.test".to_string()),
             entity_type: EntityType::new("person"),
             fields,
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let options = GeneratorOptions {
@@ -224,6 +261,10 @@ mod tests {
             id: EntityId("person.test".to_string()),
             entity_type: EntityType::new("person"),
             fields,
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let options = GeneratorOptions {
@@ -236,4 +277,41 @@ mod tests {
         let expected = "person test {\n\tname = \"Test\"\n}\n";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_generate_entity_leaves_out_unset_fields() {
+        let entity = Entity::new(
+            EntityId("person.john_doe".to_string()),
+            EntityType::new("person"),
+        )
+        .with_field(
+            FieldId("name".to_string()),
+            FieldValue::String("John Doe".to_string()),
+        )
+        .with_unset_field(FieldId("email".to_string()));
+
+        let result = generate_entity(&entity, &GeneratorOptions::default());
+
+        assert_eq!(result, "person john_doe {\n    name = \"John Doe\"\n}\n");
+    }
+
+    #[test]
+    fn test_generate_entity_writes_tags_last() {
+        let entity = Entity::new(
+            EntityId("task.fix_bug".to_string()),
+            EntityType::new("task"),
+        )
+        .with_field(
+            FieldId("name".to_string()),
+            FieldValue::String("Fix bug".to_string()),
+        )
+        .with_tags(["urgent", "q3"]);
+
+        let result = generate_entity(&entity, &GeneratorOptions::default());
+
+        assert_eq!(
+            result,
+            "task fix_bug {\n    name = \"Fix bug\"\n    tags = [\"urgent\", \"q3\"]\n}\n"
+        );
+    }
 }
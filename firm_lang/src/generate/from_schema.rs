@@ -26,6 +26,14 @@ pub fn generate_schema(schema: &EntitySchema, options: &GeneratorOptions) -> Str
             field_type_to_string(&field_schema.field_type)
         ));
 
+        if let Some(item_type) = field_schema.item_type() {
+            output.push_str(&format!(
+                "{}item_type = \"{}\"\n",
+                options.indent_style.indent_string(2),
+                field_type_to_string(item_type)
+            ));
+        }
+
         // For enum fields, include the allowed values
         if let Some(allowed_values) = field_schema.allowed_values() {
             let values_str = allowed_values
@@ -40,6 +48,36 @@ pub fn generate_schema(schema: &EntitySchema, options: &GeneratorOptions) -> Str
             ));
         }
 
+        if field_schema.case_sensitive {
+            output.push_str(&format!(
+                "{}case_sensitive = true\n",
+                options.indent_style.indent_string(2)
+            ));
+        }
+
+        if field_schema.extra_precision {
+            output.push_str(&format!(
+                "{}extra_precision = true\n",
+                options.indent_style.indent_string(2)
+            ));
+        }
+
+        if let Some(format) = field_schema.format() {
+            output.push_str(&format!(
+                "{}format = \"{}\"\n",
+                options.indent_style.indent_string(2),
+                format
+            ));
+        }
+
+        if let Some(target) = field_schema.target() {
+            output.push_str(&format!(
+                "{}target = \"{}\"\n",
+                options.indent_style.indent_string(2),
+                target
+            ));
+        }
+
         output.push_str(&format!(
             "{}required = {}\n",
             options.indent_style.indent_string(2),
@@ -48,6 +86,25 @@ pub fn generate_schema(schema: &EntitySchema, options: &GeneratorOptions) -> Str
         output.push_str(&format!("{}}}\n", options.indent_style.indent_string(1)));
     }
 
+    // Generate computed fields after the written ones
+    for (field_id, computed) in schema.ordered_computed_fields() {
+        output.push_str(&format!(
+            "{}computed {{\n",
+            options.indent_style.indent_string(1)
+        ));
+        output.push_str(&format!(
+            "{}name = \"{}\"\n",
+            options.indent_style.indent_string(2),
+            field_id
+        ));
+        output.push_str(&format!(
+            "{}expr = \"{}\"\n",
+            options.indent_style.indent_string(2),
+            computed.source
+        ));
+        output.push_str(&format!("{}}}\n", options.indent_style.indent_string(1)));
+    }
+
     // Close schema block
     output.push_str("}\n");
 
@@ -73,6 +130,7 @@ fn field_type_to_string(field_type: &FieldType) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use firm_core::schema::{FieldMode, FieldSchema};
     use firm_core::{EntityType, FieldId};
 
     #[test]
@@ -169,6 +227,147 @@ mod tests {
         required = true
     }
 }
+"#;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generate_schema_with_format() {
+        let schema = EntitySchema::new(EntityType::new("report")).with_raw_field(
+            FieldId::new("query"),
+            FieldSchema::new(FieldType::String, FieldMode::Required, 0).with_format("firm-query"),
+        );
+
+        let result = generate_schema(&schema, &GeneratorOptions::default());
+
+        let expected = r#"schema report {
+    field {
+        name = "query"
+        type = "string"
+        format = "firm-query"
+        required = true
+    }
+}
+"#;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generate_schema_with_target() {
+        let schema = EntitySchema::new(EntityType::new("account")).with_raw_field(
+            FieldId::new("owner_ref"),
+            FieldSchema::new(FieldType::Reference, FieldMode::Optional, 0)
+                .with_target(EntityType::new("person")),
+        );
+
+        let result = generate_schema(&schema, &GeneratorOptions::default());
+
+        let expected = r#"schema account {
+    field {
+        name = "owner_ref"
+        type = "reference"
+        target = "person"
+        required = false
+    }
+}
+"#;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generate_schema_with_case_sensitive_enum() {
+        let schema = EntitySchema::new(EntityType::new("ticket")).with_raw_field(
+            FieldId::new("priority"),
+            FieldSchema::new_enum(FieldMode::Optional, 0, vec!["P1".to_string(), "P2".to_string()])
+                .with_case_sensitive(true),
+        );
+
+        let result = generate_schema(&schema, &GeneratorOptions::default());
+
+        let expected = r#"schema ticket {
+    field {
+        name = "priority"
+        type = "enum"
+        allowed_values = ["P1", "P2"]
+        case_sensitive = true
+        required = false
+    }
+}
+"#;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generate_schema_with_extra_precision() {
+        let schema = EntitySchema::new(EntityType::new("trade")).with_raw_field(
+            FieldId::new("price"),
+            FieldSchema::new(FieldType::Currency, FieldMode::Required, 0)
+                .with_extra_precision(true),
+        );
+
+        let result = generate_schema(&schema, &GeneratorOptions::default());
+
+        let expected = r#"schema trade {
+    field {
+        name = "price"
+        type = "currency"
+        extra_precision = true
+        required = true
+    }
+}
+"#;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generate_schema_with_list_item_type() {
+        let schema = EntitySchema::new(EntityType::new("project")).with_raw_field(
+            FieldId::new("urls"),
+            FieldSchema::new(FieldType::List, FieldMode::Optional, 0)
+                .with_item_type(FieldType::String),
+        );
+
+        let result = generate_schema(&schema, &GeneratorOptions::default());
+
+        let expected = r#"schema project {
+    field {
+        name = "urls"
+        type = "list"
+        item_type = "string"
+        required = false
+    }
+}
+"#;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generate_schema_with_computed_field() {
+        let schema = EntitySchema::new(EntityType::new("goal"))
+            .with_required_field(FieldId::new("done"), FieldType::Integer)
+            .with_computed_field(
+                FieldId::new("remaining"),
+                firm_core::Expression::binary(
+                    firm_core::expression::Operator::Subtract,
+                    firm_core::Expression::Integer(10),
+                    firm_core::Expression::Field(FieldId::new("done")),
+                ),
+                "10 - done",
+            );
+
+        let result = generate_schema(&schema, &GeneratorOptions::default());
+
+        let expected = r#"schema goal {
+    field {
+        name = "done"
+        type = "integer"
+        required = true
+    }
+    computed {
+        name = "remaining"
+        expr = "10 - done"
+    }
+}
 "#;
         assert_eq!(result, expected);
     }
@@ -24,8 +24,11 @@ pub fn generate_value(value: &FieldValue, options: &GeneratorOptions) -> String
 }
 
 /// Generate string value with proper quoting.
+///
+/// Multi-line strings use triple quotes when parsing them gives back the same
+/// string. Anything else is written as a single-line string with escapes.
 fn generate_string(s: &str, options: &GeneratorOptions) -> String {
-    if s.contains('\n') {
+    if s.contains('\n') && round_trips_as_multiline(s) {
         // Multi-line string
         let indent = options.indent_style.indent_string(1);
         let lines: Vec<&str> = s.lines().collect();
@@ -38,10 +41,33 @@ fn generate_string(s: &str, options: &GeneratorOptions) -> String {
         result
     } else {
         // Single-line string with escape handling
-        format!("\"{}\"", s.replace('\"', "\\\""))
+        format!("\"{}\"", escape_string(s))
     }
 }
 
+/// Escapes backslashes, quotes, newlines and tabs for a single-line string.
+fn escape_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Checks whether a triple-quoted string would parse back to the same string.
+///
+/// Parsing trims surrounding whitespace (which also keeps the first line
+/// unindented), and triple-quoted content can't hold triple quotes or carriage returns.
+fn round_trips_as_multiline(s: &str) -> bool {
+    s.trim() == s && !s.contains("\"\"\"") && !s.contains('\r')
+}
+
 /// Generate float value, ensuring it always has a decimal place.
 fn generate_float(f: &f64) -> String {
     let formatted = f.to_string();
@@ -152,6 +178,47 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_generate_string_with_escapes() {
+        let options = GeneratorOptions::default();
+        let result = generate_string("C:\\docs\tdraft", &options);
+        assert_eq!(result, "\"C:\\\\docs\\tdraft\"");
+    }
+
+    #[test]
+    fn test_generate_string_multiline_with_surrounding_whitespace() {
+        let options = GeneratorOptions::default();
+        let result = generate_string("Line 1\nLine 2\n", &options);
+        assert_eq!(result, "\"Line 1\\nLine 2\\n\"");
+    }
+
+    #[test]
+    fn test_generate_string_round_trips() {
+        use crate::parser::dsl::ParsedValue;
+
+        let options = GeneratorOptions::default();
+        let strings = [
+            "John \"JD\" Doe",
+            "back\\slash",
+            "ends with \\",
+            "tab\tseparated",
+            "Line 1\nLine 2",
+            "  indented\nlines  ",
+            "quotes \"\"\" inside\nand a newline",
+        ];
+
+        for original in strings {
+            let generated = generate_string(original, &options);
+            assert_eq!(
+                ParsedValue::parse_string(generated.trim()),
+                Ok(ParsedValue::String(original.to_string())),
+                "Generated {} for {:?}",
+                generated,
+                original
+            );
+        }
+    }
+
     #[test]
     fn test_generate_string_empty() {
         let options = GeneratorOptions::default();
@@ -70,6 +70,10 @@ mod tests {
                 ),
             ]
             .into(),
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         // Create an organization
@@ -87,6 +91,10 @@ mod tests {
                 ),
             ]
             .into(),
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         // Create a project with references
@@ -127,6 +135,10 @@ mod tests {
                 ),
             ]
             .into(),
+            unset_fields: Vec::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
         };
 
         let result = generate_dsl(&[person, organization, project]);
@@ -164,6 +176,10 @@ project firm_language {
                     FieldValue::String("Alice".to_string()),
                 )]
                 .into(),
+                unset_fields: Vec::new(),
+                created: None,
+                modified: None,
+                tags: Vec::new(),
             },
             Entity {
                 id: EntityId("person.bob".to_string()),
@@ -173,6 +189,10 @@ project firm_language {
                     FieldValue::String("Bob".to_string()),
                 )]
                 .into(),
+                unset_fields: Vec::new(),
+                created: None,
+                modified: None,
+                tags: Vec::new(),
             },
         ];
 
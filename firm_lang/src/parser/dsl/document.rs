@@ -0,0 +1,298 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use tree_sitter::{InputEdit, Point, Tree};
+
+use super::source::parse_tree;
+use super::{LanguageError, ParsedSource};
+
+/// A change to a document's text, like an editor sends while typing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChange {
+    /// The zero-based start and end of the replaced text, with columns in bytes.
+    /// Without a range, the change replaces the whole document.
+    pub range: Option<(Point, Point)>,
+    /// The text to put in place of the range.
+    pub text: String,
+}
+
+impl TextChange {
+    /// Creates a change replacing the text between two positions.
+    pub fn replace(start: Point, end: Point, text: &str) -> Self {
+        Self {
+            range: Some((start, end)),
+            text: text.to_string(),
+        }
+    }
+
+    /// Creates a change replacing the whole document.
+    pub fn full(text: &str) -> Self {
+        Self {
+            range: None,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// A source document open in an editor, reparsed incrementally as it changes.
+///
+/// Keeps the start of each line so positions can be turned into byte offsets
+/// without scanning the text. Each batch of changes edits the old tree, then
+/// reparses with it, so tree-sitter only re-reads the changed parts.
+#[derive(Debug)]
+pub struct Document {
+    source: String,
+    tree: Tree,
+    path: PathBuf,
+    line_starts: Vec<usize>,
+    version: u64,
+    changed_at: Instant,
+}
+
+impl Document {
+    /// How long a document should be left unchanged before computing its diagnostics.
+    pub const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(200);
+
+    /// Parses a newly opened document.
+    pub fn new(source: String, path: Option<PathBuf>) -> Result<Self, LanguageError> {
+        let tree = parse_tree(&source, None)?;
+        Ok(Self {
+            line_starts: line_starts(&source),
+            source,
+            tree,
+            path: path.unwrap_or_default(),
+            version: 0,
+            changed_at: Instant::now(),
+        })
+    }
+
+    /// Gets the current text.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Gets the syntax tree of the current text.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Gets how many batches of changes have been applied.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Checks whether the document has been left unchanged for a while.
+    ///
+    /// Diagnostics are best computed once typing settles, rather than on every keystroke.
+    pub fn is_settled(&self, debounce: Duration) -> bool {
+        self.changed_at.elapsed() >= debounce
+    }
+
+    /// Gets a parsed source for the current text, sharing the syntax tree.
+    pub fn to_parsed_source(&self) -> ParsedSource {
+        ParsedSource::new(self.source.clone(), self.tree.clone(), self.path.clone())
+    }
+
+    /// Applies a batch of changes in order, then reparses once.
+    ///
+    /// Positions past the end of a line or of the document are clamped to it,
+    /// and positions inside a character are moved to its start.
+    pub fn apply_changes(&mut self, changes: &[TextChange]) -> Result<(), LanguageError> {
+        self.apply_changes_in(changes, false)
+    }
+
+    /// Applies a batch of changes with columns in UTF-16 code units, like LSP positions.
+    ///
+    /// Each change's range is converted against the text left by the changes
+    /// before it, then applied like `apply_changes`.
+    pub fn apply_utf16_changes(&mut self, changes: &[TextChange]) -> Result<(), LanguageError> {
+        self.apply_changes_in(changes, true)
+    }
+
+    /// Gets the UTF-16 column of a position, for editors that count columns that way.
+    pub fn utf16_column(&self, point: Point) -> usize {
+        let offset = self.offset(point);
+        let line_start = self.line_starts[self.position(offset).row];
+        self.source[line_start..offset].encode_utf16().count()
+    }
+
+    fn apply_changes_in(
+        &mut self,
+        changes: &[TextChange],
+        utf16_columns: bool,
+    ) -> Result<(), LanguageError> {
+        let mut reparse_from_scratch = false;
+
+        for change in changes {
+            match change.range {
+                Some((start, end)) if utf16_columns => {
+                    let (start, end) = (self.point_from_utf16(start), self.point_from_utf16(end));
+                    self.apply_edit(start, end, &change.text)
+                }
+                Some((start, end)) => self.apply_edit(start, end, &change.text),
+                None => {
+                    self.source = change.text.clone();
+                    self.line_starts = line_starts(&self.source);
+                    reparse_from_scratch = true;
+                }
+            }
+        }
+
+        let old_tree = (!reparse_from_scratch).then_some(&self.tree);
+        self.tree = parse_tree(&self.source, old_tree)?;
+        self.version += 1;
+        self.changed_at = Instant::now();
+        Ok(())
+    }
+
+    /// Replaces the text between two positions and records the edit in the tree.
+    fn apply_edit(&mut self, start: Point, end: Point, text: &str) {
+        let start_byte = self.offset(start);
+        let old_end_byte = self.offset(end).max(start_byte);
+        let start_position = self.position(start_byte);
+        let old_end_position = self.position(old_end_byte);
+
+        self.source.replace_range(start_byte..old_end_byte, text);
+        let new_end_byte = start_byte + text.len();
+
+        // Swap the replaced lines' starts for the inserted ones, then shift the rest
+        let first_line = start_position.row + 1;
+        let inserted = text
+            .match_indices('\n')
+            .map(|(index, _)| start_byte + index + 1);
+        self.line_starts
+            .splice(first_line..=old_end_position.row, inserted);
+        let shifted_from = first_line + text.matches('\n').count();
+        for line_start in &mut self.line_starts[shifted_from..] {
+            *line_start = *line_start + new_end_byte - old_end_byte;
+        }
+
+        self.tree.edit(&InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position: self.position(new_end_byte),
+        });
+    }
+
+    /// Turns a position into a byte offset, clamped to its line and the document.
+    fn offset(&self, point: Point) -> usize {
+        let Some(&line_start) = self.line_starts.get(point.row) else {
+            return self.source.len();
+        };
+        let line_end = self
+            .line_starts
+            .get(point.row + 1)
+            .map(|next_start| next_start - 1)
+            .unwrap_or(self.source.len());
+
+        let mut offset = (line_start + point.column).min(line_end);
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    /// Turns a position with a UTF-16 column into one with a byte column.
+    ///
+    /// Columns past the end of the line are kept, so they're clamped like byte columns.
+    fn point_from_utf16(&self, point: Point) -> Point {
+        let Some(&line_start) = self.line_starts.get(point.row) else {
+            return point;
+        };
+        let line_end = self
+            .line_starts
+            .get(point.row + 1)
+            .map(|next_start| next_start - 1)
+            .unwrap_or(self.source.len());
+        let line = &self.source[line_start..line_end];
+
+        let mut units = 0;
+        for (index, c) in line.char_indices() {
+            if units >= point.column {
+                return Point::new(point.row, index);
+            }
+            units += c.len_utf16();
+        }
+        Point::new(point.row, line.len() + point.column.saturating_sub(units))
+    }
+
+    /// Turns a byte offset into a position.
+    fn position(&self, offset: usize) -> Point {
+        let row = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= offset)
+            - 1;
+        Point {
+            row,
+            column: offset - self.line_starts[row],
+        }
+    }
+}
+
+/// Finds the byte offset where each line starts.
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(row: usize, column: usize) -> Point {
+        Point { row, column }
+    }
+
+    #[test]
+    fn test_offsets_and_positions() {
+        let document = Document::new("ab\ncdé\n\nf".to_string(), None).unwrap();
+        assert_eq!(document.line_starts, vec![0, 3, 8, 9]);
+        assert_eq!(document.offset(point(1, 1)), 4);
+        assert_eq!(document.position(4), point(1, 1));
+
+        // Past the end of a line or the document
+        assert_eq!(document.offset(point(0, 10)), 2);
+        assert_eq!(document.offset(point(9, 0)), 10);
+
+        // Inside the two bytes of é
+        assert_eq!(document.offset(point(1, 3)), 5);
+    }
+
+    #[test]
+    fn test_utf16_columns() {
+        let mut document = Document::new("a😀b\né".to_string(), None).unwrap();
+
+        // The emoji is two UTF-16 code units, but four bytes
+        assert_eq!(document.point_from_utf16(point(0, 3)), point(0, 5));
+        assert_eq!(document.utf16_column(point(0, 5)), 3);
+        assert_eq!(document.utf16_column(point(1, 2)), 1);
+
+        document
+            .apply_utf16_changes(&[
+                TextChange::replace(point(0, 3), point(0, 4), "c"),
+                TextChange::replace(point(1, 1), point(1, 1), "!"),
+            ])
+            .unwrap();
+        assert_eq!(document.source(), "a😀c\né!");
+    }
+
+    #[test]
+    fn test_line_starts_follow_edits() {
+        let mut document = Document::new("one\ntwo\nthree\n".to_string(), None).unwrap();
+        let changes = [
+            TextChange::replace(point(1, 0), point(2, 0), "2\nII\n"),
+            TextChange::replace(point(0, 3), point(1, 0), " "),
+        ];
+        document.apply_changes(&changes).unwrap();
+
+        assert_eq!(document.source(), "one 2\nII\nthree\n");
+        assert_eq!(document.line_starts, line_starts(document.source()));
+        assert_eq!(document.version(), 1);
+    }
+}
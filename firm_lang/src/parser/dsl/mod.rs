@@ -1,5 +1,7 @@
+mod document;
 mod parsed_entity;
 mod parsed_field;
+mod parsed_query;
 mod parsed_schema;
 mod parsed_schema_field;
 mod parsed_source;
@@ -8,11 +10,13 @@ mod parser_errors;
 mod parser_utils;
 mod source;
 
-pub use parsed_entity::ParsedEntity;
+pub use document::{Document, TextChange};
+pub use parsed_entity::{DuplicateField, ParsedEntity};
 pub use parsed_field::ParsedField;
+pub use parsed_query::ParsedQuery;
 pub use parsed_schema::ParsedSchema;
 pub use parsed_schema_field::ParsedSchemaField;
-pub use parsed_source::ParsedSource;
-pub use parsed_value::ParsedValue;
-pub use parser_errors::{LanguageError, ValueParseError};
+pub use parsed_source::{ParsedSource, QUERY_BLOCK_TYPE};
+pub use parsed_value::{ParsedValue, local_timezone};
+pub use parser_errors::{LanguageError, SyntaxError, ValueParseError};
 pub use source::parse_source;
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use tree_sitter::Node;
+use tree_sitter::{Node, Range};
 
 use super::{
     ParsedField,
@@ -11,6 +11,29 @@ const ENTITY_TYPE_KIND: &str = "entity_type";
 const ENTITY_ID_KIND: &str = "entity_id";
 const FIELD_KIND: &str = "field";
 
+/// A field that is assigned more than once within the same entity block.
+///
+/// Both source ranges are kept so tools can point at the original and the
+/// repeated assignment. When converted, the last assignment wins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateField {
+    pub name: String,
+    pub first: Range,
+    pub duplicate: Range,
+}
+
+impl DuplicateField {
+    /// The 1-based line of the first assignment.
+    pub fn first_line(&self) -> usize {
+        self.first.start_point.row + 1
+    }
+
+    /// The 1-based line of the repeated assignment.
+    pub fn duplicate_line(&self) -> usize {
+        self.duplicate.start_point.row + 1
+    }
+}
+
 /// A parsed entity definition from Firm DSL.
 ///
 /// Represents an entity block like `contact john_doe { ... }` with
@@ -40,6 +63,26 @@ impl<'a> ParsedEntity<'a> {
         Some(get_node_text(&id_node, self.source))
     }
 
+    /// Returns the source range of the entity type.
+    pub fn entity_type_range(&self) -> Option<Range> {
+        find_child_of_kind(&self.node, ENTITY_TYPE_KIND).map(|node| node.range())
+    }
+
+    /// Returns the source range of the entity ID.
+    pub fn id_range(&self) -> Option<Range> {
+        find_child_of_kind(&self.node, ENTITY_ID_KIND).map(|node| node.range())
+    }
+
+    /// Returns the source range of the whole entity block.
+    pub fn range(&self) -> Range {
+        self.node.range()
+    }
+
+    /// Checks whether the entity block contains syntax errors.
+    pub fn has_error(&self) -> bool {
+        self.node.has_error()
+    }
+
     /// Extracts all field definitions from the entity block.
     pub fn fields(&self) -> Vec<ParsedField<'_>> {
         let mut fields = Vec::new();
@@ -63,4 +106,30 @@ impl<'a> ParsedEntity<'a> {
 
         fields
     }
+
+    /// Finds fields that are assigned more than once in the entity block.
+    ///
+    /// Each repeated assignment is reported against the first one, so a field
+    /// set three times yields two entries.
+    pub fn duplicate_fields(&self) -> Vec<DuplicateField> {
+        let mut first_seen: Vec<(String, Range)> = Vec::new();
+        let mut duplicates = Vec::new();
+
+        for field in self.fields() {
+            let Some(name) = field.id() else {
+                continue;
+            };
+
+            match first_seen.iter().find(|(seen, _)| *seen == name) {
+                Some((_, first)) => duplicates.push(DuplicateField {
+                    name: name.to_string(),
+                    first: *first,
+                    duplicate: field.range(),
+                }),
+                None => first_seen.push((name.to_string(), field.range())),
+            }
+        }
+
+        duplicates
+    }
 }
@@ -1,9 +1,12 @@
 use std::path::PathBuf;
 
-use tree_sitter::Node;
+use chrono::FixedOffset;
+use tree_sitter::{Node, Range};
 
 use super::{
-    parsed_value::ParsedValue, parser_errors::ValueParseError, parser_utils::find_child_of_kind,
+    parsed_value::{ParsedValue, local_timezone},
+    parser_errors::ValueParseError,
+    parser_utils::find_child_of_kind,
     parser_utils::get_node_text,
 };
 
@@ -33,11 +36,54 @@ impl<'a> ParsedField<'a> {
         Some(get_node_text(&id_node, self.source))
     }
 
+    /// Gets the source range of the field name.
+    pub fn id_range(&self) -> Option<Range> {
+        find_child_of_kind(&self.node, FIELD_ID_KIND).map(|node| node.range())
+    }
+
+    /// Gets the source range of the whole field assignment.
+    pub fn range(&self) -> Range {
+        self.node.range()
+    }
+
+    /// Gets the 1-based line number where the field assignment starts.
+    pub fn line(&self) -> usize {
+        self.node.start_position().row + 1
+    }
+
+    /// Gets the source range of the field's value, if it has one.
+    pub fn value_range(&self) -> Option<Range> {
+        find_child_of_kind(&self.node, VALUE_KIND).map(|node| node.range())
+    }
+
+    /// Gets the source ranges of the items in the field's list value, in order.
+    ///
+    /// Returns no ranges if the value isn't a list.
+    pub fn list_item_ranges(&self) -> Vec<Range> {
+        let Some(list_node) =
+            find_child_of_kind(&self.node, VALUE_KIND).and_then(|value| value.named_child(0))
+        else {
+            return Vec::new();
+        };
+
+        let mut cursor = list_node.walk();
+        list_node
+            .children(&mut cursor)
+            .filter(|child| child.kind() == VALUE_KIND)
+            .map(|child| child.range())
+            .collect()
+    }
+
     /// Parses and gets the field's value with full type information.
     pub fn value(&self) -> Result<ParsedValue, ValueParseError> {
+        self.value_in(local_timezone())
+    }
+
+    /// Parses and gets the field's value, with a timezone for dates and datetimes that don't give one.
+    pub fn value_in(&self, timezone: FixedOffset) -> Result<ParsedValue, ValueParseError> {
         let value_node =
             find_child_of_kind(&self.node, VALUE_KIND).ok_or(ValueParseError::MissingValue)?;
 
-        ParsedValue::from_node(value_node, self.source, self.path)
+        ParsedValue::from_node_in(value_node, self.source, self.path, timezone)
     }
 }
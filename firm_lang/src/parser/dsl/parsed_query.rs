@@ -0,0 +1,74 @@
+use tree_sitter::Range;
+
+use super::{ParsedEntity, ParsedField, ParsedValue};
+
+/// The field of a query block holding the query.
+const QUERY_FIELD: &str = "query";
+/// The field of a query block describing the query.
+const DESCRIPTION_FIELD: &str = "description";
+
+/// A parsed named query definition from Firm DSL.
+///
+/// Represents a block like `query open_tasks { query = "from task | ..." }`,
+/// which is written like an entity of type `query` but saves a query to run by name.
+#[derive(Debug)]
+pub struct ParsedQuery<'a> {
+    block: ParsedEntity<'a>,
+}
+
+impl<'a> ParsedQuery<'a> {
+    /// Creates a new ParsedQuery from a block of type `query`.
+    pub fn new(block: ParsedEntity<'a>) -> Self {
+        Self { block }
+    }
+
+    /// Returns the query name (e.g., "open_tasks").
+    pub fn name(&self) -> Option<&str> {
+        self.block.id()
+    }
+
+    /// Returns the source range of the query name.
+    pub fn name_range(&self) -> Option<Range> {
+        self.block.id_range()
+    }
+
+    /// Returns the source range of the whole query block.
+    pub fn range(&self) -> Range {
+        self.block.range()
+    }
+
+    /// Gets the 1-based line number where the query block starts.
+    pub fn line(&self) -> usize {
+        self.block.range().start_point.row + 1
+    }
+
+    /// Checks whether the query block contains syntax errors.
+    pub fn has_error(&self) -> bool {
+        self.block.has_error()
+    }
+
+    /// Returns the query text, if the block sets it as a string.
+    pub fn query(&self) -> Option<String> {
+        self.string_field(QUERY_FIELD)
+    }
+
+    /// Returns the query's description, if the block sets one.
+    pub fn description(&self) -> Option<String> {
+        self.string_field(DESCRIPTION_FIELD)
+    }
+
+    /// Returns the fields set in the query block.
+    pub fn fields(&self) -> Vec<ParsedField<'_>> {
+        self.block.fields()
+    }
+
+    /// Gets the last string value assigned to a field.
+    fn string_field(&self, name: &str) -> Option<String> {
+        let fields = self.block.fields();
+        let field = fields.iter().rev().find(|field| field.id() == Some(name))?;
+        match field.value().ok()? {
+            ParsedValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
@@ -1,14 +1,19 @@
 use std::path::PathBuf;
 
-use tree_sitter::Node;
+use tree_sitter::{Node, Range};
 
 use super::{
-    ParsedSchemaField,
+    ParsedField, ParsedSchemaField, ParsedValue,
     parser_utils::{find_child_of_kind, get_node_text},
 };
 
 const SCHEMA_NAME_KIND: &str = "schema_name";
 const NESTED_BLOCK_KIND: &str = "nested_block";
+const FIELD_KIND: &str = "field";
+const BLOCK_KIND: &str = "block";
+const VALIDATE_ATTRIBUTE: &str = "validate";
+const FIELD_BLOCK_TYPE: &str = "field";
+const COMPUTED_BLOCK_TYPE: &str = "computed";
 
 /// A parsed schema definition from Firm DSL.
 ///
@@ -33,37 +38,87 @@ impl<'a> ParsedSchema<'a> {
         Some(get_node_text(&name_node, self.source))
     }
 
+    /// Gets the source range of the schema name.
+    pub fn name_range(&self) -> Option<Range> {
+        find_child_of_kind(&self.node, SCHEMA_NAME_KIND).map(|node| node.range())
+    }
+
+    /// Gets the source range of the whole schema block.
+    pub fn range(&self) -> Range {
+        self.node.range()
+    }
+
+    /// Checks whether the schema block contains syntax errors.
+    pub fn has_error(&self) -> bool {
+        self.node.has_error()
+    }
+
     /// Extracts all field definitions from the schema block.
     pub fn fields(&self) -> Vec<ParsedSchemaField<'_>> {
-        let mut fields = Vec::new();
+        self.nested_blocks(FIELD_BLOCK_TYPE)
+    }
+
+    /// Extracts all computed field definitions from the schema block.
+    ///
+    /// These are blocks like `computed { name = "progress" expr = "done / total" }`.
+    pub fn computed_fields(&self) -> Vec<ParsedSchemaField<'_>> {
+        self.nested_blocks(COMPUTED_BLOCK_TYPE)
+    }
+
+    /// Gets the validation script from the `validate` attribute, with its 1-based line.
+    ///
+    /// Like other path values, the script path is relative to the workspace root.
+    /// Returns None if there's no `validate` attribute or if it's not a path.
+    pub fn validate_script(&self) -> Option<(PathBuf, usize)> {
+        let block_node = find_child_of_kind(&self.node, BLOCK_KIND)?;
+        let mut cursor = block_node.walk();
+
+        // Attributes are field assignments directly in the schema block
+        for child in block_node.children(&mut cursor) {
+            if child.kind() != FIELD_KIND {
+                continue;
+            }
+            let field = ParsedField::new(child, self.source, self.path);
+            if field.id() != Some(VALIDATE_ATTRIBUTE) {
+                continue;
+            }
+            return match field.value() {
+                Ok(ParsedValue::Path(script)) => Some((script, field.line())),
+                _ => None,
+            };
+        }
+
+        None
+    }
+
+    /// Finds the nested blocks of a given type (like "field") in the schema block.
+    fn nested_blocks(&self, block_type: &str) -> Vec<ParsedSchemaField<'_>> {
+        let mut blocks = Vec::new();
         let mut cursor = self.node.walk();
 
         // First find the block node
         if let Some(block_node) = self
             .node
             .children(&mut cursor)
-            .find(|child| child.kind() == "block")
+            .find(|child| child.kind() == BLOCK_KIND)
         {
             let mut block_cursor = block_node.walk();
 
-            // Then find nested_block nodes within the block (these are the field definitions)
+            // Then find nested_block nodes of the given type within the block
             for child in block_node.children(&mut block_cursor) {
                 if child.kind() == NESTED_BLOCK_KIND {
-                    // Verify this is a "field" block by checking the block_type
                     let mut nested_cursor = child.walk();
                     if let Some(block_type_node) = child
                         .children(&mut nested_cursor)
                         .find(|c| c.kind() == "block_type")
+                        && get_node_text(&block_type_node, self.source) == block_type
                     {
-                        let block_type = get_node_text(&block_type_node, self.source);
-                        if block_type == "field" {
-                            fields.push(ParsedSchemaField::new(child, self.source, self.path));
-                        }
+                        blocks.push(ParsedSchemaField::new(child, self.source, self.path));
                     }
                 }
             }
         }
 
-        fields
+        blocks
     }
 }
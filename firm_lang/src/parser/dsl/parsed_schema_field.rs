@@ -1,5 +1,5 @@
 use std::path::PathBuf;
-use tree_sitter::Node;
+use tree_sitter::{Node, Range};
 
 use super::{
     parsed_value::ParsedValue, parser_errors::ValueParseError, parser_utils::find_child_of_kind,
@@ -18,6 +18,8 @@ const BLOCK_KIND: &str = "block";
 ///     required = true
 /// }
 /// ```
+///
+/// Computed field blocks like `computed { name = "total" expr = "a + b" }` share this shape.
 #[derive(Debug)]
 pub struct ParsedSchemaField<'a> {
     node: Node<'a>,
@@ -43,6 +45,21 @@ impl<'a> ParsedSchemaField<'a> {
         }
     }
 
+    /// Gets the source range of the field name's value.
+    pub fn name_range(&self) -> Option<Range> {
+        self.find_field_by_name("name")?.value_range()
+    }
+
+    /// Gets the source range of the whole field block.
+    pub fn range(&self) -> Range {
+        self.node.range()
+    }
+
+    /// Gets the 1-based line number where the field block starts.
+    pub fn line(&self) -> usize {
+        self.node.start_position().row + 1
+    }
+
     /// Gets the field type from the "type" field.
     pub fn field_type(&self) -> Result<String, ValueParseError> {
         let type_field = self
@@ -66,6 +83,30 @@ impl<'a> ParsedSchemaField<'a> {
         false // Default to false if not specified or invalid
     }
 
+    /// Checks whether enum values must match the allowed values exactly.
+    /// Defaults to false if not specified.
+    pub fn case_sensitive(&self) -> bool {
+        if let Some(case_sensitive_field) = self.find_field_by_name("case_sensitive")
+            && let Ok(ParsedValue::Boolean(b)) = case_sensitive_field.value()
+        {
+            return b;
+        }
+
+        false
+    }
+
+    /// Checks whether currency amounts may have more decimals than the currency's minor unit.
+    /// Defaults to false if not specified.
+    pub fn extra_precision(&self) -> bool {
+        if let Some(extra_precision_field) = self.find_field_by_name("extra_precision")
+            && let Ok(ParsedValue::Boolean(b)) = extra_precision_field.value()
+        {
+            return b;
+        }
+
+        false
+    }
+
     /// Gets the allowed values for enum fields from the "values" field.
     /// Returns None if not specified or if it's not a list of strings.
     pub fn allowed_values(&self) -> Option<Vec<String>> {
@@ -88,6 +129,55 @@ impl<'a> ParsedSchemaField<'a> {
         }
     }
 
+    /// Checks whether the field declares allowed values, whether or not they're valid.
+    pub fn has_allowed_values(&self) -> bool {
+        self.find_field_by_name("allowed_values").is_some()
+    }
+
+    /// Gets the content format hint from the "format" field (e.g. "firm-query").
+    /// Returns None if not specified or if it's not a string.
+    pub fn format(&self) -> Option<String> {
+        let format_field = self.find_field_by_name("format")?;
+
+        match format_field.value() {
+            Ok(ParsedValue::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Gets the entity type a reference field should point at from the "target" field.
+    /// Returns None if not specified or if it's not a string.
+    pub fn target(&self) -> Option<String> {
+        let target_field = self.find_field_by_name("target")?;
+
+        match target_field.value() {
+            Ok(ParsedValue::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Gets the type of a list field's items from the "item_type" field.
+    /// Returns None if not specified or if it's not a string.
+    pub fn item_type(&self) -> Option<String> {
+        let item_type_field = self.find_field_by_name("item_type")?;
+
+        match item_type_field.value() {
+            Ok(ParsedValue::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Gets the expression of a computed field from the "expr" field.
+    /// Returns None if not specified or if it's not a string.
+    pub fn expression(&self) -> Option<String> {
+        let expression_field = self.find_field_by_name("expr")?;
+
+        match expression_field.value() {
+            Ok(ParsedValue::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
     /// Helper method to find a field by name within this schema field block.
     fn find_field_by_name(&self, field_name: &str) -> Option<super::ParsedField<'_>> {
         // Find the block node within this field
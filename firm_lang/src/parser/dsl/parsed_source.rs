@@ -1,17 +1,21 @@
 use std::path::PathBuf;
 
-use tree_sitter::Tree;
+use tree_sitter::{Node, Tree};
 
-use super::{ParsedEntity, ParsedSchema};
+use super::parser_utils::get_node_text;
+use super::{ParsedEntity, ParsedQuery, ParsedSchema, SyntaxError};
 
 const ENTITY_BLOCK_KIND: &str = "entity_block";
 const SCHEMA_BLOCK_KIND: &str = "schema_block";
 
+/// The block type reserved for named queries, which are written like entities.
+pub const QUERY_BLOCK_TYPE: &str = "query";
+
 /// A parsed Firm DSL source document.
 ///
 /// Contains the original source text and the tree-sitter parse tree,
 /// providing access to entities and syntax error detection.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParsedSource {
     /// The plain text source file.
     pub source: String,
@@ -34,19 +38,56 @@ impl ParsedSource {
         self.tree.root_node().has_error()
     }
 
+    /// Collects every syntax error in the source.
+    ///
+    /// Tree-sitter recovers from errors by marking unexpected syntax and
+    /// inserting missing tokens, so one broken block doesn't hide the
+    /// problems in the rest of the file.
+    pub fn syntax_errors(&self) -> Vec<SyntaxError> {
+        let mut nodes = Vec::new();
+        collect_error_nodes(self.tree.root_node(), &mut nodes);
+
+        nodes
+            .into_iter()
+            .map(|node| SyntaxError {
+                message: syntax_error_message(&node, &self.source),
+                range: node.range(),
+            })
+            .collect()
+    }
+
     /// Extracts all entity definitions from the parsed source.
+    ///
+    /// Blocks of the `query` type are named queries, not entities, so they're left out.
     pub fn entities(&self) -> Vec<ParsedEntity<'_>> {
-        let mut entities = Vec::new();
+        self.entity_blocks()
+            .into_iter()
+            .filter(|block| block.entity_type() != Some(QUERY_BLOCK_TYPE))
+            .collect()
+    }
+
+    /// Extracts all named query definitions from the parsed source.
+    pub fn queries(&self) -> Vec<ParsedQuery<'_>> {
+        self.entity_blocks()
+            .into_iter()
+            .filter(|block| block.entity_type() == Some(QUERY_BLOCK_TYPE))
+            .map(ParsedQuery::new)
+            .collect()
+    }
+
+    /// Extracts every block written like an entity, including named queries.
+    fn entity_blocks(&self) -> Vec<ParsedEntity<'_>> {
+        let mut blocks = Vec::new();
         let root = self.tree.root_node();
         let mut cursor = root.walk();
 
         for child in root.children(&mut cursor) {
             if child.kind() == ENTITY_BLOCK_KIND {
-                entities.push(ParsedEntity::new(child, &self.source, &self.path));
+                blocks.push(ParsedEntity::new(child, &self.source, &self.path));
             }
         }
 
-        entities
+        blocks
     }
 
     /// Extracts all schema definitions from the parsed source.
@@ -65,6 +106,43 @@ impl ParsedSource {
     }
 }
 
+/// The longest unexpected text quoted in a syntax error message.
+const MAX_QUOTED_LENGTH: usize = 30;
+
+/// Collects the outermost error and missing nodes in a tree.
+fn collect_error_nodes<'a>(node: Node<'a>, errors: &mut Vec<Node<'a>>) {
+    if node.is_error() || node.is_missing() {
+        errors.push(node);
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_error_nodes(child, errors);
+    }
+}
+
+/// Describes an error node: the token that's missing, or the text that's unexpected.
+fn syntax_error_message(node: &Node, source: &str) -> String {
+    if node.is_missing() {
+        return format!("Missing '{}'", node.kind());
+    }
+
+    let text = get_node_text(node, source);
+    let first_line = text.lines().map(str::trim).find(|line| !line.is_empty());
+    match first_line {
+        Some(line) if line.chars().count() > MAX_QUOTED_LENGTH => {
+            let quoted: String = line.chars().take(MAX_QUOTED_LENGTH).collect();
+            format!("Unexpected '{}...'", quoted)
+        }
+        Some(line) => format!("Unexpected '{}'", line),
+        None => "Unexpected syntax".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -93,6 +171,38 @@ mod tests {
         assert!(entities.len() == 2);
     }
 
+    #[test]
+    fn test_queries_are_separate_from_entities() {
+        let source = r#"
+            task write_docs {
+                name = "Write docs"
+            }
+
+            query open_tasks {
+                query = "from task | where is_completed == false"
+                description = "Tasks still to do"
+            }
+        "#;
+
+        let parsed = parse_source(String::from(source), None).unwrap();
+
+        assert!(!parsed.has_error());
+        assert_eq!(parsed.entities().len(), 1);
+
+        let queries = parsed.queries();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].name(), Some("open_tasks"));
+        assert_eq!(
+            queries[0].query().as_deref(),
+            Some("from task | where is_completed == false")
+        );
+        assert_eq!(
+            queries[0].description().as_deref(),
+            Some("Tasks still to do")
+        );
+        assert_eq!(queries[0].line(), 6);
+    }
+
     #[test]
     fn test_has_schemas_for_valid_source() {
         let source = r#"
@@ -235,6 +345,32 @@ mod tests {
         assert!(parsed.has_error());
     }
 
+    #[test]
+    fn test_syntax_errors_in_separate_blocks() {
+        let source = "contact a {\n    bad_number = 42.3.4\n}\n\ncontact b {\n    name = \"Fine\"\n}\n\ncontact c {\n    bad_ref = contact.too.many.parts.here\n}\n";
+
+        let parsed = parse_source(String::from(source), None).unwrap();
+        let errors = parsed.syntax_errors();
+        let lines: Vec<usize> = errors
+            .iter()
+            .map(|error| error.range.start_point.row + 1)
+            .collect();
+
+        // Each broken block reports its own error, and the valid one in between none
+        assert!(lines.contains(&2));
+        assert!(lines.contains(&10));
+        assert!(!lines.contains(&6));
+        assert!(errors.iter().all(|error| !error.message.is_empty()));
+    }
+
+    #[test]
+    fn test_no_syntax_errors_for_valid_source() {
+        let source = "contact a {\n    name = \"A\"\n}\n";
+
+        let parsed = parse_source(String::from(source), None).unwrap();
+        assert!(parsed.syntax_errors().is_empty());
+    }
+
     #[test]
     fn test_schema_with_complex_fields() {
         let source = r#"
@@ -99,23 +99,38 @@ impl ParsedValue {
     }
 
     /// Parses a value from a tree-sitter node with type conversion.
+    ///
+    /// Dates and datetimes without a timezone are in the machine's local timezone.
     pub fn from_node<'a>(
         node: Node<'a>,
         source: &'a str,
         path: &'a PathBuf,
+    ) -> Result<ParsedValue, ValueParseError> {
+        Self::from_node_in(node, source, path, local_timezone())
+    }
+
+    /// Parses a value from a tree-sitter node, with a timezone for dates and
+    /// datetimes that don't give one.
+    pub fn from_node_in<'a>(
+        node: Node<'a>,
+        source: &'a str,
+        path: &'a PathBuf,
+        timezone: FixedOffset,
     ) -> Result<ParsedValue, ValueParseError> {
         let kind = Self::get_value_kind(node).ok_or(ValueParseError::UnknownValueKind)?;
         let raw = get_node_text(&node, source);
 
         match kind {
             ValueKind::Boolean => Self::parse_boolean(raw),
-            ValueKind::String => Self::parse_string(raw),
+            ValueKind::String => {
+                Self::parse_string(raw).map_err(|err| err.offset_by(node.start_position()))
+            }
             ValueKind::Number => Self::parse_number(raw),
             ValueKind::Currency => Self::parse_currency(raw),
             ValueKind::Reference => Self::parse_reference(raw),
-            ValueKind::List => Self::parse_list_from_node(node, source, path),
-            ValueKind::Date => Self::parse_date(raw),
-            ValueKind::DateTime => Self::parse_datetime(raw),
+            ValueKind::List => Self::parse_list_from_node(node, source, path, timezone),
+            ValueKind::Date => Self::parse_date_in(raw, timezone),
+            ValueKind::DateTime => Self::parse_datetime_in(raw, timezone),
             ValueKind::Path => Self::parse_path(raw, path),
             ValueKind::Enum => Self::parse_enum(raw),
             _ => Err(ValueParseError::MissingParseMethod),
@@ -149,11 +164,51 @@ impl ParsedValue {
         }
         // Single-line strings start and end with single quotes ("stuff")
         else {
-            // Handle single quotes
-            Ok(ParsedValue::String(raw.trim_matches('"').to_string()))
+            // Strip exactly one quote on each side, so escaped quotes at the ends are kept
+            let content = raw.strip_prefix('"').unwrap_or(raw);
+            let content = content.strip_suffix('"').unwrap_or(content);
+
+            // Positions are reported from the opening quote, 1-based
+            Self::unescape(content)
+                .map(ParsedValue::String)
+                .map_err(|offset| ValueParseError::InvalidEscape {
+                    source: raw.to_string(),
+                    line: 1,
+                    column: offset + 2,
+                })
         }
     }
 
+    /// Decodes escape sequences (`\"`, `\\`, `\n` and `\t`) in single-line string content.
+    ///
+    /// Other escapes are kept as written. Returns the byte offset of a
+    /// trailing backslash, which has nothing to escape.
+    fn unescape(content: &str) -> Result<String, usize> {
+        let mut result = String::with_capacity(content.len());
+        let mut chars = content.char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some((_, '"')) => result.push('"'),
+                Some((_, '\\')) => result.push('\\'),
+                Some((_, 'n')) => result.push('\n'),
+                Some((_, 't')) => result.push('\t'),
+                Some((_, other)) => {
+                    result.push('\\');
+                    result.push(other);
+                }
+                None => return Err(offset),
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Parses numeric values, distinguishing between integers and floats.
     pub fn parse_number(raw: &str) -> Result<ParsedValue, ValueParseError> {
         // Numbers with a period are floats (42.0)
@@ -250,6 +305,7 @@ impl ParsedValue {
         node: Node<'a>,
         source: &'a str,
         path: &'a PathBuf,
+        timezone: FixedOffset,
     ) -> Result<ParsedValue, ValueParseError> {
         // For lists, we walk each child value node and parse it
         let mut items: Vec<ParsedValue> = Vec::new();
@@ -261,7 +317,7 @@ impl ParsedValue {
             for child in list_node.children(&mut list_cursor) {
                 if child.kind() == VALUE_KIND {
                     // Recursively parse the list child value
-                    let item = Self::from_node(child, source, path)?;
+                    let item = Self::from_node_in(child, source, path, timezone)?;
                     items.push(item);
                 }
             }
@@ -273,16 +329,20 @@ impl ParsedValue {
 
     /// Parses date values (`2024-03-20`) as datetime at midnight local time.
     pub fn parse_date(raw: &str) -> Result<ParsedValue, ValueParseError> {
+        Self::parse_date_in(raw, local_timezone())
+    }
+
+    /// Parses date values (`2024-03-20`) as datetime at midnight in a timezone.
+    pub fn parse_date_in(raw: &str, timezone: FixedOffset) -> Result<ParsedValue, ValueParseError> {
         // Parse "naive date" in year-month-day format (2025-07-31)
         let date = NaiveDate::parse_from_str(raw, "%Y-%m-%d")
             .map_err(|_| ValueParseError::InvalidDate(raw.to_string()))?;
 
-        // Assume time is midnight local time
+        // Assume time is midnight in the timezone
         let datetime = date.and_hms_opt(0, 0, 0).unwrap();
-        let local_offset = Local::now().offset().fix();
 
         // Convert from local datetime to timezoned datetime
-        let with_tz = local_offset
+        let with_tz = timezone
             .from_local_datetime(&datetime)
             .single()
             .ok_or_else(|| ValueParseError::InvalidDate(raw.to_string()))?;
@@ -291,7 +351,17 @@ impl ParsedValue {
     }
 
     /// Parses datetime values with optional timezone (`2024-03-20 at 14:30 UTC-5`).
+    ///
+    /// Datetimes without a timezone are in the machine's local timezone.
     pub fn parse_datetime(raw: &str) -> Result<ParsedValue, ValueParseError> {
+        Self::parse_datetime_in(raw, local_timezone())
+    }
+
+    /// Parses datetime values, using a timezone when the value doesn't give one.
+    pub fn parse_datetime_in(
+        raw: &str,
+        timezone: FixedOffset,
+    ) -> Result<ParsedValue, ValueParseError> {
         // Datetimes start with year-month-day (2025-07-31) followed by " at ", then time (09:42), optionally timezone " UTC+3"
         let parts: Vec<&str> = raw.split(" at ").collect();
         match parts.as_slice() {
@@ -306,9 +376,8 @@ impl ParsedValue {
                     let offset_str = tz_parts.get(1).unwrap_or(&"");
                     (tz_parts[0], Self::parse_utc_offset(offset_str)?)
                 } else {
-                    // No timezone specified - use local timezone
-                    let local_offset = Local::now().offset().fix();
-                    (*time_and_tz, local_offset)
+                    // No timezone specified - use the default timezone
+                    (*time_and_tz, timezone)
                 };
 
                 // Parse time (handle both h:mm and hh:mm)
@@ -392,6 +461,14 @@ impl ParsedValue {
             .to_string()
     }
 
+    /// Parses a timezone written like in datetime values (`UTC`, `UTC+2`, `UTC-5`).
+    pub fn parse_timezone(raw: &str) -> Result<FixedOffset, ValueParseError> {
+        let offset_str = raw
+            .strip_prefix("UTC")
+            .ok_or_else(|| ValueParseError::InvalidTimezone(raw.to_string()))?;
+        Self::parse_utc_offset(offset_str)
+    }
+
     /// Parses UTC timezone offset strings (e.g., "+3", "-5", or empty for UTC).
     fn parse_utc_offset(offset_str: &str) -> Result<FixedOffset, ValueParseError> {
         if offset_str.is_empty() {
@@ -407,3 +484,8 @@ impl ParsedValue {
             .ok_or_else(|| ValueParseError::InvalidTimezone(offset_str.to_string()))
     }
 }
+
+/// Gets the machine's current offset from UTC.
+pub fn local_timezone() -> FixedOffset {
+    Local::now().offset().fix()
+}
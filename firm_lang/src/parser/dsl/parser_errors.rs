@@ -1,5 +1,7 @@
 use std::fmt;
 
+use tree_sitter::{Point, Range};
+
 /// Errors that can occur during parser initialization.
 #[derive(Debug, Clone, PartialEq)]
 pub enum LanguageError {
@@ -43,6 +45,11 @@ pub enum ValueParseError {
     InvalidDate(String),
     InvalidDateTime(String),
     InvalidTimezone(String),
+    InvalidEscape {
+        source: String,
+        line: usize,
+        column: usize,
+    },
     HeterogeneousList {
         expected_type: String,
         found_type: String,
@@ -113,6 +120,17 @@ impl fmt::Display for ValueParseError {
             ValueParseError::InvalidTimezone(timezone) => {
                 write!(f, "Timezone offset could not be parsed: '{}'", timezone)
             }
+            ValueParseError::InvalidEscape {
+                source,
+                line,
+                column,
+            } => {
+                write!(
+                    f,
+                    "String ends with a backslash that doesn't escape anything at line {}, column {}: {} (use \\\\ for a literal backslash)",
+                    line, column, source
+                )
+            }
             ValueParseError::HeterogeneousList {
                 expected_type,
                 found_type,
@@ -127,3 +145,78 @@ impl fmt::Display for ValueParseError {
         }
     }
 }
+
+impl ValueParseError {
+    /// Moves a position relative to a value to where the value starts in the source.
+    ///
+    /// Positions on the value's first line are shifted by its column too.
+    pub(crate) fn offset_by(self, start: Point) -> Self {
+        match self {
+            ValueParseError::InvalidEscape {
+                source,
+                line,
+                column,
+            } => ValueParseError::InvalidEscape {
+                source,
+                column: if line == 1 {
+                    column + start.column
+                } else {
+                    column
+                },
+                line: line + start.row,
+            },
+            other => other,
+        }
+    }
+}
+
+/// A syntax error in DSL source, as marked by tree-sitter's error recovery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    /// The span of the unexpected or missing syntax.
+    pub range: Range,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message,
+            self.range.start_point.row + 1,
+            self.range.start_point.column + 1
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_escape(line: usize, column: usize) -> ValueParseError {
+        ValueParseError::InvalidEscape {
+            source: "\"a\\\"".to_string(),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn test_offset_by_shifts_first_line_column() {
+        let error = invalid_escape(1, 3).offset_by(Point { row: 4, column: 11 });
+        assert_eq!(error, invalid_escape(5, 14));
+    }
+
+    #[test]
+    fn test_offset_by_keeps_later_line_column() {
+        let error = invalid_escape(2, 3).offset_by(Point { row: 4, column: 11 });
+        assert_eq!(error, invalid_escape(6, 3));
+    }
+
+    #[test]
+    fn test_offset_by_ignores_other_errors() {
+        let error = ValueParseError::MissingValue.offset_by(Point { row: 4, column: 11 });
+        assert_eq!(error, ValueParseError::MissingValue);
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use tree_sitter::{Language, Parser};
+use tree_sitter::{Language, Parser, Tree};
 
 use super::LanguageError;
 use super::ParsedSource;
@@ -15,15 +15,20 @@ fn language() -> Language {
 /// This is the main entry point for parsing Firm DSL. It initializes
 /// a tree-sitter parser and returns ParsedSource for further processing.
 pub fn parse_source(source: String, path: Option<PathBuf>) -> Result<ParsedSource, LanguageError> {
+    let tree = parse_tree(&source, None)?;
+    Ok(ParsedSource::new(source, tree, path.unwrap_or_default()))
+}
+
+/// Parses source code into a tree, reusing the unchanged parts of an edited old tree.
+pub(super) fn parse_tree(source: &str, old_tree: Option<&Tree>) -> Result<Tree, LanguageError> {
     let mut parser = Parser::new();
     parser
         .set_language(&language())
         .map_err(|_| LanguageError::IncompatibleLanguageVersion)?;
 
-    match parser.parse(&source, None) {
-        Some(tree) => Ok(ParsedSource::new(source, tree, path.unwrap_or_default())),
-        None => Err(LanguageError::LanguageNotInitialized),
-    }
+    parser
+        .parse(source, old_tree)
+        .ok_or(LanguageError::LanguageNotInitialized)
 }
 
 #[cfg(test)]
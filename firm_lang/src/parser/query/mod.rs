@@ -2,4 +2,4 @@ mod parsed_query;
 mod parser;
 
 pub use parsed_query::*;
-pub use parser::{QueryParseError, parse_query};
+pub use parser::{QueryParseError, parse_expression, parse_query, syntax_error_offset};
@@ -7,7 +7,10 @@ use std::fmt;
 pub struct ParsedQuery {
     pub from: ParsedFromClause,
     pub operations: Vec<ParsedOperation>,
+    pub group_by: Option<ParsedField>,
     pub aggregation: Option<ParsedAggregation>,
+    pub aggregate_order: Option<ParsedAggregateOrder>,
+    pub aggregate_limit: Option<usize>,
 }
 
 /// The FROM clause specifies the starting entity type(s)
@@ -16,10 +19,11 @@ pub struct ParsedFromClause {
     pub selector: ParsedEntitySelector,
 }
 
-/// Entity selector: specific type or wildcard
+/// Entity selector: specific type, type with its subtypes, or wildcard
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParsedEntitySelector {
     Type(String),
+    TypeWithSubtypes(String),
     Wildcard,
 }
 
@@ -29,7 +33,8 @@ pub enum ParsedOperation {
     Where(ParsedCompoundCondition),
     Related {
         degree: Option<usize>,
-        selector: Option<ParsedEntitySelector>,
+        /// The types to keep, or none to keep every type
+        selectors: Vec<ParsedEntitySelector>,
     },
     Order {
         field: ParsedField,
@@ -53,6 +58,13 @@ pub enum ParsedAggregation {
     Median(ParsedField),
 }
 
+/// Ordering of grouped results by their aggregate: order count desc
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedAggregateOrder {
+    pub aggregate: String,
+    pub direction: ParsedDirection,
+}
+
 /// A compound condition combining multiple conditions with AND/OR
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedCompoundCondition {
@@ -126,6 +138,7 @@ impl fmt::Display for ParsedEntitySelector {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ParsedEntitySelector::Type(t) => write!(f, "{}", t),
+            ParsedEntitySelector::TypeWithSubtypes(t) => write!(f, "{}+", t),
             ParsedEntitySelector::Wildcard => write!(f, "*"),
         }
     }
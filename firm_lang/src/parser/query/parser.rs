@@ -1,5 +1,7 @@
 //! Parser for query language using pest
 
+use firm_core::FieldId;
+use firm_core::expression::{Expression, Operator};
 use pest::Parser;
 use pest_derive::Parser;
 
@@ -34,7 +36,10 @@ pub fn parse_query(input: &str) -> Result<ParsedQuery, QueryParseError> {
 
     let mut from_clause = None;
     let mut operations = Vec::new();
+    let mut group_by = None;
     let mut aggregation = None;
+    let mut aggregate_order = None;
+    let mut aggregate_limit = None;
 
     for pair in pairs {
         if pair.as_rule() == Rule::query {
@@ -46,9 +51,21 @@ pub fn parse_query(input: &str) -> Result<ParsedQuery, QueryParseError> {
                     Rule::operation => {
                         operations.push(parse_operation(inner_pair)?);
                     }
+                    Rule::group_clause => {
+                        group_by = Some(parse_group_clause(inner_pair)?);
+                    }
                     Rule::aggregation => {
                         aggregation = Some(parse_aggregation(inner_pair)?);
                     }
+                    Rule::aggregate_order_clause => {
+                        aggregate_order = Some(parse_aggregate_order_clause(inner_pair)?);
+                    }
+                    // A limit outside of an operation follows the aggregation
+                    Rule::limit_clause => {
+                        if let ParsedOperation::Limit(limit) = parse_limit_clause(inner_pair)? {
+                            aggregate_limit = Some(limit);
+                        }
+                    }
                     Rule::EOI => {}
                     _ => {}
                 }
@@ -63,17 +80,114 @@ pub fn parse_query(input: &str) -> Result<ParsedQuery, QueryParseError> {
     Ok(ParsedQuery {
         from,
         operations,
+        group_by,
         aggregation,
+        aggregate_order,
+        aggregate_limit,
     })
 }
 
+/// Finds where a query's syntax stops being valid, as a byte offset into the input.
+///
+/// Returns None if the syntax is valid.
+pub fn syntax_error_offset(input: &str) -> Option<usize> {
+    let error = QueryParser::parse(Rule::query, input).err()?;
+    match error.location {
+        pest::error::InputLocation::Pos(offset) => Some(offset),
+        pest::error::InputLocation::Span((start, _)) => Some(start),
+    }
+}
+
+/// Parse an arithmetic expression over fields, like `(current - start) / (target - start)`
+///
+/// Expressions use the query language's field names and numbers, with `+`, `-`,
+/// `*`, `/` and parentheses.
+pub fn parse_expression(input: &str) -> Result<Expression, QueryParseError> {
+    let pair = QueryParser::parse(Rule::expression_input, input)
+        .map_err(|e| QueryParseError::SyntaxError(e.to_string()))?
+        .next()
+        .and_then(|p| p.into_inner().next())
+        .ok_or_else(|| QueryParseError::SyntaxError("Empty expression".to_string()))?;
+
+    parse_expression_rule(pair)
+}
+
+/// Parse an expression, term, factor, negation, field or number rule into an Expression.
+fn parse_expression_rule(pair: pest::iterators::Pair<Rule>) -> Result<Expression, QueryParseError> {
+    match pair.as_rule() {
+        // Operators of the same precedence apply from left to right
+        Rule::expression | Rule::term => {
+            let mut inner = pair.into_inner();
+            let first = inner
+                .next()
+                .ok_or_else(|| QueryParseError::SyntaxError("Empty expression".to_string()))?;
+            let mut expression = parse_expression_rule(first)?;
+
+            while let Some(operator_pair) = inner.next() {
+                let operator = match operator_pair.as_str() {
+                    "+" => Operator::Add,
+                    "-" => Operator::Subtract,
+                    "*" => Operator::Multiply,
+                    _ => Operator::Divide,
+                };
+                let operand = inner.next().ok_or_else(|| {
+                    QueryParseError::SyntaxError(format!(
+                        "Missing operand after '{}'",
+                        operator_pair.as_str()
+                    ))
+                })?;
+                expression =
+                    Expression::binary(operator, expression, parse_expression_rule(operand)?);
+            }
+            Ok(expression)
+        }
+        Rule::factor | Rule::negation => {
+            let is_negation = pair.as_rule() == Rule::negation;
+            let inner = pair
+                .into_inner()
+                .next()
+                .ok_or_else(|| QueryParseError::SyntaxError("Empty expression".to_string()))?;
+            let expression = parse_expression_rule(inner)?;
+            if is_negation {
+                Ok(Expression::Negate(Box::new(expression)))
+            } else {
+                Ok(expression)
+            }
+        }
+        Rule::field_name => Ok(Expression::Field(FieldId::new(pair.as_str()))),
+        Rule::number => {
+            let num_str = pair.as_str();
+            let number = if num_str.contains('.') {
+                num_str.parse::<f64>().map(Expression::Float).ok()
+            } else {
+                num_str.parse::<i64>().map(Expression::Integer).ok()
+            };
+            number.ok_or_else(|| {
+                QueryParseError::InvalidNumber(format!("Cannot parse number: {}", num_str))
+            })
+        }
+        _ => Err(QueryParseError::SyntaxError(format!(
+            "Unexpected {:?} in expression",
+            pair.as_rule()
+        ))),
+    }
+}
+
 fn parse_from_clause(
     pair: pest::iterators::Pair<Rule>,
 ) -> Result<ParsedFromClause, QueryParseError> {
     for inner_pair in pair.into_inner() {
-        if inner_pair.as_rule() == Rule::entity_selector {
-            let selector = parse_entity_selector(inner_pair)?;
-            return Ok(ParsedFromClause { selector });
+        match inner_pair.as_rule() {
+            Rule::entity_selector => {
+                let selector = parse_entity_selector(inner_pair)?;
+                return Ok(ParsedFromClause { selector });
+            }
+            Rule::subtype_selector => {
+                return Ok(ParsedFromClause {
+                    selector: parse_subtype_selector(inner_pair),
+                });
+            }
+            _ => {}
         }
     }
     Err(QueryParseError::SyntaxError(
@@ -92,6 +206,11 @@ fn parse_entity_selector(
     }
 }
 
+fn parse_subtype_selector(pair: pest::iterators::Pair<Rule>) -> ParsedEntitySelector {
+    let entity_type = pair.as_str().trim_end_matches('+').to_string();
+    ParsedEntitySelector::TypeWithSubtypes(entity_type)
+}
+
 fn parse_operation(pair: pest::iterators::Pair<Rule>) -> Result<ParsedOperation, QueryParseError> {
     let inner_pair = pair
         .into_inner()
@@ -327,7 +446,7 @@ fn parse_related_clause(
     pair: pest::iterators::Pair<Rule>,
 ) -> Result<ParsedOperation, QueryParseError> {
     let mut degree = None;
-    let mut selector = None;
+    let mut selectors = Vec::new();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
@@ -345,13 +464,25 @@ fn parse_related_clause(
                 degree = Some(degree_num);
             }
             Rule::entity_selector => {
-                selector = Some(parse_entity_selector(inner_pair)?);
+                selectors.push(parse_entity_selector(inner_pair)?);
+            }
+            Rule::subtype_selector => {
+                selectors.push(parse_subtype_selector(inner_pair));
+            }
+            Rule::related_types => {
+                for type_pair in inner_pair.into_inner().flat_map(|pair| pair.into_inner()) {
+                    let selector = match type_pair.as_rule() {
+                        Rule::subtype_selector => parse_subtype_selector(type_pair),
+                        _ => ParsedEntitySelector::Type(type_pair.as_str().to_string()),
+                    };
+                    selectors.push(selector);
+                }
             }
             _ => {}
         }
     }
 
-    Ok(ParsedOperation::Related { degree, selector })
+    Ok(ParsedOperation::Related { degree, selectors })
 }
 
 fn parse_order_clause(
@@ -515,7 +646,46 @@ fn parse_aggregation_field(
     parse_field_from_rule(inner)
 }
 
-/// Parse a field reference from a select_field or aggregation_field wrapper rule.
+fn parse_group_clause(pair: pest::iterators::Pair<Rule>) -> Result<ParsedField, QueryParseError> {
+    let field_pair = pair
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::group_field)
+        .ok_or_else(|| QueryParseError::SyntaxError("Missing field in group clause".to_string()))?;
+
+    parse_field_ref(field_pair)
+}
+
+fn parse_aggregate_order_clause(
+    pair: pest::iterators::Pair<Rule>,
+) -> Result<ParsedAggregateOrder, QueryParseError> {
+    let mut aggregate = None;
+    let mut direction = ParsedDirection::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::aggregate_name => aggregate = Some(inner_pair.as_str().to_string()),
+            Rule::direction => {
+                direction = match inner_pair.as_str() {
+                    "asc" => ParsedDirection::Ascending,
+                    "desc" => ParsedDirection::Descending,
+                    _ => ParsedDirection::default(),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    let aggregate = aggregate.ok_or_else(|| {
+        QueryParseError::SyntaxError("Missing aggregate in order clause".to_string())
+    })?;
+
+    Ok(ParsedAggregateOrder {
+        aggregate,
+        direction,
+    })
+}
+
+/// Parse a field reference from a select_field, aggregation_field or group_field wrapper rule.
 fn parse_field_ref(pair: pest::iterators::Pair<Rule>) -> Result<ParsedField, QueryParseError> {
     let inner = pair.into_inner().next().ok_or_else(|| {
         QueryParseError::SyntaxError("Invalid field reference".to_string())
@@ -0,0 +1,196 @@
+use std::{fmt, path::PathBuf};
+
+use firm_core::{EntityId, compose_entity_id};
+use tree_sitter::{Node, Range};
+
+use super::Workspace;
+use crate::parser::dsl::{ParsedEntity, ParsedSchema};
+
+const COMMENT_KIND: &str = "comment";
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+const SCHEMA_BLOCK_KIND: &str = "schema_block";
+
+/// The markers looked for when none are given.
+pub const DEFAULT_ANNOTATION_MARKERS: &[&str] = &["TODO", "FIXME", "NOTE"];
+
+/// The block an annotation is written in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnnotationOwner {
+    Entity(EntityId),
+    Schema(String),
+}
+
+impl fmt::Display for AnnotationOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationOwner::Entity(entity_id) => write!(f, "{}", entity_id),
+            AnnotationOwner::Schema(name) => write!(f, "schema {}", name),
+        }
+    }
+}
+
+/// A marked comment, like `// TODO: add an email`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub path: PathBuf,
+    /// The marker that matched, as given (e.g. "TODO").
+    pub marker: String,
+    /// The text after the marker, with any colon trimmed.
+    pub text: String,
+    /// The 1-based line of the marker.
+    pub line: usize,
+    /// The range of the whole comment.
+    pub range: Range,
+    /// The entity or schema the comment is inside, if any.
+    pub owner: Option<AnnotationOwner>,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.marker)?;
+        if !self.text.is_empty() {
+            write!(f, ": {}", self.text)?;
+        }
+        if let Some(owner) = &self.owner {
+            write!(f, " ({})", owner)?;
+        }
+        Ok(())
+    }
+}
+
+impl Workspace {
+    /// Finds comments that start with one of the markers, across the workspace.
+    ///
+    /// Each line of a comment is matched on its own, so a block comment can
+    /// hold several annotations. Markers are case-sensitive and must be followed
+    /// by a colon, whitespace or the end of the line. Annotations are returned
+    /// in file path order, then in source order.
+    pub fn annotations(&self, markers: &[&str]) -> Vec<Annotation> {
+        let mut annotations = Vec::new();
+
+        for (path, file) in &self.files {
+            let source = &file.parsed.source;
+            let mut comments = Vec::new();
+            collect_comments(file.parsed.tree.root_node(), &mut comments);
+
+            for comment in comments {
+                let owner = find_owner(comment, source, &file.parsed.path);
+                let text = &source[comment.byte_range()];
+
+                for (index, line) in comment_lines(text).enumerate() {
+                    if let Some((marker, text)) = match_marker(line, markers) {
+                        annotations.push(Annotation {
+                            path: path.clone(),
+                            marker: marker.to_string(),
+                            text: text.to_string(),
+                            line: comment.start_position().row + index + 1,
+                            range: comment.range(),
+                            owner: owner.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        annotations
+    }
+}
+
+/// Collects every comment node in a tree, in source order.
+fn collect_comments<'a>(node: Node<'a>, comments: &mut Vec<Node<'a>>) {
+    if node.kind() == COMMENT_KIND {
+        comments.push(node);
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_comments(child, comments);
+    }
+}
+
+/// Finds the nearest entity or schema block around a node.
+fn find_owner(node: Node<'_>, source: &str, path: &PathBuf) -> Option<AnnotationOwner> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        match parent.kind() {
+            ENTITY_BLOCK_KIND => {
+                let entity = ParsedEntity::new(parent, source, path);
+                let entity_id = compose_entity_id(entity.entity_type()?, entity.id()?);
+                return Some(AnnotationOwner::Entity(entity_id));
+            }
+            SCHEMA_BLOCK_KIND => {
+                let schema = ParsedSchema::new(parent, source, path);
+                return Some(AnnotationOwner::Schema(schema.name()?.to_string()));
+            }
+            _ => current = parent.parent(),
+        }
+    }
+    None
+}
+
+/// Splits comment text into lines with the comment syntax removed.
+fn comment_lines(text: &str) -> impl Iterator<Item = &str> {
+    let text = text.strip_prefix("//").unwrap_or(text);
+    let text = text.strip_prefix("/*").unwrap_or(text);
+    let text = text.strip_suffix("*/").unwrap_or(text);
+
+    text.lines().map(|line| {
+        // Block comments often start each line with an asterisk
+        let line = line.trim();
+        line.strip_prefix('*').unwrap_or(line).trim()
+    })
+}
+
+/// Matches a marker at the start of a comment line, returning it and the rest of the line.
+fn match_marker<'a>(line: &'a str, markers: &[&'a str]) -> Option<(&'a str, &'a str)> {
+    markers.iter().find_map(|marker| {
+        let rest = line.strip_prefix(marker)?;
+        match rest.chars().next() {
+            None => Some((*marker, "")),
+            Some(':') => Some((*marker, rest[1..].trim())),
+            Some(c) if c.is_whitespace() => Some((*marker, rest.trim())),
+            Some(_) => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_marker_with_colon() {
+        assert_eq!(
+            match_marker("TODO: add email", DEFAULT_ANNOTATION_MARKERS),
+            Some(("TODO", "add email"))
+        );
+    }
+
+    #[test]
+    fn test_match_marker_with_space_or_alone() {
+        assert_eq!(
+            match_marker("FIXME wrong rate", DEFAULT_ANNOTATION_MARKERS),
+            Some(("FIXME", "wrong rate"))
+        );
+        assert_eq!(
+            match_marker("NOTE", DEFAULT_ANNOTATION_MARKERS),
+            Some(("NOTE", ""))
+        );
+    }
+
+    #[test]
+    fn test_match_marker_needs_word_boundary() {
+        assert_eq!(match_marker("TODOS are fun", DEFAULT_ANNOTATION_MARKERS), None);
+        assert_eq!(match_marker("not a TODO", DEFAULT_ANNOTATION_MARKERS), None);
+    }
+
+    #[test]
+    fn test_comment_lines_strip_syntax() {
+        let lines: Vec<&str> = comment_lines("// TODO: one").collect();
+        assert_eq!(lines, vec!["TODO: one"]);
+
+        let lines: Vec<&str> = comment_lines("/*\n * NOTE: two\n * FIXME three\n */").collect();
+        assert_eq!(lines, vec!["", "NOTE: two", "FIXME three", ""]);
+    }
+}
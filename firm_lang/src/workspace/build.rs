@@ -1,23 +1,122 @@
-use firm_core::{Entity, EntitySchema, EntityType};
-use std::collections::HashMap;
+use firm_core::schema::ValidationErrorType;
+use firm_core::{
+    Entity, EntityId, EntitySchema, EntityType, FieldId, FieldValue, TAGS_FIELD_NAME,
+    compose_entity_id, decompose_entity_id,
+};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use super::{Workspace, WorkspaceError};
+use super::path_check::resolve;
+use super::query_fields::{QUERY_FIELD_FORMAT, check_query_string};
+use super::timestamps::git_checkout;
+use super::validation_scripts::{ScriptMessage, ScriptOutcome, run_validation_script};
+use super::{EntitySource, NamedQuery, Workspace, WorkspaceError, WorkspaceWarning};
+use crate::convert::to_entity::convert_entity_in;
+use crate::parser::dsl::{QUERY_BLOCK_TYPE, local_timezone};
 
 /// Holds converted entities and schemas after the workspace is built.
 #[derive(Debug)]
 pub struct WorkspaceBuild {
     pub entities: Vec<Entity>,
     pub schemas: Vec<EntitySchema>,
+    /// The named queries defined in the workspace, sorted by name.
+    pub queries: Vec<NamedQuery>,
+    pub warnings: Vec<WorkspaceWarning>,
+    sources: HashMap<EntityId, Vec<EntitySource>>,
+    /// Entities left out of a build that skips blocks with syntax errors
+    skipped_entities: HashSet<EntityId>,
+    /// Schemas left out of a build that skips blocks with syntax errors
+    skipped_types: HashSet<EntityType>,
 }
 
 impl WorkspaceBuild {
     pub fn new(entities: Vec<Entity>, schemas: Vec<EntitySchema>) -> Self {
-        WorkspaceBuild { entities, schemas }
+        WorkspaceBuild {
+            entities,
+            schemas,
+            queries: Vec::new(),
+            warnings: Vec::new(),
+            sources: HashMap::new(),
+            skipped_entities: HashSet::new(),
+            skipped_types: HashSet::new(),
+        }
+    }
+
+    /// Attaches warnings collected while building.
+    pub fn with_warnings(mut self, warnings: Vec<WorkspaceWarning>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Attaches the named queries defined in the workspace.
+    pub fn with_queries(mut self, queries: Vec<NamedQuery>) -> Self {
+        self.queries = queries;
+        self
+    }
+
+    /// Attaches the source locations recorded for each entity while building.
+    pub fn with_sources(mut self, sources: HashMap<EntityId, Vec<EntitySource>>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Attaches the entities and schemas that were left out for syntax errors.
+    pub fn with_skipped(
+        mut self,
+        skipped_entities: HashSet<EntityId>,
+        skipped_types: HashSet<EntityType>,
+    ) -> Self {
+        self.skipped_entities = skipped_entities;
+        self.skipped_types = skipped_types;
+        self
+    }
+
+    /// Whether an entity is defined in a block that was skipped for a syntax error.
+    ///
+    /// Entities of a type whose schema was skipped count as skipped too, so
+    /// references to them aren't reported as broken on top of the syntax error.
+    pub fn is_skipped(&self, entity_id: &EntityId) -> bool {
+        let (entity_type, _) = decompose_entity_id(entity_id.as_str());
+        self.skipped_entities.contains(entity_id)
+            || self.skipped_types.contains(&EntityType::new(entity_type))
+    }
+
+    /// Gets the source locations that contributed to an entity.
+    ///
+    /// The defining block comes first. Returns an empty slice for unknown entities.
+    pub fn entity_sources(&self, entity_id: &EntityId) -> &[EntitySource] {
+        self.sources
+            .get(entity_id)
+            .map(|sources| sources.as_slice())
+            .unwrap_or(&[])
     }
 }
 
+/// A schema's validation script, as written and canonicalized inside the workspace root.
+struct ValidationScript {
+    script: PathBuf,
+    resolved: PathBuf,
+}
+
+/// An entity to run its schema's validation script on once the entities are built.
+struct ScriptCheck<'a> {
+    index: usize,
+    path: PathBuf,
+    line: usize,
+    field_lines: HashMap<String, usize>,
+    script: &'a ValidationScript,
+}
+
 impl Workspace {
     /// Build the workspace from all loaded files.
+    ///
+    /// Entities get `created` and `modified` timestamps from git blame when the
+    /// workspace is in a git work tree, or from their file's times otherwise.
+    /// The timestamps are kept for the next build of files that don't change.
+    /// Entities of types left out by the type filter aren't built. If validation
+    /// scripts are allowed, each entity whose schema declares one is run through it.
     pub fn build(&mut self) -> Result<WorkspaceBuild, WorkspaceError> {
         self.build_with_progress(|current, total, phase| {
             log::debug!("{}: {}/{}", phase, current, total);
@@ -25,15 +124,40 @@ impl Workspace {
     }
 
     /// Build the workspace with progress reporting.
-    pub fn build_with_progress<F>(
+    pub fn build_with_progress<F>(&mut self, progress: F) -> Result<WorkspaceBuild, WorkspaceError>
+    where
+        F: FnMut(usize, usize, &str),
+    {
+        self.build_blocks(progress, false)
+    }
+
+    /// Build the workspace from the blocks without syntax errors.
+    ///
+    /// Used for diagnostics, which report syntax errors separately, so the
+    /// rest of a broken file is still checked.
+    pub(super) fn build_skipping_syntax_errors(
+        &mut self,
+    ) -> Result<WorkspaceBuild, WorkspaceError> {
+        self.build_blocks(
+            |current, total, phase| log::debug!("{}: {}/{}", phase, current, total),
+            true,
+        )
+    }
+
+    fn build_blocks<F>(
         &mut self,
         mut progress: F,
+        skip_syntax_errors: bool,
     ) -> Result<WorkspaceBuild, WorkspaceError>
     where
         F: FnMut(usize, usize, &str),
     {
         // Initialize empty schema collection
         let mut schemas: HashMap<EntityType, EntitySchema> = HashMap::new();
+        let mut warnings = Vec::new();
+        let mut skipped_schemas: HashSet<EntityType> = HashSet::new();
+        let mut scripts: HashMap<EntityType, ValidationScript> = HashMap::new();
+        let working_dir = self.root_path.clone().unwrap_or_else(|| PathBuf::from("."));
 
         let files_to_process = self.num_files();
         let mut files_processed = 0;
@@ -43,9 +167,26 @@ impl Workspace {
         for (path, file) in &self.files {
             let parsed_schemas = file.parsed.schemas();
             for parsed_schema in &parsed_schemas {
+                if skip_syntax_errors && parsed_schema.has_error() {
+                    if let Some(name) = parsed_schema.name() {
+                        skipped_schemas.insert(EntityType::new(name));
+                    }
+                    continue;
+                }
                 let schema = EntitySchema::try_from(parsed_schema)
                     .map_err(|err| WorkspaceError::ParseError(path.clone(), err.to_string()))?;
 
+                // Blocks of this type are named queries, so no entity could use the schema
+                if schema.entity_type.as_str() == QUERY_BLOCK_TYPE {
+                    return Err(WorkspaceError::ValidationError(
+                        path.clone(),
+                        format!(
+                            "Schema name '{}' is reserved for named queries",
+                            QUERY_BLOCK_TYPE
+                        ),
+                    ));
+                }
+
                 if schemas.contains_key(&schema.entity_type) {
                     return Err(WorkspaceError::ValidationError(
                         path.clone(),
@@ -53,12 +194,77 @@ impl Workspace {
                     ));
                 }
 
+                // Allowed values only constrain enum fields
+                for parsed_field in parsed_schema.fields() {
+                    let is_enum = parsed_field.field_type().is_ok_and(|t| t == "enum");
+                    if is_enum || !parsed_field.has_allowed_values() {
+                        continue;
+                    }
+
+                    let warning = WorkspaceWarning::UnusedAllowedValues {
+                        path: path.clone(),
+                        schema: schema.entity_type.to_string(),
+                        field: parsed_field.name().unwrap_or_default(),
+                        line: parsed_field.line(),
+                    };
+                    log::warn!("{}", warning);
+                    warnings.push(warning);
+                }
+
+                if let Some((script, line)) = parsed_schema.validate_script() {
+                    if self.validation_scripts.is_none() {
+                        let warning = WorkspaceWarning::SkippedScript {
+                            path: path.clone(),
+                            schema: schema.entity_type.to_string(),
+                            script,
+                            line,
+                        };
+                        log::warn!("{}", warning);
+                        warnings.push(warning);
+                    } else {
+                        // Scripts must be part of the workspace, not just anything on the machine
+                        let resolved = self.root_path.as_deref().and_then(|root| {
+                            script_in_workspace(root, &resolve(&working_dir, &script))
+                        });
+                        let Some(resolved) = resolved else {
+                            return Err(WorkspaceError::ValidationError(
+                                path.clone(),
+                                format!(
+                                    "Validation script '{}' of schema '{}' isn't a file in the workspace",
+                                    script.display(),
+                                    schema.entity_type
+                                ),
+                            ));
+                        };
+                        scripts.insert(
+                            schema.entity_type.clone(),
+                            ValidationScript { script, resolved },
+                        );
+                    }
+                }
+
                 schemas.insert(schema.entity_type.clone(), schema);
             }
         }
 
+        // Query fields are checked against every schema, since queries can traverse types
+        let schema_list: Vec<EntitySchema> = schemas.values().cloned().collect();
+
+        // Timestamps need git or the file system, so they're cached for files that didn't change
+        let git = self.root_path.as_deref().and_then(git_checkout);
+        let sources: Vec<(&PathBuf, &str)> = self
+            .files
+            .iter()
+            .map(|(path, file)| (path, file.parsed.source.as_str()))
+            .collect();
+        self.timestamps.refresh(&sources, git.as_ref());
+
         // Second pass: Walk through workspace files to build and validate entities against schemas
         let mut entities = Vec::new();
+        let mut sources: HashMap<EntityId, Vec<EntitySource>> = HashMap::new();
+        let mut skipped_entities: HashSet<EntityId> = HashSet::new();
+        let mut script_checks: Vec<ScriptCheck> = Vec::new();
+        let timezone = self.timezone.unwrap_or_else(local_timezone);
 
         files_processed = 0;
 
@@ -67,31 +273,252 @@ impl Workspace {
 
             let parsed_entities = file.parsed.entities();
             for parsed_entity in &parsed_entities {
+                let skipped_schema = parsed_entity
+                    .entity_type()
+                    .is_some_and(|t| skipped_schemas.contains(&EntityType::new(t)));
+                if skip_syntax_errors && (parsed_entity.has_error() || skipped_schema) {
+                    if let (Some(entity_type), Some(id)) =
+                        (parsed_entity.entity_type(), parsed_entity.id())
+                    {
+                        skipped_entities.insert(compose_entity_id(entity_type, id));
+                    }
+                    continue;
+                }
+                if parsed_entity
+                    .entity_type()
+                    .is_some_and(|t| !self.type_filter.includes(t))
+                {
+                    continue;
+                }
                 // Build the entity
-                let entity = Entity::try_from(parsed_entity)
+                let mut entity = convert_entity_in(parsed_entity, timezone)
                     .map_err(|err| WorkspaceError::ParseError(path.clone(), err.to_string()))?;
 
+                let range = parsed_entity.range();
+                if let Some(timestamps) = self.timestamps.get(path) {
+                    (entity.created, entity.modified) =
+                        timestamps.for_rows(range.start_point.row, range.end_point.row);
+                }
+
+                // Flag fields that are assigned more than once (conversion keeps the last value)
+                for duplicate in parsed_entity.duplicate_fields() {
+                    let warning = WorkspaceWarning::DuplicateField {
+                        path: path.clone(),
+                        entity_id: entity.id.clone(),
+                        field: duplicate.name.clone(),
+                        first_line: duplicate.first_line(),
+                        duplicate_line: duplicate.duplicate_line(),
+                    };
+                    log::warn!("{}", warning);
+                    warnings.push(warning);
+                }
+
                 // Find the appropriate schema for this entity
                 let schema = schemas.get(&entity.entity_type).ok_or_else(|| {
                     WorkspaceError::MissingSchemaError(path.clone(), entity.entity_type.clone())
                 })?;
 
+                // Tags are metadata, unless the schema declares its own field by that name
+                if !schema.fields.contains_key(&FieldId::new(TAGS_FIELD_NAME)) {
+                    entity.extract_tags();
+                }
+
                 // Validate the entity against its schema
                 if let Err(validation_errors) = schema.validate(&entity) {
                     let error_msg = format!(
                         "Entity '{}' failed validation: {:?}",
                         entity.id, validation_errors
                     );
-                    return Err(WorkspaceError::ValidationError(path.clone(), error_msg));
+
+                    // Point at the first list item with the wrong type, if that's what failed
+                    let item_line = validation_errors.iter().find_map(|error| {
+                        let ValidationErrorType::MismatchedListItemType { index, .. } =
+                            error.error_type
+                        else {
+                            return None;
+                        };
+                        let field_id = error.field.as_ref()?;
+                        let fields = parsed_entity.fields();
+                        let parsed_field = fields.iter().rev().find(|field| {
+                            field.id().map(FieldId::new).as_ref() == Some(field_id)
+                        })?;
+                        let range = parsed_field.list_item_ranges().get(index).copied()?;
+                        Some(range.start_point.row + 1)
+                    });
+
+                    return Err(match item_line {
+                        Some(line) => {
+                            WorkspaceError::InvalidListItem(path.clone(), line, error_msg)
+                        }
+                        None => WorkspaceError::ValidationError(path.clone(), error_msg),
+                    });
+                }
+                schema.canonicalize(&mut entity);
+                schema.compute(&mut entity);
+
+                // Check string fields that the schema marks as holding a query
+                for parsed_field in parsed_entity.fields() {
+                    let Some(field_name) = parsed_field.id() else {
+                        continue;
+                    };
+
+                    let holds_query = schema
+                        .fields
+                        .get(&FieldId(field_name.to_string()))
+                        .is_some_and(|field| field.format() == Some(QUERY_FIELD_FORMAT));
+                    if !holds_query {
+                        continue;
+                    }
+
+                    let value = parsed_field.value().ok().and_then(|v| v.try_into().ok());
+                    if let Some(FieldValue::String(query)) = value
+                        && let Err(message) = check_query_string(&query, &schema_list)
+                    {
+                        let warning = WorkspaceWarning::InvalidQuery {
+                            path: path.clone(),
+                            entity_id: entity.id.clone(),
+                            field: field_name.to_string(),
+                            line: parsed_field.line(),
+                            message,
+                        };
+                        log::warn!("{}", warning);
+                        warnings.push(warning);
+                    }
+                }
+
+                if let Some(script) = scripts.get(&entity.entity_type) {
+                    let field_lines = parsed_entity
+                        .fields()
+                        .iter()
+                        .filter_map(|field| Some((field.id()?.to_string(), field.line())))
+                        .collect();
+                    script_checks.push(ScriptCheck {
+                        index: entities.len(),
+                        path: path.clone(),
+                        line: range.start_point.row + 1,
+                        field_lines,
+                        script,
+                    });
                 }
 
+                sources
+                    .entry(entity.id.clone())
+                    .or_default()
+                    .push(EntitySource::new(path.clone(), range));
+
                 entities.push(entity);
             }
 
             files_processed += 1;
         }
 
+        // Each script run starts a process, so they run in parallel once everything is built
+        if let Some(options) = &self.validation_scripts
+            && !script_checks.is_empty()
+        {
+            progress(script_checks.len(), 0, "Running validation scripts");
+            let outcomes: Vec<_> = script_checks
+                .par_iter()
+                .map(|check| {
+                    let entity = &entities[check.index];
+                    run_validation_script(&check.script.resolved, &working_dir, entity, options)
+                })
+                .collect();
+
+            for (check, outcome) in script_checks.iter().zip(outcomes) {
+                warnings.extend(script_warnings(check, &entities[check.index].id, outcome)?);
+            }
+        }
+
+        let queries =
+            self.collect_named_queries(&schema_list, skip_syntax_errors, &mut warnings)?;
+
         let schemas_vec = schemas.into_values().collect();
-        Ok(WorkspaceBuild::new(entities, schemas_vec))
+        Ok(WorkspaceBuild::new(entities, schemas_vec)
+            .with_queries(queries)
+            .with_warnings(warnings)
+            .with_sources(sources)
+            .with_skipped(skipped_entities, skipped_schemas))
+    }
+}
+
+/// Turns the outcome of a validation script into warnings, or an error if it rejected the entity.
+///
+/// Messages about a field point at its line, and other messages at the entity's.
+fn script_warnings(
+    check: &ScriptCheck,
+    entity_id: &EntityId,
+    outcome: Result<ScriptOutcome, String>,
+) -> Result<Vec<WorkspaceWarning>, WorkspaceError> {
+    let script = &check.script.script;
+    let outcome = outcome.map_err(|error| {
+        WorkspaceError::ScriptRejected(
+            check.path.clone(),
+            check.line,
+            format!(
+                "Validation script '{}' failed on entity '{}': {}",
+                script.display(),
+                entity_id,
+                error
+            ),
+        )
+    })?;
+
+    let line_of = |message: &ScriptMessage| {
+        message
+            .field
+            .as_ref()
+            .and_then(|field| check.field_lines.get(field))
+            .copied()
+            .unwrap_or(check.line)
+    };
+
+    if !outcome.accepted {
+        let messages: Vec<String> = outcome.messages.iter().map(describe).collect();
+        return Err(WorkspaceError::ScriptRejected(
+            check.path.clone(),
+            outcome.messages.first().map_or(check.line, line_of),
+            format!(
+                "Entity '{}' was rejected by validation script '{}': {}",
+                entity_id,
+                script.display(),
+                messages.join("; ")
+            ),
+        ));
     }
+
+    Ok(outcome
+        .messages
+        .iter()
+        .map(|message| {
+            let warning = WorkspaceWarning::ScriptMessage {
+                path: check.path.clone(),
+                entity_id: entity_id.clone(),
+                script: script.clone(),
+                line: line_of(message),
+                message: describe(message),
+            };
+            log::warn!("{}", warning);
+            warning
+        })
+        .collect())
+}
+
+/// A script message, prefixed with the field it's about.
+fn describe(message: &ScriptMessage) -> String {
+    match &message.field {
+        Some(field) => format!("{}: {}", field, message.message),
+        None => message.message.clone(),
+    }
+}
+
+/// Canonicalizes a validation script's path, if it's a file in the workspace.
+///
+/// Symlinks and `..` are followed before checking, so a script can't reach
+/// outside the root through them. Scripts that can't be canonicalized, like
+/// missing ones, aren't in the workspace.
+fn script_in_workspace(root_path: &Path, script: &Path) -> Option<PathBuf> {
+    let root_path = fs::canonicalize(root_path).ok()?;
+    let script = fs::canonicalize(script).ok()?;
+    (script.starts_with(&root_path) && script.is_file()).then_some(script)
 }
@@ -0,0 +1,421 @@
+use firm_core::schema::FieldSchema;
+use firm_core::{EntitySchema, EntityType, FieldId, FieldType, decompose_entity_id};
+use tree_sitter::{Node, Point};
+
+use super::WorkspaceBuild;
+use crate::parser::dsl::{ParsedEntity, parse_source};
+
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+const SCHEMA_BLOCK_KIND: &str = "schema_block";
+const SCHEMA_KEYWORD: &str = "schema";
+const INDENT: &str = "    ";
+
+/// What a completion inserts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionKind {
+    /// A field name from the entity's schema.
+    Field { required: bool },
+    /// An allowed value of an enum field.
+    EnumValue,
+    /// The ID of an entity of the referenced type.
+    EntityId,
+    /// A query keyword, like `from` or `where`.
+    Keyword,
+    /// An entity type to query, after `from` or `related`.
+    EntityType,
+    /// A comparison operator in a query condition.
+    Operator,
+    /// A new entity block for a schema, with its required fields.
+    EntityBlock,
+    /// A new schema block with a field.
+    SchemaBlock,
+}
+
+/// A suggestion for the text at the cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// A short description, like the field type or the full entity ID.
+    pub detail: String,
+    /// A snippet to insert instead of the label, with `$1`-style tab stops.
+    pub insert_text: Option<String>,
+}
+
+impl WorkspaceBuild {
+    /// Suggests completions at a position in a source document.
+    ///
+    /// The source is parsed as given, so it can be an unsaved editor buffer.
+    /// Schemas and entities come from this build. The position is zero-based,
+    /// with the column in bytes, like tree-sitter points. Suggestions are
+    /// filtered by what's already typed at the cursor:
+    /// - Field names inside an entity block, skipping fields already set
+    /// - Allowed values after `= enum"`, or all of them for an invalid value
+    /// - Entity IDs after `= <type>.`
+    /// - Query keywords, entity types, fields and operators inside fields
+    ///   holding a query (see `embedded_queries`)
+    /// - New entity blocks for each schema, and a schema block, at the top level
+    pub fn completions(&self, source: &str, position: Point) -> Vec<Completion> {
+        let Ok(parsed) = parse_source(source.to_string(), None) else {
+            return Vec::new();
+        };
+
+        let query_before_cursor = self
+            .embedded_queries(&parsed)
+            .into_iter()
+            .find_map(|embedded| {
+                let offset = embedded.offset_at(position)?;
+                Some(embedded.query[..offset].to_string())
+            });
+        if let Some(before_cursor) = query_before_cursor {
+            return self.query_completions(&before_cursor);
+        }
+
+        let line_prefix = line_prefix(source, position);
+        let Some(block) = enclosing_block(parsed.tree.root_node(), position) else {
+            return self.block_completions(line_prefix);
+        };
+        if block.kind() != ENTITY_BLOCK_KIND {
+            return Vec::new();
+        }
+        let entity = ParsedEntity::new(block, &parsed.source, &parsed.path);
+        let Some(entity_type) = entity.entity_type().map(EntityType::new) else {
+            return Vec::new();
+        };
+
+        match line_prefix.split_once('=') {
+            Some((field_name, value)) => {
+                let value = current_list_item(value);
+                if let Some(partial) = value.strip_prefix("enum\"") {
+                    self.enum_completions(&entity_type, field_name.trim(), partial)
+                } else if let Some((target_type, partial)) = value.split_once('.') {
+                    self.entity_id_completions(target_type, partial)
+                } else {
+                    Vec::new()
+                }
+            }
+            None => {
+                let partial = line_prefix.trim_start();
+                if !partial.chars().all(is_identifier_char) {
+                    return Vec::new();
+                }
+
+                // Fields on the cursor's line are still being typed
+                let present: Vec<String> = entity
+                    .fields()
+                    .iter()
+                    .filter(|field| field.line() != position.row + 1)
+                    .filter_map(|field| field.id().map(str::to_string))
+                    .collect();
+                self.field_completions(&entity_type, partial, &present)
+            }
+        }
+    }
+
+    /// Suggests schema fields that aren't set yet.
+    pub(super) fn field_completions(
+        &self,
+        entity_type: &EntityType,
+        partial: &str,
+        present: &[String],
+    ) -> Vec<Completion> {
+        let Some(schema) = self.schemas.iter().find(|s| &s.entity_type == entity_type) else {
+            return Vec::new();
+        };
+
+        schema
+            .ordered_fields()
+            .into_iter()
+            .filter(|(field_id, _)| field_id.as_str().starts_with(partial))
+            .filter(|(field_id, _)| !present.iter().any(|p| p == field_id.as_str()))
+            .map(|(field_id, field_schema)| {
+                let required = field_schema.is_required();
+                Completion {
+                    label: field_id.to_string(),
+                    kind: CompletionKind::Field { required },
+                    detail: if required {
+                        format!("{} (required)", field_schema.expected_type())
+                    } else {
+                        field_schema.expected_type().to_string()
+                    },
+                    insert_text: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Suggests the allowed values of an enum field.
+    ///
+    /// Values are matched by prefix, ignoring case. A value that matches none
+    /// of them, like one flagged as invalid, gets every allowed value instead,
+    /// so a valid one can be picked to replace it.
+    fn enum_completions(
+        &self,
+        entity_type: &EntityType,
+        field_name: &str,
+        partial: &str,
+    ) -> Vec<Completion> {
+        // A closing quote means the value is already complete
+        if partial.contains('"') {
+            return Vec::new();
+        }
+
+        let allowed_values = self
+            .schemas
+            .iter()
+            .find(|s| &s.entity_type == entity_type)
+            .and_then(|schema| schema.fields.get(&FieldId::new(field_name)))
+            .and_then(|field_schema| field_schema.allowed_values());
+
+        let Some(allowed_values) = allowed_values else {
+            return Vec::new();
+        };
+
+        let partial = partial.to_lowercase();
+        let mut values: Vec<&String> = allowed_values
+            .iter()
+            .filter(|value| value.to_lowercase().starts_with(&partial))
+            .collect();
+        if values.is_empty() {
+            values = allowed_values.iter().collect();
+        }
+
+        values
+            .into_iter()
+            .map(|value| Completion {
+                label: value.clone(),
+                kind: CompletionKind::EnumValue,
+                detail: field_name.to_string(),
+                insert_text: None,
+            })
+            .collect()
+    }
+
+    /// Suggests the IDs of entities with a given type.
+    fn entity_id_completions(&self, target_type: &str, partial: &str) -> Vec<Completion> {
+        if target_type.is_empty()
+            || !target_type.chars().all(is_identifier_char)
+            || !partial.chars().all(is_identifier_char)
+        {
+            return Vec::new();
+        }
+
+        let mut completions: Vec<Completion> = self
+            .entities
+            .iter()
+            .filter(|entity| entity.entity_type.as_str() == target_type)
+            .filter_map(|entity| {
+                let (_, id) = decompose_entity_id(entity.id.as_str());
+                id.starts_with(partial).then(|| Completion {
+                    label: id.to_string(),
+                    kind: CompletionKind::EntityId,
+                    detail: entity.id.to_string(),
+                    insert_text: None,
+                })
+            })
+            .collect();
+
+        completions.sort_by(|a, b| a.label.cmp(&b.label));
+        completions.dedup_by(|a, b| a.label == b.label);
+        completions
+    }
+
+    /// Suggests a block for each schema's entity type, and a schema block.
+    ///
+    /// After a complete type and a space, like `task `, only the rest of
+    /// that type's block is inserted.
+    fn block_completions(&self, line_prefix: &str) -> Vec<Completion> {
+        let typed = line_prefix.trim_start();
+        let word = typed.trim_end();
+        if !word.chars().all(is_identifier_char) {
+            return Vec::new();
+        }
+
+        if word.len() < typed.len() {
+            return self
+                .schemas
+                .iter()
+                .filter(|schema| schema.entity_type.as_str() == word)
+                .map(|schema| entity_block_completion(schema, false))
+                .collect();
+        }
+
+        let mut completions: Vec<Completion> = self
+            .schemas
+            .iter()
+            .filter(|schema| schema.entity_type.as_str().starts_with(word))
+            .map(|schema| entity_block_completion(schema, true))
+            .collect();
+        completions.sort_by(|a, b| a.label.cmp(&b.label));
+
+        if SCHEMA_KEYWORD.starts_with(word) {
+            completions.push(schema_block_completion());
+        }
+        completions
+    }
+}
+
+/// Builds a completion for a new entity block, with placeholders for the
+/// ID and each required field.
+fn entity_block_completion(schema: &EntitySchema, include_type: bool) -> Completion {
+    let mut fields = schema.ordered_fields();
+    fields.sort_by_key(|(_, field_schema)| !field_schema.is_required());
+
+    let detail = fields
+        .iter()
+        .map(|(field_id, field_schema)| {
+            if field_schema.is_required() {
+                format!("{} (required)", field_id)
+            } else {
+                field_id.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut snippet = String::new();
+    if include_type {
+        snippet.push_str(&format!("{} ", schema.entity_type));
+    }
+    snippet.push_str("${1:id} {\n");
+    let required = fields
+        .iter()
+        .filter(|(_, field_schema)| field_schema.is_required());
+    for (tab_stop, (field_id, field_schema)) in (2..).zip(required) {
+        snippet.push_str(&format!(
+            "{}{} = {}\n",
+            INDENT,
+            field_id,
+            value_placeholder(field_schema, tab_stop)
+        ));
+    }
+    snippet.push('}');
+
+    Completion {
+        label: schema.entity_type.to_string(),
+        kind: CompletionKind::EntityBlock,
+        detail,
+        insert_text: Some(snippet),
+    }
+}
+
+/// Builds a completion for a new schema block with one field.
+fn schema_block_completion() -> Completion {
+    let snippet = [
+        "schema ${1:name} {",
+        "    field {",
+        "        name = \"$2\"",
+        "        type = \"${3:string}\"",
+        "        required = ${4:true}",
+        "    }",
+        "}",
+    ]
+    .join("\n");
+
+    Completion {
+        label: SCHEMA_KEYWORD.to_string(),
+        kind: CompletionKind::SchemaBlock,
+        detail: "new schema".to_string(),
+        insert_text: Some(snippet),
+    }
+}
+
+/// Gets a snippet placeholder for a field's value, shaped like its type.
+fn value_placeholder(field_schema: &FieldSchema, tab_stop: usize) -> String {
+    match field_schema.expected_type() {
+        FieldType::String => format!("\"${}\"", tab_stop),
+        FieldType::Enum => {
+            let first = field_schema
+                .allowed_values()
+                .and_then(|values| values.first())
+                .map(|value| escape_snippet(value))
+                .unwrap_or_default();
+            format!("enum\"${{{}:{}}}\"", tab_stop, first)
+        }
+        FieldType::Reference => format!("${{{}:type}}.", tab_stop),
+        FieldType::Boolean => format!("${{{}:false}}", tab_stop),
+        FieldType::Integer => format!("${{{}:0}}", tab_stop),
+        FieldType::Float => format!("${{{}:0.0}}", tab_stop),
+        FieldType::Currency => format!("${{{}:0.00 USD}}", tab_stop),
+        FieldType::DateTime => format!("${{{}:2025-01-01}}", tab_stop),
+        FieldType::Path => format!("path\"${}\"", tab_stop),
+        FieldType::List => format!("[${}]", tab_stop),
+    }
+}
+
+/// Escapes the characters that have a meaning in snippets.
+fn escape_snippet(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}")
+}
+
+/// Finds the entity or schema block around a position, if any.
+fn enclosing_block(root: Node<'_>, position: Point) -> Option<Node<'_>> {
+    let mut current = root.descendant_for_point_range(position, position);
+    while let Some(node) = current {
+        if node.kind() == ENTITY_BLOCK_KIND || node.kind() == SCHEMA_BLOCK_KIND {
+            return Some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// Gets the text on the position's line, up to the position.
+fn line_prefix(source: &str, position: Point) -> &str {
+    let line = source.split('\n').nth(position.row).unwrap_or("");
+    let mut end = position.column.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+/// Gets the value being typed, which is the last item when inside a list.
+fn current_list_item(value: &str) -> &str {
+    let start = value.rfind(['[', ',']).map_or(0, |index| index + 1);
+    value[start..].trim_start()
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_prefix() {
+        let source = "person john {\n    na\n}";
+        assert_eq!(line_prefix(source, Point { row: 1, column: 6 }), "    na");
+        assert_eq!(line_prefix(source, Point { row: 1, column: 99 }), "    na");
+        assert_eq!(line_prefix(source, Point { row: 9, column: 0 }), "");
+    }
+
+    #[test]
+    fn test_value_placeholder() {
+        use firm_core::schema::FieldMode;
+
+        let string = FieldSchema::new(FieldType::String, FieldMode::Required, 0);
+        assert_eq!(value_placeholder(&string, 2), "\"$2\"");
+
+        let reference = FieldSchema::new(FieldType::Reference, FieldMode::Required, 0);
+        assert_eq!(value_placeholder(&reference, 3), "${3:type}.");
+
+        let values = vec!["$1 {x}".to_string(), "other".to_string()];
+        let enumeration = FieldSchema::new_enum(FieldMode::Required, 0, values);
+        assert_eq!(
+            value_placeholder(&enumeration, 4),
+            "enum\"${4:\\$1 {x\\}}\""
+        );
+    }
+
+    #[test]
+    fn test_current_list_item() {
+        assert_eq!(current_list_item(" person.jo"), "person.jo");
+        assert_eq!(current_list_item(" [person.john, person.ja"), "person.ja");
+        assert_eq!(current_list_item(" [enum\"a"), "enum\"a");
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use chrono::FixedOffset;
+use firm_core::{CurrencyFormat, ValueFormat};
+use serde::Deserialize;
+
+use super::WorkspaceError;
+use crate::parser::dsl::ParsedValue;
+
+/// The name of the workspace config file, in the workspace root.
+pub const CONFIG_FILE_NAME: &str = "firm.json";
+
+/// The default file new entities are added to: one file per entity type.
+pub const DEFAULT_ADD_TARGET: &str = "generated/{type}.firm";
+
+/// The default field shown in hints for references.
+pub const DEFAULT_HINT_FIELD: &str = "name";
+
+const FIRM_FILE_EXTENSION: &str = "firm";
+const TYPE_PLACEHOLDER: &str = "{type}";
+const ID_PLACEHOLDER: &str = "{id}";
+
+/// Settings for a workspace, read from its `firm.json` file.
+///
+/// Every setting is optional, and a workspace without a config file uses the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    pub add: AddConfig,
+    pub hints: HintConfig,
+    pub timezone: TimezoneConfig,
+    pub display: DisplayConfig,
+}
+
+/// Settings for adding entities.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AddConfig {
+    /// Where new entities go when no file is given, relative to the workspace root.
+    ///
+    /// `{type}` and `{id}` are replaced by the entity's type and ID, so the
+    /// target can be a file per type (`generated/{type}.firm`), a single file
+    /// (`entities.firm`) or a file per entity (`{type}/{id}.firm`).
+    pub target: String,
+}
+
+impl Default for AddConfig {
+    fn default() -> Self {
+        Self {
+            target: DEFAULT_ADD_TARGET.to_string(),
+        }
+    }
+}
+
+/// Settings for the hints shown after references in an editor.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HintConfig {
+    /// The field to show for referenced entities, by entity type.
+    ///
+    /// Types that aren't listed show their `name` field.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl HintConfig {
+    /// Gets the field to show for a referenced entity of a type.
+    pub fn field_for(&self, entity_type: &str) -> &str {
+        self.fields
+            .get(entity_type)
+            .map_or(DEFAULT_HINT_FIELD, String::as_str)
+    }
+}
+
+/// Settings for how dates and datetimes are read and shown.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimezoneConfig {
+    /// The timezone for dates and datetimes written without one, like `UTC+2`.
+    ///
+    /// It's also the timezone date-only query conditions like `due == 2025-09-30`
+    /// are checked in. Without it, the machine's local timezone is used when
+    /// reading values, and each value's own timezone when querying.
+    pub default: Option<String>,
+    /// Show datetimes in the default timezone, rather than the one they were written in.
+    pub render: bool,
+}
+
+impl TimezoneConfig {
+    /// Gets the default timezone, if one is set.
+    ///
+    /// Loaded configs have a valid timezone, so an invalid one is treated as unset.
+    pub fn offset(&self) -> Option<FixedOffset> {
+        ParsedValue::parse_timezone(self.default.as_deref()?).ok()
+    }
+
+    /// Gets the timezone to show datetimes in, if rendering in the default timezone is on.
+    pub fn render_offset(&self) -> Option<FixedOffset> {
+        if self.render { self.offset() } else { None }
+    }
+
+    /// Checks that the default timezone is written like `UTC+2`.
+    fn validate(&self) -> Result<(), String> {
+        match &self.default {
+            Some(timezone) if ParsedValue::parse_timezone(timezone).is_err() => Err(format!(
+                "timezone.default '{}' must be UTC or an offset in hours, like UTC+2 or UTC-5",
+                timezone
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Settings for how values are shown in readable output.
+///
+/// JSON output and generated DSL always use the raw values.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Show currency amounts with their symbol and the currency's minor-unit
+    /// decimals, like `$100.50`, rather than as written with their code.
+    pub currency_symbols: bool,
+    /// A strftime-style format for datetimes, like `%Y-%m-%d %H:%M`.
+    pub date_format: Option<String>,
+}
+
+impl DisplayConfig {
+    /// Gets the format to show values in.
+    pub fn value_format(&self) -> ValueFormat {
+        ValueFormat {
+            currency: if self.currency_symbols {
+                CurrencyFormat::Symbol
+            } else {
+                CurrencyFormat::Code
+            },
+            date_format: self.date_format.clone(),
+        }
+    }
+
+    /// Checks that the date format only uses known specifiers.
+    fn validate(&self) -> Result<(), String> {
+        match &self.date_format {
+            Some(format) if !ValueFormat::is_valid_date_format(format) => Err(format!(
+                "display.date_format '{}' has an unknown specifier: use strftime ones like %Y-%m-%d",
+                format
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl WorkspaceConfig {
+    /// Loads the config of a workspace, or the defaults if it has no config file.
+    pub fn load(root_path: &Path) -> Result<Self, WorkspaceError> {
+        let path = root_path.join(CONFIG_FILE_NAME);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(WorkspaceError::IoError(err)),
+        };
+
+        let config: Self = serde_json::from_str(&contents)
+            .map_err(|err| WorkspaceError::ParseError(path.clone(), err.to_string()))?;
+        config
+            .add
+            .validate()
+            .and_then(|_| config.timezone.validate())
+            .and_then(|_| config.display.validate())
+            .map_err(|message| WorkspaceError::ValidationError(path, message))?;
+
+        Ok(config)
+    }
+}
+
+impl AddConfig {
+    /// Gets the file to add an entity to, relative to the workspace root.
+    pub fn target_path(&self, entity_type: &str, entity_id: &str) -> PathBuf {
+        let target = self
+            .target
+            .replace(TYPE_PLACEHOLDER, entity_type)
+            .replace(ID_PLACEHOLDER, entity_id);
+
+        PathBuf::from(target).with_extension(FIRM_FILE_EXTENSION)
+    }
+
+    /// Checks that the target is a path inside the workspace with known placeholders.
+    fn validate(&self) -> Result<(), String> {
+        let literal = self
+            .target
+            .replace(TYPE_PLACEHOLDER, "")
+            .replace(ID_PLACEHOLDER, "");
+        if literal.contains(['{', '}']) {
+            return Err(format!(
+                "add.target '{}' has an unknown placeholder: only {} and {} can be used",
+                self.target, TYPE_PLACEHOLDER, ID_PLACEHOLDER
+            ));
+        }
+
+        let path = Path::new(&self.target);
+        let is_relative = path.file_name().is_some()
+            && path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !is_relative {
+            return Err(format!(
+                "add.target '{}' must be a file path inside the workspace",
+                self.target
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn add_config(target: &str) -> AddConfig {
+        AddConfig {
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_target_path_strategies() {
+        assert_eq!(
+            AddConfig::default().target_path("person", "jane_doe"),
+            PathBuf::from("generated/person.firm")
+        );
+        assert_eq!(
+            add_config("entities.firm").target_path("person", "jane_doe"),
+            PathBuf::from("entities.firm")
+        );
+        assert_eq!(
+            add_config("{type}/{id}").target_path("person", "jane_doe"),
+            PathBuf::from("person/jane_doe.firm")
+        );
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        assert!(add_config("{type}/{id}.firm").validate().is_ok());
+        assert!(add_config("{name}.firm").validate().is_err());
+        assert!(add_config("../outside/{type}.firm").validate().is_err());
+        assert!(add_config("/etc/{type}.firm").validate().is_err());
+        assert!(add_config("").validate().is_err());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        assert_eq!(
+            WorkspaceConfig::load(root).unwrap(),
+            WorkspaceConfig::default()
+        );
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "add": { "target": "{type}/{id}.firm" } }"#,
+        )
+        .unwrap();
+        let config = WorkspaceConfig::load(root).unwrap();
+        assert_eq!(config.add.target, "{type}/{id}.firm");
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "hints": { "fields": { "task": "title" } } }"#,
+        )
+        .unwrap();
+        let config = WorkspaceConfig::load(root).unwrap();
+        assert_eq!(config.add, AddConfig::default());
+        assert_eq!(config.hints.field_for("task"), "title");
+        assert_eq!(config.hints.field_for("person"), DEFAULT_HINT_FIELD);
+        assert_eq!(config.timezone.offset(), None);
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "timezone": { "default": "UTC-5", "render": true } }"#,
+        )
+        .unwrap();
+        let config = WorkspaceConfig::load(root).unwrap();
+        assert_eq!(config.timezone.offset(), FixedOffset::west_opt(5 * 3600));
+        assert!(config.timezone.render);
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "timezone": { "default": "Europe/Oslo" } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            WorkspaceConfig::load(root),
+            Err(WorkspaceError::ValidationError(..))
+        ));
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "display": { "currency_symbols": true, "date_format": "%d.%m.%Y" } }"#,
+        )
+        .unwrap();
+        let format = WorkspaceConfig::load(root).unwrap().display.value_format();
+        assert_eq!(format.currency, CurrencyFormat::Symbol);
+        assert_eq!(format.date_format.as_deref(), Some("%d.%m.%Y"));
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "display": { "date_format": "%Q" } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            WorkspaceConfig::load(root),
+            Err(WorkspaceError::ValidationError(..))
+        ));
+
+        fs::write(root.join(CONFIG_FILE_NAME), r#"{ "add": { "file": "a" } }"#).unwrap();
+        assert!(matches!(
+            WorkspaceConfig::load(root),
+            Err(WorkspaceError::ParseError(..))
+        ));
+
+        fs::write(
+            root.join(CONFIG_FILE_NAME),
+            r#"{ "add": { "target": "../a" } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            WorkspaceConfig::load(root),
+            Err(WorkspaceError::ValidationError(..))
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use tree_sitter::{Node, Point, Range};
+
+use super::Workspace;
+use crate::parser::dsl::{ParsedEntity, ParsedValue, parse_source};
+
+const REFERENCE_KIND: &str = "reference";
+const ENTITY_TYPE_KIND: &str = "entity_type";
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+
+/// Where something is defined in the workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub path: PathBuf,
+    /// The range of the defining identifier, like an entity ID or schema name.
+    pub range: Range,
+}
+
+impl Definition {
+    /// The 1-based line of the defining identifier.
+    pub fn line(&self) -> usize {
+        self.range.start_point.row + 1
+    }
+}
+
+impl Workspace {
+    /// Finds the definition of the reference or entity type at a position in a source document.
+    ///
+    /// The source is parsed as given, so it can be an unsaved editor buffer.
+    /// References resolve to the ID of the referenced entity, or to the field
+    /// name for field references. Entity types resolve to their schema name.
+    /// Returns None when there's nothing to resolve at the position, or when
+    /// the target doesn't exist. The position is zero-based, with the column in bytes.
+    pub fn find_definition(&self, source: &str, position: Point) -> Option<Definition> {
+        let parsed = parse_source(source.to_string(), None).ok()?;
+        let root = parsed.tree.root_node();
+        let node = root.descendant_for_point_range(position, position)?;
+
+        if let Some(reference) = find_ancestor(node, REFERENCE_KIND) {
+            let text = &parsed.source[reference.byte_range()];
+            return match ParsedValue::parse_reference(text).ok()? {
+                ParsedValue::EntityReference {
+                    entity_type,
+                    entity_id,
+                } => self.find_entity_definition(&entity_type, &entity_id, None),
+                ParsedValue::FieldReference {
+                    entity_type,
+                    entity_id,
+                    field_id,
+                } => self.find_entity_definition(&entity_type, &entity_id, Some(&field_id)),
+                _ => None,
+            };
+        }
+
+        let type_node = find_ancestor(node, ENTITY_TYPE_KIND)?;
+        if type_node.parent()?.kind() != ENTITY_BLOCK_KIND {
+            return None;
+        }
+        self.find_schema_definition(&parsed.source[type_node.byte_range()])
+    }
+
+    /// Finds the ID of an entity block, or one of its fields.
+    ///
+    /// Field references fall back to the entity when the field isn't set in any block.
+    pub fn find_entity_definition(
+        &self,
+        entity_type: &str,
+        entity_id: &str,
+        field_id: Option<&str>,
+    ) -> Option<Definition> {
+        let mut entity_definition = None;
+
+        for (path, file) in &self.files {
+            for entity in file.parsed.entities() {
+                if entity.entity_type() != Some(entity_type) || entity.id() != Some(entity_id) {
+                    continue;
+                }
+
+                let Some(field_id) = field_id else {
+                    return definition(path, entity.id_range());
+                };
+
+                // Fields can be set in any block of the entity, so keep looking
+                if let Some(range) = find_field_range(&entity, field_id) {
+                    return definition(path, Some(range));
+                }
+                if entity_definition.is_none() {
+                    entity_definition = definition(path, entity.id_range());
+                }
+            }
+        }
+
+        entity_definition
+    }
+
+    /// Finds the name of a schema block.
+    pub fn find_schema_definition(&self, schema_name: &str) -> Option<Definition> {
+        for (path, file) in &self.files {
+            for schema in file.parsed.schemas() {
+                if schema.name() == Some(schema_name) {
+                    return definition(path, schema.name_range());
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Builds a definition from a block's identifier range, if it has one.
+fn definition(path: &Path, range: Option<Range>) -> Option<Definition> {
+    range.map(|range| Definition {
+        path: path.to_path_buf(),
+        range,
+    })
+}
+
+/// Finds the node or its nearest ancestor of a kind.
+pub(super) fn find_ancestor<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if node.kind() == kind {
+            return Some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// Checks whether a position is inside a range, including its end.
+pub(super) fn contains(range: &Range, position: Point) -> bool {
+    range.start_point <= position && position <= range.end_point
+}
+
+/// Finds the name of a field assignment in an entity block.
+fn find_field_range(entity: &ParsedEntity<'_>, field_id: &str) -> Option<Range> {
+    entity
+        .fields()
+        .iter()
+        .find(|field| field.id() == Some(field_id))
+        .and_then(|field| field.id_range())
+}
@@ -0,0 +1,298 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use tree_sitter::{Point, Range};
+
+use super::positions::{empty_range, line_start_offset, position_at};
+use super::{GenerationToken, Workspace, WorkspaceBuild, WorkspaceError};
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a workspace file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    /// A stable identifier for the kind of problem (e.g. "broken-reference").
+    pub code: &'static str,
+    /// The 1-based line, or None when the problem isn't tied to a line.
+    pub line: Option<usize>,
+    /// The exact span of the problem, when it's known (like for syntax errors).
+    pub range: Option<Range>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            DiagnosticSeverity::Error => "error",
+            DiagnosticSeverity::Warning => "warning",
+        };
+        write!(f, "{}[{}]: {}", severity, self.code, self.message)
+    }
+}
+
+impl Diagnostic {
+    /// Gets the span to show the diagnostic at, in the source of its file.
+    ///
+    /// Uses the exact range when it's known. Otherwise the diagnostic spans its
+    /// line without the indentation, or the start of the file when it has no
+    /// line, so every diagnostic can be shown inline. Columns are in bytes.
+    pub fn span(&self, source: &str) -> Range {
+        if let Some(range) = self.range {
+            return range;
+        }
+        let Some(row) = self.line.map(|line| line.saturating_sub(1)) else {
+            return empty_range(0, Point::new(0, 0));
+        };
+
+        let Some(line_start) = line_start_offset(source, row) else {
+            // The line is past the end, like after the file was shortened
+            return empty_range(source.len(), position_at(source, source.len()));
+        };
+        let line = source[line_start..].lines().next().unwrap_or("");
+        let start_column = line.len() - line.trim_start().len();
+        let end_column = line.trim_end().len().max(start_column);
+
+        Range {
+            start_byte: line_start + start_column,
+            end_byte: line_start + end_column,
+            start_point: Point::new(row, start_column),
+            end_point: Point::new(row, end_column),
+        }
+    }
+}
+
+/// Diagnostics for each file in a workspace, keyed by the file's path.
+pub type WorkspaceDiagnostics = BTreeMap<PathBuf, Vec<Diagnostic>>;
+
+impl Workspace {
+    /// Collects diagnostics for every file in the workspace.
+    ///
+    /// Covers syntax errors, the error that stopped the build, build warnings
+    /// and broken references. Every loaded file has an entry, empty when it's
+    /// clean, so callers can clear files that no longer have problems.
+    /// All syntax errors are reported, and the blocks without them are still
+    /// built and checked. Broken references are only checked when the build succeeds.
+    pub fn collect_diagnostics(&mut self) -> WorkspaceDiagnostics {
+        self.diagnose().0
+    }
+
+    /// Collects diagnostics for the change a token was taken for.
+    ///
+    /// Stops between the costly steps once the token is stale, and returns
+    /// None then, since the diagnostics would already be out of date.
+    pub fn collect_diagnostics_for(
+        &mut self,
+        token: &GenerationToken,
+    ) -> Option<WorkspaceDiagnostics> {
+        let diagnostics = self.diagnose_until(&|| !token.is_current())?.0;
+        token.is_current().then_some(diagnostics)
+    }
+
+    /// Collects diagnostics, keeping the build when there is one.
+    pub(super) fn diagnose(&mut self) -> (WorkspaceDiagnostics, Option<WorkspaceBuild>) {
+        self.diagnose_until(&|| false)
+            .expect("diagnostics are only cancelled on request")
+    }
+
+    /// Collects diagnostics, stopping with None when cancelled between steps.
+    fn diagnose_until(
+        &mut self,
+        is_cancelled: &dyn Fn() -> bool,
+    ) -> Option<(WorkspaceDiagnostics, Option<WorkspaceBuild>)> {
+        let mut diagnostics: WorkspaceDiagnostics = BTreeMap::new();
+
+        for (path, file) in &self.files {
+            let file_diagnostics = file
+                .parsed
+                .syntax_errors()
+                .into_iter()
+                .map(|error| Diagnostic {
+                    severity: DiagnosticSeverity::Error,
+                    code: "syntax-error",
+                    line: Some(error.range.start_point.row + 1),
+                    range: Some(error.range),
+                    message: error.message,
+                })
+                .collect();
+            diagnostics.insert(path.clone(), file_diagnostics);
+        }
+
+        if is_cancelled() {
+            return None;
+        }
+
+        // Blocks with syntax errors are left out, so the rest is still checked
+        let build = match self.build_skipping_syntax_errors() {
+            Ok(build) => build,
+            Err(error) => {
+                if let Some(path) = error.path() {
+                    diagnostics
+                        .entry(path.clone())
+                        .or_default()
+                        .push(Diagnostic {
+                            severity: DiagnosticSeverity::Error,
+                            code: build_error_code(&error),
+                            line: error.line(),
+                            range: None,
+                            message: error.to_string(),
+                        });
+                }
+                return Some((diagnostics, None));
+            }
+        };
+        if is_cancelled() {
+            return None;
+        }
+
+        for warning in &build.warnings {
+            diagnostics
+                .entry(warning.path().clone())
+                .or_default()
+                .push(Diagnostic {
+                    severity: DiagnosticSeverity::Warning,
+                    code: warning.code(),
+                    line: Some(warning.line()),
+                    range: None,
+                    message: warning.message(),
+                });
+        }
+
+        for broken in self.check_references(&build) {
+            diagnostics
+                .entry(broken.path.clone())
+                .or_default()
+                .push(Diagnostic {
+                    severity: DiagnosticSeverity::Error,
+                    code: "broken-reference",
+                    line: Some(broken.line),
+                    range: None,
+                    message: broken.message(),
+                });
+        }
+
+        Some((diagnostics, Some(build)))
+    }
+}
+
+/// Finds the files whose diagnostics differ between two collections.
+///
+/// Includes files that gained, lost or changed diagnostics, and files that
+/// are gone from the current collection, like deleted files. Publishing the
+/// current diagnostics (or none) for just these files keeps an editor in sync.
+pub fn changed_diagnostic_files(
+    previous: &WorkspaceDiagnostics,
+    current: &WorkspaceDiagnostics,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = current
+        .iter()
+        .filter(|(path, diagnostics)| {
+            previous
+                .get(*path)
+                .map_or(!diagnostics.is_empty(), |previous| previous != *diagnostics)
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    changed.extend(
+        previous
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned(),
+    );
+    changed.sort();
+    changed
+}
+
+/// A stable identifier for the kind of build error.
+fn build_error_code(error: &WorkspaceError) -> &'static str {
+    match error {
+        WorkspaceError::IoError(_) => "io-error",
+        WorkspaceError::ParseError(_, _) => "parse-error",
+        WorkspaceError::ValidationError(_, _)
+        | WorkspaceError::InvalidListItem(_, _, _)
+        | WorkspaceError::ScriptRejected(_, _, _) => "validation-error",
+        WorkspaceError::MissingSchemaError(_, _) => "missing-schema",
+        WorkspaceError::Locked(_, _) => "workspace-locked",
+        WorkspaceError::Conflict(_, _) => "conflict",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(message: &str) -> Diagnostic {
+        Diagnostic {
+            severity: DiagnosticSeverity::Error,
+            code: "broken-reference",
+            line: Some(1),
+            range: None,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_span_covers_the_line_without_indentation() {
+        let source = "person jane {\n    manager = person.nobody  \n}\n";
+        let mut diagnostic = diagnostic("broken");
+
+        diagnostic.line = Some(2);
+        let span = diagnostic.span(source);
+        assert_eq!(
+            &source[span.start_byte..span.end_byte],
+            "manager = person.nobody"
+        );
+        assert_eq!(span.start_point, Point::new(1, 4));
+        assert_eq!(span.end_point, Point::new(1, 27));
+
+        diagnostic.line = None;
+        assert_eq!(diagnostic.span(source).end_point, Point::new(0, 0));
+
+        // Lines past the end point at the end of the source
+        diagnostic.line = Some(9);
+        let span = diagnostic.span(source);
+        assert_eq!(span.start_byte, source.len());
+        assert_eq!(span.start_point, Point::new(3, 0));
+    }
+
+    #[test]
+    fn test_changed_files_include_gained_lost_and_deleted() {
+        let previous = WorkspaceDiagnostics::from([
+            (PathBuf::from("clean.firm"), vec![]),
+            (PathBuf::from("fixed.firm"), vec![diagnostic("old")]),
+            (PathBuf::from("same.firm"), vec![diagnostic("same")]),
+            (PathBuf::from("deleted.firm"), vec![diagnostic("gone")]),
+        ]);
+        let current = WorkspaceDiagnostics::from([
+            (PathBuf::from("clean.firm"), vec![diagnostic("new")]),
+            (PathBuf::from("fixed.firm"), vec![]),
+            (PathBuf::from("same.firm"), vec![diagnostic("same")]),
+            (PathBuf::from("added.firm"), vec![]),
+        ]);
+
+        assert_eq!(
+            changed_diagnostic_files(&previous, &current),
+            vec![
+                PathBuf::from("clean.firm"),
+                PathBuf::from("deleted.firm"),
+                PathBuf::from("fixed.firm"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_files_for_new_file_with_diagnostics() {
+        let current =
+            WorkspaceDiagnostics::from([(PathBuf::from("new.firm"), vec![diagnostic("new")])]);
+
+        assert_eq!(
+            changed_diagnostic_files(&WorkspaceDiagnostics::new(), &current),
+            vec![PathBuf::from("new.firm")]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+
+use tree_sitter::{Node, Range};
+
+use super::PathCheckOptions;
+use super::path_check::resolve;
+use super::positions::sub_range;
+use crate::parser::dsl::{ParsedSource, ParsedValue};
+
+const PATH_KIND: &str = "path";
+const STRING_KIND: &str = "string";
+const URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+/// Where a document link goes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentLinkTarget {
+    /// A file, as an absolute path.
+    File(PathBuf),
+    /// A web address found in a string.
+    Url(String),
+}
+
+/// A range of a document that links somewhere, like a path value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentLink {
+    pub range: Range,
+    pub target: DocumentLinkTarget,
+}
+
+/// Finds the links in a parsed document.
+///
+/// Path values link to their file, resolved relative to the document like
+/// they are when parsing, with Windows separators accepted. Paths outside the
+/// workspace root are skipped unless `allow_external_paths` is set. URLs
+/// starting with `http://` or `https://` inside strings link to themselves.
+/// Links are in document order.
+pub fn document_links(
+    parsed: &ParsedSource,
+    root_path: &Path,
+    options: &PathCheckOptions,
+) -> Vec<DocumentLink> {
+    let mut links = Vec::new();
+    collect_links(
+        parsed.tree.root_node(),
+        parsed,
+        root_path,
+        options,
+        &mut links,
+    );
+    links
+}
+
+fn collect_links(
+    node: Node<'_>,
+    parsed: &ParsedSource,
+    root_path: &Path,
+    options: &PathCheckOptions,
+    links: &mut Vec<DocumentLink>,
+) {
+    let text = &parsed.source[node.byte_range()];
+    match node.kind() {
+        PATH_KIND => {
+            if let Some(target) = path_target(text, &parsed.path, root_path, options) {
+                links.push(DocumentLink {
+                    range: node.range(),
+                    target: DocumentLinkTarget::File(target),
+                });
+            }
+        }
+        STRING_KIND => {
+            for (start, end) in find_urls(text) {
+                links.push(DocumentLink {
+                    range: sub_range(&node.range(), text, start, end),
+                    target: DocumentLinkTarget::Url(text[start..end].to_string()),
+                });
+            }
+        }
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_links(child, parsed, root_path, options, links);
+            }
+        }
+    }
+}
+
+/// Resolves a path literal to an absolute path, if it's allowed.
+fn path_target(
+    raw: &str,
+    source_path: &Path,
+    root_path: &Path,
+    options: &PathCheckOptions,
+) -> Option<PathBuf> {
+    // Backslashes are only separators on Windows, but files are shared across platforms
+    let raw = raw.replace('\\', "/");
+    let ParsedValue::Path(target) = ParsedValue::parse_path(&raw, source_path).ok()? else {
+        return None;
+    };
+
+    let resolved_path = resolve(root_path, &target);
+    (resolved_path.starts_with(root_path) || options.allow_external_paths).then_some(resolved_path)
+}
+
+/// Finds the byte ranges of the URLs in a string.
+///
+/// A URL ends at whitespace, a quote or an angle bracket.
+fn find_urls(text: &str) -> Vec<(usize, usize)> {
+    let mut urls = Vec::new();
+    let mut offset = 0;
+
+    while let Some((start, scheme)) = URL_SCHEMES
+        .iter()
+        .filter_map(|scheme| Some((text[offset..].find(scheme)? + offset, scheme)))
+        .min()
+    {
+        let end = text[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '<' | '>'))
+            .map_or(text.len(), |length| start + length);
+
+        if end > start + scheme.len() {
+            urls.push((start, end));
+        }
+        offset = end;
+    }
+
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_target_resolves_relative_to_source() {
+        let root = Path::new("/workspace");
+        let source = Path::new("./people/team.firm");
+        let options = PathCheckOptions::default();
+
+        assert_eq!(
+            path_target("path\"../contracts/msa.pdf\"", source, root, &options),
+            Some(PathBuf::from("/workspace/contracts/msa.pdf"))
+        );
+        assert_eq!(
+            path_target("path\"..\\contracts\\msa.pdf\"", source, root, &options),
+            Some(PathBuf::from("/workspace/contracts/msa.pdf"))
+        );
+        assert_eq!(
+            path_target("path\"../../secrets.txt\"", source, root, &options),
+            None
+        );
+
+        let options = PathCheckOptions {
+            allow_external_paths: true,
+        };
+        assert_eq!(
+            path_target("path\"../../secrets.txt\"", source, root, &options),
+            Some(PathBuf::from("/secrets.txt"))
+        );
+    }
+
+    #[test]
+    fn test_find_urls() {
+        let text = "\"See https://example.com/a?b=1 and http://x.io\"";
+        let urls: Vec<&str> = find_urls(text)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect();
+        assert_eq!(urls, vec!["https://example.com/a?b=1", "http://x.io"]);
+
+        assert!(find_urls("\"https:// is not a link\"").is_empty());
+    }
+}
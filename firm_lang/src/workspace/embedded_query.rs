@@ -0,0 +1,440 @@
+use firm_core::{EntityType, FieldId};
+use tree_sitter::{Point, Range};
+
+use super::WorkspaceBuild;
+use super::completion::{Completion, CompletionKind};
+use super::positions::{offset_at, point_after};
+use super::query_fields::{QUERY_FIELD_FORMAT, check_query_string};
+use crate::parser::dsl::{ParsedSource, ParsedValue};
+use crate::parser::query::syntax_error_offset;
+
+const MULTILINE_QUOTES: &str = "\"\"\"";
+
+/// Keywords that can start a query.
+const QUERY_START_KEYWORDS: [&str; 1] = ["from"];
+/// Keywords that can follow a `|` in a query.
+const OPERATION_KEYWORDS: [&str; 10] = [
+    "where", "related", "order", "limit", "group", "select", "count", "sum", "average", "median",
+];
+/// Keywords followed by a single field.
+const FIELD_KEYWORDS: [&str; 7] = [
+    "order", "group", "select", "count", "sum", "average", "median",
+];
+const CONDITION_KEYWORDS: [&str; 3] = ["where", "and", "or"];
+const OPERATORS: [&str; 10] = [
+    "==",
+    "!=",
+    ">=",
+    "<=",
+    ">",
+    "<",
+    "contains",
+    "startswith",
+    "endswith",
+    "in",
+];
+const DIRECTIONS: [&str; 2] = ["asc", "desc"];
+const METADATA_FIELDS: [&str; 5] = ["@id", "@type", "@created", "@modified", "@tags"];
+
+/// A query written in a string field whose schema marks it as holding a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedQuery {
+    pub entity_type: EntityType,
+    pub field: FieldId,
+    /// The query, with escapes decoded.
+    pub query: String,
+    /// The range of the query text inside the quotes.
+    pub range: Range,
+    /// Whether the query is written exactly as it's read, so offsets map one-to-one.
+    verbatim: bool,
+}
+
+/// A problem with an embedded query, at a position in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDiagnostic {
+    /// Where the problem is, or the start of the query when that's not known.
+    pub position: Point,
+    pub message: String,
+}
+
+impl EmbeddedQuery {
+    /// Turns a byte offset in the query into a position in the document.
+    ///
+    /// Queries with escapes or trimmed indentation don't map one-to-one, so
+    /// their offsets map to the start of the query.
+    pub fn position_at(&self, offset: usize) -> Point {
+        if !self.verbatim || offset > self.query.len() {
+            return self.range.start_point;
+        }
+
+        point_after(self.range.start_point, &self.query[..offset])
+    }
+
+    /// Turns a position in the document into a byte offset in the query.
+    ///
+    /// Returns None outside the query, or when offsets don't map one-to-one.
+    pub fn offset_at(&self, position: Point) -> Option<usize> {
+        if !self.verbatim || position < self.range.start_point || position > self.range.end_point {
+            return None;
+        }
+
+        // Columns on the first line are counted from the start of the query
+        let row = position.row - self.range.start_point.row;
+        let column = if row == 0 {
+            position.column - self.range.start_point.column
+        } else {
+            position.column
+        };
+        offset_at(&self.query, Point { row, column })
+    }
+}
+
+impl WorkspaceBuild {
+    /// Finds the queries held in the string fields of a parsed document.
+    ///
+    /// A field holds a query when its schema gives it the `firm-query` format.
+    pub fn embedded_queries(&self, parsed: &ParsedSource) -> Vec<EmbeddedQuery> {
+        let mut queries = Vec::new();
+
+        for entity in parsed.entities() {
+            let Some(entity_type) = entity.entity_type().map(EntityType::new) else {
+                continue;
+            };
+            let Some(schema) = self.schemas.iter().find(|s| s.entity_type == entity_type) else {
+                continue;
+            };
+
+            for field in entity.fields() {
+                let Some(field_id) = field.id().map(FieldId::new) else {
+                    continue;
+                };
+                let holds_query = schema
+                    .fields
+                    .get(&field_id)
+                    .is_some_and(|field_schema| field_schema.format() == Some(QUERY_FIELD_FORMAT));
+                if !holds_query {
+                    continue;
+                }
+
+                let (Ok(ParsedValue::String(query)), Some(value_range)) =
+                    (field.value(), field.value_range())
+                else {
+                    continue;
+                };
+                let raw = &parsed.source[value_range.start_byte..value_range.end_byte];
+                let Some(range) = inner_range(raw, value_range) else {
+                    continue;
+                };
+
+                queries.push(EmbeddedQuery {
+                    entity_type: entity_type.clone(),
+                    field: field_id,
+                    verbatim: parsed.source[range.start_byte..range.end_byte] == query,
+                    query,
+                    range,
+                });
+            }
+        }
+
+        queries
+    }
+
+    /// Checks the queries held in the string fields of a parsed document.
+    ///
+    /// Syntax errors point at where the query stops parsing. Other problems,
+    /// like unknown fields, point at the start of the query.
+    pub fn query_diagnostics(&self, parsed: &ParsedSource) -> Vec<QueryDiagnostic> {
+        self.embedded_queries(parsed)
+            .into_iter()
+            .filter_map(|embedded| {
+                let message = check_query_string(&embedded.query, &self.schemas).err()?;
+                let offset = syntax_error_offset(&embedded.query).unwrap_or(0);
+                Some(QueryDiagnostic {
+                    position: embedded.position_at(offset),
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    /// Suggests completions inside an embedded query, given the query text before the cursor.
+    ///
+    /// Covers keywords, entity types after `from` and `related` (also in a
+    /// `[...]` list of types), field names of the queried type after `where`,
+    /// `order`, `select` and the like, and operators after a field in a condition.
+    pub(super) fn query_completions(&self, before_cursor: &str) -> Vec<Completion> {
+        let (earlier, segment) = match before_cursor.rsplit_once('|') {
+            Some((earlier, segment)) => (Some(earlier), segment),
+            None => (None, before_cursor),
+        };
+
+        // The word being typed, and the words before it in this part of the query
+        let partial_start = segment
+            .rfind(|c: char| c.is_whitespace() || c == ',')
+            .map_or(0, |index| index + 1);
+        let partial = &segment[partial_start..];
+        let words: Vec<&str> = segment[..partial_start]
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        let entity_type = queried_type(earlier.unwrap_or(segment));
+        let keyword = words
+            .first()
+            .map(|word| word.split('(').next().unwrap_or(word));
+
+        match (earlier, keyword) {
+            (None, None) => keyword_completions(&QUERY_START_KEYWORDS, partial),
+            (None, Some("from")) if words.len() == 1 => self.entity_type_completions(partial),
+            (Some(_), None) => keyword_completions(&OPERATION_KEYWORDS, partial),
+            (Some(_), Some("related")) if words.len() == 1 || in_type_list(&words[1..]) => {
+                self.entity_type_completions(partial.trim_start_matches('['))
+            }
+            (Some(_), Some("where")) => {
+                let previous = words.last().copied().unwrap_or_default();
+                if CONDITION_KEYWORDS.contains(&previous) {
+                    self.query_field_completions(entity_type, partial)
+                } else if words.len() >= 2 && CONDITION_KEYWORDS.contains(&words[words.len() - 2]) {
+                    operator_completions(partial)
+                } else {
+                    Vec::new()
+                }
+            }
+            (Some(_), Some("order")) if words.len() == 2 => {
+                keyword_completions(&DIRECTIONS, partial)
+            }
+            (Some(_), Some("select")) => self.query_field_completions(entity_type, partial),
+            (Some(_), Some(keyword)) if FIELD_KEYWORDS.contains(&keyword) && words.len() == 1 => {
+                self.query_field_completions(entity_type, partial)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Suggests the entity types with a schema, and the `*` wildcard.
+    fn entity_type_completions(&self, partial: &str) -> Vec<Completion> {
+        let mut completions: Vec<Completion> = self
+            .schemas
+            .iter()
+            .map(|schema| schema.entity_type.to_string())
+            .chain(std::iter::once("*".to_string()))
+            .filter(|entity_type| entity_type.starts_with(partial))
+            .map(|entity_type| Completion {
+                detail: "entity type".to_string(),
+                label: entity_type,
+                kind: CompletionKind::EntityType,
+                insert_text: None,
+            })
+            .collect();
+
+        completions.sort_by(|a, b| a.label.cmp(&b.label));
+        completions
+    }
+
+    /// Suggests the fields of the queried type's schema, or metadata fields after `@`.
+    fn query_field_completions(
+        &self,
+        entity_type: Option<EntityType>,
+        partial: &str,
+    ) -> Vec<Completion> {
+        if partial.starts_with('@') {
+            return METADATA_FIELDS
+                .iter()
+                .filter(|field| field.starts_with(partial))
+                .map(|field| Completion {
+                    label: field.to_string(),
+                    kind: CompletionKind::Field { required: true },
+                    detail: "metadata".to_string(),
+                    insert_text: None,
+                })
+                .collect();
+        }
+
+        match entity_type {
+            Some(entity_type) => self.field_completions(&entity_type, partial, &[]),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Finds the range of a string value's text inside its quotes.
+fn inner_range(raw: &str, value_range: Range) -> Option<Range> {
+    let quotes = if raw.starts_with(MULTILINE_QUOTES) && raw.len() >= 6 {
+        MULTILINE_QUOTES.len()
+    } else if raw.starts_with('"') && raw.len() >= 2 {
+        1
+    } else {
+        return None;
+    };
+
+    let inner = &raw[quotes..raw.len() - quotes];
+    let end_row_offset = inner.matches('\n').count();
+    let end_column = match inner.rfind('\n') {
+        Some(newline) => inner.len() - newline - 1,
+        None => value_range.start_point.column + quotes + inner.len(),
+    };
+
+    Some(Range {
+        start_byte: value_range.start_byte + quotes,
+        end_byte: value_range.end_byte - quotes,
+        start_point: Point {
+            row: value_range.start_point.row,
+            column: value_range.start_point.column + quotes,
+        },
+        end_point: Point {
+            row: value_range.start_point.row + end_row_offset,
+            column: end_column,
+        },
+    })
+}
+
+/// Finds the entity type a query selects, from its `from` clause.
+fn queried_type(query: &str) -> Option<EntityType> {
+    let mut words = query.split_whitespace();
+    words.find(|word| *word == "from")?;
+    words
+        .next()
+        .filter(|word| *word != "*")
+        .map(EntityType::new)
+}
+
+/// Whether the words after `related` open a list of types that isn't closed yet.
+fn in_type_list(words: &[&str]) -> bool {
+    words.first().is_some_and(|word| word.starts_with('['))
+        && !words.iter().any(|word| word.contains(']'))
+}
+
+fn keyword_completions(keywords: &[&str], partial: &str) -> Vec<Completion> {
+    keywords
+        .iter()
+        .filter(|keyword| keyword.starts_with(partial))
+        .map(|keyword| Completion {
+            label: keyword.to_string(),
+            kind: CompletionKind::Keyword,
+            detail: "keyword".to_string(),
+            insert_text: None,
+        })
+        .collect()
+}
+
+fn operator_completions(partial: &str) -> Vec<Completion> {
+    OPERATORS
+        .iter()
+        .filter(|operator| operator.starts_with(partial))
+        .map(|operator| Completion {
+            label: operator.to_string(),
+            kind: CompletionKind::Operator,
+            detail: "operator".to_string(),
+            insert_text: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use firm_core::EntitySchema;
+
+    fn build() -> WorkspaceBuild {
+        let schema = EntitySchema::new(EntityType::new("task"))
+            .with_required_field(FieldId::new("name"), firm_core::FieldType::String)
+            .with_optional_field(FieldId::new("is_done"), firm_core::FieldType::Boolean);
+        WorkspaceBuild::new(Vec::new(), vec![schema])
+    }
+
+    fn labels(completions: Vec<Completion>) -> Vec<String> {
+        completions.into_iter().map(|c| c.label).collect()
+    }
+
+    #[test]
+    fn test_query_completions() {
+        let build = build();
+
+        assert_eq!(labels(build.query_completions("fr")), vec!["from"]);
+        assert_eq!(labels(build.query_completions("from t")), vec!["task"]);
+        assert_eq!(
+            labels(build.query_completions("from task | wh")),
+            vec!["where"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | where is_done == true and ")),
+            vec!["name", "is_done"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | where name con")),
+            vec!["contains"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | order @mod")),
+            vec!["@modified"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | order name d")),
+            vec!["desc"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | select name, is")),
+            vec!["is_done"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | related [t")),
+            vec!["task"]
+        );
+        assert_eq!(
+            labels(build.query_completions("from task | related(2) [task, t")),
+            vec!["task"]
+        );
+        assert!(
+            build
+                .query_completions("from task | related [task] t")
+                .is_empty()
+        );
+
+        // Fields come from the queried type only
+        assert!(build.query_completions("from * | where ").is_empty());
+    }
+
+    #[test]
+    fn test_inner_range() {
+        let start = Point { row: 2, column: 12 };
+        let value_range = |raw: &str, end_point: Point| Range {
+            start_byte: 40,
+            end_byte: 40 + raw.len(),
+            start_point: start,
+            end_point,
+        };
+
+        let raw = "\"from task\"";
+        let range = inner_range(raw, value_range(raw, Point { row: 2, column: 23 })).unwrap();
+        assert_eq!((range.start_byte, range.end_byte), (41, 50));
+        assert_eq!(range.start_point, Point { row: 2, column: 13 });
+        assert_eq!(range.end_point, Point { row: 2, column: 22 });
+
+        let raw = "\"\"\"\nfrom task\n| limit 1\"\"\"";
+        let range = inner_range(raw, value_range(raw, Point { row: 4, column: 12 })).unwrap();
+        assert_eq!(range.start_point, Point { row: 2, column: 15 });
+        assert_eq!(range.end_point, Point { row: 4, column: 9 });
+    }
+
+    #[test]
+    fn test_offsets_map_between_query_and_document() {
+        let embedded = EmbeddedQuery {
+            entity_type: EntityType::new("report"),
+            field: FieldId::new("query"),
+            query: "from task\n| limit x".to_string(),
+            range: Range {
+                start_byte: 10,
+                end_byte: 29,
+                start_point: Point { row: 1, column: 13 },
+                end_point: Point { row: 2, column: 9 },
+            },
+            verbatim: true,
+        };
+
+        assert_eq!(embedded.position_at(5), Point { row: 1, column: 18 });
+        assert_eq!(embedded.position_at(18), Point { row: 2, column: 8 });
+        assert_eq!(embedded.offset_at(Point { row: 1, column: 18 }), Some(5));
+        assert_eq!(embedded.offset_at(Point { row: 2, column: 8 }), Some(18));
+        assert_eq!(embedded.offset_at(Point { row: 0, column: 0 }), None);
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use tree_sitter::Range;
+
+/// A block in the workspace, like one that contributes to a built entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySource {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+impl EntitySource {
+    pub fn new(path: PathBuf, range: Range) -> Self {
+        Self { path, range }
+    }
+
+    /// The 1-based line where the contributing block starts.
+    pub fn line(&self) -> usize {
+        self.range.start_point.row + 1
+    }
+
+    /// The 1-based column where the block starts.
+    pub fn column(&self) -> usize {
+        self.range.start_point.column + 1
+    }
+
+    /// The 1-based line where the block ends, with its closing brace.
+    pub fn end_line(&self) -> usize {
+        let end = self.range.end_point;
+        // A range that ends at the start of a line ends on the line before it
+        if end.column == 0 && end.row > self.range.start_point.row {
+            end.row
+        } else {
+            end.row + 1
+        }
+    }
+}
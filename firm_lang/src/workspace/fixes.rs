@@ -0,0 +1,746 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use firm_core::schema::{FieldSchema, ValidationErrorType};
+use firm_core::{
+    Entity, EntityId, EntitySchema, EntityType, FieldId, FieldType, FieldValue, ReferenceValue,
+    decompose_entity_id, suggest_similar,
+};
+use tree_sitter::Range;
+
+use super::{
+    BrokenReferenceKind, Diagnostic, Location, Workspace,
+    positions::{empty_range, position_at},
+    references::{collect_references, resolve},
+};
+use crate::defaults::all_default_schemas;
+use crate::generate::{
+    from_value::generate_value, generate_schema_dsl, generator_options::GeneratorOptions,
+};
+use crate::parser::dsl::{ParsedEntity, ParsedField, local_timezone};
+
+/// A replacement of a span of source text.
+///
+/// Insertions are edits with an empty range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: Range,
+    pub replacement: String,
+}
+
+/// A suggested fix for a problem in a workspace file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedFix {
+    /// The file the edits apply to.
+    pub path: PathBuf,
+    pub line: usize,
+    /// The diagnostic code of the problem this fixes (e.g. "missing-required-field").
+    pub code: &'static str,
+    pub message: String,
+    pub edits: Vec<TextEdit>,
+    /// Where the problem is, which can be in another file than the edits.
+    pub problem: Location,
+}
+
+impl fmt::Display for SuggestedFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.message)
+    }
+}
+
+/// Applies non-overlapping edits to source text.
+///
+/// Edits are applied from the end of the text so earlier byte offsets stay valid.
+pub fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+    let mut sorted_edits: Vec<&TextEdit> = edits.iter().collect();
+    sorted_edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start_byte));
+
+    let mut result = source.to_string();
+    for edit in sorted_edits {
+        result.replace_range(
+            edit.range.start_byte..edit.range.end_byte,
+            &edit.replacement,
+        );
+    }
+    result
+}
+
+impl Workspace {
+    /// Suggests fixes for problems that have an obvious solution.
+    ///
+    /// This covers missing required fields, enum values with a near-matching
+    /// allowed value, references with a near-matching entity ID and entity types
+    /// without a schema. Schemas and entities are collected leniently, so this
+    /// works when the build fails.
+    pub fn suggest_fixes(&self) -> Vec<SuggestedFix> {
+        self.fixes(false)
+    }
+
+    /// Collects the fixes, also filling in missing fields without an obvious
+    /// value with placeholders when `placeholders` is set.
+    fn fixes(&self, placeholders: bool) -> Vec<SuggestedFix> {
+        let schemas = self.lenient_schemas();
+        let today = self.placeholder_date(Utc::now());
+
+        let entities: Vec<Entity> = self
+            .files
+            .values()
+            .flat_map(|file| file.parsed.entities())
+            .filter_map(|parsed_entity| Entity::try_from(&parsed_entity).ok())
+            .collect();
+        let entity_map: HashMap<&EntityId, &Entity> = entities.iter().map(|e| (&e.id, e)).collect();
+
+        let mut fixes = Vec::new();
+        for (path, file) in &self.files {
+            for parsed_entity in file.parsed.entities() {
+                let Ok(entity) = Entity::try_from(&parsed_entity) else {
+                    continue;
+                };
+
+                if let Some(schema) = schemas.get(&entity.entity_type)
+                    && let Err(errors) = schema.validate(&entity)
+                {
+                    let mut missing_fields = Vec::new();
+                    for error in errors {
+                        match error.error_type {
+                            ValidationErrorType::MissingRequiredField { required } => {
+                                missing_fields.push(required)
+                            }
+                            ValidationErrorType::InvalidEnumValue { actual, allowed } => {
+                                fixes.extend(enum_fix(
+                                    path,
+                                    &parsed_entity,
+                                    error.field.as_ref(),
+                                    &actual,
+                                    &allowed,
+                                ));
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    fixes.extend(missing_fields_fix(
+                        path,
+                        &parsed_entity,
+                        &file.parsed.source,
+                        schema,
+                        &missing_fields,
+                        placeholders,
+                        today,
+                    ));
+                }
+
+                for parsed_field in parsed_entity.fields() {
+                    fixes.extend(reference_fix(path, &parsed_field, &entity_map));
+                }
+            }
+        }
+
+        fixes.extend(self.missing_schema_fixes(&schemas, false));
+        fixes
+    }
+
+    /// Gets the date datetime placeholders are filled in with: the day it is
+    /// at a time in the workspace's timezone.
+    fn placeholder_date(&self, now: DateTime<Utc>) -> NaiveDate {
+        let timezone = self.timezone.unwrap_or_else(local_timezone);
+        now.with_timezone(&timezone).date_naive()
+    }
+
+    /// Gets the fixes for diagnostics in a file, like an editor's code actions.
+    ///
+    /// A fix matches a diagnostic with the same code when the diagnostic's line
+    /// falls within the problem. Validation errors from the build aren't tied to
+    /// a line, so they match every missing field and invalid enum value fix in
+    /// the file. Each fix is returned once, in the order of `suggest_fixes`.
+    ///
+    /// Unlike `suggest_fixes`, missing fields of every type are filled in, with
+    /// placeholders like `type.id` for references that are left to edit.
+    ///
+    /// A missing schema that would be added to another file can also be added
+    /// to the file with its entities, which follows right after as an alternative.
+    pub fn code_actions(&self, path: &Path, diagnostics: &[Diagnostic]) -> Vec<SuggestedFix> {
+        let fixes: Vec<SuggestedFix> = self
+            .fixes(true)
+            .into_iter()
+            .filter(|fix| fix.problem.path == path)
+            .filter(|fix| {
+                diagnostics
+                    .iter()
+                    .any(|diagnostic| fix_matches_diagnostic(fix, diagnostic))
+            })
+            .collect();
+        if !fixes.iter().any(|fix| fix.code == "missing-schema") {
+            return fixes;
+        }
+
+        let mut alternatives = self.missing_schema_fixes(&self.lenient_schemas(), true);
+        let mut actions = Vec::new();
+        for fix in fixes {
+            let alternative = alternatives.iter().position(|alternative| {
+                alternative.code == fix.code
+                    && alternative.problem == fix.problem
+                    && alternative.path != fix.path
+            });
+            actions.push(fix);
+            if let Some(index) = alternative {
+                actions.push(alternatives.swap_remove(index));
+            }
+        }
+        actions
+    }
+
+    /// Collects the schemas that convert, skipping the ones that don't.
+    fn lenient_schemas(&self) -> HashMap<EntityType, EntitySchema> {
+        self.files
+            .values()
+            .flat_map(|file| file.parsed.schemas())
+            .filter_map(|parsed_schema| EntitySchema::try_from(&parsed_schema).ok())
+            .map(|schema| (schema.entity_type.clone(), schema))
+            .collect()
+    }
+
+    /// Suggests adding a schema for each entity type that doesn't have one.
+    ///
+    /// Default entity types get their default schema. Other types get a schema
+    /// with an optional field for every field their entities set, typed from
+    /// the values. The schema is added to the end of the first file that has
+    /// schemas, or the entity's own file when there are none or `in_entity_file` is set.
+    fn missing_schema_fixes(
+        &self,
+        schemas: &HashMap<EntityType, EntitySchema>,
+        in_entity_file: bool,
+    ) -> Vec<SuggestedFix> {
+        // Entities of each missing type, with the first one's location
+        let mut missing: Vec<(EntityType, Location, Vec<Entity>)> = Vec::new();
+        for (path, file) in &self.files {
+            for parsed_entity in file.parsed.entities() {
+                let Ok(mut entity) = Entity::try_from(&parsed_entity) else {
+                    continue;
+                };
+                if schemas.contains_key(&entity.entity_type) {
+                    continue;
+                }
+
+                // Tags don't need a schema field
+                entity.extract_tags();
+
+                match missing
+                    .iter_mut()
+                    .find(|(missing_type, _, _)| *missing_type == entity.entity_type)
+                {
+                    Some((_, _, entities)) => entities.push(entity),
+                    None => missing.push((
+                        entity.entity_type.clone(),
+                        Location {
+                            path: path.clone(),
+                            range: parsed_entity.range(),
+                        },
+                        vec![entity],
+                    )),
+                }
+            }
+        }
+
+        let schema_file = self
+            .files
+            .iter()
+            .find(|(_, file)| !file.parsed.schemas().is_empty());
+        let default_schemas = all_default_schemas();
+
+        missing
+            .into_iter()
+            .filter_map(|(entity_type, problem, entities)| {
+                let (path, file) = match schema_file {
+                    Some(schema_file) if !in_entity_file => schema_file,
+                    _ => self.files.get_key_value(&problem.path)?,
+                };
+
+                let (schema, origin) = match default_schemas
+                    .iter()
+                    .find(|schema| schema.entity_type == entity_type)
+                {
+                    Some(schema) => (schema.clone(), "the default schema"),
+                    None => (
+                        infer_schema(&entity_type, &entities),
+                        "inferred from its entities",
+                    ),
+                };
+
+                // Separate the new block from existing content with a blank line
+                let source = &file.parsed.source;
+                let separator = if source.is_empty() || source.ends_with("\n\n") {
+                    ""
+                } else if source.ends_with('\n') {
+                    "\n"
+                } else {
+                    "\n\n"
+                };
+                let range = empty_range(source.len(), position_at(source, source.len()));
+
+                Some(SuggestedFix {
+                    path: path.clone(),
+                    line: range.start_point.row + 1,
+                    code: "missing-schema",
+                    message: if *path == problem.path {
+                        format!(
+                            "Add a schema for entity type '{}' to this file ({})",
+                            entity_type, origin
+                        )
+                    } else {
+                        format!(
+                            "Add a schema for entity type '{}' to {} ({})",
+                            entity_type,
+                            path.file_name().unwrap_or_default().to_string_lossy(),
+                            origin
+                        )
+                    },
+                    edits: vec![TextEdit {
+                        range,
+                        replacement: format!("{}{}", separator, generate_schema_dsl(&schema)),
+                    }],
+                    problem,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Checks whether a fix addresses a diagnostic.
+fn fix_matches_diagnostic(fix: &SuggestedFix, diagnostic: &Diagnostic) -> bool {
+    let Some(line) = diagnostic.line else {
+        return match diagnostic.code {
+            "validation-error" => {
+                matches!(fix.code, "missing-required-field" | "invalid-enum-value")
+            }
+            code => code == fix.code,
+        };
+    };
+
+    let first_line = fix.problem.range.start_point.row + 1;
+    let last_line = fix.problem.range.end_point.row + 1;
+    diagnostic.code == fix.code && (first_line..=last_line).contains(&line)
+}
+
+/// Infers a schema from the fields set on entities of a type.
+///
+/// Every field is optional, typed from its first value. Enum fields allow
+/// every value the entities use.
+fn infer_schema(entity_type: &EntityType, entities: &[Entity]) -> EntitySchema {
+    let mut fields: Vec<(&FieldId, FieldType, Vec<String>)> = Vec::new();
+    for (field_id, value) in entities.iter().flat_map(|entity| &entity.fields) {
+        let index = match fields.iter().position(|(id, _, _)| *id == field_id) {
+            Some(index) => index,
+            None => {
+                fields.push((field_id, value.get_type(), Vec::new()));
+                fields.len() - 1
+            }
+        };
+
+        let (_, field_type, allowed_values) = &mut fields[index];
+        if let (FieldType::Enum, FieldValue::Enum(enum_value)) = (field_type, value)
+            && !allowed_values.contains(enum_value)
+        {
+            allowed_values.push(enum_value.clone());
+        }
+    }
+
+    fields.into_iter().fold(
+        EntitySchema::new(entity_type.clone()),
+        |schema, (field_id, field_type, allowed_values)| match field_type {
+            FieldType::Enum => schema.with_optional_enum(field_id.clone(), allowed_values),
+            field_type => schema.with_optional_field(field_id.clone(), field_type),
+        },
+    )
+}
+
+/// Suggests inserting placeholder values for missing required fields.
+///
+/// Fields without an obvious value are skipped, unless `placeholders` is set.
+/// Datetime placeholders are set to `today`.
+fn missing_fields_fix(
+    path: &Path,
+    parsed_entity: &ParsedEntity,
+    source: &str,
+    schema: &EntitySchema,
+    missing_fields: &[FieldId],
+    placeholders: bool,
+    today: NaiveDate,
+) -> Option<SuggestedFix> {
+    // Keep schema order so the inserted fields read naturally
+    let stubs: Vec<(&FieldId, String)> = schema
+        .ordered_fields()
+        .into_iter()
+        .filter(|(field_id, _)| missing_fields.contains(field_id))
+        .filter_map(|(field_id, field_schema)| {
+            let value = if placeholders {
+                Some(placeholder_value(field_schema, today))
+            } else {
+                stub_value(field_schema)
+            };
+            Some((field_id, value?))
+        })
+        .collect();
+    if stubs.is_empty() {
+        return None;
+    }
+
+    // The entity block ends with its closing brace
+    let entity_range = parsed_entity.range();
+    let brace_byte = entity_range.end_byte.checked_sub(1)?;
+    if source.as_bytes().get(brace_byte) != Some(&b'}') {
+        return None;
+    }
+
+    let line_start = source[..brace_byte].rfind('\n').map_or(0, |i| i + 1);
+    let brace_on_own_line = source[line_start..brace_byte].trim().is_empty();
+
+    let (insert_byte, replacement) = if brace_on_own_line {
+        // Insert whole lines above the closing brace, indented one level deeper
+        let indent = &source[line_start..brace_byte];
+        let lines: String = stubs
+            .iter()
+            .map(|(field_id, value)| format!("{}    {} = {}\n", indent, field_id, value))
+            .collect();
+        (line_start, lines)
+    } else {
+        let fields: String = stubs
+            .iter()
+            .map(|(field_id, value)| format!("{} = {} ", field_id, value))
+            .collect();
+        (brace_byte, fields)
+    };
+
+    let names: Vec<&str> = stubs
+        .iter()
+        .map(|(field_id, _)| field_id.as_str())
+        .collect();
+    Some(SuggestedFix {
+        path: path.to_path_buf(),
+        line: parsed_entity.range().start_point.row + 1,
+        code: "missing-required-field",
+        message: format!(
+            "Add missing required field(s) to '{}': {}",
+            parsed_entity.id().unwrap_or_default(),
+            names.join(", ")
+        ),
+        edits: vec![TextEdit {
+            range: empty_range(insert_byte, position_at(source, insert_byte)),
+            replacement,
+        }],
+        problem: Location {
+            path: path.to_path_buf(),
+            range: entity_range,
+        },
+    })
+}
+
+/// Suggests replacing an invalid enum value with the closest allowed value.
+fn enum_fix(
+    path: &Path,
+    parsed_entity: &ParsedEntity,
+    field: Option<&FieldId>,
+    actual: &str,
+    allowed: &[String],
+) -> Option<SuggestedFix> {
+    let field = field?;
+    let suggestion = closest_match(actual, allowed.iter().map(|v| v.as_str()))?;
+    let parsed_field = parsed_entity
+        .fields()
+        .into_iter()
+        .rfind(|f| f.id() == Some(field.as_str()))?;
+
+    Some(SuggestedFix {
+        path: path.to_path_buf(),
+        line: parsed_field.line(),
+        code: "invalid-enum-value",
+        message: format!(
+            "Replace invalid value '{}' of field '{}' with '{}'",
+            actual, field, suggestion
+        ),
+        edits: vec![TextEdit {
+            range: parsed_field.value_range()?,
+            replacement: generate_value(
+                &FieldValue::Enum(suggestion.clone()),
+                &GeneratorOptions::default(),
+            ),
+        }],
+        problem: Location {
+            path: path.to_path_buf(),
+            range: parsed_field.range(),
+        },
+    })
+}
+
+/// Suggests replacing references to missing entities with the closest existing entity ID.
+fn reference_fix(
+    path: &Path,
+    parsed_field: &ParsedField,
+    entities: &HashMap<&EntityId, &Entity>,
+) -> Option<SuggestedFix> {
+    let value = FieldValue::try_from(parsed_field.value().ok()?).ok()?;
+
+    let mut references = Vec::new();
+    collect_references(&value, &mut references);
+
+    let mut replacements = Vec::new();
+    for reference in references {
+        if resolve(entities, reference) != Some(BrokenReferenceKind::MissingEntity) {
+            continue;
+        }
+
+        let target_id = match reference {
+            ReferenceValue::Entity(id) | ReferenceValue::Field(id, _) => id,
+        };
+        let (target_type, _) = decompose_entity_id(target_id.as_str());
+        let candidates = entities
+            .keys()
+            .map(|id| id.as_str())
+            .filter(|id| decompose_entity_id(id).0 == target_type);
+
+        if let Some(suggestion) = closest_match(target_id.as_str(), candidates) {
+            replacements.push((target_id.clone(), EntityId::new(suggestion)));
+        }
+    }
+    if replacements.is_empty() {
+        return None;
+    }
+
+    // Regenerate the whole value so references inside lists are fixed too
+    let fixed_value = replace_references(&value, &replacements);
+    let descriptions: Vec<String> = replacements
+        .iter()
+        .map(|(from, to)| format!("'{}' with '{}'", from, to))
+        .collect();
+
+    Some(SuggestedFix {
+        path: path.to_path_buf(),
+        line: parsed_field.line(),
+        code: "broken-reference",
+        message: format!(
+            "Replace reference to missing entity {} in field '{}'",
+            descriptions.join(", "),
+            parsed_field.id().unwrap_or_default()
+        ),
+        edits: vec![TextEdit {
+            range: parsed_field.value_range()?,
+            replacement: generate_value(&fixed_value, &GeneratorOptions::default()),
+        }],
+        problem: Location {
+            path: path.to_path_buf(),
+            range: parsed_field.range(),
+        },
+    })
+}
+
+/// Replaces the target entity of references in a value, including inside lists.
+fn replace_references(value: &FieldValue, replacements: &[(EntityId, EntityId)]) -> FieldValue {
+    let replace = |id: &EntityId| {
+        replacements
+            .iter()
+            .find(|(from, _)| from == id)
+            .map_or_else(|| id.clone(), |(_, to)| to.clone())
+    };
+
+    match value {
+        FieldValue::Reference(ReferenceValue::Entity(id)) => {
+            FieldValue::Reference(ReferenceValue::Entity(replace(id)))
+        }
+        FieldValue::Reference(ReferenceValue::Field(id, field)) => {
+            FieldValue::Reference(ReferenceValue::Field(replace(id), field.clone()))
+        }
+        FieldValue::List(items) => FieldValue::List(
+            items
+                .iter()
+                .map(|item| replace_references(item, replacements))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Gets a placeholder value for a field, for types that have an obvious one.
+fn stub_value(field_schema: &FieldSchema) -> Option<String> {
+    match field_schema.expected_type() {
+        FieldType::Boolean => Some("false".to_string()),
+        FieldType::String => Some("\"\"".to_string()),
+        FieldType::Integer => Some("0".to_string()),
+        FieldType::Float => Some("0.0".to_string()),
+        FieldType::List => Some("[]".to_string()),
+        FieldType::Enum => {
+            let first_value = field_schema.allowed_values()?.first()?;
+            Some(generate_value(
+                &FieldValue::Enum(first_value.clone()),
+                &GeneratorOptions::default(),
+            ))
+        }
+        FieldType::Currency | FieldType::Reference | FieldType::DateTime | FieldType::Path => None,
+    }
+}
+
+/// Gets a value to fill in for a field of any type.
+///
+/// Types without an obvious value get a placeholder that parses but is meant
+/// to be edited, like `type.id` for a reference or today's date.
+fn placeholder_value(field_schema: &FieldSchema, today: NaiveDate) -> String {
+    match field_schema.expected_type() {
+        FieldType::Reference => "type.id".to_string(),
+        FieldType::Currency => "0.00 USD".to_string(),
+        FieldType::DateTime => today.format("%Y-%m-%d").to_string(),
+        FieldType::Path => "path\"\"".to_string(),
+        _ => stub_value(field_schema).unwrap_or_else(|| "enum\"\"".to_string()),
+    }
+}
+
+/// Finds the candidate closest to a value, if it's a near match.
+fn closest_match<'a>(value: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    suggest_similar(value, candidates).into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Point;
+
+    fn range(start_byte: usize, end_byte: usize) -> Range {
+        Range {
+            start_byte,
+            end_byte,
+            start_point: Point::new(0, start_byte),
+            end_point: Point::new(0, end_byte),
+        }
+    }
+
+    #[test]
+    fn test_apply_edits_in_any_order() {
+        let source = "status = enum\"activ\"\nowner = person.jon";
+        let edits = vec![
+            TextEdit {
+                range: range(9, 20),
+                replacement: "enum\"active\"".to_string(),
+            },
+            TextEdit {
+                range: range(29, 39),
+                replacement: "person.john".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            apply_edits(source, &edits),
+            "status = enum\"active\"\nowner = person.john"
+        );
+    }
+
+    #[test]
+    fn test_apply_insertion() {
+        let source = "a {\n}";
+        let edits = vec![TextEdit {
+            range: empty_range(4, Point::new(1, 0)),
+            replacement: "    x = 0\n".to_string(),
+        }];
+
+        assert_eq!(apply_edits(source, &edits), "a {\n    x = 0\n}");
+    }
+
+    #[test]
+    fn test_placeholder_value_for_every_type() {
+        use firm_core::schema::FieldMode;
+
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let placeholder = |field_type| {
+            placeholder_value(&FieldSchema::new(field_type, FieldMode::Required, 0), today)
+        };
+
+        assert_eq!(placeholder(FieldType::String), "\"\"");
+        assert_eq!(placeholder(FieldType::Integer), "0");
+        assert_eq!(placeholder(FieldType::Reference), "type.id");
+        assert_eq!(placeholder(FieldType::Currency), "0.00 USD");
+        assert_eq!(placeholder(FieldType::Path), "path\"\"");
+        assert_eq!(placeholder(FieldType::DateTime), "2025-01-15");
+    }
+
+    #[test]
+    fn test_placeholder_date_is_in_the_workspace_timezone() {
+        use chrono::FixedOffset;
+
+        let now = DateTime::parse_from_rfc3339("2025-01-15T23:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let workspace = |hours| {
+            Workspace::new().with_timezone(Some(FixedOffset::east_opt(hours * 3600).unwrap()))
+        };
+
+        assert_eq!(
+            workspace(2).placeholder_date(now),
+            NaiveDate::from_ymd_opt(2025, 1, 16).unwrap()
+        );
+        assert_eq!(
+            workspace(-5).placeholder_date(now),
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_closest_match() {
+        let candidates = ["prospect", "customer", "partner"];
+
+        assert_eq!(
+            closest_match("custmer", candidates.iter().copied()),
+            Some("customer".to_string())
+        );
+        assert_eq!(
+            closest_match("Partnr", candidates.iter().copied()),
+            Some("partner".to_string())
+        );
+        assert_eq!(closest_match("vendor", candidates.iter().copied()), None);
+    }
+
+    #[test]
+    fn test_closest_match_breaks_ties_by_name() {
+        let expected = Some("acme.bob".to_string());
+        assert_eq!(
+            closest_match("acme.bo", ["acme.box", "acme.bob"].into_iter()),
+            expected
+        );
+        assert_eq!(
+            closest_match("acme.bo", ["acme.bob", "acme.box"].into_iter()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_from_entities() {
+        let entity_type = EntityType::new("gadget");
+        let entities = vec![
+            Entity::new(EntityId::new("phone"), entity_type.clone())
+                .with_field(FieldId::new("model"), "X")
+                .with_field(FieldId::new("kind"), FieldValue::Enum("mobile".to_string())),
+            Entity::new(EntityId::new("laptop"), entity_type.clone())
+                .with_field(FieldId::new("kind"), FieldValue::Enum("computer".to_string()))
+                .with_field(FieldId::new("ports"), 2),
+        ];
+
+        let schema = infer_schema(&entity_type, &entities);
+        let fields: Vec<(&str, &FieldType, bool)> = schema
+            .ordered_fields()
+            .into_iter()
+            .map(|(id, field)| (id.as_str(), &field.field_type, field.is_required()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("model", &FieldType::String, false),
+                ("kind", &FieldType::Enum, false),
+                ("ports", &FieldType::Integer, false),
+            ]
+        );
+        assert_eq!(
+            schema.fields[&FieldId::new("kind")].allowed_values(),
+            Some(&vec!["mobile".to_string(), "computer".to_string()])
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use tree_sitter::Node;
+
+use crate::parser::dsl::ParsedSource;
+
+const FOLDABLE_KINDS: [&str; 4] = ["entity_block", "schema_block", "nested_block", "list"];
+const COMMENT_KIND: &str = "comment";
+
+/// What a folding range covers, so editors can fold all comments at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FoldingKind {
+    /// A block or a list.
+    Region,
+    /// A multi-line comment or a run of comment lines.
+    Comment,
+}
+
+/// Lines of a document that can be folded away.
+///
+/// Rows are zero-based and inclusive: the range starts on the line that
+/// opens a block and ends on the line that closes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldingRange {
+    pub start_row: usize,
+    pub end_row: usize,
+    pub kind: FoldingKind,
+}
+
+/// Finds the foldable ranges of a parsed document.
+///
+/// Schema and entity blocks, nested blocks like schema fields and lists
+/// spanning more than one line can be folded, as can multi-line comments and
+/// runs of comments on consecutive lines. Ranges are sorted by start row.
+pub fn folding_ranges(parsed: &ParsedSource) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    let mut comment_run: Option<(usize, usize)> = None;
+    collect_folding_ranges(
+        parsed.tree.root_node(),
+        &parsed.source,
+        &mut ranges,
+        &mut comment_run,
+    );
+    push_comment_run(comment_run, &mut ranges);
+
+    ranges.sort_by_key(|range| (range.start_row, std::cmp::Reverse(range.end_row)));
+    ranges
+}
+
+fn collect_folding_ranges(
+    node: Node,
+    source: &str,
+    ranges: &mut Vec<FoldingRange>,
+    comment_run: &mut Option<(usize, usize)>,
+) {
+    let start_row = node.start_position().row;
+    let end_row = node.end_position().row;
+
+    if node.kind() == COMMENT_KIND {
+        // Comments with code before them on their line don't join a run
+        let line_start = source[..node.start_byte()]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let starts_line = source[line_start..node.start_byte()].trim().is_empty();
+
+        match comment_run {
+            Some((_, run_end)) if starts_line && start_row == *run_end + 1 => {
+                *run_end = end_row;
+            }
+            _ => {
+                push_comment_run(comment_run.take(), ranges);
+                if starts_line {
+                    *comment_run = Some((start_row, end_row));
+                } else if end_row > start_row {
+                    push_comment_run(Some((start_row, end_row)), ranges);
+                }
+            }
+        }
+        return;
+    }
+
+    if FOLDABLE_KINDS.contains(&node.kind()) && end_row > start_row {
+        ranges.push(FoldingRange {
+            start_row,
+            end_row,
+            kind: FoldingKind::Region,
+        });
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_folding_ranges(child, source, ranges, comment_run);
+    }
+}
+
+/// Adds a run of comment lines as a folding range, if it spans more than one line.
+fn push_comment_run(comment_run: Option<(usize, usize)>, ranges: &mut Vec<FoldingRange>) {
+    if let Some((start_row, end_row)) = comment_run
+        && end_row > start_row
+    {
+        ranges.push(FoldingRange {
+            start_row,
+            end_row,
+            kind: FoldingKind::Comment,
+        });
+    }
+}
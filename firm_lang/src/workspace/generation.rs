@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A counter of changes, to tell stale work from work on the latest change.
+///
+/// An editor integration advances the generation on every change and hands
+/// the token to the work it starts, like computing diagnostics after a
+/// debounce. Work whose token went stale can stop early and drop its
+/// results, since newer work will replace them, so what's published always
+/// converges to the latest change.
+#[derive(Debug, Clone, Default)]
+pub struct Generation {
+    current: Arc<AtomicU64>,
+}
+
+/// The generation some work was started for.
+#[derive(Debug, Clone)]
+pub struct GenerationToken {
+    generation: u64,
+    current: Arc<AtomicU64>,
+}
+
+impl Generation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves to a new generation for a change, making earlier tokens stale.
+    pub fn advance(&self) -> GenerationToken {
+        let generation = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        GenerationToken {
+            generation,
+            current: Arc::clone(&self.current),
+        }
+    }
+
+    /// Gets a token for the current generation, without advancing it.
+    pub fn token(&self) -> GenerationToken {
+        GenerationToken {
+            generation: self.current.load(Ordering::SeqCst),
+            current: Arc::clone(&self.current),
+        }
+    }
+}
+
+impl GenerationToken {
+    /// Checks whether no change came in since the token was taken.
+    pub fn is_current(&self) -> bool {
+        self.current.load(Ordering::SeqCst) == self.generation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advancing_makes_earlier_tokens_stale() {
+        let generation = Generation::new();
+        let first = generation.advance();
+        let same = generation.token();
+        assert!(first.is_current());
+        assert!(same.is_current());
+
+        let second = generation.clone().advance();
+        assert!(!first.is_current());
+        assert!(!same.is_current());
+        assert!(second.is_current());
+    }
+}
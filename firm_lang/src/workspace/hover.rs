@@ -0,0 +1,220 @@
+use firm_core::schema::FieldSchema;
+use firm_core::{Entity, EntityId, FieldId, FieldValue, compose_entity_id};
+use tree_sitter::{Point, Range};
+
+use super::WorkspaceBuild;
+use super::definition::{contains, find_ancestor};
+use crate::parser::dsl::{ParsedEntity, ParsedValue, parse_source};
+
+const REFERENCE_KIND: &str = "reference";
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+
+/// The most fields shown on an entity card.
+const MAX_FIELDS: usize = 20;
+/// The most items shown for a list value.
+const MAX_LIST_ITEMS: usize = 5;
+/// The most characters shown for a single value.
+const MAX_VALUE_CHARS: usize = 80;
+
+/// A Markdown summary of the thing at a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hover {
+    pub contents: String,
+    /// The range of the hovered text, like a reference or field name.
+    pub range: Range,
+}
+
+impl WorkspaceBuild {
+    /// Summarizes the reference or field name at a position in a source document.
+    ///
+    /// The source is parsed as given, so it can be an unsaved editor buffer.
+    /// References and entity IDs render as a card with the entity's fields and
+    /// the file that defines it. Field names render as their schema definition.
+    /// Long values and lists are truncated. The position is zero-based, with the column in bytes.
+    pub fn hover(&self, source: &str, position: Point) -> Option<Hover> {
+        let parsed = parse_source(source.to_string(), None).ok()?;
+        let node = parsed
+            .tree
+            .root_node()
+            .descendant_for_point_range(position, position)?;
+
+        if let Some(reference) = find_ancestor(node, REFERENCE_KIND) {
+            let text = &parsed.source[reference.byte_range()];
+            let entity_id = match ParsedValue::parse_reference(text).ok()? {
+                ParsedValue::EntityReference {
+                    entity_type,
+                    entity_id,
+                }
+                | ParsedValue::FieldReference {
+                    entity_type,
+                    entity_id,
+                    ..
+                } => compose_entity_id(&entity_type, &entity_id),
+                _ => return None,
+            };
+            return self.entity_hover(&entity_id, reference.range());
+        }
+
+        let block = find_ancestor(node, ENTITY_BLOCK_KIND)?;
+        let entity = ParsedEntity::new(block, &parsed.source, &parsed.path);
+        let entity_type = entity.entity_type()?;
+
+        if let Some(range) = entity.id_range().filter(|range| contains(range, position)) {
+            let entity_id = compose_entity_id(entity_type, entity.id()?);
+            return self.entity_hover(&entity_id, range);
+        }
+
+        let field = entity.fields().into_iter().find(|field| {
+            field
+                .id_range()
+                .is_some_and(|range| contains(&range, position))
+        })?;
+        let field_id = FieldId::new(field.id()?);
+        let field_schema = self
+            .schemas
+            .iter()
+            .find(|schema| schema.entity_type.as_str() == entity_type)?
+            .fields
+            .get(&field_id)?;
+
+        Some(Hover {
+            contents: render_field_schema(entity_type, &field_id, field_schema),
+            range: field.id_range()?,
+        })
+    }
+
+    /// Renders the card for a built entity.
+    fn entity_hover(&self, entity_id: &EntityId, range: Range) -> Option<Hover> {
+        let entity = self
+            .entities
+            .iter()
+            .find(|entity| &entity.id == entity_id)?;
+        let mut contents = self.render_entity(entity);
+
+        if let Some(source) = self.entity_sources(entity_id).first() {
+            contents.push_str(&format!(
+                "\n---\n\n_{}:{}_",
+                source.path.display(),
+                source.line()
+            ));
+        }
+
+        Some(Hover { contents, range })
+    }
+
+    /// Renders an entity's fields, in schema order when the schema is known.
+    fn render_entity(&self, entity: &Entity) -> String {
+        let mut fields: Vec<&(FieldId, FieldValue)> = entity.fields.iter().collect();
+        if let Some(schema) = self
+            .schemas
+            .iter()
+            .find(|schema| schema.entity_type == entity.entity_type)
+        {
+            fields.sort_by_key(|(field_id, _)| {
+                schema
+                    .fields
+                    .get(field_id)
+                    .map_or(usize::MAX, |field_schema| field_schema.order)
+            });
+        }
+
+        let mut contents = format!("**{}** `{}`\n\n", entity.entity_type, entity.id);
+        for (field_id, value) in fields.iter().take(MAX_FIELDS) {
+            contents.push_str(&format!("- **{}**: {}\n", field_id, render_value(value)));
+        }
+        if fields.len() > MAX_FIELDS {
+            contents.push_str(&format!(
+                "- _{} more field(s)_\n",
+                fields.len() - MAX_FIELDS
+            ));
+        }
+        contents
+    }
+}
+
+/// Renders a field's schema definition.
+fn render_field_schema(
+    entity_type: &str,
+    field_id: &FieldId,
+    field_schema: &FieldSchema,
+) -> String {
+    let mut contents = format!(
+        "**{}**: {} ({})\n\nField of schema `{}`",
+        field_id,
+        field_schema.expected_type(),
+        if field_schema.is_required() {
+            "required"
+        } else {
+            "optional"
+        },
+        entity_type
+    );
+
+    if let Some(allowed_values) = field_schema.allowed_values() {
+        let values: Vec<String> = allowed_values
+            .iter()
+            .map(|value| format!("`{}`", value))
+            .collect();
+        contents.push_str(&format!("\n\nAllowed values: {}", values.join(", ")));
+    }
+    if let Some(format) = field_schema.format() {
+        contents.push_str(&format!("\n\nFormat: `{}`", format));
+    }
+    contents
+}
+
+/// Renders a value on one line, truncating long lists and text.
+fn render_value(value: &FieldValue) -> String {
+    match value {
+        FieldValue::List(items) => {
+            let mut rendered: Vec<String> = items
+                .iter()
+                .take(MAX_LIST_ITEMS)
+                .map(render_value)
+                .collect();
+            if items.len() > MAX_LIST_ITEMS {
+                rendered.push(format!("… {} more", items.len() - MAX_LIST_ITEMS));
+            }
+            format!("[{}]", rendered.join(", "))
+        }
+        _ => truncate(&value.to_string()),
+    }
+}
+
+/// Shortens text to its first line and at most `MAX_VALUE_CHARS` characters.
+pub(super) fn truncate(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or("");
+    let truncated = first_line.chars().count() > MAX_VALUE_CHARS || first_line.len() < text.len();
+
+    let mut shortened: String = first_line.chars().take(MAX_VALUE_CHARS).collect();
+    if truncated {
+        shortened.push('…');
+    }
+    shortened
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_short_text() {
+        assert_eq!(truncate("John Doe"), "John Doe");
+    }
+
+    #[test]
+    fn test_truncate_long_and_multiline_text() {
+        let long = "a".repeat(MAX_VALUE_CHARS + 10);
+        assert_eq!(truncate(&long), format!("{}…", "a".repeat(MAX_VALUE_CHARS)));
+        assert_eq!(truncate("first\nsecond"), "first…");
+    }
+
+    #[test]
+    fn test_render_value_truncates_lists() {
+        let items = (1..=7).map(FieldValue::Integer).collect();
+        assert_eq!(
+            render_value(&FieldValue::List(items)),
+            "[1, 2, 3, 4, 5, … 2 more]"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+/// The name of the files listing paths to leave out of the workspace.
+pub const IGNORE_FILE_NAME: &str = ".firmignore";
+
+/// A single pattern from an ignore file.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// The directory holding the ignore file, which patterns are relative to.
+    base: PathBuf,
+    matcher: GlobMatcher,
+    negated: bool,
+    directory_only: bool,
+}
+
+/// Patterns from `.firmignore` files, matched like `.gitignore` files.
+///
+/// Each line is a glob pattern relative to the directory of its file. Blank
+/// lines and lines starting with `#` are skipped. A pattern without a slash
+/// matches at any depth, one ending in a slash only matches directories, and
+/// a leading `!` re-includes paths ignored by earlier patterns. Later patterns
+/// win, and patterns from nested files come after those of their parents.
+/// Like git, a path can't be re-included when its directory is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Loads the rules that apply to a path in a workspace.
+    ///
+    /// Reads the ignore files of the root and of every directory between the
+    /// root and the path. Missing ignore files are skipped.
+    pub fn for_path(root_path: &Path, path: &Path) -> io::Result<Self> {
+        let mut rules = Self::default();
+        rules.add_ignore_file(root_path)?;
+
+        if let Ok(relative_path) = path.strip_prefix(root_path)
+            && let Some(parent) = relative_path.parent()
+        {
+            let mut directory = root_path.to_path_buf();
+            for component in parent.components() {
+                directory.push(component);
+                rules.add_ignore_file(&directory)?;
+            }
+        }
+
+        Ok(rules)
+    }
+
+    /// Adds the patterns from a directory's ignore file, if it has one.
+    pub fn add_ignore_file(&mut self, directory: &Path) -> io::Result<()> {
+        match fs::read_to_string(directory.join(IGNORE_FILE_NAME)) {
+            Ok(contents) => {
+                self.add_patterns(directory, &contents);
+                Ok(())
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Adds patterns relative to a directory, one per line.
+    ///
+    /// Invalid patterns are logged and skipped.
+    pub fn add_patterns(&mut self, base: &Path, contents: &str) {
+        for line in contents.lines() {
+            let pattern = line.trim_end();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+
+            let (negated, pattern) = match pattern.strip_prefix('!') {
+                Some(pattern) => (true, pattern),
+                None => (false, pattern.strip_prefix('\\').unwrap_or(pattern)),
+            };
+            let (directory_only, pattern) = match pattern.strip_suffix('/') {
+                Some(pattern) => (true, pattern),
+                None => (false, pattern),
+            };
+
+            // Patterns with a slash are relative to the base, others match at any depth
+            let glob = match pattern.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if pattern.contains('/') => pattern.to_string(),
+                None => format!("**/{}", pattern),
+            };
+
+            match GlobBuilder::new(&glob).literal_separator(true).build() {
+                Ok(glob) => self.rules.push(IgnoreRule {
+                    base: base.to_path_buf(),
+                    matcher: glob.compile_matcher(),
+                    negated,
+                    directory_only,
+                }),
+                Err(err) => log::warn!(
+                    "Skipping invalid pattern '{}' in {}: {}",
+                    line,
+                    base.join(IGNORE_FILE_NAME).display(),
+                    err
+                ),
+            }
+        }
+    }
+
+    /// Checks whether a path matches the rules, without looking at its directories.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.directory_only && !is_dir {
+                continue;
+            }
+            let Ok(relative_path) = path.strip_prefix(&rule.base) else {
+                continue;
+            };
+            if rule.matcher.is_match(relative_path) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// Checks whether a path or any of its directories below the root is ignored.
+    pub fn is_ignored_in(&self, root_path: &Path, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative_path) = path.strip_prefix(root_path) else {
+            return false;
+        };
+
+        let mut directory = root_path.to_path_buf();
+        if let Some(parent) = relative_path.parent() {
+            for component in parent.components() {
+                directory.push(component);
+                if self.is_ignored(&directory, true) {
+                    return true;
+                }
+            }
+        }
+
+        self.is_ignored(path, is_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rules(contents: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.add_patterns(Path::new("/ws"), contents);
+        rules
+    }
+
+    #[test]
+    fn test_unanchored_patterns_match_at_any_depth() {
+        let rules = rules("# Drafts\n\n*.draft.firm\n");
+        assert!(rules.is_ignored(Path::new("/ws/a.draft.firm"), false));
+        assert!(rules.is_ignored(Path::new("/ws/deep/er/b.draft.firm"), false));
+        assert!(!rules.is_ignored(Path::new("/ws/a.firm"), false));
+    }
+
+    #[test]
+    fn test_anchored_and_directory_patterns() {
+        let rules = rules("/generated/\ndrafts/old.firm\n");
+        assert!(rules.is_ignored(Path::new("/ws/generated"), true));
+        assert!(!rules.is_ignored(Path::new("/ws/generated"), false));
+        assert!(!rules.is_ignored(Path::new("/ws/nested/generated"), true));
+        assert!(rules.is_ignored(Path::new("/ws/drafts/old.firm"), false));
+        assert!(!rules.is_ignored(Path::new("/ws/other/drafts/old.firm"), false));
+    }
+
+    #[test]
+    fn test_negation_reincludes_files() {
+        let rules = rules("*.firm\n!keep.firm\n");
+        assert!(rules.is_ignored(Path::new("/ws/a.firm"), false));
+        assert!(!rules.is_ignored(Path::new("/ws/keep.firm"), false));
+
+        // Later patterns win
+        let rules = self::rules("!keep.firm\n*.firm\n");
+        assert!(rules.is_ignored(Path::new("/ws/keep.firm"), false));
+    }
+
+    #[test]
+    fn test_ignored_directory_cannot_be_reincluded() {
+        let rules = rules("generated/\n!generated/keep.firm\n");
+        let root = Path::new("/ws");
+        assert!(rules.is_ignored_in(root, Path::new("/ws/generated/keep.firm"), false));
+        assert!(rules.is_ignored_in(root, Path::new("/ws/generated/a/b.firm"), false));
+        assert!(!rules.is_ignored_in(root, Path::new("/ws/main.firm"), false));
+    }
+
+    #[test]
+    fn test_nested_ignore_files_apply_to_their_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join(IGNORE_FILE_NAME), "*.draft.firm\n").unwrap();
+        fs::write(
+            root.join("notes").join(IGNORE_FILE_NAME),
+            "!keep.draft.firm\nscratch.firm\n",
+        )
+        .unwrap();
+
+        let path = root.join("notes").join("keep.draft.firm");
+        let rules = IgnoreRules::for_path(root, &path).unwrap();
+        assert!(!rules.is_ignored_in(root, &path, false));
+        assert!(rules.is_ignored_in(root, &root.join("notes").join("scratch.firm"), false));
+        assert!(rules.is_ignored_in(root, &root.join("notes").join("a.draft.firm"), false));
+
+        // The nested file doesn't apply outside its directory
+        let rules = IgnoreRules::for_path(root, &root.join("scratch.firm")).unwrap();
+        assert!(!rules.is_ignored_in(root, &root.join("scratch.firm"), false));
+    }
+}
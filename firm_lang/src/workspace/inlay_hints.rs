@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use firm_core::{Entity, EntityId, FieldId, compose_entity_id};
+use tree_sitter::{Node, Point};
+
+use super::hover::truncate;
+use super::{HintConfig, WorkspaceBuild};
+use crate::parser::dsl::{ParsedSource, ParsedValue};
+
+const REFERENCE_KIND: &str = "reference";
+
+/// The hint shown after a reference that doesn't resolve.
+pub const DANGLING_REFERENCE_HINT: &str = "(?)";
+
+/// A short label shown after a reference, like the name of the referenced entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlayHint {
+    /// Where the hint goes: the end of the reference.
+    pub position: Point,
+    pub label: String,
+}
+
+impl WorkspaceBuild {
+    /// Finds hints for the references between two positions in a parsed document.
+    ///
+    /// A reference to an entity, or to one of its fields, gets the entity's hint
+    /// field in parentheses, like `(John Doe)`. References that don't resolve
+    /// get `(?)`, and entities without the hint field get no hint. Only syntax
+    /// nodes overlapping the range are visited, so hinting the visible part of
+    /// a large file stays cheap. Hints come from this build, so they should be
+    /// recomputed after the workspace is rebuilt. Positions are zero-based,
+    /// with columns in bytes.
+    pub fn inlay_hints(
+        &self,
+        parsed: &ParsedSource,
+        start: Point,
+        end: Point,
+        config: &HintConfig,
+    ) -> Vec<InlayHint> {
+        let mut references = Vec::new();
+        collect_references(parsed.tree.root_node(), start, end, &mut references);
+        if references.is_empty() {
+            return Vec::new();
+        }
+
+        let entities: HashMap<&EntityId, &Entity> = self
+            .entities
+            .iter()
+            .map(|entity| (&entity.id, entity))
+            .collect();
+
+        references
+            .into_iter()
+            .filter_map(|node| {
+                let (entity_id, field_id) =
+                    match ParsedValue::parse_reference(&parsed.source[node.byte_range()]).ok()? {
+                        ParsedValue::EntityReference {
+                            entity_type,
+                            entity_id,
+                        } => (compose_entity_id(&entity_type, &entity_id), None),
+                        ParsedValue::FieldReference {
+                            entity_type,
+                            entity_id,
+                            field_id,
+                        } => (
+                            compose_entity_id(&entity_type, &entity_id),
+                            Some(FieldId::new(field_id)),
+                        ),
+                        _ => return None,
+                    };
+
+                let label = match entities.get(&entity_id) {
+                    Some(entity)
+                        if field_id
+                            .is_none_or(|field_id| entity.get_field(&field_id).is_some()) =>
+                    {
+                        entity_hint(entity, config)?
+                    }
+                    _ => DANGLING_REFERENCE_HINT.to_string(),
+                };
+
+                Some(InlayHint {
+                    position: node.end_position(),
+                    label,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Renders the hint field of a referenced entity, if it has one.
+fn entity_hint(entity: &Entity, config: &HintConfig) -> Option<String> {
+    let hint_field = FieldId::new(config.field_for(entity.entity_type.as_str()));
+    let value = entity.get_field(&hint_field)?;
+    Some(format!("({})", truncate(&value.to_string())))
+}
+
+/// Collects the reference nodes overlapping a range, skipping subtrees outside it.
+fn collect_references<'tree>(
+    node: Node<'tree>,
+    start: Point,
+    end: Point,
+    references: &mut Vec<Node<'tree>>,
+) {
+    if node.end_position() < start || node.start_position() > end {
+        return;
+    }
+    if node.kind() == REFERENCE_KIND {
+        references.push(node);
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_references(child, start, end, references);
+    }
+}
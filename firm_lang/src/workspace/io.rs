@@ -1,6 +1,15 @@
-use std::{fs, path::{Path, PathBuf}};
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
-use crate::{parser::dsl::parse_source, workspace::WorkspaceFile};
+use rayon::prelude::*;
+
+use crate::{
+    parser::dsl::{ParsedSource, parse_source},
+    workspace::{WorkspaceFile, ignore::IgnoreRules},
+};
 
 use super::{Workspace, WorkspaceError};
 
@@ -8,59 +17,298 @@ const FIRM_FILE_EXTENSION: &str = "firm";
 
 impl Workspace {
     /// Load a single firm source file.
+    ///
+    /// The file is stored by its canonical path, so the same file is only
+    /// loaded once however it's referred to. Open documents keep their
+    /// in-memory source.
     pub fn load_file(
         &mut self,
         path: &PathBuf,
         workspace_path: &PathBuf,
     ) -> Result<(), WorkspaceError> {
-        // Read the source text
-        let text = fs::read_to_string(path).map_err(WorkspaceError::IoError)?;
-
-        // Make the source path relative to the workspace
-        let relative_path = path
-            .strip_prefix(workspace_path)
-            .map_err(|err| WorkspaceError::ParseError(path.clone(), err.to_string()))?;
-
-        // Parse the source text
-        let parsed = parse_source(text.clone(), Some(relative_path.to_path_buf()))
-            .map_err(|err| WorkspaceError::ParseError(path.clone(), err.to_string()))?;
+        let workspace_path = self.set_root_path(workspace_path);
+        let path = fs::canonicalize(path).map_err(WorkspaceError::IoError)?;
+        if self.open_documents.contains(&path) {
+            return Ok(());
+        }
 
-        self.files.insert(path.clone(), WorkspaceFile::new(parsed));
+        let parsed = read_and_parse(&path, &workspace_path)?;
+        self.files.insert(path, WorkspaceFile::new(parsed));
         Ok(())
     }
 
+    /// Replaces the parsed source of a file, like the latest tree of an open document.
+    ///
+    /// Builds and diagnostics use it instead of the file on disk until it's loaded
+    /// again. The path should be absolute, with the workspace root canonicalized.
+    pub fn update_file(&mut self, path: PathBuf, parsed: ParsedSource) {
+        self.files.insert(path, WorkspaceFile::new(parsed));
+    }
+
     /// Loads all firm files in a directory and its subdirectories.
+    ///
+    /// Hidden files and directories (like `.git`) are skipped, like the
+    /// watcher does, and so are paths matching the `.firmignore` files in the
+    /// directory tree and open documents. Files are read and parsed in parallel, then
+    /// added in path order.
+    /// Every failing file is logged, and the error for the first failing path is returned.
     pub fn load_directory(&mut self, directory_path: &PathBuf) -> Result<(), WorkspaceError> {
-        self.load_directory_recursive(directory_path, directory_path)
-    }
+        // Collect from the canonical root so stored paths don't depend on symlinks
+        let directory_path = &self.set_root_path(directory_path);
 
-    /// Load all firm files in a directory recursively.
-    fn load_directory_recursive(
-        &mut self,
-        directory_path: &PathBuf,
-        root_directory_path: &PathBuf,
-    ) -> Result<(), WorkspaceError> {
-        let entries = fs::read_dir(directory_path).map_err(WorkspaceError::IoError)?;
+        let mut paths = Vec::new();
+        collect_firm_files(directory_path, &IgnoreRules::default(), &mut paths)?;
+        paths.retain(|path| !self.open_documents.contains(path));
+        paths.sort();
 
-        for entry in entries {
-            let entry = entry.map_err(WorkspaceError::IoError)?;
-            let path = entry.path();
+        // Each parse creates its own tree-sitter parser, so workers don't share state
+        let results: Vec<Result<ParsedSource, WorkspaceError>> = paths
+            .par_iter()
+            .map(|path| read_and_parse(path, directory_path))
+            .collect();
 
-            if path.is_dir() {
-                self.load_directory_recursive(&path, root_directory_path)?;
-            } else if path.is_file() && self.is_firm_file(&path) {
-                self.load_file(&path, root_directory_path)?;
+        let mut first_error = None;
+        for (path, result) in paths.into_iter().zip(results) {
+            match result {
+                Ok(parsed) => {
+                    self.files.insert(path, WorkspaceFile::new(parsed));
+                }
+                Err(err) => {
+                    log::error!("Failed to load {}: {}", path.display(), err);
+                    first_error.get_or_insert(err);
+                }
             }
         }
 
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Reloads every file from the workspace directory, dropping the ones that are gone.
+    ///
+    /// This rescans from scratch, like after bulk changes outside an editor.
+    /// Open documents keep their in-memory source, but other sources replaced
+    /// with `update_file` are read from disk again. A workspace that wasn't
+    /// loaded from a directory has nothing to reload.
+    pub fn reload(&mut self) -> Result<(), WorkspaceError> {
+        let Some(root_path) = self.root_path.clone() else {
+            return Ok(());
+        };
+
+        let open_documents = &self.open_documents;
+        self.files.retain(|path, _| open_documents.contains(path));
+        self.load_directory(&root_path)
+    }
+
+    /// Reads only the given files from disk again, leaving the others as they are.
+    ///
+    /// Files that are gone, ignored or not firm files are dropped. Open documents
+    /// keep their in-memory source. Relative paths are resolved from the workspace
+    /// root, and a workspace that wasn't loaded from a directory has nothing to reload.
+    pub fn reload_files(&mut self, paths: &[PathBuf]) -> Result<(), WorkspaceError> {
+        let Some(root_path) = self.root_path.clone() else {
+            return Ok(());
+        };
+
+        let updates = read_file_updates(&root_path, paths)?;
+        self.apply_file_updates(updates);
         Ok(())
     }
 
-    /// Returns true if a path has the .firm extension
-    fn is_firm_file(&self, path: &Path) -> bool {
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext == FIRM_FILE_EXTENSION)
-            .unwrap_or(false)
+    /// Applies files read with `read_file_updates`, skipping open documents.
+    pub fn apply_file_updates(&mut self, updates: Vec<FileUpdate>) {
+        for update in updates {
+            if self.open_documents.contains(&update.path) {
+                continue;
+            }
+            match update.parsed {
+                Some(parsed) => {
+                    self.files.insert(update.path, WorkspaceFile::new(parsed));
+                }
+                None => {
+                    self.files.remove(&update.path);
+                }
+            }
+        }
+    }
+
+    /// Canonicalizes a workspace path and records it as the root, if none is set yet.
+    ///
+    /// Paths that can't be canonicalized (like an empty path) are used as given,
+    /// but aren't recorded as the root.
+    fn set_root_path(&mut self, workspace_path: &PathBuf) -> PathBuf {
+        match fs::canonicalize(workspace_path) {
+            Ok(root_path) => {
+                self.root_path.get_or_insert_with(|| root_path.clone());
+                root_path
+            }
+            Err(_) => workspace_path.clone(),
+        }
+    }
+}
+
+/// A file read again from disk, to apply to a workspace.
+#[derive(Debug)]
+pub struct FileUpdate {
+    path: PathBuf,
+    /// The new source, or None if the file should be dropped.
+    parsed: Option<ParsedSource>,
+}
+
+/// Reads and parses changed files without touching a workspace.
+///
+/// This is the reading half of `Workspace::reload_files`, so callers sharing a
+/// workspace can parse before taking it. Files that are gone, ignored or not
+/// firm files become removals. Every failing file is logged, and the error for
+/// the first failing path is returned.
+pub fn read_file_updates(
+    workspace_path: &PathBuf,
+    paths: &[PathBuf],
+) -> Result<Vec<FileUpdate>, WorkspaceError> {
+    read_file_updates_with(workspace_path, paths, |path| fs::read_to_string(path))
+}
+
+/// Like `read_file_updates`, but reads the text of each file with `read`.
+///
+/// Lets callers see or stub what's read from disk, like tests counting reads.
+pub fn read_file_updates_with<R>(
+    workspace_path: &PathBuf,
+    paths: &[PathBuf],
+    read: R,
+) -> Result<Vec<FileUpdate>, WorkspaceError>
+where
+    R: Fn(&Path) -> io::Result<String> + Sync,
+{
+    let root_path = fs::canonicalize(workspace_path).map_err(WorkspaceError::IoError)?;
+
+    let results: Vec<Result<FileUpdate, WorkspaceError>> = paths
+        .par_iter()
+        .map(|path| read_file_update(&root_path, &root_path.join(path), &read))
+        .collect();
+
+    let mut updates = Vec::new();
+    let mut first_error = None;
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok(update) => updates.push(update),
+            Err(err) => {
+                log::error!("Failed to load {}: {}", path.display(), err);
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    match first_error {
+        Some(err) => Err(err),
+        None => Ok(updates),
+    }
+}
+
+/// Reads one changed file, or notes its removal.
+fn read_file_update<R>(
+    root_path: &Path,
+    path: &Path,
+    read: &R,
+) -> Result<FileUpdate, WorkspaceError>
+where
+    R: Fn(&Path) -> io::Result<String>,
+{
+    let Ok(canonical_path) = fs::canonicalize(path) else {
+        // Gone files can't be canonicalized, but their directory usually still can
+        let path = match (path.parent().map(fs::canonicalize), path.file_name()) {
+            (Some(Ok(parent)), Some(file_name)) => parent.join(file_name),
+            _ => path.to_path_buf(),
+        };
+        return Ok(FileUpdate { path, parsed: None });
+    };
+
+    let ignored = match IgnoreRules::for_path(root_path, &canonical_path) {
+        Ok(rules) => rules.is_ignored_in(root_path, &canonical_path, false),
+        Err(err) => return Err(WorkspaceError::IoError(err)),
+    };
+    if !canonical_path.is_file() || !is_firm_file(&canonical_path) || ignored {
+        return Ok(FileUpdate {
+            path: canonical_path,
+            parsed: None,
+        });
+    }
+
+    let text = read(&canonical_path).map_err(WorkspaceError::IoError)?;
+    let parsed = parse_file(text, &canonical_path, root_path)?;
+    Ok(FileUpdate {
+        path: canonical_path,
+        parsed: Some(parsed),
+    })
+}
+
+/// Read and parse a single firm source file.
+pub(super) fn read_and_parse(path: &Path, workspace_path: &Path) -> Result<ParsedSource, WorkspaceError> {
+    // Read the source text
+    let text = fs::read_to_string(path).map_err(WorkspaceError::IoError)?;
+    parse_file(text, path, workspace_path)
+}
+
+/// Parse the text of a firm source file read from the given path.
+fn parse_file(
+    text: String,
+    path: &Path,
+    workspace_path: &Path,
+) -> Result<ParsedSource, WorkspaceError> {
+    // Make the source path relative to the workspace
+    let relative_path = path
+        .strip_prefix(workspace_path)
+        .map_err(|err| WorkspaceError::ParseError(path.to_path_buf(), err.to_string()))?;
+
+    // Parse the source text
+    parse_source(text, Some(relative_path.to_path_buf()))
+        .map_err(|err| WorkspaceError::ParseError(path.to_path_buf(), err.to_string()))
+}
+
+/// Collect all firm files in a directory recursively, skipping hidden and ignored paths.
+///
+/// Each directory's ignore file adds to the rules inherited from its parents.
+fn collect_firm_files(
+    directory_path: &Path,
+    parent_rules: &IgnoreRules,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), WorkspaceError> {
+    let mut rules = parent_rules.clone();
+    rules
+        .add_ignore_file(directory_path)
+        .map_err(WorkspaceError::IoError)?;
+
+    let entries = fs::read_dir(directory_path).map_err(WorkspaceError::IoError)?;
+
+    for entry in entries {
+        let entry = entry.map_err(WorkspaceError::IoError)?;
+        if is_hidden(&entry.file_name()) {
+            continue;
+        }
+        let path = entry.path();
+
+        if path.is_dir() {
+            if !rules.is_ignored(&path, true) {
+                collect_firm_files(&path, &rules, paths)?;
+            }
+        } else if path.is_file() && is_firm_file(&path) && !rules.is_ignored(&path, false) {
+            paths.push(path);
+        }
     }
+
+    Ok(())
+}
+
+/// Returns true for hidden files and directories, whose names start with a dot.
+pub(super) fn is_hidden(name: &OsStr) -> bool {
+    name.to_str().is_some_and(|name| name.starts_with('.'))
+}
+
+/// Returns true if a path has the .firm extension
+pub(super) fn is_firm_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext == FIRM_FILE_EXTENSION)
+        .unwrap_or(false)
 }
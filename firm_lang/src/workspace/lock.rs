@@ -0,0 +1,212 @@
+//! Coordinates writes to a workspace between processes.
+//!
+//! Commands and tools that change source files take an advisory lock file in
+//! the workspace root while they write, so two writers can't interleave. A
+//! writer that edits content it read earlier can also check the file's content
+//! hash, and refuse to write if someone else changed the file in between.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::WorkspaceError;
+
+/// The name of the lock file taken in the workspace root while writing.
+pub const LOCK_FILE_NAME: &str = ".firm.lock";
+
+/// How long to wait for another writer to release the workspace by default.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How old a lock file has to be before it's taken as left behind by a writer that crashed.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(300);
+
+/// How often a held lock is checked for having been released.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An advisory lock on a workspace, released when dropped.
+///
+/// Only writers that take the lock are kept apart: it doesn't stop other
+/// programs from editing the workspace files.
+#[derive(Debug)]
+pub struct WorkspaceLock {
+    path: PathBuf,
+}
+
+impl WorkspaceLock {
+    /// Takes the lock on a workspace, waiting up to the timeout for other writers to finish.
+    ///
+    /// Returns `WorkspaceError::Locked` if the lock is still held when the timeout runs out.
+    pub fn acquire(workspace_path: &Path, timeout: Duration) -> Result<Self, WorkspaceError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(lock) = Self::try_acquire(workspace_path)? {
+                return Ok(lock);
+            }
+            if Instant::now() >= deadline {
+                let path = workspace_path.join(LOCK_FILE_NAME);
+                let holder = fs::read_to_string(&path).unwrap_or_default();
+                return Err(WorkspaceError::Locked(path, holder.trim().to_string()));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Takes the lock on a workspace if no other writer holds it.
+    ///
+    /// A lock file older than a few minutes is left from a writer that didn't
+    /// finish, and is replaced.
+    pub fn try_acquire(workspace_path: &Path) -> Result<Option<Self>, WorkspaceError> {
+        let path = workspace_path.join(LOCK_FILE_NAME);
+        if let Some(lock) = Self::create(&path)? {
+            return Ok(Some(lock));
+        }
+
+        if is_stale(&path) {
+            log::warn!("Replacing stale workspace lock at {}", path.display());
+            let _ = fs::remove_file(&path);
+            // Another writer may have replaced it first
+            return Self::create(&path);
+        }
+        Ok(None)
+    }
+
+    /// Creates the lock file, unless it already exists.
+    fn create(path: &Path) -> Result<Option<Self>, WorkspaceError> {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                // The holder is only informational, so a failed write still holds the lock
+                let _ = writeln!(file, "pid {}", process::id());
+                Ok(Some(Self {
+                    path: path.to_path_buf(),
+                }))
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(WorkspaceError::IoError(e)),
+        }
+    }
+
+    /// The path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for WorkspaceLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Whether a lock file was last written long enough ago to be left behind.
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_LOCK_AGE)
+}
+
+/// Hashes file content, to tell whether a file changed since it was read.
+///
+/// The hash is 64-bit FNV-1a as 16 hex digits, which is stable across
+/// platforms and versions. It's meant for spotting edits, not for security.
+pub fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Checks that a file still has the content a change was derived from.
+///
+/// Returns `WorkspaceError::Conflict` if the file's content hash differs from
+/// the base hash, or if the file no longer exists.
+pub fn check_base_hash(path: &Path, base_hash: &str) -> Result<(), WorkspaceError> {
+    let current_hash = match fs::read_to_string(path) {
+        Ok(content) => content_hash(&content),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(WorkspaceError::Conflict(
+                path.to_path_buf(),
+                "the file no longer exists".to_string(),
+            ));
+        }
+        Err(e) => return Err(WorkspaceError::IoError(e)),
+    };
+
+    if current_hash != base_hash.trim() {
+        return Err(WorkspaceError::Conflict(
+            path.to_path_buf(),
+            format!(
+                "its content hash is {}, not the base hash {}",
+                current_hash,
+                base_hash.trim()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let dir = TempDir::new().unwrap();
+
+        let lock = WorkspaceLock::acquire(dir.path(), DEFAULT_LOCK_TIMEOUT).unwrap();
+        assert!(lock.path().exists());
+        assert!(fs::read_to_string(lock.path()).unwrap().starts_with("pid "));
+        assert!(WorkspaceLock::try_acquire(dir.path()).unwrap().is_none());
+
+        let error = WorkspaceLock::acquire(dir.path(), Duration::from_millis(50)).unwrap_err();
+        assert_matches!(error, WorkspaceError::Locked(_, ref holder) if holder.starts_with("pid "));
+
+        drop(lock);
+        assert!(!dir.path().join(LOCK_FILE_NAME).exists());
+        assert!(WorkspaceLock::try_acquire(dir.path()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_lock_waits_for_release() {
+        let dir = TempDir::new().unwrap();
+        let lock = WorkspaceLock::acquire(dir.path(), DEFAULT_LOCK_TIMEOUT).unwrap();
+
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(lock);
+        });
+        let second = WorkspaceLock::acquire(dir.path(), DEFAULT_LOCK_TIMEOUT);
+        releaser.join().unwrap();
+        assert!(second.is_ok());
+    }
+
+    #[test]
+    fn test_check_base_hash() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("main.firm");
+        fs::write(&path, "person alice {}\n").unwrap();
+
+        let hash = content_hash("person alice {}\n");
+        assert_eq!(hash.len(), 16);
+        assert_ne!(hash, content_hash("person alice { }\n"));
+        assert!(check_base_hash(&path, &hash).is_ok());
+
+        fs::write(&path, "person bob {}\n").unwrap();
+        assert_matches!(
+            check_base_hash(&path, &hash),
+            Err(WorkspaceError::Conflict(..))
+        );
+
+        fs::remove_file(&path).unwrap();
+        assert_matches!(
+            check_base_hash(&path, &hash),
+            Err(WorkspaceError::Conflict(_, message)) if message == "the file no longer exists"
+        );
+    }
+}
@@ -1,21 +1,114 @@
+mod annotations;
 mod build;
+mod completion;
+mod config;
+mod definition;
+mod diagnostics;
+mod document_links;
+mod embedded_query;
+mod entity_source;
+mod fixes;
+mod folding;
+mod generation;
+mod hover;
+mod ignore;
+mod inlay_hints;
 mod io;
+mod lock;
+mod named_queries;
+mod on_type_formatting;
+mod overlay;
+mod path_check;
+mod paths;
+mod positions;
+mod query_fields;
+mod query_files;
+mod query_signature;
+mod references;
+mod rename;
+mod selection;
+mod semantic_tokens;
+mod status;
+mod symbols;
+mod timestamps;
+mod type_definition;
+mod type_filter;
+mod usages;
+mod validation_scripts;
+mod watcher;
 mod workspace_errors;
+mod workspace_warnings;
 
-use std::{collections::HashMap, path::PathBuf};
+use chrono::FixedOffset;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+pub use annotations::{Annotation, AnnotationOwner, DEFAULT_ANNOTATION_MARKERS};
 pub use build::WorkspaceBuild;
+pub use completion::{Completion, CompletionKind};
+pub use config::{
+    AddConfig, CONFIG_FILE_NAME, DEFAULT_ADD_TARGET, DEFAULT_HINT_FIELD, DisplayConfig,
+    HintConfig, TimezoneConfig, WorkspaceConfig,
+};
+pub use definition::Definition;
+pub use diagnostics::{
+    Diagnostic, DiagnosticSeverity, WorkspaceDiagnostics, changed_diagnostic_files,
+};
+pub use document_links::{DocumentLink, DocumentLinkTarget, document_links};
+pub use embedded_query::{EmbeddedQuery, QueryDiagnostic};
+pub use entity_source::EntitySource;
+pub use fixes::{SuggestedFix, TextEdit, apply_edits};
+pub use folding::{FoldingKind, FoldingRange, folding_ranges};
+pub use generation::{Generation, GenerationToken};
+pub use hover::Hover;
+pub use ignore::{IGNORE_FILE_NAME, IgnoreRules};
+pub use inlay_hints::{DANGLING_REFERENCE_HINT, InlayHint};
+pub use io::{FileUpdate, read_file_updates, read_file_updates_with};
+pub use lock::{
+    DEFAULT_LOCK_TIMEOUT, LOCK_FILE_NAME, WorkspaceLock, check_base_hash, content_hash,
+};
+pub use named_queries::NamedQuery;
+pub use on_type_formatting::on_type_formatting;
+pub use path_check::{PathCheck, PathCheckOptions};
+pub use paths::{path_relocation, relative_path, to_forward_slashes};
+pub use query_fields::{QUERY_FIELD_FORMAT, check_query_string};
+pub use query_files::{QUERY_FILE_EXTENSION, query_file_signature_help};
+pub use query_signature::{QuerySignature, query_signature};
+pub use references::{BrokenReference, BrokenReferenceKind, InboundReference, MismatchedReference};
+pub use rename::{RenameError, WorkspaceEdit};
+pub use selection::{SelectionRange, selection_ranges};
+pub use semantic_tokens::{SemanticToken, SemanticTokenModifier, SemanticTokenType};
+pub use status::WorkspaceStatus;
+pub use symbols::{DocumentSymbol, SymbolKind, WorkspaceSymbol, document_symbols};
+pub use type_definition::{DEFAULT_IMPLEMENTATION_LIMIT, Implementations, TypeDefinition};
+pub use type_filter::TypeFilter;
+pub use usages::Location;
+pub use validation_scripts::{DEFAULT_SCRIPT_TIMEOUT, ScriptOptions};
+pub use watcher::{WorkspaceChange, WorkspaceWatcher};
 pub use workspace_errors::WorkspaceError;
+pub use workspace_warnings::WorkspaceWarning;
 
 use crate::parser::dsl::ParsedSource;
+use timestamps::TimestampCache;
 
 /// Represents a collection of files to be processed by Firm.
 ///
 /// Initally, we collect DSL files in the workspace, parsing the source.
 /// Afterwards, the workspace can be "built", converting that to core entities and schemas.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Workspace {
-    files: HashMap<PathBuf, WorkspaceFile>,
+    root_path: Option<PathBuf>,
+    files: BTreeMap<PathBuf, WorkspaceFile>,
+    /// Files whose in-memory source takes precedence over the disk.
+    open_documents: BTreeSet<PathBuf>,
+    timezone: Option<FixedOffset>,
+    type_filter: TypeFilter,
+    validation_scripts: Option<ScriptOptions>,
+    /// Entity timestamps of each file as of the last build
+    timestamps: TimestampCache,
 }
 
 impl Default for Workspace {
@@ -27,35 +120,88 @@ impl Default for Workspace {
 impl Workspace {
     pub fn new() -> Self {
         Self {
-            files: HashMap::new(),
+            root_path: None,
+            files: BTreeMap::new(),
+            open_documents: BTreeSet::new(),
+            timezone: None,
+            type_filter: TypeFilter::new(),
+            validation_scripts: None,
+            timestamps: TimestampCache::default(),
         }
     }
 
+    /// Sets the timezone for dates and datetimes written without one.
+    ///
+    /// Without it, they're in the machine's local timezone when building.
+    pub fn with_timezone(mut self, timezone: Option<FixedOffset>) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Restricts which entity types are built and have their references checked.
+    pub fn with_type_filter(mut self, type_filter: TypeFilter) -> Self {
+        self.type_filter = type_filter;
+        self
+    }
+
+    /// Allows the validation scripts that schemas declare to run when building.
+    ///
+    /// Scripts can run any program, so they're skipped with a warning unless allowed.
+    pub fn with_validation_scripts(mut self, options: Option<ScriptOptions>) -> Self {
+        self.validation_scripts = options;
+        self
+    }
+
     /// Gets the number of files currently in the workspace.
     pub fn num_files(&self) -> usize {
         self.files.len()
     }
 
-    /// Gets all file paths in the workspace.
+    /// Gets all file paths in the workspace, in sorted order.
+    ///
+    /// Paths are absolute, with the workspace root canonicalized.
     pub fn file_paths(&self) -> Vec<&PathBuf> {
         self.files.keys().collect()
     }
 
+    /// Gets every parsed file in the workspace with its path, in sorted path order.
+    pub fn parsed_sources(&self) -> Vec<(&PathBuf, &ParsedSource)> {
+        self.files
+            .iter()
+            .map(|(path, file)| (path, file.parsed.as_ref()))
+            .collect()
+    }
+
+    /// Gets the text of a file as it was last loaded, or as it's open in an editor.
+    pub fn file_source(&self, path: &Path) -> Option<&str> {
+        self.files.get(path).map(|file| file.parsed.source.as_str())
+    }
+
     /// Finds the source file path for an entity by its type and ID.
     ///
-    /// This performs a linear search through all parsed files in the workspace,
-    /// returning the absolute path of the first file containing a matching entity.
-    ///
-    /// Returns None if no matching entity is found.
+    /// Returns the path of the first file containing a matching entity,
+    /// or None if no matching entity is found.
     pub fn find_entity_source(&self, entity_type: &str, entity_id: &str) -> Option<PathBuf> {
+        self.find_entity_sources(entity_type, entity_id)
+            .into_iter()
+            .next()
+            .map(|source| source.path)
+    }
+
+    /// Finds every block in the workspace that contributes to an entity.
+    ///
+    /// This performs a linear search through all parsed files in the workspace.
+    /// Sources are returned in file path order, then in order within each file.
+    pub fn find_entity_sources(&self, entity_type: &str, entity_id: &str) -> Vec<EntitySource> {
+        let mut sources = Vec::new();
         for (path, file) in &self.files {
             for entity in file.parsed.entities() {
                 if entity.entity_type() == Some(entity_type) && entity.id() == Some(entity_id) {
-                    return Some(path.clone());
+                    sources.push(EntitySource::new(path.clone(), entity.range()));
                 }
             }
         }
-        None
+        sources
     }
 
     /// Finds the source file path for a schema by its name.
@@ -65,25 +211,58 @@ impl Workspace {
     ///
     /// Returns None if no matching schema is found.
     pub fn find_schema_source(&self, schema_name: &str) -> Option<PathBuf> {
+        self.find_schema_block(schema_name).map(|source| source.path)
+    }
+
+    /// Finds the block that defines a schema, with where it starts and ends.
+    ///
+    /// Like `find_schema_source`, the first matching schema in file path order is returned.
+    pub fn find_schema_block(&self, schema_name: &str) -> Option<EntitySource> {
         for (path, file) in &self.files {
             for schema in file.parsed.schemas() {
                 if schema.name() == Some(schema_name) {
-                    return Some(path.clone());
+                    return Some(EntitySource::new(path.clone(), schema.range()));
                 }
             }
         }
         None
     }
+
+    /// Gets the IDs of every entity of a type in the workspace, in file path order.
+    ///
+    /// Entities defined across several blocks are listed once per block.
+    pub fn entity_ids(&self, entity_type: &str) -> Vec<String> {
+        self.files
+            .values()
+            .flat_map(|file| file.parsed.entities())
+            .filter(|entity| entity.entity_type() == Some(entity_type))
+            .filter_map(|entity| entity.id().map(str::to_string))
+            .collect()
+    }
+
+    /// Gets the names of every schema in the workspace, in file path order.
+    pub fn schema_names(&self) -> Vec<String> {
+        self.files
+            .values()
+            .flat_map(|file| file.parsed.schemas())
+            .filter_map(|schema| schema.name().map(str::to_string))
+            .collect()
+    }
 }
 
 /// Represents a parsed file in the workspace.
-#[derive(Debug)]
+///
+/// The parsed source is shared between copies, so copying a workspace to
+/// rebuild it doesn't copy the source and tree of every file.
+#[derive(Debug, Clone)]
 pub struct WorkspaceFile {
-    parsed: ParsedSource,
+    parsed: Arc<ParsedSource>,
 }
 
 impl WorkspaceFile {
     pub fn new(parsed: ParsedSource) -> Self {
-        Self { parsed }
+        Self {
+            parsed: Arc::new(parsed),
+        }
     }
 }
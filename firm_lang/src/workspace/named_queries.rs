@@ -0,0 +1,101 @@
+//! Named queries saved in the workspace with `query <name> { query = "..." }` blocks.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use firm_core::EntitySchema;
+use serde::Serialize;
+
+use super::query_fields::check_query_string;
+use super::{Workspace, WorkspaceBuild, WorkspaceError, WorkspaceWarning};
+
+/// A query saved in the workspace under a name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NamedQuery {
+    pub name: String,
+    pub query: String,
+    pub description: Option<String>,
+    /// The file the query is defined in.
+    pub path: PathBuf,
+    /// The 1-based line the query block starts at.
+    pub line: usize,
+}
+
+impl WorkspaceBuild {
+    /// Finds a named query by its name.
+    pub fn named_query(&self, name: &str) -> Option<&NamedQuery> {
+        self.queries.iter().find(|query| query.name == name)
+    }
+}
+
+impl Workspace {
+    /// Collects the named queries defined in the workspace files, sorted by name.
+    ///
+    /// Every query block must set its query as a string, and names must be
+    /// unique. Queries that don't parse or reference unknown fields are kept,
+    /// with a warning, since running them is what shows the problem.
+    pub(super) fn collect_named_queries(
+        &self,
+        schemas: &[EntitySchema],
+        skip_syntax_errors: bool,
+        warnings: &mut Vec<WorkspaceWarning>,
+    ) -> Result<Vec<NamedQuery>, WorkspaceError> {
+        let mut queries: Vec<NamedQuery> = Vec::new();
+        let mut defined_in: HashMap<String, PathBuf> = HashMap::new();
+
+        for (path, file) in &self.files {
+            for parsed_query in file.parsed.queries() {
+                if skip_syntax_errors && parsed_query.has_error() {
+                    continue;
+                }
+                let Some(name) = parsed_query.name() else {
+                    continue;
+                };
+
+                let Some(query) = parsed_query.query() else {
+                    return Err(WorkspaceError::ValidationError(
+                        path.clone(),
+                        format!(
+                            "Query '{}' at line {} needs a 'query' string",
+                            name,
+                            parsed_query.line()
+                        ),
+                    ));
+                };
+                if let Some(other_path) = defined_in.get(name) {
+                    return Err(WorkspaceError::ValidationError(
+                        path.clone(),
+                        format!(
+                            "Query '{}' is already defined in {}",
+                            name,
+                            other_path.display()
+                        ),
+                    ));
+                }
+
+                if let Err(message) = check_query_string(&query, schemas) {
+                    let warning = WorkspaceWarning::InvalidNamedQuery {
+                        path: path.clone(),
+                        name: name.to_string(),
+                        line: parsed_query.line(),
+                        message,
+                    };
+                    log::warn!("{}", warning);
+                    warnings.push(warning);
+                }
+
+                defined_in.insert(name.to_string(), path.clone());
+                queries.push(NamedQuery {
+                    name: name.to_string(),
+                    query,
+                    description: parsed_query.description(),
+                    path: path.clone(),
+                    line: parsed_query.line(),
+                });
+            }
+        }
+
+        queries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(queries)
+    }
+}
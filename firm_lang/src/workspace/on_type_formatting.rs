@@ -0,0 +1,134 @@
+use tree_sitter::{Node, Point, Range};
+
+use super::TextEdit;
+use super::positions::line_start_offset;
+use crate::generate::generator_options::IndentStyle;
+use crate::parser::dsl::ParsedSource;
+
+const OPENING_BRACKETS: [&str; 2] = ["{", "["];
+const CLOSING_BRACKETS: [&str; 2] = ["}", "]"];
+const UNFORMATTED_KINDS: [&str; 2] = ["string", "comment"];
+
+/// Re-indents the cursor's line after a character is typed.
+///
+/// The position is just after the typed character, zero-based with the
+/// column in bytes, like tree-sitter points. Handles:
+/// - A newline: the new line is indented one level per enclosing block or
+///   list, or one level less when it starts with a closing bracket
+/// - `}` or `]` starting a line: the bracket is aligned with its opening line
+///
+/// Levels come from the brackets in the syntax tree, so brackets in strings
+/// and comments don't count, and half-typed blocks still indent. Nothing is
+/// changed inside a multi-line string or comment.
+pub fn on_type_formatting(
+    parsed: &ParsedSource,
+    position: Point,
+    typed: char,
+    indent_style: &IndentStyle,
+) -> Vec<TextEdit> {
+    let source = parsed.source.as_str();
+    let Some(line_start) = line_start_offset(source, position.row) else {
+        return Vec::new();
+    };
+    let line = &source[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let offset = line_start + position.column;
+    if position.column > line.len() || inside_unformatted(parsed.tree.root_node(), offset) {
+        return Vec::new();
+    }
+
+    let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    let rest = &line[indent_len..];
+
+    let level = match typed {
+        '\n' => {
+            let level = bracket_depth(parsed.tree.root_node(), offset);
+            if rest.starts_with(['}', ']']) {
+                level.saturating_sub(1)
+            } else {
+                level
+            }
+        }
+        '}' | ']' => {
+            // Only a bracket that starts its line is re-aligned
+            if !rest.starts_with(typed) || line_start + indent_len + 1 != offset {
+                return Vec::new();
+            }
+            bracket_depth(parsed.tree.root_node(), offset - 1).saturating_sub(1)
+        }
+        _ => return Vec::new(),
+    };
+
+    let indent = indent_style.indent_string(level);
+    if line[..indent_len] == indent {
+        return Vec::new();
+    }
+
+    vec![TextEdit {
+        range: Range {
+            start_byte: line_start,
+            end_byte: line_start + indent_len,
+            start_point: Point {
+                row: position.row,
+                column: 0,
+            },
+            end_point: Point {
+                row: position.row,
+                column: indent_len,
+            },
+        },
+        replacement: indent,
+    }]
+}
+
+/// Counts the brackets opened and not yet closed before an offset.
+///
+/// Brackets the parser inserted to recover from errors are skipped, since
+/// they aren't in the source.
+fn bracket_depth(root: Node, offset: usize) -> usize {
+    let mut depth: usize = 0;
+    let mut cursor = root.walk();
+    let mut visited_children = false;
+
+    loop {
+        let node = cursor.node();
+        if !visited_children {
+            if node.start_byte() >= offset {
+                visited_children = true;
+                continue;
+            }
+            if node.child_count() == 0 && !node.is_missing() {
+                if OPENING_BRACKETS.contains(&node.kind()) {
+                    depth += 1;
+                } else if CLOSING_BRACKETS.contains(&node.kind()) {
+                    depth = depth.saturating_sub(1);
+                }
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            visited_children = true;
+        }
+
+        if cursor.goto_next_sibling() {
+            visited_children = false;
+        } else if !cursor.goto_parent() {
+            return depth;
+        }
+    }
+}
+
+/// Checks if an offset is inside a string or comment.
+fn inside_unformatted(root: Node, offset: usize) -> bool {
+    let mut current = root.descendant_for_byte_range(offset, offset);
+    while let Some(node) = current {
+        if UNFORMATTED_KINDS.contains(&node.kind())
+            && node.start_byte() < offset
+            && offset < node.end_byte()
+        {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
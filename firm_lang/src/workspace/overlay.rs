@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::io::read_and_parse;
+use super::{Workspace, WorkspaceError, WorkspaceFile};
+use crate::parser::dsl::parse_source;
+
+impl Workspace {
+    /// Uses in-memory sources for files, like the unsaved buffers of an editor.
+    ///
+    /// Each source is opened as a document, see `open_document`. Use it after
+    /// loading the workspace directory, so the documents' paths are resolved
+    /// from its root.
+    pub fn with_overlay(
+        mut self,
+        overlay: HashMap<PathBuf, String>,
+    ) -> Result<Self, WorkspaceError> {
+        for (path, source) in overlay {
+            self.open_document(path, source)?;
+        }
+        Ok(self)
+    }
+
+    /// Opens a document, or updates its source.
+    ///
+    /// Until it's closed, builds and diagnostics use this source instead of the
+    /// file on disk, and loading or reloading from disk leaves it alone. The file
+    /// doesn't have to exist yet. The path should be absolute, with the workspace
+    /// root canonicalized.
+    pub fn open_document(&mut self, path: PathBuf, source: String) -> Result<(), WorkspaceError> {
+        let relative_path = self
+            .root_path
+            .as_ref()
+            .and_then(|root_path| path.strip_prefix(root_path).ok())
+            .map(Path::to_path_buf);
+        let parsed = parse_source(source, relative_path)
+            .map_err(|err| WorkspaceError::ParseError(path.clone(), err.to_string()))?;
+
+        self.files.insert(path.clone(), WorkspaceFile::new(parsed));
+        self.open_documents.insert(path);
+        Ok(())
+    }
+
+    /// Closes a document, going back to the file on disk.
+    ///
+    /// A document without a file on disk is removed from the workspace.
+    pub fn close_document(&mut self, path: &Path) -> Result<(), WorkspaceError> {
+        if !self.open_documents.remove(path) {
+            return Ok(());
+        }
+
+        match &self.root_path {
+            Some(root_path) if path.is_file() => {
+                let parsed = read_and_parse(path, root_path)?;
+                self.files
+                    .insert(path.to_path_buf(), WorkspaceFile::new(parsed));
+            }
+            _ => {
+                self.files.remove(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether a file is an open document.
+    pub fn is_document_open(&self, path: &Path) -> bool {
+        self.open_documents.contains(path)
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use firm_core::compose_entity_id;
+use path_clean::PathClean;
+
+use super::{Workspace, WorkspaceWarning};
+use crate::parser::dsl::ParsedValue;
+
+/// Options for checking that path fields point at existing files.
+#[derive(Debug, Clone, Default)]
+pub struct PathCheckOptions {
+    /// Also check paths that resolve outside the workspace root.
+    pub allow_external_paths: bool,
+}
+
+/// The outcome of checking the path fields in a workspace.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathCheck {
+    /// How many path values were checked on disk.
+    pub checked: usize,
+    /// How many path values were skipped because they point outside the workspace.
+    pub skipped_external: usize,
+    /// A warning for each path that points at a missing file.
+    pub warnings: Vec<WorkspaceWarning>,
+}
+
+impl Workspace {
+    /// Checks that path field values point at files that exist.
+    ///
+    /// Paths are resolved relative to the workspace root, like they're stored
+    /// after parsing. Paths outside the root aren't touched unless
+    /// `allow_external_paths` is set. This reads the filesystem, so it's kept
+    /// out of `build` and only runs when asked for. Returns an empty check if
+    /// no directory has been loaded.
+    pub fn check_paths(&self, options: &PathCheckOptions) -> PathCheck {
+        let mut check = PathCheck::default();
+        let Some(root_path) = self.root_path() else {
+            return check;
+        };
+
+        for (path, file) in &self.files {
+            for parsed_entity in file.parsed.entities() {
+                let (Some(entity_type), Some(id)) =
+                    (parsed_entity.entity_type(), parsed_entity.id())
+                else {
+                    continue;
+                };
+
+                for parsed_field in parsed_entity.fields() {
+                    let (Some(field_name), Ok(value)) = (parsed_field.id(), parsed_field.value())
+                    else {
+                        continue;
+                    };
+
+                    let mut targets = Vec::new();
+                    collect_paths(&value, &mut targets);
+
+                    for target in targets {
+                        let resolved_path = resolve(root_path, &target);
+                        if !resolved_path.starts_with(root_path) && !options.allow_external_paths {
+                            check.skipped_external += 1;
+                            continue;
+                        }
+
+                        check.checked += 1;
+                        if !resolved_path.exists() {
+                            // Point at the value, which may be on a later line than the field name
+                            let line = parsed_field
+                                .value_range()
+                                .map_or(parsed_field.line(), |range| range.start_point.row + 1);
+
+                            check.warnings.push(WorkspaceWarning::MissingPath {
+                                path: path.clone(),
+                                entity_id: compose_entity_id(entity_type, id),
+                                field: field_name.to_string(),
+                                line,
+                                target,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        check
+    }
+}
+
+/// Collects the path values in a parsed value, including inside lists.
+fn collect_paths(value: &ParsedValue, paths: &mut Vec<PathBuf>) {
+    match value {
+        ParsedValue::Path(path) => paths.push(path.clone()),
+        ParsedValue::List(items) => {
+            for item in items {
+                collect_paths(item, paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolves a workspace-relative path value against the workspace root.
+pub(super) fn resolve(root_path: &Path, target: &Path) -> PathBuf {
+    if target.is_absolute() {
+        target.clean()
+    } else {
+        root_path.join(target).clean()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_relative_path() {
+        assert_eq!(
+            resolve(Path::new("/workspace"), Path::new("./assets/logo.png")),
+            PathBuf::from("/workspace/assets/logo.png")
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_outside_root() {
+        let resolved = resolve(Path::new("/workspace"), Path::new("../shared/logo.png"));
+        assert_eq!(resolved, PathBuf::from("/shared/logo.png"));
+        assert!(!resolved.starts_with("/workspace"));
+    }
+
+    #[test]
+    fn test_collect_paths_in_lists() {
+        let value = ParsedValue::List(vec![
+            ParsedValue::Path(PathBuf::from("./a.txt")),
+            ParsedValue::String("not a path".to_string()),
+            ParsedValue::Path(PathBuf::from("./b.txt")),
+        ]);
+
+        let mut paths = Vec::new();
+        collect_paths(&value, &mut paths);
+        assert_eq!(paths, vec![PathBuf::from("./a.txt"), PathBuf::from("./b.txt")]);
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::Workspace;
+
+impl Workspace {
+    /// Gets the canonical root directory of the workspace, once files are loaded.
+    pub fn root_path(&self) -> Option<&Path> {
+        self.root_path.as_deref()
+    }
+
+    /// Converts a path in the workspace to a workspace-relative path with forward slashes.
+    ///
+    /// Accepts both the canonical paths stored in the workspace and paths
+    /// through a symlinked root. Returns None if no files have been loaded
+    /// or the path is outside the workspace.
+    pub fn relative_path(&self, path: &Path) -> Option<String> {
+        relative_path(self.root_path()?, path)
+    }
+}
+
+/// Converts a path under a root directory to a relative path with forward slashes.
+///
+/// If the path doesn't start with the root as given, both are canonicalized
+/// and compared again, so symlinked roots and canonical paths agree.
+/// Removed files are matched through their parent directory.
+/// Returns None if the path is outside the root.
+pub fn relative_path(root_path: &Path, path: &Path) -> Option<String> {
+    if let Ok(relative) = path.strip_prefix(root_path) {
+        return Some(to_forward_slashes(relative));
+    }
+
+    let canonical_root = root_path.canonicalize().ok()?;
+    let canonical_path = canonicalize_lenient(path)?;
+    canonical_path
+        .strip_prefix(&canonical_root)
+        .ok()
+        .map(to_forward_slashes)
+}
+
+/// Joins the normal components of a relative path with forward slashes.
+///
+/// Components are split by the platform, so Windows paths with mixed
+/// separators produce the same result as paths built from components.
+pub fn to_forward_slashes(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Makes paths relative to the workspace root relative to a target file instead.
+///
+/// Paths in the graph are relative to the workspace root, while paths written
+/// to a file are relative to that file, so values copied or written to a file
+/// are mapped with this. Absolute paths are kept as they are.
+pub fn path_relocation<'a>(
+    workspace_path: &'a Path,
+    target_file_path: &'a Path,
+) -> impl Fn(&Path) -> PathBuf + 'a {
+    let target_dir = target_file_path.parent().unwrap_or(Path::new(""));
+    move |path_from_root: &Path| {
+        if path_from_root.is_absolute() {
+            return path_from_root.to_path_buf();
+        }
+        let path = workspace_path.join(path_from_root);
+        pathdiff::diff_paths(&path, target_dir).unwrap_or(path)
+    }
+}
+
+/// Canonicalizes a path, falling back to its parent directory for files that don't exist.
+pub(super) fn canonicalize_lenient(path: &Path) -> Option<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Some(canonical);
+    }
+
+    let parent = path.parent()?.canonicalize().ok()?;
+    Some(parent.join(path.file_name()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_forward_slashes_from_components() {
+        let path: PathBuf = ["core", "people", "john.firm"].iter().collect();
+        assert_eq!(to_forward_slashes(&path), "core/people/john.firm");
+    }
+
+    #[test]
+    fn test_to_forward_slashes_skips_current_dir() {
+        assert_eq!(
+            to_forward_slashes(Path::new("./core/people.firm")),
+            "core/people.firm"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_to_forward_slashes_with_mixed_separators() {
+        assert_eq!(
+            to_forward_slashes(Path::new(r"core\people/john.firm")),
+            "core/people/john.firm"
+        );
+    }
+
+    #[test]
+    fn test_path_relocation() {
+        let root: PathBuf = ["/", "workspace"].iter().collect();
+        let target = root.join("people").join("john.firm");
+        let relocate = path_relocation(&root, &target);
+
+        let from_root: PathBuf = ["docs", "cv.pdf"].iter().collect();
+        let expected: PathBuf = ["..", "docs", "cv.pdf"].iter().collect();
+        assert_eq!(relocate(&from_root), expected);
+
+        let absolute: PathBuf = ["/", "other", "cv.pdf"].iter().collect();
+        assert_eq!(relocate(&absolute), absolute);
+    }
+
+    #[test]
+    fn test_relative_path_outside_root() {
+        assert_eq!(
+            relative_path(Path::new("/workspace"), Path::new("/other/people.firm")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_relative_path_under_root() {
+        let root: PathBuf = ["/", "workspace"].iter().collect();
+        let path = root.join("core").join("people.firm");
+        assert_eq!(
+            relative_path(&root, &path),
+            Some("core/people.firm".to_string())
+        );
+    }
+}
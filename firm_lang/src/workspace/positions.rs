@@ -0,0 +1,123 @@
+use tree_sitter::{Point, Range};
+
+/// Finds the byte offset a zero-based row starts at.
+pub(super) fn line_start_offset(text: &str, row: usize) -> Option<usize> {
+    if row == 0 {
+        return Some(0);
+    }
+    text.match_indices('\n')
+        .nth(row - 1)
+        .map(|(index, _)| index + 1)
+}
+
+/// Turns a byte offset in text into a position, with columns in bytes.
+///
+/// Offsets past the end map to the end of the text.
+pub(super) fn position_at(text: &str, offset: usize) -> Point {
+    point_after(Point::new(0, 0), &text[..offset.min(text.len())])
+}
+
+/// Turns a position into a byte offset in text, if its row is in the text.
+///
+/// Columns past the end of their line map to the end of the line.
+pub(super) fn offset_at(text: &str, position: Point) -> Option<usize> {
+    let line_start = line_start_offset(text, position.row)?;
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |index| line_start + index);
+
+    let offset = (line_start + position.column).min(line_end);
+    text.is_char_boundary(offset).then_some(offset)
+}
+
+/// Gets the position after some text starting at a position.
+pub(super) fn point_after(start: Point, text: &str) -> Point {
+    match text.rfind('\n') {
+        Some(newline) => Point {
+            row: start.row + text.matches('\n').count(),
+            column: text.len() - newline - 1,
+        },
+        None => Point {
+            row: start.row,
+            column: start.column + text.len(),
+        },
+    }
+}
+
+/// Narrows a range to the bytes between two offsets into its text.
+pub(super) fn sub_range(range: &Range, text: &str, start: usize, end: usize) -> Range {
+    Range {
+        start_byte: range.start_byte + start,
+        end_byte: range.start_byte + end,
+        start_point: point_after(range.start_point, &text[..start]),
+        end_point: point_after(range.start_point, &text[..end]),
+    }
+}
+
+/// Creates an empty range at a byte offset, for inserting text.
+pub(super) fn empty_range(byte: usize, point: Point) -> Range {
+    Range {
+        start_byte: byte,
+        end_byte: byte,
+        start_point: point,
+        end_point: point,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_start_offset() {
+        let source = "a {\n    b\n}";
+        assert_eq!(line_start_offset(source, 0), Some(0));
+        assert_eq!(line_start_offset(source, 1), Some(4));
+        assert_eq!(line_start_offset(source, 2), Some(10));
+        assert_eq!(line_start_offset(source, 3), None);
+    }
+
+    #[test]
+    fn test_offsets_map_to_positions() {
+        let query = "from task\n| limit 5";
+
+        assert_eq!(position_at(query, 0), Point { row: 0, column: 0 });
+        assert_eq!(position_at(query, 12), Point { row: 1, column: 2 });
+        assert_eq!(position_at(query, 40), Point { row: 1, column: 9 });
+        assert_eq!(offset_at(query, Point { row: 1, column: 2 }), Some(12));
+        assert_eq!(offset_at(query, Point { row: 0, column: 40 }), Some(9));
+        assert_eq!(offset_at(query, Point { row: 3, column: 0 }), None);
+    }
+
+    #[test]
+    fn test_point_after() {
+        let start = Point { row: 3, column: 10 };
+        assert_eq!(point_after(start, "abc"), Point { row: 3, column: 13 });
+        assert_eq!(point_after(start, "abc\nde"), Point { row: 4, column: 2 });
+    }
+
+    #[test]
+    fn test_empty_range_points() {
+        let source = "first\nsecond\nthird";
+        let range = empty_range(15, position_at(source, 15));
+
+        assert_eq!(range.start_point, Point::new(2, 2));
+        assert_eq!(range.start_byte, range.end_byte);
+    }
+
+    #[test]
+    fn test_sub_range_across_lines() {
+        let text = "\"see\nhttps://x.io\"";
+        let range = Range {
+            start_byte: 20,
+            end_byte: 20 + text.len(),
+            start_point: Point::new(2, 8),
+            end_point: Point::new(3, 13),
+        };
+
+        let url = sub_range(&range, text, 5, 17);
+        assert_eq!((url.start_byte, url.end_byte), (25, 37));
+        assert_eq!(url.start_point, Point::new(3, 0));
+        assert_eq!(url.end_point, Point::new(3, 12));
+    }
+}
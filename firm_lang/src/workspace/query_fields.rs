@@ -0,0 +1,20 @@
+use firm_core::EntitySchema;
+use firm_core::graph::Query;
+
+use crate::parser::query::parse_query;
+
+/// The schema field format marking string fields that hold a Firm query.
+pub const QUERY_FIELD_FORMAT: &str = "firm-query";
+
+/// Checks that a query string parses, converts and only references known fields.
+///
+/// Returns a human-readable description of the first problem found.
+pub fn check_query_string(query: &str, schemas: &[EntitySchema]) -> Result<(), String> {
+    let parsed_query = parse_query(query).map_err(|e| format!("failed to parse: {}", e))?;
+
+    let query: Query = parsed_query
+        .try_into()
+        .map_err(|e| format!("failed to convert: {}", e))?;
+
+    query.validate(schemas).map_err(|e| e.to_string())
+}
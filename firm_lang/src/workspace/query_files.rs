@@ -0,0 +1,44 @@
+use tree_sitter::Point;
+
+use super::WorkspaceBuild;
+use super::completion::Completion;
+use super::embedded_query::QueryDiagnostic;
+use super::positions::{offset_at, position_at};
+use super::query_fields::check_query_string;
+use super::query_signature::{QuerySignature, query_signature};
+use crate::parser::query::syntax_error_offset;
+
+/// The extension of files holding a single query.
+pub const QUERY_FILE_EXTENSION: &str = "firmq";
+
+impl WorkspaceBuild {
+    /// Checks a query file, the whole text of which is one query.
+    ///
+    /// Like `query_diagnostics`, syntax errors point at where the query stops
+    /// parsing, and other problems at the start of the file.
+    pub fn query_file_diagnostics(&self, query: &str) -> Vec<QueryDiagnostic> {
+        let Err(message) = check_query_string(query, &self.schemas) else {
+            return Vec::new();
+        };
+        let offset = syntax_error_offset(query).unwrap_or(0);
+        vec![QueryDiagnostic {
+            position: position_at(query, offset),
+            message,
+        }]
+    }
+
+    /// Suggests completions at a position in a query file.
+    ///
+    /// Suggestions are the same as inside embedded queries (see `completions`).
+    pub fn query_file_completions(&self, query: &str, position: Point) -> Vec<Completion> {
+        match offset_at(query, position) {
+            Some(offset) => self.query_completions(&query[..offset]),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Finds the signature of the clause at a position in a query file.
+pub fn query_file_signature_help(query: &str, position: Point) -> Option<QuerySignature> {
+    query_signature(&query[..offset_at(query, position)?])
+}
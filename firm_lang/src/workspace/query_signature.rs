@@ -0,0 +1,214 @@
+use tree_sitter::Point;
+
+use super::WorkspaceBuild;
+use crate::parser::dsl::parse_source;
+
+const CONDITION_KEYWORDS: [&str; 2] = ["and", "or"];
+
+/// The syntax of each query clause: its keyword, label, parameters and documentation.
+const SIGNATURES: [(&str, &str, &[&str], &str); 11] = [
+    (
+        "from",
+        "from <type>",
+        &["<type>"],
+        "Selects the entities of a type, a type and its subtypes with `+`, or every type with `*`.",
+    ),
+    (
+        "where",
+        "where <field> <operator> <value>",
+        &["<field>", "<operator>", "<value>"],
+        "Keeps the entities matching a condition. Combine conditions with `and` or `or`, but not both.",
+    ),
+    (
+        "related",
+        "related(<degrees>) <type>",
+        &["<degrees>", "<type>"],
+        "Replaces the entities with the ones they reference or are referenced by. Both the degrees (default 1) and the type are optional, and `[task, project]` keeps several types.",
+    ),
+    (
+        "order",
+        "order <field> <direction>",
+        &["<field>", "<direction>"],
+        "Sorts the entities by a field, `asc` (default) or `desc`.",
+    ),
+    (
+        "limit",
+        "limit <count>",
+        &["<count>"],
+        "Keeps the first entities, or groups after a grouped aggregation.",
+    ),
+    (
+        "group",
+        "group <field>",
+        &["<field>"],
+        "Groups the entities by a field's value, right before `count`, `sum`, `average` or `median`.",
+    ),
+    (
+        "select",
+        "select <field>, ...",
+        &["<field>, ..."],
+        "Returns the values of one or more fields instead of the entities.",
+    ),
+    (
+        "count",
+        "count <field>",
+        &["<field>"],
+        "Counts the entities, or the ones that have a field.",
+    ),
+    (
+        "sum",
+        "sum <field>",
+        &["<field>"],
+        "Adds up an integer, float or currency field.",
+    ),
+    (
+        "average",
+        "average <field>",
+        &["<field>"],
+        "Takes the mean of a numeric field, skipping entities without it.",
+    ),
+    (
+        "median",
+        "median <field>",
+        &["<field>"],
+        "Takes the median of a numeric field, skipping entities without it.",
+    ),
+];
+
+/// The syntax of the query clause at the cursor, for signature help.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuerySignature {
+    /// The clause's syntax, like `order <field> <direction>`.
+    pub label: String,
+    pub documentation: String,
+    /// The parameters, as they're written in the label.
+    pub parameters: Vec<String>,
+    /// The index of the parameter being typed.
+    pub active_parameter: usize,
+}
+
+/// Finds the signature of the clause being typed, given the query text before the cursor.
+///
+/// The clause's keyword has to be complete, followed by a space or `(`.
+pub fn query_signature(before_cursor: &str) -> Option<QuerySignature> {
+    let segment = before_cursor
+        .rsplit_once('|')
+        .map_or(before_cursor, |(_, segment)| segment)
+        .trim_start();
+    let keyword_end = segment.find(|c: char| c.is_whitespace() || c == '(')?;
+    let (keyword, rest) = segment.split_at(keyword_end);
+    let (_, label, parameters, documentation) =
+        SIGNATURES.iter().find(|(name, ..)| *name == keyword)?;
+
+    Some(QuerySignature {
+        label: label.to_string(),
+        documentation: documentation.to_string(),
+        parameters: parameters.iter().map(|p| p.to_string()).collect(),
+        active_parameter: active_parameter(keyword, rest).min(parameters.len() - 1),
+    })
+}
+
+impl WorkspaceBuild {
+    /// Finds the signature of the query clause at a position in a source document.
+    ///
+    /// Only queries held in string fields are covered (see `embedded_queries`).
+    /// The position is zero-based, with the column in bytes.
+    pub fn query_signature_help(&self, source: &str, position: Point) -> Option<QuerySignature> {
+        let parsed = parse_source(source.to_string(), None).ok()?;
+        self.embedded_queries(&parsed)
+            .into_iter()
+            .find_map(|embedded| {
+                let offset = embedded.offset_at(position)?;
+                query_signature(&embedded.query[..offset])
+            })
+    }
+}
+
+/// Finds which parameter of a clause is being typed, from the text after its keyword.
+fn active_parameter(keyword: &str, rest: &str) -> usize {
+    match keyword {
+        // The degrees are inside the parentheses, the type after them
+        "related" if rest.starts_with('(') && !rest.contains(')') => 0,
+        "related" => 1,
+        "select" => 0,
+        "where" => {
+            // Each condition starts over after `and` or `or`
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let condition_start = words
+                .iter()
+                .rposition(|word| CONDITION_KEYWORDS.contains(word))
+                .map_or(0, |index| index + 1);
+            typed_words(&words[condition_start..], rest)
+        }
+        _ => {
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            typed_words(&words, rest)
+        }
+    }
+}
+
+/// Counts the complete words, so the word at the cursor is the next parameter.
+fn typed_words(words: &[&str], rest: &str) -> usize {
+    if rest.ends_with(char::is_whitespace) {
+        words.len()
+    } else {
+        words.len().saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(before_cursor: &str) -> Option<(String, usize)> {
+        query_signature(before_cursor).map(|s| (s.label, s.active_parameter))
+    }
+
+    #[test]
+    fn test_query_signature_follows_the_clause() {
+        assert_eq!(active("fro"), None);
+        assert_eq!(active("from ta"), Some(("from <type>".to_string(), 0)));
+        assert_eq!(
+            active("from task | where "),
+            Some(("where <field> <operator> <value>".to_string(), 0))
+        );
+        assert_eq!(
+            active("from task | where name =="),
+            Some(("where <field> <operator> <value>".to_string(), 1))
+        );
+        assert_eq!(
+            active("from task | where name == \"Jane Doe\" and is_done "),
+            Some(("where <field> <operator> <value>".to_string(), 1))
+        );
+        assert_eq!(
+            active("from task | order due_date "),
+            Some(("order <field> <direction>".to_string(), 1))
+        );
+        assert_eq!(
+            active("from task | select name, due_date, "),
+            Some(("select <field>, ...".to_string(), 0))
+        );
+    }
+
+    #[test]
+    fn test_query_signature_for_related() {
+        assert_eq!(
+            active("from task | related("),
+            Some(("related(<degrees>) <type>".to_string(), 0))
+        );
+        assert_eq!(
+            active("from task | related(2) "),
+            Some(("related(<degrees>) <type>".to_string(), 1))
+        );
+        assert_eq!(
+            active("from task | related pe"),
+            Some(("related(<degrees>) <type>".to_string(), 1))
+        );
+    }
+
+    #[test]
+    fn test_query_signature_needs_a_known_keyword() {
+        assert_eq!(active("from task | wh"), None);
+        assert_eq!(active("from task | frobnicate x"), None);
+    }
+}
@@ -0,0 +1,362 @@
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use firm_core::{
+    Entity, EntityId, EntitySchema, EntityType, FieldId, FieldValue, ReferenceValue,
+    compose_entity_id, decompose_entity_id,
+};
+
+use super::{Workspace, WorkspaceBuild};
+
+/// Why a reference can't be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrokenReferenceKind {
+    /// The referenced entity doesn't exist.
+    MissingEntity,
+    /// The referenced entity exists, but doesn't have the referenced field.
+    MissingField,
+}
+
+/// A reference in a field value that points at something that doesn't exist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenReference {
+    pub path: PathBuf,
+    pub line: usize,
+    pub entity_id: EntityId,
+    pub field: FieldId,
+    pub reference: ReferenceValue,
+    pub kind: BrokenReferenceKind,
+}
+
+impl BrokenReference {
+    /// Describes the broken reference, without its location.
+    pub fn message(&self) -> String {
+        let problem = match self.kind {
+            BrokenReferenceKind::MissingEntity => "an entity that doesn't exist",
+            BrokenReferenceKind::MissingField => "a field that doesn't exist",
+        };
+
+        format!(
+            "field '{}' of entity '{}' references {}: '{}'",
+            self.field, self.entity_id, problem, self.reference
+        )
+    }
+}
+
+impl fmt::Display for BrokenReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.message())
+    }
+}
+
+/// A reference to an entity of another type than its field should point at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MismatchedReference {
+    pub path: PathBuf,
+    pub line: usize,
+    pub entity_id: EntityId,
+    pub field: FieldId,
+    pub reference: ReferenceValue,
+    /// The type the field should point at.
+    pub expected_type: EntityType,
+    /// Whether the expected type was inferred from the field name, rather than
+    /// declared as the field's `target` in the schema.
+    pub inferred: bool,
+}
+
+impl MismatchedReference {
+    /// Describes the mismatch, without its location.
+    pub fn message(&self) -> String {
+        let (actual_type, _) = decompose_entity_id(target_id(&self.reference).as_str());
+        let source = if self.inferred {
+            "inferred from the field name"
+        } else {
+            "the schema's target"
+        };
+
+        format!(
+            "field '{}' of entity '{}' references a '{}' where a '{}' is expected ({}): '{}'",
+            self.field, self.entity_id, actual_type, self.expected_type, source, self.reference
+        )
+    }
+}
+
+impl fmt::Display for MismatchedReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.message()
+        )
+    }
+}
+
+/// A field value that references an entity, or one of its fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboundReference {
+    pub path: PathBuf,
+    pub line: usize,
+    /// The entity holding the reference.
+    pub entity_id: EntityId,
+    pub field: FieldId,
+    pub reference: ReferenceValue,
+}
+
+impl fmt::Display for InboundReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: field '{}' of entity '{}' references '{}'",
+            self.path.display(),
+            self.line,
+            self.field,
+            self.entity_id,
+            self.reference
+        )
+    }
+}
+
+impl Workspace {
+    /// Finds every reference that doesn't resolve against the built entities.
+    ///
+    /// References are resolved against `build`, while source locations come
+    /// from the loaded files, so both should come from the same workspace.
+    /// Results are ordered by file path, then by position in the file.
+    ///
+    /// With a type filter, only references from included types are checked,
+    /// and references to excluded types are skipped rather than reported,
+    /// since their targets weren't built. So are references to entities that a
+    /// build skipping syntax errors left out.
+    pub fn check_references(&self, build: &WorkspaceBuild) -> Vec<BrokenReference> {
+        let entities: HashMap<&EntityId, &Entity> =
+            build.entities.iter().map(|e| (&e.id, e)).collect();
+
+        self.field_references()
+            .into_iter()
+            .filter(|site| self.is_checked(site))
+            // Targets in blocks with syntax errors exist, they just weren't built
+            .filter(|site| !build.is_skipped(target_id(&site.reference)))
+            .filter_map(|site| {
+                let kind = resolve(&entities, &site.reference)?;
+                Some(BrokenReference {
+                    path: site.path,
+                    line: site.line,
+                    entity_id: site.entity_id,
+                    field: site.field,
+                    reference: site.reference,
+                    kind,
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the references that point at an entity of another type than their field should.
+    ///
+    /// The expected type is the field's `target` in the schema when declared.
+    /// Otherwise it's inferred from the field name: a field named `organization_ref`
+    /// or `organization_refs` should point at an `organization`, and so should
+    /// `primary_organization_ref`, as long as a schema of that type exists.
+    /// References that don't resolve are left to [`Workspace::check_references`].
+    /// Results are ordered by file path, then by position in the file.
+    pub fn check_reference_types(&self, build: &WorkspaceBuild) -> Vec<MismatchedReference> {
+        let entities: HashMap<&EntityId, &Entity> =
+            build.entities.iter().map(|e| (&e.id, e)).collect();
+        let schemas: HashMap<&EntityType, &EntitySchema> = build
+            .schemas
+            .iter()
+            .map(|schema| (&schema.entity_type, schema))
+            .collect();
+
+        self.field_references()
+            .into_iter()
+            .filter(|site| self.is_checked(site) && resolve(&entities, &site.reference).is_none())
+            .filter_map(|site| {
+                let (entity_type, _) = decompose_entity_id(site.entity_id.as_str());
+                let declared = schemas
+                    .get(&EntityType::new(entity_type))
+                    .and_then(|schema| schema.fields.get(&site.field))
+                    .and_then(|field| field.target());
+                let (expected_type, inferred) = match declared {
+                    Some(target) => (target.clone(), false),
+                    None => (infer_target(site.field.as_str(), &schemas)?, true),
+                };
+
+                let (actual_type, _) = decompose_entity_id(target_id(&site.reference).as_str());
+                if EntityType::new(actual_type) == expected_type {
+                    return None;
+                }
+                Some(MismatchedReference {
+                    path: site.path,
+                    line: site.line,
+                    entity_id: site.entity_id,
+                    field: site.field,
+                    reference: site.reference,
+                    expected_type,
+                    inferred,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether a reference is checked, given the type filter.
+    ///
+    /// References from excluded types aren't checked, and neither are references
+    /// to excluded types, since their targets weren't built.
+    fn is_checked(&self, site: &InboundReference) -> bool {
+        let (source_type, _) = decompose_entity_id(site.entity_id.as_str());
+        let (target_type, _) = decompose_entity_id(target_id(&site.reference).as_str());
+        self.type_filter.includes(source_type) && self.type_filter.includes(target_type)
+    }
+
+    /// Finds the references to an entity or its fields from other entities.
+    ///
+    /// These are the references that break if the entity is removed. The
+    /// entity's references to itself aren't included. Results are ordered
+    /// by file path, then by position in the file.
+    pub fn inbound_references(&self, target: &EntityId) -> Vec<InboundReference> {
+        self.field_references()
+            .into_iter()
+            .filter(|site| target_id(&site.reference) == target && &site.entity_id != target)
+            .collect()
+    }
+
+    /// Gets every reference in the field values of the loaded files, as an inbound
+    /// reference of its target.
+    fn field_references(&self) -> Vec<InboundReference> {
+        let mut sites = Vec::new();
+        for (path, file) in &self.files {
+            for parsed_entity in file.parsed.entities() {
+                let (Some(entity_type), Some(id)) =
+                    (parsed_entity.entity_type(), parsed_entity.id())
+                else {
+                    continue;
+                };
+                let entity_id = compose_entity_id(entity_type, id);
+
+                for parsed_field in parsed_entity.fields() {
+                    let Some(field_name) = parsed_field.id() else {
+                        continue;
+                    };
+                    let Some(value) = parsed_field
+                        .value()
+                        .ok()
+                        .and_then(|v| FieldValue::try_from(v).ok())
+                    else {
+                        continue;
+                    };
+
+                    let mut references = Vec::new();
+                    collect_references(&value, &mut references);
+
+                    for reference in references {
+                        sites.push(InboundReference {
+                            path: path.clone(),
+                            line: parsed_field.line(),
+                            entity_id: entity_id.clone(),
+                            field: FieldId::new(field_name),
+                            reference: reference.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        sites
+    }
+}
+
+/// Collects the references in a field value, including inside lists.
+pub(super) fn collect_references<'a>(
+    value: &'a FieldValue,
+    references: &mut Vec<&'a ReferenceValue>,
+) {
+    match value {
+        FieldValue::Reference(reference) => references.push(reference),
+        FieldValue::List(items) => {
+            for item in items {
+                collect_references(item, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Gets the entity a reference points at, or whose field it points at.
+fn target_id(reference: &ReferenceValue) -> &EntityId {
+    match reference {
+        ReferenceValue::Entity(target_id) => target_id,
+        ReferenceValue::Field(target_id, _) => target_id,
+    }
+}
+
+/// Infers the entity type a field should point at from its `_ref` or `_refs` suffix.
+///
+/// The whole prefix is tried first, then the endings after each underscore,
+/// and the first one naming a schema is the type.
+fn infer_target(
+    field_name: &str,
+    schemas: &HashMap<&EntityType, &EntitySchema>,
+) -> Option<EntityType> {
+    let prefix = field_name
+        .strip_suffix("_refs")
+        .or_else(|| field_name.strip_suffix("_ref"))?;
+
+    std::iter::once(prefix)
+        .chain(
+            prefix
+                .match_indices('_')
+                .map(|(index, _)| &prefix[index + 1..]),
+        )
+        .map(EntityType::new)
+        .find(|entity_type| schemas.contains_key(entity_type))
+}
+
+/// Returns why a reference can't be resolved, or None if it resolves.
+pub(super) fn resolve(
+    entities: &HashMap<&EntityId, &Entity>,
+    reference: &ReferenceValue,
+) -> Option<BrokenReferenceKind> {
+    match reference {
+        ReferenceValue::Entity(target_id) => {
+            if entities.contains_key(target_id) {
+                None
+            } else {
+                Some(BrokenReferenceKind::MissingEntity)
+            }
+        }
+        ReferenceValue::Field(target_id, target_field) => match entities.get(target_id) {
+            None => Some(BrokenReferenceKind::MissingEntity),
+            Some(entity) if entity.get_field(target_field).is_none() => {
+                Some(BrokenReferenceKind::MissingField)
+            }
+            Some(_) => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_target() {
+        let schemas: Vec<EntitySchema> = ["organization", "contact"]
+            .into_iter()
+            .map(|name| EntitySchema::new(EntityType::new(name)))
+            .collect();
+        let schemas: HashMap<&EntityType, &EntitySchema> = schemas
+            .iter()
+            .map(|schema| (&schema.entity_type, schema))
+            .collect();
+
+        let infer = |field_name| infer_target(field_name, &schemas).map(|t| t.to_string());
+        assert_eq!(infer("organization_ref").as_deref(), Some("organization"));
+        assert_eq!(infer("organization_refs").as_deref(), Some("organization"));
+        assert_eq!(infer("primary_contact_ref").as_deref(), Some("contact"));
+
+        // No schema by the name, or not a reference field name
+        assert_eq!(infer("owner_ref"), None);
+        assert_eq!(infer("organization"), None);
+    }
+}
@@ -0,0 +1,391 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use tree_sitter::{Point, Range};
+
+use super::definition::{contains, find_ancestor};
+use super::positions::sub_range;
+use super::usages::collect_reference_nodes;
+use super::{TextEdit, Workspace};
+use crate::parser::dsl::{ParsedEntity, ParsedSchema, ParsedSource, parse_source};
+
+const REFERENCE_KIND: &str = "reference";
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+const SCHEMA_BLOCK_KIND: &str = "schema_block";
+
+/// Edits to several workspace files, keyed by the file's path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceEdit {
+    pub changes: BTreeMap<PathBuf, Vec<TextEdit>>,
+}
+
+impl WorkspaceEdit {
+    /// Counts the edits across all files.
+    pub fn num_edits(&self) -> usize {
+        self.changes.values().map(Vec::len).sum()
+    }
+
+    fn push(&mut self, path: &Path, range: Range, replacement: &str) {
+        self.changes
+            .entry(path.to_path_buf())
+            .or_default()
+            .push(TextEdit {
+                range,
+                replacement: replacement.to_string(),
+            });
+    }
+}
+
+/// Why a rename can't be done.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameError {
+    /// The position isn't on an entity ID or field name.
+    NotRenameable,
+    /// The new name isn't a snake_case identifier.
+    InvalidName(String),
+    /// Another entity of the type already has the new ID.
+    EntityExists {
+        entity_type: String,
+        entity_id: String,
+    },
+    /// The schema already declares a field with the new name.
+    FieldExists { entity_type: String, field: String },
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NotRenameable => {
+                write!(f, "Only entity IDs and field names can be renamed")
+            }
+            RenameError::InvalidName(name) => write!(
+                f,
+                "'{}' is not a valid name: use lowercase letters, digits and underscores, starting with a letter",
+                name
+            ),
+            RenameError::EntityExists {
+                entity_type,
+                entity_id,
+            } => write!(f, "Entity '{}.{}' already exists", entity_type, entity_id),
+            RenameError::FieldExists { entity_type, field } => write!(
+                f,
+                "Schema '{}' already has a field named '{}'",
+                entity_type, field
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// The entity or field a rename applies to.
+#[derive(Debug, Clone, PartialEq)]
+enum RenameTarget {
+    Entity {
+        entity_type: String,
+        entity_id: String,
+    },
+    Field {
+        entity_type: String,
+        field: String,
+    },
+}
+
+impl Workspace {
+    /// Checks that the identifier at a position in a source document can be renamed.
+    ///
+    /// Returns the range of the identifier, like the ID part of a reference.
+    /// The source is parsed as given, so it can be an unsaved editor buffer.
+    /// The position is zero-based, with the column in bytes.
+    pub fn prepare_rename(&self, source: &str, position: Point) -> Result<Range, RenameError> {
+        let parsed =
+            parse_source(source.to_string(), None).map_err(|_| RenameError::NotRenameable)?;
+        rename_target(&parsed, position).map(|(_, range)| range)
+    }
+
+    /// Renames the entity ID or field name at a position across the workspace.
+    ///
+    /// Renaming an entity ID edits its defining blocks and every reference to it.
+    /// Renaming a field, from a schema's field name, an entity's field or a field
+    /// reference, edits the schema, every assignment in entities of that type and
+    /// every field reference. Renames that would clash with an existing entity or
+    /// schema field are rejected.
+    pub fn rename(
+        &self,
+        source: &str,
+        position: Point,
+        new_name: &str,
+    ) -> Result<WorkspaceEdit, RenameError> {
+        let parsed =
+            parse_source(source.to_string(), None).map_err(|_| RenameError::NotRenameable)?;
+        let (target, _) = rename_target(&parsed, position)?;
+
+        if !is_valid_name(new_name) {
+            return Err(RenameError::InvalidName(new_name.to_string()));
+        }
+
+        let mut edit = match target {
+            RenameTarget::Entity {
+                entity_type,
+                entity_id,
+            } => self.entity_rename_edit(&entity_type, &entity_id, new_name)?,
+            RenameTarget::Field { entity_type, field } => {
+                self.field_rename_edit(&entity_type, &field, new_name)?
+            }
+        };
+
+        for edits in edit.changes.values_mut() {
+            edits.sort_by_key(|edit| edit.range.start_byte);
+        }
+        Ok(edit)
+    }
+
+    fn entity_rename_edit(
+        &self,
+        entity_type: &str,
+        entity_id: &str,
+        new_id: &str,
+    ) -> Result<WorkspaceEdit, RenameError> {
+        let mut edit = WorkspaceEdit::default();
+        if new_id == entity_id {
+            return Ok(edit);
+        }
+
+        let exists = self.files.values().any(|file| {
+            file.parsed.entities().iter().any(|entity| {
+                entity.entity_type() == Some(entity_type) && entity.id() == Some(new_id)
+            })
+        });
+        if exists {
+            return Err(RenameError::EntityExists {
+                entity_type: entity_type.to_string(),
+                entity_id: new_id.to_string(),
+            });
+        }
+
+        for usage in self.entity_usages(entity_type, entity_id, true) {
+            let Some(file) = self.files.get(&usage.path) else {
+                continue;
+            };
+            let text = &file.parsed.source[usage.range.start_byte..usage.range.end_byte];
+
+            // Declarations are the bare ID, references also have the type
+            let range = if text.contains('.') {
+                match reference_segments(text).get(1) {
+                    Some(&(start, end)) => sub_range(&usage.range, text, start, end),
+                    None => continue,
+                }
+            } else {
+                usage.range
+            };
+            edit.push(&usage.path, range, new_id);
+        }
+
+        Ok(edit)
+    }
+
+    fn field_rename_edit(
+        &self,
+        entity_type: &str,
+        field: &str,
+        new_field: &str,
+    ) -> Result<WorkspaceEdit, RenameError> {
+        let mut edit = WorkspaceEdit::default();
+        if new_field == field {
+            return Ok(edit);
+        }
+
+        for (path, file) in &self.files {
+            for schema in file.parsed.schemas() {
+                if schema.name() != Some(entity_type) {
+                    continue;
+                }
+
+                for schema_field in schema.fields() {
+                    let Ok(name) = schema_field.name() else {
+                        continue;
+                    };
+                    if name == new_field {
+                        return Err(RenameError::FieldExists {
+                            entity_type: entity_type.to_string(),
+                            field: new_field.to_string(),
+                        });
+                    }
+                    if name == field
+                        && let Some(range) = schema_field.name_range()
+                    {
+                        // Keep the quotes around the name
+                        let text = &file.parsed.source[range.start_byte..range.end_byte];
+                        edit.push(path, sub_range(&range, text, 1, text.len() - 1), new_field);
+                    }
+                }
+            }
+
+            let mut references = Vec::new();
+            collect_reference_nodes(file.parsed.tree.root_node(), &mut references);
+            for reference in references {
+                let text = &file.parsed.source[reference.byte_range()];
+                let segments = reference_segments(text);
+                if let [(type_start, type_end), _, (field_start, field_end)] = segments[..]
+                    && &text[type_start..type_end] == entity_type
+                    && &text[field_start..field_end] == field
+                {
+                    edit.push(
+                        path,
+                        sub_range(&reference.range(), text, field_start, field_end),
+                        new_field,
+                    );
+                }
+            }
+        }
+
+        for assignment in self.field_assignments(entity_type, field) {
+            edit.push(&assignment.path, assignment.range, new_field);
+        }
+
+        Ok(edit)
+    }
+}
+
+/// Finds what a rename at a position applies to, and the range of the identifier there.
+fn rename_target(
+    parsed: &ParsedSource,
+    position: Point,
+) -> Result<(RenameTarget, Range), RenameError> {
+    let node = parsed
+        .tree
+        .root_node()
+        .descendant_for_point_range(position, position)
+        .ok_or(RenameError::NotRenameable)?;
+
+    if let Some(reference) = find_ancestor(node, REFERENCE_KIND) {
+        let text = &parsed.source[reference.byte_range()];
+        let segments = reference_segments(text);
+        if segments.len() != 2 && segments.len() != 3 {
+            return Err(RenameError::NotRenameable);
+        }
+
+        // References are on one line, so the column gives the offset into the text
+        let offset = position
+            .column
+            .saturating_sub(reference.start_position().column);
+        let index = segments
+            .iter()
+            .position(|&(start, end)| start <= offset && offset <= end)
+            .ok_or(RenameError::NotRenameable)?;
+        let entity_type = text[segments[0].0..segments[0].1].to_string();
+        let (start, end) = segments[index];
+        let range = sub_range(&reference.range(), text, start, end);
+
+        return match index {
+            1 => Ok((
+                RenameTarget::Entity {
+                    entity_type,
+                    entity_id: text[start..end].to_string(),
+                },
+                range,
+            )),
+            2 => Ok((
+                RenameTarget::Field {
+                    entity_type,
+                    field: text[start..end].to_string(),
+                },
+                range,
+            )),
+            _ => Err(RenameError::NotRenameable),
+        };
+    }
+
+    if let Some(block) = find_ancestor(node, ENTITY_BLOCK_KIND) {
+        let entity = ParsedEntity::new(block, &parsed.source, &parsed.path);
+        let entity_type = entity.entity_type().ok_or(RenameError::NotRenameable)?;
+
+        if let (Some(entity_id), Some(range)) = (entity.id(), entity.id_range())
+            && contains(&range, position)
+        {
+            let target = RenameTarget::Entity {
+                entity_type: entity_type.to_string(),
+                entity_id: entity_id.to_string(),
+            };
+            return Ok((target, range));
+        }
+
+        for field in entity.fields() {
+            if let (Some(field_id), Some(range)) = (field.id(), field.id_range())
+                && contains(&range, position)
+            {
+                let target = RenameTarget::Field {
+                    entity_type: entity_type.to_string(),
+                    field: field_id.to_string(),
+                };
+                return Ok((target, range));
+            }
+        }
+        return Err(RenameError::NotRenameable);
+    }
+
+    if let Some(block) = find_ancestor(node, SCHEMA_BLOCK_KIND) {
+        let schema = ParsedSchema::new(block, &parsed.source, &parsed.path);
+        let schema_name = schema.name().ok_or(RenameError::NotRenameable)?;
+
+        for schema_field in schema.fields() {
+            if let (Ok(name), Some(range)) = (schema_field.name(), schema_field.name_range())
+                && contains(&range, position)
+            {
+                let text = &parsed.source[range.start_byte..range.end_byte];
+                let target = RenameTarget::Field {
+                    entity_type: schema_name.to_string(),
+                    field: name,
+                };
+                return Ok((target, sub_range(&range, text, 1, text.len() - 1)));
+            }
+        }
+    }
+
+    Err(RenameError::NotRenameable)
+}
+
+/// Checks whether a name is a snake_case identifier, like `jane_doe` or `q3_budget`.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Splits reference text into the byte ranges of its dot-separated segments.
+fn reference_segments(text: &str) -> Vec<(usize, usize)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for part in text.split('.') {
+        segments.push((start, start + part.len()));
+        start += part.len() + 1;
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_names() {
+        assert!(is_valid_name("jane_doe"));
+        assert!(is_valid_name("q3_budget"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("Jane"));
+        assert!(!is_valid_name("3rd"));
+        assert!(!is_valid_name("jane-doe"));
+        assert!(!is_valid_name("person.jane"));
+    }
+
+    #[test]
+    fn test_reference_segments() {
+        assert_eq!(
+            reference_segments("person.jane.name"),
+            vec![(0, 6), (7, 11), (12, 16)]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use tree_sitter::{Point, Range};
+
+use crate::parser::dsl::ParsedSource;
+
+/// A range to select around a position, with the next larger range as its parent.
+///
+/// Expanding a selection steps from a range to its parent, like from a field
+/// name to the whole field assignment and then to its block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionRange {
+    pub range: Range,
+    pub parent: Option<Box<SelectionRange>>,
+}
+
+/// Finds the selection ranges around each position in a parsed document.
+///
+/// The ranges come from the syntax nodes containing the position, from the
+/// innermost out to the whole document, skipping nodes with the same range as
+/// their child. Positions are zero-based, with the column in bytes, and each
+/// gets one selection range, which is empty outside the document.
+pub fn selection_ranges(parsed: &ParsedSource, positions: &[Point]) -> Vec<SelectionRange> {
+    positions
+        .iter()
+        .map(|&position| selection_range(parsed, position))
+        .collect()
+}
+
+fn selection_range(parsed: &ParsedSource, position: Point) -> SelectionRange {
+    let root = parsed.tree.root_node();
+    let Some(node) = root.named_descendant_for_point_range(position, position) else {
+        return empty_selection(position);
+    };
+
+    // Innermost first, without repeats
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut current = Some(node);
+    while let Some(node) = current {
+        let range = node.range();
+        if node.is_named() && ranges.last() != Some(&range) {
+            ranges.push(range);
+        }
+        current = node.parent();
+    }
+
+    ranges
+        .into_iter()
+        .rev()
+        .fold(None, |parent, range| {
+            Some(SelectionRange {
+                range,
+                parent: parent.map(Box::new),
+            })
+        })
+        .unwrap_or_else(|| empty_selection(position))
+}
+
+fn empty_selection(position: Point) -> SelectionRange {
+    SelectionRange {
+        range: Range {
+            start_byte: 0,
+            end_byte: 0,
+            start_point: position,
+            end_point: position,
+        },
+        parent: None,
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use firm_core::{Entity, EntityId, FieldId, ReferenceValue, compose_entity_id};
+use tree_sitter::{Node, Point};
+
+use super::WorkspaceBuild;
+use super::references::resolve;
+use crate::parser::dsl::{ParsedSource, ParsedValue};
+
+const ENTITY_TYPE_KIND: &str = "entity_type";
+const ENTITY_ID_KIND: &str = "entity_id";
+const SCHEMA_NAME_KIND: &str = "schema_name";
+const FIELD_NAME_KIND: &str = "field_name";
+const REFERENCE_KIND: &str = "reference";
+const ENUM_KIND: &str = "enum";
+const CURRENCY_KIND: &str = "currency";
+
+/// What a semantic token is, beyond its syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticTokenType {
+    /// An entity type, or the name of a schema.
+    Type,
+    /// The ID of an entity where it's defined.
+    Entity,
+    /// A field name.
+    Property,
+    /// A reference to an entity or one of its fields.
+    Reference,
+    /// An enum value.
+    EnumMember,
+    /// The currency code of an amount.
+    Currency,
+}
+
+impl SemanticTokenType {
+    /// Every token type, in legend order.
+    pub const ALL: [SemanticTokenType; 6] = [
+        SemanticTokenType::Type,
+        SemanticTokenType::Entity,
+        SemanticTokenType::Property,
+        SemanticTokenType::Reference,
+        SemanticTokenType::EnumMember,
+        SemanticTokenType::Currency,
+    ];
+
+    /// The name in an editor's token legend, using the standard LSP names where one fits.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SemanticTokenType::Type => "type",
+            SemanticTokenType::Entity => "variable",
+            SemanticTokenType::Property => "property",
+            SemanticTokenType::Reference => "reference",
+            SemanticTokenType::EnumMember => "enumMember",
+            SemanticTokenType::Currency => "currency",
+        }
+    }
+}
+
+/// Extra information about a semantic token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticTokenModifier {
+    /// Where an entity or schema is defined.
+    Declaration,
+    /// A reference that doesn't resolve.
+    Dangling,
+}
+
+impl SemanticTokenModifier {
+    /// Every token modifier, in legend order.
+    pub const ALL: [SemanticTokenModifier; 2] = [
+        SemanticTokenModifier::Declaration,
+        SemanticTokenModifier::Dangling,
+    ];
+
+    /// The name in an editor's token legend.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SemanticTokenModifier::Declaration => "declaration",
+            SemanticTokenModifier::Dangling => "dangling",
+        }
+    }
+
+    /// The bit of this modifier in an LSP modifier set.
+    pub fn bit(&self) -> u32 {
+        match self {
+            SemanticTokenModifier::Declaration => 1,
+            SemanticTokenModifier::Dangling => 1 << 1,
+        }
+    }
+}
+
+/// A span of a single line with semantic meaning, for highlighting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticToken {
+    pub start: Point,
+    /// The length in bytes.
+    pub length: usize,
+    pub token_type: SemanticTokenType,
+    pub modifiers: Vec<SemanticTokenModifier>,
+}
+
+impl WorkspaceBuild {
+    /// Finds the semantic tokens of a parsed document, in source order.
+    ///
+    /// Entity types and schema names, entity IDs, field names, references,
+    /// enum values and currency codes each get a token. References that don't
+    /// resolve in this build are marked dangling, so the tokens should be
+    /// recomputed after the workspace is rebuilt. Positions are zero-based,
+    /// with columns in bytes.
+    pub fn semantic_tokens(&self, parsed: &ParsedSource) -> Vec<SemanticToken> {
+        let entities: HashMap<&EntityId, &Entity> = self
+            .entities
+            .iter()
+            .map(|entity| (&entity.id, entity))
+            .collect();
+
+        let mut tokens = Vec::new();
+        collect_tokens(
+            parsed.tree.root_node(),
+            &parsed.source,
+            &entities,
+            &mut tokens,
+        );
+        tokens
+    }
+}
+
+fn collect_tokens(
+    node: Node,
+    source: &str,
+    entities: &HashMap<&EntityId, &Entity>,
+    tokens: &mut Vec<SemanticToken>,
+) {
+    let text = &source[node.byte_range()];
+    let token = match node.kind() {
+        ENTITY_TYPE_KIND => Some((SemanticTokenType::Type, vec![])),
+        SCHEMA_NAME_KIND => Some((
+            SemanticTokenType::Type,
+            vec![SemanticTokenModifier::Declaration],
+        )),
+        ENTITY_ID_KIND => Some((
+            SemanticTokenType::Entity,
+            vec![SemanticTokenModifier::Declaration],
+        )),
+        FIELD_NAME_KIND => Some((SemanticTokenType::Property, vec![])),
+        ENUM_KIND => Some((SemanticTokenType::EnumMember, vec![])),
+        REFERENCE_KIND => {
+            let modifiers = match reference_value(text) {
+                Some(reference) if resolve(entities, &reference).is_none() => vec![],
+                _ => vec![SemanticTokenModifier::Dangling],
+            };
+            Some((SemanticTokenType::Reference, modifiers))
+        }
+        CURRENCY_KIND => {
+            // Only the code after the amount, like `USD` in `5000.00 USD`
+            if let Some(offset) = text.trim_end().rfind(char::is_whitespace).map(|i| i + 1)
+                && node.start_position().row == node.end_position().row
+            {
+                let start = node.start_position();
+                tokens.push(SemanticToken {
+                    start: Point::new(start.row, start.column + offset),
+                    length: text.trim_end().len() - offset,
+                    token_type: SemanticTokenType::Currency,
+                    modifiers: vec![],
+                });
+            }
+            return;
+        }
+        _ => None,
+    };
+
+    if let Some((token_type, modifiers)) = token {
+        // Tokens can't span lines
+        if node.start_position().row == node.end_position().row {
+            tokens.push(SemanticToken {
+                start: node.start_position(),
+                length: node.byte_range().len(),
+                token_type,
+                modifiers,
+            });
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_tokens(child, source, entities, tokens);
+    }
+}
+
+/// Parses the text of a reference node into the reference it points to.
+fn reference_value(text: &str) -> Option<ReferenceValue> {
+    match ParsedValue::parse_reference(text).ok()? {
+        ParsedValue::EntityReference {
+            entity_type,
+            entity_id,
+        } => Some(ReferenceValue::Entity(compose_entity_id(
+            &entity_type,
+            &entity_id,
+        ))),
+        ParsedValue::FieldReference {
+            entity_type,
+            entity_id,
+            field_id,
+        } => Some(ReferenceValue::Field(
+            compose_entity_id(&entity_type, &entity_id),
+            FieldId::new(field_id),
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifier_bits_follow_legend_order() {
+        for (index, modifier) in SemanticTokenModifier::ALL.iter().enumerate() {
+            assert_eq!(modifier.bit(), 1 << index);
+        }
+    }
+
+    #[test]
+    fn test_reference_value() {
+        assert_eq!(
+            reference_value("person.john"),
+            Some(ReferenceValue::Entity(EntityId::new("person.john")))
+        );
+        assert_eq!(
+            reference_value("person.john.name"),
+            Some(ReferenceValue::Field(
+                EntityId::new("person.john"),
+                FieldId::new("name")
+            ))
+        );
+        assert_eq!(reference_value("not a reference"), None);
+    }
+}
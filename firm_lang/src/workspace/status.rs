@@ -0,0 +1,96 @@
+use std::fmt;
+
+use super::{DiagnosticSeverity, Workspace, WorkspaceDiagnostics};
+
+/// A summary of a workspace's health, like for an editor's status bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WorkspaceStatus {
+    /// Whether the workspace builds. Broken references don't stop a build.
+    pub builds: bool,
+    pub files: usize,
+    /// Entity blocks in the workspace files, counted even when the build fails.
+    pub entities: usize,
+    /// Schema blocks in the workspace files, counted even when the build fails.
+    pub schemas: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl fmt::Display for WorkspaceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.builds {
+            write!(f, "Build failed: ")?;
+        }
+        write!(
+            f,
+            "{}, {}, {}, {}",
+            count(self.entities, "entity", "entities"),
+            count(self.schemas, "schema", "schemas"),
+            count(self.errors, "error", "errors"),
+            count(self.warnings, "warning", "warnings")
+        )
+    }
+}
+
+impl Workspace {
+    /// Collects diagnostics for every file, and a summary of the workspace's health.
+    ///
+    /// The diagnostics are the same as from `collect_diagnostics`, and the
+    /// status counts them, so the two can be published together.
+    pub fn collect_status(&mut self) -> (WorkspaceDiagnostics, WorkspaceStatus) {
+        let (diagnostics, build) = self.diagnose();
+
+        let mut status = WorkspaceStatus {
+            builds: build.is_some(),
+            files: self.files.len(),
+            ..WorkspaceStatus::default()
+        };
+        for file in self.files.values() {
+            status.entities += file.parsed.entities().len();
+            status.schemas += file.parsed.schemas().len();
+        }
+        for diagnostic in diagnostics.values().flatten() {
+            match diagnostic.severity {
+                DiagnosticSeverity::Error => status.errors += 1,
+                DiagnosticSeverity::Warning => status.warnings += 1,
+            }
+        }
+
+        (diagnostics, status)
+    }
+}
+
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_display() {
+        let status = WorkspaceStatus {
+            builds: true,
+            files: 2,
+            entities: 12,
+            schemas: 1,
+            errors: 0,
+            warnings: 1,
+        };
+        assert_eq!(
+            status.to_string(),
+            "12 entities, 1 schema, 0 errors, 1 warning"
+        );
+
+        let failed = WorkspaceStatus {
+            builds: false,
+            errors: 1,
+            ..status
+        };
+        assert_eq!(
+            failed.to_string(),
+            "Build failed: 12 entities, 1 schema, 1 error, 1 warning"
+        );
+    }
+}
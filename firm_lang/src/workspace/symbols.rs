@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+
+use tree_sitter::Range;
+
+use super::Workspace;
+use crate::parser::dsl::{ParsedEntity, ParsedSchema, ParsedSource};
+
+/// What a symbol names.
+///
+/// Editors usually show these as a class, an object and a field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Schema,
+    Entity,
+    Field,
+}
+
+/// A symbol in a document outline, with nested symbols as children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Extra context shown next to the name, like an entity's type.
+    pub detail: Option<String>,
+    /// The range of the whole block or assignment.
+    pub range: Range,
+    /// The range of the name, which is selected when jumping to the symbol.
+    pub selection_range: Range,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A schema or entity found by searching the workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The entity type, for entities.
+    pub container: Option<String>,
+    pub path: PathBuf,
+    /// The range of the name.
+    pub range: Range,
+}
+
+/// How closely a symbol's name matches a query, from loosest to closest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolMatch {
+    /// The query's characters appear in the name in order, like `jdoe` in `jane_doe`.
+    Fuzzy,
+    Substring,
+    Prefix,
+    Exact,
+}
+
+/// Builds the outline of a parsed document: schemas with their fields, and entities with theirs.
+///
+/// Symbols are returned in source order. Blocks without a name are left out.
+pub fn document_symbols(parsed: &ParsedSource) -> Vec<DocumentSymbol> {
+    let mut symbols: Vec<DocumentSymbol> = parsed
+        .schemas()
+        .iter()
+        .filter_map(schema_symbol)
+        .chain(parsed.entities().iter().filter_map(entity_symbol))
+        .collect();
+
+    symbols.sort_by_key(|symbol| symbol.range.start_byte);
+    symbols
+}
+
+impl Workspace {
+    /// Finds schemas and entities whose name matches the query, ignoring case.
+    ///
+    /// Entities match on their ID or on `type.id`. Names match when they contain
+    /// the query's characters in order, so `jdoe` finds `jane_doe`. Exact matches
+    /// come first, then names starting with the query, names containing it and
+    /// the rest, each in name and path order. Uses the parsed files already in
+    /// the workspace, so nothing is re-read. An empty query matches every symbol.
+    pub fn workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        let query = query.to_lowercase();
+        let mut symbols = Vec::new();
+
+        for (path, file) in &self.files {
+            for schema in file.parsed.schemas() {
+                if let (Some(name), Some(range)) = (schema.name(), schema.name_range())
+                    && let Some(quality) = match_symbol(name, &query)
+                {
+                    symbols.push((
+                        quality,
+                        WorkspaceSymbol {
+                            name: name.to_string(),
+                            kind: SymbolKind::Schema,
+                            container: None,
+                            path: path.clone(),
+                            range,
+                        },
+                    ));
+                }
+            }
+
+            for entity in file.parsed.entities() {
+                let (Some(entity_type), Some(id), Some(range)) =
+                    (entity.entity_type(), entity.id(), entity.id_range())
+                else {
+                    continue;
+                };
+
+                let full_id = format!("{}.{}", entity_type, id);
+                let quality = match_symbol(id, &query).max(match_symbol(&full_id, &query));
+                if let Some(quality) = quality {
+                    symbols.push((
+                        quality,
+                        WorkspaceSymbol {
+                            name: id.to_string(),
+                            kind: SymbolKind::Entity,
+                            container: Some(entity_type.to_string()),
+                            path: path.clone(),
+                            range,
+                        },
+                    ));
+                }
+            }
+        }
+
+        symbols.sort_by(|(a_quality, a), (b_quality, b)| {
+            b_quality
+                .cmp(a_quality)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.range.start_byte.cmp(&b.range.start_byte))
+        });
+        symbols.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+}
+
+/// Matches a name against a lowercase query.
+fn match_symbol(name: &str, query: &str) -> Option<SymbolMatch> {
+    let name = name.to_lowercase();
+    if name == query {
+        Some(SymbolMatch::Exact)
+    } else if name.starts_with(query) {
+        Some(SymbolMatch::Prefix)
+    } else if name.contains(query) {
+        Some(SymbolMatch::Substring)
+    } else {
+        let mut chars = name.chars();
+        query
+            .chars()
+            .all(|query_char| chars.any(|c| c == query_char))
+            .then_some(SymbolMatch::Fuzzy)
+    }
+}
+
+/// Builds the symbol for a schema block, with its field definitions as children.
+fn schema_symbol(schema: &ParsedSchema<'_>) -> Option<DocumentSymbol> {
+    let children = schema
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            Some(DocumentSymbol {
+                name: field.name().ok()?,
+                kind: SymbolKind::Field,
+                detail: field.field_type().ok(),
+                range: field.range(),
+                selection_range: field.name_range()?,
+                children: Vec::new(),
+            })
+        })
+        .collect();
+
+    Some(DocumentSymbol {
+        name: schema.name()?.to_string(),
+        kind: SymbolKind::Schema,
+        detail: None,
+        range: schema.range(),
+        selection_range: schema.name_range()?,
+        children,
+    })
+}
+
+/// Builds the symbol for an entity block, with its field assignments as children.
+fn entity_symbol(entity: &ParsedEntity<'_>) -> Option<DocumentSymbol> {
+    let children = entity
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            Some(DocumentSymbol {
+                name: field.id()?.to_string(),
+                kind: SymbolKind::Field,
+                detail: None,
+                range: field.range(),
+                selection_range: field.id_range()?,
+                children: Vec::new(),
+            })
+        })
+        .collect();
+
+    Some(DocumentSymbol {
+        name: entity.id()?.to_string(),
+        kind: SymbolKind::Entity,
+        detail: entity.entity_type().map(str::to_string),
+        range: entity.range(),
+        selection_range: entity.id_range()?,
+        children,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_symbol() {
+        assert_eq!(match_symbol("Person", "person"), Some(SymbolMatch::Exact));
+        assert_eq!(
+            match_symbol("person.jane", "person"),
+            Some(SymbolMatch::Prefix)
+        );
+        assert_eq!(
+            match_symbol("jane_doe", "doe"),
+            Some(SymbolMatch::Substring)
+        );
+        assert_eq!(match_symbol("jane_doe", "jdoe"), Some(SymbolMatch::Fuzzy));
+        assert_eq!(match_symbol("jane_doe", "doej"), None);
+        assert_eq!(match_symbol("jane_doe", ""), Some(SymbolMatch::Prefix));
+    }
+}
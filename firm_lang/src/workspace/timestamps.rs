@@ -0,0 +1,461 @@
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The file in the git directory that keeps timestamps between runs.
+const CACHE_FILE_NAME: &str = "firm-timestamps.json";
+
+/// When the lines of a source file were written, used to timestamp its entities.
+///
+/// In a git work tree, each line gets the author time of the commit that last
+/// changed it, and uncommitted lines get the file's modified time. Otherwise,
+/// or when git can't blame the file, every entity gets the file's creation
+/// time (where the file system records it) and modified time. Files that can't
+/// be read give no timestamps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct SourceTimestamps {
+    line_times: Vec<DateTime<FixedOffset>>,
+    file_created: Option<DateTime<FixedOffset>>,
+    file_modified: Option<DateTime<FixedOffset>>,
+}
+
+impl SourceTimestamps {
+    /// Reads the timestamps of a file, from git blame if `use_git` is set.
+    pub(super) fn read(path: &Path, use_git: bool) -> Self {
+        let metadata = fs::metadata(path).ok();
+        let file_created = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.created().ok())
+            .map(to_datetime);
+        let file_modified = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok())
+            .map(to_datetime);
+
+        let line_times = match file_modified {
+            Some(uncommitted_time) if use_git => {
+                blame_line_times(path, uncommitted_time).unwrap_or_default()
+            }
+            _ => Vec::new(),
+        };
+
+        Self {
+            line_times,
+            file_created,
+            file_modified,
+        }
+    }
+
+    /// Gets when the zero-based rows of a block were first and last written.
+    ///
+    /// With git, that's the oldest and newest time of the block's lines.
+    pub(super) fn for_rows(
+        &self,
+        start_row: usize,
+        end_row: usize,
+    ) -> (Option<DateTime<FixedOffset>>, Option<DateTime<FixedOffset>>) {
+        match self.line_times.get(start_row..=end_row) {
+            Some(times) if !times.is_empty() => {
+                (times.iter().min().copied(), times.iter().max().copied())
+            }
+            _ => (self.file_created, self.file_modified),
+        }
+    }
+}
+
+/// Timestamps of source files, kept between builds.
+///
+/// Git blame runs a process for every file, so a file's timestamps are only
+/// read again once its modified time or content changes, or HEAD moves to
+/// another commit. With git, they're also kept in the git directory, so
+/// commands that build the workspace once don't blame every file each run.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TimestampCache {
+    entries: HashMap<PathBuf, CachedTimestamps>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedTimestamps {
+    modified: Option<SystemTime>,
+    content_hash: u64,
+    git_head: Option<String>,
+    timestamps: SourceTimestamps,
+}
+
+impl TimestampCache {
+    /// Reads the timestamps of the given sources, in parallel, reusing the ones of unchanged files.
+    ///
+    /// `git` is the checkout of the workspace, or None to not use git blame.
+    /// Files that aren't given are dropped.
+    pub(super) fn refresh(&mut self, sources: &[(&PathBuf, &str)], git: Option<&GitCheckout>) {
+        let git_head = git.map(|git| git.head.as_str());
+        if let Some(git) = git
+            && self.entries.is_empty()
+        {
+            self.entries = read_stored(git);
+        }
+
+        let refreshed: Vec<(PathBuf, CachedTimestamps, bool)> = sources
+            .par_iter()
+            .map(|&(path, source)| {
+                let modified = fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                let content_hash = hash_source(source);
+                let (timestamps, is_new) = match self.entries.get(path) {
+                    Some(cached)
+                        if cached.modified == modified
+                            && cached.content_hash == content_hash
+                            && cached.git_head.as_deref() == git_head =>
+                    {
+                        (cached.timestamps.clone(), false)
+                    }
+                    _ => (SourceTimestamps::read(path, git_head.is_some()), true),
+                };
+
+                let cached = CachedTimestamps {
+                    modified,
+                    content_hash,
+                    git_head: git_head.map(str::to_string),
+                    timestamps,
+                };
+                (path.clone(), cached, is_new)
+            })
+            .collect();
+
+        let changed = refreshed.iter().any(|(_, _, is_new)| *is_new);
+        self.entries = refreshed
+            .into_iter()
+            .map(|(path, cached, _)| (path, cached))
+            .collect();
+        if let Some(git) = git
+            && changed
+        {
+            self.store(git);
+        }
+    }
+
+    /// Gets the timestamps of a file as of the last refresh.
+    pub(super) fn get(&self, path: &Path) -> Option<&SourceTimestamps> {
+        self.entries.get(path).map(|cached| &cached.timestamps)
+    }
+
+    /// Writes the timestamps to the git directory, keeping the stored ones of
+    /// other workspaces in the same repository.
+    ///
+    /// The cache only speeds up later runs, so failing to write it is ignored.
+    fn store(&self, git: &GitCheckout) {
+        let mut stored = read_stored(git);
+        stored.extend(
+            self.entries
+                .iter()
+                .map(|(path, cached)| (path.clone(), cached.clone())),
+        );
+
+        let Ok(json) = serde_json::to_string(&stored) else {
+            return;
+        };
+        let path = git.git_dir.join(CACHE_FILE_NAME);
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        if fs::write(&temp_path, json).is_err() || fs::rename(&temp_path, &path).is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+}
+
+/// Reads the timestamps kept in the git directory for the checked out commit.
+fn read_stored(git: &GitCheckout) -> HashMap<PathBuf, CachedTimestamps> {
+    let stored: HashMap<PathBuf, CachedTimestamps> =
+        fs::read_to_string(git.git_dir.join(CACHE_FILE_NAME))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+    stored
+        .into_iter()
+        .filter(|(_, cached)| cached.git_head.as_deref() == Some(git.head.as_str()))
+        .collect()
+}
+
+/// Hashes a file's source to notice changes that keep its modified time.
+///
+/// The hash can differ between Rust releases, which only means stored
+/// timestamps are read again.
+fn hash_source(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The commit checked out in a git work tree, and where git keeps its data.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct GitCheckout {
+    head: String,
+    git_dir: PathBuf,
+}
+
+/// Gets the checkout of the git work tree a directory is in, with git installed.
+///
+/// Returns nothing outside a work tree, or before the first commit.
+pub(super) fn git_checkout(directory: &Path) -> Option<GitCheckout> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--is-inside-work-tree")
+        .arg("--absolute-git-dir")
+        .arg("HEAD")
+        .current_dir(directory)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().collect::<Vec<_>>()[..] {
+        ["true", git_dir, head] => Some(GitCheckout {
+            head: head.to_string(),
+            git_dir: PathBuf::from(git_dir),
+        }),
+        _ => None,
+    }
+}
+
+fn to_datetime(time: SystemTime) -> DateTime<FixedOffset> {
+    DateTime::<Local>::from(time).fixed_offset()
+}
+
+/// Runs git blame on a file, returning the time each line was written.
+///
+/// Returns nothing if git fails, like for files that aren't tracked.
+fn blame_line_times(
+    path: &Path,
+    uncommitted_time: DateTime<FixedOffset>,
+) -> Option<Vec<DateTime<FixedOffset>>> {
+    let output = Command::new("git")
+        .arg("blame")
+        .arg("--porcelain")
+        .arg("--")
+        .arg(path.file_name()?)
+        .current_dir(path.parent()?)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let porcelain = String::from_utf8_lossy(&output.stdout);
+    Some(parse_blame(&porcelain, uncommitted_time))
+}
+
+/// Parses `git blame --porcelain` output into the time of each line.
+///
+/// Each line starts with a header naming its commit. Commit details like the
+/// author time are only given for the first line from each commit.
+fn parse_blame(
+    porcelain: &str,
+    uncommitted_time: DateTime<FixedOffset>,
+) -> Vec<DateTime<FixedOffset>> {
+    let mut commit_times: HashMap<&str, DateTime<FixedOffset>> = HashMap::new();
+    let mut line_times = Vec::new();
+    let mut commit = None;
+    let mut author_time = None;
+
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            // The line's content ends its entry
+            let time = commit
+                .filter(|commit: &&str| commit.bytes().any(|b| b != b'0'))
+                .and_then(|commit| commit_times.get(commit).copied())
+                .unwrap_or(uncommitted_time);
+            line_times.push(time);
+        } else if let Some(seconds) = line.strip_prefix("author-time ") {
+            author_time = seconds.parse::<i64>().ok();
+        } else if let Some(zone) = line.strip_prefix("author-tz ") {
+            if let (Some(commit), Some(seconds), Some(offset)) =
+                (commit, author_time, parse_offset(zone))
+                && let Some(time) = offset.timestamp_opt(seconds, 0).single()
+            {
+                commit_times.insert(commit, time);
+            }
+        } else if let Some(hash) = line.split(' ').next()
+            && hash.len() >= 40
+            && hash.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            commit = Some(hash);
+        }
+    }
+
+    line_times
+}
+
+/// Parses a git time zone like `+0200` or `-0530`.
+fn parse_offset(zone: &str) -> Option<FixedOffset> {
+    let (sign, digits) = match zone.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if digits.len() != 4 {
+        return None;
+    }
+
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_COMMIT: &str = "1111111111111111111111111111111111111111";
+    const NEW_COMMIT: &str = "2222222222222222222222222222222222222222";
+    const UNCOMMITTED: &str = "0000000000000000000000000000000000000000";
+
+    fn porcelain() -> String {
+        [
+            format!("{} 1 1 2", OLD_COMMIT),
+            "author Jane".to_string(),
+            "author-time 1700000000".to_string(),
+            "author-tz +0200".to_string(),
+            "summary Add jane".to_string(),
+            "filename people.firm".to_string(),
+            "\tperson jane {".to_string(),
+            format!("{} 2 2", OLD_COMMIT),
+            "\t  name = \"Jane\"".to_string(),
+            format!("{} 3 3 1", NEW_COMMIT),
+            "author-time 1750000000".to_string(),
+            "author-tz -0530".to_string(),
+            format!("previous {} people.firm", OLD_COMMIT),
+            "filename people.firm".to_string(),
+            "\t  email = \"jane@example.com\"".to_string(),
+            format!("{} 4 4 1", UNCOMMITTED),
+            "author-time 1760000000".to_string(),
+            "author-tz +0000".to_string(),
+            "\t}".to_string(),
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let uncommitted_time = "2030-01-01T00:00:00+00:00".parse().unwrap();
+        let times = parse_blame(&porcelain(), uncommitted_time);
+
+        assert_eq!(times.len(), 4);
+        assert_eq!(times[0].timestamp(), 1700000000);
+        assert_eq!(times[0].offset().local_minus_utc(), 2 * 3600);
+        assert_eq!(times[1], times[0]);
+        assert_eq!(times[2].timestamp(), 1750000000);
+        assert_eq!(times[2].offset().local_minus_utc(), -(5 * 3600 + 30 * 60));
+        assert_eq!(times[3], uncommitted_time);
+    }
+
+    #[test]
+    fn test_rows_use_oldest_and_newest_line() {
+        let uncommitted_time = "2030-01-01T00:00:00+00:00".parse().unwrap();
+        let timestamps = SourceTimestamps {
+            line_times: parse_blame(&porcelain(), uncommitted_time),
+            file_created: None,
+            file_modified: Some(uncommitted_time),
+        };
+
+        let (created, modified) = timestamps.for_rows(0, 2);
+        assert_eq!(created.unwrap().timestamp(), 1700000000);
+        assert_eq!(modified.unwrap().timestamp(), 1750000000);
+
+        // Rows past the blamed lines fall back to the file's times
+        assert_eq!(timestamps.for_rows(3, 9), (None, Some(uncommitted_time)));
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("+0000"), FixedOffset::east_opt(0));
+        assert_eq!(parse_offset("-0130"), FixedOffset::east_opt(-5400));
+        assert_eq!(parse_offset("0200"), None);
+        assert_eq!(parse_offset("+2"), None);
+    }
+
+    fn checkout(dir: &tempfile::TempDir, head: &str) -> GitCheckout {
+        GitCheckout {
+            head: head.to_string(),
+            git_dir: dir.path().to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn test_cache_reads_timestamps_again_when_a_file_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("people.firm");
+        fs::write(&path, "person jane {}\n").unwrap();
+
+        // Stands in for timestamps that would read differently now
+        let forget_modified = |cache: &mut TimestampCache| {
+            if let Some(cached) = cache.entries.get_mut(&path) {
+                cached.timestamps.file_modified = None;
+            }
+        };
+
+        let mut cache = TimestampCache::default();
+        cache.refresh(&[(&path, "person jane {}\n")], None);
+        let first = cache.get(&path).unwrap().file_modified;
+        assert!(first.is_some());
+
+        // Unchanged files keep their cached timestamps
+        forget_modified(&mut cache);
+        cache.refresh(&[(&path, "person jane {}\n")], None);
+        assert_eq!(cache.get(&path).unwrap().file_modified, None);
+
+        // Changed content or another commit reads them again
+        cache.refresh(&[(&path, "person john {}\n")], None);
+        assert_eq!(cache.get(&path).unwrap().file_modified, first);
+        forget_modified(&mut cache);
+        cache.refresh(
+            &[(&path, "person john {}\n")],
+            Some(&checkout(&dir, OLD_COMMIT)),
+        );
+        assert_eq!(cache.get(&path).unwrap().file_modified, first);
+
+        // Files that are gone from the workspace are dropped
+        cache.refresh(&[], None);
+        assert!(cache.get(&path).is_none());
+    }
+
+    #[test]
+    fn test_cache_keeps_git_timestamps_between_runs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("people.firm");
+        fs::write(&path, "person jane {}\n").unwrap();
+        let sources = [(&path, "person jane {}\n")];
+
+        let mut cache = TimestampCache::default();
+        cache.refresh(&sources, Some(&checkout(&dir, OLD_COMMIT)));
+        assert!(dir.path().join(CACHE_FILE_NAME).exists());
+
+        // A later run starts from the stored timestamps of the same commit
+        let stored = read_stored(&checkout(&dir, OLD_COMMIT));
+        assert_eq!(
+            stored.get(&path).map(|cached| cached.content_hash),
+            Some(hash_source(sources[0].1))
+        );
+        assert!(read_stored(&checkout(&dir, NEW_COMMIT)).is_empty());
+
+        // Timestamps of other workspaces in the repository are kept
+        let other_path = dir.path().join("other.firm");
+        fs::write(&other_path, "person john {}\n").unwrap();
+        TimestampCache::default().refresh(
+            &[(&other_path, "person john {}\n")],
+            Some(&checkout(&dir, OLD_COMMIT)),
+        );
+        let stored = read_stored(&checkout(&dir, OLD_COMMIT));
+        assert!(stored.contains_key(&path) && stored.contains_key(&other_path));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use tree_sitter::Point;
+
+use super::definition::{contains, find_ancestor};
+use super::{Definition, Location, Workspace};
+use crate::parser::dsl::{ParsedEntity, ParsedSchema, parse_source};
+
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+const SCHEMA_BLOCK_KIND: &str = "schema_block";
+
+/// How many entities `find_implementations` lists by default.
+pub const DEFAULT_IMPLEMENTATION_LIMIT: usize = 100;
+
+/// The schema of an entity block's type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDefinition {
+    /// The name of the schema block for the type.
+    Schema(Definition),
+    /// No schema is defined for the type.
+    Missing { entity_type: String },
+}
+
+impl TypeDefinition {
+    /// A hint to show when there's no schema to go to.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            TypeDefinition::Schema(_) => None,
+            TypeDefinition::Missing { entity_type } => {
+                Some(format!("No schema defined for '{}'", entity_type))
+            }
+        }
+    }
+}
+
+/// The entities of a schema's type.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Implementations {
+    /// The ID of each entity, sorted by ID, up to the limit.
+    pub locations: Vec<Location>,
+    /// How many entities there are, including any past the limit.
+    pub total: usize,
+}
+
+impl Implementations {
+    /// Checks whether entities were left out by the limit.
+    pub fn is_truncated(&self) -> bool {
+        self.locations.len() < self.total
+    }
+}
+
+impl Workspace {
+    /// Finds the schema of the entity block at a position in a source document.
+    ///
+    /// Works from anywhere inside the block, not just on its type. The source
+    /// is parsed as given, so it can be an unsaved editor buffer. Returns None
+    /// outside an entity block. The position is zero-based, with the column in bytes.
+    pub fn find_type_definition(&self, source: &str, position: Point) -> Option<TypeDefinition> {
+        let parsed = parse_source(source.to_string(), None).ok()?;
+        let node = parsed
+            .tree
+            .root_node()
+            .descendant_for_point_range(position, position)?;
+        let block = find_ancestor(node, ENTITY_BLOCK_KIND)?;
+        let entity_type = ParsedEntity::new(block, &parsed.source, &parsed.path)
+            .entity_type()?
+            .to_string();
+
+        Some(match self.find_schema_definition(&entity_type) {
+            Some(definition) => TypeDefinition::Schema(definition),
+            None => TypeDefinition::Missing { entity_type },
+        })
+    }
+
+    /// Finds the entities of the schema named at a position in a source document.
+    ///
+    /// The position has to be on the schema's name. Entities defined across
+    /// several blocks are listed once, at their first block in file path order.
+    /// At most `limit` entities are listed, and the total is counted.
+    /// The position is zero-based, with the column in bytes.
+    pub fn find_implementations(
+        &self,
+        source: &str,
+        position: Point,
+        limit: usize,
+    ) -> Implementations {
+        let Ok(parsed) = parse_source(source.to_string(), None) else {
+            return Implementations::default();
+        };
+        let Some(block) = parsed
+            .tree
+            .root_node()
+            .descendant_for_point_range(position, position)
+            .and_then(|node| find_ancestor(node, SCHEMA_BLOCK_KIND))
+        else {
+            return Implementations::default();
+        };
+
+        let schema = ParsedSchema::new(block, &parsed.source, &parsed.path);
+        match (schema.name(), schema.name_range()) {
+            (Some(schema_name), Some(range)) if contains(&range, position) => {
+                self.schema_implementations(schema_name, limit)
+            }
+            _ => Implementations::default(),
+        }
+    }
+
+    /// Finds the entities of a type, sorted by ID, up to a limit.
+    pub fn schema_implementations(&self, entity_type: &str, limit: usize) -> Implementations {
+        let mut entities = BTreeMap::new();
+        for (path, file) in &self.files {
+            for entity in file.parsed.entities() {
+                if entity.entity_type() != Some(entity_type) {
+                    continue;
+                }
+                if let (Some(entity_id), Some(range)) = (entity.id(), entity.id_range()) {
+                    entities
+                        .entry(entity_id.to_string())
+                        .or_insert_with(|| Location {
+                            path: path.clone(),
+                            range,
+                        });
+                }
+            }
+        }
+
+        Implementations {
+            total: entities.len(),
+            locations: entities.into_values().take(limit).collect(),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+
+/// Restricts a build to some entity types.
+///
+/// Entities of other types are left out of the build without being converted
+/// or validated. Schemas are always built, so queries and references can
+/// still be checked against every type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeFilter {
+    only: Option<BTreeSet<String>>,
+    exclude: BTreeSet<String>,
+}
+
+impl TypeFilter {
+    /// Creates a filter that includes every type.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes only the given types, unless excluded as well.
+    pub fn only<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only = Some(types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Excludes the given types.
+    pub fn excluding<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude.extend(types.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether entities of a type are included.
+    pub fn includes(&self, entity_type: &str) -> bool {
+        !self.exclude.contains(entity_type)
+            && self
+                .only
+                .as_ref()
+                .is_none_or(|only| only.contains(entity_type))
+    }
+
+    /// Whether the filter includes every type.
+    pub fn is_unrestricted(&self) -> bool {
+        self.only.is_none() && self.exclude.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_filter_includes() {
+        assert!(TypeFilter::new().includes("task"));
+        assert!(TypeFilter::new().is_unrestricted());
+
+        let filter = TypeFilter::new().only(["task", "project"]);
+        assert!(filter.includes("task"));
+        assert!(!filter.includes("person"));
+
+        let filter = TypeFilter::new().excluding(["file_asset"]);
+        assert!(filter.includes("task"));
+        assert!(!filter.includes("file_asset"));
+
+        // Exclusions win over inclusions
+        let filter = TypeFilter::new().only(["task"]).excluding(["task"]);
+        assert!(!filter.includes("task"));
+        assert!(!filter.is_unrestricted());
+    }
+}
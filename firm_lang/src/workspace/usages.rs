@@ -0,0 +1,192 @@
+use std::path::PathBuf;
+
+use tree_sitter::{Node, Point, Range};
+
+use super::Workspace;
+use super::definition::{contains, find_ancestor};
+use crate::parser::dsl::{ParsedEntity, ParsedSchema, ParsedValue, parse_source};
+
+const REFERENCE_KIND: &str = "reference";
+const ENTITY_BLOCK_KIND: &str = "entity_block";
+const SCHEMA_BLOCK_KIND: &str = "schema_block";
+
+/// A range in a workspace file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+impl Location {
+    /// The 1-based line where the range starts.
+    pub fn line(&self) -> usize {
+        self.range.start_point.row + 1
+    }
+}
+
+impl Workspace {
+    /// Finds the usages of the entity or schema field at a position in a source document.
+    ///
+    /// The source is parsed as given, so it can be an unsaved editor buffer.
+    /// On an entity ID or a reference, this finds every reference to the entity
+    /// across the workspace, including inside lists and field references. On the
+    /// name of a schema field, this finds every assignment of the field in entities
+    /// of that type. The position is zero-based, with the column in bytes.
+    pub fn find_usages(
+        &self,
+        source: &str,
+        position: Point,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Ok(parsed) = parse_source(source.to_string(), None) else {
+            return Vec::new();
+        };
+        let Some(node) = parsed
+            .tree
+            .root_node()
+            .descendant_for_point_range(position, position)
+        else {
+            return Vec::new();
+        };
+
+        if let Some(reference) = find_ancestor(node, REFERENCE_KIND) {
+            return match ParsedValue::parse_reference(&parsed.source[reference.byte_range()]) {
+                Ok(ParsedValue::EntityReference {
+                    entity_type,
+                    entity_id,
+                })
+                | Ok(ParsedValue::FieldReference {
+                    entity_type,
+                    entity_id,
+                    ..
+                }) => self.entity_usages(&entity_type, &entity_id, include_declaration),
+                _ => Vec::new(),
+            };
+        }
+
+        if let Some(block) = find_ancestor(node, ENTITY_BLOCK_KIND) {
+            let entity = ParsedEntity::new(block, &parsed.source, &parsed.path);
+            if let (Some(entity_type), Some(entity_id), Some(range)) =
+                (entity.entity_type(), entity.id(), entity.id_range())
+                && contains(&range, position)
+            {
+                return self.entity_usages(entity_type, entity_id, include_declaration);
+            }
+            return Vec::new();
+        }
+
+        if let Some(block) = find_ancestor(node, SCHEMA_BLOCK_KIND) {
+            let schema = ParsedSchema::new(block, &parsed.source, &parsed.path);
+            let Some(schema_name) = schema.name() else {
+                return Vec::new();
+            };
+
+            let field_name = schema.fields().iter().find_map(|field| {
+                let range = field.name_range()?;
+                if !contains(&range, position) {
+                    return None;
+                }
+                field.name().ok()
+            });
+            if let Some(field_name) = field_name {
+                return self.field_assignments(schema_name, &field_name);
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// Finds every reference to an entity, optionally with the ID in its defining blocks.
+    pub fn entity_usages(
+        &self,
+        entity_type: &str,
+        entity_id: &str,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let mut usages = Vec::new();
+
+        for (path, file) in &self.files {
+            if include_declaration {
+                for entity in file.parsed.entities() {
+                    if entity.entity_type() == Some(entity_type)
+                        && entity.id() == Some(entity_id)
+                        && let Some(range) = entity.id_range()
+                    {
+                        usages.push(Location {
+                            path: path.clone(),
+                            range,
+                        });
+                    }
+                }
+            }
+
+            let mut references = Vec::new();
+            collect_reference_nodes(file.parsed.tree.root_node(), &mut references);
+
+            for reference in references {
+                let text = &file.parsed.source[reference.byte_range()];
+                let target = match ParsedValue::parse_reference(text) {
+                    Ok(ParsedValue::EntityReference {
+                        entity_type,
+                        entity_id,
+                    })
+                    | Ok(ParsedValue::FieldReference {
+                        entity_type,
+                        entity_id,
+                        ..
+                    }) => (entity_type, entity_id),
+                    _ => continue,
+                };
+
+                if target.0 == entity_type && target.1 == entity_id {
+                    usages.push(Location {
+                        path: path.clone(),
+                        range: reference.range(),
+                    });
+                }
+            }
+        }
+
+        usages.sort_by(|a, b| (&a.path, a.range.start_byte).cmp(&(&b.path, b.range.start_byte)));
+        usages
+    }
+
+    /// Finds every assignment of a field in entities of a type.
+    pub fn field_assignments(&self, entity_type: &str, field_name: &str) -> Vec<Location> {
+        let mut assignments = Vec::new();
+
+        for (path, file) in &self.files {
+            for entity in file.parsed.entities() {
+                if entity.entity_type() != Some(entity_type) {
+                    continue;
+                }
+
+                for field in entity.fields() {
+                    if field.id() == Some(field_name)
+                        && let Some(range) = field.id_range()
+                    {
+                        assignments.push(Location {
+                            path: path.clone(),
+                            range,
+                        });
+                    }
+                }
+            }
+        }
+
+        assignments
+    }
+}
+
+/// Collects every reference node in a tree, in source order.
+pub(super) fn collect_reference_nodes<'a>(node: Node<'a>, references: &mut Vec<Node<'a>>) {
+    if node.kind() == REFERENCE_KIND {
+        references.push(node);
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_reference_nodes(child, references);
+    }
+}
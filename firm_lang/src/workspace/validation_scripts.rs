@@ -0,0 +1,266 @@
+//! Validation scripts that schemas declare with `validate = path"./script"`.
+//!
+//! A script gets an entity as JSON on stdin. Exiting with a nonzero status
+//! rejects the entity, and each line it prints is a message about it. A line
+//! can be plain text or a JSON object like `{"field": "amount", "message": "..."}`
+//! to point at one of the entity's fields.
+
+use std::env;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use firm_core::Entity;
+use serde_json::{Map, Value, json};
+
+/// How long a validation script may run for each entity by default.
+pub const DEFAULT_SCRIPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The most output that's read from a validation script, in bytes.
+const MAX_SCRIPT_OUTPUT: u64 = 64 * 1024;
+
+/// How often a running script is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Options for running the validation scripts that schemas declare.
+///
+/// Scripts run with the permissions of the user building the workspace, so
+/// they only run when a workspace is given these options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptOptions {
+    /// How long a script may run for each entity before it's stopped.
+    pub timeout: Duration,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_SCRIPT_TIMEOUT,
+        }
+    }
+}
+
+/// A message a validation script printed about an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptMessage {
+    /// The field the message is about, if the script named one.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// What a validation script decided about an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptOutcome {
+    /// Whether the script exited successfully.
+    pub accepted: bool,
+    pub messages: Vec<ScriptMessage>,
+}
+
+/// Runs a validation script with an entity's JSON on stdin.
+///
+/// The script runs in the given directory, with only `PATH` kept from the
+/// environment. It's stopped if it runs longer than the timeout. Returns an
+/// error if the script couldn't be run or was stopped.
+pub fn run_validation_script(
+    script: &Path,
+    working_dir: &Path,
+    entity: &Entity,
+    options: &ScriptOptions,
+) -> Result<ScriptOutcome, String> {
+    let mut command = Command::new(script);
+    command
+        .current_dir(working_dir)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(path) = env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+
+    // Write and read in the background, so a script that doesn't read its input can't block us
+    let input = entity_json(entity).to_string();
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || stdin.write_all(input.as_bytes()));
+    }
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    let deadline = Instant::now() + options.timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!(
+                "timed out after {} second(s)",
+                options.timeout.as_secs_f64()
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stdout = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+    let mut messages: Vec<ScriptMessage> = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_message)
+        .collect();
+
+    // A rejection always says why, falling back to what the script wrote to stderr
+    if !status.success() && messages.is_empty() {
+        let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+        let message = match stderr.trim() {
+            "" => format!("exited with {}", status),
+            stderr => stderr.to_string(),
+        };
+        messages.push(ScriptMessage {
+            field: None,
+            message,
+        });
+    }
+
+    Ok(ScriptOutcome {
+        accepted: status.success(),
+        messages,
+    })
+}
+
+/// Reads up to `MAX_SCRIPT_OUTPUT` bytes from a script's output on another thread.
+fn read_in_background<R: Read + Send + 'static>(reader: R) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = reader.take(MAX_SCRIPT_OUTPUT).read_to_end(&mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    })
+}
+
+/// Parses a line of script output, as a JSON message object or plain text.
+fn parse_message(line: &str) -> ScriptMessage {
+    if let Ok(Value::Object(object)) = serde_json::from_str::<Value>(line)
+        && let Some(message) = object.get("message").and_then(Value::as_str)
+    {
+        return ScriptMessage {
+            field: object
+                .get("field")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            message: message.to_string(),
+        };
+    }
+
+    ScriptMessage {
+        field: None,
+        message: line.to_string(),
+    }
+}
+
+/// The JSON a validation script gets: the entity's ID, type and fields by name.
+fn entity_json(entity: &Entity) -> Value {
+    let fields: Map<String, Value> = entity
+        .fields
+        .iter()
+        .map(|(field_id, value)| (field_id.to_string(), value.to_json()))
+        .collect();
+
+    json!({
+        "id": entity.id.as_str(),
+        "type": entity.entity_type.as_str(),
+        "fields": fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use firm_core::{EntityId, EntityType, FieldId};
+    use std::fs;
+
+    /// Writes an executable shell script to a temporary directory.
+    fn script(body: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("validate.sh");
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        (dir, path)
+    }
+
+    fn invoice() -> Entity {
+        Entity::new(EntityId::new("invoice.one"), EntityType::new("invoice"))
+            .with_field(FieldId::new("amount"), -5)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_gets_entity_and_reports_messages() {
+        let (dir, path) = script(
+            r#"input=$(cat)
+case "$input" in *'"amount":-5'*) ;; *) exit 3 ;; esac
+echo '{"field": "amount", "message": "must be positive"}'
+echo 'and one more thing'
+exit 1"#,
+        );
+
+        let outcome =
+            run_validation_script(&path, dir.path(), &invoice(), &ScriptOptions::default())
+                .unwrap();
+        assert!(!outcome.accepted);
+        assert_eq!(
+            outcome.messages,
+            vec![
+                ScriptMessage {
+                    field: Some("amount".to_string()),
+                    message: "must be positive".to_string(),
+                },
+                ScriptMessage {
+                    field: None,
+                    message: "and one more thing".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_rejection_falls_back_to_stderr() {
+        let (dir, path) = script("echo 'bad invoice' >&2\nexit 2");
+
+        let outcome =
+            run_validation_script(&path, dir.path(), &invoice(), &ScriptOptions::default())
+                .unwrap();
+        assert!(!outcome.accepted);
+        assert_eq!(outcome.messages[0].message, "bad invoice");
+
+        let (dir, path) = script("exit 0");
+        let outcome =
+            run_validation_script(&path, dir.path(), &invoice(), &ScriptOptions::default())
+                .unwrap();
+        assert!(outcome.accepted);
+        assert!(outcome.messages.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_timeout() {
+        let (dir, path) = script("sleep 5");
+        let options = ScriptOptions {
+            timeout: Duration::from_millis(100),
+        };
+
+        let started = Instant::now();
+        let error = run_validation_script(&path, dir.path(), &invoice(), &options).unwrap_err();
+        assert_eq!(error, "timed out after 0.1 second(s)");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
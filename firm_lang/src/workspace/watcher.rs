@@ -0,0 +1,230 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Component, Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher, event::ModifyKind};
+
+use super::{
+    WorkspaceError,
+    ignore::IgnoreRules,
+    io::{is_firm_file, is_hidden},
+};
+
+/// A change to a firm file in a watched workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceChange {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+impl WorkspaceChange {
+    /// Gets the path of the changed file.
+    pub fn path(&self) -> &Path {
+        match self {
+            WorkspaceChange::Created(path)
+            | WorkspaceChange::Modified(path)
+            | WorkspaceChange::Removed(path) => path,
+        }
+    }
+}
+
+/// A change that hasn't settled yet, with the time it was last touched.
+struct PendingChange {
+    change: WorkspaceChange,
+    last_seen: Instant,
+}
+
+/// Watches a workspace directory for changes to firm files.
+///
+/// Events for the same file are coalesced and only reported once the file
+/// has been quiet for the debounce duration, so an editor saving in several
+/// steps produces a single change. Hidden files and directories (like `.git`),
+/// paths matching `.firmignore` files and non-firm files are ignored, the
+/// same as when loading the workspace.
+pub struct WorkspaceWatcher {
+    root_path: PathBuf,
+    debounce: Duration,
+    pending: HashMap<PathBuf, PendingChange>,
+    receiver: Receiver<notify::Result<Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl WorkspaceWatcher {
+    /// The default time a file must be quiet before its change is reported.
+    pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+    /// Starts watching a workspace directory and its subdirectories.
+    pub fn new(directory_path: &Path) -> Result<Self, WorkspaceError> {
+        let (sender, receiver) = mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|err| WorkspaceError::IoError(io::Error::other(err)))?;
+        watcher
+            .watch(directory_path, RecursiveMode::Recursive)
+            .map_err(|err| WorkspaceError::IoError(io::Error::other(err)))?;
+
+        Ok(Self {
+            root_path: directory_path.to_path_buf(),
+            debounce: Self::DEFAULT_DEBOUNCE,
+            pending: HashMap::new(),
+            receiver,
+            _watcher: watcher,
+        })
+    }
+
+    /// Sets how long a file must be quiet before its change is reported.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Returns the settled changes since the last call without blocking.
+    pub fn poll(&mut self) -> Vec<WorkspaceChange> {
+        while let Ok(event) = self.receiver.try_recv() {
+            self.record(event);
+        }
+
+        self.take_settled()
+    }
+
+    /// Blocks until some changes have settled, or the timeout expires.
+    ///
+    /// Returns an empty list if nothing changed within the timeout.
+    pub fn wait(&mut self, timeout: Duration) -> Vec<WorkspaceChange> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let settled = self.poll();
+            let now = Instant::now();
+            if !settled.is_empty() || now >= deadline {
+                return settled;
+            }
+
+            // Wake up for the next event, or when the oldest pending change settles
+            let next_settle = self
+                .pending
+                .values()
+                .map(|pending| pending.last_seen + self.debounce)
+                .min()
+                .unwrap_or(deadline);
+            let wake_at = next_settle.min(deadline);
+
+            match self
+                .receiver
+                .recv_timeout(wake_at.saturating_duration_since(now))
+            {
+                Ok(event) => self.record(event),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return self.take_settled(),
+            }
+        }
+    }
+
+    /// Records a raw watcher event as pending changes.
+    fn record(&mut self, event: notify::Result<Event>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                log::warn!("Workspace watcher error: {}", err);
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        for path in event.paths {
+            if !self.is_watched(&path) {
+                continue;
+            }
+
+            let Some(change) = classify(&event.kind, path.clone()) else {
+                continue;
+            };
+
+            // A file created and then written is still a new file
+            let change = match (self.pending.get(&path), change) {
+                (
+                    Some(PendingChange {
+                        change: WorkspaceChange::Created(_),
+                        ..
+                    }),
+                    WorkspaceChange::Modified(path),
+                ) => WorkspaceChange::Created(path),
+                (_, change) => change,
+            };
+
+            self.pending.insert(
+                path,
+                PendingChange {
+                    change,
+                    last_seen: now,
+                },
+            );
+        }
+    }
+
+    /// Removes and returns the pending changes that have been quiet long enough.
+    fn take_settled(&mut self) -> Vec<WorkspaceChange> {
+        let now = Instant::now();
+        let settled_paths: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.last_seen) >= self.debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let mut changes: Vec<WorkspaceChange> = settled_paths
+            .iter()
+            .filter_map(|path| self.pending.remove(path))
+            .map(|pending| pending.change)
+            .collect();
+
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+
+    /// Returns true for firm files that `load_directory` would load.
+    ///
+    /// That's files that aren't hidden or in hidden directories, and aren't ignored.
+    fn is_watched(&self, path: &Path) -> bool {
+        let relative_path = path.strip_prefix(&self.root_path).unwrap_or(path);
+        let in_hidden = relative_path.components().any(|component| match component {
+            Component::Normal(name) => is_hidden(name),
+            _ => false,
+        });
+        if in_hidden || !is_firm_file(path) {
+            return false;
+        }
+
+        // Ignore files are read on every event, so edits to them apply right away
+        match IgnoreRules::for_path(&self.root_path, path) {
+            Ok(rules) => !rules.is_ignored_in(&self.root_path, path, false),
+            Err(err) => {
+                log::warn!("Failed to read ignore rules for {}: {}", path.display(), err);
+                true
+            }
+        }
+    }
+}
+
+/// Maps a watcher event kind to a workspace change.
+fn classify(kind: &EventKind, path: PathBuf) -> Option<WorkspaceChange> {
+    match kind {
+        EventKind::Create(_) => Some(WorkspaceChange::Created(path)),
+        EventKind::Remove(_) => Some(WorkspaceChange::Removed(path)),
+        // Renames report both sides, so check which side this path is on
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            if path.exists() {
+                Some(WorkspaceChange::Created(path))
+            } else {
+                Some(WorkspaceChange::Removed(path))
+            }
+        }
+        EventKind::Modify(_) | EventKind::Any => Some(WorkspaceChange::Modified(path)),
+        EventKind::Access(_) | EventKind::Other => None,
+    }
+}
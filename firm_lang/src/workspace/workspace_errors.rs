@@ -10,7 +10,40 @@ pub enum WorkspaceError {
     IoError(io::Error),
     ParseError(PathBuf, String),
     ValidationError(PathBuf, String),
+    /// A list item has the wrong type, at a 1-based line.
+    InvalidListItem(PathBuf, usize, String),
+    /// A schema's validation script rejected an entity, at a 1-based line.
+    ScriptRejected(PathBuf, usize, String),
     MissingSchemaError(PathBuf, EntityType),
+    /// Another writer held the workspace lock file for too long, with what it says about its holder.
+    Locked(PathBuf, String),
+    /// A file changed since the content a change was derived from was read.
+    Conflict(PathBuf, String),
+}
+
+impl WorkspaceError {
+    /// The file the error is about, if it's about a single file.
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            WorkspaceError::IoError(_) => None,
+            WorkspaceError::ParseError(path, _)
+            | WorkspaceError::ValidationError(path, _)
+            | WorkspaceError::InvalidListItem(path, _, _)
+            | WorkspaceError::ScriptRejected(path, _, _)
+            | WorkspaceError::MissingSchemaError(path, _)
+            | WorkspaceError::Locked(path, _)
+            | WorkspaceError::Conflict(path, _) => Some(path),
+        }
+    }
+
+    /// The 1-based line the error is about, if it's known.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            WorkspaceError::InvalidListItem(_, line, _)
+            | WorkspaceError::ScriptRejected(_, line, _) => Some(*line),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for WorkspaceError {
@@ -31,6 +64,34 @@ impl fmt::Display for WorkspaceError {
                 path_buf.display(),
                 error
             ),
+            WorkspaceError::InvalidListItem(path_buf, line, error)
+            | WorkspaceError::ScriptRejected(path_buf, line, error) => write!(
+                f,
+                "Workspace file at {} was invalid at line {}: {}",
+                path_buf.display(),
+                line,
+                error
+            ),
+            WorkspaceError::Locked(path_buf, holder) => {
+                let holder = match holder.as_str() {
+                    "" => String::new(),
+                    holder => format!(" ({})", holder),
+                };
+                write!(
+                    f,
+                    "The workspace is locked by another writer{}. Try again once it's done, \
+                     or delete {} if no other Firm process is running.",
+                    holder,
+                    path_buf.display()
+                )
+            }
+            WorkspaceError::Conflict(path_buf, reason) => write!(
+                f,
+                "Workspace file at {} changed since it was read: {}. \
+                 Read it again and reapply the change.",
+                path_buf.display(),
+                reason
+            ),
             WorkspaceError::MissingSchemaError(path_buf, entity_type) => {
                 let is_default_schema = is_default_schema_type(entity_type);
 
@@ -0,0 +1,168 @@
+use std::{fmt, path::PathBuf};
+
+use firm_core::EntityId;
+
+/// Defines the non-fatal problems found while building a workspace.
+///
+/// Unlike `WorkspaceError`, warnings don't stop the build. They're collected
+/// on the `WorkspaceBuild` so callers can decide how to surface them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkspaceWarning {
+    DuplicateField {
+        path: PathBuf,
+        entity_id: EntityId,
+        field: String,
+        first_line: usize,
+        duplicate_line: usize,
+    },
+    InvalidQuery {
+        path: PathBuf,
+        entity_id: EntityId,
+        field: String,
+        line: usize,
+        message: String,
+    },
+    InvalidNamedQuery {
+        path: PathBuf,
+        name: String,
+        line: usize,
+        message: String,
+    },
+    MissingPath {
+        path: PathBuf,
+        entity_id: EntityId,
+        field: String,
+        line: usize,
+        target: PathBuf,
+    },
+    UnusedAllowedValues {
+        path: PathBuf,
+        schema: String,
+        field: String,
+        line: usize,
+    },
+    ScriptMessage {
+        path: PathBuf,
+        entity_id: EntityId,
+        script: PathBuf,
+        line: usize,
+        message: String,
+    },
+    SkippedScript {
+        path: PathBuf,
+        schema: String,
+        script: PathBuf,
+        line: usize,
+    },
+}
+
+impl WorkspaceWarning {
+    /// The file the warning is about.
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            WorkspaceWarning::DuplicateField { path, .. }
+            | WorkspaceWarning::InvalidQuery { path, .. }
+            | WorkspaceWarning::InvalidNamedQuery { path, .. }
+            | WorkspaceWarning::MissingPath { path, .. }
+            | WorkspaceWarning::UnusedAllowedValues { path, .. }
+            | WorkspaceWarning::ScriptMessage { path, .. }
+            | WorkspaceWarning::SkippedScript { path, .. } => path,
+        }
+    }
+
+    /// The 1-based line the warning points at.
+    pub fn line(&self) -> usize {
+        match self {
+            WorkspaceWarning::DuplicateField { duplicate_line, .. } => *duplicate_line,
+            WorkspaceWarning::InvalidQuery { line, .. }
+            | WorkspaceWarning::InvalidNamedQuery { line, .. }
+            | WorkspaceWarning::MissingPath { line, .. }
+            | WorkspaceWarning::UnusedAllowedValues { line, .. }
+            | WorkspaceWarning::ScriptMessage { line, .. }
+            | WorkspaceWarning::SkippedScript { line, .. } => *line,
+        }
+    }
+
+    /// A stable identifier for the kind of warning (e.g. "duplicate-field").
+    pub fn code(&self) -> &'static str {
+        match self {
+            WorkspaceWarning::DuplicateField { .. } => "duplicate-field",
+            WorkspaceWarning::InvalidQuery { .. } => "invalid-query",
+            WorkspaceWarning::InvalidNamedQuery { .. } => "invalid-named-query",
+            WorkspaceWarning::MissingPath { .. } => "missing-path",
+            WorkspaceWarning::UnusedAllowedValues { .. } => "unused-allowed-values",
+            WorkspaceWarning::ScriptMessage { .. } => "script-message",
+            WorkspaceWarning::SkippedScript { .. } => "skipped-script",
+        }
+    }
+
+    /// Describes the warning, without its location.
+    pub fn message(&self) -> String {
+        match self {
+            WorkspaceWarning::DuplicateField {
+                entity_id,
+                field,
+                first_line,
+                ..
+            } => format!(
+                "field '{}' of entity '{}' already set at line {} (the last value is used)",
+                field, entity_id, first_line
+            ),
+            WorkspaceWarning::InvalidQuery {
+                entity_id,
+                field,
+                message,
+                ..
+            } => format!(
+                "query in field '{}' of entity '{}' is invalid: {}",
+                field, entity_id, message
+            ),
+            WorkspaceWarning::InvalidNamedQuery { name, message, .. } => {
+                format!("named query '{}' is invalid: {}", name, message)
+            }
+            WorkspaceWarning::MissingPath {
+                entity_id,
+                field,
+                target,
+                ..
+            } => format!(
+                "path in field '{}' of entity '{}' points at a missing file: '{}'",
+                field,
+                entity_id,
+                target.display()
+            ),
+            WorkspaceWarning::UnusedAllowedValues { schema, field, .. } => format!(
+                "field '{}' of schema '{}' has allowed_values but isn't an enum (they're ignored)",
+                field, schema
+            ),
+            WorkspaceWarning::ScriptMessage {
+                entity_id,
+                script,
+                message,
+                ..
+            } => format!(
+                "validation script '{}' on entity '{}': {}",
+                script.display(),
+                entity_id,
+                message
+            ),
+            WorkspaceWarning::SkippedScript { schema, script, .. } => format!(
+                "validation script '{}' of schema '{}' wasn't run (scripts aren't allowed)",
+                script.display(),
+                schema
+            ),
+        }
+    }
+}
+
+impl fmt::Display for WorkspaceWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.path().display(),
+            self.line(),
+            self.message()
+        )
+    }
+}
@@ -612,4 +612,58 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn test_duplicate_field_last_wins() {
+        let source = r#"
+            task write_docs {
+                status = "todo"
+                name = "Write docs"
+                status = "done"
+            }
+        "#;
+
+        let parsed = parse_source(String::from(source), None).unwrap();
+        let entities = parsed.entities();
+        let entity: Entity = (&entities[0]).try_into().unwrap();
+
+        assert_eq!(entity.fields.len(), 2);
+        assert_eq!(entity.fields[0].0, FieldId("status".to_string()));
+        assert_eq!(
+            entity.get_field(&FieldId("status".to_string())),
+            Some(&FieldValue::String("done".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_dates_without_timezone_use_default_timezone() {
+        use chrono::FixedOffset;
+        use firm_lang::convert::to_entity::convert_entity_in;
+        use firm_lang::parser::dsl::ParsedValue;
+
+        let source = r#"
+            task launch {
+                starts = 2025-09-30
+                reviewed = 2025-09-30 at 09:00
+                shipped = 2025-09-30 at 09:00 UTC-5
+            }
+        "#;
+        let parsed = parse_source(String::from(source), None).unwrap();
+        let entities = parsed.entities();
+
+        let timezone = ParsedValue::parse_timezone("UTC+2").unwrap();
+        assert_eq!(timezone, FixedOffset::east_opt(2 * 3600).unwrap());
+        let entity = convert_entity_in(&entities[0], timezone).unwrap();
+
+        let rendered = |field: &str| match entity.get_field(&FieldId::new(field)) {
+            Some(FieldValue::DateTime(value)) => value.to_rfc3339(),
+            other => panic!("Expected DateTime for {}, got {:?}", field, other),
+        };
+        assert_eq!(rendered("starts"), "2025-09-30T00:00:00+02:00");
+        assert_eq!(rendered("reviewed"), "2025-09-30T09:00:00+02:00");
+        assert_eq!(rendered("shipped"), "2025-09-30T09:00:00-05:00");
+
+        assert!(ParsedValue::parse_timezone("UTC").is_ok());
+        assert!(ParsedValue::parse_timezone("CET").is_err());
+    }
 }
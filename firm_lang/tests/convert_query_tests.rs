@@ -18,6 +18,17 @@ fn test_convert_simple_query() {
     assert!(matches!(query.operations[0], QueryOperation::Limit(5)));
 }
 
+#[test]
+fn test_convert_type_with_subtypes() {
+    let parsed = parse_query("from person+").unwrap();
+    let query: Query = parsed.try_into().unwrap();
+
+    assert_eq!(
+        query.from,
+        EntitySelector::TypeWithSubtypes(EntityType::new("person"))
+    );
+}
+
 #[test]
 fn test_convert_wildcard_selector() {
     let query_str = "from *";
@@ -104,11 +115,11 @@ fn test_convert_related_with_degree() {
     assert_eq!(query.operations.len(), 1);
     if let QueryOperation::Related {
         degrees,
-        entity_type,
+        entity_types,
     } = &query.operations[0]
     {
         assert_eq!(*degrees, 2);
-        assert!(entity_type.is_none());
+        assert!(entity_types.is_empty());
     } else {
         panic!("Expected Related operation");
     }
@@ -123,12 +134,14 @@ fn test_convert_related_with_type_filter() {
     assert_eq!(query.operations.len(), 1);
     if let QueryOperation::Related {
         degrees,
-        entity_type,
+        entity_types,
     } = &query.operations[0]
     {
         assert_eq!(*degrees, 1); // Default degree
-        assert!(entity_type.is_some());
-        assert_eq!(entity_type.as_ref().unwrap(), &EntityType::new("task"));
+        assert_eq!(
+            entity_types,
+            &vec![EntitySelector::Type(EntityType::new("task"))]
+        );
     } else {
         panic!("Expected Related operation");
     }
@@ -143,15 +156,52 @@ fn test_convert_related_with_degree_and_type() {
     assert_eq!(query.operations.len(), 1);
     if let QueryOperation::Related {
         degrees,
-        entity_type,
+        entity_types,
     } = &query.operations[0]
     {
         assert_eq!(*degrees, 3);
-        assert!(entity_type.is_some());
-        assert_eq!(entity_type.as_ref().unwrap(), &EntityType::new("task"));
+        assert_eq!(
+            entity_types,
+            &vec![EntitySelector::Type(EntityType::new("task"))]
+        );
+    } else {
+        panic!("Expected Related operation");
+    }
+}
+
+#[test]
+fn test_convert_related_with_several_types() {
+    let parsed = parse_query("from person | related(2) [task, project+]").unwrap();
+    let query: Query = parsed.try_into().unwrap();
+
+    if let QueryOperation::Related {
+        degrees,
+        entity_types,
+    } = &query.operations[0]
+    {
+        assert_eq!(*degrees, 2);
+        assert_eq!(
+            entity_types,
+            &vec![
+                EntitySelector::Type(EntityType::new("task")),
+                EntitySelector::TypeWithSubtypes(EntityType::new("project")),
+            ]
+        );
     } else {
         panic!("Expected Related operation");
     }
+    assert_eq!(
+        query.to_string(),
+        "from person | related(2) [task, project+]"
+    );
+
+    // A wildcard keeps every type
+    let parsed = parse_query("from person | related *").unwrap();
+    let query: Query = parsed.try_into().unwrap();
+    assert!(matches!(
+        &query.operations[0],
+        QueryOperation::Related { entity_types, .. } if entity_types.is_empty()
+    ));
 }
 
 #[test]
@@ -202,3 +252,44 @@ fn test_convert_reference_value() {
         panic!("Expected Where operation");
     }
 }
+
+#[test]
+fn test_convert_group_with_aggregate_order() {
+    let parsed = parse_query("from task | group status | count | order count desc").unwrap();
+    let query: Query = parsed.try_into().unwrap();
+
+    assert_eq!(
+        query.group_by,
+        Some(FieldRef::Regular(firm_core::FieldId::new("status")))
+    );
+    assert_eq!(query.aggregate_order, Some(SortDirection::Descending));
+}
+
+#[test]
+fn test_convert_aggregate_order_must_match_aggregation() {
+    let parsed = parse_query("from task | group status | sum hours | order count desc").unwrap();
+    let result: Result<Query, _> = parsed.try_into();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_convert_limit_after_grouped_aggregation() {
+    let parsed =
+        parse_query("from task | group status | count | order count desc | limit 5").unwrap();
+    let query: Query = parsed.try_into().unwrap();
+
+    assert!(query.operations.is_empty());
+    assert_eq!(query.aggregate_limit, Some(5));
+}
+
+#[test]
+fn test_limit_after_scalar_aggregation_fails_validation() {
+    let parsed = parse_query("from task | count | limit 5").unwrap();
+    let query: Query = parsed.try_into().unwrap();
+
+    assert!(matches!(
+        query.validate(&[]),
+        Err(firm_core::graph::QueryError::InvalidAggregation { .. })
+    ));
+}
@@ -191,3 +191,293 @@ fn test_convert_schema_with_enum() {
     assert!(allowed.contains(&"customer".to_string()));
     assert!(allowed.contains(&"partner".to_string()));
 }
+
+#[test]
+fn test_convert_schema_rejects_enum_without_allowed_values() {
+    let source = r#"
+        schema account {
+            field {
+                name = "status"
+                type = "enum"
+                required = true
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let result: Result<EntitySchema, SchemaConversionError> = (&schemas[0]).try_into();
+    match result {
+        Err(SchemaConversionError::MissingAllowedValues { field, line }) => {
+            assert_eq!(field, "status");
+            assert_eq!(line, 3);
+        }
+        other => panic!("Expected MissingAllowedValues, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_convert_schema_rejects_enum_with_empty_allowed_values() {
+    let source = r#"
+        schema account {
+            field {
+                name = "status"
+                type = "enum"
+                allowed_values = []
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let result: Result<EntitySchema, SchemaConversionError> = (&schemas[0]).try_into();
+    assert!(matches!(
+        result,
+        Err(SchemaConversionError::MissingAllowedValues { .. })
+    ));
+}
+
+#[test]
+fn test_convert_schema_with_case_sensitive_enum() {
+    let source = r#"
+        schema ticket {
+            field {
+                name = "priority"
+                type = "enum"
+                allowed_values = ["P1", "P2"]
+                case_sensitive = true
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let schema: EntitySchema = (&schemas[0]).try_into().unwrap();
+    let priority_field = &schema.fields[&FieldId("priority".to_string())];
+    assert!(priority_field.case_sensitive);
+    assert_eq!(
+        priority_field.allowed_values(),
+        Some(&vec!["P1".to_string(), "P2".to_string()])
+    );
+}
+
+#[test]
+fn test_convert_schema_with_extra_precision() {
+    let source = r#"
+        schema trade {
+            field {
+                name = "price"
+                type = "currency"
+                extra_precision = true
+            }
+            field {
+                name = "fee"
+                type = "currency"
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let schema: EntitySchema = (&schemas[0]).try_into().unwrap();
+    assert!(schema.fields[&FieldId("price".to_string())].extra_precision);
+    assert!(!schema.fields[&FieldId("fee".to_string())].extra_precision);
+}
+
+#[test]
+fn test_convert_schema_rejects_allowed_values_differing_by_case() {
+    let source = r#"
+        schema ticket {
+            field {
+                name = "priority"
+                type = "enum"
+                allowed_values = ["high", "High"]
+                case_sensitive = true
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let result: Result<EntitySchema, SchemaConversionError> = (&schemas[0]).try_into();
+    match result {
+        Err(SchemaConversionError::AmbiguousAllowedValue { value, .. }) => {
+            assert_eq!(value, "High");
+        }
+        other => panic!("Expected AmbiguousAllowedValue, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_convert_schema_with_list_item_type() {
+    let source = r#"
+        schema project {
+            field {
+                name = "urls"
+                type = "list"
+                item_type = "string"
+            }
+            field {
+                name = "tags"
+                type = "list"
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let schema: EntitySchema = (&schemas[0]).try_into().unwrap();
+    assert_eq!(
+        schema.fields[&FieldId("urls".to_string())].item_type(),
+        Some(&FieldType::String)
+    );
+    assert_eq!(
+        schema.fields[&FieldId("tags".to_string())].item_type(),
+        None
+    );
+}
+
+#[test]
+fn test_convert_schema_with_target() {
+    let source = r#"
+        schema account {
+            field {
+                name = "owner"
+                type = "reference"
+                target = "person"
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schemas = parsed.schemas();
+
+    let schema: EntitySchema = (&schemas[0]).try_into().unwrap();
+    assert_eq!(
+        schema.fields[&FieldId("owner".to_string())].target(),
+        Some(&EntityType::new("person"))
+    );
+}
+
+#[test]
+fn test_convert_schema_rejects_invalid_item_types() {
+    let unknown = r#"
+        schema project {
+            field {
+                name = "urls"
+                type = "list"
+                item_type = "url"
+            }
+        }
+    "#;
+    let parsed = parse_source(String::from(unknown), None).unwrap();
+    let result: Result<EntitySchema, SchemaConversionError> = (&parsed.schemas()[0]).try_into();
+    assert!(matches!(
+        result,
+        Err(SchemaConversionError::UnknownFieldType(field_type)) if field_type == "url"
+    ));
+
+    let not_a_list = r#"
+        schema project {
+            field {
+                name = "url"
+                type = "string"
+                item_type = "string"
+            }
+        }
+    "#;
+    let parsed = parse_source(String::from(not_a_list), None).unwrap();
+    let result: Result<EntitySchema, SchemaConversionError> = (&parsed.schemas()[0]).try_into();
+    assert!(matches!(
+        result,
+        Err(SchemaConversionError::ItemTypeOnNonList { line: 3, .. })
+    ));
+
+    let nested = r#"
+        schema project {
+            field {
+                name = "matrix"
+                type = "list"
+                item_type = "list"
+            }
+        }
+    "#;
+    let parsed = parse_source(String::from(nested), None).unwrap();
+    let result: Result<EntitySchema, SchemaConversionError> = (&parsed.schemas()[0]).try_into();
+    assert!(matches!(
+        result,
+        Err(SchemaConversionError::UnsupportedItemType {
+            item_type: FieldType::List,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_convert_schema_with_computed_field() {
+    let source = r#"
+        schema goal {
+            field {
+                name = "done"
+                type = "integer"
+                required = true
+            }
+            computed {
+                name = "remaining"
+                expr = "10 - done"
+            }
+        }
+    "#;
+
+    let parsed = parse_source(String::from(source), None).unwrap();
+    let schema: EntitySchema = (&parsed.schemas()[0]).try_into().unwrap();
+
+    assert_eq!(schema.fields.len(), 1);
+    let computed = &schema.computed[&FieldId("remaining".to_string())];
+    assert_eq!(computed.source, "10 - done");
+    assert_eq!(computed.order, 1);
+    assert_eq!(computed.expression.fields(), vec![&FieldId::new("done")]);
+}
+
+#[test]
+fn test_convert_schema_rejects_invalid_computed_fields() {
+    let invalid = r#"
+        schema goal {
+            computed {
+                name = "remaining"
+                expr = "10 - "
+            }
+        }
+    "#;
+    let parsed = parse_source(String::from(invalid), None).unwrap();
+    let result: Result<EntitySchema, SchemaConversionError> = (&parsed.schemas()[0]).try_into();
+    assert!(matches!(
+        result,
+        Err(SchemaConversionError::InvalidExpression { line: 3, .. })
+    ));
+
+    let conflict = r#"
+        schema goal {
+            field {
+                name = "done"
+                type = "integer"
+            }
+            computed {
+                name = "done"
+                expr = "1 + 1"
+            }
+        }
+    "#;
+    let parsed = parse_source(String::from(conflict), None).unwrap();
+    let result: Result<EntitySchema, SchemaConversionError> = (&parsed.schemas()[0]).try_into();
+    assert!(matches!(
+        result,
+        Err(SchemaConversionError::ComputedFieldConflict { line: 7, .. })
+    ));
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use chrono::{Datelike, Offset, Timelike};
-    use firm_lang::parser::dsl::{ParsedValue, parse_source};
+    use firm_lang::parser::dsl::{ParsedValue, ValueParseError, parse_source};
 
     #[test]
     fn test_basic_entity_parsing() {
@@ -605,4 +605,103 @@ mod tests {
 
         assert!(fields[0].value().is_err());
     }
+
+    #[test]
+    fn test_duplicate_fields_detected() {
+        let source = r#"task write_docs {
+            name = "Write docs"
+            status = "todo"
+            priority = 2
+            status = "done"
+        }"#;
+        let parsed = parse_source(String::from(source), None).unwrap();
+        let entities = parsed.entities();
+        let duplicates = entities[0].duplicate_fields();
+
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].name, "status");
+        assert_eq!(duplicates[0].first_line(), 3);
+        assert_eq!(duplicates[0].duplicate_line(), 5);
+    }
+
+    #[test]
+    fn test_no_duplicate_fields() {
+        let source = r#"task write_docs {
+            name = "Write docs"
+            status = "todo"
+        }"#;
+        let parsed = parse_source(String::from(source), None).unwrap();
+        let entities = parsed.entities();
+
+        assert!(entities[0].duplicate_fields().is_empty());
+    }
+
+    #[test]
+    fn test_string_escaped_quotes() {
+        let source = r#"person john { name = "John \"JD\" Doe" }"#;
+        let parsed = parse_source(String::from(source), None).unwrap();
+        let entities = parsed.entities();
+        let fields = entities[0].fields();
+
+        assert_eq!(
+            fields[0].value(),
+            Ok(ParsedValue::String("John \"JD\" Doe".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_string_escape_sequences() {
+        let cases = [
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""back\\slash""#, "back\\slash"),
+            (r#""line\nbreak""#, "line\nbreak"),
+            (r#""tab\there""#, "tab\there"),
+            (r#""ends with \\""#, "ends with \\"),
+        ];
+
+        for (raw, expected) in cases {
+            assert_eq!(
+                ParsedValue::parse_string(raw),
+                Ok(ParsedValue::String(expected.to_string())),
+                "Unexpected value for {}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn test_string_unknown_escape_is_kept() {
+        assert_eq!(
+            ParsedValue::parse_string(r#""C:\docs""#),
+            Ok(ParsedValue::String("C:\\docs".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_string_multiline_keeps_backslashes() {
+        assert_eq!(
+            ParsedValue::parse_string(r#""""a\nb""""#),
+            Ok(ParsedValue::String("a\\nb".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_string_trailing_backslash_is_an_error() {
+        let result = ParsedValue::parse_string(r#""C:\docs\""#);
+
+        assert_eq!(
+            result,
+            Err(ValueParseError::InvalidEscape {
+                source: r#""C:\docs\""#.to_string(),
+                line: 1,
+                column: 9,
+            })
+        );
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("backslash that doesn't escape anything at line 1, column 9")
+        );
+    }
 }
@@ -1,8 +1,9 @@
 //! Tests for query language parsing
 
 use firm_lang::parser::query::{
-    ParsedAggregation, ParsedCombinator, ParsedDirection, ParsedEntitySelector, ParsedField,
-    ParsedOperation, ParsedQueryValue, parse_query,
+    ParsedAggregateOrder, ParsedAggregation, ParsedCombinator, ParsedDirection,
+    ParsedEntitySelector, ParsedField, ParsedOperation, ParsedQueryValue, parse_expression,
+    parse_query, syntax_error_offset,
 };
 
 #[test]
@@ -29,6 +30,20 @@ fn test_parse_wildcard() {
     assert_eq!(query.from.selector, ParsedEntitySelector::Wildcard);
 }
 
+#[test]
+fn test_parse_type_with_subtypes() {
+    let query = parse_query("from person+ | limit 5").unwrap();
+    assert_eq!(
+        query.from.selector,
+        ParsedEntitySelector::TypeWithSubtypes("person".to_string())
+    );
+    assert_eq!(query.from.selector.to_string(), "person+");
+
+    // The marker belongs to the type name
+    assert!(parse_query("from person +").is_err());
+    assert!(parse_query("from project | related person +").is_err());
+}
+
 #[test]
 fn test_parse_related_with_degree() {
     let query_str = "from project | related(2) task";
@@ -36,15 +51,44 @@ fn test_parse_related_with_degree() {
     assert!(result.is_ok());
 
     let query = result.unwrap();
-    if let Some(ParsedOperation::Related { degree, selector }) = query.operations.first() {
+    if let Some(ParsedOperation::Related { degree, selectors }) = query.operations.first() {
+        assert_eq!(*degree, Some(2));
+        assert_eq!(
+            *selectors,
+            vec![ParsedEntitySelector::Type("task".to_string())]
+        );
+    } else {
+        panic!("Expected Related operation");
+    }
+}
+
+#[test]
+fn test_parse_related_with_several_types() {
+    let query = parse_query("from person | related(2) [task, project+] | limit 5").unwrap();
+
+    if let Some(ParsedOperation::Related { degree, selectors }) = query.operations.first() {
         assert_eq!(*degree, Some(2));
         assert_eq!(
-            *selector,
-            Some(ParsedEntitySelector::Type("task".to_string()))
+            *selectors,
+            vec![
+                ParsedEntitySelector::Type("task".to_string()),
+                ParsedEntitySelector::TypeWithSubtypes("project".to_string()),
+            ]
         );
     } else {
         panic!("Expected Related operation");
     }
+
+    let query = parse_query("from project | related person+").unwrap();
+    assert!(matches!(
+        query.operations.first(),
+        Some(ParsedOperation::Related { selectors, .. })
+            if *selectors == vec![ParsedEntitySelector::TypeWithSubtypes("person".to_string())]
+    ));
+
+    assert!(parse_query("from person | related []").is_err());
+    assert!(parse_query("from person | related [task,]").is_err());
+    assert!(parse_query("from person | related [*]").is_err());
 }
 
 #[test]
@@ -307,3 +351,134 @@ fn test_parse_query_without_aggregation_unchanged() {
     assert_eq!(query.aggregation, None);
     assert_eq!(query.operations.len(), 1);
 }
+
+// --- Grouping parsing tests ---
+
+#[test]
+fn test_parse_group_with_aggregate_order() {
+    let query = parse_query("from task | group status | count | order count desc").unwrap();
+    assert_eq!(
+        query.group_by,
+        Some(ParsedField::Regular("status".to_string()))
+    );
+    assert_eq!(query.aggregation, Some(ParsedAggregation::Count(None)));
+    assert_eq!(
+        query.aggregate_order,
+        Some(ParsedAggregateOrder {
+            aggregate: "count".to_string(),
+            direction: ParsedDirection::Descending,
+        })
+    );
+}
+
+#[test]
+fn test_parse_group_by_metadata_after_operations() {
+    let query = parse_query("from * | where priority > 1 | group @type | sum priority").unwrap();
+    assert_eq!(query.operations.len(), 1);
+    assert_eq!(query.group_by, Some(ParsedField::Metadata("type".to_string())));
+    assert_eq!(
+        query.aggregation,
+        Some(ParsedAggregation::Sum(ParsedField::Regular(
+            "priority".to_string()
+        )))
+    );
+    assert_eq!(query.aggregate_order, None);
+}
+
+#[test]
+fn test_parse_aggregate_order_defaults_to_ascending() {
+    let query = parse_query("from task | group status | average hours | order average").unwrap();
+    assert_eq!(
+        query.aggregate_order,
+        Some(ParsedAggregateOrder {
+            aggregate: "average".to_string(),
+            direction: ParsedDirection::Ascending,
+        })
+    );
+}
+
+#[test]
+fn test_parse_aggregate_order_requires_aggregation() {
+    assert!(parse_query("from task | group status | order count desc").is_err());
+}
+
+#[test]
+fn test_parse_limit_after_grouped_aggregation() {
+    let query =
+        parse_query("from task | group status | count | order count desc | limit 5").unwrap();
+    assert!(query.operations.is_empty());
+    assert_eq!(query.aggregate_limit, Some(5));
+    assert!(query.aggregate_order.is_some());
+}
+
+#[test]
+fn test_parse_limit_before_aggregation_is_an_operation() {
+    let query = parse_query("from task | limit 5 | count").unwrap();
+    assert_eq!(query.operations, vec![ParsedOperation::Limit(5)]);
+    assert_eq!(query.aggregate_limit, None);
+}
+
+#[test]
+fn test_syntax_error_offset() {
+    assert_eq!(syntax_error_offset("from task | order name"), None);
+    assert_eq!(syntax_error_offset("from task | wher name == 1"), Some(12));
+    assert_eq!(syntax_error_offset("from task | limit"), Some(17));
+}
+
+#[test]
+fn test_parse_expression() {
+    use firm_core::FieldId;
+    use firm_core::expression::{Expression, Operator};
+
+    let field = |name: &str| Expression::Field(FieldId::new(name));
+
+    let expression =
+        parse_expression("(current_value - start_value)/(target_value - start_value)").unwrap();
+    assert_eq!(
+        expression,
+        Expression::binary(
+            Operator::Divide,
+            Expression::binary(
+                Operator::Subtract,
+                field("current_value"),
+                field("start_value")
+            ),
+            Expression::binary(
+                Operator::Subtract,
+                field("target_value"),
+                field("start_value")
+            ),
+        )
+    );
+
+    // Multiplication binds tighter, and operators of the same precedence apply left to right
+    assert_eq!(
+        parse_expression("a + b * 2 - 1.5").unwrap(),
+        Expression::binary(
+            Operator::Subtract,
+            Expression::binary(
+                Operator::Add,
+                field("a"),
+                Expression::binary(Operator::Multiply, field("b"), Expression::Integer(2)),
+            ),
+            Expression::Float(1.5),
+        )
+    );
+    assert_eq!(
+        parse_expression("-a * -2").unwrap(),
+        Expression::binary(
+            Operator::Multiply,
+            Expression::Negate(Box::new(field("a"))),
+            Expression::Integer(-2),
+        )
+    );
+}
+
+#[test]
+fn test_parse_expression_errors() {
+    assert!(parse_expression("").is_err());
+    assert!(parse_expression("a +").is_err());
+    assert!(parse_expression("(a + b").is_err());
+    assert!(parse_expression("a == b").is_err());
+    assert!(parse_expression("\"text\" + 1").is_err());
+}
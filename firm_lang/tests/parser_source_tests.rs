@@ -3,7 +3,8 @@ mod tests {
     use std::fs;
     use std::path::Path;
 
-    use firm_lang::parser::dsl::parse_source;
+    use firm_lang::parser::dsl::{Document, TextChange, parse_source};
+    use tree_sitter::Point;
 
     #[test]
     fn test_all_example_files_parse_without_errors() {
@@ -128,4 +129,100 @@ mod tests {
         let parsed = parse_source(String::from(source), None).unwrap();
         assert!(parsed.has_error());
     }
+
+    fn assert_matches_cold_parse(document: &Document) {
+        let cold = parse_source(document.source().to_string(), None).unwrap();
+        assert_eq!(
+            document.tree().root_node().to_sexp(),
+            cold.tree.root_node().to_sexp()
+        );
+    }
+
+    #[test]
+    fn test_document_typing_matches_cold_parse() {
+        let source = "person jane {\n    name = \"Jane\"\n}\n";
+        let mut document = Document::new(source.to_string(), None).unwrap();
+
+        // Type a new field one character at a time
+        let typed = "\n    email = \"jane@example.com\"";
+        let mut at = Point { row: 1, column: 17 };
+        for c in typed.chars() {
+            document
+                .apply_changes(&[TextChange::replace(at, at, &c.to_string())])
+                .unwrap();
+            assert_matches_cold_parse(&document);
+
+            at = match c {
+                '\n' => Point {
+                    row: at.row + 1,
+                    column: 0,
+                },
+                _ => Point {
+                    row: at.row,
+                    column: at.column + c.len_utf8(),
+                },
+            };
+        }
+        assert_eq!(
+            document.source(),
+            "person jane {\n    name = \"Jane\"\n    email = \"jane@example.com\"\n}\n"
+        );
+
+        // Backspace over the domain, then rename the entity
+        for _ in 0..".com".len() {
+            let line = document.source().lines().nth(2).unwrap();
+            let end = Point {
+                row: 2,
+                column: line.len() - 1,
+            };
+            let start = Point {
+                row: 2,
+                column: end.column - 1,
+            };
+            document
+                .apply_changes(&[TextChange::replace(start, end, "")])
+                .unwrap();
+        }
+        document
+            .apply_changes(&[TextChange::replace(
+                Point { row: 0, column: 7 },
+                Point { row: 0, column: 11 },
+                "janet",
+            )])
+            .unwrap();
+
+        assert!(document.source().contains("person janet {"));
+        assert!(document.source().contains("\"jane@example\""));
+        assert_matches_cold_parse(&document);
+        assert!(document.version() > typed.len() as u64);
+    }
+
+    #[test]
+    fn test_document_multiline_and_full_changes() {
+        let mut document = Document::new("person a {\n}\n".to_string(), None).unwrap();
+
+        // Paste a schema and a second entity in one batch
+        let changes = [
+            TextChange::replace(
+                Point { row: 0, column: 0 },
+                Point { row: 0, column: 0 },
+                "schema person {\n    field { name = \"name\" type = \"string\" }\n}\n\n",
+            ),
+            TextChange::replace(
+                Point { row: 5, column: 1 },
+                Point { row: 5, column: 1 },
+                "\n\nperson b {\n    name = \"B\"\n}",
+            ),
+        ];
+        document.apply_changes(&changes).unwrap();
+        assert_matches_cold_parse(&document);
+        assert_eq!(document.to_parsed_source().entities().len(), 2);
+
+        // Replace everything
+        document
+            .apply_changes(&[TextChange::full("person c {\n}\n")])
+            .unwrap();
+        assert_eq!(document.source(), "person c {\n}\n");
+        assert_matches_cold_parse(&document);
+    }
 }
@@ -1,7 +1,7 @@
 use firm_core::EntityType;
 use firm_lang::workspace::{Workspace, WorkspaceError};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 #[cfg(test)]
@@ -52,6 +52,174 @@ mod tests {
         assert!(result.is_ok(), "Should load directory successfully");
     }
 
+    #[test]
+    fn test_load_directory_orders_files_by_path() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let nested_dir = temp_dir.path().join("nested");
+        fs::create_dir(&nested_dir).unwrap();
+
+        for (index, path) in [
+            temp_dir.path().join("c.firm"),
+            nested_dir.join("b.firm"),
+            temp_dir.path().join("a.firm"),
+        ]
+        .iter()
+        .enumerate()
+        {
+            fs::write(path, format!("person p{} {{ name = \"P\" }}", index))
+                .expect("Write entity file");
+        }
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        let paths: Vec<_> = workspace.file_paths().into_iter().cloned().collect();
+        assert_eq!(
+            paths,
+            vec![
+                temp_dir.path().join("a.firm"),
+                temp_dir.path().join("c.firm"),
+                nested_dir.join("b.firm"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_directory_skips_ignored_paths() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = temp_dir.path().join("notes");
+        let generated_dir = temp_dir.path().join("generated");
+        let hidden_dir = temp_dir.path().join(".backup");
+        fs::create_dir(&notes_dir).unwrap();
+        fs::create_dir(&generated_dir).unwrap();
+        fs::create_dir(&hidden_dir).unwrap();
+
+        fs::write(
+            temp_dir.path().join(".firmignore"),
+            "# Local drafts\n*.draft.firm\ngenerated/\n",
+        )
+        .unwrap();
+        fs::write(notes_dir.join(".firmignore"), "!keep.draft.firm\nscratch.firm\n").unwrap();
+
+        for path in [
+            temp_dir.path().join("main.firm"),
+            temp_dir.path().join("todo.draft.firm"),
+            generated_dir.join("export.firm"),
+            notes_dir.join("keep.draft.firm"),
+            notes_dir.join("other.draft.firm"),
+            notes_dir.join("scratch.firm"),
+            notes_dir.join(".hidden.firm"),
+            hidden_dir.join("main.firm"),
+        ] {
+            fs::write(path, "person p { name = \"P\" }").expect("Write entity file");
+        }
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        let paths: Vec<_> = workspace.file_paths().into_iter().cloned().collect();
+        assert_eq!(
+            paths,
+            vec![
+                temp_dir.path().join("main.firm"),
+                notes_dir.join("keep.draft.firm"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_timestamps_entities_from_file_times() {
+        use std::fs;
+
+        // Temporary directories aren't in a git work tree, so file times are used
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.firm");
+        fs::write(
+            &path,
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person jane { name = "Jane" }
+"#,
+        )
+        .unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let entity_modified = build.entities[0].modified.expect("Modified time");
+        assert_eq!(
+            entity_modified.timestamp(),
+            chrono::DateTime::<chrono::Utc>::from(modified).timestamp()
+        );
+    }
+
+    #[test]
+    fn test_build_moves_tags_field_to_entity_tags() {
+        use firm_core::FieldId;
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("main.firm"),
+            r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+}
+
+schema label {
+    field { name = "tags" type = "list" required = true }
+}
+
+task fix_bug {
+    name = "Fix bug"
+    tags = ["urgent", "q3"]
+}
+
+label shelf {
+    tags = ["a", "b"]
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let task = build
+            .entities
+            .iter()
+            .find(|entity| entity.id.as_str() == "task.fix_bug")
+            .unwrap();
+        assert_eq!(task.tags, vec!["urgent", "q3"]);
+        assert!(task.get_field(&FieldId::new("tags")).is_none());
+
+        // A schema field named tags stays a regular field
+        let label = build
+            .entities
+            .iter()
+            .find(|entity| entity.id.as_str() == "label.shelf")
+            .unwrap();
+        assert!(label.tags.is_empty());
+        assert!(label.get_field(&FieldId::new("tags")).is_some());
+    }
+
     #[test]
     fn test_load_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -419,6 +587,71 @@ account test2 {
         }
     }
 
+    #[test]
+    fn test_enum_values_canonicalized_to_declared_casing() {
+        use firm_core::{EntityId, FieldId, FieldValue};
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("enum_casing.firm");
+
+        let content = r#"
+schema account {
+    field {
+        name = "status"
+        type = "enum"
+        allowed_values = ["Prospect", "Customer"]
+        required = true
+    }
+    field {
+        name = "tier"
+        type = "enum"
+        allowed_values = ["Gold", "Silver"]
+        case_sensitive = true
+        required = false
+    }
+}
+
+account acme {
+    status = enum"customer"
+    tier = enum"Gold"
+}
+"#;
+
+        fs::write(&test_file, content).expect("Write test file");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&test_file, &temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let acme = build
+            .entities
+            .iter()
+            .find(|entity| entity.id == EntityId::new("account.acme"))
+            .unwrap();
+        assert_eq!(
+            acme.get_field(&FieldId::new("status")),
+            Some(&FieldValue::Enum("Customer".to_string()))
+        );
+
+        // Case-sensitive fields only accept the declared casing
+        fs::write(
+            &test_file,
+            content.replace(r#"tier = enum"Gold""#, r#"tier = enum"gold""#),
+        )
+        .expect("Write test file");
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&test_file, &temp_dir.path().to_path_buf())
+            .unwrap();
+        assert!(matches!(
+            workspace.build(),
+            Err(WorkspaceError::ValidationError(_, _))
+        ));
+    }
+
     #[test]
     fn test_enum_validation_fails_for_invalid_value() {
         use std::fs;
@@ -431,7 +664,7 @@ schema account {
     field {
         name = "status"
         type = "enum"
-        values = ["prospect", "customer"]
+        allowed_values = ["prospect", "customer"]
         required = true
     }
 }
@@ -501,6 +734,57 @@ organization acme {
         assert!(result.is_none(), "Should not find non-existent type");
     }
 
+    #[test]
+    fn test_build_records_entity_sources_across_files() {
+        use firm_core::EntityId;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let first_file = temp_dir.path().join("a.firm");
+        let second_file = temp_dir.path().join("b.firm");
+
+        fs::write(
+            &first_file,
+            r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john {
+    name = "John"
+}
+"#,
+        )
+        .unwrap();
+        fs::write(&second_file, "\nperson john {\n    name = \"Johnny\"\n}\n").unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let sources = build.entity_sources(&EntityId::new("person.john"));
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].path, first_file);
+        assert_eq!(sources[0].line(), 5);
+        assert_eq!(sources[0].column(), 1);
+        assert_eq!(sources[0].end_line(), 7);
+        assert_eq!(sources[1].path, second_file);
+        assert_eq!(sources[1].line(), 2);
+        assert_eq!(sources[1].end_line(), 4);
+
+        assert_eq!(workspace.find_entity_sources("person", "john"), sources);
+        assert_eq!(
+            workspace.find_entity_source("person", "john"),
+            Some(first_file)
+        );
+        assert!(
+            build
+                .entity_sources(&EntityId::new("person.jane"))
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_find_schema_source() {
         use std::fs;
@@ -544,6 +828,12 @@ schema project {
         // Should return None for non-existent schemas
         let result = workspace.find_schema_source("organization");
         assert!(result.is_none(), "Should not find non-existent schema");
+
+        // Blocks span from the schema keyword to the closing brace
+        let block = workspace.find_schema_block("project").unwrap();
+        assert_eq!(block.path, schema_file);
+        assert_eq!((block.line(), block.end_line()), (10, 16));
+        assert!(workspace.find_schema_block("organization").is_none());
     }
 
     #[test]
@@ -623,4 +913,2496 @@ schema organization {
         assert!(result.is_some());
         assert_eq!(result.unwrap(), schema2);
     }
+
+    #[test]
+    fn test_build_warns_on_duplicate_field() {
+        use firm_lang::workspace::WorkspaceWarning;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("duplicates.firm");
+
+        let content = r#"schema person {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+}
+
+person john {
+    name = "John"
+    name = "John Doe"
+}
+"#;
+        fs::write(&file_path, content).expect("Should write file");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&file_path, &PathBuf::new())
+            .expect("Should load file");
+
+        let build = workspace.build().expect("Duplicates should not fail the build");
+        assert_eq!(build.warnings.len(), 1);
+
+        match &build.warnings[0] {
+            WorkspaceWarning::DuplicateField {
+                field,
+                first_line,
+                duplicate_line,
+                ..
+            } => {
+                assert_eq!(field, "name");
+                assert_eq!(*first_line, 10);
+                assert_eq!(*duplicate_line, 11);
+            }
+            other => panic!("Expected DuplicateField warning, got {:?}", other),
+        }
+        assert!(
+            build.warnings[0]
+                .to_string()
+                .contains("field 'name' of entity 'person.john' already set at line 10")
+        );
+    }
+
+    #[test]
+    fn test_build_warns_on_allowed_values_for_non_enum_field() {
+        use firm_lang::workspace::WorkspaceWarning;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("schemas.firm");
+
+        let content = r#"schema person {
+    field {
+        name = "role"
+        type = "string"
+        allowed_values = ["admin", "member"]
+    }
+}
+"#;
+        fs::write(&file_path, content).expect("Should write file");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&file_path, &PathBuf::new())
+            .expect("Should load file");
+
+        let build = workspace
+            .build()
+            .expect("Unused allowed values should not fail the build");
+        assert_eq!(build.warnings.len(), 1);
+
+        match &build.warnings[0] {
+            WorkspaceWarning::UnusedAllowedValues {
+                schema,
+                field,
+                line,
+                ..
+            } => {
+                assert_eq!(schema, "person");
+                assert_eq!(field, "role");
+                assert_eq!(*line, 2);
+            }
+            other => panic!("Expected UnusedAllowedValues warning, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_warns_on_invalid_query_field() {
+        use firm_lang::workspace::WorkspaceWarning;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("reports.firm");
+
+        let content = r#"schema task {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+}
+
+schema report {
+    field {
+        name = "query"
+        type = "string"
+        format = "firm-query"
+        required = true
+    }
+}
+
+report open_tasks {
+    query = "from task | order name"
+}
+
+report broken {
+    query = "from task | order nmae"
+}
+
+report garbled {
+    query = "from task | wher name == 1"
+}
+"#;
+        fs::write(&file_path, content).expect("Should write file");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&file_path, &PathBuf::new())
+            .expect("Should load file");
+
+        let build = workspace
+            .build()
+            .expect("Invalid queries should not fail the build");
+        assert_eq!(build.warnings.len(), 2);
+
+        let mut lines: Vec<usize> = build
+            .warnings
+            .iter()
+            .map(|warning| match warning {
+                WorkspaceWarning::InvalidQuery { field, line, .. } => {
+                    assert_eq!(field, "query");
+                    *line
+                }
+                other => panic!("Expected InvalidQuery warning, got {:?}", other),
+            })
+            .collect();
+        lines.sort();
+        assert_eq!(lines, vec![24, 28]);
+    }
+
+    #[test]
+    fn test_build_collects_named_queries() {
+        use firm_lang::workspace::WorkspaceWarning;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("tasks.firm");
+
+        let content = r#"schema task {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+}
+
+task write_docs {
+    name = "Write docs"
+}
+
+query open_tasks {
+    query = "from task | order name"
+    description = "Tasks by name"
+}
+
+query broken {
+    query = "from task | order nmae"
+}
+"#;
+        fs::write(&file_path, content).expect("Should write file");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&file_path, &PathBuf::new())
+            .expect("Should load file");
+
+        let build = workspace.build().expect("Named queries should build");
+        assert_eq!(build.entities.len(), 1);
+
+        let names: Vec<&str> = build.queries.iter().map(|q| q.name.as_str()).collect();
+        assert_eq!(names, vec!["broken", "open_tasks"]);
+
+        let open_tasks = build.named_query("open_tasks").unwrap();
+        assert_eq!(open_tasks.query, "from task | order name");
+        assert_eq!(open_tasks.description.as_deref(), Some("Tasks by name"));
+        assert_eq!(open_tasks.path, file_path);
+        assert_eq!(open_tasks.line, 12);
+        assert!(build.named_query("closed_tasks").is_none());
+
+        assert_eq!(build.warnings.len(), 1);
+        match &build.warnings[0] {
+            WorkspaceWarning::InvalidNamedQuery { name, line, .. } => {
+                assert_eq!(name, "broken");
+                assert_eq!(*line, 17);
+            }
+            other => panic!("Expected InvalidNamedQuery warning, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_fails_on_invalid_named_query_blocks() {
+        use std::fs;
+
+        let cases = [
+            (
+                "query open_tasks {\n    description = \"No query\"\n}\n",
+                "needs a 'query' string",
+            ),
+            (
+                "query open_tasks {\n    query = \"from task\"\n}\n\nquery open_tasks {\n    query = \"from person\"\n}\n",
+                "is already defined",
+            ),
+            (
+                "schema query {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n",
+                "reserved for named queries",
+            ),
+        ];
+
+        for (content, expected) in cases {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("queries.firm");
+            fs::write(&file_path, content).expect("Should write file");
+
+            let mut workspace = Workspace::new();
+            workspace
+                .load_file(&file_path, &PathBuf::new())
+                .expect("Should load file");
+
+            match workspace.build() {
+                Err(WorkspaceError::ValidationError(path, message)) => {
+                    assert_eq!(path, file_path);
+                    assert!(
+                        message.contains(expected),
+                        "Expected '{}' in '{}'",
+                        expected,
+                        message
+                    );
+                }
+                other => panic!("Expected ValidationError, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_watcher_reports_firm_file_changes() {
+        use firm_lang::workspace::{WorkspaceChange, WorkspaceWatcher};
+        use std::fs;
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut watcher = WorkspaceWatcher::new(temp_dir.path())
+            .unwrap()
+            .with_debounce(Duration::from_millis(50));
+
+        let firm_file = temp_dir.path().join("people.firm");
+        fs::write(&firm_file, "person john { name = \"John\" }").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "not a firm file").unwrap();
+
+        let changes = watcher.wait(Duration::from_secs(5));
+        assert_eq!(changes, vec![WorkspaceChange::Created(firm_file.clone())]);
+
+        fs::remove_file(&firm_file).unwrap();
+        let changes = watcher.wait(Duration::from_secs(5));
+        assert_eq!(changes, vec![WorkspaceChange::Removed(firm_file)]);
+    }
+
+    #[test]
+    fn test_check_references_reports_broken_references() {
+        use firm_lang::workspace::BrokenReferenceKind;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("people.firm");
+        fs::write(
+            &file_path,
+            r#"schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+    field { name = "manager_email" type = "reference" required = false }
+    field { name = "peers" type = "list" required = false }
+}
+
+person john {
+    name = "John"
+}
+
+person jane {
+    name = "Jane"
+    manager = person.john
+    manager_email = person.john.email
+    peers = [person.john, person.ghost]
+}
+
+person bob {
+    name = "Bob"
+    manager = person.nobody
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let broken = workspace.check_references(&build);
+        let summary: Vec<(usize, &str, String, BrokenReferenceKind)> = broken
+            .iter()
+            .map(|b| {
+                assert_eq!(b.path, file_path);
+                (
+                    b.line,
+                    b.entity_id.as_str(),
+                    b.reference.to_string(),
+                    b.kind,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    15,
+                    "person.jane",
+                    "person.john.email".to_string(),
+                    BrokenReferenceKind::MissingField
+                ),
+                (
+                    16,
+                    "person.jane",
+                    "person.ghost".to_string(),
+                    BrokenReferenceKind::MissingEntity
+                ),
+                (
+                    21,
+                    "person.bob",
+                    "person.nobody".to_string(),
+                    BrokenReferenceKind::MissingEntity
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_reference_types() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("crm.firm"),
+            r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema organization {
+    field { name = "name" type = "string" required = true }
+}
+
+schema account {
+    field { name = "organization_ref" type = "reference" required = false }
+    field { name = "owner_ref" type = "reference" required = false }
+    field { name = "backup" type = "reference" required = false target = "person" }
+    field { name = "partner_organization_refs" type = "list" required = false }
+}
+
+person jane {
+    name = "Jane"
+}
+
+organization acme {
+    name = "Acme"
+}
+
+account good {
+    organization_ref = organization.acme
+    owner_ref = organization.acme
+    backup = person.jane
+    partner_organization_refs = [organization.acme]
+}
+
+account bad {
+    organization_ref = person.jane
+    backup = organization.acme.name
+    partner_organization_refs = [organization.acme, person.jane, organization.ghost]
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let mismatched = workspace.check_reference_types(&build);
+        let summary: Vec<(usize, &str, String, String, bool)> = mismatched
+            .iter()
+            .map(|m| {
+                (
+                    m.line,
+                    m.field.as_str(),
+                    m.reference.to_string(),
+                    m.expected_type.to_string(),
+                    m.inferred,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    32,
+                    "organization_ref",
+                    "person.jane".to_string(),
+                    "organization".to_string(),
+                    true
+                ),
+                (
+                    33,
+                    "backup",
+                    "organization.acme.name".to_string(),
+                    "person".to_string(),
+                    false
+                ),
+                (
+                    34,
+                    "partner_organization_refs",
+                    "person.jane".to_string(),
+                    "organization".to_string(),
+                    true
+                ),
+            ]
+        );
+        assert!(mismatched[1].message().contains("the schema's target"));
+    }
+
+    #[test]
+    fn test_type_filter_restricts_build_and_reference_checks() {
+        use firm_lang::workspace::TypeFilter;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("work.firm"),
+            r#"schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+}
+
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "assignee" type = "reference" required = false }
+}
+
+person john {
+    name = "John"
+    manager = person.nobody
+}
+
+task review {
+    name = "Review"
+    assignee = person.john
+}
+
+task ship {
+    name = "Ship"
+    assignee = task.missing
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new().with_type_filter(TypeFilter::new().only(["task"]));
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let mut ids: Vec<&str> = build.entities.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["task.review", "task.ship"]);
+        assert_eq!(build.schemas.len(), 2);
+
+        // References to and from people aren't checked
+        let broken = workspace.check_references(&build);
+        let references: Vec<String> = broken.iter().map(|b| b.reference.to_string()).collect();
+        assert_eq!(references, vec!["task.missing"]);
+
+        let mut workspace =
+            Workspace::new().with_type_filter(TypeFilter::new().excluding(["task"]));
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let ids: Vec<&str> = build.entities.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["person.john"]);
+        let broken = workspace.check_references(&build);
+        let references: Vec<String> = broken.iter().map(|b| b.reference.to_string()).collect();
+        assert_eq!(references, vec!["person.nobody"]);
+    }
+
+    #[test]
+    fn test_inbound_references() {
+        use firm_core::EntityId;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            r#"schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+    field { name = "peers" type = "list" required = false }
+}
+
+person john {
+    name = "John"
+    manager = person.john
+}
+
+person jane {
+    name = "Jane"
+    manager = person.john
+    peers = [person.bob, person.john.name]
+}
+
+person bob {
+    name = "Bob"
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        // Only references from other entities are inbound
+        let inbound = workspace.inbound_references(&EntityId::new("person.john"));
+        let summary: Vec<(usize, &str, &str, String)> = inbound
+            .iter()
+            .map(|r| {
+                (
+                    r.line,
+                    r.entity_id.as_str(),
+                    r.field.as_str(),
+                    r.reference.to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (14, "person.jane", "manager", "person.john".to_string()),
+                (15, "person.jane", "peers", "person.john.name".to_string()),
+            ]
+        );
+
+        assert!(
+            workspace
+                .inbound_references(&EntityId::new("person.jane"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_suggest_fixes_applies_cleanly() {
+        use firm_lang::workspace::apply_edits;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("projects.firm");
+        fs::write(
+            &file_path,
+            r#"schema project {
+    field { name = "name" type = "string" required = true }
+    field { name = "budget" type = "integer" required = true }
+    field { name = "status" type = "enum" allowed_values = ["planning", "active"] required = false }
+    field { name = "owner" type = "reference" required = false }
+}
+
+person john {
+    name = "John"
+}
+
+project website {
+    status = enum"activ"
+    owner = person.jon
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        let fixes = workspace.suggest_fixes();
+        assert_eq!(fixes.len(), 4);
+        assert!(fixes.iter().all(|fix| fix.path == file_path));
+
+        let edits: Vec<_> = fixes.into_iter().flat_map(|fix| fix.edits).collect();
+        let source = fs::read_to_string(&file_path).unwrap();
+        let fixed = apply_edits(&source, &edits);
+
+        assert!(fixed.contains("    status = enum\"active\"\n"));
+        assert!(fixed.contains("    owner = person.john\n"));
+        assert!(fixed.contains("    name = \"\"\n    budget = 0\n}"));
+        assert!(fixed.contains("\n\nschema person {\n"));
+
+        fs::write(&file_path, fixed).unwrap();
+        let mut fixed_workspace = Workspace::new();
+        fixed_workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        assert!(fixed_workspace.build().is_ok());
+        assert!(fixed_workspace.suggest_fixes().is_empty());
+    }
+
+    #[test]
+    fn test_code_actions_for_diagnostics() {
+        use firm_lang::workspace::{Diagnostic, DiagnosticSeverity};
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("projects.firm"),
+            r#"schema project {
+    field { name = "name" type = "string" required = true }
+    field { name = "owner" type = "reference" required = false }
+}
+
+project website {
+    name = "Website"
+    owner = person.jon
+}
+
+person john {
+    name = "John"
+}
+
+gadget phone {
+    model = "X"
+    ports = 2
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let path = temp_dir.path().canonicalize().unwrap().join("projects.firm");
+
+        // The build stops at the first entity type without a schema
+        let diagnostics = workspace.collect_diagnostics().remove(&path).unwrap();
+        let actions = workspace.code_actions(&path, &diagnostics);
+        assert_eq!(actions.len(), 2);
+        assert!(actions.iter().all(|fix| fix.code == "missing-schema"));
+        assert!(actions[0].edits[0].replacement.contains("schema person {"));
+        let skeleton = &actions[1].edits[0].replacement;
+        assert!(skeleton.starts_with("\nschema gadget {"));
+        assert!(skeleton.contains("name = \"ports\"\n        type = \"integer\""));
+
+        // Line-based diagnostics only match fixes for the problem on that line
+        let broken_reference = Diagnostic {
+            severity: DiagnosticSeverity::Error,
+            code: "broken-reference",
+            line: Some(8),
+            range: None,
+            message: "Reference to missing entity 'person.jon'".to_string(),
+        };
+        let actions = workspace.code_actions(&path, std::slice::from_ref(&broken_reference));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].edits[0].replacement, "person.john");
+
+        let elsewhere = Diagnostic {
+            line: Some(2),
+            ..broken_reference
+        };
+        assert!(workspace.code_actions(&path, &[elsewhere]).is_empty());
+    }
+
+    #[test]
+    fn test_code_actions_fill_in_missing_fields_of_every_type() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("contacts.firm"),
+            r#"schema contact {
+    field { name = "email" type = "string" required = true }
+    field { name = "employer" type = "reference" required = true }
+    field { name = "rate" type = "currency" required = true }
+}
+
+contact jane {
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let path = temp_dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .join("contacts.firm");
+
+        // Fixing the workspace only fills in fields with an obvious value
+        let fixes = workspace.suggest_fixes();
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].edits[0].replacement, "    email = \"\"\n");
+
+        // Editors get placeholders for the rest, to fill in
+        let diagnostics = workspace.collect_diagnostics().remove(&path).unwrap();
+        let actions = workspace.code_actions(&path, &diagnostics);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].code, "missing-required-field");
+        assert_eq!(
+            actions[0].edits[0].replacement,
+            "    email = \"\"\n    employer = type.id\n    rate = 0.00 USD\n"
+        );
+    }
+
+    #[test]
+    fn test_code_actions_offer_missing_schema_in_either_file() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("schemas.firm"),
+            "schema project {\n    field { name = \"name\" type = \"string\" required = true }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("gadgets.firm"),
+            "gadget phone {\n    model = \"X\"\n    ports = 2\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let gadgets = root.join("gadgets.firm");
+
+        let diagnostics = workspace.collect_diagnostics().remove(&gadgets).unwrap();
+        let actions = workspace.code_actions(&gadgets, &diagnostics);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].path, root.join("schemas.firm"));
+        assert!(actions[0].message.contains("to schemas.firm"));
+        assert_eq!(actions[1].path, gadgets);
+        assert!(actions[1].message.contains("to this file"));
+        let skeleton = &actions[1].edits[0].replacement;
+        assert!(skeleton.starts_with("\nschema gadget {"));
+        assert!(skeleton.contains("name = \"ports\"\n        type = \"integer\""));
+
+        // Fixing the whole workspace adds the schema just once
+        let fixes = workspace.suggest_fixes();
+        assert_eq!(
+            fixes
+                .iter()
+                .filter(|fix| fix.code == "missing-schema")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_relative_path_uses_forward_slashes() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let nested_dir = temp_dir.path().join("core").join("people");
+        fs::create_dir_all(&nested_dir).unwrap();
+        fs::write(
+            nested_dir.join("john.firm"),
+            "person john {\n    name = \"John\"\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        assert_eq!(workspace.relative_path(&nested_dir.join("john.firm")), None);
+
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        let paths = workspace.file_paths();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].is_absolute());
+        assert_eq!(
+            workspace.relative_path(paths[0]),
+            Some("core/people/john.firm".to_string())
+        );
+
+        // Paths built from components resolve to the same relative path
+        let from_components: PathBuf = [temp_dir.path(), Path::new("core"), Path::new("people")]
+            .iter()
+            .collect::<PathBuf>()
+            .join("john.firm");
+        assert_eq!(
+            workspace.relative_path(&from_components),
+            Some("core/people/john.firm".to_string())
+        );
+
+        // Removed files still resolve through their directory
+        assert_eq!(
+            workspace.relative_path(&nested_dir.join("removed.firm")),
+            Some("core/people/removed.firm".to_string())
+        );
+        assert_eq!(
+            workspace.relative_path(Path::new("/elsewhere/a.firm")),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_directory_through_symlinked_root() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let real_root = temp_dir.path().join("real");
+        fs::create_dir_all(&real_root).unwrap();
+        fs::write(
+            real_root.join("people.firm"),
+            "person john {\n    name = \"John\"\n}\n",
+        )
+        .unwrap();
+
+        let link_root = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&real_root, &link_root).unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&link_root).unwrap();
+
+        let canonical_root = real_root.canonicalize().unwrap();
+        assert_eq!(workspace.root_path(), Some(canonical_root.as_path()));
+        assert_eq!(
+            workspace.find_entity_source("person", "john"),
+            Some(canonical_root.join("people.firm"))
+        );
+
+        // The symlinked and canonical paths agree on the relative path
+        assert_eq!(
+            workspace.relative_path(&link_root.join("people.firm")),
+            Some("people.firm".to_string())
+        );
+        assert_eq!(
+            workspace.relative_path(&canonical_root.join("people.firm")),
+            Some("people.firm".to_string())
+        );
+
+        // Loading the same file through the symlink doesn't add it twice
+        workspace
+            .load_file(&link_root.join("people.firm"), &link_root)
+            .unwrap();
+        assert_eq!(workspace.num_files(), 1);
+    }
+
+    #[test]
+    fn test_check_paths_warns_on_missing_file() {
+        use firm_lang::workspace::{PathCheckOptions, WorkspaceWarning};
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("contract.pdf"), "").unwrap();
+        fs::write(
+            temp_dir.path().join("documents.firm"),
+            r#"document acme {
+    file = path"./contract.pdf"
+    attachments = [
+        path"./contract.pdf",
+        path"./missing.pdf"
+    ]
+    shared = path"../outside.pdf"
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        let check = workspace.check_paths(&PathCheckOptions::default());
+        assert_eq!(check.checked, 3);
+        assert_eq!(check.skipped_external, 1);
+        assert_eq!(check.warnings.len(), 1);
+
+        match &check.warnings[0] {
+            WorkspaceWarning::MissingPath {
+                field,
+                line,
+                target,
+                ..
+            } => {
+                assert_eq!(field, "attachments");
+                // The list value starts on the field's line
+                assert_eq!(*line, 3);
+                assert_eq!(target, &PathBuf::from("./missing.pdf"));
+            }
+            other => panic!("Expected a missing path warning, got {:?}", other),
+        }
+
+        // External paths are checked when allowed
+        let check = workspace.check_paths(&PathCheckOptions {
+            allow_external_paths: true,
+        });
+        assert_eq!(check.checked, 4);
+        assert_eq!(check.skipped_external, 0);
+        assert_eq!(check.warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_annotations_with_enclosing_blocks() {
+        use firm_core::EntityId;
+        use firm_lang::workspace::{AnnotationOwner, DEFAULT_ANNOTATION_MARKERS};
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("notes.firm"),
+            r#"// TODO: split this file
+person john {
+    // FIXME: wrong email
+    email = "john@example"
+}
+
+// NOTE between blocks
+schema project {
+    /*
+     * TODO: add a budget field
+     */
+    field {
+        name = "name"
+        type = "string"
+    }
+}
+// just a comment
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        let annotations = workspace.annotations(DEFAULT_ANNOTATION_MARKERS);
+        let summary: Vec<(usize, &str, &str, Option<AnnotationOwner>)> = annotations
+            .iter()
+            .map(|a| (a.line, a.marker.as_str(), a.text.as_str(), a.owner.clone()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (1, "TODO", "split this file", None),
+                (
+                    3,
+                    "FIXME",
+                    "wrong email",
+                    Some(AnnotationOwner::Entity(EntityId::new("person.john")))
+                ),
+                (7, "NOTE", "between blocks", None),
+                (
+                    10,
+                    "TODO",
+                    "add a budget field",
+                    Some(AnnotationOwner::Schema("project".to_string()))
+                ),
+            ]
+        );
+
+        // Custom markers replace the defaults
+        let annotations = workspace.annotations(&["FIXME"]);
+        assert_eq!(annotations.len(), 1);
+        assert!(
+            annotations[0]
+                .to_string()
+                .ends_with("notes.firm:3: FIXME: wrong email (person.john)")
+        );
+    }
+
+    fn completion_build() -> (TempDir, firm_lang::workspace::WorkspaceBuild) {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            r#"schema task {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+    field {
+        name = "status"
+        type = "enum"
+        allowed_values = ["todo", "doing", "done"]
+        required = false
+    }
+    field {
+        name = "assignee"
+        type = "reference"
+        required = false
+    }
+}
+
+schema person {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+}
+
+person jane { name = "Jane" }
+person john { name = "John" }
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let build = workspace.build().unwrap();
+        (temp_dir, build)
+    }
+
+    #[test]
+    fn test_completions_for_field_names() {
+        use firm_lang::workspace::CompletionKind;
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        // An unsaved buffer with one field already set
+        let source = "task write_docs {\n    name = \"Write docs\"\n    \n}\n";
+        let completions = build.completions(source, Point { row: 2, column: 4 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["status", "assignee"]);
+        assert_eq!(completions[0].kind, CompletionKind::Field { required: false });
+
+        // A partial name filters the fields
+        let source = "task write_docs {\n    na\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 6 });
+        assert_eq!(completions.len(), 1);
+        assert_eq!(completions[0].label, "name");
+        assert_eq!(completions[0].kind, CompletionKind::Field { required: true });
+    }
+
+    #[test]
+    fn test_completions_for_enum_values() {
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        let source = "task write_docs {\n    status = enum\"do\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 20 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["doing", "done"]);
+    }
+
+    #[test]
+    fn test_completions_for_invalid_enum_values() {
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        // Prefixes match regardless of case
+        let source = "task write_docs {\n    status = enum\"DO\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 20 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["doing", "done"]);
+
+        // A value that isn't allowed can be replaced with any of the allowed ones
+        let source = "task write_docs {\n    status = enum\"finished\"\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 26 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["todo", "doing", "done"]);
+    }
+
+    #[test]
+    fn test_completions_for_entity_references() {
+        use firm_lang::workspace::CompletionKind;
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        let source = "task write_docs {\n    assignee = person.j\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 23 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["jane", "john"]);
+        assert_eq!(completions[0].kind, CompletionKind::EntityId);
+        assert_eq!(completions[0].detail, "person.jane");
+
+        // Outside a block, only a new block can be typed
+        let completions = build.completions("}\n", Point { row: 0, column: 1 });
+        assert!(completions.is_empty());
+    }
+
+    #[test]
+    fn test_completions_for_new_blocks() {
+        use firm_lang::workspace::CompletionKind;
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        // A partial type at the top level suggests that type's block
+        let completions = build.completions("ta", Point { row: 0, column: 2 });
+        assert_eq!(completions.len(), 1);
+        assert_eq!(completions[0].label, "task");
+        assert_eq!(completions[0].kind, CompletionKind::EntityBlock);
+        assert_eq!(completions[0].detail, "name (required), status, assignee");
+        assert_eq!(
+            completions[0].insert_text.as_deref(),
+            Some("task ${1:id} {\n    name = \"$2\"\n}")
+        );
+
+        // After the type, only the rest of the block is inserted
+        let completions = build.completions("task ", Point { row: 0, column: 5 });
+        assert_eq!(completions.len(), 1);
+        assert_eq!(
+            completions[0].insert_text.as_deref(),
+            Some("${1:id} {\n    name = \"$2\"\n}")
+        );
+
+        // A schema block can be scaffolded too
+        let completions = build.completions("sch", Point { row: 0, column: 3 });
+        assert_eq!(completions.len(), 1);
+        assert_eq!(completions[0].kind, CompletionKind::SchemaBlock);
+        let snippet = completions[0].insert_text.as_deref().unwrap_or_default();
+        assert!(snippet.contains("    field {\n"));
+    }
+
+    #[test]
+    fn test_completions_and_diagnostics_inside_query_strings() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::CompletionKind;
+        use std::fs;
+        use tree_sitter::Point;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            r#"schema task {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+}
+
+schema report {
+    field {
+        name = "query"
+        type = "string"
+        format = "firm-query"
+        required = true
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let build = workspace.build().unwrap();
+
+        // Entity types after from
+        let source = "report open {\n    query = \"from t\"\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 19 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["task"]);
+        assert_eq!(completions[0].kind, CompletionKind::EntityType);
+
+        // Fields of the queried type after where
+        let source = "report open {\n    query = \"from task | where n\"\n}\n";
+        let completions = build.completions(source, Point { row: 1, column: 32 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["name"]);
+
+        // Syntax errors point inside the string
+        let source = "report broken {\n    query = \"from task | wher name == 1\"\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+        let diagnostics = build.query_diagnostics(&parsed);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].position, Point { row: 1, column: 25 });
+        assert!(diagnostics[0].message.contains("failed to parse"));
+
+        // Unknown fields point at the start of the query
+        let source = "report broken {\n    query = \"from task | order nmae\"\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+        let diagnostics = build.query_diagnostics(&parsed);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].position, Point { row: 1, column: 13 });
+    }
+
+    #[test]
+    fn test_signature_help_and_query_files() {
+        use firm_lang::workspace::query_file_signature_help;
+        use std::fs;
+        use tree_sitter::Point;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            r#"schema task {
+    field {
+        name = "name"
+        type = "string"
+        required = true
+    }
+}
+
+schema report {
+    field {
+        name = "query"
+        type = "string"
+        format = "firm-query"
+        required = true
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        // The clause at the cursor of an embedded query, with the parameter being typed
+        let source = "report open {\n    query = \"from task | order name \"\n}\n";
+        let signature = build
+            .query_signature_help(source, Point { row: 1, column: 36 })
+            .unwrap();
+        assert_eq!(signature.label, "order <field> <direction>");
+        assert_eq!(
+            signature.parameters[signature.active_parameter],
+            "<direction>"
+        );
+
+        // Outside a query there's no signature
+        assert!(
+            build
+                .query_signature_help(source, Point { row: 0, column: 3 })
+                .is_none()
+        );
+
+        // Query files are a query from start to end
+        let query = "from task\n| where n";
+        let completions = build.query_file_completions(query, Point { row: 1, column: 9 });
+        let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["name"]);
+        let signature = query_file_signature_help(query, Point { row: 1, column: 9 }).unwrap();
+        assert_eq!(signature.label, "where <field> <operator> <value>");
+        assert_eq!(signature.active_parameter, 0);
+
+        let diagnostics = build.query_file_diagnostics("from task\n| wher name == 1");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].position, Point { row: 1, column: 2 });
+        assert!(
+            build
+                .query_file_diagnostics("from task | limit 1")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_hover_for_references_and_field_names() {
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        let source = "task write_docs {\n    status = enum\"todo\"\n    assignee = person.jane\n}\n";
+
+        // References render the entity card with its defining file
+        let hover = build.hover(source, Point { row: 2, column: 20 }).unwrap();
+        assert!(hover.contents.starts_with("**person** `person.jane`"));
+        assert!(hover.contents.contains("- **name**: Jane"));
+        assert!(hover.contents.contains("main.firm:"));
+        assert_eq!(hover.range.start_point, Point { row: 2, column: 15 });
+
+        // Field names render their schema definition
+        let hover = build.hover(source, Point { row: 1, column: 6 }).unwrap();
+        assert!(hover.contents.starts_with("**status**: Enum (optional)"));
+        assert!(hover.contents.contains("Allowed values: `todo`, `doing`, `done`"));
+
+        // Values other than references have nothing to show
+        assert!(build.hover(source, Point { row: 1, column: 20 }).is_none());
+    }
+
+    #[test]
+    fn test_inlay_hints_for_references() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::{DANGLING_REFERENCE_HINT, HintConfig};
+        use tree_sitter::Point;
+
+        let (_temp_dir, build) = completion_build();
+
+        let source = "task a {\n    assignee = person.jane\n}\n\ntask b {\n    assignee = person.nobody\n}\n\ntask c {\n    assignee = task.a\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+        let start = Point { row: 0, column: 0 };
+        let end = Point { row: 11, column: 0 };
+
+        let hints = build.inlay_hints(&parsed, start, end, &HintConfig::default());
+        let labels: Vec<&str> = hints.iter().map(|hint| hint.label.as_str()).collect();
+        // task.a isn't in the build, so it dangles too
+        assert_eq!(labels, vec!["(Jane)", DANGLING_REFERENCE_HINT, DANGLING_REFERENCE_HINT]);
+        assert_eq!(hints[0].position, Point { row: 1, column: 26 });
+
+        // Only references in the range get hints
+        let hints = build.inlay_hints(
+            &parsed,
+            Point { row: 4, column: 0 },
+            Point { row: 6, column: 0 },
+            &HintConfig::default(),
+        );
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].position.row, 5);
+
+        // The hint field can be configured per type
+        let mut config = HintConfig::default();
+        config.fields.insert("person".to_string(), "nickname".to_string());
+        let hints = build.inlay_hints(&parsed, start, Point { row: 2, column: 0 }, &config);
+        assert!(hints.is_empty());
+    }
+
+    #[test]
+    fn test_semantic_tokens() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::{SemanticTokenModifier, SemanticTokenType};
+
+        let (_temp_dir, build) = completion_build();
+
+        let source = "task a {\n    assignee = person.jane\n    status = enum\"open\"\n    budget = 5000.00 USD\n}\n\ntask b {\n    assignee = person.nobody\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+
+        let tokens: Vec<_> = build
+            .semantic_tokens(&parsed)
+            .into_iter()
+            .map(|token| {
+                (
+                    token.start.row,
+                    token.start.column,
+                    token.length,
+                    token.token_type,
+                    token.modifiers,
+                )
+            })
+            .collect();
+
+        let declaration = vec![SemanticTokenModifier::Declaration];
+        assert_eq!(
+            tokens,
+            vec![
+                (0, 0, 4, SemanticTokenType::Type, vec![]),
+                (0, 5, 1, SemanticTokenType::Entity, declaration.clone()),
+                (1, 4, 8, SemanticTokenType::Property, vec![]),
+                (1, 15, 11, SemanticTokenType::Reference, vec![]),
+                (2, 4, 6, SemanticTokenType::Property, vec![]),
+                (2, 13, 10, SemanticTokenType::EnumMember, vec![]),
+                (3, 4, 6, SemanticTokenType::Property, vec![]),
+                (3, 21, 3, SemanticTokenType::Currency, vec![]),
+                (6, 0, 4, SemanticTokenType::Type, vec![]),
+                (6, 5, 1, SemanticTokenType::Entity, declaration),
+                (7, 4, 8, SemanticTokenType::Property, vec![]),
+                (
+                    7,
+                    15,
+                    13,
+                    SemanticTokenType::Reference,
+                    vec![SemanticTokenModifier::Dangling]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_and_workspace_symbols() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::{SymbolKind, document_symbols};
+
+        let parsed = parse_source(
+            "person jane {\n    name = \"Jane\"\n}\n\nschema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n".to_string(),
+            None,
+        )
+        .unwrap();
+        let symbols = document_symbols(&parsed);
+        assert_eq!(symbols.len(), 2);
+
+        // Symbols come in source order, with fields as children
+        assert_eq!(symbols[0].name, "jane");
+        assert_eq!(symbols[0].kind, SymbolKind::Entity);
+        assert_eq!(symbols[0].detail.as_deref(), Some("person"));
+        assert_eq!(symbols[0].children[0].name, "name");
+        assert_eq!(symbols[0].children[0].kind, SymbolKind::Field);
+
+        assert_eq!(symbols[1].name, "person");
+        assert_eq!(symbols[1].kind, SymbolKind::Schema);
+        assert_eq!(symbols[1].children[0].name, "name");
+        assert_eq!(symbols[1].children[0].detail.as_deref(), Some("string"));
+    }
+
+    #[test]
+    fn test_folding_ranges() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::{FoldingKind, folding_ranges};
+
+        let source = "// People we work with\n// and their roles\nperson jane {\n    name = \"Jane\" // Full name\n    // Skills\n    skills = [\n        \"rust\",\n    ]\n}\n\nschema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+
+        let ranges: Vec<_> = folding_ranges(&parsed)
+            .into_iter()
+            .map(|range| (range.start_row, range.end_row, range.kind))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0, 1, FoldingKind::Comment),
+                (2, 8, FoldingKind::Region),
+                (5, 7, FoldingKind::Region),
+                (10, 15, FoldingKind::Region),
+                (11, 14, FoldingKind::Region),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_on_type_formatting_indents_blocks_and_lists() {
+        use firm_lang::generate::generator_options::IndentStyle;
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::{apply_edits, on_type_formatting};
+        use tree_sitter::Point;
+
+        let indent = IndentStyle::Spaces(4);
+        let format = |source: &str, position: Point, typed: char| {
+            let parsed = parse_source(source.to_string(), None).unwrap();
+            let edits = on_type_formatting(&parsed, position, typed, &indent);
+            apply_edits(source, &edits)
+        };
+
+        // Enter inside a list continues at the list's level
+        let source = "person jane {\n    skills = [\n\"rust\",\n    ]\n}\n";
+        assert_eq!(
+            format(source, Point { row: 2, column: 0 }, '\n'),
+            "person jane {\n    skills = [\n        \"rust\",\n    ]\n}\n"
+        );
+
+        // A half-typed block still indents
+        let source = "person jane {\n";
+        assert_eq!(
+            format(source, Point { row: 1, column: 0 }, '\n'),
+            "person jane {\n    "
+        );
+
+        // A closing bracket lines up with its opening line
+        let source = "person jane {\n    name = \"Jane\"\n        }\n";
+        assert_eq!(
+            format(source, Point { row: 2, column: 9 }, '}'),
+            "person jane {\n    name = \"Jane\"\n}\n"
+        );
+
+        // Multi-line strings are left alone
+        let source = "person jane {\n    bio = \"\"\"\nFirst line\n\"\"\"\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+        assert!(on_type_formatting(&parsed, Point { row: 2, column: 0 }, '\n', &indent).is_empty());
+    }
+
+    #[test]
+    fn test_document_links_for_paths_and_urls() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::{DocumentLinkTarget, PathCheckOptions, document_links};
+        use tree_sitter::Point;
+
+        let source = "contract msa {\n    file = path\"../contracts/msa.pdf\"\n    scan = path\"..\\\\scans\\\\msa.png\"\n    secret = path\"../../secrets.txt\"\n    notes = \"Signed copy at https://example.com/msa\"\n}\n";
+        let parsed = parse_source(
+            source.to_string(),
+            Some(PathBuf::from("./clients/acme.firm")),
+        )
+        .unwrap();
+        let root = Path::new("/workspace");
+
+        let links = document_links(&parsed, root, &PathCheckOptions::default());
+        let targets: Vec<_> = links.iter().map(|link| link.target.clone()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                DocumentLinkTarget::File(PathBuf::from("/workspace/contracts/msa.pdf")),
+                DocumentLinkTarget::File(PathBuf::from("/workspace/scans/msa.png")),
+                DocumentLinkTarget::Url("https://example.com/msa".to_string()),
+            ]
+        );
+        assert_eq!(links[0].range.start_point, Point { row: 1, column: 11 });
+        assert_eq!(links[2].range.start_point, Point { row: 4, column: 28 });
+
+        // Paths outside the workspace are linked when allowed
+        let options = PathCheckOptions {
+            allow_external_paths: true,
+        };
+        assert_eq!(document_links(&parsed, root, &options).len(), 4);
+    }
+
+    #[test]
+    fn test_selection_ranges_grow_to_the_block() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::selection_ranges;
+        use tree_sitter::Point;
+
+        let source = "person jane {\n    name = \"Jane\"\n}\n";
+        let parsed = parse_source(source.to_string(), None).unwrap();
+
+        // Inside the string value
+        let selections = selection_ranges(&parsed, &[Point { row: 1, column: 13 }]);
+        assert_eq!(selections.len(), 1);
+
+        let mut texts = Vec::new();
+        let mut selection = Some(&selections[0]);
+        while let Some(current) = selection {
+            texts.push(&source[current.range.start_byte..current.range.end_byte]);
+            selection = current.parent.as_deref();
+        }
+
+        // Each range contains the previous one, ending with the whole document
+        assert!(texts.windows(2).all(|pair| pair[1].contains(pair[0])));
+        assert!(texts.contains(&"name = \"Jane\""));
+        assert!(texts.contains(&source.trim_end()));
+        assert_eq!(texts.last().unwrap().trim_end(), source.trim_end());
+    }
+
+    #[test]
+    fn test_workspace_symbols_match_ids_and_types() {
+        use firm_lang::workspace::SymbolKind;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n\nperson jane { name = \"Jane\" }\nperson john { name = \"John\" }\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        let symbols = workspace.workspace_symbols("JA");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "jane");
+        assert_eq!(symbols[0].container.as_deref(), Some("person"));
+
+        // The type prefix matches the schema and all of its entities
+        let symbols = workspace.workspace_symbols("person");
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0].kind, SymbolKind::Schema);
+
+        assert_eq!(workspace.workspace_symbols("person.j").len(), 2);
+        assert!(workspace.workspace_symbols("nobody").is_empty());
+    }
+
+    #[test]
+    fn test_workspace_symbols_fuzzy_match_across_files() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person jane_doe { name = \"Jane\" }\nperson doe { name = \"Doe\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("projects.firm"),
+            "project doe_launch { name = \"Launch\" }\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        // Closer matches come first, wherever they're defined
+        let symbols = workspace.workspace_symbols("doe");
+        let names: Vec<&str> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["doe", "doe_launch", "jane_doe"]);
+        assert!(symbols[1].path.ends_with("projects.firm"));
+
+        let symbols = workspace.workspace_symbols("jdoe");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "jane_doe");
+        assert_eq!(symbols[0].range.start_point.column, 7);
+    }
+
+    #[test]
+    fn test_collect_diagnostics_across_files() {
+        use firm_lang::workspace::DiagnosticSeverity;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("schemas.firm"),
+            "schema person {\n    field {\n        name = \"manager\"\n        type = \"reference\"\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person jane {\n    manager = person.nobody\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        let diagnostics = workspace.collect_diagnostics();
+
+        // Clean files still get an entry, so editors can clear them
+        assert!(diagnostics[&root.join("schemas.firm")].is_empty());
+
+        let people = &diagnostics[&root.join("people.firm")];
+        assert_eq!(people.len(), 1);
+        assert_eq!(people[0].code, "broken-reference");
+        assert_eq!(people[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(people[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_changes_publish_diagnostics_for_other_files() {
+        use firm_lang::workspace::changed_diagnostic_files;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("schemas.firm"),
+            "schema person {\n    field {\n        name = \"manager\"\n        type = \"reference\"\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person jane {\n    manager = person.nobody\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let people = root.join("people.firm");
+
+        // The broken reference spans its field, without the indentation
+        let before = workspace.collect_diagnostics();
+        let source = workspace.file_source(&people).unwrap();
+        let span = before[&people][0].span(source);
+        assert_eq!(
+            &source[span.start_byte..span.end_byte],
+            "manager = person.nobody"
+        );
+
+        // Adding the entity in another file fixes the reference
+        workspace
+            .open_document(root.join("more.firm"), "person nobody {}\n".to_string())
+            .unwrap();
+        let after = workspace.collect_diagnostics();
+        assert!(after[&people].is_empty());
+        assert_eq!(changed_diagnostic_files(&before, &after), vec![people]);
+    }
+
+    #[test]
+    fn test_diagnostics_for_stale_changes_are_dropped() {
+        use firm_lang::workspace::Generation;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            "schema person {\n    field {\n        name = \"manager\"\n        type = \"reference\"\n    }\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let people = root.join("people.firm");
+        let generation = Generation::new();
+
+        // A change is superseded before its diagnostics are collected
+        workspace
+            .open_document(
+                people.clone(),
+                "person jane {\n    manager = person.nobody\n}\n".to_string(),
+            )
+            .unwrap();
+        let stale = generation.advance();
+        workspace
+            .open_document(people.clone(), "person jane {}\n".to_string())
+            .unwrap();
+        let latest = generation.advance();
+        assert!(workspace.collect_diagnostics_for(&stale).is_none());
+
+        // The latest change's diagnostics match its text
+        let diagnostics = workspace.collect_diagnostics_for(&latest).unwrap();
+        assert!(diagnostics[&people].is_empty());
+    }
+
+    #[test]
+    fn test_diagnostics_point_at_list_items_with_wrong_type() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            "schema project {\n    field {\n        name = \"urls\"\n        type = \"list\"\n        item_type = \"string\"\n    }\n}\n\nproject site {\n    urls = [\n        1,\n        2\n    ]\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        assert!(matches!(
+            workspace.build(),
+            Err(WorkspaceError::InvalidListItem(_, 11, _))
+        ));
+
+        let root = temp_dir.path().canonicalize().unwrap();
+        let diagnostics = workspace.collect_diagnostics();
+        let main = &diagnostics[&root.join("main.firm")];
+        assert_eq!(main.len(), 1);
+        assert_eq!(main[0].code, "validation-error");
+        assert_eq!(main[0].line, Some(11));
+    }
+
+    #[test]
+    fn test_diagnostics_report_every_syntax_error_and_check_the_rest() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            r#"schema task {
+    field {
+        name = "title"
+        type = "string"
+        required = true
+    }
+    field {
+        name = "owner"
+        type = "reference"
+    }
+}
+
+task first {
+    title = "First"
+    owner = 42.3.4
+}
+
+task second {
+    title = "Second"
+    owner = task.too.many.parts.here
+}
+
+task third {
+    title = "Third"
+    owner = task.nobody
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        let root = temp_dir.path().canonicalize().unwrap();
+        let diagnostics = workspace.collect_diagnostics();
+        let main = &diagnostics[&root.join("main.firm")];
+
+        // Both broken blocks are reported, with their spans
+        let syntax_lines: Vec<usize> = main
+            .iter()
+            .filter(|d| d.code == "syntax-error")
+            .map(|d| d.line.unwrap())
+            .collect();
+        assert!(syntax_lines.contains(&15));
+        assert!(syntax_lines.contains(&20));
+        assert!(main.iter().filter(|d| d.code == "syntax-error").all(|d| {
+            d.range
+                .is_some_and(|range| range.start_point.row + 1 == d.line.unwrap())
+        }));
+
+        // The valid block is still built and checked
+        let broken_reference = main.iter().find(|d| d.code == "broken-reference").unwrap();
+        assert_eq!(broken_reference.line, Some(25));
+    }
+
+    #[test]
+    fn test_diagnostics_skip_references_to_blocks_with_syntax_errors() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("schemas.firm"),
+            r#"schema person {
+    field { name = "manager" type = "reference" }
+}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person john {
+    manager = 42.3.4
+}
+",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("team.firm"),
+            r#"person jane {
+    manager = person.john
+}
+
+person ann {
+    manager = person.nobody
+}
+"#,
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        let root = temp_dir.path().canonicalize().unwrap();
+        let diagnostics = workspace.collect_diagnostics();
+
+        let people = &diagnostics[&root.join("people.firm")];
+        assert!(people.iter().any(|d| d.code == "syntax-error"));
+
+        // Only the reference to an entity that doesn't exist at all is broken
+        let team = &diagnostics[&root.join("team.firm")];
+        assert_eq!(team.len(), 1);
+        assert_eq!(team[0].code, "broken-reference");
+        assert_eq!(team[0].line, Some(6));
+    }
+
+    #[test]
+    fn test_collect_status_counts_blocks_and_diagnostics() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("main.firm"),
+            "schema person {\n    field {\n        name = \"manager\"\n        type = \"reference\"\n    }\n}\n\nperson jane {\n    manager = person.john\n}\n\nperson john {\n    manager = person.nobody\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        let (diagnostics, status) = workspace.collect_status();
+        assert_eq!(diagnostics, workspace.collect_diagnostics());
+        assert!(status.builds);
+        assert_eq!(status.files, 1);
+        assert_eq!(status.entities, 2);
+        assert_eq!(status.schemas, 1);
+        assert_eq!(status.errors, 1);
+        assert_eq!(status.warnings, 0);
+    }
+
+    #[test]
+    fn test_reload_rescans_the_workspace_directory() {
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.firm"), "").unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(workspace.num_files(), 1);
+
+        // Files added and removed outside the workspace show up after a reload
+        fs::write(temp_dir.path().join("b.firm"), "").unwrap();
+        fs::write(temp_dir.path().join("c.firm"), "").unwrap();
+        fs::remove_file(temp_dir.path().join("a.firm")).unwrap();
+        workspace.reload().unwrap();
+        assert_eq!(workspace.num_files(), 2);
+
+        // Without a directory there's nothing to reload
+        let mut empty = Workspace::new();
+        empty.reload().unwrap();
+        assert_eq!(empty.num_files(), 0);
+    }
+
+    #[test]
+    fn test_reload_files_only_reads_the_given_files() {
+        use firm_core::{FieldId, FieldValue};
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let write = |name: &str, content: &str| {
+            fs::write(temp_dir.path().join(name), content).unwrap();
+        };
+        write(
+            "schemas.firm",
+            "schema person {\n    field { name = \"name\" type = \"string\" required = true }\n}\n",
+        );
+        write("a.firm", "person a {\n    name = \"A\"\n}\n");
+        write("b.firm", "person b {\n    name = \"B\"\n}\n");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+
+        // Both files change on disk, but only one is read again
+        write("a.firm", "person a {\n    name = \"A2\"\n}\n");
+        write("b.firm", "person b {\n    name = \"B2\"\n}\n");
+        workspace.reload_files(&[PathBuf::from("a.firm")]).unwrap();
+
+        let build = workspace.build().unwrap();
+        let name = |id: &str| {
+            build
+                .entities
+                .iter()
+                .find(|entity| entity.id.as_str() == id)
+                .and_then(|entity| entity.get_field(&FieldId::new("name")))
+                .cloned()
+        };
+        assert_eq!(name("person.a"), Some(FieldValue::String("A2".to_string())));
+        assert_eq!(name("person.b"), Some(FieldValue::String("B".to_string())));
+
+        // New files are added and gone files are dropped
+        write("c.firm", "person c {\n    name = \"C\"\n}\n");
+        fs::remove_file(temp_dir.path().join("b.firm")).unwrap();
+        workspace
+            .reload_files(&[
+                temp_dir.path().join("b.firm"),
+                temp_dir.path().join("c.firm"),
+            ])
+            .unwrap();
+        assert_eq!(workspace.num_files(), 3);
+
+        let build = workspace.build().unwrap();
+        let mut ids: Vec<&str> = build.entities.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["person.a", "person.c"]);
+    }
+
+    #[test]
+    fn test_open_documents_take_precedence_over_disk() {
+        use std::collections::HashMap;
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("schemas.firm"),
+            "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n    field {\n        name = \"email\"\n        type = \"string\"\n        required = true\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person jane {\n    name = \"Jane\"\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let schemas = root.join("schemas.firm");
+        let people = root.join("people.firm");
+        assert!(!workspace.collect_diagnostics()[&people].is_empty());
+
+        // An unsaved fix to the schema clears the errors in the dependent file
+        let fixed = "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n    field {\n        name = \"email\"\n        type = \"string\"\n    }\n}\n";
+        let mut workspace = workspace
+            .with_overlay(HashMap::from([(schemas.clone(), fixed.to_string())]))
+            .unwrap();
+        assert!(workspace.is_document_open(&schemas));
+        assert!(workspace.collect_diagnostics()[&people].is_empty());
+
+        // Reloading from disk keeps the open document
+        workspace.reload().unwrap();
+        workspace.load_file(&schemas, &root).unwrap();
+        assert!(workspace.collect_diagnostics()[&people].is_empty());
+
+        // Closing it goes back to the file on disk
+        workspace.close_document(&schemas).unwrap();
+        assert!(!workspace.is_document_open(&schemas));
+        assert!(!workspace.collect_diagnostics()[&people].is_empty());
+
+        // Documents without a file are dropped when closed
+        let draft = root.join("draft.firm");
+        workspace
+            .open_document(draft.clone(), "person john {\n}\n".to_string())
+            .unwrap();
+        assert_eq!(workspace.num_files(), 3);
+        workspace.close_document(&draft).unwrap();
+        assert_eq!(workspace.num_files(), 2);
+    }
+
+    #[test]
+    fn test_find_usages_of_entities_and_schema_fields() {
+        use std::fs;
+        use tree_sitter::Point;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schemas = "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n";
+        fs::write(temp_dir.path().join("schemas.firm"), schemas).unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person jane {\n    name = \"Jane\"\n}\n\nperson john {\n    name = \"John\"\n    manager = person.jane\n    mentors = [person.jane, person.john]\n    manager_name = person.jane.name\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        // On a reference: every reference to the entity, including in lists and field references
+        let source = "person bob {\n    manager = person.jane\n}\n";
+        let usages = workspace.find_usages(source, Point { row: 1, column: 20 }, false);
+        let lines: Vec<usize> = usages.iter().map(|usage| usage.line()).collect();
+        assert_eq!(lines, vec![7, 8, 9]);
+
+        // On the entity ID, optionally with the declaration
+        let source = "person jane {\n    name = \"Jane\"\n}\n";
+        let usages = workspace.find_usages(source, Point { row: 0, column: 8 }, true);
+        let lines: Vec<usize> = usages.iter().map(|usage| usage.line()).collect();
+        assert_eq!(lines, vec![1, 7, 8, 9]);
+
+        // On a schema field name: every assignment of the field
+        let usages = workspace.find_usages(schemas, Point { row: 2, column: 17 }, false);
+        let lines: Vec<usize> = usages.iter().map(|usage| usage.line()).collect();
+        assert_eq!(lines, vec![2, 6]);
+    }
+
+    #[test]
+    fn test_rename_entities_and_schema_fields() {
+        use firm_lang::workspace::{RenameError, apply_edits};
+        use std::fs;
+        use tree_sitter::Point;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schemas = "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n    field {\n        name = \"nickname\"\n        type = \"string\"\n    }\n}\n";
+        let people = "person jane {\n    name = \"Jane\"\n}\n\nperson john {\n    name = \"John\"\n    manager = person.jane\n    mentors = [person.jane, person.john]\n    manager_name = person.jane.name\n}\n";
+        fs::write(temp_dir.path().join("schemas.firm"), schemas).unwrap();
+        fs::write(temp_dir.path().join("people.firm"), people).unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        // Only the ID part of a reference is renamed
+        let range = workspace
+            .prepare_rename(people, Point { row: 6, column: 23 })
+            .unwrap();
+        assert_eq!(range.start_point.column, 21);
+        assert_eq!(range.end_point.column, 25);
+        assert_eq!(
+            workspace.prepare_rename(people, Point { row: 6, column: 15 }),
+            Err(RenameError::NotRenameable)
+        );
+
+        let edit = workspace
+            .rename(people, Point { row: 0, column: 8 }, "jane_doe")
+            .unwrap();
+        assert_eq!(edit.num_edits(), 4);
+        let renamed = apply_edits(people, &edit.changes[&root.join("people.firm")]);
+        assert!(renamed.starts_with("person jane_doe {"));
+        assert!(renamed.contains("manager = person.jane_doe\n"));
+        assert!(renamed.contains("[person.jane_doe, person.john]"));
+        assert!(renamed.contains("manager_name = person.jane_doe.name"));
+
+        // Field renames edit the schema, assignments and field references
+        let edit = workspace
+            .rename(schemas, Point { row: 2, column: 17 }, "full_name")
+            .unwrap();
+        let renamed_schemas = apply_edits(schemas, &edit.changes[&root.join("schemas.firm")]);
+        assert!(renamed_schemas.contains("name = \"full_name\""));
+        let renamed_people = apply_edits(people, &edit.changes[&root.join("people.firm")]);
+        assert_eq!(renamed_people.matches("full_name = ").count(), 2);
+        assert!(renamed_people.contains("manager_name = person.jane.full_name"));
+
+        // Conflicts and invalid names are rejected
+        assert_eq!(
+            workspace.rename(people, Point { row: 0, column: 8 }, "john"),
+            Err(RenameError::EntityExists {
+                entity_type: "person".to_string(),
+                entity_id: "john".to_string(),
+            })
+        );
+        assert_eq!(
+            workspace.rename(schemas, Point { row: 2, column: 17 }, "nickname"),
+            Err(RenameError::FieldExists {
+                entity_type: "person".to_string(),
+                field: "nickname".to_string(),
+            })
+        );
+        assert_eq!(
+            workspace.rename(people, Point { row: 0, column: 8 }, "Jane Doe"),
+            Err(RenameError::InvalidName("Jane Doe".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_find_definition_for_references_and_types() {
+        use std::fs;
+        use tree_sitter::Point;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("schemas.firm"),
+            "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person jane {\n    name = \"Jane\"\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        // An unsaved buffer referring to the saved entities
+        let source =
+            "person john {\n    manager = person.jane\n    boss_name = person.jane.name\n    other = person.nobody\n}\n";
+
+        let definition = workspace
+            .find_definition(source, Point { row: 1, column: 24 })
+            .expect("Reference should resolve");
+        assert_eq!(definition.path, root.join("people.firm"));
+        assert_eq!(definition.line(), 1);
+        assert_eq!(definition.range.start_point.column, 7);
+        assert_eq!(definition.range.end_point.column, 11);
+
+        let definition = workspace
+            .find_definition(source, Point { row: 2, column: 30 })
+            .expect("Field reference should resolve");
+        assert_eq!(definition.path, root.join("people.firm"));
+        assert_eq!(definition.line(), 2);
+        assert_eq!(definition.range.start_point.column, 4);
+
+        let definition = workspace
+            .find_definition(source, Point { row: 0, column: 2 })
+            .expect("Entity type should resolve to its schema");
+        assert_eq!(definition.path, root.join("schemas.firm"));
+        assert_eq!(definition.line(), 1);
+        assert_eq!(definition.range.start_point.column, 7);
+
+        // Dangling references and other tokens have no location
+        assert_eq!(
+            workspace.find_definition(source, Point { row: 3, column: 20 }),
+            None
+        );
+        assert_eq!(
+            workspace.find_definition(source, Point { row: 0, column: 8 }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_type_definition_and_implementations() {
+        use firm_lang::workspace::{Implementations, TypeDefinition};
+        use std::fs;
+        use tree_sitter::Point;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schemas = "schema person {\n    field {\n        name = \"name\"\n        type = \"string\"\n    }\n}\n";
+        fs::write(temp_dir.path().join("schemas.firm"), schemas).unwrap();
+        fs::write(
+            temp_dir.path().join("people.firm"),
+            "person zoe {\n    name = \"Zoe\"\n}\n\nperson adam {\n    name = \"Adam\"\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("more.firm"),
+            "person adam {\n    email = \"adam@example.com\"\n}\n\nperson mia {\n    name = \"Mia\"\n}\n",
+        )
+        .unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        // From anywhere in an entity block to its schema
+        let source = "person john {\n    name = \"John\"\n}\n\ntask chores {\n}\n";
+        let Some(TypeDefinition::Schema(definition)) =
+            workspace.find_type_definition(source, Point { row: 1, column: 12 })
+        else {
+            panic!("Entity block should resolve to its schema");
+        };
+        assert_eq!(definition.path, root.join("schemas.firm"));
+        assert_eq!(definition.line(), 1);
+
+        let missing = workspace
+            .find_type_definition(source, Point { row: 4, column: 7 })
+            .expect("Entity block should have a type definition");
+        assert_eq!(
+            missing.hint(),
+            Some("No schema defined for 'task'".to_string())
+        );
+        assert_eq!(
+            workspace.find_type_definition(source, Point { row: 3, column: 0 }),
+            None
+        );
+
+        // From a schema name to its entities, sorted by ID and listed once each
+        let implementations =
+            workspace.find_implementations(schemas, Point { row: 0, column: 9 }, 10);
+        let ids: Vec<_> = implementations
+            .locations
+            .iter()
+            .map(|location| (location.path.clone(), location.line()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (root.join("more.firm"), 1),
+                (root.join("more.firm"), 5),
+                (root.join("people.firm"), 1),
+            ]
+        );
+        assert_eq!(implementations.total, 3);
+        assert!(!implementations.is_truncated());
+
+        let capped = workspace.find_implementations(schemas, Point { row: 0, column: 9 }, 2);
+        assert_eq!(capped.locations.len(), 2);
+        assert_eq!(capped.total, 3);
+        assert!(capped.is_truncated());
+
+        // Only on the schema name
+        let inside = workspace.find_implementations(schemas, Point { row: 2, column: 10 }, 10);
+        assert_eq!(inside, Implementations::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_runs_validation_scripts() {
+        use firm_lang::workspace::{ScriptOptions, WorkspaceWarning};
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("invoices.firm"),
+            r#"schema invoice {
+    validate = path"./validate_invoice.sh"
+    field { name = "amount" type = "integer" required = true }
+}
+
+invoice small {
+    amount = 5
+}
+
+invoice negative {
+    amount = -5
+}
+"#,
+        )
+        .unwrap();
+        let script = temp_dir.path().join("validate_invoice.sh");
+        fs::write(
+            &script,
+            r#"#!/bin/sh
+input=$(cat)
+case "$input" in
+    *'"amount":-'*) echo '{"field": "amount", "message": "must be positive"}'; exit 1 ;;
+    *'"amount":5'*) echo 'small invoice' ;;
+esac
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Without scripts allowed, the build only warns that the script was skipped
+        let mut workspace = Workspace::new();
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+        assert_eq!(build.entities.len(), 2);
+        assert!(matches!(
+            build.warnings.as_slice(),
+            [WorkspaceWarning::SkippedScript { line: 2, .. }]
+        ));
+
+        // With scripts allowed, the rejection points at the field
+        let mut workspace =
+            Workspace::new().with_validation_scripts(Some(ScriptOptions::default()));
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let error = workspace.build().unwrap_err();
+        assert!(matches!(error, WorkspaceError::ScriptRejected(_, 11, _)));
+        assert!(error.to_string().contains(
+            "Entity 'invoice.negative' was rejected by validation script './validate_invoice.sh': \
+             amount: must be positive"
+        ));
+
+        // Messages from accepted entities are warnings
+        fs::write(
+            temp_dir.path().join("invoices.firm"),
+            fs::read_to_string(temp_dir.path().join("invoices.firm"))
+                .unwrap()
+                .replace("-5", "7"),
+        )
+        .unwrap();
+        let mut workspace =
+            Workspace::new().with_validation_scripts(Some(ScriptOptions::default()));
+        workspace
+            .load_directory(&temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+        let messages: Vec<String> = build.warnings.iter().map(|w| w.message()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "validation script './validate_invoice.sh' on entity 'invoice.small': small invoice"
+            ]
+        );
+        assert_eq!(build.warnings[0].line(), 6);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_rejects_validation_scripts_outside_the_workspace() {
+        use firm_lang::parser::dsl::parse_source;
+        use firm_lang::workspace::ScriptOptions;
+        use std::fs;
+        use std::os::unix::fs::symlink;
+
+        let outside_dir = TempDir::new().unwrap();
+        let outside_script = outside_dir.path().join("validate.sh");
+        fs::write(&outside_script, "#!/bin/sh\n").unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("inside.sh"), "#!/bin/sh\n").unwrap();
+        symlink(&outside_script, temp_dir.path().join("linked.sh")).unwrap();
+        let schema = |script: &str| {
+            format!(
+                r#"schema invoice {{
+    validate = path"{}"
+    field {{ name = "amount" type = "integer" required = true }}
+}}
+"#,
+                script
+            )
+        };
+        let build = |script: &str| {
+            fs::write(temp_dir.path().join("invoices.firm"), schema(script)).unwrap();
+            let mut workspace =
+                Workspace::new().with_validation_scripts(Some(ScriptOptions::default()));
+            workspace
+                .load_directory(&temp_dir.path().to_path_buf())
+                .unwrap();
+            workspace.build()
+        };
+
+        assert!(build("./inside.sh").is_ok());
+
+        // Links and missing files are followed before checking, and rejected
+        for script in ["./linked.sh", "./missing.sh", "../validate.sh"] {
+            let error = build(script).unwrap_err();
+            assert!(
+                error.to_string().contains("isn't a file in the workspace"),
+                "{}: {}",
+                script,
+                error
+            );
+        }
+
+        // Without a workspace root, no script is part of the workspace
+        let mut workspace =
+            Workspace::new().with_validation_scripts(Some(ScriptOptions::default()));
+        let parsed = parse_source(schema("./inside.sh"), None).unwrap();
+        workspace.update_file(temp_dir.path().join("invoices.firm"), parsed);
+        assert!(workspace.build().is_err());
+    }
+
+    #[test]
+    fn test_build_sets_computed_fields() {
+        use firm_core::{EntityId, FieldId, FieldValue};
+        use std::fs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("goals.firm");
+
+        let content = r#"
+schema goal {
+    field {
+        name = "start_value"
+        type = "integer"
+        required = true
+    }
+    field {
+        name = "current_value"
+        type = "integer"
+        required = true
+    }
+    field {
+        name = "target_value"
+        type = "integer"
+        required = false
+    }
+    computed {
+        name = "progress"
+        expr = "(current_value - start_value)/(target_value - start_value)"
+    }
+}
+
+goal revenue {
+    start_value = 10
+    current_value = 25
+    target_value = 40
+}
+
+goal hiring {
+    start_value = 0
+    current_value = 3
+}
+"#;
+
+        fs::write(&test_file, content).expect("Write test file");
+
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&test_file, &temp_dir.path().to_path_buf())
+            .unwrap();
+        let build = workspace.build().unwrap();
+
+        let progress = |id: &str| {
+            build
+                .entities
+                .iter()
+                .find(|entity| entity.id == EntityId::new(id))
+                .unwrap()
+                .get_field(&FieldId::new("progress"))
+                .cloned()
+        };
+        assert_eq!(progress("goal.revenue"), Some(FieldValue::Float(0.5)));
+        // A missing target leaves progress unset
+        assert_eq!(progress("goal.hiring"), None);
+
+        // Computed fields can't be written
+        fs::write(
+            &test_file,
+            content.replace("target_value = 40", "target_value = 40\n    progress = 0.9"),
+        )
+        .expect("Write test file");
+        let mut workspace = Workspace::new();
+        workspace
+            .load_file(&test_file, &temp_dir.path().to_path_buf())
+            .unwrap();
+        assert!(matches!(
+            workspace.build(),
+            Err(WorkspaceError::ValidationError(_, _))
+        ));
+    }
 }
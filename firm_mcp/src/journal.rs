@@ -0,0 +1,230 @@
+//! Change journal for the Firm MCP server.
+//!
+//! Every mutating tool records the files it changed, with their content before
+//! and after, so the most recent changes can be listed and undone. The journal
+//! lives in memory for the lifetime of the server process, so restarting the
+//! server clears it.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+/// How many changes the journal keeps, dropping the oldest beyond that.
+pub const JOURNAL_CAPACITY: usize = 20;
+
+/// A file changed by a tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    /// The path of the file (relative to workspace root).
+    pub path: String,
+    /// The content before the change, or None if the change created the file.
+    pub before: Option<String>,
+    /// The content after the change, or None if the change deleted the file.
+    pub after: Option<String>,
+}
+
+/// A change made by one call of a mutating tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Increasing number identifying the change within the server process.
+    pub id: u64,
+    /// The name of the tool that made the change.
+    pub tool: String,
+    /// A short description of the change.
+    pub summary: String,
+    /// The files the change wrote, created or deleted.
+    pub files: Vec<FileChange>,
+}
+
+/// The most recent changes made by mutating tools, oldest first.
+#[derive(Debug, Default)]
+pub struct ChangeJournal {
+    entries: VecDeque<JournalEntry>,
+    next_id: u64,
+}
+
+impl ChangeJournal {
+    /// Creates an empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a change, given the content of each file before it.
+    ///
+    /// The content after the change is read from disk, so this is called once
+    /// the tool has written its files. Files the tool didn't change are left out.
+    pub fn record<I>(
+        &mut self,
+        workspace_path: &Path,
+        tool: &str,
+        summary: impl Into<String>,
+        before: I,
+    ) -> Option<&JournalEntry>
+    where
+        I: IntoIterator<Item = (String, Option<String>)>,
+    {
+        let files: Vec<FileChange> = before
+            .into_iter()
+            .map(|(path, before)| {
+                let after = fs::read_to_string(workspace_path.join(&path)).ok();
+                FileChange {
+                    path,
+                    before,
+                    after,
+                }
+            })
+            .filter(|change| change.before != change.after)
+            .collect();
+        if files.is_empty() {
+            return None;
+        }
+
+        self.next_id += 1;
+        self.entries.push_back(JournalEntry {
+            id: self.next_id,
+            tool: tool.to_string(),
+            summary: summary.into(),
+            files,
+        });
+        if self.entries.len() > JOURNAL_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.back()
+    }
+
+    /// Gets the most recent change.
+    pub fn last(&self) -> Option<&JournalEntry> {
+        self.entries.back()
+    }
+
+    /// Removes a change once it's been undone.
+    pub fn remove(&mut self, id: u64) -> Option<JournalEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        self.entries.remove(index)
+    }
+
+    /// Gets the recorded changes, most recent first.
+    pub fn entries(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.entries.iter().rev()
+    }
+
+    /// Gets the number of recorded changes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no changes are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Sets the content of a file, or removes it for None.
+///
+/// Returns whether the file ended up with the content.
+pub fn restore(workspace_path: &Path, path: &str, content: Option<&str>) -> bool {
+    let absolute_path = workspace_path.join(path);
+    match content {
+        Some(content) => {
+            if let Some(parent) = absolute_path.parent()
+                && fs::create_dir_all(parent).is_err()
+            {
+                return false;
+            }
+            fs::write(&absolute_path, content).is_ok()
+        }
+        None => !absolute_path.exists() || fs::remove_file(&absolute_path).is_ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_reads_content_after_change() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.firm"), "after").unwrap();
+        fs::write(dir.path().join("same.firm"), "same").unwrap();
+
+        let mut journal = ChangeJournal::new();
+        let entry = journal
+            .record(
+                dir.path(),
+                "write_source",
+                "Wrote a.firm",
+                [
+                    ("a.firm".to_string(), Some("before".to_string())),
+                    ("same.firm".to_string(), Some("same".to_string())),
+                    ("gone.firm".to_string(), Some("deleted".to_string())),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(entry.id, 1);
+        assert_eq!(
+            entry.files,
+            vec![
+                FileChange {
+                    path: "a.firm".to_string(),
+                    before: Some("before".to_string()),
+                    after: Some("after".to_string()),
+                },
+                FileChange {
+                    path: "gone.firm".to_string(),
+                    before: Some("deleted".to_string()),
+                    after: None,
+                },
+            ]
+        );
+
+        // Nothing changed, so nothing is recorded
+        let entry = journal.record(
+            dir.path(),
+            "write_source",
+            "Wrote same.firm",
+            [("same.firm".to_string(), Some("same".to_string()))],
+        );
+        assert!(entry.is_none());
+        assert_eq!(journal.len(), 1);
+    }
+
+    #[test]
+    fn test_journal_keeps_latest_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.firm"), "after").unwrap();
+
+        let mut journal = ChangeJournal::new();
+        for i in 0..JOURNAL_CAPACITY + 5 {
+            journal.record(
+                dir.path(),
+                "write_source",
+                format!("Change {}", i),
+                [("a.firm".to_string(), None)],
+            );
+        }
+
+        assert_eq!(journal.len(), JOURNAL_CAPACITY);
+        let last = journal.last().unwrap();
+        assert_eq!(last.summary, format!("Change {}", JOURNAL_CAPACITY + 4));
+        assert_eq!(journal.entries().next(), Some(last));
+
+        let id = last.id;
+        assert!(journal.remove(id).is_some());
+        assert!(journal.remove(id).is_none());
+        assert_eq!(journal.last().unwrap().id, id - 1);
+    }
+
+    #[test]
+    fn test_restore() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(restore(dir.path(), "people/a.firm", Some("content")));
+        let content = fs::read_to_string(dir.path().join("people/a.firm")).unwrap();
+        assert_eq!(content, "content");
+
+        assert!(restore(dir.path(), "people/a.firm", None));
+        assert!(!dir.path().join("people/a.firm").exists());
+        assert!(restore(dir.path(), "people/a.firm", None));
+    }
+}
@@ -3,6 +3,7 @@
 //! This crate provides an MCP (Model Context Protocol) server that exposes
 //! Firm workspace operations to AI assistants like Claude.
 
+pub mod journal;
 pub mod resources;
 mod server;
 pub mod tools;
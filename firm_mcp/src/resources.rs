@@ -1,13 +1,26 @@
 //! Resource handling for the Firm MCP server.
 //!
-//! Resources expose .firm source files to MCP clients:
+//! Resources expose .firm source files, schemas and named queries to MCP clients:
 //! - `firm://source` - lists all .firm file paths in the workspace
 //! - `firm://source/{path}` - reads the contents of a specific .firm file
+//! - `firm://schema/{name}` - reads a schema, from the workspace or the built-in defaults,
+//!   as DSL and JSON, or only as JSON with `?format=json`
+//! - `firm://reference/{topic}` - reads the DSL (`dsl`) or query language (`query`) reference
+//! - `firm://query/{name}` - runs a named query saved in the workspace and reads its results
 
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use rmcp::model::{AnnotateAble, RawResource, Resource};
+use firm_core::EntitySchema;
+use firm_lang::defaults::all_default_schemas;
+use firm_lang::generate::generate_schema_dsl;
+use firm_lang::workspace::{self, NamedQuery, WorkspaceChange};
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+
+use crate::tools::dsl_reference::reference_content;
+use crate::tools::get::{Candidates, schema_not_found_message};
+use crate::tools::response_format::{ResponseFormat, schema_to_json};
 
 /// The URI scheme for Firm resources.
 pub const SCHEME: &str = "firm";
@@ -15,6 +28,29 @@ pub const SCHEME: &str = "firm";
 /// The resource type for source files.
 pub const SOURCE_TYPE: &str = "source";
 
+/// The resource type for schemas.
+pub const SCHEMA_TYPE: &str = "schema";
+
+/// The resource type for language references.
+pub const REFERENCE_TYPE: &str = "reference";
+
+/// The resource type for named queries.
+pub const QUERY_TYPE: &str = "query";
+
+/// The language reference topics, with their titles and descriptions.
+const REFERENCES: [(&str, &str, &str); 2] = [
+    (
+        "dsl",
+        "Firm DSL reference",
+        "Firm DSL reference: entities, schemas and field types",
+    ),
+    (
+        "query",
+        "Firm query language reference",
+        "Firm query language reference: clauses, operators, values and examples",
+    ),
+];
+
 /// Creates a URI for a specific source file.
 pub fn source_file_uri(relative_path: &str) -> String {
     format!("{}://{}/{}", SCHEME, SOURCE_TYPE, relative_path)
@@ -47,12 +83,290 @@ pub fn source_file_resource(relative_path: &str) -> Resource {
     .no_annotation()
 }
 
+/// Creates a URI for a schema.
+pub fn schema_uri(schema_name: &str) -> String {
+    format!("{}://{}/{}", SCHEME, SCHEMA_TYPE, schema_name)
+}
+
+/// Parses a schema URI and returns the schema name and the requested format.
+///
+/// A `format=json` query asks for the schema as JSON only.
+/// Returns `None` if the URI doesn't match the expected format.
+pub fn parse_schema_uri(uri: &str) -> Option<(String, ResponseFormat)> {
+    let prefix = format!("{}://{}/", SCHEME, SCHEMA_TYPE);
+    let rest = uri.strip_prefix(&prefix)?;
+    let (name, format) = match rest.split_once('?') {
+        Some((name, query)) if query.split('&').any(|param| param == "format=json") => {
+            (name, ResponseFormat::Json)
+        }
+        Some((name, _)) => (name, ResponseFormat::Text),
+        None => (rest, ResponseFormat::Text),
+    };
+    (!name.is_empty()).then(|| (name.to_string(), format))
+}
+
+/// Creates a Resource for a schema.
+pub fn schema_resource(schema_name: &str, built_in: bool) -> Resource {
+    let description = if built_in {
+        format!("Built-in Firm schema: {}", schema_name)
+    } else {
+        format!("Firm schema: {}", schema_name)
+    };
+
+    RawResource {
+        uri: schema_uri(schema_name),
+        name: schema_name.to_string(),
+        title: Some(format!("{} schema", schema_name)),
+        description: Some(description),
+        mime_type: Some("text/plain".to_string()),
+        size: None,
+        icons: None,
+        meta: None,
+    }
+    .no_annotation()
+}
+
+/// Creates Resources for the workspace schemas, and the built-in defaults it doesn't define.
+///
+/// Resources are sorted by schema name.
+pub fn schema_resources(schemas: &[EntitySchema]) -> Vec<Resource> {
+    let mut resources: Vec<Resource> = schemas
+        .iter()
+        .map(|schema| schema_resource(schema.entity_type.as_str(), false))
+        .chain(
+            all_default_schemas()
+                .iter()
+                .filter(|default| !schemas.iter().any(|s| s.entity_type == default.entity_type))
+                .map(|default| schema_resource(default.entity_type.as_str(), true)),
+        )
+        .collect();
+
+    resources.sort_by(|a, b| a.name.cmp(&b.name));
+    resources
+}
+
+/// Reads a schema as DSL and as JSON.
+///
+/// Schemas defined in the workspace come first, then the built-in defaults.
+pub fn read_schema(
+    schemas: &[EntitySchema],
+    schema_name: &str,
+) -> Result<(String, serde_json::Value), String> {
+    let read = |schema: &EntitySchema| (generate_schema_dsl(schema), schema_to_json(schema));
+
+    if let Some(schema) = schemas
+        .iter()
+        .find(|s| s.entity_type.as_str() == schema_name)
+    {
+        return Ok(read(schema));
+    }
+
+    let defaults = all_default_schemas();
+    match defaults
+        .iter()
+        .find(|s| s.entity_type.as_str() == schema_name)
+    {
+        Some(schema) => Ok(read(schema)),
+        None => {
+            let names = schemas
+                .iter()
+                .chain(&defaults)
+                .map(|s| s.entity_type.as_str());
+            Err(schema_not_found_message(
+                schema_name,
+                names,
+                Candidates::Similar,
+            ))
+        }
+    }
+}
+
+/// Creates the contents of a schema resource in the requested format.
+///
+/// Text gives the DSL followed by the JSON, so clients can pick either.
+pub fn schema_contents(
+    uri: &str,
+    dsl: String,
+    json: String,
+    format: ResponseFormat,
+) -> Vec<ResourceContents> {
+    let json = text_contents(json, uri, "application/json");
+    match format {
+        ResponseFormat::Text => vec![text_contents(dsl, uri, "text/plain"), json],
+        ResponseFormat::Json => vec![json],
+    }
+}
+
+/// Creates text contents for a resource with a specific MIME type.
+pub fn text_contents(text: String, uri: &str, mime_type: &str) -> ResourceContents {
+    let mut contents = ResourceContents::text(text, uri);
+    if let ResourceContents::TextResourceContents {
+        mime_type: contents_mime_type,
+        ..
+    } = &mut contents
+    {
+        *contents_mime_type = Some(mime_type.to_string());
+    }
+    contents
+}
+
+/// Creates a URI for a language reference.
+pub fn reference_uri(topic: &str) -> String {
+    format!("{}://{}/{}", SCHEME, REFERENCE_TYPE, topic)
+}
+
+/// Parses a language reference URI and returns the topic.
+///
+/// Returns `None` if the URI doesn't match the expected format.
+pub fn parse_reference_uri(uri: &str) -> Option<String> {
+    let prefix = format!("{}://{}/", SCHEME, REFERENCE_TYPE);
+    uri.strip_prefix(&prefix)
+        .filter(|topic| REFERENCES.iter().any(|(name, _, _)| name == topic))
+        .map(str::to_string)
+}
+
+/// Creates Resources for the DSL and query language references.
+pub fn reference_resources() -> Vec<Resource> {
+    REFERENCES
+        .iter()
+        .map(|(topic, title, description)| {
+            RawResource {
+                uri: reference_uri(topic),
+                name: format!("{} reference", topic),
+                title: Some(title.to_string()),
+                description: Some(description.to_string()),
+                mime_type: Some("text/markdown".to_string()),
+                size: None,
+                icons: None,
+                meta: None,
+            }
+            .no_annotation()
+        })
+        .collect()
+}
+
+/// Reads a language reference by topic.
+pub fn read_reference(topic: &str) -> Result<String, String> {
+    reference_content(topic).ok_or_else(|| format!("Unknown reference: {}", topic))
+}
+
+/// Creates a URI for a named query.
+pub fn query_uri(query_name: &str) -> String {
+    format!("{}://{}/{}", SCHEME, QUERY_TYPE, query_name)
+}
+
+/// Parses a named query URI and returns the query name.
+///
+/// Returns `None` if the URI doesn't match the expected format.
+pub fn parse_query_uri(uri: &str) -> Option<String> {
+    let prefix = format!("{}://{}/", SCHEME, QUERY_TYPE);
+    uri.strip_prefix(&prefix)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// Creates Resources for the named queries saved in the workspace.
+pub fn query_resources(queries: &[NamedQuery]) -> Vec<Resource> {
+    queries
+        .iter()
+        .map(|query| {
+            let description = query
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Saved query: {}", query.query.trim()));
+            RawResource {
+                uri: query_uri(&query.name),
+                name: query.name.clone(),
+                title: Some(format!("{} query", query.name)),
+                description: Some(description),
+                mime_type: Some("text/plain".to_string()),
+                size: None,
+                icons: None,
+                meta: None,
+            }
+            .no_annotation()
+        })
+        .collect()
+}
+
+/// The resources a client subscribed to, to notify it when they change.
+#[derive(Debug, Default)]
+pub struct ResourceSubscriptions {
+    uris: BTreeSet<String>,
+}
+
+/// What to notify a client of after files changed on disk.
+#[derive(Debug, Default, PartialEq)]
+pub struct ResourceNotifications {
+    /// The subscribed resources that may have changed, sorted.
+    pub updated: Vec<String>,
+    /// Whether files were created or removed, changing the list of resources.
+    pub list_changed: bool,
+}
+
+impl ResourceSubscriptions {
+    /// Subscribes to a source file, schema or reference resource.
+    pub fn subscribe(&mut self, uri: &str) -> Result<(), String> {
+        let known = parse_source_uri(uri).is_some()
+            || parse_schema_uri(uri).is_some()
+            || parse_reference_uri(uri).is_some()
+            || parse_query_uri(uri).is_some();
+        if !known {
+            return Err(format!("Invalid resource URI: {}", uri));
+        }
+
+        self.uris.insert(uri.to_string());
+        Ok(())
+    }
+
+    /// Unsubscribes from a resource. Unknown URIs are ignored.
+    pub fn unsubscribe(&mut self, uri: &str) {
+        self.uris.remove(uri);
+    }
+
+    /// Works out the notifications for files that changed on disk.
+    ///
+    /// Subscribed source files are updated when they change. Schemas can be
+    /// defined in any file and query results can depend on any entity, so
+    /// subscribed schemas and queries are updated on every change.
+    pub fn notifications(
+        &self,
+        workspace_path: &Path,
+        changes: &[WorkspaceChange],
+    ) -> ResourceNotifications {
+        let mut updated = BTreeSet::new();
+        for change in changes {
+            if let Some(relative_path) = to_relative_path(workspace_path, change.path()) {
+                let uri = source_file_uri(&relative_path);
+                if self.uris.contains(&uri) {
+                    updated.insert(uri);
+                }
+            }
+        }
+        if !changes.is_empty() {
+            updated.extend(
+                self.uris
+                    .iter()
+                    .filter(|uri| parse_schema_uri(uri).is_some() || parse_query_uri(uri).is_some())
+                    .cloned(),
+            );
+        }
+
+        ResourceNotifications {
+            updated: updated.into_iter().collect(),
+            list_changed: changes
+                .iter()
+                .any(|change| !matches!(change, WorkspaceChange::Modified(_))),
+        }
+    }
+}
+
 /// Converts an absolute path to a relative path within the workspace.
+///
+/// The result always uses forward slashes, and symlinked workspace roots
+/// match the canonical paths stored by the workspace.
 pub fn to_relative_path(workspace_path: &Path, absolute_path: &Path) -> Option<String> {
-    absolute_path
-        .strip_prefix(workspace_path)
-        .ok()
-        .map(|p| p.to_string_lossy().to_string())
+    workspace::relative_path(workspace_path, absolute_path)
 }
 
 /// Converts a relative path to an absolute path within the workspace.
@@ -102,6 +416,21 @@ pub fn read_source_file(workspace_path: &Path, relative_path: &str) -> Result<St
     fs::read_to_string(&absolute_path).map_err(|e| format!("Failed to read file: {}", e))
 }
 
+/// Gets the absolute path of a source file that may be written.
+///
+/// Only allows .firm files within the workspace, which don't have to exist yet.
+pub fn source_file_path(workspace_path: &Path, relative_path: &str) -> Result<PathBuf, String> {
+    if !relative_path.ends_with(".firm") {
+        return Err(format!(
+            "Path must end with .firm extension: {}",
+            relative_path
+        ));
+    }
+
+    to_absolute_path(workspace_path, relative_path)
+        .ok_or_else(|| format!("Invalid path (must be within workspace): {}", relative_path))
+}
+
 /// Writes content to a source file.
 ///
 /// Creates parent directories if they don't exist.
@@ -111,16 +440,7 @@ pub fn write_source_file(
     relative_path: &str,
     content: &str,
 ) -> Result<(), String> {
-    // Validate the path
-    if !relative_path.ends_with(".firm") {
-        return Err(format!(
-            "Path must end with .firm extension: {}",
-            relative_path
-        ));
-    }
-
-    let absolute_path = to_absolute_path(workspace_path, relative_path)
-        .ok_or_else(|| format!("Invalid path (must be within workspace): {}", relative_path))?;
+    let absolute_path = source_file_path(workspace_path, relative_path)?;
 
     // Create parent directories if needed
     if let Some(parent) = absolute_path.parent() {
@@ -134,6 +454,7 @@ pub fn write_source_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use firm_core::{EntityType, FieldId, FieldType};
 
     #[test]
     fn test_source_file_uri() {
@@ -153,6 +474,159 @@ mod tests {
         assert_eq!(parse_source_uri("other://source/file.firm"), None);
     }
 
+    #[test]
+    fn test_schema_uri() {
+        assert_eq!(schema_uri("person"), "firm://schema/person");
+        assert_eq!(
+            parse_schema_uri("firm://schema/person"),
+            Some(("person".to_string(), ResponseFormat::Text))
+        );
+        assert_eq!(
+            parse_schema_uri("firm://schema/person?format=json"),
+            Some(("person".to_string(), ResponseFormat::Json))
+        );
+        assert_eq!(parse_schema_uri("firm://schema/?format=json"), None);
+        assert_eq!(parse_schema_uri("firm://schema/"), None);
+        assert_eq!(parse_schema_uri("firm://source/people.firm"), None);
+    }
+
+    #[test]
+    fn test_reference_uri() {
+        assert_eq!(reference_uri("query"), "firm://reference/query");
+        assert_eq!(
+            parse_reference_uri("firm://reference/query"),
+            Some("query".to_string())
+        );
+        assert_eq!(parse_reference_uri("firm://reference/all"), None);
+
+        let uris: Vec<_> = reference_resources()
+            .iter()
+            .map(|r| r.uri.clone())
+            .collect();
+        assert_eq!(uris, vec!["firm://reference/dsl", "firm://reference/query"]);
+        assert!(read_reference("query").unwrap().contains("## Aggregations"));
+    }
+
+    #[test]
+    fn test_query_uri() {
+        assert_eq!(query_uri("open_tasks"), "firm://query/open_tasks");
+        assert_eq!(
+            parse_query_uri("firm://query/open_tasks"),
+            Some("open_tasks".to_string())
+        );
+        assert_eq!(parse_query_uri("firm://query/"), None);
+        assert_eq!(parse_query_uri("firm://schema/open_tasks"), None);
+    }
+
+    #[test]
+    fn test_query_resources() {
+        let queries = vec![
+            NamedQuery {
+                name: "open_tasks".to_string(),
+                query: "from task | where is_completed == false".to_string(),
+                description: Some("Tasks still to do".to_string()),
+                path: PathBuf::from("/workspace/queries.firm"),
+                line: 1,
+            },
+            NamedQuery {
+                name: "people".to_string(),
+                query: "from person".to_string(),
+                description: None,
+                path: PathBuf::from("/workspace/queries.firm"),
+                line: 5,
+            },
+        ];
+
+        let resources = query_resources(&queries);
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].uri, "firm://query/open_tasks");
+        assert_eq!(
+            resources[0].description.as_deref(),
+            Some("Tasks still to do")
+        );
+        assert_eq!(
+            resources[1].description.as_deref(),
+            Some("Saved query: from person")
+        );
+    }
+
+    #[test]
+    fn test_schema_resources_include_defaults() {
+        let custom = EntitySchema::new(EntityType::new("invoice"));
+        let person = EntitySchema::new(EntityType::new("person"));
+        let resources = schema_resources(&[custom, person]);
+
+        let names: Vec<_> = resources.iter().map(|r| r.name.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+        assert_eq!(names.iter().filter(|name| **name == "person").count(), 1);
+        assert!(names.contains(&"invoice"));
+        assert!(names.contains(&"task"));
+
+        let description = |name: &str| {
+            let resource = resources.iter().find(|r| r.name == name).unwrap();
+            resource.description.clone().unwrap()
+        };
+        assert_eq!(description("person"), "Firm schema: person");
+        assert_eq!(description("task"), "Built-in Firm schema: task");
+    }
+
+    #[test]
+    fn test_read_schema_prefers_the_workspace() {
+        let person = EntitySchema::new(EntityType::new("person"))
+            .with_required_field(FieldId::new("nickname"), FieldType::String);
+
+        let (dsl, json) = read_schema(&[person], "person").unwrap();
+        assert!(dsl.contains("nickname"));
+        assert_eq!(json["fields"].as_array().unwrap().len(), 1);
+
+        let (dsl, _) = read_schema(&[], "task").unwrap();
+        assert!(dsl.starts_with("schema task {"));
+
+        let error = read_schema(&[], "persn").unwrap_err();
+        assert!(error.contains("person"));
+    }
+
+    #[test]
+    fn test_schema_contents_by_format() {
+        let mime_types = |contents: &[ResourceContents]| -> Vec<Option<String>> {
+            contents
+                .iter()
+                .map(|c| match c {
+                    ResourceContents::TextResourceContents { mime_type, .. } => mime_type.clone(),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let uri = "firm://schema/person";
+        let both = schema_contents(
+            uri,
+            "schema person {}".into(),
+            "{}".into(),
+            ResponseFormat::Text,
+        );
+        assert_eq!(
+            mime_types(&both),
+            vec![
+                Some("text/plain".to_string()),
+                Some("application/json".to_string())
+            ]
+        );
+
+        let json = schema_contents(
+            uri,
+            "schema person {}".into(),
+            "{}".into(),
+            ResponseFormat::Json,
+        );
+        assert_eq!(
+            mime_types(&json),
+            vec![Some("application/json".to_string())]
+        );
+    }
+
     #[test]
     fn test_to_relative_path() {
         let workspace = Path::new("/workspace");
@@ -162,4 +636,14 @@ mod tests {
             Some("core/people.firm".to_string())
         );
     }
+
+    #[test]
+    fn test_to_relative_path_from_components() {
+        let workspace: PathBuf = ["/", "workspace"].iter().collect();
+        let absolute = workspace.join("core").join("people").join("john.firm");
+        assert_eq!(
+            to_relative_path(&workspace, &absolute),
+            Some("core/people/john.firm".to_string())
+        );
+    }
 }
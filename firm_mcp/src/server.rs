@@ -3,25 +3,37 @@
 //! This module contains the MCP protocol handling and delegates to the
 //! tools module for actual business logic.
 
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use log::debug;
+use log::{debug, warn};
 use rmcp::{
-    ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
-    handler::server::wrapper::Parameters, model::*, service::RequestContext, tool, tool_handler,
-    tool_router, transport::stdio,
+    ErrorData as McpError, Peer, RoleServer, ServerHandler, ServiceExt,
+    handler::server::wrapper::Parameters, model::*, service::NotificationContext,
+    service::RequestContext, tool, tool_handler, tool_router, transport::stdio,
 };
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard};
 
 use firm_core::graph::EntityGraph;
-use firm_lang::workspace::{Workspace, WorkspaceBuild, WorkspaceError};
+use firm_core::{Entity, EntityId};
+use firm_lang::workspace::{
+    DEFAULT_LOCK_TIMEOUT, Workspace, WorkspaceBuild, WorkspaceChange, WorkspaceConfig,
+    WorkspaceError, WorkspaceLock, WorkspaceWatcher, read_file_updates_with,
+};
 
-use crate::resources;
+use crate::journal::ChangeJournal;
+use crate::resources::{self, ResourceSubscriptions};
+use crate::tools::update_entity::ChangedFile;
 use crate::tools::{
-    self, AddEntityParams, BuildParams, DeleteSourceParams, DslReferenceParams,
-    FindSourceParams, GetParams, ListParams, QueryParams, ReadSourceParams, RelatedParams,
-    ReplaceSourceParams, SearchSourceParams, SourceTreeParams, WriteSourceParams,
+    self, AddEntitiesParams, AddEntityParams, AddSchemaParams, BuildParams, CheckReferencesParams,
+    DeleteEntityParams, DeleteSourceParams, DslReferenceParams, FindSourceParams, GetManyParams,
+    GetParams, ListChangesParams, ListParams, ListTodosParams, MoveEntityParams, OrphansParams,
+    QueryParams, ReadSourceParams, RelatedParams, ReplaceSourceParams, ResponseLimits,
+    RunQueryParams, SearchParams, SearchSourceParams, SourceTreeParams, StatsParams,
+    UndoLastChangeParams, UpdateEntityParams, ValidateParams, WriteSourceParams,
 };
 
 /// Error type for MCP server operations.
@@ -50,21 +62,46 @@ impl std::fmt::Display for ServerError {
 
 impl std::error::Error for ServerError {}
 
+/// Reads the text of a source file.
+type SourceReader = Arc<dyn Fn(&Path) -> io::Result<String> + Send + Sync>;
+
+/// How often to check for external edits to notify the client of.
+const CHANGE_NOTIFY_INTERVAL: Duration = Duration::from_millis(500);
+
 /// Internal state of the MCP server.
 pub struct ServerState {
     pub workspace: Workspace,
     pub build: WorkspaceBuild,
     pub graph: EntityGraph,
+    /// Watches for external edits, if watching could be started
+    watcher: Option<WorkspaceWatcher>,
+    /// Files changed on disk since the last successful build
+    changed_paths: BTreeSet<PathBuf>,
+    /// Changes seen by the watcher that the client hasn't been notified of
+    unnotified: Vec<WorkspaceChange>,
 }
 
 /// MCP server for a Firm workspace.
 ///
 /// Exposes workspace operations (query, list, get, etc.) as MCP tools,
-/// and source files as MCP resources.
+/// and source files and schemas as MCP resources.
 #[derive(Clone)]
 pub struct FirmMcpServer {
     workspace_path: PathBuf,
+    config: WorkspaceConfig,
     state: Arc<Mutex<ServerState>>,
+    /// Held while rebuilding, so rebuilds don't race each other
+    rebuild_lock: Arc<Mutex<()>>,
+    /// Resources the client wants to be notified about
+    subscriptions: Arc<Mutex<ResourceSubscriptions>>,
+    /// Changes made by mutating tools, for undoing them
+    journal: Arc<Mutex<ChangeJournal>>,
+    /// Whether tools that change the workspace are left out
+    read_only: bool,
+    /// How much the tools that return entities put in one response
+    response_limits: ResponseLimits,
+    /// Reads changed source files when rebuilding
+    source_reader: SourceReader,
     tool_router: rmcp::handler::server::router::tool::ToolRouter<FirmMcpServer>,
 }
 
@@ -72,21 +109,21 @@ pub struct FirmMcpServer {
 impl FirmMcpServer {
     /// Create a new MCP server for the given workspace path.
     ///
-    /// This will load and build the workspace. Returns an error if the
-    /// workspace cannot be loaded or has validation errors.
+    /// This will load the workspace config and build the workspace. Returns an
+    /// error if the config or workspace cannot be loaded or has validation errors.
     pub fn new(workspace_path: PathBuf) -> Result<Self, WorkspaceError> {
+        let config = WorkspaceConfig::load(&workspace_path)?;
+        Self::with_config(workspace_path, config)
+    }
+
+    /// Create a new MCP server for the given workspace path, with an already loaded config.
+    pub fn with_config(
+        workspace_path: PathBuf,
+        config: WorkspaceConfig,
+    ) -> Result<Self, WorkspaceError> {
         debug!("Creating MCP server for workspace: {:?}", workspace_path);
 
-        let mut workspace = Workspace::new();
-        workspace.load_directory(&workspace_path)?;
-        let build = workspace.build()?;
-
-        // Build the entity graph for query support
-        let mut graph = EntityGraph::new();
-        graph.add_entities(build.entities.clone()).map_err(|e| {
-            WorkspaceError::ValidationError(workspace_path.clone(), format!("{:?}", e))
-        })?;
-        graph.build();
+        let (workspace, build, graph) = load_workspace(&workspace_path, &config)?;
 
         debug!(
             "Workspace loaded: {} entities, {} schemas",
@@ -94,17 +131,103 @@ impl FirmMcpServer {
             build.schemas.len()
         );
 
+        // Watch for external edits so tools don't serve a stale build
+        let watcher = match WorkspaceWatcher::new(&workspace_path) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                warn!(
+                    "Failed to watch workspace, external edits need a manual rebuild: {}",
+                    e
+                );
+                None
+            }
+        };
+
         Ok(Self {
             workspace_path,
+            config,
             state: Arc::new(Mutex::new(ServerState {
                 workspace,
                 build,
                 graph,
+                watcher,
+                changed_paths: BTreeSet::new(),
+                unnotified: Vec::new(),
             })),
+            rebuild_lock: Arc::new(Mutex::new(())),
+            subscriptions: Arc::new(Mutex::new(ResourceSubscriptions::default())),
+            journal: Arc::new(Mutex::new(ChangeJournal::new())),
+            read_only: false,
+            response_limits: ResponseLimits::default(),
+            source_reader: Arc::new(|path: &Path| std::fs::read_to_string(path)),
             tool_router: Self::tool_router(),
         })
     }
 
+    /// Create a read-only MCP server for the given workspace path.
+    ///
+    /// Like `new`, but only tools that don't change the workspace are offered.
+    pub fn new_read_only(workspace_path: PathBuf) -> Result<Self, WorkspaceError> {
+        Ok(Self::new(workspace_path)?.with_read_only(true))
+    }
+
+    /// Sets whether the server is read-only.
+    ///
+    /// A read-only server only offers the tools annotated as read-only, so tools
+    /// that change the workspace can't be listed or called. Tools without the
+    /// annotation are left out too, so new tools are treated as writing by default.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self.tool_router = Self::tool_router();
+        if read_only {
+            let writing_tools: Vec<String> = self
+                .tool_router
+                .list_all()
+                .into_iter()
+                .filter(|tool| {
+                    let read_only_hint = tool.annotations.as_ref().and_then(|a| a.read_only_hint);
+                    read_only_hint != Some(true)
+                })
+                .map(|tool| tool.name.to_string())
+                .collect();
+            for name in writing_tools {
+                self.tool_router.remove_route(&name);
+            }
+        }
+        self
+    }
+
+    /// Sets how many entities, and how many bytes of them, the query, get, list
+    /// and related tools return before truncating.
+    ///
+    /// Callers can still lift the limits for a single call with `unlimited`.
+    pub fn with_response_limits(mut self, response_limits: ResponseLimits) -> Self {
+        self.response_limits = response_limits;
+        self
+    }
+
+    /// Sets how changed source files are read when rebuilding.
+    ///
+    /// Reads go to disk by default. Replacing them lets callers see what's
+    /// read, like tests checking that a rebuild only reads the changed files.
+    pub fn with_source_reader<R>(mut self, reader: R) -> Self
+    where
+        R: Fn(&Path) -> io::Result<String> + Send + Sync + 'static,
+    {
+        self.source_reader = Arc::new(reader);
+        self
+    }
+
+    /// Returns whether the server is read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns the tools the server offers, as they're listed to clients.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tool_router.list_all()
+    }
+
     /// Returns the workspace path this server operates on.
     pub fn workspace_path(&self) -> &PathBuf {
         &self.workspace_path
@@ -117,29 +240,74 @@ impl FirmMcpServer {
 
     #[tool(
         description = "List all entity IDs of a given type, or all schema names if type is 'schema'. \
+        Use type '*' to list entities of every type grouped by type, or omit type to get the number of entities per type. \
+        Filter IDs with 'id_pattern' (a glob like 'q1_*'), 'prefix' or 'contains'. \
+        IDs are sorted; set 'limit' to page through them, passing the returned cursor as 'cursor' to continue. \
         Returns only IDs/names for discovery purposes. Use 'get' to retrieve full details for a specific entity or schema, \
-        or use 'query' to fetch details for multiple entities matching search criteria."
+        or use 'query' to fetch details for multiple entities matching search criteria. \
+        Set format='json' for structured output with the total count and next cursor. \
+        Long pages are cut at the server's response limits with a cursor to continue; set 'unlimited: true' to lift them.",
+        annotations(read_only_hint = true)
     )]
     async fn list(
         &self,
         Parameters(params): Parameters<ListParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Tool: list, type={}", params.r#type);
-        let state = self.state.lock().await;
-        Ok(tools::list::execute(&state.build, &params))
+        debug!(
+            "Tool: list, type={:?}, id_pattern={:?}, limit={:?}",
+            params.r#type, params.id_pattern, params.limit
+        );
+        let state = self.current_state().await;
+        Ok(tools::list::execute(
+            &state.build,
+            &self.response_limits,
+            &params,
+        ))
     }
 
-    #[tool(description = "Get full details of a single entity or schema. \
+    #[tool(
+        description = "Get full details of a single entity or schema. \
         For entities: provide the entity type (e.g., 'person') and ID (e.g., 'john_doe'). \
         For schemas: use type='schema' and id=<schema_name> (e.g., id='person'). \
-        Returns all fields and their values. Use 'list' first to discover available IDs.")]
+        Returns all fields and their values. Use 'list' first to discover available IDs. \
+        Set include_related=true to also get the entities it references or is referenced by, grouped by type \
+        (optionally only 'related_types', up to 'related_limit' per type, default 10). \
+        Related entities past the server's response limits are counted instead; set 'unlimited: true' to list them all. \
+        Set format='json' for structured output with typed field values.",
+        annotations(read_only_hint = true)
+    )]
     async fn get(
         &self,
         Parameters(params): Parameters<GetParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Tool: get, type={}, id={}", params.r#type, params.id);
-        let state = self.state.lock().await;
-        Ok(tools::get::execute(&state.build, &params))
+        debug!(
+            "Tool: get, type={}, id={}, include_related={}",
+            params.r#type, params.id, params.include_related
+        );
+        let state = self.current_state().await;
+        Ok(tools::get::execute(
+            &state.build,
+            &state.graph,
+            &self.config.display.value_format(),
+            &self.response_limits,
+            &params,
+        ))
+    }
+
+    #[tool(
+        description = "Get full details of several entities in one call. \
+        Provide 'ids' as a list of {\"type\": ..., \"id\": ...} objects or composite IDs like \"person.john_doe\". \
+        Returns the found entities in the requested order, and lists any IDs that were not found. \
+        Set format='json' for structured output with typed field values.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_many(
+        &self,
+        Parameters(params): Parameters<GetManyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: get_many, count={}", params.ids.len());
+        let state = self.current_state().await;
+        Ok(tools::get_many::execute(&state.build, &params))
     }
 
     #[tool(
@@ -151,54 +319,169 @@ impl FirmMcpServer {
         'from person | where name contains \"John\" | limit 5', \
         'from task | count', 'from invoice | where status == \"sent\" | sum amount', \
         'from task | where is_completed == false | select @id, name, due_date'. \
-        Use 'list' for a simple ID overview, or 'get' for a single entity's details."
+        Set 'strict: true' to reject fields that are not declared in the schema. \
+        Set format='json' for structured output with typed field values. \
+        Large results are truncated at the server's response limits, ending with a marker that gives the total; \
+        refine the query with 'limit' or 'where', or set 'unlimited: true' to get every entity. \
+        Use 'list' for a simple ID overview, or 'get' for a single entity's details.",
+        annotations(read_only_hint = true)
     )]
     async fn query(
         &self,
         Parameters(params): Parameters<QueryParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Tool: query, query={}", params.query);
-        let state = self.state.lock().await;
-        Ok(tools::query::execute(&state.graph, &params))
+        debug!(
+            "Tool: query, query={}, strict={}",
+            params.query, params.strict
+        );
+        let state = self.current_state().await;
+        Ok(tools::query::execute(
+            &state.graph,
+            &state.build.schemas,
+            &self.config.timezone,
+            &self.config.display.value_format(),
+            &self.response_limits,
+            &params,
+        ))
     }
 
-    #[tool(description = "Get IDs of entities related to a specific entity. \
-        Returns entity IDs that reference or are referenced by the given entity. \
+    #[tool(
+        description = "Run a query saved in the workspace by its name. \
+        Named queries are defined in .firm files with blocks like \
+        'query open_tasks { query = \"from task | where is_completed == false\" description = \"Tasks still to do\" }'. \
+        Leave out 'name' to list the saved queries with their descriptions. \
+        Results are the same as running the query with the 'query' tool, \
+        and each saved query can also be read as the resource firm://query/{name}.",
+        annotations(read_only_hint = true)
+    )]
+    async fn run_query(
+        &self,
+        Parameters(params): Parameters<RunQueryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: run_query, name={:?}", params.name);
+        let state = self.current_state().await;
+        Ok(tools::run_query::execute(
+            &state.graph,
+            &state.build,
+            &self.config.timezone,
+            &self.config.display.value_format(),
+            &self.workspace_path,
+            &self.response_limits,
+            &params,
+        ))
+    }
+
+    #[tool(
+        description = "Answer numeric questions about the workspace with an aggregation. \
+        Pass a 'query' ending in count, sum, average or median, e.g. 'from task | count', \
+        'from task | group status | count', 'from invoice | where status == \"sent\" | sum amount'. \
+        Or use the shorthand: 'type' with optional 'where', 'group_by' and 'aggregate' \
+        (default 'count'), e.g. type='task', group_by='status'. \
+        Returns a one-line summary followed by the structured result as JSON. \
+        Use 'query' to fetch the matching entities instead.",
+        annotations(read_only_hint = true)
+    )]
+    async fn stats(
+        &self,
+        Parameters(params): Parameters<StatsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: stats, query={:?}, type={:?}, group_by={:?}",
+            params.query, params.r#type, params.group_by
+        );
+        let state = self.current_state().await;
+        Ok(tools::stats::execute(
+            &state.graph,
+            &self.config.timezone,
+            &params,
+        ))
+    }
+
+    #[tool(
+        description = "Get entities related to a specific entity. \
+        Returns the ID, type and name of each entity that references or is referenced \
+        by the given entity. \
         Use 'direction' to filter: 'incoming' (entities that reference this one), \
-        'outgoing' (entities this one references), or omit for both.")]
+        'outgoing' (entities this one references), or omit for both. \
+        Use 'degrees' to follow more hops, 'related_type' to only return entities of one type, \
+        and 'include_edges' to explain how each entity is connected as \
+        (entity, via_field, from_entity) triples. \
+        Large results are truncated at the server's response limits; set 'unlimited: true' to get them all.",
+        annotations(read_only_hint = true)
+    )]
     async fn related(
         &self,
         Parameters(params): Parameters<RelatedParams>,
     ) -> Result<CallToolResult, McpError> {
         debug!(
-            "Tool: related, type={}, id={}, direction={:?}",
-            params.r#type, params.id, params.direction
+            "Tool: related, type={}, id={}, direction={:?}, related_type={:?}",
+            params.r#type, params.id, params.direction, params.related_type
         );
-        let state = self.state.lock().await;
-        Ok(tools::related::execute(&state.graph, &params))
+        let state = self.current_state().await;
+        Ok(tools::related::execute(
+            &state.graph,
+            &self.response_limits,
+            &params,
+        ))
+    }
+
+    #[tool(
+        description = "Find entities that neither reference nor are referenced by other entities. \
+        These are often forgotten drafts, or data that should be connected to something. \
+        Use 'type' to only look at entities of one type. Returns entity IDs, like 'list'.",
+        annotations(read_only_hint = true)
+    )]
+    async fn orphans(
+        &self,
+        Parameters(params): Parameters<OrphansParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: orphans, type={:?}", params.r#type);
+        let state = self.current_state().await;
+        Ok(tools::orphans::execute(&state.graph, &params))
     }
 
     #[tool(description = "Add a new entity to the workspace. \
         Provide the entity type, ID, and a map of field values. \
         Field value formats: strings as JSON strings, numbers as JSON numbers, booleans as JSON booleans, \
-        references as \"type.id\" strings, currency as \"100 USD\" strings, \
+        references as \"type.id\" strings, currency as {\"amount\": \"100.50\", \"code\": \"USD\"} or \"100.50 USD\", \
+        enums as strings or {\"enum\": \"value\"}, \
         datetime as ISO 8601 strings (e.g. \"2025-01-15T17:00:00+03:00\" — not DSL format), \
-        lists as JSON arrays (requires list_item_types). \
+        lists as JSON arrays (requires list_item_types unless the schema field declares an item_type). \
+        To start from a copy of a similar entity, give its ID as from_id; the fields given override its fields. \
         The tool validates against the schema, generates DSL, and writes to a file.")]
     async fn add_entity(
         &self,
         Parameters(params): Parameters<AddEntityParams>,
     ) -> Result<CallToolResult, McpError> {
         debug!("Tool: add_entity, type={}, id={}", params.r#type, params.id);
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
         let result = {
-            let state = self.state.lock().await;
-            tools::add_entity::execute(&self.workspace_path, &state.build, &state.graph, &params)
+            let state = self.current_state().await;
+            tools::add_entity::execute(
+                &self.workspace_path,
+                &state.build,
+                &state.graph,
+                &self.config.add,
+                &params,
+            )
         };
 
         match result {
             Ok(add_result) => {
                 // Rebuild workspace so in-memory state reflects the new entity
-                match self.rebuild().await {
+                let changed_paths = self.source_paths([add_result.path.as_str()]);
+                let rebuild_result = self.rebuild_files(&changed_paths).await;
+                self.record_change(
+                    "add_entity",
+                    format!("Added '{}'", add_result.entity_id),
+                    [(add_result.path.clone(), add_result.original_content.clone())],
+                )
+                .await;
+                match rebuild_result {
                     Ok(_) => Ok(tools::add_entity::success_result(add_result)),
                     Err(e) => Ok(tools::add_entity::warning_result(add_result, &e)),
                 }
@@ -207,9 +490,334 @@ impl FirmMcpServer {
         }
     }
 
-    #[tool(description = "Find the source file path for an entity or schema. \
-        Returns the relative path to the .firm file containing the definition. \
-        Use this to locate where an entity or schema is defined before reading or editing the source file.")]
+    #[tool(description = "Add several new entities in one call. \
+        Provide a list of entities, each with a type, ID, field values and optionally from_id \
+        and list_item_types, in the same formats as add_entity. \
+        All entities go to 'to_file' when it's given, or else to the add target for their type. \
+        Every entity is validated before anything is written, and the error names the index of the failing entity. \
+        The workspace is rebuilt once, and if validation fails, none of the entities are added. \
+        Returns the IDs of the added entities and the files written.")]
+    async fn add_entities(
+        &self,
+        Parameters(params): Parameters<AddEntitiesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: add_entities, count={}", params.entities.len());
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+        let result = {
+            let state = self.current_state().await;
+            tools::add_entities::execute(
+                &self.workspace_path,
+                &state.build,
+                &state.graph,
+                &self.config.add,
+                &params,
+            )
+        };
+        let add_result = match result {
+            Ok(result) => result,
+            Err(e) => return Ok(tools::build::error_result(&e)),
+        };
+
+        // Rebuild once for all the entities (semantic validation)
+        let changed_paths =
+            self.source_paths(add_result.files.iter().map(|file| file.path.as_str()));
+        match self.rebuild_files(&changed_paths).await {
+            Ok(_) => {
+                self.record_change(
+                    "add_entities",
+                    format!("Added {} entities", add_result.entity_ids.len()),
+                    add_result
+                        .files
+                        .iter()
+                        .map(|file| (file.path.clone(), file.original_content.clone())),
+                )
+                .await;
+                Ok(tools::add_entities::success_result(&add_result))
+            }
+            Err(e) => {
+                let rollback_success =
+                    tools::add_entities::rollback(&self.workspace_path, &add_result.files);
+                Ok(tools::add_entities::validation_error_result(
+                    &e.to_string(),
+                    rollback_success,
+                ))
+            }
+        }
+    }
+
+    #[tool(description = "Add a new schema, defining an entity type. \
+        Provide the schema name and its fields in order, each with a name, type \
+        (string, integer, float, boolean, currency, reference, datetime, path, enum or list), \
+        whether it's required, allowed_values for enums, an optional item_type for lists \
+        and an optional description. \
+        The tool validates the definition, generates DSL and writes it to 'schemas/<name>.firm' \
+        unless 'to_file' is given. If the workspace doesn't build afterwards, the change is rolled back. \
+        Returns the generated DSL for review.")]
+    async fn add_schema(
+        &self,
+        Parameters(params): Parameters<AddSchemaParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: add_schema, name={}, fields={}",
+            params.name,
+            params.fields.len()
+        );
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+        let result = {
+            let state = self.current_state().await;
+            tools::add_schema::execute(&self.workspace_path, &state.build, &params)
+        };
+        let add_result = match result {
+            Ok(result) => result,
+            Err(e) => return Ok(tools::build::error_result(&e)),
+        };
+
+        // Try to rebuild the workspace (semantic validation)
+        let changed_paths = self.source_paths([add_result.path.as_str()]);
+        match self.rebuild_files(&changed_paths).await {
+            Ok(_) => {
+                self.record_change(
+                    "add_schema",
+                    format!("Added schema '{}'", params.name),
+                    [(add_result.path.clone(), add_result.original_content.clone())],
+                )
+                .await;
+                Ok(tools::add_schema::success_result(&add_result))
+            }
+            Err(e) => {
+                let rollback_success =
+                    tools::add_schema::rollback(&self.workspace_path, &add_result);
+                Ok(tools::add_schema::validation_error_result(
+                    &e.to_string(),
+                    rollback_success,
+                ))
+            }
+        }
+    }
+
+    #[tool(description = "Update fields of an existing entity without editing its source. \
+        Provide the entity type, ID, a 'set' map of field values (same formats as add_entity) \
+        and an 'unset' list of field names to remove. \
+        Only the affected field lines change, so comments and field order are kept. \
+        If validation fails, changes are rolled back unless 'force' is true. \
+        Returns the entity's updated DSL.")]
+    async fn update_entity(
+        &self,
+        Parameters(params): Parameters<UpdateEntityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: update_entity, type={}, id={}, set={}, unset={}, force={}",
+            params.r#type,
+            params.id,
+            params.set.len(),
+            params.unset.len(),
+            params.force
+        );
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+        let result = {
+            let state = self.current_state().await;
+            tools::update_entity::execute(
+                &self.workspace_path,
+                &state.workspace,
+                &state.build,
+                &params,
+            )
+        };
+        let update_result = match result {
+            Ok(result) => result,
+            Err(e) => return Ok(tools::build::error_result(&e)),
+        };
+
+        // Try to rebuild the workspace (semantic validation)
+        let changed_paths = self.source_paths(
+            update_result
+                .changed_files
+                .iter()
+                .map(|file| file.path.as_str()),
+        );
+        let rebuild_result = self.rebuild_files(&changed_paths).await;
+        if rebuild_result.is_ok() || params.force {
+            self.record_change(
+                "update_entity",
+                format!("Updated '{}.{}'", params.r#type, params.id),
+                changed_file_contents(&update_result.changed_files),
+            )
+            .await;
+        }
+        match rebuild_result {
+            Ok(_) => Ok(tools::update_entity::success_result(&update_result)),
+            Err(e) => {
+                if params.force {
+                    Ok(tools::update_entity::force_success_result(
+                        &update_result,
+                        &e.to_string(),
+                    ))
+                } else {
+                    let rollback_success = tools::update_entity::rollback(
+                        &self.workspace_path,
+                        &update_result.changed_files,
+                    );
+                    Ok(tools::update_entity::validation_error_result(
+                        &e.to_string(),
+                        rollback_success,
+                    ))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Delete a single entity from the workspace. \
+        Provide the entity type and ID. Every block of the entity is removed from its source file. \
+        If other entities reference it, nothing is deleted and the referencing entities and fields are listed, \
+        so they can be cleaned up first; with 'force' true, the entity is deleted anyway \
+        and the broken references are reported. \
+        If validation fails, the entity is restored unless 'force' is true.")]
+    async fn delete_entity(
+        &self,
+        Parameters(params): Parameters<DeleteEntityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: delete_entity, type={}, id={}, force={}",
+            params.r#type, params.id, params.force
+        );
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+        let result = {
+            let state = self.current_state().await;
+            tools::delete_entity::execute(&self.workspace_path, &state.workspace, &params)
+        };
+        let delete_result = match result {
+            Ok(result) => result,
+            Err(e) => return Ok(tools::build::error_result(&e)),
+        };
+
+        // Try to rebuild the workspace (semantic validation)
+        let changed_paths = self.source_paths(
+            delete_result
+                .changed_files
+                .iter()
+                .map(|file| file.path.as_str()),
+        );
+        let rebuild_result = self.rebuild_files(&changed_paths).await;
+        if rebuild_result.is_ok() || params.force {
+            self.record_change(
+                "delete_entity",
+                format!("Deleted '{}'", delete_result.entity_id),
+                changed_file_contents(&delete_result.changed_files),
+            )
+            .await;
+        }
+        match rebuild_result {
+            Ok(_) => Ok(tools::delete_entity::success_result(&delete_result)),
+            Err(e) => {
+                if params.force {
+                    Ok(tools::delete_entity::force_success_result(
+                        &delete_result,
+                        &e.to_string(),
+                    ))
+                } else {
+                    let rollback_success = tools::delete_entity::rollback(
+                        &self.workspace_path,
+                        &delete_result.changed_files,
+                    );
+                    Ok(tools::delete_entity::validation_error_result(
+                        &e.to_string(),
+                        rollback_success,
+                    ))
+                }
+            }
+        }
+    }
+
+    #[tool(description = "Move an entity to another source file. \
+        Provide the entity type, ID and the destination path relative to the workspace root \
+        (e.g., 'people/team.firm'), which is created if it doesn't exist. \
+        Every block of the entity is taken out of its source file together with the comments right above it, \
+        and appended to the destination. \
+        If validation fails, both files are restored.")]
+    async fn move_entity(
+        &self,
+        Parameters(params): Parameters<MoveEntityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: move_entity, type={}, id={}, to_file={}",
+            params.r#type, params.id, params.to_file
+        );
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+        let result = {
+            let state = self.current_state().await;
+            tools::move_entity::execute(&self.workspace_path, &state.workspace, &params)
+        };
+        let move_result = match result {
+            Ok(result) => result,
+            Err(e) => return Ok(tools::build::error_result(&e)),
+        };
+
+        // Try to rebuild the workspace (semantic validation)
+        let changed_paths = self.source_paths(
+            move_result
+                .from_paths
+                .iter()
+                .chain([&move_result.to_path])
+                .map(String::as_str),
+        );
+        match self.rebuild_files(&changed_paths).await {
+            Ok(_) => {
+                let mut before = changed_file_contents(&move_result.changed_files);
+                if move_result.created_new_file {
+                    before.push((move_result.to_path.clone(), None));
+                }
+                self.record_change(
+                    "move_entity",
+                    format!(
+                        "Moved '{}' to {}",
+                        move_result.entity_id, move_result.to_path
+                    ),
+                    before,
+                )
+                .await;
+                Ok(tools::move_entity::success_result(&move_result))
+            }
+            Err(e) => {
+                let rollback_success =
+                    tools::move_entity::rollback(&self.workspace_path, &move_result);
+                Ok(tools::move_entity::validation_error_result(
+                    &e.to_string(),
+                    &move_result,
+                    rollback_success,
+                ))
+            }
+        }
+    }
+
+    #[tool(
+        description = "Find the source file path for an entity or schema. \
+        Returns the relative path to the .firm file containing the definition with the lines its block spans, as 'path:start-end'. \
+        If several blocks contribute to an entity, each is listed with the defining block first. \
+        Use format 'json' for structured output with the start column. \
+        If nothing matches, the error lists the closest IDs of the type. \
+        Use this to locate where an entity or schema is defined before reading or editing the source file.",
+        annotations(read_only_hint = true)
+    )]
     async fn find_source(
         &self,
         Parameters(params): Parameters<FindSourceParams>,
@@ -218,7 +826,7 @@ impl FirmMcpServer {
             "Tool: find_source, type={}, id={}",
             params.r#type, params.id
         );
-        let state = self.state.lock().await;
+        let state = self.current_state().await;
         Ok(tools::find_source::execute(
             &state.workspace,
             &self.workspace_path,
@@ -226,9 +834,13 @@ impl FirmMcpServer {
         ))
     }
 
-    #[tool(description = "Read the raw DSL content of a .firm source file. \
+    #[tool(
+        description = "Read the raw DSL content of a .firm source file. \
         Provide the relative path to the file (e.g., 'schemas/person.firm', 'core/main.firm'). \
-        Use 'find_source' first to locate the file path for a specific entity or schema.")]
+        Use 'find_source' first to locate the file path for a specific entity or schema. \
+        Set 'include_hash: true' to also get the content's hash, to pass as 'base_hash' when editing the file.",
+        annotations(read_only_hint = true)
+    )]
     async fn read_source(
         &self,
         Parameters(params): Parameters<ReadSourceParams>,
@@ -237,22 +849,66 @@ impl FirmMcpServer {
         Ok(tools::read_source::execute(&self.workspace_path, &params))
     }
 
+    #[tool(
+        description = "Validate a .firm file without writing anything. \
+        Pass a 'path' to check the file on disk in the context of the workspace, \
+        or 'content' with the path it would be written to, which doesn't have to exist yet, \
+        to check a draft before writing it. \
+        Returns JSON with 'valid', error and warning counts, and every diagnostic in the workspace \
+        with its file, line, column, severity, code and message. \
+        Use it to iterate on DSL before calling 'write_source'.",
+        annotations(read_only_hint = true)
+    )]
+    async fn validate(
+        &self,
+        Parameters(params): Parameters<ValidateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: validate, path={}, content_len={:?}",
+            params.path,
+            params.content.as_ref().map(String::len)
+        );
+        Ok(tools::validate::execute(
+            &self.workspace_path,
+            self.config.timezone.offset(),
+            &params,
+        ))
+    }
+
     #[tool(description = "Write DSL content to a .firm source file. \
         The content is validated for correct syntax and semantics (references, schema conformance). \
         If validation fails, changes are rolled back unless 'force' is true. \
         Use 'find_source' to locate existing files, or provide a new path to create a new file. \
-        Use 'force: true' to fix a broken workspace where normal writes would be rolled back.")]
+        Use 'force: true' to fix a broken workspace where normal writes would be rolled back. \
+        Use 'dry_run: true' to preview the change as a unified diff with the validation result, \
+        without writing anything. \
+        Pass the 'base_hash' from 'read_source' to refuse the write if the file changed since it was read.")]
     async fn write_source(
         &self,
         Parameters(params): Parameters<WriteSourceParams>,
     ) -> Result<CallToolResult, McpError> {
         debug!(
-            "Tool: write_source, path={}, content_len={}, force={}",
+            "Tool: write_source, path={}, content_len={}, force={}, dry_run={}",
             params.path,
             params.content.len(),
-            params.force
+            params.force,
+            params.dry_run
         );
 
+        if params.dry_run {
+            return Ok(tools::write_source::preview(
+                &self.workspace_path,
+                self.config.timezone.offset(),
+                &params.path,
+                &params.content,
+            ));
+        }
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+
         // Validate syntax and write the file
         let write_result =
             match tools::write_source::validate_and_write(&self.workspace_path, &params) {
@@ -261,7 +917,17 @@ impl FirmMcpServer {
             };
 
         // Try to rebuild the workspace (semantic validation)
-        match self.rebuild().await {
+        let changed_paths = self.source_paths([params.path.as_str()]);
+        let rebuild_result = self.rebuild_files(&changed_paths).await;
+        if rebuild_result.is_ok() || params.force {
+            self.record_change(
+                "write_source",
+                format!("Wrote {}", params.path),
+                [(params.path.clone(), write_result.original_content.clone())],
+            )
+            .await;
+        }
+        match rebuild_result {
             Ok(_) => {
                 // Success - workspace is valid
                 Ok(tools::write_source::success_result(
@@ -308,12 +974,30 @@ impl FirmMcpServer {
             params.path, params.force
         );
 
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+
         let delete_result = match tools::delete_source::execute(&self.workspace_path, &params) {
             Ok(result) => result,
             Err(e) => return Ok(tools::build::error_result(&e)),
         };
 
-        match self.rebuild().await {
+        let changed_paths = self.source_paths([params.path.as_str()]);
+        let rebuild_result = self.rebuild_files(&changed_paths).await;
+        if rebuild_result.is_ok() || params.force {
+            self.record_change(
+                "delete_source",
+                format!("Deleted {}", params.path),
+                [(
+                    params.path.clone(),
+                    Some(delete_result.original_content.clone()),
+                )],
+            )
+            .await;
+        }
+        match rebuild_result {
             Ok(_) => Ok(tools::delete_source::success_result(&params.path)),
             Err(e) => {
                 if params.force {
@@ -340,31 +1024,51 @@ impl FirmMcpServer {
         Validates that old_string exists exactly once (or use replace_all for multiple). \
         The result is validated for correct syntax and semantics. \
         If validation fails, changes are rolled back unless 'force' is true. \
+        Use 'dry_run: true' to preview the change as a unified diff with the validation result, \
+        without writing anything. \
+        Pass the 'base_hash' from 'read_source' to refuse the replacement if the file changed since it was read. \
         Use 'find_source' to locate the file, then 'read_source' to see current content.")]
     async fn replace_source(
         &self,
         Parameters(params): Parameters<ReplaceSourceParams>,
     ) -> Result<CallToolResult, McpError> {
         debug!(
-            "Tool: replace_source, path={}, old_len={}, new_len={}, replace_all={}, force={}",
+            "Tool: replace_source, path={}, old_len={}, new_len={}, replace_all={}, force={}, dry_run={}",
             params.path,
             params.old_string.len(),
             params.new_string.len(),
             params.replace_all,
-            params.force
+            params.force,
+            params.dry_run
         );
 
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+
         // Execute the replacement (validates and computes new content)
         let replace_result = match tools::replace_source::execute(&self.workspace_path, &params) {
             Ok(result) => result,
             Err(error_result) => return Ok(error_result),
         };
 
+        if params.dry_run {
+            return Ok(tools::write_source::preview(
+                &self.workspace_path,
+                self.config.timezone.offset(),
+                &params.path,
+                &replace_result.new_content,
+            ));
+        }
+
         // Create WriteSourceParams to reuse write_source validation
         let write_params = WriteSourceParams {
             path: params.path.clone(),
             content: replace_result.new_content.clone(),
             force: params.force,
+            dry_run: false,
+            base_hash: None,
         };
 
         // Validate syntax and write the file
@@ -375,7 +1079,20 @@ impl FirmMcpServer {
             };
 
         // Try to rebuild the workspace (semantic validation)
-        match self.rebuild().await {
+        let changed_paths = self.source_paths([params.path.as_str()]);
+        let rebuild_result = self.rebuild_files(&changed_paths).await;
+        if rebuild_result.is_ok() || params.force {
+            self.record_change(
+                "replace_source",
+                format!(
+                    "Replaced {} occurrence(s) in {}",
+                    replace_result.occurrences_replaced, params.path
+                ),
+                [(params.path.clone(), write_result.original_content.clone())],
+            )
+            .await;
+        }
+        match rebuild_result {
             Ok(_) => {
                 // Success - workspace is valid
                 Ok(tools::replace_source::success_result(
@@ -407,10 +1124,80 @@ impl FirmMcpServer {
         }
     }
 
-    #[tool(description = "Rebuild and validate the workspace. \
+    #[tool(description = "Undo the most recent change made by a mutating tool \
+        (add, update, delete, move, write or replace). \
+        The files it changed are restored, created files are removed and deleted files are recreated. \
+        If the files were edited since, nothing is undone unless 'force' is true. \
+        If validation fails, the change is kept unless 'force' is true. \
+        Call it again to undo earlier changes. The journal is kept in memory, so restarting the server clears it.")]
+    async fn undo_last_change(
+        &self,
+        Parameters(params): Parameters<UndoLastChangeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: undo_last_change, force={}", params.force);
+
+        let _lock = match self.lock_workspace().await {
+            Ok(lock) => lock,
+            Err(error_result) => return Ok(error_result),
+        };
+        let Some(entry) = self.journal.lock().await.last().cloned() else {
+            return Ok(tools::undo_last_change::nothing_to_undo_result());
+        };
+        let restored_files =
+            match tools::undo_last_change::execute(&self.workspace_path, &entry, &params) {
+                Ok(result) => result,
+                Err(e) => return Ok(tools::build::error_result(&e)),
+            };
+
+        // Try to rebuild the workspace (semantic validation)
+        let changed_paths = self.source_paths(entry.files.iter().map(|file| file.path.as_str()));
+        let rebuild_result = self.rebuild_files(&changed_paths).await;
+        if rebuild_result.is_ok() || params.force {
+            self.journal.lock().await.remove(entry.id);
+        }
+        match rebuild_result {
+            Ok(_) => Ok(tools::undo_last_change::success_result(&entry)),
+            Err(e) => {
+                if params.force {
+                    Ok(tools::undo_last_change::force_success_result(
+                        &entry,
+                        &e.to_string(),
+                    ))
+                } else {
+                    let rollback_success =
+                        tools::undo_last_change::rollback(&self.workspace_path, &restored_files);
+                    Ok(tools::undo_last_change::validation_error_result(
+                        &e.to_string(),
+                        rollback_success,
+                    ))
+                }
+            }
+        }
+    }
+
+    #[tool(
+        description = "List the changes made by mutating tools, most recent first, \
+        with the files each one touched. \
+        The last 20 changes are kept, and undo_last_change undoes them from the most recent. \
+        The journal is kept in memory, so restarting the server clears it.",
+        annotations(read_only_hint = true)
+    )]
+    async fn list_changes(
+        &self,
+        #[allow(unused_variables)] Parameters(params): Parameters<ListChangesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: list_changes");
+        let journal = self.journal.lock().await;
+        Ok(tools::list_changes::execute(&journal))
+    }
+
+    #[tool(
+        description = "Rebuild and validate the workspace. \
         Returns the current status: number of entities and schemas if valid, \
         or validation errors if the workspace is broken. \
-        Use this to check workspace health or refresh state after external changes.")]
+        Use this to check workspace health or refresh state after external changes.",
+        annotations(read_only_hint = true)
+    )]
     async fn build(
         &self,
         #[allow(unused_variables)] Parameters(params): Parameters<BuildParams>,
@@ -419,7 +1206,7 @@ impl FirmMcpServer {
 
         match self.rebuild().await {
             Ok(_) => {
-                let state = self.state.lock().await;
+                let state = self.current_state().await;
                 Ok(tools::build::success_result(
                     state.build.entities.len(),
                     state.build.schemas.len(),
@@ -429,12 +1216,55 @@ impl FirmMcpServer {
         }
     }
 
+    #[tool(
+        description = "Check the workspace for broken references. \
+        Lists every reference field value that points at an entity or field that doesn't exist, \
+        with the referring entity, field, and source location (path:line). \
+        Set format='json' for structured output.",
+        annotations(read_only_hint = true)
+    )]
+    async fn check_references(
+        &self,
+        Parameters(params): Parameters<CheckReferencesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: check_references");
+        let state = self.current_state().await;
+        Ok(tools::check_references::execute(
+            &state.workspace,
+            &state.build,
+            &self.workspace_path,
+            &params,
+        ))
+    }
+
+    #[tool(
+        description = "List TODO, FIXME and NOTE comments in the workspace's .firm files. \
+        Returns each comment's text, source location (path:line), and the entity or schema it's written in. \
+        Use 'markers' to look for other markers. \
+        Use this to find open work, for example to turn todos into task entities. \
+        Set format='json' for structured output.",
+        annotations(read_only_hint = true)
+    )]
+    async fn list_todos(
+        &self,
+        Parameters(params): Parameters<ListTodosParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Tool: list_todos");
+        let state = self.current_state().await;
+        Ok(tools::list_todos::execute(
+            &state.workspace,
+            &self.workspace_path,
+            &params,
+        ))
+    }
+
     #[tool(
         description = "Get reference documentation for the Firm DSL syntax and query language. \
         Use 'topic' parameter: 'dsl' for DSL syntax (entities, schemas, field types), \
         'query' for query language (from, where, related, order, limit, aggregations), \
         or 'all' for both (default). \
-        Call this before writing or modifying .firm files to understand the correct syntax."
+        Call this before writing or modifying .firm files to understand the correct syntax.",
+        annotations(read_only_hint = true)
     )]
     async fn dsl_reference(
         &self,
@@ -445,26 +1275,55 @@ impl FirmMcpServer {
     }
 
     #[tool(
-        description = "Show the file tree of all .firm source files in the workspace. \
-        Use this to understand the file layout before reading, writing, or organizing source files."
+        description = "Outline the workspace's .firm source files by directory, with the schemas \
+        and entities each file defines and their line numbers, plus file, schema and entity counts. \
+        Use 'path_prefix' to outline one directory or file, and 'depth' to limit how many \
+        directory levels are shown (deeper files are summarized as counts). \
+        Use this to navigate the workspace before reading, writing, or organizing source files.",
+        annotations(read_only_hint = true)
     )]
     async fn source_tree(
         &self,
-        #[allow(unused_variables)] Parameters(params): Parameters<SourceTreeParams>,
+        Parameters(params): Parameters<SourceTreeParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Tool: source_tree");
-        let state = self.state.lock().await;
+        debug!(
+            "Tool: source_tree, path_prefix={:?}, depth={:?}",
+            params.path_prefix, params.depth
+        );
+        let state = self.current_state().await;
         Ok(tools::source_tree::execute(
             &state.workspace,
             &self.workspace_path,
+            &params,
         ))
     }
 
+    #[tool(
+        description = "Find entities whose field values mention a text, ignoring case. \
+        Searches string, enum and path fields, and the string items of lists with \
+        'include_lists'. Returns the matching entities with a snippet of each matching field. \
+        Use 'type' to only search one entity type, and 'limit' to return more than 20 entities. \
+        Use 'search_source' instead to search the raw DSL, including comments.",
+        annotations(read_only_hint = true)
+    )]
+    async fn search(
+        &self,
+        Parameters(params): Parameters<SearchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Tool: search, query={}, type={:?}, include_lists={}, limit={:?}",
+            params.query, params.r#type, params.include_lists, params.limit
+        );
+        let state = self.current_state().await;
+        Ok(tools::search::execute(&state.build.entities, &params))
+    }
+
     #[tool(
         description = "Search for a text string across all .firm source files. \
         Returns matching lines with file paths and line numbers. \
         Case-insensitive by default. \
-        Use this to find where entities, fields, or values are defined or referenced."
+        Use this to find where entities, fields, or values are defined or referenced.",
+        annotations(read_only_hint = true)
     )]
     async fn search_source(
         &self,
@@ -474,7 +1333,7 @@ impl FirmMcpServer {
             "Tool: search_source, query={}, case_sensitive={}",
             params.query, params.case_sensitive
         );
-        let state = self.state.lock().await;
+        let state = self.current_state().await;
         Ok(tools::search_source::execute(
             &state.workspace,
             &self.workspace_path,
@@ -500,22 +1359,176 @@ impl FirmMcpServer {
 
     /// Rebuild the workspace from disk.
     ///
-    /// Called after write operations to ensure the in-memory state is fresh.
+    /// Reloads every file, like after bulk changes the watcher may have missed.
     pub async fn rebuild(&self) -> Result<(), WorkspaceError> {
+        let _rebuilding = self.rebuild_lock.lock().await;
+
         debug!("Rebuilding workspace");
+        let (workspace, build, graph) = load_workspace(&self.workspace_path, &self.config)?;
+
         let mut state = self.state.lock().await;
+        state.changed_paths.clear();
+        self.swap_state(&mut state, workspace, build, graph);
+        Ok(())
+    }
+
+    /// Rebuild the workspace after the given files changed on disk.
+    ///
+    /// Only these files, and earlier changes that haven't built yet, are read
+    /// again. Parsing and building happen on a copy of the workspace, which
+    /// shares the parsed files with the state, so the state is only locked to
+    /// take the copy and to swap in the new build. The graph is updated while
+    /// swapping, moving over the entities that didn't change. If the rebuild
+    /// fails, the previous build is kept and the files are read again on the
+    /// next rebuild. Called after write operations to ensure the in-memory
+    /// state is fresh.
+    pub async fn rebuild_files(&self, paths: &[PathBuf]) -> Result<(), WorkspaceError> {
+        let _rebuilding = self.rebuild_lock.lock().await;
 
-        let mut workspace = Workspace::new();
-        workspace.load_directory(&self.workspace_path)?;
+        let (mut workspace, paths) = {
+            let mut state = self.state.lock().await;
+            state.poll_changes();
+            state.changed_paths.extend(paths.iter().cloned());
+            let paths: Vec<PathBuf> = state.changed_paths.iter().cloned().collect();
+            (state.workspace.clone(), paths)
+        };
+
+        debug!("Rebuilding workspace for {} changed files", paths.len());
+        let updates =
+            read_file_updates_with(&self.workspace_path, &paths, self.source_reader.as_ref())?;
+        workspace.apply_file_updates(updates);
         let build = workspace.build()?;
 
-        // Rebuild the entity graph
-        let mut graph = EntityGraph::new();
-        graph.add_entities(build.entities.clone()).map_err(|e| {
-            WorkspaceError::ValidationError(self.workspace_path.clone(), format!("{:?}", e))
-        })?;
-        graph.build();
+        let mut state = self.state.lock().await;
+        let previous = std::mem::take(&mut state.graph);
+        let graph = match build_graph(&build, previous, &self.workspace_path) {
+            Ok(graph) => graph,
+            Err(e) => {
+                // The previous graph was taken apart, so put it together again
+                state.graph = build_graph(&state.build, EntityGraph::new(), &self.workspace_path)?;
+                return Err(e);
+            }
+        };
+        for path in &paths {
+            state.changed_paths.remove(path);
+        }
+        self.swap_state(&mut state, workspace, build, graph);
+        Ok(())
+    }
 
+    /// Lock the server state, first rebuilding it if files changed on disk.
+    ///
+    /// If the rebuild fails (e.g. a file is mid-edit), the previous build is
+    /// kept and the rebuild is retried on the next call.
+    async fn current_state(&self) -> MutexGuard<'_, ServerState> {
+        let files_changed = {
+            let mut state = self.state.lock().await;
+            state.poll_changes();
+            !state.changed_paths.is_empty()
+        };
+
+        if files_changed && let Err(e) = self.rebuild_files(&[]).await {
+            warn!("Failed to rebuild workspace after external changes: {}", e);
+        }
+
+        self.state.lock().await
+    }
+
+    /// Notifies the client of external edits until it disconnects.
+    ///
+    /// Changed files are recorded for the next rebuild, which still happens
+    /// lazily when a tool or resource needs the state. Subscribed resources
+    /// get an update notification, and created or removed files a list
+    /// change. The watcher debounces bursts of events for the same file.
+    /// Changes a tool call picked up first are queued on the state, so
+    /// they're notified too.
+    fn spawn_change_notifier(&self, peer: Peer<RoleServer>) {
+        let server = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHANGE_NOTIFY_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let changes = {
+                    let mut state = server.state.lock().await;
+                    state.poll_changes();
+                    state.take_unnotified()
+                };
+                if changes.is_empty() {
+                    continue;
+                }
+                let notifications = server
+                    .subscriptions
+                    .lock()
+                    .await
+                    .notifications(&server.workspace_path, &changes);
+
+                for uri in notifications.updated {
+                    debug!("Notifying client that {} was updated", uri);
+                    let param = ResourceUpdatedNotificationParam { uri };
+                    if peer.notify_resource_updated(param).await.is_err() {
+                        return;
+                    }
+                }
+                if notifications.list_changed && peer.notify_resource_list_changed().await.is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Takes the workspace lock for a mutating tool, waiting for other writers to finish.
+    ///
+    /// The lock is held until it's dropped, so a tool keeps it through
+    /// writing, rebuilding and any rollback.
+    async fn lock_workspace(&self) -> Result<WorkspaceLock, CallToolResult> {
+        let workspace_path = self.workspace_path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            WorkspaceLock::acquire(&workspace_path, DEFAULT_LOCK_TIMEOUT)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(lock)) => Ok(lock),
+            Ok(Err(e)) => Err(CallToolResult::error(vec![Content::text(e.to_string())])),
+            Err(e) => Err(CallToolResult::error(vec![Content::text(format!(
+                "Failed to lock the workspace: {}",
+                e
+            ))])),
+        }
+    }
+
+    /// Records a change made by a tool in the journal, so it can be undone.
+    ///
+    /// Takes the content of each file before the change, and is called once
+    /// the change is kept.
+    async fn record_change(
+        &self,
+        tool: &str,
+        summary: String,
+        before: impl IntoIterator<Item = (String, Option<String>)>,
+    ) {
+        let mut journal = self.journal.lock().await;
+        journal.record(&self.workspace_path, tool, summary, before);
+    }
+
+    /// Resolves paths relative to the workspace root.
+    fn source_paths<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> Vec<PathBuf> {
+        paths
+            .into_iter()
+            .map(|path| self.workspace_path.join(path))
+            .collect()
+    }
+
+    /// Replaces the workspace, build and graph of the state with new ones.
+    fn swap_state(
+        &self,
+        state: &mut ServerState,
+        workspace: Workspace,
+        build: WorkspaceBuild,
+        graph: EntityGraph,
+    ) {
         state.workspace = workspace;
         state.build = build;
         state.graph = graph;
@@ -525,11 +1538,125 @@ impl FirmMcpServer {
             state.build.entities.len(),
             state.build.schemas.len()
         );
+    }
+}
 
-        Ok(())
+impl ServerState {
+    /// Records the files the watcher saw change since the last poll.
+    ///
+    /// The changes are queued for the change notifier, whoever polled them.
+    fn poll_changes(&mut self) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let changes = watcher.poll();
+        self.changed_paths
+            .extend(changes.iter().map(|change| change.path().to_path_buf()));
+        self.unnotified.extend(changes);
+    }
+
+    /// Takes the queued changes the client hasn't been notified of.
+    fn take_unnotified(&mut self) -> Vec<WorkspaceChange> {
+        std::mem::take(&mut self.unnotified)
     }
 }
 
+/// Gets the paths and original content of changed files, for the journal.
+fn changed_file_contents(changed_files: &[ChangedFile]) -> Vec<(String, Option<String>)> {
+    changed_files
+        .iter()
+        .map(|file| (file.path.clone(), Some(file.original_content.clone())))
+        .collect()
+}
+
+/// Instructions for clients on how to use the server.
+fn server_instructions(read_only: bool) -> String {
+    let usage = if read_only {
+        "This server is in read-only mode: tools that add, change or delete entities, \
+         schemas or files aren't available, and there's no way to enable them from here. \
+         Don't retry writes; tell the user the workspace is read-only instead. \
+         Use 'query', 'list', and 'get' to explore existing data. \
+         Use 'read_source' to read the DSL of a file."
+    } else {
+        "Use 'add_entity' to create new entities. \
+         Use 'query', 'list', and 'get' to explore existing data. \
+         Use 'read_source' and 'write_source' for low-level file operations."
+    };
+
+    format!(
+        "Firm MCP server. Use 'list schema' to explore available entity types. {} \
+         Schemas are also resources, at 'firm://schema/{{name}}' \
+         (add '?format=json' for JSON only), and so are the \
+         DSL and query language references, at 'firm://reference/dsl' and 'firm://reference/query'. \
+         Use 'run_query' to run the queries saved in the workspace, \
+         which are also resources at 'firm://query/{{name}}'.",
+        usage
+    )
+}
+
+/// Load and build a workspace, along with its entity graph for query support.
+fn load_workspace(
+    workspace_path: &PathBuf,
+    config: &WorkspaceConfig,
+) -> Result<(Workspace, WorkspaceBuild, EntityGraph), WorkspaceError> {
+    let mut workspace = Workspace::new().with_timezone(config.timezone.offset());
+    workspace.load_directory(workspace_path)?;
+    let build = workspace.build()?;
+    let graph = build_graph(&build, EntityGraph::new(), workspace_path)?;
+
+    Ok((workspace, build, graph))
+}
+
+/// Build the entity graph of a workspace build.
+///
+/// Entities that are the same in the previous graph are moved over from it,
+/// so only new and changed entities are copied from the build.
+fn build_graph(
+    build: &WorkspaceBuild,
+    previous: EntityGraph,
+    workspace_path: &Path,
+) -> Result<EntityGraph, WorkspaceError> {
+    let mut previous: Vec<Option<Entity>> =
+        previous.into_entities().into_iter().map(Some).collect();
+    let unchanged: Vec<Option<usize>> = {
+        let positions: HashMap<&EntityId, usize> = previous
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entity)| Some((&entity.as_ref()?.id, index)))
+            .collect();
+        build
+            .entities
+            .iter()
+            .map(|entity| {
+                positions
+                    .get(&entity.id)
+                    .copied()
+                    .filter(|&index| previous[index].as_ref() == Some(entity))
+            })
+            .collect()
+    };
+
+    let entities = build
+        .entities
+        .iter()
+        .zip(unchanged)
+        .map(|(entity, index)| {
+            index
+                .and_then(|index| previous[index].take())
+                .unwrap_or_else(|| entity.clone())
+        })
+        .collect();
+
+    let mut graph = EntityGraph::new();
+    graph.add_entities(entities).map_err(|e| {
+        WorkspaceError::ValidationError(workspace_path.to_path_buf(), format!("{:?}", e))
+    })?;
+    graph.build();
+
+    Ok(graph)
+}
+
 #[tool_handler]
 impl ServerHandler for FirmMcpServer {
     fn get_info(&self) -> ServerInfo {
@@ -537,26 +1664,27 @@ impl ServerHandler for FirmMcpServer {
             protocol_version: ProtocolVersion::LATEST,
             capabilities: ServerCapabilities::builder()
                 .enable_resources()
+                .enable_resources_subscribe()
+                .enable_resources_list_changed()
                 .enable_tools()
                 .build(),
             server_info: Implementation::from_build_env(),
-            instructions: Some(
-                "Firm MCP server. Use 'list schema' to explore available entity types. \
-                 Use 'add_entity' to create new entities. \
-                 Use 'query', 'list', and 'get' to explore existing data. \
-                 Use 'read_source' and 'write_source' for low-level file operations."
-                    .into(),
-            ),
+            instructions: Some(server_instructions(self.read_only)),
         }
     }
 
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        debug!("Client initialized, watching for external edits");
+        self.spawn_change_notifier(context.peer);
+    }
+
     async fn list_resources(
         &self,
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListResourcesResult, McpError> {
         debug!("Listing resources");
-        let state = self.state.lock().await;
+        let state = self.current_state().await;
 
         // Collect all source file resources
         let mut resource_list: Vec<Resource> = state
@@ -574,6 +1702,11 @@ impl ServerHandler for FirmMcpServer {
 
         debug!("Found {} source file resources", resource_list.len());
 
+        // Schemas follow the source files, including built-in defaults
+        resource_list.extend(resources::schema_resources(&state.build.schemas));
+        resource_list.extend(resources::reference_resources());
+        resource_list.extend(resources::query_resources(&state.build.queries));
+
         Ok(ListResourcesResult {
             resources: resource_list,
             next_cursor: None,
@@ -581,6 +1714,29 @@ impl ServerHandler for FirmMcpServer {
         })
     }
 
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        debug!("Subscribing to resource: {}", request.uri);
+        self.subscriptions
+            .lock()
+            .await
+            .subscribe(&request.uri)
+            .map_err(|e| McpError::resource_not_found(e, None))
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        debug!("Unsubscribing from resource: {}", request.uri);
+        self.subscriptions.lock().await.unsubscribe(&request.uri);
+        Ok(())
+    }
+
     async fn read_resource(
         &self,
         request: ReadResourceRequestParam,
@@ -589,6 +1745,64 @@ impl ServerHandler for FirmMcpServer {
         let uri = &request.uri;
         debug!("Reading resource: {}", uri);
 
+        if let Some(topic) = resources::parse_reference_uri(uri) {
+            let reference = resources::read_reference(&topic)
+                .map_err(|e| McpError::resource_not_found(e, None))?;
+            return Ok(ReadResourceResult {
+                contents: vec![resources::text_contents(reference, uri, "text/markdown")],
+            });
+        }
+
+        if let Some((schema_name, format)) = resources::parse_schema_uri(uri) {
+            let state = self.current_state().await;
+            let (dsl, json) = resources::read_schema(&state.build.schemas, &schema_name)
+                .map_err(|e| McpError::resource_not_found(e, None))?;
+            let json = serde_json::to_string_pretty(&json).map_err(|e| {
+                McpError::internal_error(format!("Failed to serialize schema: {}", e), None)
+            })?;
+
+            return Ok(ReadResourceResult {
+                contents: resources::schema_contents(uri, dsl, json, format),
+            });
+        }
+
+        if let Some(query_name) = resources::parse_query_uri(uri) {
+            let state = self.current_state().await;
+            if state.build.named_query(&query_name).is_none() {
+                let message =
+                    tools::run_query::not_found_message(&query_name, &state.build.queries);
+                return Err(McpError::resource_not_found(message, None));
+            }
+
+            let params = RunQueryParams {
+                name: Some(query_name),
+                ..Default::default()
+            };
+            let result = tools::run_query::execute(
+                &state.graph,
+                &state.build,
+                &self.config.timezone,
+                &self.config.display.value_format(),
+                &self.workspace_path,
+                &self.response_limits,
+                &params,
+            );
+            let text = result
+                .content
+                .iter()
+                .filter_map(|content| content.as_text())
+                .map(|content| content.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if result.is_error == Some(true) {
+                return Err(McpError::internal_error(text, None));
+            }
+
+            return Ok(ReadResourceResult {
+                contents: vec![resources::text_contents(text, uri, "text/plain")],
+            });
+        }
+
         // Parse the URI to get the relative path
         let relative_path = resources::parse_source_uri(uri).ok_or_else(|| {
             McpError::resource_not_found(format!("Invalid resource URI: {}", uri), None)
@@ -599,7 +1813,7 @@ impl ServerHandler for FirmMcpServer {
             .map_err(|e| McpError::resource_not_found(e, None))?;
 
         Ok(ReadResourceResult {
-            contents: vec![ResourceContents::text(contents, uri.clone())],
+            contents: vec![resources::text_contents(contents, uri, "text/plain")],
         })
     }
 }
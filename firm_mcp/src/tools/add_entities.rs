@@ -0,0 +1,206 @@
+//! Add entities tool implementation.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use firm_core::EntityId;
+use firm_core::graph::EntityGraph;
+use firm_lang::workspace::{AddConfig, WorkspaceBuild};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use super::add_entity::{self, AddEntityParams};
+
+/// Parameters for the add_entities tool.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AddEntitiesParams {
+    /// The entities to add, in the order they're written.
+    pub entities: Vec<NewEntity>,
+
+    /// Optional target file path relative to workspace root, for every entity.
+    /// If omitted, each entity goes to the workspace's add target for its type
+    /// ("generated/<type>.firm" unless configured).
+    /// Files will be created if they don't exist.
+    pub to_file: Option<String>,
+}
+
+/// An entity to add, like the parameters of add_entity without a target file.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct NewEntity {
+    /// Entity type (e.g., "person", "task").
+    pub r#type: String,
+
+    /// Entity ID (e.g., "john_doe", "fix_bug").
+    /// Will be converted to snake_case automatically.
+    pub id: String,
+
+    /// Optional ID of an existing entity of the same type to copy fields from (e.g., "task.fix_bug").
+    /// Its fields and tags are the starting point, and `fields` override them.
+    pub from_id: Option<String>,
+
+    /// Field values as a key-value map, in the same formats as add_entity.
+    #[serde(default)]
+    pub fields: HashMap<String, serde_json::Value>,
+
+    /// Optional type annotations for list fields, as in add_entity.
+    pub list_item_types: Option<HashMap<String, String>>,
+}
+
+/// A file entities were added to.
+#[derive(Debug)]
+pub struct AddedFile {
+    /// The path of the file (relative to workspace root).
+    pub path: String,
+    /// The content before adding, or None if the file was created.
+    pub original_content: Option<String>,
+}
+
+/// Result of adding entities (before validation).
+#[derive(Debug)]
+pub struct AddEntitiesResult {
+    /// The composite IDs of the added entities, in order.
+    pub entity_ids: Vec<EntityId>,
+    /// The files the entities were written to.
+    pub files: Vec<AddedFile>,
+}
+
+/// Execute the add_entities tool.
+///
+/// Validates every entity against its schema and checks that their IDs are
+/// new, to the workspace and to each other, before writing anything. Errors
+/// name the index of the failing entity. The caller should rebuild the
+/// workspace once and roll back if the rebuild fails.
+pub fn execute(
+    workspace_path: &Path,
+    build: &WorkspaceBuild,
+    graph: &EntityGraph,
+    add_config: &AddConfig,
+    params: &AddEntitiesParams,
+) -> Result<AddEntitiesResult, String> {
+    if params.entities.is_empty() {
+        return Err("No entities to add".to_string());
+    }
+
+    // Generate the DSL of every entity, grouped by target file
+    let mut entity_ids = Vec::new();
+    let mut seen = HashSet::new();
+    let mut files: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for (index, new_entity) in params.entities.iter().enumerate() {
+        let add_params = new_entity.add_params(&params.to_file);
+        let generated = add_entity::generate(workspace_path, build, graph, add_config, &add_params)
+            .map_err(|e| {
+                format!(
+                    "Entity {} ('{}.{}'): {}",
+                    index, new_entity.r#type, new_entity.id, e
+                )
+            })?;
+
+        if !seen.insert(generated.entity_id.clone()) {
+            return Err(format!(
+                "Entity {} ('{}'): the ID is used by an earlier entity in the list",
+                index, generated.entity_id
+            ));
+        }
+        entity_ids.push(generated.entity_id);
+        files
+            .entry(generated.target_path)
+            .or_default()
+            .push(generated.dsl);
+    }
+
+    // Write each file once, restoring the written ones if a write fails
+    let mut added_files = Vec::new();
+    for (target_path, dsls) in files {
+        let path = target_path.to_string_lossy().into_owned();
+        match append(workspace_path, &target_path, &dsls.join("\n")) {
+            Ok(original_content) => added_files.push(AddedFile {
+                path,
+                original_content,
+            }),
+            Err(e) => {
+                rollback(workspace_path, &added_files);
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(AddEntitiesResult {
+        entity_ids,
+        files: added_files,
+    })
+}
+
+impl NewEntity {
+    /// Gets the add_entity parameters for the entity, writing it to the given file.
+    fn add_params(&self, to_file: &Option<String>) -> AddEntityParams {
+        AddEntityParams {
+            r#type: self.r#type.clone(),
+            id: self.id.clone(),
+            from_id: self.from_id.clone(),
+            fields: self.fields.clone(),
+            to_file: to_file.clone(),
+            list_item_types: self.list_item_types.clone(),
+        }
+    }
+}
+
+/// Appends DSL to a file, creating it if needed, and returns its original content.
+fn append(workspace_path: &Path, target_path: &Path, dsl: &str) -> Result<Option<String>, String> {
+    let absolute_path = workspace_path.join(target_path);
+    if let Some(parent) = absolute_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let original_content = if absolute_path.exists() {
+        Some(
+            fs::read_to_string(&absolute_path)
+                .map_err(|e| format!("Failed to read file: {}", e))?,
+        )
+    } else {
+        None
+    };
+
+    let mut content = original_content.clone().unwrap_or_default();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(dsl);
+    fs::write(&absolute_path, content).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(original_content)
+}
+
+/// Restore the files entities were added to, removing the created ones.
+pub fn rollback(workspace_path: &Path, files: &[AddedFile]) -> bool {
+    files.iter().all(|file| {
+        super::write_source::rollback(workspace_path, &file.path, file.original_content.clone())
+    })
+}
+
+/// Create a success result for add_entities.
+pub fn success_result(result: &AddEntitiesResult) -> CallToolResult {
+    let ids: Vec<&str> = result.entity_ids.iter().map(|id| id.as_str()).collect();
+    let paths: Vec<&str> = result.files.iter().map(|file| file.path.as_str()).collect();
+
+    CallToolResult::success(vec![Content::text(format!(
+        "Added {} entities: {}\nFiles: {}\nWorkspace is valid.",
+        ids.len(),
+        ids.join(", "),
+        paths.join(", ")
+    ))])
+}
+
+/// Create an error result for add_entities when validation fails and rollback occurred.
+pub fn validation_error_result(error: &str, rollback_success: bool) -> CallToolResult {
+    let rollback_msg = if rollback_success {
+        "No entities were added."
+    } else {
+        "Warning: Failed to roll back the added entities."
+    };
+
+    CallToolResult::error(vec![Content::text(format!(
+        "Adding the entities would break the workspace: {}. {}",
+        error, rollback_msg
+    ))])
+}
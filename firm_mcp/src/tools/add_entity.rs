@@ -8,10 +8,11 @@ use std::path::{Path, PathBuf};
 
 use firm_core::graph::EntityGraph;
 use firm_core::{
-    Entity, EntityId, EntityType, FieldId, FieldType, FieldValue, ReferenceValue, compose_entity_id,
+    Entity, EntityId, EntitySchema, EntityType, FieldId, FieldJsonContext, FieldJsonError,
+    FieldType, FieldValue, compose_entity_id,
 };
 use firm_lang::generate::generate_dsl;
-use firm_lang::workspace::WorkspaceBuild;
+use firm_lang::workspace::{AddConfig, WorkspaceBuild, path_relocation};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
 
@@ -25,19 +26,25 @@ pub struct AddEntityParams {
     /// Will be converted to snake_case automatically.
     pub id: String,
 
+    /// Optional ID of an entity of the same type to copy fields from (e.g., "task.fix_bug").
+    /// Its fields and tags are the starting point, and `fields` override them.
+    pub from_id: Option<String>,
+
     /// Field values as a key-value map.
     /// Values must match the schema types (string, number, boolean, array, etc.).
     /// Paths should be relative to the workspace root.
+    #[serde(default)]
     pub fields: HashMap<String, serde_json::Value>,
 
     /// Optional target file path relative to workspace root.
-    /// If omitted, defaults to "generated/<type>.firm".
+    /// If omitted, defaults to the workspace's add target ("generated/<type>.firm" unless configured).
     /// The file will be created if it doesn't exist.
     pub to_file: Option<String>,
 
     /// Optional type annotations for list fields.
     /// Maps field names to their inner type (e.g., "secondary_contacts" -> "reference").
-    /// Required for any field with type List in the schema.
+    /// Required for list fields whose schema doesn't declare an item_type,
+    /// and must agree with it when it does.
     /// Valid types: string, integer, float, boolean, currency, reference, datetime, path, enum.
     pub list_item_types: Option<HashMap<String, String>>,
 }
@@ -45,24 +52,92 @@ pub struct AddEntityParams {
 /// Result of adding an entity.
 #[derive(Debug)]
 pub struct AddEntityResult {
+    /// The composite ID of the added entity.
+    pub entity_id: EntityId,
     /// The path where the entity was written (relative to workspace root).
     pub path: String,
     /// The generated DSL content.
     pub dsl: String,
     /// Whether the file was created (true) or appended to (false).
     pub created_new_file: bool,
+    /// Original file content (None if the file was new).
+    pub original_content: Option<String>,
+}
+
+/// An entity validated against its schema, with its DSL (before writing).
+#[derive(Debug)]
+pub(crate) struct GeneratedEntity {
+    pub entity_id: EntityId,
+    /// The file to write the entity to (relative to workspace root).
+    pub target_path: PathBuf,
+    pub dsl: String,
 }
 
 /// Execute the add_entity tool.
 ///
 /// Validates the entity against the schema, generates DSL, and writes it to a file.
+/// Without a target file, the entity goes to the workspace config's add target.
+/// With an entity to copy from, the given fields override its fields.
 /// Returns the file path and generated content.
 pub fn execute(
     workspace_path: &Path,
     build: &WorkspaceBuild,
     graph: &EntityGraph,
+    add_config: &AddConfig,
     params: &AddEntityParams,
 ) -> Result<AddEntityResult, String> {
+    let generated = generate(workspace_path, build, graph, add_config, params)?;
+    let target_abs_path = workspace_path.join(&generated.target_path);
+
+    // 7. Write to File
+    if let Some(parent) = target_abs_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let file_exists = target_abs_path.exists();
+
+    // Read existing content to ensure we append with a newline if needed
+    let mut prefix = String::new();
+    let mut original_content = None;
+    if file_exists {
+        let mut file =
+            File::open(&target_abs_path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if !content.ends_with('\n') && !content.is_empty() {
+            prefix.push('\n');
+        }
+        original_content = Some(content);
+    }
+
+    let mut file = File::options()
+        .create(true)
+        .append(true)
+        .open(&target_abs_path)
+        .map_err(|e| format!("Failed to open file for writing: {}", e))?;
+
+    let final_content = format!("{}{}", prefix, generated.dsl);
+    file.write_all(final_content.as_bytes())
+        .map_err(|e| format!("Failed to write to file: {}", e))?;
+
+    Ok(AddEntityResult {
+        entity_id: generated.entity_id,
+        path: generated.target_path.to_string_lossy().into_owned(),
+        dsl: generated.dsl,
+        created_new_file: !file_exists,
+        original_content,
+    })
+}
+
+/// Validates an entity against its schema and generates its DSL, without writing it.
+pub(crate) fn generate(
+    workspace_path: &Path,
+    build: &WorkspaceBuild,
+    graph: &EntityGraph,
+    add_config: &AddConfig,
+    params: &AddEntityParams,
+) -> Result<GeneratedEntity, String> {
     let entity_type_str = params.r#type.as_str();
     let entity_id_str = params.id.as_str();
 
@@ -85,9 +160,7 @@ pub fn execute(
     // 3. Determine Target Path
     let target_rel_path = match &params.to_file {
         Some(p) => PathBuf::from(p),
-        None => PathBuf::from("generated")
-            .join(entity_type_str)
-            .with_extension("firm"),
+        None => add_config.target_path(entity_type_str, entity_id.as_str()),
     };
 
     let target_abs_path = workspace_path.join(&target_rel_path);
@@ -95,28 +168,39 @@ pub fn execute(
     // 4. Construct Entity
     let mut entity = Entity::new(composite_id, EntityType::new(entity_type_str));
 
-    // Convert fields
-    for (name, json_value) in &params.fields {
-        let field_id = FieldId::new(name);
+    // Copy the fields of the entity to start from
+    if let Some(from_id) = &params.from_id {
+        let source = graph
+            .get_entity(&EntityId::new(from_id))
+            .ok_or_else(|| format!("Entity '{}' to copy from not found", from_id))?;
+        if source.entity_type != schema.entity_type {
+            return Err(format!(
+                "Can't copy '{}' into a '{}' entity, since it's a '{}'",
+                from_id, entity_type_str, source.entity_type
+            ));
+        }
 
-        // Find field definition in schema
-        let field_def = schema.fields.get(&field_id).ok_or_else(|| {
-            format!(
-                "Field '{}' not found in schema for '{}'",
-                name, entity_type_str
-            )
-        })?;
+        let relocate_path = path_relocation(workspace_path, &target_abs_path);
+        entity = entity.with_tags(source.tags.iter().cloned());
+        // Computed fields are set when the new entity is built
+        for (field_id, value) in &source.fields {
+            if !schema.is_computed(field_id) {
+                entity.set_field(field_id.clone(), value.map_paths(&relocate_path));
+            }
+        }
+    }
 
-        let value = json_to_field_value(
+    // Convert fields
+    for (name, json_value) in &params.fields {
+        let (field_id, value) = json_to_field_value(
+            schema,
+            name,
             json_value,
-            field_def.expected_type(),
+            &params.list_item_types,
             workspace_path,
             &target_abs_path,
-            &params.list_item_types,
-            name,
         )?;
-
-        entity = entity.with_field(field_id, value);
+        entity.set_field(field_id, value);
     }
 
     // 5. Validate Entity against Schema
@@ -125,254 +209,88 @@ pub fn execute(
         format!("Validation failed:\n- {}", msgs.join("\n- "))
     })?;
 
-    // 6. Generate DSL
-    let dsl = generate_dsl(&[entity]);
-
-    // 7. Write to File
-    if let Some(parent) = target_abs_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
+    // 6. Generate DSL, with enum values in their declared casing
+    schema.canonicalize(&mut entity);
+    let dsl = generate_dsl(std::slice::from_ref(&entity));
 
-    let file_exists = target_abs_path.exists();
-
-    // Read existing content to ensure we append with a newline if needed
-    let mut prefix = String::new();
-    if file_exists {
-        let mut file =
-            File::open(&target_abs_path).map_err(|e| format!("Failed to open file: {}", e))?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)
-            .map_err(|e| format!("Failed to read file: {}", e))?;
-        if !content.ends_with('\n') && !content.is_empty() {
-            prefix.push('\n');
-        }
-    }
-
-    let mut file = File::options()
-        .create(true)
-        .append(true)
-        .open(&target_abs_path)
-        .map_err(|e| format!("Failed to open file for writing: {}", e))?;
-
-    let final_content = format!("{}{}", prefix, dsl);
-    file.write_all(final_content.as_bytes())
-        .map_err(|e| format!("Failed to write to file: {}", e))?;
-
-    Ok(AddEntityResult {
-        path: target_rel_path.to_string_lossy().into_owned(),
+    Ok(GeneratedEntity {
+        entity_id: entity.id,
+        target_path: target_rel_path,
         dsl,
-        created_new_file: !file_exists,
     })
 }
 
-/// Convert JSON value to FieldValue based on expected type.
-fn json_to_field_value(
+/// Converts a JSON value to the value of a schema field.
+///
+/// Values use the canonical JSON encoding (see `firm_core::json`), with paths
+/// relative to the workspace root, written relative to the target file.
+/// List items take the schema's item type, falling back to the given list item types.
+pub(crate) fn json_to_field_value(
+    schema: &EntitySchema,
+    name: &str,
     value: &serde_json::Value,
-    expected_type: &FieldType,
+    list_item_types: &Option<HashMap<String, String>>,
     workspace_path: &Path,
     target_file_path: &Path,
-    list_item_types: &Option<HashMap<String, String>>,
-    field_name: &str,
-) -> Result<FieldValue, String> {
-    match expected_type {
-        FieldType::String => match value {
-            serde_json::Value::String(s) => Ok(FieldValue::String(s.clone())),
-            _ => Err(format!(
-                "Expected string for field type String, got {:?}",
-                value
-            )),
-        },
-        FieldType::Integer => match value {
-            serde_json::Value::Number(n) if n.is_i64() => {
-                Ok(FieldValue::Integer(n.as_i64().unwrap()))
-            }
-            serde_json::Value::Number(n) => {
-                Err(format!("Expected integer, got float/other: {}", n))
-            }
-            _ => Err(format!(
-                "Expected number for field type Integer, got {:?}",
-                value
-            )),
-        },
-        FieldType::Float => match value {
-            serde_json::Value::Number(n) => {
-                let f = n
-                    .as_f64()
-                    .ok_or_else(|| format!("Invalid float value: {}", n))?;
-                Ok(FieldValue::Float(f))
-            }
-            _ => Err(format!(
-                "Expected number for field type Float, got {:?}",
-                value
-            )),
-        },
-        FieldType::Boolean => match value {
-            serde_json::Value::Bool(b) => Ok(FieldValue::Boolean(*b)),
-            _ => Err(format!(
-                "Expected boolean for field type Boolean, got {:?}",
-                value
-            )),
-        },
-        FieldType::Reference => {
-            match value {
-                serde_json::Value::String(s) => {
-                    // Check for dots to determine reference type
-                    if s.contains('.') {
-                        // Could be type.id.field or type.id
-                        let parts: Vec<&str> = s.split('.').collect();
-                        if parts.len() >= 3 {
-                            // type.id.field
-                            let entity_id = compose_entity_id(parts[0], parts[1]);
-                            let field_id = FieldId::new(parts[2]);
-                            Ok(FieldValue::Reference(ReferenceValue::Field(
-                                entity_id, field_id,
-                            )))
-                        } else if parts.len() == 2 {
-                            // type.id (Entity ref)
-                            let entity_id = EntityId::new(s); // Already has dot
-                            Ok(FieldValue::Reference(ReferenceValue::Entity(entity_id)))
-                        } else {
-                            // Should not happen with split('.') but fallback
-                            Ok(FieldValue::Reference(ReferenceValue::Entity(
-                                EntityId::new(s),
-                            )))
-                        }
-                    } else {
-                        // Simple ID, assume Entity ref
-                        Ok(FieldValue::Reference(ReferenceValue::Entity(
-                            EntityId::new(s),
-                        )))
-                    }
-                }
-                _ => Err(format!(
-                    "Expected string for field type Reference, got {:?}",
-                    value
-                )),
-            }
-        }
-        FieldType::List => match value {
-            serde_json::Value::Array(arr) => {
-                if arr.is_empty() {
-                    return Ok(FieldValue::List(Vec::new()));
-                }
-
-                let item_type_str = list_item_types
-                        .as_ref()
-                        .and_then(|types| types.get(field_name))
-                        .ok_or_else(|| {
-                            format!(
-                                "Field '{}' has type List. Specify the inner type in list_item_types (e.g., {{\"{}\": \"reference\"}})",
-                                field_name, field_name
-                            )
-                        })?;
-
-                let item_type = parse_list_item_type(item_type_str)?;
-
-                let mut values = Vec::new();
-                for item in arr {
-                    let val = json_to_field_value(
-                        item,
-                        &item_type,
-                        workspace_path,
-                        target_file_path,
-                        list_item_types,
-                        field_name,
-                    )?;
-                    values.push(val);
-                }
-                Ok(FieldValue::List(values))
-            }
-            _ => Err(format!(
-                "Expected array for field type List, got {:?}",
-                value
-            )),
-        },
-        FieldType::Enum => match value {
-            serde_json::Value::String(s) => Ok(FieldValue::Enum(s.clone())),
-            _ => Err(format!(
-                "Expected string for field type Enum, got {:?}",
-                value
-            )),
-        },
-        FieldType::Path => {
-            match value {
-                serde_json::Value::String(s) => {
-                    // Path is relative to workspace root
-                    let path_from_root = PathBuf::from(s);
-                    let abs_target = workspace_path.join(path_from_root);
-
-                    // Calculate relative path from target file dir
-                    let target_dir = target_file_path.parent().unwrap_or(Path::new(""));
-                    let rel_path =
-                        pathdiff::diff_paths(&abs_target, target_dir).unwrap_or(abs_target);
-
-                    Ok(FieldValue::Path(rel_path))
-                }
-                _ => Err(format!(
-                    "Expected string for field type Path, got {:?}",
-                    value
-                )),
-            }
-        }
-        FieldType::Currency => {
-            match value {
-                serde_json::Value::String(s) => {
-                    // Parse "100 USD"
-                    let parts: Vec<&str> = s.split_whitespace().collect();
-                    if parts.len() != 2 {
-                        return Err(format!(
-                            "Invalid currency format '{}'. Expected 'AMOUNT CURRENCY' (e.g. '100 USD')",
-                            s
-                        ));
-                    }
-                    let amount = rust_decimal::Decimal::from_str_exact(parts[0])
-                        .map_err(|e| format!("Invalid currency amount: {}", e))?;
-                    let currency = iso_currency::Currency::from_code(parts[1])
-                        .ok_or_else(|| format!("Invalid currency code: {}", parts[1]))?;
-
-                    Ok(FieldValue::Currency { amount, currency })
-                }
-                _ => Err(format!(
-                    "Expected string for field type Currency, got {:?}",
-                    value
-                )),
-            }
-        }
-        FieldType::DateTime => match value {
-            serde_json::Value::String(s) => {
-                chrono::DateTime::parse_from_rfc3339(s)
-                    .map(FieldValue::DateTime)
-                    .map_err(|_| format!(
-                        "Invalid datetime '{}'. Use ISO 8601 / RFC 3339 format: \
-                         \"2025-01-15T17:00:00+03:00\" or \"2025-01-15T00:00:00Z\". \
-                         Note: this is different from the DSL format (2025-01-15 at 17:00 UTC+3).",
-                        s
-                    ))
-            }
-            _ => Err(format!(
-                "Expected ISO 8601 string for DateTime, got {:?}",
-                value
-            )),
-        },
+) -> Result<(FieldId, FieldValue), String> {
+    let field_id = FieldId::new(name);
+    if schema.is_computed(&field_id) {
+        return Err(computed_field_error(schema, name));
     }
+    let field_def = schema.fields.get(&field_id).ok_or_else(|| {
+        format!(
+            "Field '{}' not found in schema for '{}'",
+            name, schema.entity_type
+        )
+    })?;
+
+    let relocate_path = path_relocation(workspace_path, target_file_path);
+    let declared_item_type = list_item_types
+        .as_ref()
+        .and_then(|types| types.get(name))
+        .map(|item_type| parse_list_item_type(item_type))
+        .transpose()?;
+    let context = FieldJsonContext {
+        field: Some(name),
+        item_type: field_def.item_type(),
+        declared_item_type: declared_item_type.as_ref(),
+        map_path: Some(&relocate_path),
+    };
+
+    let value = FieldValue::try_from_json(value, field_def.expected_type(), &context).map_err(
+        |error| match error.cause() {
+            FieldJsonError::MissingItemType => format!(
+                "Field '{}' has type List. Specify the inner type in list_item_types (e.g., {{\"{}\": \"reference\"}})",
+                name, name
+            ),
+            _ => error.to_string(),
+        },
+    )?;
+    Ok((field_id, value))
+}
+
+/// The error for trying to set a field that the schema computes.
+pub(crate) fn computed_field_error(schema: &EntitySchema, name: &str) -> String {
+    format!(
+        "Field '{}' of '{}' is computed from the entity's other fields and can't be set",
+        name, schema.entity_type
+    )
 }
 
-/// Parses a list item type string into a FieldType enum.
+/// Parses a list item type string into a FieldType enum, ignoring case.
 fn parse_list_item_type(type_str: &str) -> Result<FieldType, String> {
-    match type_str.to_lowercase().as_str() {
-        "string" => Ok(FieldType::String),
-        "integer" => Ok(FieldType::Integer),
-        "float" => Ok(FieldType::Float),
-        "boolean" => Ok(FieldType::Boolean),
-        "currency" => Ok(FieldType::Currency),
-        "reference" => Ok(FieldType::Reference),
-        "datetime" => Ok(FieldType::DateTime),
-        "path" => Ok(FieldType::Path),
-        "enum" => Ok(FieldType::Enum),
-        _ => Err(format!(
-            "Invalid list item type '{}'. Valid types: string, integer, float, boolean, currency, reference, datetime, path, enum",
-            type_str
+    match type_str.to_lowercase().parse() {
+        Ok(FieldType::List) | Err(_) => Err(format!(
+            "Invalid list item type '{}'. Valid types: {}",
+            type_str,
+            FieldType::NAMES
+                .iter()
+                .filter(|name| **name != "list")
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", ")
         )),
+        Ok(item_type) => Ok(item_type),
     }
 }
 
@@ -0,0 +1,308 @@
+//! Add schema tool implementation.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use firm_core::schema::{EntitySchema, FieldMode, FieldSchema};
+use firm_core::{EntityType, FieldId, FieldType};
+use firm_lang::generate::generate_schema_dsl;
+use firm_lang::workspace::WorkspaceBuild;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use crate::resources;
+
+/// Parameters for the add_schema tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct AddSchemaParams {
+    /// Schema name, which is the entity type it defines (e.g., "invoice").
+    pub name: String,
+
+    /// The fields of the schema, in the order they should be declared.
+    pub fields: Vec<SchemaFieldDefinition>,
+
+    /// Optional target file path relative to workspace root.
+    /// If omitted, defaults to "schemas/<name>.firm".
+    /// The file will be created if it doesn't exist.
+    pub to_file: Option<String>,
+}
+
+/// A field definition for the add_schema tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SchemaFieldDefinition {
+    /// Field name (e.g., "due_date").
+    pub name: String,
+
+    /// Field type: string, integer, float, boolean, currency, reference,
+    /// datetime, path, enum or list.
+    pub r#type: String,
+
+    /// Whether entities must set the field. Default: false.
+    #[serde(default)]
+    pub required: bool,
+
+    /// The allowed values of an enum field (e.g., ["draft", "sent", "paid"]).
+    /// Required for enum fields, and not allowed for other types.
+    pub allowed_values: Option<Vec<String>>,
+
+    /// The type of every item of a list field (e.g., "reference"). Only for list fields.
+    pub item_type: Option<String>,
+
+    /// Optional description, written as a comment above the field.
+    pub description: Option<String>,
+}
+
+/// Result of adding a schema.
+#[derive(Debug)]
+pub struct AddSchemaResult {
+    /// The path where the schema was written (relative to workspace root).
+    pub path: String,
+    /// The generated DSL content.
+    pub dsl: String,
+    /// Original file content for rollback (None if the file was new).
+    pub original_content: Option<String>,
+}
+
+/// Execute the add_schema tool.
+///
+/// Validates the definition, generates DSL, and appends it to the target file.
+/// The caller should rebuild the workspace and roll back if the rebuild fails.
+pub fn execute(
+    workspace_path: &Path,
+    build: &WorkspaceBuild,
+    params: &AddSchemaParams,
+) -> Result<AddSchemaResult, String> {
+    let schema = to_schema(build, params)?;
+
+    // Generate DSL, with descriptions as comments above their fields
+    let descriptions = params
+        .fields
+        .iter()
+        .map(|field| field.description.as_deref());
+    let dsl = with_field_comments(&generate_schema_dsl(&schema), descriptions);
+
+    let target_path = params
+        .to_file
+        .clone()
+        .unwrap_or_else(|| format!("schemas/{}.firm", params.name));
+    let absolute_path = resources::to_absolute_path(workspace_path, &target_path)
+        .ok_or_else(|| format!("Invalid path (must be within workspace): {}", target_path))?;
+
+    // Append to an existing file, separated by a blank line
+    let original_content = fs::read_to_string(&absolute_path).ok();
+    let content = match &original_content {
+        Some(existing) if existing.trim().is_empty() => dsl.clone(),
+        Some(existing) => format!("{}\n\n{}", existing.trim_end(), dsl),
+        None => dsl.clone(),
+    };
+    resources::write_source_file(workspace_path, &target_path, &content)?;
+
+    Ok(AddSchemaResult {
+        path: target_path,
+        dsl,
+        original_content,
+    })
+}
+
+/// Validates a schema definition and converts it to a schema.
+fn to_schema(build: &WorkspaceBuild, params: &AddSchemaParams) -> Result<EntitySchema, String> {
+    if !is_identifier(&params.name) {
+        return Err(format!(
+            "Invalid schema name '{}': use letters, digits and underscores, starting with a letter",
+            params.name
+        ));
+    }
+    if build
+        .schemas
+        .iter()
+        .any(|schema| schema.entity_type.as_str() == params.name)
+    {
+        return Err(format!(
+            "Schema '{}' already exists. Use 'find_source' and 'replace_source' to change it.",
+            params.name
+        ));
+    }
+    if params.fields.is_empty() {
+        return Err("A schema needs at least one field".to_string());
+    }
+
+    let mut schema = EntitySchema::new(EntityType::new(&params.name));
+    let mut names = HashSet::new();
+    for (order, field) in params.fields.iter().enumerate() {
+        if !is_identifier(&field.name) {
+            return Err(format!(
+                "Invalid field name '{}': use letters, digits and underscores, starting with a letter",
+                field.name
+            ));
+        }
+        if !names.insert(field.name.as_str()) {
+            return Err(format!("Field '{}' is defined more than once", field.name));
+        }
+
+        let field_type = parse_field_type(&field.r#type)?;
+        let field_mode = if field.required {
+            FieldMode::Required
+        } else {
+            FieldMode::Optional
+        };
+
+        let field_schema = match (&field_type, &field.allowed_values) {
+            (FieldType::Enum, Some(values)) if !values.is_empty() => {
+                if let Some(value) = values
+                    .iter()
+                    .enumerate()
+                    .find(|(index, value)| {
+                        values[..*index].iter().any(|earlier| {
+                            earlier.trim().to_lowercase() == value.trim().to_lowercase()
+                        })
+                    })
+                    .map(|(_, value)| value)
+                {
+                    return Err(format!(
+                        "Enum field '{}' allows '{}' more than once (values are compared ignoring case)",
+                        field.name, value
+                    ));
+                }
+                FieldSchema::new_enum(field_mode, order, values.clone())
+            }
+            (FieldType::Enum, _) => {
+                return Err(format!(
+                    "Enum field '{}' needs a non-empty list of allowed_values",
+                    field.name
+                ));
+            }
+            (_, Some(_)) => {
+                return Err(format!(
+                    "Field '{}' has allowed_values, but only enum fields can have them",
+                    field.name
+                ));
+            }
+            (_, None) => FieldSchema::new(field_type.clone(), field_mode, order),
+        };
+
+        let field_schema = match &field.item_type {
+            Some(_) if field_type != FieldType::List => {
+                return Err(format!(
+                    "Field '{}' has an item_type, but only list fields can have one",
+                    field.name
+                ));
+            }
+            Some(item_type) => {
+                let item_type = parse_field_type(item_type)?;
+                if !item_type.is_item_type() {
+                    return Err(format!(
+                        "List field '{}' can't hold {} items. Use one of the other types as its item_type",
+                        field.name, item_type
+                    ));
+                }
+                field_schema.with_item_type(item_type)
+            }
+            None => field_schema,
+        };
+
+        schema = schema.add_field_schema(FieldId::new(&field.name), field_schema);
+    }
+
+    Ok(schema)
+}
+
+/// Checks whether a name can be used for a schema or field.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses a field type string into a FieldType enum, ignoring case.
+fn parse_field_type(type_str: &str) -> Result<FieldType, String> {
+    type_str.to_lowercase().parse().map_err(|_| {
+        format!(
+            "Invalid field type '{}'. Valid types: {}",
+            type_str,
+            FieldType::NAMES.join(", ")
+        )
+    })
+}
+
+/// Writes descriptions as comments above the field blocks of generated schema DSL.
+///
+/// Descriptions are given in field order, and fields without one are left as they are.
+fn with_field_comments<'a>(
+    dsl: &str,
+    descriptions: impl IntoIterator<Item = Option<&'a str>>,
+) -> String {
+    let mut descriptions = descriptions.into_iter();
+    let mut output = String::new();
+    for line in dsl.lines() {
+        if line.trim() == "field {"
+            && let Some(Some(description)) = descriptions.next()
+        {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            for comment in description.lines() {
+                output.push_str(&format!("{}// {}\n", indent, comment.trim()));
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Rollback an added schema by restoring the original file or deleting a new file.
+pub fn rollback(workspace_path: &Path, result: &AddSchemaResult) -> bool {
+    super::write_source::rollback(
+        workspace_path,
+        &result.path,
+        result.original_content.clone(),
+    )
+}
+
+/// Create a success result for add_schema.
+pub fn success_result(result: &AddSchemaResult) -> CallToolResult {
+    let msg = match result.original_content {
+        Some(_) => format!("Added schema to existing file '{}'.", result.path),
+        None => format!("Created new file '{}' and added schema.", result.path),
+    };
+
+    CallToolResult::success(vec![Content::text(msg), Content::text(result.dsl.clone())])
+}
+
+/// Create an error result for add_schema when validation fails and rollback occurred.
+pub fn validation_error_result(error: &str, rollback_success: bool) -> CallToolResult {
+    let rollback_msg = if rollback_success {
+        "Changes have been rolled back."
+    } else {
+        "Warning: Failed to rollback changes."
+    };
+
+    CallToolResult::error(vec![Content::text(format!(
+        "Validation failed: {}. {}",
+        error, rollback_msg
+    ))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_comments() {
+        let dsl = "schema invoice {\n    field {\n        name = \"number\"\n    }\n    field {\n        name = \"total\"\n    }\n}\n";
+        let result = with_field_comments(dsl, [None, Some("The amount due\nincluding tax")]);
+
+        assert_eq!(
+            result,
+            "schema invoice {\n    field {\n        name = \"number\"\n    }\n    // The amount due\n    // including tax\n    field {\n        name = \"total\"\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("due_date"));
+        assert!(is_identifier("q1"));
+        assert!(!is_identifier("1st"));
+        assert!(!is_identifier("due-date"));
+        assert!(!is_identifier(""));
+    }
+}
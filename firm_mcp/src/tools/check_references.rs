@@ -0,0 +1,89 @@
+//! Check references tool implementation.
+
+use std::path::Path;
+
+use firm_lang::workspace::{BrokenReferenceKind, Workspace, WorkspaceBuild};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::json;
+
+use super::response_format::{ResponseFormat, json_result};
+use crate::resources;
+
+/// Parameters for the check_references tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CheckReferencesParams {
+    /// Response format: "text" (default) for a readable report, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+}
+
+/// Execute the check_references tool.
+///
+/// Reports every reference that points at a missing entity or field,
+/// with the referring entity, field and source location.
+pub fn execute(
+    workspace: &Workspace,
+    build: &WorkspaceBuild,
+    workspace_path: &Path,
+    params: &CheckReferencesParams,
+) -> CallToolResult {
+    let broken = workspace.check_references(build);
+
+    let relative = |path: &Path| {
+        resources::to_relative_path(workspace_path, path)
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    };
+
+    match params.format {
+        ResponseFormat::Text => {
+            if broken.is_empty() {
+                return CallToolResult::success(vec![Content::text("No broken references found.")]);
+            }
+
+            let lines: Vec<String> = broken
+                .iter()
+                .map(|b| {
+                    let problem = match b.kind {
+                        BrokenReferenceKind::MissingEntity => "missing entity",
+                        BrokenReferenceKind::MissingField => "missing field",
+                    };
+                    format!(
+                        "{}:{}: {}.{} -> {} ({})",
+                        relative(&b.path),
+                        b.line,
+                        b.entity_id,
+                        b.field,
+                        b.reference,
+                        problem
+                    )
+                })
+                .collect();
+
+            CallToolResult::success(vec![Content::text(format!(
+                "Found {} broken reference(s):\n{}",
+                broken.len(),
+                lines.join("\n")
+            ))])
+        }
+        ResponseFormat::Json => {
+            let items: Vec<serde_json::Value> = broken
+                .iter()
+                .map(|b| {
+                    json!({
+                        "path": relative(&b.path),
+                        "line": b.line,
+                        "entity": b.entity_id.as_str(),
+                        "field": b.field.as_str(),
+                        "reference": b.reference.to_string(),
+                        "problem": match b.kind {
+                            BrokenReferenceKind::MissingEntity => "missing_entity",
+                            BrokenReferenceKind::MissingField => "missing_field",
+                        },
+                    })
+                })
+                .collect();
+            json_result(&items)
+        }
+    }
+}
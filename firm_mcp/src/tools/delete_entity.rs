@@ -0,0 +1,285 @@
+//! Delete entity tool implementation.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use firm_core::{EntityId, compose_entity_id};
+use firm_lang::parser::dsl::parse_source;
+use firm_lang::workspace::{InboundReference, Workspace};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use super::update_entity::{ChangedFile, removal_range};
+use crate::resources;
+
+/// Parameters for the delete_entity tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct DeleteEntityParams {
+    /// Entity type (e.g., "person", "task").
+    pub r#type: String,
+
+    /// Entity ID (e.g., "john_doe", "fix_bug").
+    pub id: String,
+
+    /// If true, delete the entity even if other entities reference it,
+    /// and keep it deleted even if workspace validation fails afterward.
+    /// Default: false.
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Result of deleting an entity (before validation).
+#[derive(Debug)]
+pub struct DeleteEntityResult {
+    /// The composite ID of the deleted entity.
+    pub entity_id: EntityId,
+    /// The files the entity's blocks were removed from.
+    pub changed_files: Vec<ChangedFile>,
+    /// References to the entity from other entities, broken by the deletion.
+    pub broken_references: Vec<String>,
+}
+
+/// Execute the delete_entity tool.
+///
+/// Removes every block of the entity from its source files. Unless forced,
+/// entities that reference it have to be cleaned up first, and the error lists them.
+/// The changed files are written, and the caller should rebuild the workspace
+/// and roll back if the rebuild fails (unless force mode is enabled).
+pub fn execute(
+    workspace_path: &Path,
+    workspace: &Workspace,
+    params: &DeleteEntityParams,
+) -> Result<DeleteEntityResult, String> {
+    let entity_type = params.r#type.as_str();
+    let entity_id = EntityId::new(&params.id);
+    let composite_id = compose_entity_id(entity_type, entity_id.as_str());
+
+    let sources = workspace.find_entity_sources(entity_type, entity_id.as_str());
+    if sources.is_empty() {
+        return Err(format!("Entity '{}' not found", composite_id));
+    }
+
+    let broken_references: Vec<String> = workspace
+        .inbound_references(&composite_id)
+        .iter()
+        .map(|reference| describe_reference(workspace_path, reference))
+        .collect();
+    if !broken_references.is_empty() && !params.force {
+        return Err(format!(
+            "Entity '{}' is referenced by other entities:\n- {}\n\
+             Remove or change these references first, or use 'force: true' to delete anyway.",
+            composite_id,
+            broken_references.join("\n- ")
+        ));
+    }
+
+    // Remove the entity's blocks from the files on disk
+    let mut new_contents: BTreeMap<PathBuf, (String, String)> = BTreeMap::new();
+    for source in &sources {
+        if new_contents.contains_key(&source.path) {
+            continue;
+        }
+        let content =
+            fs::read_to_string(&source.path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let new_content = remove_blocks(&content, entity_type, entity_id.as_str())?;
+        let parsed = parse_source(new_content.clone(), None)
+            .map_err(|e| format!("Failed to parse DSL: {}", e))?;
+        if parsed.has_error() {
+            return Err(format!(
+                "The deletion would leave {} with invalid syntax",
+                source.path.display()
+            ));
+        }
+        new_contents.insert(source.path.clone(), (content, new_content));
+    }
+
+    let mut changed_files = Vec::new();
+    for (path, (content, new_content)) in new_contents {
+        if content == new_content {
+            continue;
+        }
+        fs::write(&path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
+        changed_files.push(ChangedFile {
+            path: resources::to_relative_path(workspace_path, &path)
+                .unwrap_or_else(|| path.to_string_lossy().into_owned()),
+            original_content: content,
+        });
+    }
+    if changed_files.is_empty() {
+        return Err(format!("Entity '{}' not found", composite_id));
+    }
+
+    Ok(DeleteEntityResult {
+        entity_id: composite_id,
+        changed_files,
+        broken_references,
+    })
+}
+
+/// Removes the blocks of an entity from a file's content.
+fn remove_blocks(content: &str, entity_type: &str, entity_id: &str) -> Result<String, String> {
+    let parsed = parse_source(content.to_string(), None)
+        .map_err(|e| format!("Failed to parse DSL: {}", e))?;
+
+    let mut ranges: Vec<Range<usize>> = parsed
+        .entities()
+        .iter()
+        .filter(|entity| {
+            entity.entity_type() == Some(entity_type) && entity.id() == Some(entity_id)
+        })
+        .map(|entity| {
+            block_removal_range(
+                content,
+                &(entity.range().start_byte..entity.range().end_byte),
+            )
+        })
+        .collect();
+    ranges.sort_by_key(|range| std::cmp::Reverse(range.start));
+
+    let mut result = content.to_string();
+    for range in ranges {
+        result.replace_range(range, "");
+    }
+    Ok(result)
+}
+
+/// Gets the range to remove for a block, with the blank lines that separated it.
+///
+/// Blank lines after the block go with it, or the ones before it at the end of the file.
+pub(crate) fn block_removal_range(content: &str, block: &Range<usize>) -> Range<usize> {
+    let range = removal_range(content, block);
+    let starts_line = range.start == 0 || content[..range.start].ends_with('\n');
+    let ends_line = range.end == content.len() || content[..range.end].ends_with('\n');
+    if !starts_line || !ends_line {
+        // The block shares its lines with other content
+        return range;
+    }
+
+    let is_blank = |line: &str| line.trim().is_empty();
+    let mut start = range.start;
+    let mut end = range.end;
+    while end < content.len() {
+        let line_end = content[end..]
+            .find('\n')
+            .map_or(content.len(), |i| end + i + 1);
+        if !is_blank(&content[end..line_end]) {
+            break;
+        }
+        end = line_end;
+    }
+    if end == content.len() {
+        while start > 0 {
+            let line_start = content[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+            if !is_blank(&content[line_start..start]) {
+                break;
+            }
+            start = line_start;
+        }
+    }
+    start..end
+}
+
+/// Describes a reference to a deleted entity, with its location relative to the workspace.
+fn describe_reference(workspace_path: &Path, reference: &InboundReference) -> String {
+    let path = resources::to_relative_path(workspace_path, &reference.path)
+        .unwrap_or_else(|| reference.path.to_string_lossy().into_owned());
+    format!(
+        "{}:{}: field '{}' of '{}' references '{}'",
+        path, reference.line, reference.field, reference.entity_id, reference.reference
+    )
+}
+
+/// Restore the files an entity was deleted from.
+pub fn rollback(workspace_path: &Path, changed_files: &[ChangedFile]) -> bool {
+    super::update_entity::rollback(workspace_path, changed_files)
+}
+
+/// Create a success result for delete_entity.
+pub fn success_result(result: &DeleteEntityResult) -> CallToolResult {
+    let mut message = format!(
+        "Deleted '{}' from {}.",
+        result.entity_id,
+        changed_paths(result)
+    );
+    if result.broken_references.is_empty() {
+        message.push_str(" Workspace is valid.");
+    } else {
+        message.push_str(&format!(
+            " These references are now broken:\n- {}",
+            result.broken_references.join("\n- ")
+        ));
+    }
+
+    CallToolResult::success(vec![Content::text(message)])
+}
+
+/// Create a success result for delete_entity with force mode (validation warning).
+pub fn force_success_result(result: &DeleteEntityResult, error: &str) -> CallToolResult {
+    let mut message = format!(
+        "Deleted '{}' from {}. Warning: workspace has validation errors: {}. \
+         Use 'build' to check status after making more changes.",
+        result.entity_id,
+        changed_paths(result),
+        error
+    );
+    if !result.broken_references.is_empty() {
+        message.push_str(&format!(
+            "\nThese references are now broken:\n- {}",
+            result.broken_references.join("\n- ")
+        ));
+    }
+
+    CallToolResult::success(vec![Content::text(message)])
+}
+
+/// Create an error result for delete_entity when validation fails and rollback occurred.
+pub fn validation_error_result(error: &str, rollback_success: bool) -> CallToolResult {
+    let rollback_msg = if rollback_success {
+        "The entity has been restored."
+    } else {
+        "Warning: Failed to restore the entity."
+    };
+
+    CallToolResult::error(vec![Content::text(format!(
+        "Deletion would break the workspace: {}. {} Use 'force: true' to delete anyway.",
+        error, rollback_msg
+    ))])
+}
+
+fn changed_paths(result: &DeleteEntityResult) -> String {
+    let paths: Vec<&str> = result
+        .changed_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    paths.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_removal_range_takes_following_blank_lines() {
+        let content = "a {\n}\n\nb {\n}\n\nc {\n}\n";
+        let block = content.find("b {").unwrap()..content.find("b {").unwrap() + 5;
+        let range = block_removal_range(content, &block);
+
+        let mut result = content.to_string();
+        result.replace_range(range, "");
+        assert_eq!(result, "a {\n}\n\nc {\n}\n");
+    }
+
+    #[test]
+    fn test_block_removal_range_at_end_of_file() {
+        let content = "a {\n}\n\nb {\n}\n";
+        let block = content.find("b {").unwrap()..content.len() - 1;
+        let range = block_removal_range(content, &block);
+
+        let mut result = content.to_string();
+        result.replace_range(range, "");
+        assert_eq!(result, "a {\n}\n");
+    }
+}
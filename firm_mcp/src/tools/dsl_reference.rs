@@ -22,17 +22,21 @@ fn default_topic() -> String {
 
 /// Execute the dsl_reference tool.
 pub fn execute(params: &DslReferenceParams) -> CallToolResult {
-    let content = match params.topic.to_lowercase().as_str() {
-        "dsl" => DSL_REFERENCE.to_string(),
-        "query" => QUERY_REFERENCE.to_string(),
-        "all" | "" => format!("{}\n\n---\n\n{}", DSL_REFERENCE, QUERY_REFERENCE),
-        other => {
-            return CallToolResult::error(vec![Content::text(format!(
-                "Unknown topic '{}'. Valid options: 'dsl', 'query', 'all'",
-                other
-            ))]);
-        }
-    };
+    match reference_content(&params.topic) {
+        Some(content) => CallToolResult::success(vec![Content::text(content)]),
+        None => CallToolResult::error(vec![Content::text(format!(
+            "Unknown topic '{}'. Valid options: 'dsl', 'query', 'all'",
+            params.topic.to_lowercase()
+        ))]),
+    }
+}
 
-    CallToolResult::success(vec![Content::text(content)])
+/// Gets the reference for a topic, or None if there's no such topic.
+pub fn reference_content(topic: &str) -> Option<String> {
+    match topic.to_lowercase().as_str() {
+        "dsl" => Some(DSL_REFERENCE.to_string()),
+        "query" => Some(QUERY_REFERENCE.to_string()),
+        "all" | "" => Some(format!("{}\n\n---\n\n{}", DSL_REFERENCE, QUERY_REFERENCE)),
+        _ => None,
+    }
 }
@@ -54,6 +54,8 @@ with triple quotes.
 """
 ```
 
+Single-line strings support the escapes `\"`, `\\`, `\n` and `\t`. Other backslashes are kept as written, and triple-quoted strings don't process escapes.
+
 ### Number
 ```firm
 age = 30
@@ -74,6 +76,8 @@ cost = 299.99 EUR
 
 Syntax: `<amount> <CURRENCY_CODE>`
 
+An amount can't have more decimals than its currency's minor unit: `100.555 USD` fails validation because USD has two decimals, and `1500.5 JPY` because JPY has none. Trailing zeros don't count. Add `extra_precision = true` to a currency field in the schema to allow more decimals, like for exchange rates or crypto prices.
+
 ### Date
 ```firm
 start_date = 2025-01-15
@@ -110,7 +114,7 @@ urls = [
 ]
 ```
 
-Lists are homogeneous (all items must be the same type). Trailing commas are allowed.
+Lists are homogeneous (all items must be the same type). Trailing commas are allowed. A schema field can add `item_type = "string"` (or any other type name but list and enum) to require a type for every item.
 
 ### Path
 ```firm
@@ -127,6 +131,53 @@ priority = enum"high"
 
 Syntax: `enum"<value>"`
 
+The schema must list the allowed values in `allowed_values`. A schema with an enum field that has no allowed values fails to build, and `allowed_values` on any other field type is ignored with a warning.
+
+Enum values match the allowed values ignoring case and surrounding whitespace, and are stored in the casing the schema declares: `enum"ACTIVE"` becomes `enum"active"` when the schema allows `"active"`. Add `case_sensitive = true` to a schema field to require the exact declared casing instead. Allowed values can't differ only by case, so query filters, which ignore case, match the same entities in both modes.
+
+## Computed fields
+
+Schemas can declare fields that are computed from the entity's other fields when the workspace is built:
+
+```firm
+schema goal {
+    field { name = "current_value" type = "integer" required = true }
+    field { name = "target_value" type = "integer" required = true }
+    computed {
+        name = "progress"
+        expr = "current_value / target_value"
+    }
+}
+```
+
+Expressions use numbers, integer, float and currency fields, `+`, `-`, `*`, `/` and parentheses. Computed fields can be queried, but can't be set on entities. They're left unset when a field they use is missing.
+
+## Named queries
+
+A `query` block saves a query in the workspace under a name, to run with the `run_query` tool:
+
+```firm
+query open_tasks {
+    query = "from task | where is_completed == false"
+    description = "Tasks still to do"
+}
+```
+
+`query` is required and `description` optional. Query blocks aren't entities, and `query` can't be used as a schema name.
+
+## Tags
+
+Any entity can have a `tags` list of strings without a schema field for it. Tags are queried across types with `@tags`:
+
+```firm
+task fix_login {
+    name = "Fix login"
+    tags = ["urgent", "q3"]
+}
+```
+
+If a schema declares its own `tags` field, it stays a regular field for that type.
+
 ## Comments
 
 ```firm
@@ -158,13 +209,14 @@ pub const QUERY_REFERENCE: &str = r#"# Firm Query Language Reference
 ## Basic Syntax
 
 ```
-from <entity_selector> | <operation> | <operation> | ...
+from <entity_selector> | <operation> | <operation> | ... | <aggregation>
 ```
 
 ## Entity Selector
 
 ```bash
 from task    # Select entities of a specific type
+from person+ # Select a type and its subtypes (same as the type until schemas can extend others)
 from *       # Select all entities (wildcard)
 ```
 
@@ -193,7 +245,7 @@ from invoice | where status == "draft" or status == "sent" | where amount > 1000
 
 **Operators:** `==`, `!=`, `>`, `<`, `>=`, `<=`, `contains`, `startswith`, `endswith`, `in`
 
-**Metadata fields:** `@type`, `@id`
+**Metadata fields:** `@type`, `@id`, `@created`, `@modified` (timestamps from git blame, or file times outside git), `@tags` (e.g. `where @tags contains "urgent"`, matching whole tags)
 
 **Value types in queries:**
 - String: `"John Doe"` or `'active'`
@@ -213,6 +265,7 @@ from organization | related              # All related (1 degree)
 from organization | related task         # Related tasks (1 degree)
 from organization | related(2)           # All related (2 degrees)
 from organization | related(2) task      # Related tasks (2 degrees)
+from organization | related [task, project]  # Related tasks and projects
 ```
 
 ### order - Sort results
@@ -222,6 +275,7 @@ from task | order due_date           # Ascending (default)
 from task | order due_date desc      # Descending
 from task | order priority asc       # Ascending (explicit)
 from * | order @type                 # Sort by metadata
+from * | order @modified desc        # Recently changed first
 ```
 
 ### limit - Limit results
@@ -274,29 +328,47 @@ from task | median estimated_hours
 
 For all numeric aggregations, entities missing the field are skipped.
 
+### group - Aggregate per distinct value
+
+```bash
+from task | group status | count
+from task | group status | count | order count desc
+from invoice | group customer | sum amount | order sum desc
+```
+
+Place `group <field>` right before `count`, `sum`, `average` or `median` to get one result per distinct value (entities missing the field form a last group). Groups are ordered by value; add `order <aggregate> [asc|desc]` after the aggregation to order them by the aggregated value instead. A final `limit <n>` keeps the first groups (e.g. `from task | group status | count | order count desc | limit 5` for the top 5). A `limit` after an ungrouped aggregation is an error.
+
 ## Example Queries
 
+These use the built-in schemas (`person`, `organization`, `project`, `task`, `opportunity`, ...), so they work in a workspace created with `firm init`.
+
 ```bash
-# Find incomplete tasks
-from task | where is_completed == false
+# Find incomplete tasks, soonest due first
+from task | where is_completed == false | order due_date
 
 # Find tasks assigned to a person
 from task | where assignee_ref == person.john_doe
 
-# Find high-value opportunities
-from opportunity | where value >= 10000.00 USD | order value desc
+# Find high-value open opportunities
+from opportunity | where status == enum"open" and value >= 10000.00 USD | order value desc
 
-# Find tasks for active projects
-from project | where status == "active" | related task
+# Find tasks for projects in progress
+from project | where status == enum"in progress" | related task
 
 # Complex multi-hop query
-from organization | where industry == "tech" | related(2) task | where is_completed == false | order due_date | limit 10
+from organization | where industry_ref == industry.software | related(2) task | where is_completed == false | order due_date | limit 10
 
 # Count incomplete tasks
 from task | where is_completed == false | count
 
-# Total invoice amount
-from invoice | where status == "sent" | sum amount
+# Open pipeline value
+from opportunity | where status == enum"open" | sum value
+
+# Pipeline value per status, largest first
+from opportunity | group status | sum value | order sum desc
+
+# The 5 people with the most assigned tasks
+from task | group assignee_ref | count | order count desc | limit 5
 
 # Extract specific fields
 from task | where is_completed == false | select @id, name, due_date
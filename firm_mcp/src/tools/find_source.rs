@@ -2,12 +2,20 @@
 
 use std::path::Path;
 
-use firm_lang::workspace::Workspace;
+use firm_lang::workspace::{EntitySource, Workspace};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
+use serde_json::{Value, json};
 
+use super::get::{
+    Candidates, entity_candidates, entity_not_found_message, schema_not_found_message,
+};
+use super::response_format::{ResponseFormat, json_result};
 use crate::resources;
 
+/// The most IDs to offer when the entity or schema isn't found.
+const MAX_CLOSEST: usize = 5;
+
 /// Parameters for the find_source tool.
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct FindSourceParams {
@@ -15,41 +23,114 @@ pub struct FindSourceParams {
     pub r#type: String,
     /// Entity ID (e.g., "john_doe") or schema name (e.g., "person").
     pub id: String,
+    /// Response format: "text" (default) for `path:start-end` lines, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
 }
 
 /// Execute the find_source tool.
 ///
-/// Returns the relative path to the .firm file containing the entity or schema definition.
+/// Returns the relative path to the .firm file containing the entity or schema
+/// definition, with the lines its block spans. If several blocks contribute to
+/// an entity, each is listed, defining block first. When nothing matches, the
+/// error lists the closest IDs of the type, as a `closest` array in JSON mode.
 pub fn execute(
     workspace: &Workspace,
     workspace_path: &Path,
     params: &FindSourceParams,
 ) -> CallToolResult {
-    let source_path = if params.r#type == "schema" {
-        workspace.find_schema_source(&params.id)
+    let sources = if params.r#type == "schema" {
+        workspace
+            .find_schema_block(&params.id)
+            .into_iter()
+            .collect()
+    } else {
+        workspace.find_entity_sources(&params.r#type, &params.id)
+    };
+
+    if sources.is_empty() {
+        return not_found(workspace, params);
+    }
+
+    let relative = |path: &Path| {
+        resources::to_relative_path(workspace_path, path)
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    };
+
+    match params.format {
+        ResponseFormat::Text => {
+            let lines: Vec<String> = sources
+                .iter()
+                .map(|source| {
+                    format!(
+                        "{}:{}-{}",
+                        relative(&source.path),
+                        source.line(),
+                        source.end_line()
+                    )
+                })
+                .collect();
+            CallToolResult::success(vec![Content::text(lines.join("\n"))])
+        }
+        ResponseFormat::Json => {
+            let sources: Vec<Value> = sources
+                .iter()
+                .map(|source| source_to_json(source, &relative(&source.path)))
+                .collect();
+            json_result(&json!({
+                "type": params.r#type,
+                "id": params.id,
+                "sources": sources,
+            }))
+        }
+    }
+}
+
+/// Convert a block's location to JSON, with 1-based lines and column.
+fn source_to_json(source: &EntitySource, path: &str) -> Value {
+    json!({
+        "path": path,
+        "start_line": source.line(),
+        "start_column": source.column(),
+        "end_line": source.end_line(),
+    })
+}
+
+/// Reports a missing entity or schema, offering the closest IDs of its type.
+///
+/// In JSON mode, the IDs are listed as `closest` instead of in a message.
+fn not_found(workspace: &Workspace, params: &FindSourceParams) -> CallToolResult {
+    let candidates = Candidates::Closest(MAX_CLOSEST);
+    let is_schema = params.r#type == "schema";
+    let existing_ids = if is_schema {
+        workspace.schema_names()
     } else {
-        workspace.find_entity_source(&params.r#type, &params.id)
+        workspace.entity_ids(&params.r#type)
     };
 
-    match source_path {
-        Some(path) => {
-            let relative = resources::to_relative_path(workspace_path, &path)
-                .unwrap_or_else(|| path.to_string_lossy().to_string());
-            CallToolResult::success(vec![Content::text(relative)])
+    match params.format {
+        ResponseFormat::Text => {
+            let message = if is_schema {
+                schema_not_found_message(&params.id, existing_ids, candidates)
+            } else {
+                entity_not_found_message(&params.r#type, &params.id, existing_ids, candidates)
+            };
+            CallToolResult::error(vec![Content::text(message)])
         }
-        None => {
-            let msg = if params.r#type == "schema" {
-                format!(
-                    "Schema '{}' not found. Use list with type='schema' to see available schemas.",
-                    params.id
-                )
+        ResponseFormat::Json => {
+            let closest_ids = if is_schema {
+                candidates.select(&params.id, existing_ids)
             } else {
-                format!(
-                    "Entity '{}' with type '{}' not found. Use list with type='{}' to see available IDs.",
-                    params.id, params.r#type, params.r#type
-                )
+                entity_candidates(&params.r#type, &params.id, existing_ids, candidates)
             };
-            CallToolResult::error(vec![Content::text(msg)])
+            let mut result = json_result(&json!({
+                "type": params.r#type,
+                "id": params.id,
+                "sources": [],
+                "closest": closest_ids,
+            }));
+            result.is_error = Some(true);
+            result
         }
     }
 }
@@ -1,23 +1,70 @@
 //! Get tool implementation.
 
-use firm_core::compose_entity_id;
+use std::collections::BTreeMap;
+
+use firm_core::graph::EntityGraph;
+use firm_core::{
+    Entity, ValueFormat, closest, compose_entity_id, decompose_entity_id, did_you_mean,
+    suggest_similar,
+};
 use firm_lang::workspace::WorkspaceBuild;
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
+use serde_json::json;
+
+use super::related::{entity_name, summarize};
+use super::response_format::{ResponseFormat, json_result, schema_to_json};
+use super::response_limits::{ResponseLimits, truncation_marker};
+
+/// How many related entities of each type are listed by default.
+const DEFAULT_RELATED_LIMIT: usize = 10;
 
 /// Parameters for the get tool.
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
 pub struct GetParams {
     /// Entity type (e.g., "person", "organization"), or "schema" to get a schema definition.
     pub r#type: String,
     /// Entity ID (e.g., "john_doe") or schema name (e.g., "person").
     pub id: String,
+    /// Response format: "text" (default) for a readable summary, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+    /// Also list the entities that reference this entity or that it references,
+    /// grouped by type. Default: false.
+    #[serde(default)]
+    pub include_related: bool,
+    /// Only list related entities of these types (e.g., ["task", "interaction"]).
+    #[serde(default)]
+    pub related_types: Option<Vec<String>>,
+    /// How many related entities to list per type (default 10); the rest are counted.
+    #[serde(default)]
+    pub related_limit: Option<usize>,
+    /// If true, list every related entity up to 'related_limit' per type even
+    /// past the server's response size limits. Default: false.
+    #[serde(default)]
+    pub unlimited: bool,
+}
+
+/// The entities directly related to an entity, grouped by type.
+struct RelatedGroup<'a> {
+    entities: Vec<&'a Entity>,
+    /// How many more entities of the type there are beyond the limit.
+    more: usize,
 }
 
 /// Execute the get tool.
 ///
-/// Returns full details of a single entity or schema.
-pub fn execute(build: &WorkspaceBuild, params: &GetParams) -> CallToolResult {
+/// Returns full details of a single entity or schema. With `include_related`,
+/// the entities it's connected to by a reference in either direction follow,
+/// which the graph only holds for references that resolve. Related entities
+/// beyond the response limits are counted instead of listed.
+pub fn execute(
+    build: &WorkspaceBuild,
+    graph: &EntityGraph,
+    value_format: &ValueFormat,
+    limits: &ResponseLimits,
+    params: &GetParams,
+) -> CallToolResult {
     if params.r#type == "schema" {
         // Get schema by name
         let schema = build
@@ -26,21 +73,294 @@ pub fn execute(build: &WorkspaceBuild, params: &GetParams) -> CallToolResult {
             .find(|s| s.entity_type.as_str() == params.id);
 
         match schema {
-            Some(schema) => CallToolResult::success(vec![Content::text(schema.to_string())]),
-            None => CallToolResult::error(vec![Content::text(format!(
-                "Schema '{}' not found. Use list with type='schema' to see available schemas.",
-                params.id
-            ))]),
+            Some(schema) => match params.format {
+                ResponseFormat::Text => {
+                    CallToolResult::success(vec![Content::text(schema.to_string())])
+                }
+                ResponseFormat::Json => json_result(&schema_to_json(schema)),
+            },
+            None => {
+                let names = build.schemas.iter().map(|s| s.entity_type.as_str());
+                CallToolResult::error(vec![Content::text(schema_not_found_message(
+                    &params.id,
+                    names,
+                    Candidates::Similar,
+                ))])
+            }
         }
     } else {
         // Get entity by type and ID
-        let id = compose_entity_id(&params.r#type, &params.id);
-        match build.entities.iter().find(|e| e.id == id) {
-            Some(entity) => CallToolResult::success(vec![Content::text(entity.to_string())]),
-            None => CallToolResult::error(vec![Content::text(format!(
-                "Entity '{}' with type '{}' not found. Use list with type='{}' to see available IDs.",
-                params.id, params.r#type, params.r#type
-            ))]),
+        match find_entity(build, &params.r#type, &params.id) {
+            Some(entity) => {
+                let mut related = params
+                    .include_related
+                    .then(|| related_groups(graph, entity, params));
+                let marker = related
+                    .as_mut()
+                    .and_then(|related| limit_related(related, &limits.for_call(params.unlimited)));
+                match params.format {
+                    ResponseFormat::Text => {
+                        let mut text = value_format.entity(entity);
+                        if let Some(related) = &related {
+                            text.push_str(&related_text(related));
+                        }
+                        if let Some(marker) = marker {
+                            text.push_str("\n\n");
+                            text.push_str(&marker);
+                        }
+                        CallToolResult::success(vec![Content::text(text)])
+                    }
+                    ResponseFormat::Json => {
+                        let mut json = entity.to_json();
+                        if let Some(related) = &related {
+                            json["related"] = related_json(related);
+                        }
+                        let mut result = json_result(&json);
+                        if let Some(marker) = marker {
+                            // A separate item, so the JSON stays parseable
+                            result.content.push(Content::text(marker));
+                        }
+                        result
+                    }
+                }
+            }
+            None => {
+                let ids = build
+                    .entities
+                    .iter()
+                    .map(|e| decompose_entity_id(e.id.as_str()))
+                    .filter(|(entity_type, _)| *entity_type == params.r#type)
+                    .map(|(_, id)| id);
+                CallToolResult::error(vec![Content::text(entity_not_found_message(
+                    &params.r#type,
+                    &params.id,
+                    ids,
+                    Candidates::Similar,
+                ))])
+            }
+        }
+    }
+}
+
+/// Groups the entities directly related to an entity by type, in ID order.
+fn related_groups<'a>(
+    graph: &'a EntityGraph,
+    entity: &Entity,
+    params: &GetParams,
+) -> BTreeMap<String, RelatedGroup<'a>> {
+    let limit = params.related_limit.unwrap_or(DEFAULT_RELATED_LIMIT);
+    let mut groups: BTreeMap<String, RelatedGroup> = BTreeMap::new();
+
+    for related in graph.get_related(&entity.id, None).unwrap_or_default() {
+        let entity_type = related.entity_type.to_string();
+        if related.id == entity.id
+            || params
+                .related_types
+                .as_ref()
+                .is_some_and(|types| !types.contains(&entity_type))
+        {
+            continue;
+        }
+
+        let group = groups.entry(entity_type).or_insert(RelatedGroup {
+            entities: Vec::new(),
+            more: 0,
+        });
+        if group.entities.len() < limit {
+            group.entities.push(related);
+        } else {
+            group.more += 1;
+        }
+    }
+    groups
+}
+
+/// Cuts the listed related entities down to the response limits, in type order.
+///
+/// The entities left out are counted with the rest of their type. Returns the
+/// truncation marker if any were left out.
+fn limit_related(
+    groups: &mut BTreeMap<String, RelatedGroup>,
+    limits: &ResponseLimits,
+) -> Option<String> {
+    let sizes = groups
+        .values()
+        .flat_map(|group| &group.entities)
+        .map(|entity| summarize(entity).len() + 3);
+    let listed: usize = groups.values().map(|group| group.entities.len()).sum();
+    let mut remaining = limits.fit(sizes);
+    if remaining == listed {
+        return None;
+    }
+
+    let shown = remaining;
+    let mut total = 0;
+    for group in groups.values_mut() {
+        total += group.entities.len() + group.more;
+        let kept = remaining.min(group.entities.len());
+        group.more += group.entities.len() - kept;
+        group.entities.truncate(kept);
+        remaining -= kept;
+    }
+    Some(truncation_marker(
+        shown,
+        total,
+        "related entities",
+        "refine with related_types/related_limit",
+    ))
+}
+
+fn related_text(groups: &BTreeMap<String, RelatedGroup>) -> String {
+    if groups.is_empty() {
+        return "\n\nRelated: none".to_string();
+    }
+
+    let mut text = String::from("\n\nRelated:");
+    for (entity_type, group) in groups {
+        text.push_str(&format!("\n{}:", entity_type));
+        for entity in &group.entities {
+            text.push_str(&format!("\n- {}", summarize(entity)));
+        }
+        if group.more > 0 {
+            text.push_str(&format!("\n- +{} more", group.more));
+        }
+    }
+    text
+}
+
+fn related_json(groups: &BTreeMap<String, RelatedGroup>) -> serde_json::Value {
+    let groups: serde_json::Map<String, serde_json::Value> = groups
+        .iter()
+        .map(|(entity_type, group)| {
+            let entities: Vec<serde_json::Value> = group
+                .entities
+                .iter()
+                .map(|entity| {
+                    json!({
+                        "id": entity.id.as_str(),
+                        "type": entity.entity_type.as_str(),
+                        "name": entity_name(entity),
+                    })
+                })
+                .collect();
+            (
+                entity_type.clone(),
+                json!({ "entities": entities, "more": group.more }),
+            )
+        })
+        .collect();
+    serde_json::Value::Object(groups)
+}
+
+/// Find an entity in the build by its type and ID.
+pub fn find_entity<'a>(
+    build: &'a WorkspaceBuild,
+    entity_type: &str,
+    id: &str,
+) -> Option<&'a Entity> {
+    let id = compose_entity_id(entity_type, id);
+    build.entities.iter().find(|e| e.id == id)
+}
+
+/// Which existing IDs to offer when an entity or schema isn't found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Candidates {
+    /// IDs close enough to the missing one to be a likely typo.
+    Similar,
+    /// Up to this many of the closest IDs, however far off, to list what's there.
+    Closest(usize),
+}
+
+impl Candidates {
+    /// Picks the IDs to offer for a missing ID among the existing ones.
+    pub fn select<I, S>(self, id: &str, existing_ids: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        match self {
+            Candidates::Similar => suggest_similar(id, existing_ids),
+            Candidates::Closest(count) => closest(id, existing_ids, count),
+        }
+    }
+}
+
+/// Picks the IDs to offer for a missing entity, as full `type.id` entity IDs.
+pub fn entity_candidates<I, S>(
+    entity_type: &str,
+    id: &str,
+    existing_ids: I,
+    candidates: Candidates,
+) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    candidates
+        .select(id, existing_ids)
+        .iter()
+        .map(|candidate| compose_entity_id(entity_type, candidate).to_string())
+        .collect()
+}
+
+/// Create the error message for a missing entity, offering candidate IDs of its type.
+pub fn entity_not_found_message<I, S>(
+    entity_type: &str,
+    id: &str,
+    existing_ids: I,
+    candidates: Candidates,
+) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let offered = entity_candidates(entity_type, id, existing_ids, candidates);
+
+    let mut msg = format!("Entity '{}' with type '{}' not found.", id, entity_type);
+    match did_you_mean(&offered) {
+        Some(question) => {
+            msg.push(' ');
+            msg.push_str(&question);
+        }
+        // The closest IDs are only missing when there are none to list
+        None if candidates != Candidates::Similar => {
+            msg.push_str(&format!(
+                " The workspace has no entities of type '{}'.",
+                entity_type
+            ));
+            return msg;
+        }
+        None => {}
+    }
+    msg.push_str(&format!(
+        " Use list with type='{}' to see available IDs.",
+        entity_type
+    ));
+    msg
+}
+
+/// Create the error message for a missing schema, offering candidate schema names.
+pub fn schema_not_found_message<I, S>(
+    name: &str,
+    existing_names: I,
+    candidates: Candidates,
+) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut msg = format!("Schema '{}' not found.", name);
+    match did_you_mean(&candidates.select(name, existing_names)) {
+        Some(question) => {
+            msg.push(' ');
+            msg.push_str(&question);
+        }
+        None if candidates != Candidates::Similar => {
+            msg.push_str(" The workspace has no schemas.");
+            return msg;
         }
+        None => {}
     }
+    msg.push_str(" Use list with type='schema' to see available schemas.");
+    msg
 }
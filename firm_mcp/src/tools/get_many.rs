@@ -0,0 +1,70 @@
+//! Get many tool implementation.
+
+use firm_core::decompose_entity_id;
+use firm_lang::workspace::WorkspaceBuild;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::json;
+
+use super::get::find_entity;
+use super::response_format::{ResponseFormat, json_result};
+
+/// A reference to an entity, either as a type and ID or as a composite ID.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum EntityRef {
+    /// Entity type and ID (e.g., {"type": "person", "id": "john_doe"}).
+    Parts { r#type: String, id: String },
+    /// Composite ID (e.g., "person.john_doe").
+    Composite(String),
+}
+
+impl EntityRef {
+    /// Returns the entity type and ID this reference points to.
+    fn parts(&self) -> (&str, &str) {
+        match self {
+            EntityRef::Parts { r#type, id } => (r#type, id),
+            EntityRef::Composite(composite_id) => decompose_entity_id(composite_id),
+        }
+    }
+}
+
+/// Parameters for the get_many tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetManyParams {
+    /// Entities to fetch, as {"type", "id"} objects or composite IDs like "person.john_doe".
+    pub ids: Vec<EntityRef>,
+    /// Response format: "text" (default) for a readable summary, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+}
+
+/// Execute the get_many tool.
+///
+/// Returns the found entities in the requested order, plus the IDs that weren't found.
+pub fn execute(build: &WorkspaceBuild, params: &GetManyParams) -> CallToolResult {
+    let mut entities = Vec::new();
+    let mut not_found = Vec::new();
+
+    for entity_ref in &params.ids {
+        let (entity_type, id) = entity_ref.parts();
+        match find_entity(build, entity_type, id) {
+            Some(entity) => entities.push(entity),
+            None => not_found.push(format!("{}.{}", entity_type, id)),
+        }
+    }
+
+    match params.format {
+        ResponseFormat::Text => {
+            let mut sections: Vec<String> = entities.iter().map(|e| e.to_string()).collect();
+            if !not_found.is_empty() {
+                sections.push(format!("Not found: {}", not_found.join(", ")));
+            }
+            CallToolResult::success(vec![Content::text(sections.join("\n---\n"))])
+        }
+        ResponseFormat::Json => json_result(&json!({
+            "entities": entities.iter().map(|entity| entity.to_json()).collect::<Vec<_>>(),
+            "not_found": not_found,
+        })),
+    }
+}
@@ -1,38 +1,271 @@
 //! List tool implementation.
 
+use std::collections::BTreeMap;
+
+use firm_core::decompose_entity_id;
 use firm_lang::workspace::WorkspaceBuild;
+use globset::{Glob, GlobMatcher};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
+use serde_json::json;
+
+use super::response_format::{ResponseFormat, json_result};
+use super::response_limits::{ResponseLimits, truncation_marker};
+
+/// The type value that lists entities of every type.
+const ALL_TYPES: &str = "*";
 
 /// Parameters for the list tool.
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ListParams {
-    /// Entity type to list (e.g., "person", "organization"), or "schema" to list all schemas.
-    pub r#type: String,
+    /// Entity type to list (e.g., "person", "organization"), "schema" to list all schemas,
+    /// or "*" to list entities of every type grouped by type.
+    /// If omitted, the number of entities of each type is returned instead of IDs.
+    #[serde(default)]
+    pub r#type: Option<String>,
+
+    /// Optional glob pattern matched against entity IDs without the type prefix
+    /// (e.g., "q1_*"), or against schema names when listing schemas.
+    #[serde(default)]
+    pub id_pattern: Option<String>,
+
+    /// Optional prefix that IDs without the type prefix (or schema names) must start with.
+    #[serde(default)]
+    pub prefix: Option<String>,
+
+    /// Optional text that IDs without the type prefix (or schema names) must contain.
+    #[serde(default)]
+    pub contains: Option<String>,
+
+    /// Optional maximum number of IDs to return. When there are more,
+    /// the response includes a cursor to continue from.
+    #[serde(default)]
+    pub limit: Option<usize>,
+
+    /// Cursor from a previous response, to continue where it stopped.
+    /// Cursors stay valid as long as the workspace doesn't change.
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    /// Response format: "text" (default) for one ID per line, or "json" for an object with
+    /// the IDs (by type for "*"), the total count and the next cursor.
+    #[serde(default)]
+    pub format: ResponseFormat,
+
+    /// If true, return every ID up to 'limit' even past the server's response
+    /// size limits. Default: false.
+    #[serde(default)]
+    pub unlimited: bool,
+}
+
+/// Filters on IDs or names, matched without the type prefix.
+struct Filters {
+    matcher: Option<GlobMatcher>,
+    prefix: Option<String>,
+    contains: Option<String>,
+}
+
+impl Filters {
+    fn matches(&self, value: &str) -> bool {
+        self.matcher.as_ref().is_none_or(|m| m.is_match(value))
+            && self.prefix.as_ref().is_none_or(|p| value.starts_with(p))
+            && self.contains.as_ref().is_none_or(|c| value.contains(c))
+    }
+}
+
+/// A page of sorted items, with what's needed to fetch the next one.
+struct Page<'a> {
+    items: &'a [&'a str],
+    total: usize,
+    next_cursor: Option<String>,
+    /// Whether the page was cut short by the response limits rather than the requested limit.
+    truncated: bool,
 }
 
 /// Execute the list tool.
 ///
 /// Returns all entity IDs of the given type, or all schema names if type is "schema".
-pub fn execute(build: &WorkspaceBuild, params: &ListParams) -> CallToolResult {
-    let result = if params.r#type == "schema" {
+/// With type "*", entity IDs of every type are returned grouped under their type.
+/// Without a type, the number of entities of each type is returned.
+/// IDs and names are sorted, so a limit and cursor page through them in a stable order.
+/// Pages are also cut at the response limits, with a cursor to continue from.
+pub fn execute(
+    build: &WorkspaceBuild,
+    limits: &ResponseLimits,
+    params: &ListParams,
+) -> CallToolResult {
+    let matcher = match &params.id_pattern {
+        Some(pattern) => match Glob::new(pattern) {
+            Ok(glob) => Some(glob.compile_matcher()),
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid id_pattern '{}': {}",
+                    pattern, e
+                ))]);
+            }
+        },
+        None => None,
+    };
+    let filters = Filters {
+        matcher,
+        prefix: params.prefix.clone(),
+        contains: params.contains.clone(),
+    };
+
+    let Some(entity_type) = params.r#type.as_deref() else {
+        return count_by_type(build, &filters, params.format);
+    };
+
+    let mut items: Vec<&str> = if entity_type == "schema" {
         // List all schema names
-        let names: Vec<&str> = build
+        build
             .schemas
             .iter()
             .map(|s| s.entity_type.as_str())
-            .collect();
-        names.join("\n")
+            .filter(|name| filters.matches(name))
+            .collect()
     } else {
-        // List all entity IDs of the given type
-        let ids: Vec<&str> = build
+        // List entity IDs of the given type, or of every type
+        build
             .entities
             .iter()
-            .filter(|e| e.entity_type.as_str() == params.r#type)
+            .filter(|e| entity_type == ALL_TYPES || e.entity_type.as_str() == entity_type)
+            .filter(|e| filters.matches(decompose_entity_id(e.id.as_str()).1))
             .map(|e| e.id.as_str())
-            .collect();
-        ids.join("\n")
+            .collect()
+    };
+    items.sort_unstable();
+
+    let limits = limits.for_call(params.unlimited);
+    let page = match paginate(&items, params.limit, params.cursor.as_deref(), &limits) {
+        Ok(page) => page,
+        Err(message) => return CallToolResult::error(vec![Content::text(message)]),
+    };
+
+    if entity_type == ALL_TYPES {
+        // Group the page's entity IDs by type
+        let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for id in page.items {
+            groups
+                .entry(decompose_entity_id(id).0)
+                .or_default()
+                .push(id);
+        }
+
+        match params.format {
+            ResponseFormat::Text => {
+                let text = groups
+                    .iter()
+                    .map(|(entity_type, ids)| format!("{}:\n{}", entity_type, ids.join("\n")))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                text_result(text, &page)
+            }
+            ResponseFormat::Json => page_json(json!(groups), &page),
+        }
+    } else {
+        match params.format {
+            ResponseFormat::Text => text_result(page.items.join("\n"), &page),
+            ResponseFormat::Json => page_json(json!(page.items), &page),
+        }
+    }
+}
+
+/// Counts the entities of each type, sorted by type.
+fn count_by_type(
+    build: &WorkspaceBuild,
+    filters: &Filters,
+    format: ResponseFormat,
+) -> CallToolResult {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for entity in &build.entities {
+        let (_, local_id) = decompose_entity_id(entity.id.as_str());
+        if filters.matches(local_id) {
+            *counts.entry(entity.entity_type.as_str()).or_default() += 1;
+        }
+    }
+
+    match format {
+        ResponseFormat::Text => {
+            let lines: Vec<String> = counts
+                .iter()
+                .map(|(entity_type, count)| format!("{}: {}", entity_type, count))
+                .collect();
+            CallToolResult::success(vec![Content::text(lines.join("\n"))])
+        }
+        ResponseFormat::Json => json_result(&counts),
+    }
+}
+
+/// Takes the page of sorted items after the cursor, up to the limit and
+/// within the response limits.
+///
+/// The cursor is the number of items already returned.
+fn paginate<'a>(
+    items: &'a [&'a str],
+    limit: Option<usize>,
+    cursor: Option<&str>,
+    limits: &ResponseLimits,
+) -> Result<Page<'a>, String> {
+    let start = match cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .ok()
+            .filter(|start| *start <= items.len())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid cursor '{}'. Use the cursor from the previous response, \
+                     or list again without one if the workspace has changed.",
+                    cursor
+                )
+            })?,
+        None => 0,
     };
+    let requested_end = limit.map_or(items.len(), |limit| (start + limit).min(items.len()));
+    let sizes = items[start..requested_end]
+        .iter()
+        .map(|item| item.len() + 1);
+    let end = start + limits.fit(sizes);
+
+    Ok(Page {
+        items: &items[start..end],
+        total: items.len(),
+        next_cursor: (end < items.len()).then(|| end.to_string()),
+        truncated: end < requested_end,
+    })
+}
+
+/// Creates a text result, noting how to continue when the page is truncated.
+fn text_result(mut text: String, page: &Page) -> CallToolResult {
+    if let Some(cursor) = page.next_cursor.as_ref().filter(|_| page.truncated) {
+        text.push_str("\n\n");
+        text.push_str(&truncation_marker(
+            page.items.len(),
+            page.total,
+            "IDs",
+            &format!(
+                "continue with cursor '{}', or refine with id_pattern/prefix/contains",
+                cursor
+            ),
+        ));
+    } else if let Some(cursor) = &page.next_cursor {
+        text.push_str(&format!(
+            "\n\nShowing {} of {}. Use cursor '{}' to continue.",
+            page.items.len(),
+            page.total,
+            cursor
+        ));
+    }
+    CallToolResult::success(vec![Content::text(text)])
+}
 
-    CallToolResult::success(vec![Content::text(result)])
+/// Creates a JSON result with the page's items, the total, the next cursor
+/// and whether the response limits cut the page short.
+fn page_json(items: serde_json::Value, page: &Page) -> CallToolResult {
+    json_result(&json!({
+        "items": items,
+        "total": page.total,
+        "next_cursor": page.next_cursor,
+        "truncated": page.truncated,
+    }))
 }
@@ -0,0 +1,40 @@
+//! List changes tool implementation.
+
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use crate::journal::{ChangeJournal, JOURNAL_CAPACITY};
+
+/// Parameters for the list_changes tool.
+/// This tool takes no parameters - it lists the whole journal.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListChangesParams {}
+
+/// Execute the list_changes tool.
+///
+/// Lists the changes in the journal, most recent first, with the files each one touched.
+pub fn execute(journal: &ChangeJournal) -> CallToolResult {
+    if journal.is_empty() {
+        return CallToolResult::success(vec![Content::text(
+            "No changes recorded since the server started.".to_string(),
+        )]);
+    }
+
+    let mut text = format!(
+        "{} change(s), most recent first (up to {} are kept until the server restarts):",
+        journal.len(),
+        JOURNAL_CAPACITY
+    );
+    for entry in journal.entries() {
+        let paths: Vec<&str> = entry.files.iter().map(|file| file.path.as_str()).collect();
+        text.push_str(&format!(
+            "\n#{} {}: {} ({})",
+            entry.id,
+            entry.tool,
+            entry.summary,
+            paths.join(", ")
+        ));
+    }
+
+    CallToolResult::success(vec![Content::text(text)])
+}
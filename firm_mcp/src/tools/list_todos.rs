@@ -0,0 +1,93 @@
+//! List todos tool implementation.
+
+use std::path::Path;
+
+use firm_lang::workspace::{Annotation, DEFAULT_ANNOTATION_MARKERS, Workspace};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::json;
+
+use super::response_format::{ResponseFormat, json_result};
+use crate::resources;
+
+/// Parameters for the list_todos tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListTodosParams {
+    /// Comment markers to look for. Default: ["TODO", "FIXME", "NOTE"].
+    #[serde(default)]
+    pub markers: Vec<String>,
+
+    /// Response format: "text" (default) for a readable report, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+}
+
+/// Execute the list_todos tool.
+///
+/// Lists comments starting with a marker, with their source location
+/// and the entity or schema they're written in.
+pub fn execute(
+    workspace: &Workspace,
+    workspace_path: &Path,
+    params: &ListTodosParams,
+) -> CallToolResult {
+    let markers: Vec<&str> = if params.markers.is_empty() {
+        DEFAULT_ANNOTATION_MARKERS.to_vec()
+    } else {
+        params.markers.iter().map(String::as_str).collect()
+    };
+    let annotations = workspace.annotations(&markers);
+
+    let relative = |annotation: &Annotation| {
+        resources::to_relative_path(workspace_path, &annotation.path)
+            .unwrap_or_else(|| annotation.path.to_string_lossy().to_string())
+    };
+
+    match params.format {
+        ResponseFormat::Text => {
+            if annotations.is_empty() {
+                return CallToolResult::success(vec![Content::text("No annotations found.")]);
+            }
+
+            let lines: Vec<String> = annotations
+                .iter()
+                .map(|annotation| {
+                    let owner = annotation
+                        .owner
+                        .as_ref()
+                        .map(|owner| format!(" ({})", owner))
+                        .unwrap_or_default();
+                    format!(
+                        "{}:{}: {} {}{}",
+                        relative(annotation),
+                        annotation.line,
+                        annotation.marker,
+                        annotation.text,
+                        owner
+                    )
+                })
+                .collect();
+
+            CallToolResult::success(vec![Content::text(format!(
+                "Found {} annotation(s):\n{}",
+                annotations.len(),
+                lines.join("\n")
+            ))])
+        }
+        ResponseFormat::Json => {
+            let items: Vec<serde_json::Value> = annotations
+                .iter()
+                .map(|annotation| {
+                    json!({
+                        "path": relative(annotation),
+                        "line": annotation.line,
+                        "marker": annotation.marker.as_str(),
+                        "text": annotation.text.as_str(),
+                        "owner": annotation.owner.as_ref().map(|owner| owner.to_string()),
+                    })
+                })
+                .collect();
+            json_result(&items)
+        }
+    }
+}
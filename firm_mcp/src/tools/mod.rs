@@ -8,34 +8,68 @@
 //! The server.rs file contains thin wrappers that handle MCP protocol concerns
 //! and delegate to these modules for the actual work.
 
+pub mod add_entities;
 pub mod add_entity;
+pub mod add_schema;
 pub mod build;
+pub mod check_references;
+pub mod delete_entity;
 pub mod delete_source;
 pub mod dsl_reference;
 mod dsl_reference_content;
 pub mod find_source;
 pub mod get;
+pub mod get_many;
 pub mod list;
+pub mod list_changes;
+pub mod list_todos;
+pub mod move_entity;
+pub mod orphans;
 pub mod query;
 pub mod read_source;
 pub mod related;
 pub mod replace_source;
+pub mod run_query;
+pub mod response_format;
+pub mod response_limits;
+pub mod search;
 pub mod search_source;
 pub mod source_tree;
+pub mod stats;
+pub mod undo_last_change;
+pub mod update_entity;
+pub mod validate;
 pub mod write_source;
 
 // Re-export param structs for convenience
+pub use add_entities::AddEntitiesParams;
 pub use add_entity::AddEntityParams;
+pub use add_schema::AddSchemaParams;
 pub use build::BuildParams;
+pub use check_references::CheckReferencesParams;
+pub use delete_entity::DeleteEntityParams;
 pub use delete_source::DeleteSourceParams;
 pub use dsl_reference::DslReferenceParams;
 pub use find_source::FindSourceParams;
 pub use get::GetParams;
+pub use get_many::GetManyParams;
 pub use list::ListParams;
+pub use list_changes::ListChangesParams;
+pub use list_todos::ListTodosParams;
+pub use move_entity::MoveEntityParams;
+pub use orphans::OrphansParams;
 pub use query::QueryParams;
 pub use read_source::ReadSourceParams;
 pub use related::RelatedParams;
 pub use replace_source::ReplaceSourceParams;
+pub use run_query::RunQueryParams;
+pub use response_format::ResponseFormat;
+pub use response_limits::ResponseLimits;
+pub use search::SearchParams;
 pub use search_source::SearchSourceParams;
 pub use source_tree::SourceTreeParams;
+pub use stats::StatsParams;
+pub use undo_last_change::UndoLastChangeParams;
+pub use update_entity::UpdateEntityParams;
+pub use validate::ValidateParams;
 pub use write_source::WriteSourceParams;
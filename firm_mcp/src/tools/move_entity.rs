@@ -0,0 +1,336 @@
+//! Move entity tool implementation.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use firm_core::{EntityId, compose_entity_id};
+use firm_lang::parser::dsl::parse_source;
+use firm_lang::workspace::Workspace;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use super::delete_entity::block_removal_range;
+use super::update_entity::ChangedFile;
+use crate::resources;
+
+/// Parameters for the move_entity tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct MoveEntityParams {
+    /// Entity type (e.g., "person", "task").
+    pub r#type: String,
+
+    /// Entity ID (e.g., "john_doe", "fix_bug").
+    pub id: String,
+
+    /// Destination file path relative to workspace root (e.g., "people/team.firm").
+    /// The file will be created if it doesn't exist.
+    pub to_file: String,
+}
+
+/// Result of moving an entity (before validation).
+#[derive(Debug)]
+pub struct MoveEntityResult {
+    /// The composite ID of the moved entity.
+    pub entity_id: EntityId,
+    /// The files the entity's blocks were taken from (relative to workspace root).
+    pub from_paths: Vec<String>,
+    /// The file the blocks were appended to (relative to workspace root).
+    pub to_path: String,
+    /// Whether the destination file was created.
+    pub created_new_file: bool,
+    /// The files that were written, with their original content.
+    /// A created destination file is left out, since rolling back removes it.
+    pub changed_files: Vec<ChangedFile>,
+}
+
+/// Execute the move_entity tool.
+///
+/// Takes every block of the entity out of its source files, together with the
+/// comments right above each block, and appends them to the destination file.
+/// The changed files are written, and the caller should rebuild the workspace
+/// and roll back if the rebuild fails.
+pub fn execute(
+    workspace_path: &Path,
+    workspace: &Workspace,
+    params: &MoveEntityParams,
+) -> Result<MoveEntityResult, String> {
+    let entity_type = params.r#type.as_str();
+    let entity_id = EntityId::new(&params.id);
+    let composite_id = compose_entity_id(entity_type, entity_id.as_str());
+
+    let to_absolute = resources::source_file_path(workspace_path, &params.to_file)?;
+    let to_path = resources::to_relative_path(workspace_path, &to_absolute)
+        .unwrap_or_else(|| params.to_file.clone());
+
+    let sources = workspace.find_entity_sources(entity_type, entity_id.as_str());
+    if sources.is_empty() {
+        return Err(format!("Entity '{}' not found", composite_id));
+    }
+
+    // Take the blocks out of their files, keyed by relative path
+    let mut contents: BTreeMap<String, (String, String)> = BTreeMap::new();
+    let mut blocks = Vec::new();
+    for source in &sources {
+        let path = resources::to_relative_path(workspace_path, &source.path)
+            .unwrap_or_else(|| source.path.to_string_lossy().into_owned());
+        if contents.contains_key(&path) {
+            continue;
+        }
+        let content =
+            fs::read_to_string(&source.path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let (new_content, file_blocks) = take_blocks(&content, entity_type, entity_id.as_str())?;
+        blocks.extend(file_blocks);
+        contents.insert(path, (content, new_content));
+    }
+    if blocks.is_empty() {
+        return Err(format!("Entity '{}' not found", composite_id));
+    }
+    let from_paths: Vec<String> = contents.keys().cloned().collect();
+    if from_paths == [to_path.as_str()] {
+        return Err(format!(
+            "Entity '{}' is already in {}",
+            composite_id, to_path
+        ));
+    }
+
+    // Append the blocks to the destination, which may also be one of the sources
+    let created_new_file = !to_absolute.exists();
+    let (original, destination) = match contents.remove(&to_path) {
+        Some(contents) => contents,
+        None if created_new_file => (String::new(), String::new()),
+        None => {
+            let content = fs::read_to_string(&to_absolute)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            (content.clone(), content)
+        }
+    };
+    let destination = append_blocks(&destination, &blocks);
+    contents.insert(to_path.clone(), (original, destination));
+
+    // Check the syntax of every file before writing anything
+    for (path, (_, new_content)) in &contents {
+        let parsed = parse_source(new_content.clone(), None)
+            .map_err(|e| format!("Failed to parse DSL: {}", e))?;
+        if parsed.has_error() {
+            return Err(format!("The move would leave {} with invalid syntax", path));
+        }
+    }
+
+    if let Some(parent) = to_absolute.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let mut changed_files = Vec::new();
+    for (path, (content, new_content)) in contents {
+        if content == new_content {
+            continue;
+        }
+        fs::write(workspace_path.join(&path), new_content)
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        if path != to_path || !created_new_file {
+            changed_files.push(ChangedFile {
+                path,
+                original_content: content,
+            });
+        }
+    }
+
+    Ok(MoveEntityResult {
+        entity_id: composite_id,
+        from_paths,
+        to_path,
+        created_new_file,
+        changed_files,
+    })
+}
+
+/// Takes the blocks of an entity out of a file's content.
+///
+/// Returns the content without the blocks, and the text of each block
+/// with its leading comments, in file order.
+fn take_blocks(
+    content: &str,
+    entity_type: &str,
+    entity_id: &str,
+) -> Result<(String, Vec<String>), String> {
+    let parsed = parse_source(content.to_string(), None)
+        .map_err(|e| format!("Failed to parse DSL: {}", e))?;
+
+    let spans: Vec<Range<usize>> = parsed
+        .entities()
+        .iter()
+        .filter(|entity| {
+            entity.entity_type() == Some(entity_type) && entity.id() == Some(entity_id)
+        })
+        .map(|entity| block_span(content, entity.range().start_byte..entity.range().end_byte))
+        .collect();
+
+    let blocks = spans
+        .iter()
+        .map(|span| content[span.clone()].to_string())
+        .collect();
+
+    let mut result = content.to_string();
+    for span in spans.iter().rev() {
+        result.replace_range(block_removal_range(content, span), "");
+    }
+    Ok((result, blocks))
+}
+
+/// Gets the span of a block together with the comments attached to it.
+///
+/// Comments are attached when they're on the lines right above the block,
+/// with nothing else on their lines and no blank line in between.
+fn block_span(content: &str, block: Range<usize>) -> Range<usize> {
+    let line_start = content[..block.start].rfind('\n').map_or(0, |i| i + 1);
+    if !content[line_start..block.start].trim().is_empty() {
+        // The block shares its line with other content
+        return block;
+    }
+
+    let mut start = line_start;
+    while start > 0 {
+        let line_start = content[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+        let line = content[line_start..start].trim();
+        if line.starts_with("//") {
+            start = line_start;
+        } else if line.ends_with("*/") {
+            // A block comment, which may start lines above
+            match comment_start(content, line_start) {
+                Some(comment_start) => start = comment_start,
+                None => break,
+            }
+        } else {
+            break;
+        }
+    }
+
+    // Keep the block's indentation when there are no comments
+    if start == line_start {
+        block
+    } else {
+        start..block.end
+    }
+}
+
+/// Finds the start of the line opening the block comment that ends on the line at `line_end_start`.
+///
+/// Returns `None` if the comment shares its first line with other content.
+fn comment_start(content: &str, line_end_start: usize) -> Option<usize> {
+    let end = line_end_start + content[line_end_start..].find("*/")?;
+    let open = content[..end].rfind("/*")?;
+    let line_start = content[..open].rfind('\n').map_or(0, |i| i + 1);
+    content[line_start..open]
+        .trim()
+        .is_empty()
+        .then_some(line_start)
+}
+
+/// Appends blocks to a file's content, separated by blank lines.
+fn append_blocks(content: &str, blocks: &[String]) -> String {
+    let mut result = content.trim_end().to_string();
+    for block in blocks {
+        if !result.is_empty() {
+            result.push_str("\n\n");
+        }
+        result.push_str(block.trim_end());
+    }
+    result.push('\n');
+    result
+}
+
+/// Restore the files an entity was moved between.
+///
+/// A destination file created by the move is removed.
+pub fn rollback(workspace_path: &Path, result: &MoveEntityResult) -> bool {
+    let restored = super::update_entity::rollback(workspace_path, &result.changed_files);
+    let removed =
+        !result.created_new_file || fs::remove_file(workspace_path.join(&result.to_path)).is_ok();
+    restored && removed
+}
+
+/// Create a success result for move_entity.
+pub fn success_result(result: &MoveEntityResult) -> CallToolResult {
+    let action = if result.created_new_file {
+        "new file"
+    } else {
+        "existing file"
+    };
+    CallToolResult::success(vec![Content::text(format!(
+        "Moved '{}' from {} to {} ({}). Workspace is valid.",
+        result.entity_id,
+        result.from_paths.join(", "),
+        result.to_path,
+        action
+    ))])
+}
+
+/// Create an error result for move_entity when validation fails and rollback occurred.
+pub fn validation_error_result(
+    error: &str,
+    result: &MoveEntityResult,
+    rollback_success: bool,
+) -> CallToolResult {
+    // A created destination file is restored by removing it
+    let file_count = result.changed_files.len() + usize::from(result.created_new_file);
+    let rollback_msg = match (rollback_success, file_count) {
+        (true, 1) => "The file has been restored.".to_string(),
+        (true, count) => format!("All {} files have been restored.", count),
+        (false, _) => "Warning: Failed to restore the files.".to_string(),
+    };
+
+    CallToolResult::error(vec![Content::text(format!(
+        "Moving the entity would break the workspace: {}. {}",
+        error, rollback_msg
+    ))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span_text(content: &str, block: &str) -> String {
+        let start = content.find(block).unwrap();
+        content[block_span(content, start..start + block.len())].to_string()
+    }
+
+    #[test]
+    fn test_block_span_takes_leading_comments() {
+        let content = "a {\n}\n\n// Not attached\n\n// The lead\n  // and more\nb {\n}\n";
+        assert_eq!(
+            span_text(content, "b {\n}"),
+            "// The lead\n  // and more\nb {\n}"
+        );
+    }
+
+    #[test]
+    fn test_block_span_takes_block_comments() {
+        let content = "a {\n} /* trailing */\n/*\n * About b\n */\nb {\n}\n";
+        assert_eq!(span_text(content, "b {\n}"), "/*\n * About b\n */\nb {\n}");
+
+        // A comment that starts after other content stays where it is
+        let content = "a {\n} /* trailing\n */\nb {\n}\n";
+        assert_eq!(span_text(content, "b {\n}"), "b {\n}");
+    }
+
+    #[test]
+    fn test_block_span_skips_other_content() {
+        // The trailing comment of the block above isn't attached
+        let content = "a {\n} // about a\nb {\n}\n";
+        assert_eq!(span_text(content, "b {\n}"), "b {\n}");
+
+        let content = "a {\n}\n\nb {\n}\n";
+        assert_eq!(span_text(content, "b {\n}"), "b {\n}");
+    }
+
+    #[test]
+    fn test_append_blocks() {
+        let blocks = vec!["// Lead\nb {\n}".to_string()];
+        assert_eq!(append_blocks("", &blocks), "// Lead\nb {\n}\n");
+        assert_eq!(
+            append_blocks("a {\n}\n\n", &blocks),
+            "a {\n}\n\n// Lead\nb {\n}\n"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Orphans tool implementation.
+
+use firm_core::graph::EntityGraph;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+/// Parameters for the orphans tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct OrphansParams {
+    /// Optional entity type to limit the results to (e.g., "task").
+    #[serde(default)]
+    pub r#type: Option<String>,
+}
+
+/// Execute the orphans tool.
+///
+/// Returns the IDs of entities that neither reference nor are referenced by
+/// other entities, like list does.
+pub fn execute(graph: &EntityGraph, params: &OrphansParams) -> CallToolResult {
+    let ids: Vec<&str> = graph
+        .orphans()
+        .into_iter()
+        .filter(|e| {
+            params
+                .r#type
+                .as_ref()
+                .is_none_or(|entity_type| e.entity_type.as_str() == entity_type)
+        })
+        .map(|e| e.id.as_str())
+        .collect();
+
+    if ids.is_empty() {
+        return CallToolResult::success(vec![Content::text(
+            "No disconnected entities found.".to_string(),
+        )]);
+    }
+
+    CallToolResult::success(vec![Content::text(ids.join("\n"))])
+}
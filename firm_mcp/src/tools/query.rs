@@ -1,10 +1,18 @@
 //! Query tool implementation.
 
 use firm_core::graph::{EntityGraph, Query, QueryResult};
+use firm_core::{Entity, EntitySchema, ValueFormat};
 use firm_lang::parser::query::parse_query;
+use firm_lang::workspace::TimezoneConfig;
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
 
+use super::response_format::{ResponseFormat, json_result};
+use super::response_limits::{ResponseLimits, truncation_marker};
+
+/// Separates entities in text responses.
+const TEXT_SEPARATOR: &str = "\n---\n";
+
 /// Parameters for the query tool.
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct QueryParams {
@@ -13,12 +21,38 @@ pub struct QueryParams {
     /// - "from task | where is_completed == false" (incomplete tasks)
     /// - "from person | where name contains 'John' | limit 5"
     pub query: String,
+
+    /// If true, reject queries that reference fields not declared in the
+    /// schema of the queried entity type. Default: false.
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Response format: "text" (default) for readable entities, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+
+    /// If true, return every matching entity even past the server's response
+    /// size limits. Default: false.
+    #[serde(default)]
+    pub unlimited: bool,
 }
 
 /// Execute the query tool.
 ///
 /// Parses and executes a Firm query, returning full details for all matching entities.
-pub fn execute(graph: &EntityGraph, params: &QueryParams) -> CallToolResult {
+/// In strict mode, field references are first validated against the schemas.
+/// Date-only conditions are checked in the configured default timezone, if any.
+/// Text output shows values in the given format; JSON output keeps them raw.
+/// In JSON mode, entities are returned as an array and aggregations as their values.
+/// Entities beyond the response limits are left out, and a marker with the total follows.
+pub fn execute(
+    graph: &EntityGraph,
+    schemas: &[EntitySchema],
+    timezone: &TimezoneConfig,
+    value_format: &ValueFormat,
+    limits: &ResponseLimits,
+    params: &QueryParams,
+) -> CallToolResult {
     // Parse the query
     let parsed_query = match parse_query(&params.query) {
         Ok(q) => q,
@@ -31,7 +65,7 @@ pub fn execute(graph: &EntityGraph, params: &QueryParams) -> CallToolResult {
     };
 
     // Convert to executable query
-    let query: Query = match parsed_query.try_into() {
+    let mut query: Query = match parsed_query.try_into() {
         Ok(q) => q,
         Err(e) => {
             return CallToolResult::error(vec![Content::text(format!(
@@ -41,6 +75,20 @@ pub fn execute(graph: &EntityGraph, params: &QueryParams) -> CallToolResult {
         }
     };
 
+    if let Some(offset) = timezone.offset() {
+        query = query.with_timezone(offset);
+    }
+
+    // Validate field references against schemas in strict mode
+    if params.strict
+        && let Err(e) = query.validate(schemas)
+    {
+        return CallToolResult::error(vec![Content::text(format!(
+            "Query validation failed: {}",
+            e
+        ))]);
+    }
+
     // Execute the query
     let result = match query.execute(graph) {
         Ok(r) => r,
@@ -55,16 +103,62 @@ pub fn execute(graph: &EntityGraph, params: &QueryParams) -> CallToolResult {
     // Format results
     match result {
         QueryResult::Entities(entities) => {
-            if entities.is_empty() {
-                return CallToolResult::success(vec![Content::text(
-                    "No entities found matching the query.",
-                )]);
+            let render_offset = timezone.render_offset();
+            let rendered: Vec<_> = entities
+                .iter()
+                .map(|e| match &render_offset {
+                    Some(offset) => e.in_timezone(offset),
+                    None => (*e).clone(),
+                })
+                .collect();
+
+            let limits = limits.for_call(params.unlimited);
+            match params.format {
+                ResponseFormat::Text if rendered.is_empty() => {
+                    CallToolResult::success(vec![Content::text(
+                        "No entities found matching the query.",
+                    )])
+                }
+                ResponseFormat::Text => {
+                    let output: Vec<String> =
+                        rendered.iter().map(|e| value_format.entity(e)).collect();
+                    let shown = limits.fit(
+                        output
+                            .iter()
+                            .map(|entity| entity.len() + TEXT_SEPARATOR.len()),
+                    );
+                    let mut text = output[..shown].join(TEXT_SEPARATOR);
+                    if shown < output.len() {
+                        text.push_str("\n\n");
+                        text.push_str(&marker(shown, output.len()));
+                    }
+                    CallToolResult::success(vec![Content::text(text)])
+                }
+                ResponseFormat::Json => {
+                    let mut json: Vec<_> = rendered.iter().map(Entity::to_json).collect();
+                    let total = json.len();
+                    json.truncate(limits.fit(json.iter().map(|entity| entity.to_string().len())));
+                    let mut result = json_result(&json);
+                    if json.len() < total {
+                        // A separate item, so the JSON stays parseable
+                        result
+                            .content
+                            .push(Content::text(marker(json.len(), total)));
+                    }
+                    result
+                }
             }
-            let output: Vec<String> = entities.iter().map(|e| e.to_string()).collect();
-            CallToolResult::success(vec![Content::text(output.join("\n---\n"))])
-        }
-        QueryResult::Aggregation(agg_result) => {
-            CallToolResult::success(vec![Content::text(agg_result.to_string())])
         }
+        QueryResult::Aggregation(agg_result) => match params.format {
+            ResponseFormat::Text => {
+                CallToolResult::success(vec![Content::text(value_format.aggregation(&agg_result))])
+            }
+            ResponseFormat::Json => json_result(&agg_result),
+        },
     }
 }
+
+/// Creates the marker for a truncated list of matching entities.
+fn marker(shown: usize, total: usize) -> String {
+    truncation_marker(shown, total, "entities", "refine with limit/where")
+}
@@ -2,6 +2,7 @@
 
 use std::path::Path;
 
+use firm_lang::workspace::content_hash;
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
 
@@ -13,13 +14,26 @@ pub struct ReadSourceParams {
     /// Relative path to the .firm file (e.g., "schemas/person.firm", "core/main.firm").
     /// Use 'find_source' to locate the file path for a specific entity or schema.
     pub path: String,
+
+    /// If true, the content is followed by its hash, to pass as 'base_hash' to 'write_source'
+    /// or 'replace_source' so they refuse to overwrite changes made since. Default: false.
+    #[serde(default)]
+    pub include_hash: bool,
 }
 
 /// Execute the read_source tool.
 ///
-/// Returns the raw DSL content of the specified .firm file.
+/// Returns the raw DSL content of the specified .firm file, and its content
+/// hash as a second item when asked for.
 pub fn execute(workspace_path: &Path, params: &ReadSourceParams) -> CallToolResult {
     match resources::read_source_file(workspace_path, &params.path) {
+        Ok(contents) if params.include_hash => {
+            let hash = content_hash(&contents);
+            CallToolResult::success(vec![
+                Content::text(contents),
+                Content::text(format!("base_hash: {}", hash)),
+            ])
+        }
         Ok(contents) => CallToolResult::success(vec![Content::text(contents)]),
         Err(e) => CallToolResult::error(vec![Content::text(e)]),
     }
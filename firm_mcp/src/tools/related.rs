@@ -1,10 +1,15 @@
 //! Related tool implementation.
 
-use firm_core::compose_entity_id;
-use firm_core::graph::{Direction, EntityGraph};
+use firm_core::graph::{
+    Direction, EdgeDirection, EntityGraph, EntitySelector, RelatedEdge, get_related_with_edges,
+};
+use firm_core::{Entity, EntityType, FieldId, FieldValue, compose_entity_id, decompose_entity_id};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
 
+use super::get::{Candidates, entity_not_found_message};
+use super::response_limits::{ResponseLimits, truncation_marker};
+
 /// Parameters for the related tool.
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct RelatedParams {
@@ -20,6 +25,25 @@ pub struct RelatedParams {
     /// - omit or null: both directions
     #[serde(default)]
     pub direction: Option<RelatedDirection>,
+
+    /// Number of relationship hops to follow (default 1, max 5).
+    #[serde(default)]
+    pub degrees: Option<usize>,
+
+    /// Only return related entities of this type (e.g., "task").
+    /// Entities of other types are still followed to reach them.
+    #[serde(default)]
+    pub related_type: Option<String>,
+
+    /// Also return how each entity is connected, as (entity, via_field, from_entity)
+    /// triples with the entity holding the referencing field.
+    #[serde(default)]
+    pub include_edges: bool,
+
+    /// If true, return every related entity even past the server's response
+    /// size limits. Default: false.
+    #[serde(default)]
+    pub unlimited: bool,
 }
 
 /// Direction for related entity lookup.
@@ -41,31 +65,119 @@ impl From<RelatedDirection> for Direction {
 
 /// Execute the related tool.
 ///
-/// Returns IDs of entities related to the specified entity.
-pub fn execute(graph: &EntityGraph, params: &RelatedParams) -> CallToolResult {
+/// Returns a summary (ID, type and name) of each entity related to the
+/// specified entity, optionally followed by the edges that connect them.
+/// Entities beyond the response limits are left out along with their edges.
+pub fn execute(
+    graph: &EntityGraph,
+    limits: &ResponseLimits,
+    params: &RelatedParams,
+) -> CallToolResult {
     let id = compose_entity_id(&params.r#type, &params.id);
 
-    match graph.get_related(&id, params.direction.clone().map(|d| d.into())) {
-        Some(entities) => {
-            if entities.is_empty() {
-                let direction_text = match &params.direction {
-                    Some(RelatedDirection::Incoming) => " (incoming)",
-                    Some(RelatedDirection::Outgoing) => " (outgoing)",
-                    None => "",
-                };
-                return CallToolResult::success(vec![Content::text(format!(
-                    "No related entities found{}.",
-                    direction_text
-                ))]);
-            }
-
-            // Return just the IDs, like list does
-            let ids: Vec<&str> = entities.iter().map(|e| e.id.as_str()).collect();
-            CallToolResult::success(vec![Content::text(ids.join("\n"))])
-        }
-        None => CallToolResult::error(vec![Content::text(format!(
-            "Entity '{}' with type '{}' not found. Use list with type='{}' to see available IDs.",
-            params.id, params.r#type, params.r#type
-        ))]),
+    let Some(entity) = graph.get_entity(&id) else {
+        let entity_type = EntityType::new(&params.r#type);
+        let ids = graph
+            .list_by_type(&entity_type)
+            .into_iter()
+            .map(|e| decompose_entity_id(e.id.as_str()).1);
+        return CallToolResult::error(vec![Content::text(entity_not_found_message(
+            &params.r#type,
+            &params.id,
+            ids,
+            Candidates::Similar,
+        ))]);
+    };
+
+    let related_types: Vec<EntitySelector> = params
+        .related_type
+        .as_deref()
+        .map(|related_type| EntitySelector::Type(EntityType::new(related_type)))
+        .into_iter()
+        .collect();
+    let related = get_related_with_edges(
+        graph,
+        vec![entity],
+        params.degrees.unwrap_or(1),
+        params.direction.clone().map(|d| d.into()),
+        &related_types,
+    );
+    let entities: Vec<&Entity> = related
+        .entities
+        .iter()
+        .copied()
+        .filter(|e| e.id != id)
+        .collect();
+    let summaries: Vec<String> = entities.iter().map(|e| summarize(e)).collect();
+
+    if summaries.is_empty() {
+        let direction_text = match &params.direction {
+            Some(RelatedDirection::Incoming) => " (incoming)",
+            Some(RelatedDirection::Outgoing) => " (outgoing)",
+            None => "",
+        };
+        let type_text = match &params.related_type {
+            Some(related_type) => format!(" of type '{}'", related_type),
+            None => String::new(),
+        };
+        return CallToolResult::success(vec![Content::text(format!(
+            "No related entities{} found{}.",
+            type_text, direction_text
+        ))]);
     }
+
+    let limits = limits.for_call(params.unlimited);
+    let shown = limits.fit(summaries.iter().map(|summary| summary.len() + 1));
+    let mut text = summaries[..shown].join("\n");
+    if params.include_edges {
+        let hidden_ids: Vec<_> = entities[shown..].iter().map(|e| &e.id).collect();
+        let edges: Vec<String> = related
+            .edges
+            .iter()
+            .filter(|edge| !hidden_ids.contains(&&edge.entity))
+            .map(describe_edge)
+            .collect();
+        text.push_str(&format!(
+            "\n\nEdges (entity, via_field, from_entity):\n{}",
+            edges.join("\n")
+        ));
+    }
+    if shown < summaries.len() {
+        text.push_str("\n\n");
+        text.push_str(&truncation_marker(
+            shown,
+            summaries.len(),
+            "entities",
+            "refine with related_type/direction/degrees",
+        ));
+    }
+    CallToolResult::success(vec![Content::text(text)])
+}
+
+/// Summarizes an entity as its ID and type, followed by its name if it has one.
+pub(crate) fn summarize(entity: &Entity) -> String {
+    match entity_name(entity) {
+        Some(name) => format!("{} ({}): {}", entity.id, entity.entity_type, name),
+        None => format!("{} ({})", entity.id, entity.entity_type),
+    }
+}
+
+/// Gets the name of an entity, if it has one.
+pub(crate) fn entity_name(entity: &Entity) -> Option<&str> {
+    match entity.get_field(&FieldId::new("name")) {
+        Some(FieldValue::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+/// Describes an edge as its triple, followed by the reference it follows.
+fn describe_edge(edge: &RelatedEdge) -> String {
+    let (holder, target) = match edge.direction {
+        EdgeDirection::Outgoing => (&edge.from_entity, &edge.entity),
+        EdgeDirection::Incoming => (&edge.entity, &edge.from_entity),
+    };
+    format!(
+        "({}, {}, {}): {}.{} -> {}",
+        edge.entity, edge.via_field, edge.from_entity, holder, edge.via_field, target
+    )
 }
@@ -5,6 +5,7 @@ use std::path::Path;
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
 
+use super::write_source;
 use crate::resources;
 
 /// Parameters for the replace_source tool.
@@ -31,6 +32,17 @@ pub struct ReplaceSourceParams {
     /// The file must still have valid syntax. Default: false.
     #[serde(default)]
     pub force: bool,
+
+    /// If true, only preview the replacement: the result is validated against the
+    /// workspace without writing anything, and a unified diff is returned. Default: false.
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// The hash 'read_source' returned with 'include_hash: true' for the content the
+    /// replacement was chosen from. If the file changed on disk since, nothing is replaced
+    /// and a conflict error is returned. Leave out to replace regardless.
+    #[serde(default)]
+    pub base_hash: Option<String>,
 }
 
 /// Result of the replacement operation (before validation).
@@ -59,6 +71,8 @@ pub fn execute(
         )]));
     }
 
+    write_source::check_base_hash(workspace_path, &params.path, params.base_hash.as_deref())?;
+
     // Read current file content
     let content = resources::read_source_file(workspace_path, &params.path)
         .map_err(|e| CallToolResult::error(vec![Content::text(e)]))?;
@@ -0,0 +1,120 @@
+//! Output format shared by tools that can return structured data.
+//!
+//! In JSON mode, entities are `{"id", "type", "fields"}` objects with field
+//! values in their canonical JSON encoding (see `Entity::to_json`), and schemas
+//! list their field definitions in declaration order.
+
+use firm_core::EntitySchema;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::{Value, json};
+
+/// How a tool should format its response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    /// Human-readable text (default).
+    #[default]
+    Text,
+    /// Structured JSON for programmatic use.
+    Json,
+}
+
+/// Create a success result containing pretty-printed JSON.
+pub fn json_result<T: serde::Serialize>(data: &T) -> CallToolResult {
+    match serde_json::to_string_pretty(data) {
+        Ok(json) => CallToolResult::success(vec![Content::text(json)]),
+        Err(e) => CallToolResult::error(vec![Content::text(format!(
+            "Failed to serialize result as JSON: {}",
+            e
+        ))]),
+    }
+}
+
+/// Convert a schema to a JSON structure with its fields in declaration order.
+///
+/// Computed fields follow the others, with their expression as written.
+pub fn schema_to_json(schema: &EntitySchema) -> Value {
+    let mut fields: Vec<Value> = schema
+        .ordered_fields()
+        .into_iter()
+        .map(|(field_id, field_schema)| {
+            let mut field = json!({
+                "name": field_id.as_str(),
+                "type": field_schema.expected_type().to_string().to_lowercase(),
+                "required": field_schema.is_required(),
+            });
+            if let Some(item_type) = field_schema.item_type() {
+                field["item_type"] = json!(item_type.to_string().to_lowercase());
+            }
+            if let Some(allowed_values) = field_schema.allowed_values() {
+                field["allowed_values"] = json!(allowed_values);
+                if field_schema.case_sensitive {
+                    field["case_sensitive"] = json!(true);
+                }
+            }
+            if field_schema.extra_precision {
+                field["extra_precision"] = json!(true);
+            }
+            if let Some(format) = field_schema.format() {
+                field["format"] = json!(format);
+            }
+            field
+        })
+        .collect();
+    fields.extend(
+        schema
+            .ordered_computed_fields()
+            .into_iter()
+            .map(|(field_id, computed)| {
+                json!({
+                    "name": field_id.as_str(),
+                    "computed": computed.source,
+                })
+            }),
+    );
+
+    json!({
+        "name": schema.entity_type.as_str(),
+        "fields": fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use firm_core::expression::Operator;
+    use firm_core::{EntityType, Expression, FieldId, FieldType};
+
+    use super::*;
+
+    #[test]
+    fn test_schema_to_json_includes_computed_fields() {
+        let budget = || Expression::Field(FieldId::new("budget"));
+        let schema = EntitySchema::new(EntityType::new("project"))
+            .with_required_field(FieldId::new("name"), FieldType::String)
+            .with_computed_field(
+                FieldId::new("monthly"),
+                Expression::binary(Operator::Divide, budget(), Expression::Integer(12)),
+                "budget / 12",
+            )
+            .with_optional_field(FieldId::new("budget"), FieldType::Currency)
+            .with_computed_field(
+                FieldId::new("with_tax"),
+                Expression::binary(Operator::Multiply, budget(), Expression::Float(1.25)),
+                "budget * 1.25",
+            );
+
+        assert_eq!(
+            schema_to_json(&schema),
+            json!({
+                "name": "project",
+                "fields": [
+                    { "name": "name", "type": "string", "required": true },
+                    { "name": "budget", "type": "currency", "required": false },
+                    { "name": "monthly", "computed": "budget / 12" },
+                    { "name": "with_tax", "computed": "budget * 1.25" },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Limits on how much the tools that return entities put in one response.
+//!
+//! A query over hundreds of entities would otherwise fill the client's context.
+//! Responses are cut at whole entries in the order the tool produced them, so
+//! the same call always shows the same entries, and end with a marker saying
+//! how many were shown out of the total. Tools take an `unlimited` parameter
+//! for when everything is deliberately wanted.
+
+/// Default maximum number of entities in a response.
+pub const DEFAULT_MAX_RESPONSE_ENTITIES: usize = 100;
+
+/// Default maximum size of a response, in bytes.
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 100_000;
+
+/// How many entries, and how many bytes of them, a response may hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseLimits {
+    /// Maximum number of entries, or `None` for no limit.
+    pub max_entities: Option<usize>,
+    /// Maximum total size of the entries in bytes, or `None` for no limit.
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_entities: Some(DEFAULT_MAX_RESPONSE_ENTITIES),
+            max_bytes: Some(DEFAULT_MAX_RESPONSE_BYTES),
+        }
+    }
+}
+
+impl ResponseLimits {
+    /// Limits that let every entry through.
+    pub fn unlimited() -> Self {
+        Self {
+            max_entities: None,
+            max_bytes: None,
+        }
+    }
+
+    /// The limits for a single call, lifted entirely when it asks for everything.
+    pub fn for_call(self, unlimited: bool) -> Self {
+        if unlimited { Self::unlimited() } else { self }
+    }
+
+    /// Counts how many of the leading entries fit, given the size of each in bytes.
+    ///
+    /// The first entry is always kept, so a single large entity is still shown.
+    pub fn fit(&self, sizes: impl IntoIterator<Item = usize>) -> usize {
+        let mut count = 0;
+        let mut bytes = 0;
+        for size in sizes {
+            if self.max_entities.is_some_and(|max| count >= max) {
+                break;
+            }
+            bytes += size;
+            if count > 0 && self.max_bytes.is_some_and(|max| bytes > max) {
+                break;
+            }
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Creates the marker that ends a truncated response.
+///
+/// `hint` says how to narrow the request, e.g. "refine with limit/where".
+pub fn truncation_marker(shown: usize, total: usize, noun: &str, hint: &str) -> String {
+    format!(
+        "[truncated: {} of {} {} shown — {}, or set unlimited=true to get everything]",
+        shown, total, noun, hint
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_stops_at_entity_limit() {
+        let limits = ResponseLimits {
+            max_entities: Some(2),
+            max_bytes: None,
+        };
+
+        assert_eq!(limits.fit([10, 10, 10]), 2);
+        assert_eq!(limits.fit([10]), 1);
+    }
+
+    #[test]
+    fn test_fit_stops_at_byte_limit() {
+        let limits = ResponseLimits {
+            max_entities: None,
+            max_bytes: Some(25),
+        };
+
+        assert_eq!(limits.fit([10, 10, 10]), 2);
+        // The first entry is kept even when it's too large on its own
+        assert_eq!(limits.fit([100, 10]), 1);
+    }
+
+    #[test]
+    fn test_unlimited_fits_everything() {
+        let limits = ResponseLimits::default().for_call(true);
+
+        assert_eq!(limits, ResponseLimits::unlimited());
+        assert_eq!(limits.fit(vec![1_000_000; 500]), 500);
+    }
+
+    #[test]
+    fn test_truncation_marker() {
+        assert_eq!(
+            truncation_marker(180, 312, "entities", "refine with limit/where"),
+            "[truncated: 180 of 312 entities shown — refine with limit/where, \
+             or set unlimited=true to get everything]"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Run query tool implementation.
+
+use std::path::Path;
+
+use firm_core::ValueFormat;
+use firm_core::graph::EntityGraph;
+use firm_lang::workspace::{NamedQuery, TimezoneConfig, WorkspaceBuild};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::{Value, json};
+
+use super::query::{self, QueryParams};
+use super::response_format::{ResponseFormat, json_result};
+use super::response_limits::ResponseLimits;
+use crate::resources;
+
+/// Parameters for the run_query tool.
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunQueryParams {
+    /// Name of the saved query to run (e.g., "open_tasks").
+    /// Leave out to list the saved queries with their descriptions instead.
+    pub name: Option<String>,
+
+    /// If true, reject the query if it references fields not declared in the
+    /// schema of the queried entity type. Default: false.
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Response format: "text" (default) for readable output, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+
+    /// If true, return every matching entity even past the server's response
+    /// size limits. Default: false.
+    #[serde(default)]
+    pub unlimited: bool,
+}
+
+/// Execute the run_query tool.
+///
+/// Runs a named query saved in the workspace like the query tool would, or
+/// lists the saved queries when no name is given.
+pub fn execute(
+    graph: &EntityGraph,
+    build: &WorkspaceBuild,
+    timezone: &TimezoneConfig,
+    value_format: &ValueFormat,
+    workspace_path: &Path,
+    limits: &ResponseLimits,
+    params: &RunQueryParams,
+) -> CallToolResult {
+    let Some(name) = &params.name else {
+        return list_result(&build.queries, workspace_path, params.format);
+    };
+
+    let Some(named_query) = build.named_query(name) else {
+        return CallToolResult::error(vec![Content::text(not_found_message(name, &build.queries))]);
+    };
+
+    let query_params = QueryParams {
+        query: named_query.query.clone(),
+        strict: params.strict,
+        format: params.format,
+        unlimited: params.unlimited,
+    };
+    query::execute(
+        graph,
+        &build.schemas,
+        timezone,
+        value_format,
+        limits,
+        &query_params,
+    )
+}
+
+/// Lists the saved queries, with where each is defined.
+fn list_result(
+    queries: &[NamedQuery],
+    workspace_path: &Path,
+    format: ResponseFormat,
+) -> CallToolResult {
+    let location = |query: &NamedQuery| {
+        let path = resources::to_relative_path(workspace_path, &query.path)
+            .unwrap_or_else(|| query.path.display().to_string());
+        (path, query.line)
+    };
+
+    match format {
+        ResponseFormat::Text if queries.is_empty() => CallToolResult::success(vec![Content::text(
+            "No saved queries found. Define one with a 'query <name> { query = \"...\" }' block.",
+        )]),
+        ResponseFormat::Text => {
+            let lines: Vec<String> = queries
+                .iter()
+                .map(|query| {
+                    let (path, line) = location(query);
+                    let description = query
+                        .description
+                        .as_ref()
+                        .map(|description| format!(" - {}", description))
+                        .unwrap_or_default();
+                    format!(
+                        "{}{} ({}:{})\n  {}",
+                        query.name,
+                        description,
+                        path,
+                        line,
+                        query.query.trim()
+                    )
+                })
+                .collect();
+            CallToolResult::success(vec![Content::text(lines.join("\n"))])
+        }
+        ResponseFormat::Json => {
+            let queries: Vec<Value> = queries
+                .iter()
+                .map(|query| {
+                    let (path, line) = location(query);
+                    json!({
+                        "name": query.name,
+                        "query": query.query,
+                        "description": query.description,
+                        "path": path,
+                        "line": line,
+                    })
+                })
+                .collect();
+            json_result(&queries)
+        }
+    }
+}
+
+/// Describes a missing query, with the names that do exist.
+pub fn not_found_message(name: &str, queries: &[NamedQuery]) -> String {
+    if queries.is_empty() {
+        return format!("No query named '{}'. There are no saved queries.", name);
+    }
+
+    let names: Vec<&str> = queries.iter().map(|query| query.name.as_str()).collect();
+    format!(
+        "No query named '{}'. Saved queries: {}",
+        name,
+        names.join(", ")
+    )
+}
@@ -0,0 +1,208 @@
+//! Search tool implementation.
+
+use std::fmt::Write;
+
+use firm_core::{Entity, FieldId, FieldValue};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+/// Number of matching entities to return when no limit is given.
+const DEFAULT_LIMIT: usize = 20;
+
+/// Characters of context shown on each side of a match.
+const SNIPPET_CONTEXT: usize = 30;
+
+/// Parameters for the search tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SearchParams {
+    /// Text to search for in entity field values (case-insensitive).
+    pub query: String,
+
+    /// Optional entity type to search (e.g., "organization"). Searches every type if omitted.
+    #[serde(default)]
+    pub r#type: Option<String>,
+
+    /// If true, also search the string items of list fields. Default: false.
+    #[serde(default)]
+    pub include_lists: bool,
+
+    /// Maximum number of matching entities to return (default 20).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// A field whose value contains the search text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMatch {
+    pub field: FieldId,
+    /// The matching part of the value, with some context around it.
+    pub snippet: String,
+}
+
+/// An entity with the fields that contain the search text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityMatch<'a> {
+    pub entity: &'a Entity,
+    pub fields: Vec<FieldMatch>,
+}
+
+/// Finds the entities with string, enum or path fields containing the query, ignoring case.
+///
+/// With `include_lists`, string and enum items of list fields are searched too.
+/// Each entity is visited once. The matches are sorted by entity ID, with
+/// their fields in the entity's order.
+pub fn search_entities<'a>(
+    entities: &'a [Entity],
+    query: &str,
+    entity_type: Option<&str>,
+    include_lists: bool,
+) -> Vec<EntityMatch<'a>> {
+    let needle = query.to_lowercase();
+
+    let mut matches: Vec<EntityMatch> = entities
+        .iter()
+        .filter(|entity| entity_type.is_none_or(|t| entity.entity_type.as_str() == t))
+        .filter_map(|entity| {
+            let fields: Vec<FieldMatch> = entity
+                .fields
+                .iter()
+                .filter_map(|(field_id, value)| {
+                    let snippet = match value {
+                        FieldValue::List(items) if include_lists => {
+                            items.iter().find_map(|item| match_value(item, &needle))
+                        }
+                        value => match_value(value, &needle),
+                    }?;
+                    Some(FieldMatch {
+                        field: field_id.clone(),
+                        snippet,
+                    })
+                })
+                .collect();
+            if fields.is_empty() {
+                return None;
+            }
+            Some(EntityMatch { entity, fields })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| a.entity.id.as_str().cmp(b.entity.id.as_str()));
+    matches
+}
+
+/// Execute the search tool.
+///
+/// Returns the matching entities with a snippet of each matching field.
+pub fn execute(entities: &[Entity], params: &SearchParams) -> CallToolResult {
+    if params.query.is_empty() {
+        return CallToolResult::error(vec![Content::text("Search query cannot be empty.")]);
+    }
+
+    let matches = search_entities(
+        entities,
+        &params.query,
+        params.r#type.as_deref(),
+        params.include_lists,
+    );
+    if matches.is_empty() {
+        return CallToolResult::success(vec![Content::text(format!(
+            "No entities match '{}'.",
+            params.query
+        ))]);
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    let mut output = String::new();
+    for entity_match in matches.iter().take(limit) {
+        writeln!(output, "{}:", entity_match.entity.id).unwrap();
+        for field_match in &entity_match.fields {
+            writeln!(output, "  {}: {}", field_match.field, field_match.snippet).unwrap();
+        }
+        writeln!(output).unwrap();
+    }
+
+    if matches.len() > limit {
+        write!(
+            output,
+            "Showing {} of {} matching entities. Use a higher 'limit' or a 'type' to see more.",
+            limit,
+            matches.len()
+        )
+        .unwrap();
+    }
+
+    CallToolResult::success(vec![Content::text(output.trim_end().to_string())])
+}
+
+/// Gets a snippet of a searchable value if it contains the lowercased needle.
+fn match_value(value: &FieldValue, needle: &str) -> Option<String> {
+    let text = match value {
+        FieldValue::String(text) | FieldValue::Enum(text) => text.clone(),
+        FieldValue::Path(path) => path.display().to_string(),
+        _ => return None,
+    };
+
+    // Lowercasing can change byte lengths, so only slice the original when it doesn't
+    let lowercase = text.to_lowercase();
+    let start = lowercase.find(needle)?;
+    let haystack = if lowercase.len() == text.len() {
+        &text
+    } else {
+        &lowercase
+    };
+    Some(snippet(haystack, start, start + needle.len()))
+}
+
+/// Cuts the text around a match, on one line.
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let before: Vec<(usize, char)> = text[..start].char_indices().collect();
+    let snippet_start = before
+        .len()
+        .checked_sub(SNIPPET_CONTEXT)
+        .map_or(0, |index| before[index].0);
+    let snippet_end = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(text.len(), |(index, _)| end + index);
+
+    let mut result = String::new();
+    if snippet_start > 0 {
+        result.push_str("...");
+    }
+    result.push_str(&text[snippet_start..snippet_end]);
+    if snippet_end < text.len() {
+        result.push_str("...");
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_keeps_short_values_whole() {
+        assert_eq!(snippet("Acme Corp", 0, 4), "Acme Corp");
+    }
+
+    #[test]
+    fn test_snippet_cuts_long_values_on_one_line() {
+        let text = format!("{}Acme\n{}", "a".repeat(40), "b".repeat(40));
+
+        assert_eq!(
+            snippet(&text, 40, 44),
+            format!("...{}Acme {}...", "a".repeat(30), "b".repeat(29))
+        );
+    }
+
+    #[test]
+    fn test_match_value_ignores_case() {
+        let value = FieldValue::String("Works at ACME".to_string());
+
+        assert_eq!(
+            match_value(&value, "acme"),
+            Some("Works at ACME".to_string())
+        );
+        assert_eq!(match_value(&FieldValue::Integer(1), "1"), None);
+    }
+}
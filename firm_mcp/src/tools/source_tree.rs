@@ -3,58 +3,302 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 
+use firm_lang::parser::dsl::ParsedSource;
 use firm_lang::workspace::Workspace;
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
+use serde_json::{Value, json};
 
+use super::response_format::{ResponseFormat, json_result};
 use crate::resources;
 
 /// Parameters for the source_tree tool.
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
-pub struct SourceTreeParams {}
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
+pub struct SourceTreeParams {
+    /// Only include files under this directory or file path, relative to the workspace
+    /// root (e.g., "schemas" or "projects/acme.firm").
+    pub path_prefix: Option<String>,
+
+    /// How many directory levels to show below the workspace root, or below 'path_prefix'.
+    /// Files in deeper directories are summarized as counts. Leave out to show every directory.
+    pub depth: Option<usize>,
+
+    /// Response format: "text" (default) for an indented outline, or "json" for structured output.
+    #[serde(default)]
+    pub format: ResponseFormat,
+}
+
+/// A schema or entity defined in a source file.
+struct Definition {
+    /// The schema name, or the entity's type.
+    name: String,
+    /// The entity's ID, or None for schemas.
+    id: Option<String>,
+    line: usize,
+}
+
+/// The definitions in a source file, in the order they appear.
+struct FileOutline {
+    path: String,
+    definitions: Vec<Definition>,
+}
+
+impl FileOutline {
+    fn new(path: String, parsed: &ParsedSource) -> Self {
+        let schemas = parsed.schemas().into_iter().filter_map(|schema| {
+            Some(Definition {
+                name: schema.name()?.to_string(),
+                id: None,
+                line: schema.range().start_point.row + 1,
+            })
+        });
+        let entities = parsed.entities().into_iter().filter_map(|entity| {
+            Some(Definition {
+                name: entity.entity_type()?.to_string(),
+                id: Some(entity.id()?.to_string()),
+                line: entity.range().start_point.row + 1,
+            })
+        });
+
+        let mut definitions: Vec<Definition> = schemas.chain(entities).collect();
+        definitions.sort_by_key(|definition| definition.line);
+        Self { path, definitions }
+    }
+
+    fn counts(&self) -> Counts {
+        let schemas = self.definitions.iter().filter(|d| d.id.is_none()).count();
+        Counts {
+            files: 1,
+            schemas,
+            entities: self.definitions.len() - schemas,
+        }
+    }
+
+    /// The file name, without its directory.
+    fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// How many files, schemas and entities part of the tree holds.
+#[derive(Debug, Default, Clone, Copy)]
+struct Counts {
+    files: usize,
+    schemas: usize,
+    entities: usize,
+}
+
+impl Counts {
+    fn add(&mut self, other: Counts) {
+        self.files += other.files;
+        self.schemas += other.schemas;
+        self.entities += other.entities;
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "files": self.files,
+            "schemas": self.schemas,
+            "entities": self.entities,
+        })
+    }
+
+    /// Describes the schemas and entities, like "1 schema, 3 entities".
+    fn describe_definitions(&self) -> String {
+        let mut parts = Vec::new();
+        if self.schemas > 0 {
+            parts.push(count(self.schemas, "schema", "schemas"));
+        }
+        if self.entities > 0 {
+            parts.push(count(self.entities, "entity", "entities"));
+        }
+        if parts.is_empty() {
+            "no definitions".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// Describes the files, schemas and entities, like "2 files, 1 schema, 3 entities".
+    fn describe(&self) -> String {
+        let files = count(self.files, "file", "files");
+        match (self.schemas, self.entities) {
+            (0, 0) => files,
+            _ => format!("{}, {}", files, self.describe_definitions()),
+        }
+    }
+}
+
+/// The files shown in a directory, and the counts of those below the depth limit.
+#[derive(Default)]
+struct DirectoryOutline {
+    files: Vec<FileOutline>,
+    hidden: Counts,
+}
+
+impl DirectoryOutline {
+    fn counts(&self) -> Counts {
+        let mut counts = self.hidden;
+        for file in &self.files {
+            counts.add(file.counts());
+        }
+        counts
+    }
+}
 
 /// Execute the source_tree tool.
-pub fn execute(workspace: &Workspace, workspace_path: &Path) -> CallToolResult {
-    let mut paths: Vec<String> = workspace
-        .file_paths()
-        .iter()
-        .filter_map(|path| resources::to_relative_path(workspace_path, path))
-        .collect();
+///
+/// Outlines the workspace's source files grouped by directory, with the schemas
+/// and entities each file defines and their lines.
+pub fn execute(
+    workspace: &Workspace,
+    workspace_path: &Path,
+    params: &SourceTreeParams,
+) -> CallToolResult {
+    let prefix = params
+        .path_prefix
+        .as_deref()
+        .map(|p| p.trim().trim_start_matches("./").trim_end_matches('/'))
+        .filter(|p| !p.is_empty());
+    let prefix_depth = prefix.map_or(0, |p| Path::new(p).components().count());
 
-    paths.sort();
+    // Group files by directory, summarizing the ones below the depth limit
+    let mut tree: BTreeMap<String, DirectoryOutline> = BTreeMap::new();
+    for (path, parsed) in workspace.parsed_sources() {
+        let Some(relative) = resources::to_relative_path(workspace_path, path) else {
+            continue;
+        };
+        if prefix.is_some_and(|prefix| !Path::new(&relative).starts_with(prefix)) {
+            continue;
+        }
 
-    if paths.is_empty() {
-        return CallToolResult::success(vec![Content::text("No .firm source files found.")]);
+        let dir = relative
+            .rsplit_once('/')
+            .map(|(dir, _)| dir.to_string())
+            .unwrap_or_default();
+        let dir_depth = dir.split('/').filter(|part| !part.is_empty()).count();
+        let file = FileOutline::new(relative, parsed);
+
+        match params.depth {
+            Some(depth) if dir_depth > prefix_depth + depth => {
+                let shown: Vec<&str> = dir.split('/').take(prefix_depth + depth).collect();
+                tree.entry(shown.join("/"))
+                    .or_default()
+                    .hidden
+                    .add(file.counts());
+            }
+            _ => tree.entry(dir).or_default().files.push(file),
+        }
     }
 
-    // Group files by directory
-    let mut tree: BTreeMap<String, Vec<String>> = BTreeMap::new();
-    for path in &paths {
-        let (dir, file) = match path.rsplit_once('/') {
-            Some((d, f)) => (d.to_string(), f.to_string()),
-            None => (String::new(), path.clone()),
+    if tree.is_empty() {
+        let message = match prefix {
+            Some(prefix) => format!("No .firm source files found under '{}'.", prefix),
+            None => "No .firm source files found.".to_string(),
         };
-        tree.entry(dir).or_default().push(file);
+        return CallToolResult::success(vec![Content::text(message)]);
+    }
+
+    let mut totals = Counts::default();
+    for directory in tree.values() {
+        totals.add(directory.counts());
+    }
+
+    match params.format {
+        ResponseFormat::Text => {
+            CallToolResult::success(vec![Content::text(render_text(&tree, totals))])
+        }
+        ResponseFormat::Json => json_result(&render_json(&tree, totals)),
     }
+}
 
-    // Render as indented tree
+/// Renders the tree as an indented outline, ending with the totals.
+fn render_text(tree: &BTreeMap<String, DirectoryOutline>, totals: Counts) -> String {
     let mut output = String::new();
-    for (dir, files) in &tree {
-        if dir.is_empty() {
-            for file in files {
-                output.push_str(file);
-                output.push('\n');
-            }
+    for (dir, directory) in tree {
+        // Files at the root aren't indented
+        let indent = if dir.is_empty() {
+            ""
         } else {
-            output.push_str(dir);
-            output.push_str("/\n");
-            for file in files {
-                output.push_str("  ");
-                output.push_str(file);
-                output.push('\n');
+            output.push_str(&format!("{}/ ({})\n", dir, directory.counts().describe()));
+            "  "
+        };
+
+        for file in &directory.files {
+            output.push_str(&format!(
+                "{}{} ({})\n",
+                indent,
+                file.name(),
+                file.counts().describe_definitions()
+            ));
+            for definition in &file.definitions {
+                let name = match &definition.id {
+                    Some(id) => format!("{} {}", definition.name, id),
+                    None => format!("schema {}", definition.name),
+                };
+                output.push_str(&format!(
+                    "{}  {} (line {})\n",
+                    indent, name, definition.line
+                ));
             }
         }
+
+        if directory.hidden.files > 0 {
+            output.push_str(&format!(
+                "{}+ {} in subdirectories\n",
+                indent,
+                directory.hidden.describe()
+            ));
+        }
     }
 
-    CallToolResult::success(vec![Content::text(output.trim_end())])
+    output.push_str(&format!("\nTotal: {}", totals.describe()));
+    output
+}
+
+/// Renders the tree as directories holding files and their definitions.
+fn render_json(tree: &BTreeMap<String, DirectoryOutline>, totals: Counts) -> Value {
+    let directories: Vec<Value> = tree
+        .iter()
+        .map(|(dir, directory)| {
+            let files: Vec<Value> = directory
+                .files
+                .iter()
+                .map(|file| {
+                    let definitions: Vec<Value> = file
+                        .definitions
+                        .iter()
+                        .map(|definition| match &definition.id {
+                            Some(id) => json!({
+                                "kind": "entity",
+                                "type": definition.name,
+                                "id": id,
+                                "line": definition.line,
+                            }),
+                            None => json!({
+                                "kind": "schema",
+                                "name": definition.name,
+                                "line": definition.line,
+                            }),
+                        })
+                        .collect();
+                    json!({ "path": file.path, "definitions": definitions })
+                })
+                .collect();
+
+            json!({
+                "path": dir,
+                "counts": directory.counts().to_json(),
+                "files": files,
+                "hidden": directory.hidden.to_json(),
+            })
+        })
+        .collect();
+
+    json!({ "directories": directories, "totals": totals.to_json() })
+}
+
+/// Formats a count with the singular or plural noun.
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
 }
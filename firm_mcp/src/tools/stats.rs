@@ -0,0 +1,200 @@
+//! Stats tool implementation.
+
+use firm_core::graph::{Aggregation, AggregationResult, EntityGraph, Query, QueryResult};
+use firm_lang::parser::query::parse_query;
+use firm_lang::workspace::TimezoneConfig;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::json;
+
+/// How many groups the one-line summary names before abbreviating.
+const SUMMARY_GROUP_LIMIT: usize = 10;
+
+/// Parameters for the stats tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct StatsParams {
+    /// Query string ending in an aggregation. Examples:
+    /// - "from task | count"
+    /// - "from task | group status | count"
+    /// - "from invoice | where status == \"sent\" | sum amount"
+    ///
+    /// Leave out to compose the query from 'type', 'where', 'group_by' and 'aggregate'.
+    pub query: Option<String>,
+
+    /// Entity type to aggregate over, instead of a query (e.g. "task").
+    pub r#type: Option<String>,
+
+    /// Filter condition for the entities of 'type' (e.g. "is_completed == false").
+    pub r#where: Option<String>,
+
+    /// Field to group the entities of 'type' by (e.g. "status").
+    pub group_by: Option<String>,
+
+    /// Aggregation for the entities of 'type': "count" (default), "count <field>",
+    /// "sum <field>", "average <field>" or "median <field>".
+    pub aggregate: Option<String>,
+}
+
+/// Execute the stats tool.
+///
+/// Runs a query ending in a numeric aggregation, either given directly or
+/// composed from the shorthand parameters, and returns a one-line summary
+/// followed by the aggregation result as JSON.
+pub fn execute(
+    graph: &EntityGraph,
+    timezone: &TimezoneConfig,
+    params: &StatsParams,
+) -> CallToolResult {
+    let query_string = match compose_query(params) {
+        Ok(q) => q,
+        Err(e) => return CallToolResult::error(vec![Content::text(e)]),
+    };
+
+    let parsed_query = match parse_query(&query_string) {
+        Ok(q) => q,
+        Err(e) => {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to parse query '{}': {}",
+                query_string, e
+            ))]);
+        }
+    };
+
+    let mut query: Query = match parsed_query.try_into() {
+        Ok(q) => q,
+        Err(e) => {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to convert query: {}",
+                e
+            ))]);
+        }
+    };
+
+    match &query.aggregation {
+        None => {
+            return CallToolResult::error(vec![Content::text(format!(
+                "The query '{}' doesn't end in an aggregation. Add one of 'count', \
+                 'sum <field>', 'average <field>' or 'median <field>', optionally after \
+                 'group <field>' (e.g. 'from task | group status | count'). \
+                 Use 'query' to fetch the matching entities instead.",
+                query_string
+            ))]);
+        }
+        Some(Aggregation::Select(_)) => {
+            return CallToolResult::error(vec![Content::text(format!(
+                "The query '{}' selects fields, which is not a numeric aggregation. \
+                 Use 'count', 'sum <field>', 'average <field>' or 'median <field>', \
+                 or use 'query' to fetch the selected fields.",
+                query_string
+            ))]);
+        }
+        Some(_) => {}
+    }
+
+    if let Some(offset) = timezone.offset() {
+        query = query.with_timezone(offset);
+    }
+
+    let result = match query.execute(graph) {
+        Ok(QueryResult::Aggregation(result)) => result,
+        Ok(QueryResult::Entities(_)) => {
+            return CallToolResult::error(vec![Content::text(
+                "Query returned entities instead of an aggregation".to_string(),
+            )]);
+        }
+        Err(e) => {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Query execution failed: {}",
+                e
+            ))]);
+        }
+    };
+
+    let summary = summarize(&query, &result);
+    let data = json!({ "query": query_string, "result": result });
+    match serde_json::to_string_pretty(&data) {
+        Ok(json) => CallToolResult::success(vec![Content::text(summary), Content::text(json)]),
+        Err(e) => CallToolResult::error(vec![Content::text(format!(
+            "Failed to serialize result as JSON: {}",
+            e
+        ))]),
+    }
+}
+
+/// Returns the query to run: the given one, or one composed from the shorthand parameters.
+fn compose_query(params: &StatsParams) -> Result<String, String> {
+    match (&params.query, &params.r#type) {
+        (Some(_), Some(_)) => Err("Use either 'query' or 'type', not both.".to_string()),
+        (None, None) => Err(
+            "Provide a 'query' ending in an aggregation (e.g. 'from task | group status | count'), \
+             or a 'type' with an optional 'where', 'group_by' and 'aggregate'."
+                .to_string(),
+        ),
+        (Some(query), None) => {
+            if params.r#where.is_some() || params.group_by.is_some() || params.aggregate.is_some()
+            {
+                return Err("'where', 'group_by' and 'aggregate' only apply with 'type'. \
+                     Put them in the query instead."
+                    .to_string());
+            }
+            Ok(query.trim().to_string())
+        }
+        (None, Some(entity_type)) => {
+            let mut query = format!("from {}", entity_type.trim());
+            if let Some(condition) = &params.r#where {
+                query.push_str(&format!(" | where {}", condition.trim()));
+            }
+            if let Some(field) = &params.group_by {
+                query.push_str(&format!(" | group {}", field.trim()));
+            }
+            let aggregate = params.aggregate.as_deref().unwrap_or("count");
+            query.push_str(&format!(" | {}", aggregate.trim()));
+            Ok(query)
+        }
+    }
+}
+
+/// One-line description of an aggregation result, e.g. "count of task by status: done 3, todo 2".
+fn summarize(query: &Query, result: &AggregationResult) -> String {
+    let aggregation = query
+        .aggregation
+        .as_ref()
+        .map(|a| a.to_string())
+        .unwrap_or_default();
+    let subject = format!("{} of {}", aggregation, query.from);
+
+    match result {
+        AggregationResult::Grouped { field, groups, .. } => {
+            let mut named: Vec<String> = groups
+                .iter()
+                .take(SUMMARY_GROUP_LIMIT)
+                .map(|(key, value)| {
+                    let key = key
+                        .as_ref()
+                        .map(|k| k.to_string())
+                        .unwrap_or_else(|| "(none)".to_string());
+                    format!("{} {}", key, summary_value(value))
+                })
+                .collect();
+            if groups.len() > SUMMARY_GROUP_LIMIT {
+                named.push(format!("and {} more", groups.len() - SUMMARY_GROUP_LIMIT));
+            }
+            if named.is_empty() {
+                format!("{} by {}: no entities", subject, field)
+            } else {
+                format!("{} by {}: {}", subject, field, named.join(", "))
+            }
+        }
+        _ => format!("{}: {}", subject, summary_value(result)),
+    }
+}
+
+/// A scalar aggregation value for the summary, with averages and medians rounded.
+fn summary_value(result: &AggregationResult) -> String {
+    match result {
+        AggregationResult::Average(value) | AggregationResult::Median(value) => {
+            ((value * 100.0).round() / 100.0).to_string()
+        }
+        other => other.to_string(),
+    }
+}
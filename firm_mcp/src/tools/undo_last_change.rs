@@ -0,0 +1,131 @@
+//! Undo last change tool implementation.
+
+use std::fs;
+use std::path::Path;
+
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use crate::journal::{self, JournalEntry};
+
+/// Parameters for the undo_last_change tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct UndoLastChangeParams {
+    /// Undo even if the files were edited since the change, and keep the
+    /// restored files if the workspace doesn't validate afterwards. Default: false.
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// A file restored by undoing a change.
+#[derive(Debug)]
+pub struct RestoredFile {
+    /// The path of the file (relative to workspace root).
+    pub path: String,
+    /// The content before the undo, or None if the file didn't exist, for rollback.
+    pub current_content: Option<String>,
+}
+
+/// Execute the undo_last_change tool.
+///
+/// Restores the files of a journal entry to their content before the change,
+/// removing the files it created and recreating the files it deleted. Unless
+/// forced, files edited since the change are left alone and the error names them.
+/// The caller should rebuild the workspace and roll back if the rebuild fails.
+pub fn execute(
+    workspace_path: &Path,
+    entry: &JournalEntry,
+    params: &UndoLastChangeParams,
+) -> Result<Vec<RestoredFile>, String> {
+    let mut restored_files = Vec::new();
+    for file in &entry.files {
+        let current_content = fs::read_to_string(workspace_path.join(&file.path)).ok();
+        if current_content != file.after && !params.force {
+            return Err(format!(
+                "{} was edited after '{}' changed it. Use 'force' to undo anyway",
+                file.path, entry.tool
+            ));
+        }
+        restored_files.push(RestoredFile {
+            path: file.path.clone(),
+            current_content,
+        });
+    }
+
+    for (index, file) in entry.files.iter().enumerate() {
+        if !journal::restore(workspace_path, &file.path, file.before.as_deref()) {
+            rollback(workspace_path, &restored_files[..index]);
+            return Err(format!("Failed to restore {}", file.path));
+        }
+    }
+
+    Ok(restored_files)
+}
+
+/// Put back the content the files had before the undo.
+pub fn rollback(workspace_path: &Path, restored_files: &[RestoredFile]) -> bool {
+    restored_files
+        .iter()
+        .all(|file| journal::restore(workspace_path, &file.path, file.current_content.as_deref()))
+}
+
+/// Describes how undoing a change affects each of its files.
+fn reverted_files(entry: &JournalEntry) -> String {
+    entry
+        .files
+        .iter()
+        .map(|file| match (&file.before, &file.after) {
+            (None, _) => format!("- {} (removed)", file.path),
+            (Some(_), None) => format!("- {} (recreated)", file.path),
+            (Some(_), Some(_)) => format!("- {} (restored)", file.path),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Create a success result for undo_last_change.
+pub fn success_result(entry: &JournalEntry) -> CallToolResult {
+    CallToolResult::success(vec![Content::text(format!(
+        "Undid change #{} by {}: {}\n{}\nWorkspace is valid.",
+        entry.id,
+        entry.tool,
+        entry.summary,
+        reverted_files(entry)
+    ))])
+}
+
+/// Create a success result for undo_last_change when force mode kept an invalid workspace.
+pub fn force_success_result(entry: &JournalEntry, error: &str) -> CallToolResult {
+    CallToolResult::success(vec![Content::text(format!(
+        "Undid change #{} by {}: {}\n{}\nWarning: workspace has validation errors: {}. \
+         Use 'build' to check status after making more changes.",
+        entry.id,
+        entry.tool,
+        entry.summary,
+        reverted_files(entry),
+        error
+    ))])
+}
+
+/// Create an error result for undo_last_change when validation fails and rollback occurred.
+pub fn validation_error_result(error: &str, rollback_success: bool) -> CallToolResult {
+    let rollback_msg = if rollback_success {
+        "The change was kept."
+    } else {
+        "Warning: Failed to put back the changed files."
+    };
+
+    CallToolResult::error(vec![Content::text(format!(
+        "Undoing the change would break the workspace: {}. {}",
+        error, rollback_msg
+    ))])
+}
+
+/// Create an error result for undo_last_change when there's nothing to undo.
+pub fn nothing_to_undo_result() -> CallToolResult {
+    CallToolResult::error(vec![Content::text(
+        "No changes to undo. The journal only holds changes made through this server \
+         since it started."
+            .to_string(),
+    )])
+}
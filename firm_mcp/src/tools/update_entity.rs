@@ -0,0 +1,489 @@
+//! Update entity tool implementation.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use firm_core::{EntityId, FieldId, FieldValue, compose_entity_id};
+use firm_lang::generate::from_value::generate_value;
+use firm_lang::generate::generator_options::GeneratorOptions;
+use firm_lang::parser::dsl::{ParsedEntity, parse_source};
+use firm_lang::workspace::{Workspace, WorkspaceBuild};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+
+use super::add_entity::{computed_field_error, json_to_field_value};
+use crate::resources;
+
+/// Parameters for the update_entity tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct UpdateEntityParams {
+    /// Entity type (e.g., "person", "task").
+    pub r#type: String,
+
+    /// Entity ID (e.g., "john_doe", "fix_bug").
+    pub id: String,
+
+    /// Field values to set, as a key-value map.
+    /// Values use the same format as add_entity. Paths should be relative to the workspace root.
+    #[serde(default)]
+    pub set: HashMap<String, serde_json::Value>,
+
+    /// Names of fields to remove from the entity.
+    #[serde(default)]
+    pub unset: Vec<String>,
+
+    /// Optional type annotations for list fields, like for add_entity.
+    /// Maps field names to their inner type (e.g., "secondary_contacts" -> "reference").
+    pub list_item_types: Option<HashMap<String, String>>,
+
+    /// If true, keep the changes even if workspace validation fails.
+    /// The files must still have valid syntax. Default: false.
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// A file changed by an update, with its content from before.
+#[derive(Debug)]
+pub struct ChangedFile {
+    /// The path of the file (relative to workspace root).
+    pub path: String,
+    /// Original file content for rollback.
+    pub original_content: String,
+}
+
+/// Result of updating an entity (before validation).
+#[derive(Debug)]
+pub struct UpdateEntityResult {
+    /// The files that were written.
+    pub changed_files: Vec<ChangedFile>,
+    /// The entity's blocks after the update.
+    pub dsl: String,
+}
+
+/// A replacement of a byte range in a file.
+struct Edit {
+    range: Range<usize>,
+    replacement: String,
+}
+
+/// An entity block in a file, with the byte ranges of its fields.
+struct Block {
+    path: PathBuf,
+    /// The byte offset of the closing brace.
+    closing_brace: usize,
+    fields: Vec<BlockField>,
+}
+
+struct BlockField {
+    name: String,
+    range: Range<usize>,
+    value_range: Range<usize>,
+}
+
+/// Execute the update_entity tool.
+///
+/// Edits only the affected fields in the entity's blocks, leaving other lines
+/// (including comments) as they are. Set fields replace the value wherever the
+/// field is assigned, or are added to the end of the entity's first block.
+/// Unset fields have their assignments removed from every block.
+/// The changed files are written, and the caller should rebuild the workspace
+/// and roll back if the rebuild fails (unless force mode is enabled).
+pub fn execute(
+    workspace_path: &Path,
+    workspace: &Workspace,
+    build: &WorkspaceBuild,
+    params: &UpdateEntityParams,
+) -> Result<UpdateEntityResult, String> {
+    let entity_type = params.r#type.as_str();
+    let entity_id = EntityId::new(&params.id);
+    let composite_id = compose_entity_id(entity_type, entity_id.as_str());
+
+    if params.set.is_empty() && params.unset.is_empty() {
+        return Err("Nothing to update: provide fields to 'set' or 'unset'".to_string());
+    }
+    if let Some(name) = params
+        .unset
+        .iter()
+        .find(|name| params.set.contains_key(*name))
+    {
+        return Err(format!("Field '{}' can't be both set and unset", name));
+    }
+
+    let schema = build
+        .schemas
+        .iter()
+        .find(|s| s.entity_type.as_str() == entity_type)
+        .ok_or_else(|| format!("Schema for type '{}' not found", entity_type))?;
+    for name in params.set.keys() {
+        if schema.is_computed(&FieldId::new(name)) {
+            return Err(computed_field_error(schema, name));
+        }
+        if !schema
+            .fields
+            .keys()
+            .any(|field_id| field_id.as_str() == name)
+        {
+            return Err(format!(
+                "Field '{}' not found in schema for '{}'",
+                name, entity_type
+            ));
+        }
+    }
+
+    // Read the entity's blocks from the files on disk
+    let sources = workspace.find_entity_sources(entity_type, entity_id.as_str());
+    if sources.is_empty() {
+        return Err(format!("Entity '{}' not found", composite_id));
+    }
+    let mut contents: BTreeMap<PathBuf, String> = BTreeMap::new();
+    for source in &sources {
+        if !contents.contains_key(&source.path) {
+            let content = fs::read_to_string(&source.path)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            contents.insert(source.path.clone(), content);
+        }
+    }
+    let mut blocks = Vec::new();
+    for (path, content) in &contents {
+        blocks.extend(entity_blocks(
+            path,
+            content,
+            entity_type,
+            entity_id.as_str(),
+        )?);
+    }
+    let first_block = blocks
+        .first()
+        .ok_or_else(|| format!("Entity '{}' not found", composite_id))?;
+
+    let mut edits: BTreeMap<PathBuf, Vec<Edit>> = BTreeMap::new();
+
+    // Set fields in schema order, so added fields read naturally
+    let mut insertions = String::new();
+    for (field_id, field_schema) in schema.ordered_fields() {
+        let Some(json_value) = params.set.get(field_id.as_str()) else {
+            continue;
+        };
+
+        let assignments = field_assignments(&blocks, field_id.as_str());
+        let targets: Vec<&Path> = if assignments.is_empty() {
+            vec![&first_block.path]
+        } else {
+            assignments
+                .iter()
+                .map(|(block, _)| block.path.as_path())
+                .collect()
+        };
+
+        for (index, path) in targets.into_iter().enumerate() {
+            let (_, mut value) = json_to_field_value(
+                schema,
+                field_id.as_str(),
+                json_value,
+                &params.list_item_types,
+                workspace_path,
+                path,
+            )?;
+            if let FieldValue::Enum(enum_value) = &mut value
+                && let Some(canonical) = field_schema.canonical_enum_value(enum_value)
+            {
+                *enum_value = canonical.to_string();
+            }
+            let value = generate_value(&value, &GeneratorOptions::default());
+
+            match assignments.get(index) {
+                Some((block, field)) => edits.entry(block.path.clone()).or_default().push(Edit {
+                    range: field.value_range.clone(),
+                    replacement: value,
+                }),
+                None => insertions.push_str(&format!("{} = {}\n", field_id, value)),
+            }
+        }
+    }
+    if !insertions.is_empty() {
+        let content = &contents[&first_block.path];
+        edits
+            .entry(first_block.path.clone())
+            .or_default()
+            .push(insertion(content, first_block.closing_brace, &insertions));
+    }
+
+    // Unset fields in every block that assigns them
+    for name in &params.unset {
+        let assignments = field_assignments(&blocks, name);
+        if assignments.is_empty() {
+            return Err(format!("Field '{}' is not set on '{}'", name, composite_id));
+        }
+        for (block, field) in assignments {
+            let content = &contents[&block.path];
+            edits.entry(block.path.clone()).or_default().push(Edit {
+                range: removal_range(content, &field.range),
+                replacement: String::new(),
+            });
+        }
+    }
+
+    // Apply the edits and check the syntax before writing anything
+    let mut new_contents = Vec::new();
+    for (path, file_edits) in edits {
+        let new_content = apply(&contents[&path], file_edits);
+        let parsed = parse_source(new_content.clone(), None)
+            .map_err(|e| format!("Failed to parse DSL: {}", e))?;
+        if parsed.has_error() {
+            return Err(format!(
+                "The update would leave {} with invalid syntax",
+                path.display()
+            ));
+        }
+        new_contents.push((path, new_content));
+    }
+
+    let mut changed_files = Vec::new();
+    for (path, new_content) in &new_contents {
+        let relative_path = resources::to_relative_path(workspace_path, path)
+            .unwrap_or_else(|| path.to_string_lossy().into_owned());
+        fs::write(path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
+        changed_files.push(ChangedFile {
+            path: relative_path,
+            original_content: contents[path].clone(),
+        });
+    }
+
+    // Show every block of the entity as it is now
+    let mut dsl_blocks = Vec::new();
+    for (path, content) in &contents {
+        let content = new_contents
+            .iter()
+            .find(|(changed, _)| changed == path)
+            .map_or(content, |(_, new_content)| new_content);
+        let parsed = parse_source(content.clone(), None)
+            .map_err(|e| format!("Failed to parse DSL: {}", e))?;
+        for entity in parsed.entities() {
+            if entity.entity_type() == Some(entity_type) && entity.id() == Some(entity_id.as_str())
+            {
+                dsl_blocks
+                    .push(content[entity.range().start_byte..entity.range().end_byte].to_string());
+            }
+        }
+    }
+
+    Ok(UpdateEntityResult {
+        changed_files,
+        dsl: dsl_blocks.join("\n\n"),
+    })
+}
+
+/// Rollback an update by restoring the original content of every changed file.
+pub fn rollback(workspace_path: &Path, changed_files: &[ChangedFile]) -> bool {
+    changed_files
+        .iter()
+        .all(|file| fs::write(workspace_path.join(&file.path), &file.original_content).is_ok())
+}
+
+/// Finds the blocks of an entity in a file.
+fn entity_blocks(
+    path: &Path,
+    content: &str,
+    entity_type: &str,
+    entity_id: &str,
+) -> Result<Vec<Block>, String> {
+    let parsed = parse_source(content.to_string(), None)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let blocks = parsed
+        .entities()
+        .iter()
+        .filter(|entity| {
+            entity.entity_type() == Some(entity_type) && entity.id() == Some(entity_id)
+        })
+        .filter_map(|entity| block(path, content, entity))
+        .collect();
+    Ok(blocks)
+}
+
+fn block(path: &Path, content: &str, entity: &ParsedEntity) -> Option<Block> {
+    // The entity block ends with its closing brace
+    let closing_brace = entity.range().end_byte.checked_sub(1)?;
+    if content.as_bytes().get(closing_brace) != Some(&b'}') {
+        return None;
+    }
+
+    let fields = entity
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let value_range = field.value_range()?;
+            Some(BlockField {
+                name: field.id()?.to_string(),
+                range: field.range().start_byte..field.range().end_byte,
+                value_range: value_range.start_byte..value_range.end_byte,
+            })
+        })
+        .collect();
+
+    Some(Block {
+        path: path.to_path_buf(),
+        closing_brace,
+        fields,
+    })
+}
+
+/// Finds every assignment of a field across an entity's blocks.
+fn field_assignments<'a>(blocks: &'a [Block], name: &str) -> Vec<(&'a Block, &'a BlockField)> {
+    blocks
+        .iter()
+        .flat_map(|block| block.fields.iter().map(move |field| (block, field)))
+        .filter(|(_, field)| field.name == name)
+        .collect()
+}
+
+/// Inserts field lines above a block's closing brace.
+///
+/// Fields are indented one level deeper than the brace's line, or added
+/// inline when the brace shares its line with other content.
+fn insertion(content: &str, closing_brace: usize, lines: &str) -> Edit {
+    let line_start = content[..closing_brace].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &content[line_start..closing_brace];
+
+    if indent.trim().is_empty() {
+        let replacement = lines
+            .lines()
+            .map(|line| format!("{}    {}\n", indent, line))
+            .collect();
+        Edit {
+            range: line_start..line_start,
+            replacement,
+        }
+    } else {
+        let replacement = lines.lines().map(|line| format!("{} ", line)).collect();
+        Edit {
+            range: closing_brace..closing_brace,
+            replacement,
+        }
+    }
+}
+
+/// Gets the range to remove for a field, with its whole lines when it has them to itself.
+pub(crate) fn removal_range(content: &str, field: &Range<usize>) -> Range<usize> {
+    let line_start = content[..field.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[field.end..]
+        .find('\n')
+        .map_or(content.len(), |i| field.end + i + 1);
+
+    let before = &content[line_start..field.start];
+    let after = &content[field.end..line_end];
+    if before.trim().is_empty() && after.trim().is_empty() {
+        line_start..line_end
+    } else {
+        let trailing = after.len() - after.trim_start_matches([' ', '\t']).len();
+        field.start..field.end + trailing
+    }
+}
+
+/// Applies non-overlapping edits, from the end so earlier offsets stay valid.
+fn apply(content: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+
+    let mut result = content.to_string();
+    for edit in edits {
+        result.replace_range(edit.range, &edit.replacement);
+    }
+    result
+}
+
+/// Create a success result for update_entity.
+pub fn success_result(result: &UpdateEntityResult) -> CallToolResult {
+    CallToolResult::success(vec![
+        Content::text(format!(
+            "Updated {}. Workspace is valid.",
+            changed_paths(result)
+        )),
+        Content::text(result.dsl.clone()),
+    ])
+}
+
+/// Create a success result for update_entity with force mode (validation warning).
+pub fn force_success_result(result: &UpdateEntityResult, error: &str) -> CallToolResult {
+    CallToolResult::success(vec![
+        Content::text(format!(
+            "Updated {}. Warning: workspace has validation errors: {}. \
+             Use 'build' to check status after making more changes.",
+            changed_paths(result),
+            error
+        )),
+        Content::text(result.dsl.clone()),
+    ])
+}
+
+/// Create an error result for update_entity when validation fails and rollback occurred.
+pub fn validation_error_result(error: &str, rollback_success: bool) -> CallToolResult {
+    let rollback_msg = if rollback_success {
+        "Changes have been rolled back."
+    } else {
+        "Warning: Failed to rollback changes."
+    };
+
+    CallToolResult::error(vec![Content::text(format!(
+        "Validation failed: {}. {} Use 'force: true' to update anyway.",
+        error, rollback_msg
+    ))])
+}
+
+fn changed_paths(result: &UpdateEntityResult) -> String {
+    let paths: Vec<&str> = result
+        .changed_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    paths.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removal_range_takes_whole_lines() {
+        let content = "person john {\n    name = \"John\"\n    email = \"j@x.com\"\n}\n";
+        let start = content.find("email").unwrap();
+        let end = content.find(".com\"").unwrap() + 5;
+
+        let range = removal_range(content, &(start..end));
+        let mut result = content.to_string();
+        result.replace_range(range, "");
+        assert_eq!(result, "person john {\n    name = \"John\"\n}\n");
+    }
+
+    #[test]
+    fn test_removal_range_inline() {
+        let content = "person john { name = \"John\" age = 30 }\n";
+        let start = content.find("name").unwrap();
+        let end = content.find(" age").unwrap();
+
+        let range = removal_range(content, &(start..end));
+        let mut result = content.to_string();
+        result.replace_range(range, "");
+        assert_eq!(result, "person john { age = 30 }\n");
+    }
+
+    #[test]
+    fn test_insertion_indents_above_the_closing_brace() {
+        let content = "person john {\n    name = \"John\"\n}\n";
+        let closing_brace = content.rfind('}').unwrap();
+
+        let edit = insertion(content, closing_brace, "age = 30\n");
+        assert_eq!(
+            apply(content, vec![edit]),
+            "person john {\n    name = \"John\"\n    age = 30\n}\n"
+        );
+
+        let inline = "person john { name = \"John\" }";
+        let edit = insertion(inline, inline.rfind('}').unwrap(), "age = 30\n");
+        assert_eq!(
+            apply(inline, vec![edit]),
+            "person john { name = \"John\" age = 30 }"
+        );
+    }
+}
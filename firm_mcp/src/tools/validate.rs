@@ -0,0 +1,120 @@
+//! Validate tool implementation.
+
+use std::path::Path;
+
+use chrono::FixedOffset;
+use firm_lang::workspace::{Diagnostic, DiagnosticSeverity, Workspace};
+use rmcp::model::{CallToolResult, Content};
+use rmcp::schemars;
+use serde_json::{Value, json};
+
+use super::response_format::json_result;
+use super::write_source::document_path;
+use crate::resources;
+
+/// Parameters for the validate tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ValidateParams {
+    /// Relative path to the .firm file to validate (e.g., "core/main.firm").
+    /// With 'content', the path the content would be written to, which doesn't have to exist yet.
+    pub path: String,
+
+    /// DSL content to validate in place of the file, without writing it.
+    /// Leave out to validate the file as it is on disk.
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+/// Execute the validate tool.
+///
+/// The workspace is loaded fresh, with the content in place of the file when
+/// it's given, so nothing on disk or in the server state changes. Returns the
+/// diagnostics of every file as JSON, since a change to one file can break
+/// references in others.
+pub fn execute(
+    workspace_path: &Path,
+    timezone: Option<FixedOffset>,
+    params: &ValidateParams,
+) -> CallToolResult {
+    let absolute_path = match resources::source_file_path(workspace_path, &params.path) {
+        Ok(absolute_path) => absolute_path,
+        Err(e) => return CallToolResult::error(vec![Content::text(e)]),
+    };
+    if params.content.is_none() && !absolute_path.is_file() {
+        return CallToolResult::error(vec![Content::text(format!(
+            "File not found: {}. Pass 'content' to validate a file that doesn't exist yet.",
+            params.path
+        ))]);
+    }
+
+    let mut workspace = Workspace::new().with_timezone(timezone);
+    if let Err(e) = workspace.load_directory(&workspace_path.to_path_buf()) {
+        return CallToolResult::error(vec![Content::text(format!(
+            "Failed to load workspace: {}",
+            e
+        ))]);
+    }
+    if let Some(content) = &params.content {
+        let document_path = match document_path(workspace_path, &absolute_path, &params.path) {
+            Ok(document_path) => document_path,
+            Err(error_result) => return error_result,
+        };
+        if let Err(e) = workspace.open_document(document_path, content.clone()) {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to parse DSL: {}",
+                e
+            ))]);
+        }
+    }
+
+    let mut diagnostics: Vec<Value> = Vec::new();
+    for (file, file_diagnostics) in workspace.collect_diagnostics() {
+        let source = workspace.file_source(&file).unwrap_or_default();
+        let relative_path = resources::to_relative_path(workspace_path, &file)
+            .unwrap_or_else(|| file.display().to_string());
+        diagnostics.extend(
+            file_diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic_to_json(diagnostic, &relative_path, source)),
+        );
+    }
+
+    let count = |severity: &str| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic["severity"] == severity)
+            .count()
+    };
+    let errors = count("error");
+    let warnings = count("warning");
+
+    json_result(&json!({
+        "path": params.path,
+        "valid": errors == 0,
+        "errors": errors,
+        "warnings": warnings,
+        "diagnostics": diagnostics,
+    }))
+}
+
+/// Convert a diagnostic to JSON, with its 1-based line and column.
+///
+/// Line and column are null for problems that aren't tied to a line.
+fn diagnostic_to_json(diagnostic: &Diagnostic, file: &str, source: &str) -> Value {
+    let severity = match diagnostic.severity {
+        DiagnosticSeverity::Error => "error",
+        DiagnosticSeverity::Warning => "warning",
+    };
+    let column = diagnostic
+        .line
+        .map(|_| diagnostic.span(source).start_point.column + 1);
+
+    json!({
+        "file": file,
+        "line": diagnostic.line,
+        "column": column,
+        "severity": severity,
+        "code": diagnostic.code,
+        "message": diagnostic.message,
+    })
+}
@@ -1,11 +1,14 @@
 //! Write source tool implementation.
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use chrono::FixedOffset;
 use firm_lang::parser::dsl::parse_source;
+use firm_lang::workspace::{self, Workspace, WorkspaceError};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::schemars;
+use similar::TextDiff;
 
 use crate::resources;
 
@@ -24,6 +27,17 @@ pub struct WriteSourceParams {
     /// The file must still have valid syntax. Default: false.
     #[serde(default)]
     pub force: bool,
+
+    /// If true, only preview the change: the content is validated against the
+    /// workspace without writing anything, and a unified diff is returned. Default: false.
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// The hash 'read_source' returned with 'include_hash: true' for the content this change
+    /// was derived from. If the file changed on disk since, nothing is written and a conflict
+    /// error is returned. Leave out to write regardless.
+    #[serde(default)]
+    pub base_hash: Option<String>,
 }
 
 /// Result of syntax validation and file write.
@@ -38,7 +52,8 @@ pub struct WriteResult {
 /// Validate syntax and write the file.
 ///
 /// Returns Ok(WriteResult) if syntax is valid and file was written.
-/// Returns Err(CallToolResult) if syntax validation failed.
+/// Returns Err(CallToolResult) if syntax validation failed, or if the file
+/// changed since the base hash was taken.
 ///
 /// After calling this, the caller should rebuild the workspace and handle
 /// rollback if rebuild fails (unless force mode is enabled).
@@ -47,7 +62,53 @@ pub fn validate_and_write(
     params: &WriteSourceParams,
 ) -> Result<WriteResult, CallToolResult> {
     // First, validate the content by parsing it (syntax check - always required)
-    let parsed = match parse_source(params.content.clone(), None) {
+    check_syntax(&params.content)?;
+    check_base_hash(workspace_path, &params.path, params.base_hash.as_deref())?;
+
+    // Get absolute path for the file
+    let absolute_path = workspace_path.join(&params.path);
+
+    // Read existing file content for potential rollback (None if file doesn't exist)
+    let original_content = fs::read_to_string(&absolute_path).ok();
+    let file_existed = original_content.is_some();
+
+    // Write the new content
+    if let Err(e) = resources::write_source_file(workspace_path, &params.path, &params.content) {
+        return Err(CallToolResult::error(vec![Content::text(e)]));
+    }
+
+    Ok(WriteResult {
+        file_existed,
+        original_content,
+    })
+}
+
+/// Check that a file still has the content a change was derived from, if a base hash is given.
+pub fn check_base_hash(
+    workspace_path: &Path,
+    path: &str,
+    base_hash: Option<&str>,
+) -> Result<(), CallToolResult> {
+    let Some(base_hash) = base_hash else {
+        return Ok(());
+    };
+
+    match workspace::check_base_hash(&workspace_path.join(path), base_hash) {
+        Ok(()) => Ok(()),
+        Err(WorkspaceError::Conflict(_, reason)) => {
+            Err(CallToolResult::error(vec![Content::text(format!(
+                "Conflict: {} changed since it was read ({}). \
+                 Use 'read_source' to read it again, then reapply the change.",
+                path, reason
+            ))]))
+        }
+        Err(e) => Err(CallToolResult::error(vec![Content::text(e.to_string())])),
+    }
+}
+
+/// Check that content parses without syntax errors.
+fn check_syntax(content: &str) -> Result<(), CallToolResult> {
+    let parsed = match parse_source(content.to_string(), None) {
         Ok(parsed) => parsed,
         Err(e) => {
             return Err(CallToolResult::error(vec![Content::text(format!(
@@ -64,23 +125,86 @@ pub fn validate_and_write(
              Please check for unclosed braces, missing values, or malformed references.",
         )]));
     }
+    Ok(())
+}
 
-    // Get absolute path for the file
-    let absolute_path = workspace_path.join(&params.path);
+/// Preview writing content to a file, without changing anything on disk.
+///
+/// The workspace is loaded fresh with the content in place of the file, so
+/// the shared server state isn't touched. Returns a unified diff between the
+/// current and proposed content, and whether the workspace would be valid.
+pub fn preview(
+    workspace_path: &Path,
+    timezone: Option<FixedOffset>,
+    path: &str,
+    content: &str,
+) -> CallToolResult {
+    if let Err(error_result) = check_syntax(content) {
+        return error_result;
+    }
 
-    // Read existing file content for potential rollback (None if file doesn't exist)
+    let absolute_path = match resources::source_file_path(workspace_path, path) {
+        Ok(absolute_path) => absolute_path,
+        Err(e) => return CallToolResult::error(vec![Content::text(e)]),
+    };
     let original_content = fs::read_to_string(&absolute_path).ok();
-    let file_existed = original_content.is_some();
 
-    // Write the new content
-    if let Err(e) = resources::write_source_file(workspace_path, &params.path, &params.content) {
-        return Err(CallToolResult::error(vec![Content::text(e)]));
+    // Open the content as a document, at the path the workspace stores the file under
+    let document_path = match document_path(workspace_path, &absolute_path, path) {
+        Ok(document_path) => document_path,
+        Err(error_result) => return error_result,
+    };
+    let mut workspace = Workspace::new().with_timezone(timezone);
+    let verdict = workspace
+        .load_directory(&workspace_path.to_path_buf())
+        .and_then(|_| workspace.open_document(document_path, content.to_string()))
+        .and_then(|_| workspace.build());
+
+    let mut text = format!("Dry run of {}: no changes were written. ", path);
+    match verdict {
+        Ok(_) => text.push_str("Workspace would be valid."),
+        Err(e) => text.push_str(&format!(
+            "Validation would fail: {}. Use 'force: true' to write anyway.",
+            e
+        )),
     }
 
-    Ok(WriteResult {
-        file_existed,
-        original_content,
-    })
+    let original = original_content.as_deref().unwrap_or_default();
+    if original == content {
+        text.push_str("\n\nThe content is unchanged.");
+    } else {
+        let old_header = match original_content {
+            Some(_) => format!("a/{}", path),
+            None => "/dev/null".to_string(),
+        };
+        let diff = TextDiff::from_lines(original, content)
+            .unified_diff()
+            .header(&old_header, &format!("b/{}", path))
+            .to_string();
+        text.push_str(&format!("\n\n{}", diff));
+    }
+
+    CallToolResult::success(vec![Content::text(text)])
+}
+
+/// Resolves the path a workspace stores a file under, to open content for it as a document.
+///
+/// The file doesn't have to exist yet.
+pub fn document_path(
+    workspace_path: &Path,
+    absolute_path: &Path,
+    path: &str,
+) -> Result<PathBuf, CallToolResult> {
+    match absolute_path.canonicalize() {
+        Ok(canonical) => Ok(canonical),
+        Err(_) => match workspace_path.canonicalize() {
+            Ok(root) => Ok(root.join(path)),
+            Err(e) => Err(CallToolResult::error(vec![Content::text(format!(
+                "Failed to resolve workspace path: {}",
+                e
+            ))])),
+        },
+    }
 }
 
 /// Rollback a write operation by restoring the original file or deleting a new file.
@@ -0,0 +1,132 @@
+mod helpers;
+
+use firm_core::graph::EntityGraph;
+use firm_lang::workspace::{AddConfig, WorkspaceBuild};
+use firm_mcp::tools::add_entities::{AddEntitiesParams, NewEntity, execute, rollback};
+use helpers::create_workspace;
+use std::collections::HashMap;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"schema task {
+    field { name = "title" type = "string" required = true }
+    field { name = "priority" type = "integer" required = false }
+}
+
+task existing {
+    title = "Existing"
+}
+"#;
+
+    fn task(id: &str, title: serde_json::Value) -> NewEntity {
+        NewEntity {
+            r#type: "task".to_string(),
+            id: id.to_string(),
+            from_id: None,
+            fields: HashMap::from([("title".to_string(), title)]),
+            list_item_types: None,
+        }
+    }
+
+    fn setup() -> (tempfile::TempDir, WorkspaceBuild, EntityGraph) {
+        let (dir, mut workspace) = create_workspace(&[("main.firm", SCHEMA)]);
+        let build = workspace.build().unwrap();
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+        (dir, build, graph)
+    }
+
+    #[test]
+    fn test_add_entities_writes_all_of_them() {
+        let (dir, build, graph) = setup();
+        let params = AddEntitiesParams {
+            entities: vec![
+                task("first", serde_json::json!("First")),
+                task("second", serde_json::json!("Second")),
+            ],
+            to_file: None,
+        };
+
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params).unwrap();
+        let ids: Vec<&str> = result.entity_ids.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids, vec!["task.first", "task.second"]);
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files[0].original_content.is_none());
+
+        let content = fs::read_to_string(dir.path().join(&result.files[0].path)).unwrap();
+        assert!(content.contains("task first {"));
+        assert!(content.contains("task second {"));
+        assert!(content.find("task first").unwrap() < content.find("task second").unwrap());
+
+        // Rolling back removes the created file
+        assert!(rollback(dir.path(), &result.files));
+        assert!(!dir.path().join(&result.files[0].path).exists());
+    }
+
+    #[test]
+    fn test_add_entities_to_shared_file() {
+        let (dir, build, graph) = setup();
+        let params = AddEntitiesParams {
+            entities: vec![task("first", serde_json::json!("First"))],
+            to_file: Some("main.firm".to_string()),
+        };
+
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params).unwrap();
+        assert_eq!(result.files[0].path, "main.firm");
+        assert_eq!(result.files[0].original_content.as_deref(), Some(SCHEMA));
+
+        let content = fs::read_to_string(dir.path().join("main.firm")).unwrap();
+        assert!(content.starts_with(SCHEMA));
+        assert!(content.contains("task first {"));
+
+        // Rolling back restores the original content
+        assert!(rollback(dir.path(), &result.files));
+        let content = fs::read_to_string(dir.path().join("main.firm")).unwrap();
+        assert_eq!(content, SCHEMA);
+    }
+
+    #[test]
+    fn test_add_entities_writes_nothing_when_one_fails() {
+        let (dir, build, graph) = setup();
+
+        // Invalid field value
+        let params = AddEntitiesParams {
+            entities: vec![
+                task("first", serde_json::json!("First")),
+                task("second", serde_json::json!(42)),
+            ],
+            to_file: Some("tasks.firm".to_string()),
+        };
+        let error =
+            execute(dir.path(), &build, &graph, &AddConfig::default(), &params).unwrap_err();
+        assert!(error.starts_with("Entity 1 ('task.second')"));
+        assert!(!dir.path().join("tasks.firm").exists());
+
+        // Duplicate IDs within the list
+        let params = AddEntitiesParams {
+            entities: vec![
+                task("first", serde_json::json!("First")),
+                task("First", serde_json::json!("Again")),
+            ],
+            to_file: Some("tasks.firm".to_string()),
+        };
+        let error =
+            execute(dir.path(), &build, &graph, &AddConfig::default(), &params).unwrap_err();
+        assert!(error.starts_with("Entity 1 ('task.first')"));
+        assert!(!dir.path().join("tasks.firm").exists());
+
+        // An ID already in the workspace
+        let params = AddEntitiesParams {
+            entities: vec![task("existing", serde_json::json!("Again"))],
+            to_file: Some("tasks.firm".to_string()),
+        };
+        let error =
+            execute(dir.path(), &build, &graph, &AddConfig::default(), &params).unwrap_err();
+        assert!(error.starts_with("Entity 0"));
+        assert!(error.contains("already exists"));
+        assert!(!dir.path().join("tasks.firm").exists());
+    }
+}
@@ -1,6 +1,7 @@
 mod helpers;
 
 use firm_core::graph::EntityGraph;
+use firm_lang::workspace::AddConfig;
 use firm_mcp::tools::add_entity::{AddEntityParams, execute};
 use helpers::create_workspace;
 use std::collections::HashMap;
@@ -36,12 +37,13 @@ schema task {
         let params = AddEntityParams {
             r#type: "task".to_string(),
             id: "bug_fix".to_string(),
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: None,
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
 
         assert!(result.is_ok());
         let result_val = result.unwrap();
@@ -87,12 +89,13 @@ schema event {
         let params = AddEntityParams {
             r#type: "event".to_string(),
             id: "launch".to_string(),
+            from_id: None,
             fields,
             to_file: Some("events/launch.firm".to_string()),
             list_item_types: None,
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_ok());
         let val = result.unwrap();
 
@@ -128,12 +131,13 @@ schema task {
         let params = AddEntityParams {
             r#type: "task".to_string(),
             id: "bug_fix".to_string(),
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: None,
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_err());
     }
 
@@ -162,12 +166,13 @@ task bug_fix {
         let params = AddEntityParams {
             r#type: "task".to_string(),
             id: "bug_fix".to_string(), // ID collision
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: None,
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_err());
     }
 
@@ -203,12 +208,13 @@ schema project {
         let params = AddEntityParams {
             r#type: "project".to_string(),
             id: "proj_one".to_string(),
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: Some(list_types),
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_ok());
         let val = result.unwrap();
 
@@ -246,12 +252,13 @@ schema task {
         let params = AddEntityParams {
             r#type: "task".to_string(),
             id: "bug_fix_tagged".to_string(),
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: Some(list_types),
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_ok());
         let val = result.unwrap();
 
@@ -286,15 +293,82 @@ schema task {
         let params = AddEntityParams {
             r#type: "task".to_string(),
             id: "bug_fix_no_type".to_string(),
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: None, // Missing list_item_types
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_add_entity_list_uses_schema_item_type() {
+        let (dir, mut workspace) = create_workspace(&[(
+            "schema.firm",
+            r#"
+schema task {
+    field { name = "title" type = "string" required = true }
+    field { name = "labels" type = "list" item_type = "string" required = false }
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!("Fix bug"));
+        fields.insert("labels".to_string(), serde_json::json!(["urgent"]));
+
+        let params = AddEntityParams {
+            r#type: "task".to_string(),
+            id: "bug_fix_labeled".to_string(),
+            from_id: None,
+            fields: fields.clone(),
+            to_file: None,
+            list_item_types: None,
+        };
+
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
+        let val = result.unwrap();
+        let content = fs::read_to_string(dir.path().join(&val.path)).unwrap();
+        assert!(content.contains(r#"labels = ["urgent"]"#));
+
+        // Items that don't match the schema's item type are rejected
+        fields.insert("labels".to_string(), serde_json::json!([1, 2]));
+        let params = AddEntityParams {
+            r#type: "task".to_string(),
+            id: "bug_fix_numbered".to_string(),
+            from_id: None,
+            fields,
+            to_file: None,
+            list_item_types: None,
+        };
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
+        assert!(result.is_err());
+
+        // A declared item type has to agree with the schema's
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!("Fix bug"));
+        fields.insert("labels".to_string(), serde_json::json!(["urgent"]));
+        let params = AddEntityParams {
+            r#type: "task".to_string(),
+            id: "bug_fix_mismatched".to_string(),
+            from_id: None,
+            fields,
+            to_file: None,
+            list_item_types: Some(HashMap::from([(
+                "labels".to_string(),
+                "reference".to_string(),
+            )])),
+        };
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
+        assert!(result.unwrap_err().contains("holds String items in the schema"));
+    }
+
     #[test]
     fn test_add_entity_invalid_list_item_type_error() {
         let (dir, mut workspace) = create_workspace(&[(
@@ -324,12 +398,111 @@ schema task {
         let params = AddEntityParams {
             r#type: "task".to_string(),
             id: "bug_fix_invalid".to_string(),
+            from_id: None,
             fields,
             to_file: None,
             list_item_types: Some(list_types),
         };
 
-        let result = execute(dir.path(), &build, &graph, &params);
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_add_entity_uses_configured_target() {
+        let (dir, mut workspace) = create_workspace(&[(
+            "schema.firm",
+            r#"
+schema task {
+    field { name = "title" type = "string" required = true }
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!("Fix bug"));
+
+        let params = AddEntityParams {
+            r#type: "task".to_string(),
+            id: "Bug Fix".to_string(),
+            from_id: None,
+            fields,
+            to_file: None,
+            list_item_types: None,
+        };
+        let add_config = AddConfig {
+            target: "{type}/{id}.firm".to_string(),
+        };
+
+        let result = execute(dir.path(), &build, &graph, &add_config, &params).unwrap();
+        assert_eq!(result.path, "task/bug_fix.firm");
+        assert!(dir.path().join("task").join("bug_fix.firm").exists());
+    }
+
+    #[test]
+    fn test_add_entity_from_existing() {
+        let (dir, mut workspace) = create_workspace(&[
+            (
+                "schema.firm",
+                r#"
+schema task {
+    field { name = "title" type = "string" required = true }
+    field { name = "priority" type = "integer" required = false }
+    field { name = "spec" type = "path" required = false }
+}
+"#,
+            ),
+            (
+                "tasks/main.firm",
+                r#"
+task fix_bug {
+    title = "Fix bug"
+    priority = 2
+    spec = path"../docs/spec.md"
+    tags = ["backend"]
+}
+"#,
+            ),
+        ]);
+
+        let build = workspace.build().unwrap();
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!("Fix another bug"));
+
+        let params = AddEntityParams {
+            r#type: "task".to_string(),
+            id: "fix_another_bug".to_string(),
+            from_id: Some("task.fix_bug".to_string()),
+            fields,
+            to_file: None,
+            list_item_types: None,
+        };
+
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params).unwrap();
+        let content = fs::read_to_string(dir.path().join(&result.path)).unwrap();
+        assert!(content.contains("task fix_another_bug {"));
+        assert!(content.contains(r#"title = "Fix another bug""#));
+        assert!(content.contains("priority = 2"));
+        assert!(content.contains(r#"spec = path"../docs/spec.md""#));
+        assert!(content.contains(r#"tags = ["backend"]"#));
+
+        // The entity to copy from has to exist and have the same type
+        let params = AddEntityParams {
+            r#type: "task".to_string(),
+            id: "fix_third_bug".to_string(),
+            from_id: Some("task.missing".to_string()),
+            fields: HashMap::new(),
+            to_file: None,
+            list_item_types: None,
+        };
+        let result = execute(dir.path(), &build, &graph, &AddConfig::default(), &params);
+        assert!(result.unwrap_err().contains("not found"));
+    }
 }
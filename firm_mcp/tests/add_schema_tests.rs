@@ -0,0 +1,166 @@
+mod helpers;
+
+use firm_mcp::tools::add_schema::{AddSchemaParams, SchemaFieldDefinition, execute, rollback};
+use helpers::create_workspace;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, field_type: &str) -> SchemaFieldDefinition {
+        SchemaFieldDefinition {
+            name: name.to_string(),
+            r#type: field_type.to_string(),
+            required: false,
+            allowed_values: None,
+            item_type: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_add_schema_success() {
+        let (dir, mut workspace) = create_workspace(&[("main.firm", "")]);
+        let build = workspace.build().unwrap();
+
+        let params = AddSchemaParams {
+            name: "invoice".to_string(),
+            fields: vec![
+                SchemaFieldDefinition {
+                    required: true,
+                    description: Some("The invoice number".to_string()),
+                    ..field("number", "string")
+                },
+                SchemaFieldDefinition {
+                    allowed_values: Some(vec!["draft".to_string(), "paid".to_string()]),
+                    ..field("status", "enum")
+                },
+                SchemaFieldDefinition {
+                    item_type: Some("reference".to_string()),
+                    ..field("lines", "list")
+                },
+            ],
+            to_file: None,
+        };
+
+        let result = execute(dir.path(), &build, &params).unwrap();
+        assert_eq!(result.path, "schemas/invoice.firm");
+        assert!(result.original_content.is_none());
+
+        let content = fs::read_to_string(dir.path().join("schemas/invoice.firm")).unwrap();
+        assert_eq!(content, result.dsl);
+        assert_eq!(
+            content,
+            r#"schema invoice {
+    // The invoice number
+    field {
+        name = "number"
+        type = "string"
+        required = true
+    }
+    field {
+        name = "status"
+        type = "enum"
+        allowed_values = ["draft", "paid"]
+        required = false
+    }
+    field {
+        name = "lines"
+        type = "list"
+        item_type = "reference"
+        required = false
+    }
+}
+"#
+        );
+
+        // Rolling back removes the new file
+        assert!(rollback(dir.path(), &result));
+        assert!(!dir.path().join("schemas/invoice.firm").exists());
+    }
+
+    #[test]
+    fn test_add_schema_appends_to_file() {
+        let existing =
+            "schema note {\n    field { name = \"text\" type = \"string\" required = true }\n}\n";
+        let (dir, mut workspace) = create_workspace(&[("schemas.firm", existing)]);
+        let build = workspace.build().unwrap();
+
+        let params = AddSchemaParams {
+            name: "memo".to_string(),
+            fields: vec![field("text", "string")],
+            to_file: Some("schemas.firm".to_string()),
+        };
+
+        let result = execute(dir.path(), &build, &params).unwrap();
+        let content = fs::read_to_string(dir.path().join("schemas.firm")).unwrap();
+        assert!(content.starts_with(existing));
+        assert!(content.ends_with(&format!("}}\n\n{}", result.dsl)));
+    }
+
+    #[test]
+    fn test_add_schema_rejects_invalid_definitions() {
+        let existing =
+            "schema note {\n    field { name = \"text\" type = \"string\" required = true }\n}\n";
+        let (dir, mut workspace) = create_workspace(&[("schemas.firm", existing)]);
+        let build = workspace.build().unwrap();
+
+        let cases = vec![
+            ("note", vec![field("text", "string")], "already exists"),
+            ("memo", vec![], "at least one field"),
+            ("memo", vec![field("text", "text")], "Invalid field type"),
+            (
+                "memo",
+                vec![field("text", "string"), field("text", "string")],
+                "more than once",
+            ),
+            ("memo", vec![field("status", "enum")], "allowed_values"),
+            (
+                "memo",
+                vec![SchemaFieldDefinition {
+                    allowed_values: Some(vec!["Open".to_string(), "open".to_string()]),
+                    ..field("status", "enum")
+                }],
+                "more than once",
+            ),
+            (
+                "memo",
+                vec![SchemaFieldDefinition {
+                    item_type: Some("string".to_string()),
+                    ..field("text", "string")
+                }],
+                "only list fields",
+            ),
+            (
+                "memo",
+                vec![SchemaFieldDefinition {
+                    item_type: Some("enum".to_string()),
+                    ..field("statuses", "list")
+                }],
+                "can't hold Enum items",
+            ),
+            (
+                "my-memo",
+                vec![field("text", "string")],
+                "Invalid schema name",
+            ),
+        ];
+
+        for (name, fields, expected) in cases {
+            let params = AddSchemaParams {
+                name: name.to_string(),
+                fields,
+                to_file: None,
+            };
+            let error = execute(dir.path(), &build, &params).unwrap_err();
+            assert!(
+                error.contains(expected),
+                "'{}' should contain '{}'",
+                error,
+                expected
+            );
+        }
+        assert!(!dir.path().join("schemas").exists());
+    }
+}
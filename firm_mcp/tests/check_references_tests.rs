@@ -0,0 +1,111 @@
+mod helpers;
+
+use firm_mcp::tools::ResponseFormat;
+use firm_mcp::tools::check_references::{CheckReferencesParams, execute};
+use helpers::{create_workspace, get_text, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+}
+"#;
+
+    #[test]
+    fn test_check_references_none_broken() {
+        let (dir, mut workspace) = create_workspace(&[
+            ("schemas.firm", SCHEMA),
+            (
+                "people.firm",
+                r#"
+person john { name = "John" }
+person jane {
+    name = "Jane"
+    manager = person.john
+}
+"#,
+            ),
+        ]);
+        let build = workspace.build().unwrap();
+        let params = CheckReferencesParams {
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, &build, dir.path(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "No broken references found.");
+    }
+
+    #[test]
+    fn test_check_references_reports_location() {
+        let (dir, mut workspace) = create_workspace(&[
+            ("schemas.firm", SCHEMA),
+            (
+                "people/team.firm",
+                r#"
+person jane {
+    name = "Jane"
+    manager = person.nobody
+}
+"#,
+            ),
+        ]);
+        let build = workspace.build().unwrap();
+        let params = CheckReferencesParams {
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, &build, dir.path(), &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Found 1 broken reference(s)"));
+        assert!(
+            text.contains(
+                "people/team.firm:4: person.jane.manager -> person.nobody (missing entity)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_check_references_json_format() {
+        let (dir, mut workspace) = create_workspace(&[
+            ("schemas.firm", SCHEMA),
+            (
+                "people.firm",
+                r#"
+person john { name = "John" }
+person jane {
+    name = "Jane"
+    manager = person.john.email
+}
+"#,
+            ),
+        ]);
+        let build = workspace.build().unwrap();
+        let params = CheckReferencesParams {
+            format: ResponseFormat::Json,
+        };
+
+        let result = execute(&workspace, &build, dir.path(), &params);
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "path": "people.firm",
+                "line": 5,
+                "entity": "person.jane",
+                "field": "manager",
+                "reference": "person.john.email",
+                "problem": "missing_field",
+            }])
+        );
+    }
+}
@@ -0,0 +1,88 @@
+mod helpers;
+
+use firm_mcp::tools::delete_entity::{DeleteEntityParams, execute, rollback};
+use helpers::create_workspace;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEOPLE: &str = r#"schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+}
+
+person john {
+    name = "John"
+}
+
+person jane {
+    name = "Jane"
+    manager = person.john
+}
+
+person bob {
+    name = "Bob"
+}
+"#;
+
+    fn params(id: &str, force: bool) -> DeleteEntityParams {
+        DeleteEntityParams {
+            r#type: "person".to_string(),
+            id: id.to_string(),
+            force,
+        }
+    }
+
+    #[test]
+    fn test_delete_entity_removes_its_block() {
+        let (dir, workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let result = execute(dir.path(), &workspace, &params("jane", false)).unwrap();
+        assert_eq!(result.entity_id.as_str(), "person.jane");
+        assert!(result.broken_references.is_empty());
+        assert_eq!(result.changed_files.len(), 1);
+        assert_eq!(result.changed_files[0].path, "people.firm");
+
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(
+            content,
+            PEOPLE.replace(
+                "person jane {\n    name = \"Jane\"\n    manager = person.john\n}\n\n",
+                ""
+            )
+        );
+
+        // Rolling back restores the entity
+        assert!(rollback(dir.path(), &result.changed_files));
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(content, PEOPLE);
+    }
+
+    #[test]
+    fn test_delete_entity_with_references() {
+        let (dir, workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        // The referencing entities are listed, and nothing is deleted
+        let error = execute(dir.path(), &workspace, &params("john", false)).unwrap_err();
+        assert!(error.contains("people.firm:12: field 'manager' of 'person.jane'"));
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(content, PEOPLE);
+
+        // With force, the broken references are reported
+        let result = execute(dir.path(), &workspace, &params("john", true)).unwrap();
+        assert_eq!(result.broken_references.len(), 1);
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert!(!content.contains("person john {"));
+        assert!(content.contains("manager = person.john"));
+    }
+
+    #[test]
+    fn test_delete_missing_entity() {
+        let (dir, workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let error = execute(dir.path(), &workspace, &params("nobody", false)).unwrap_err();
+        assert!(error.contains("not found"));
+    }
+}
@@ -0,0 +1,80 @@
+mod helpers;
+
+use firm_core::graph::Query;
+use firm_lang::defaults::all_default_schemas;
+use firm_lang::parser::query::parse_query;
+use firm_mcp::tools::dsl_reference::{DslReferenceParams, execute};
+use helpers::{get_text, is_error, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_reference() -> String {
+        let params = DslReferenceParams {
+            topic: "query".to_string(),
+        };
+        let result = execute(&params);
+        assert!(is_success(&result));
+        get_text(&result)
+    }
+
+    /// Gets the queries in the code blocks of a reference, without their comments.
+    fn example_queries(reference: &str) -> Vec<String> {
+        let mut queries = Vec::new();
+        let mut in_code_block = false;
+        for line in reference.lines() {
+            if line.starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+            // Skip the syntax outline, and the execution diagrams that annotate each step
+            let outline = line.contains(" ... ") || line.contains('→');
+            if in_code_block && line.starts_with("from ") && !outline {
+                let query = line.split(" # ").next().unwrap().trim();
+                queries.push(query.to_string());
+            }
+        }
+        queries
+    }
+
+    #[test]
+    fn test_query_reference_examples_parse() {
+        let queries = example_queries(&query_reference());
+        assert!(queries.len() > 20);
+
+        for query in queries {
+            let parsed = parse_query(&query)
+                .unwrap_or_else(|e| panic!("Failed to parse '{}': {}", query, e));
+            let converted: Result<Query, _> = parsed.try_into();
+            assert!(converted.is_ok(), "Failed to convert '{}'", query);
+        }
+    }
+
+    #[test]
+    fn test_query_reference_examples_use_default_schemas() {
+        let reference = query_reference();
+        let start = reference.find("## Example Queries").unwrap();
+        let end = start + reference[start..].find("\n## Query Execution").unwrap();
+        let schemas = all_default_schemas();
+
+        for query in example_queries(&reference[start..end]) {
+            let query: Query = parse_query(&query).unwrap().try_into().unwrap();
+            assert!(
+                query.validate(&schemas).is_ok(),
+                "'{}' doesn't match the default schemas",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_topic() {
+        let params = DslReferenceParams {
+            topic: "grammar".to_string(),
+        };
+        let result = execute(&params);
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("'grammar'"));
+    }
+}
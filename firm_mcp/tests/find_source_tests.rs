@@ -1,7 +1,9 @@
 mod helpers;
 
+use firm_mcp::tools::ResponseFormat;
 use firm_mcp::tools::find_source::{FindSourceParams, execute};
 use helpers::{create_workspace, get_text, is_error, is_success};
+use serde_json::Value;
 use tempfile::TempDir;
 
 #[cfg(test)]
@@ -27,12 +29,13 @@ person john {
         let params = FindSourceParams {
             r#type: "person".to_string(),
             id: "john".to_string(),
+            format: ResponseFormat::Text,
         };
 
         let result = execute(&workspace, dir.path(), &params);
 
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "people.firm");
+        assert_eq!(get_text(&result), "people.firm:6-8");
     }
 
     #[test]
@@ -53,12 +56,13 @@ person jane {
         let params = FindSourceParams {
             r#type: "person".to_string(),
             id: "jane".to_string(),
+            format: ResponseFormat::Text,
         };
 
         let result = execute(&workspace, dir.path(), &params);
 
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "entities/people.firm");
+        assert_eq!(get_text(&result), "entities/people.firm:6-8");
     }
 
     #[test]
@@ -79,6 +83,7 @@ person john {
         let params = FindSourceParams {
             r#type: "person".to_string(),
             id: "nonexistent".to_string(),
+            format: ResponseFormat::Text,
         };
 
         let result = execute(&workspace, dir.path(), &params);
@@ -86,6 +91,33 @@ person john {
         assert!(is_error(&result));
     }
 
+    #[test]
+    fn test_find_entity_source_not_found_suggests_similar_ids() {
+        let (dir, workspace) = create_workspace(&[(
+            "people.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john_doe {
+    name = "John Doe"
+}
+"#,
+        )]);
+
+        let params = FindSourceParams {
+            r#type: "person".to_string(),
+            id: "jon_doe".to_string(),
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("Did you mean 'person.john_doe'?"));
+    }
+
     #[test]
     fn test_find_entity_wrong_type() {
         let (dir, workspace) = create_workspace(&[(
@@ -105,6 +137,7 @@ person john {
         let params = FindSourceParams {
             r#type: "organization".to_string(),
             id: "john".to_string(),
+            format: ResponseFormat::Text,
         };
 
         let result = execute(&workspace, dir.path(), &params);
@@ -149,19 +182,21 @@ organization acme {
         let params = FindSourceParams {
             r#type: "person".to_string(),
             id: "alice".to_string(),
+            format: ResponseFormat::Text,
         };
         let result = execute(&workspace, dir.path(), &params);
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "people.firm");
+        assert_eq!(get_text(&result), "people.firm:2-4");
 
         // Find organization in orgs.firm
         let params = FindSourceParams {
             r#type: "organization".to_string(),
             id: "acme".to_string(),
+            format: ResponseFormat::Text,
         };
         let result = execute(&workspace, dir.path(), &params);
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "orgs.firm");
+        assert_eq!(get_text(&result), "orgs.firm:2-4");
     }
 
     #[test]
@@ -179,12 +214,13 @@ schema person {
         let params = FindSourceParams {
             r#type: "schema".to_string(),
             id: "person".to_string(),
+            format: ResponseFormat::Text,
         };
 
         let result = execute(&workspace, dir.path(), &params);
 
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "schemas/person.firm");
+        assert_eq!(get_text(&result), "schemas/person.firm:2-5");
     }
 
     #[test]
@@ -201,6 +237,7 @@ schema person {
         let params = FindSourceParams {
             r#type: "schema".to_string(),
             id: "organization".to_string(),
+            format: ResponseFormat::Text,
         };
 
         let result = execute(&workspace, dir.path(), &params);
@@ -233,19 +270,21 @@ schema project {
         let params = FindSourceParams {
             r#type: "schema".to_string(),
             id: "person".to_string(),
+            format: ResponseFormat::Text,
         };
         let result = execute(&workspace, dir.path(), &params);
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "schemas/person.firm");
+        assert_eq!(get_text(&result), "schemas/person.firm:2-4");
 
         // Find project schema
         let params = FindSourceParams {
             r#type: "schema".to_string(),
             id: "project".to_string(),
+            format: ResponseFormat::Text,
         };
         let result = execute(&workspace, dir.path(), &params);
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "schemas/project.firm");
+        assert_eq!(get_text(&result), "schemas/project.firm:2-4");
     }
 
     #[test]
@@ -259,10 +298,208 @@ schema project {
         let params = FindSourceParams {
             r#type: "person".to_string(),
             id: "anyone".to_string(),
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_error(&result));
+    }
+
+    #[test]
+    fn test_find_entity_source_lists_all_contributing_files() {
+        let (dir, workspace) = create_workspace(&[
+            (
+                "a_people.firm",
+                r#"
+person john {
+    name = "John Doe"
+}
+"#,
+            ),
+            (
+                "b_more_people.firm",
+                r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john {
+    name = "Johnny"
+}
+"#,
+            ),
+        ]);
+
+        let params = FindSourceParams {
+            r#type: "person".to_string(),
+            id: "john".to_string(),
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_text(&result),
+            "a_people.firm:2-4\nb_more_people.firm:5-7"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_entity_source_through_symlinked_workspace() {
+        let (dir, _) = create_workspace(&[(
+            "entities/people.firm",
+            r#"
+person jane {
+    name = "Jane Smith"
+}
+"#,
+        )]);
+
+        let link_dir = TempDir::new().unwrap();
+        let link_path = link_dir.path().join("workspace");
+        std::os::unix::fs::symlink(dir.path(), &link_path).unwrap();
+
+        let mut workspace = Workspace::new();
+        workspace.load_directory(&link_path).unwrap();
+
+        let params = FindSourceParams {
+            r#type: "person".to_string(),
+            id: "jane".to_string(),
+            format: ResponseFormat::Text,
         };
 
+        let result = execute(&workspace, &link_path, &params);
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "entities/people.firm:2-4");
+    }
+
+    #[test]
+    fn test_find_source_json_includes_block_lines() {
+        let (dir, workspace) = create_workspace(&[(
+            "people.firm",
+            r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john {
+    name = "John Doe"
+}
+"#,
+        )]);
+
+        let params = FindSourceParams {
+            r#type: "person".to_string(),
+            id: "john".to_string(),
+            format: ResponseFormat::Json,
+        };
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_success(&result));
+        let json: Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["type"], "person");
+        assert_eq!(json["id"], "john");
+        let sources = json["sources"].as_array().unwrap();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0]["path"], "people.firm");
+        assert_eq!(sources[0]["start_line"], 5);
+        assert_eq!(sources[0]["start_column"], 1);
+        assert_eq!(sources[0]["end_line"], 7);
+
+        let params = FindSourceParams {
+            r#type: "schema".to_string(),
+            id: "person".to_string(),
+            format: ResponseFormat::Json,
+        };
+        let result = execute(&workspace, dir.path(), &params);
+
+        let json: Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["sources"][0]["start_line"], 1);
+        assert_eq!(json["sources"][0]["end_line"], 3);
+    }
+
+    #[test]
+    fn test_find_entity_source_not_found_lists_closest_ids() {
+        let (dir, workspace) = create_workspace(&[(
+            "people.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+person bob { name = "Bob" }
+person carol { name = "Carol" }
+person dave { name = "Dave" }
+person erin { name = "Erin" }
+person frank { name = "Frank" }
+"#,
+        )]);
+
+        // Even an ID that's nothing like the others gets the closest five
+        let params = FindSourceParams {
+            r#type: "person".to_string(),
+            id: "zed".to_string(),
+            format: ResponseFormat::Text,
+        };
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_error(&result));
+        let text = get_text(&result);
+        assert!(text.starts_with("Entity 'zed' with type 'person' not found. Did you mean "));
+        assert_eq!(text.matches("'person.").count(), 5);
+
+        let params = FindSourceParams {
+            r#type: "project".to_string(),
+            id: "launch".to_string(),
+            format: ResponseFormat::Text,
+        };
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("The workspace has no entities of type 'project'."));
+    }
+
+    #[test]
+    fn test_find_source_json_not_found_lists_closest_ids() {
+        let (dir, workspace) = create_workspace(&[(
+            "people.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john { name = "John" }
+person jane { name = "Jane" }
+"#,
+        )]);
+
+        let params = FindSourceParams {
+            r#type: "person".to_string(),
+            id: "jon".to_string(),
+            format: ResponseFormat::Json,
+        };
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_error(&result));
+        let json: Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["sources"], serde_json::json!([]));
+        assert_eq!(
+            json["closest"],
+            serde_json::json!(["person.john", "person.jane"])
+        );
+
+        let params = FindSourceParams {
+            r#type: "schema".to_string(),
+            id: "persn".to_string(),
+            format: ResponseFormat::Json,
+        };
         let result = execute(&workspace, dir.path(), &params);
 
         assert!(is_error(&result));
+        let json: Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["closest"], serde_json::json!(["person"]));
     }
 }
@@ -0,0 +1,116 @@
+mod helpers;
+
+use firm_mcp::tools::ResponseFormat;
+use firm_mcp::tools::get_many::{EntityRef, GetManyParams, execute};
+use helpers::{create_workspace, get_text, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use firm_lang::workspace::Workspace;
+    use tempfile::TempDir;
+
+    const WORKSPACE: &str = r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema organization {
+    field { name = "name" type = "string" required = true }
+}
+
+person john { name = "John Doe" }
+person jane { name = "Jane Doe" }
+organization acme { name = "ACME" }
+"#;
+
+    fn create_test_workspace() -> (TempDir, Workspace) {
+        create_workspace(&[("data.firm", WORKSPACE)])
+    }
+
+    fn entity_ref(entity_type: &str, id: &str) -> EntityRef {
+        EntityRef::Parts {
+            r#type: entity_type.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_get_many_preserves_input_order() {
+        let (_dir, mut workspace) = create_test_workspace();
+        let build = workspace.build().unwrap();
+        let params = GetManyParams {
+            ids: vec![
+                entity_ref("organization", "acme"),
+                EntityRef::Composite("person.jane".to_string()),
+                entity_ref("person", "john"),
+            ],
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&build, &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        let acme = text.find("ACME").unwrap();
+        let jane = text.find("Jane Doe").unwrap();
+        let john = text.find("John Doe").unwrap();
+        assert!(acme < jane && jane < john);
+        assert!(!text.contains("Not found"));
+    }
+
+    #[test]
+    fn test_get_many_reports_not_found() {
+        let (_dir, mut workspace) = create_test_workspace();
+        let build = workspace.build().unwrap();
+        let params = GetManyParams {
+            ids: vec![
+                entity_ref("person", "john"),
+                entity_ref("person", "nobody"),
+                EntityRef::Composite("project.unknown".to_string()),
+            ],
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&build, &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("John Doe"));
+        assert!(text.contains("Not found: person.nobody, project.unknown"));
+    }
+
+    #[test]
+    fn test_get_many_json_format() {
+        let (_dir, mut workspace) = create_test_workspace();
+        let build = workspace.build().unwrap();
+        let params = GetManyParams {
+            ids: vec![
+                EntityRef::Composite("person.jane".to_string()),
+                entity_ref("person", "nobody"),
+            ],
+            format: ResponseFormat::Json,
+        };
+
+        let result = execute(&build, &params);
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        let entities = json["entities"].as_array().unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0]["id"], "person.jane");
+        assert_eq!(json["not_found"], serde_json::json!(["person.nobody"]));
+    }
+
+    #[test]
+    fn test_get_many_params_accept_both_id_forms() {
+        let params: GetManyParams = serde_json::from_value(serde_json::json!({
+            "ids": [{"type": "person", "id": "john"}, "person.jane"]
+        }))
+        .unwrap();
+
+        assert!(matches!(params.ids[0], EntityRef::Parts { .. }));
+        assert!(matches!(params.ids[1], EntityRef::Composite(_)));
+        assert_eq!(params.format, ResponseFormat::Text);
+    }
+}
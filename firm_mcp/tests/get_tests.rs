@@ -1,12 +1,24 @@
 mod helpers;
 
+use firm_core::ValueFormat;
+use firm_core::graph::EntityGraph;
+use firm_lang::workspace::WorkspaceBuild;
 use firm_mcp::tools::get::{GetParams, execute};
-use helpers::{create_workspace, get_text, is_error, is_success};
+use firm_mcp::tools::{ResponseFormat, ResponseLimits};
+use helpers::{create_workspace, get_text, get_texts, is_error, is_success};
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Helper to build the graph of a workspace build.
+    fn create_graph(build: &WorkspaceBuild) -> EntityGraph {
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+        graph.build();
+        graph
+    }
+
     #[test]
     fn test_get_entity_success() {
         let (_dir, mut workspace) = create_workspace(&[(
@@ -28,9 +40,17 @@ person john {
         let params = GetParams {
             r#type: "person".to_string(),
             id: "john".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -64,9 +84,17 @@ person bob {
         let params = GetParams {
             r#type: "person".to_string(),
             id: "bob".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -93,11 +121,64 @@ person john {
         let params = GetParams {
             r#type: "person".to_string(),
             id: "nonexistent".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
+        };
+
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_error(&result));
+    }
+
+    #[test]
+    fn test_get_entity_not_found_suggests_similar_ids() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john_doe {
+    name = "John"
+}
+
+person jane_doe {
+    name = "Jane"
+}
+
+person someone_else {
+    name = "Someone"
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = GetParams {
+            r#type: "person".to_string(),
+            id: "jon_doe".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_error(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Did you mean 'person.john_doe' or 'person.jane_doe'?"));
+        assert!(!text.contains("someone_else"));
     }
 
     #[test]
@@ -120,9 +201,17 @@ person john {
         let params = GetParams {
             r#type: "organization".to_string(),
             id: "john".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_error(&result));
     }
@@ -144,9 +233,17 @@ schema person {
         let params = GetParams {
             r#type: "schema".to_string(),
             id: "person".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -156,6 +253,37 @@ schema person {
         assert!(text.contains("age"));
     }
 
+    #[test]
+    fn test_get_schema_not_found_suggests_similar_names() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = GetParams {
+            r#type: "schema".to_string(),
+            id: "persn".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
+        };
+
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("Did you mean 'person'?"));
+    }
+
     #[test]
     fn test_get_schema_with_enum() {
         let (_dir, mut workspace) = create_workspace(&[(
@@ -172,9 +300,17 @@ schema task {
         let params = GetParams {
             r#type: "schema".to_string(),
             id: "task".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -197,9 +333,17 @@ schema person {
         let params = GetParams {
             r#type: "schema".to_string(),
             id: "organization".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
 
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_error(&result));
     }
@@ -225,8 +369,16 @@ person person {
         let params = GetParams {
             r#type: "person".to_string(),
             id: "person".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
         assert!(is_success(&result));
         assert!(get_text(&result).contains("A person named Person"));
 
@@ -234,11 +386,280 @@ person person {
         let params = GetParams {
             r#type: "schema".to_string(),
             id: "person".to_string(),
+            format: ResponseFormat::Text,
+            ..Default::default()
         };
-        let result = execute(&build, &params);
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
         assert!(is_success(&result));
         // Schema output should contain field definitions, not entity data
         assert!(get_text(&result).contains("name"));
         assert!(!get_text(&result).contains("A person named Person"));
     }
+
+    #[test]
+    fn test_get_entity_json() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "age" type = "integer" required = false }
+}
+
+person john {
+    name = "John Doe"
+    age = 42
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = GetParams {
+            r#type: "person".to_string(),
+            id: "john".to_string(),
+            format: ResponseFormat::Json,
+            ..Default::default()
+        };
+
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["id"], "person.john");
+        assert_eq!(json["type"], "person");
+        assert_eq!(json["fields"]["name"], "John Doe");
+        assert_eq!(json["fields"]["age"], 42);
+    }
+
+    const RELATED: &str = r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "assignee" type = "reference" required = false }
+}
+
+schema interaction {
+    field { name = "name" type = "string" required = true }
+    field { name = "people" type = "list" item_type = "reference" required = false }
+}
+
+person jane {
+    name = "Jane Doe"
+}
+
+person loner {
+    name = "Loner"
+}
+
+task first {
+    name = "First task"
+    assignee = person.jane
+}
+
+task second {
+    name = "Second task"
+    assignee = person.jane
+}
+
+task third {
+    name = "Third task"
+    assignee = person.jane
+}
+
+task orphaned {
+    name = "Orphaned task"
+    assignee = person.nobody
+}
+
+interaction call {
+    name = "Kickoff call"
+    people = [person.jane]
+}
+"#;
+
+    fn related_params(id: &str, format: ResponseFormat) -> GetParams {
+        GetParams {
+            r#type: "person".to_string(),
+            id: id.to_string(),
+            format,
+            include_related: true,
+            related_limit: Some(2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_entity_with_related() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", RELATED)]);
+        let build = workspace.build().unwrap();
+        let graph = create_graph(&build);
+
+        let result = execute(
+            &build,
+            &graph,
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &related_params("jane", ResponseFormat::Text),
+        );
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        let related = text.split("Related:").nth(1).unwrap();
+        assert!(related.contains("interaction:\n- interaction.call (interaction): Kickoff call"));
+        assert!(related.contains(
+            "task:\n- task.first (task): First task\n- task.second (task): Second task\n- +1 more"
+        ));
+        assert!(!related.contains("orphaned"));
+
+        // Filtered by type, as JSON
+        let mut params = related_params("jane", ResponseFormat::Json);
+        params.related_types = Some(vec!["interaction".to_string()]);
+        let result = execute(
+            &build,
+            &graph,
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(
+            json["related"]["interaction"]["entities"][0]["id"],
+            "interaction.call"
+        );
+        assert_eq!(
+            json["related"]["interaction"]["entities"][0]["name"],
+            "Kickoff call"
+        );
+        assert_eq!(json["related"]["interaction"]["more"], 0);
+        assert!(json["related"].get("task").is_none());
+    }
+
+    #[test]
+    fn test_get_entity_without_related() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", RELATED)]);
+        let build = workspace.build().unwrap();
+        let graph = create_graph(&build);
+
+        let result = execute(
+            &build,
+            &graph,
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &related_params("loner", ResponseFormat::Text),
+        );
+        assert!(get_text(&result).ends_with("Related: none"));
+
+        // A dangling reference doesn't relate the entity to anything
+        let mut params = related_params("orphaned", ResponseFormat::Json);
+        params.r#type = "task".to_string();
+        let result = execute(
+            &build,
+            &graph,
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["related"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_get_schema_json() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "status" type = "enum" allowed_values = ["todo", "done"] required = false }
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = GetParams {
+            r#type: "schema".to_string(),
+            id: "task".to_string(),
+            format: ResponseFormat::Json,
+            ..Default::default()
+        };
+
+        let result = execute(
+            &build,
+            &create_graph(&build),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["name"], "task");
+        assert_eq!(json["fields"][0]["name"], "name");
+        assert_eq!(json["fields"][0]["type"], "string");
+        assert_eq!(json["fields"][0]["required"], true);
+        assert_eq!(json["fields"][1]["name"], "status");
+        assert_eq!(json["fields"][1]["type"], "enum");
+        assert_eq!(json["fields"][1]["allowed_values"][1], "done");
+        assert!(json["fields"][0].get("allowed_values").is_none());
+    }
+
+    #[test]
+    fn test_get_entity_related_truncates_at_response_limits() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", RELATED)]);
+        let build = workspace.build().unwrap();
+        let graph = create_graph(&build);
+        let limits = ResponseLimits {
+            max_entities: Some(2),
+            max_bytes: None,
+        };
+
+        let result = execute(
+            &build,
+            &graph,
+            &ValueFormat::default(),
+            &limits,
+            &related_params("jane", ResponseFormat::Text),
+        );
+
+        // Cut in type order, with what's left out counted per type
+        let text = get_text(&result);
+        assert!(text.contains(
+            "task:\n- task.first (task): First task\n- +2 more\n\n\
+             [truncated: 2 of 4 related entities shown — refine with related_types/related_limit"
+        ));
+
+        // In JSON, the marker follows the entity so it stays parseable
+        let result = execute(
+            &build,
+            &graph,
+            &ValueFormat::default(),
+            &limits,
+            &related_params("jane", ResponseFormat::Json),
+        );
+        let texts = get_texts(&result);
+        assert_eq!(texts.len(), 2);
+        let json: serde_json::Value = serde_json::from_str(&texts[0]).unwrap();
+        assert_eq!(json["related"]["task"]["more"], 2);
+        assert!(texts[1].starts_with("[truncated: 2 of 4 related entities shown"));
+
+        let mut params = related_params("jane", ResponseFormat::Text);
+        params.unlimited = true;
+        let result = execute(&build, &graph, &ValueFormat::default(), &limits, &params);
+        assert!(!get_text(&result).contains("[truncated"));
+    }
 }
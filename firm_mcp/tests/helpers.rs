@@ -17,6 +17,18 @@ pub fn get_text(result: &CallToolResult) -> String {
     }
 }
 
+/// Extract the text of every content item in a CallToolResult.
+pub fn get_texts(result: &CallToolResult) -> Vec<String> {
+    result
+        .content
+        .iter()
+        .map(|content| match &content.raw {
+            RawContent::Text(text_content) => text_content.text.clone(),
+            _ => panic!("Expected text content"),
+        })
+        .collect()
+}
+
 /// Check if the result is a success.
 pub fn is_success(result: &CallToolResult) -> bool {
     result.is_error == Some(false)
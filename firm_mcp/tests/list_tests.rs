@@ -3,7 +3,8 @@ mod helpers;
 use std::fs;
 
 use firm_mcp::tools::list::{ListParams, execute};
-use helpers::{create_workspace, get_text, is_success};
+use firm_mcp::tools::{ResponseFormat, ResponseLimits};
+use helpers::{create_workspace, get_text, is_error, is_success};
 use tempfile::TempDir;
 
 #[cfg(test)]
@@ -28,10 +29,17 @@ person bob { name = "Bob" }
 
         let build = workspace.build().unwrap();
         let params = ListParams {
-            r#type: "person".to_string(),
+            r#type: Some("person".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -64,9 +72,16 @@ organization globex { name = "Globex" }
 
         // List only persons
         let params = ListParams {
-            r#type: "person".to_string(),
+            r#type: Some("person".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
         let text = get_text(&result);
         assert!(text.contains("person.alice"));
         assert!(text.contains("person.bob"));
@@ -74,9 +89,16 @@ organization globex { name = "Globex" }
 
         // List only organizations
         let params = ListParams {
-            r#type: "organization".to_string(),
+            r#type: Some("organization".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
         let text = get_text(&result);
         assert!(text.contains("organization.acme"));
         assert!(text.contains("organization.globex"));
@@ -98,10 +120,17 @@ person john { name = "John" }
 
         let build = workspace.build().unwrap();
         let params = ListParams {
-            r#type: "project".to_string(),
+            r#type: Some("project".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         assert_eq!(get_text(&result), "");
@@ -124,10 +153,17 @@ person john { name = "John" }
         let build = workspace.build().unwrap();
 
         let params = ListParams {
-            r#type: "person".to_string(),
+            r#type: Some("person".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         assert_eq!(get_text(&result), "");
@@ -154,10 +190,17 @@ schema project {
 
         let build = workspace.build().unwrap();
         let params = ListParams {
-            r#type: "schema".to_string(),
+            r#type: Some("schema".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -179,10 +222,17 @@ schema task {
 
         let build = workspace.build().unwrap();
         let params = ListParams {
-            r#type: "schema".to_string(),
+            r#type: Some("schema".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         assert_eq!(get_text(&result), "task");
@@ -211,14 +261,383 @@ schema project {
 
         let build = workspace.build().unwrap();
         let params = ListParams {
-            r#type: "schema".to_string(),
+            r#type: Some("schema".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&build, &params);
+        let result = execute(&build, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
         assert!(text.contains("person"));
         assert!(text.contains("project"));
     }
+
+    #[test]
+    fn test_list_entities_with_id_pattern() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+}
+
+task q1_plan { name = "Plan" }
+task q1_review { name = "Review" }
+task q2_plan { name = "Plan" }
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = ListParams {
+            r#type: Some("task".to_string()),
+            id_pattern: Some("q1_*".to_string()),
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        let result = execute(&build, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("task.q1_plan"));
+        assert!(text.contains("task.q1_review"));
+        assert!(!text.contains("task.q2_plan"));
+    }
+
+    #[test]
+    fn test_list_all_types_grouped() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema organization {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+organization acme { name = "ACME" }
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = ListParams {
+            r#type: Some("*".to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        let result = execute(&build, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_text(&result),
+            "organization:\norganization.acme\n\nperson:\nperson.alice"
+        );
+    }
+
+    #[test]
+    fn test_list_all_types_with_id_pattern() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema organization {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+person bob { name = "Bob" }
+organization acme { name = "ACME" }
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = ListParams {
+            r#type: Some("*".to_string()),
+            id_pattern: Some("a*".to_string()),
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        let result = execute(&build, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("person.alice"));
+        assert!(text.contains("organization.acme"));
+        assert!(!text.contains("person.bob"));
+    }
+
+    #[test]
+    fn test_list_invalid_id_pattern() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let params = ListParams {
+            r#type: Some("person".to_string()),
+            id_pattern: Some("[unclosed".to_string()),
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        let result = execute(&build, &ResponseLimits::default(), &params);
+
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("Invalid id_pattern"));
+    }
+
+    #[test]
+    fn test_list_json() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema organization {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+organization acme { name = "Acme" }
+"#,
+        )]);
+
+        let build = workspace.build().unwrap();
+        let list = |entity_type: &str| {
+            let params = ListParams {
+                r#type: Some(entity_type.to_string()),
+                id_pattern: None,
+                prefix: None,
+                contains: None,
+                limit: None,
+                cursor: None,
+                format: ResponseFormat::Json,
+                unlimited: false,
+            };
+            let result = execute(&build, &ResponseLimits::default(), &params);
+            assert!(is_success(&result));
+            serde_json::from_str::<serde_json::Value>(&get_text(&result)).unwrap()
+        };
+
+        assert_eq!(
+            list("person"),
+            serde_json::json!({ "items": ["person.alice"], "total": 1, "next_cursor": null })
+        );
+        assert_eq!(
+            list("schema")["items"],
+            serde_json::json!(["organization", "person"])
+        );
+        assert_eq!(
+            list("*")["items"],
+            serde_json::json!({
+                "organization": ["organization.acme"],
+                "person": ["person.alice"],
+            })
+        );
+    }
+
+    const TASKS: &str = r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+}
+
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+task write_docs { name = "Write docs" }
+task fix_bug { name = "Fix bug" }
+task review_docs { name = "Review docs" }
+task fix_typo { name = "Fix typo" }
+task deploy { name = "Deploy" }
+person jane { name = "Jane" }
+"#;
+
+    fn params(entity_type: Option<&str>) -> ListParams {
+        ListParams {
+            r#type: entity_type.map(|t| t.to_string()),
+            id_pattern: None,
+            prefix: None,
+            contains: None,
+            limit: None,
+            cursor: None,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        }
+    }
+
+    #[test]
+    fn test_list_pages_through_sorted_ids() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", TASKS)]);
+        let build = workspace.build().unwrap();
+
+        let mut page_params = params(Some("task"));
+        page_params.limit = Some(2);
+        page_params.format = ResponseFormat::Json;
+
+        let mut ids = Vec::new();
+        let mut pages = 0;
+        loop {
+            let result = execute(&build, &ResponseLimits::default(), &page_params);
+            assert!(is_success(&result));
+            let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+            assert_eq!(json["total"], 5);
+            ids.extend(
+                json["items"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|id| id.as_str().unwrap().to_string()),
+            );
+            pages += 1;
+            match json["next_cursor"].as_str() {
+                Some(cursor) => page_params.cursor = Some(cursor.to_string()),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        assert_eq!(
+            ids,
+            vec![
+                "task.deploy",
+                "task.fix_bug",
+                "task.fix_typo",
+                "task.review_docs",
+                "task.write_docs",
+            ]
+        );
+
+        // Text output says how to continue
+        let mut text_params = params(Some("task"));
+        text_params.limit = Some(2);
+        let result = execute(&build, &ResponseLimits::default(), &text_params);
+        assert_eq!(
+            get_text(&result),
+            "task.deploy\ntask.fix_bug\n\nShowing 2 of 5. Use cursor '2' to continue."
+        );
+
+        let mut invalid = params(Some("task"));
+        invalid.cursor = Some("not a cursor".to_string());
+        let result = execute(&build, &ResponseLimits::default(), &invalid);
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("Invalid cursor"));
+    }
+
+    #[test]
+    fn test_list_prefix_and_contains_filters() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", TASKS)]);
+        let build = workspace.build().unwrap();
+
+        let mut prefix = params(Some("task"));
+        prefix.prefix = Some("fix_".to_string());
+        assert_eq!(
+            get_text(&execute(&build, &ResponseLimits::default(), &prefix)),
+            "task.fix_bug\ntask.fix_typo"
+        );
+
+        let mut contains = params(Some("task"));
+        contains.contains = Some("docs".to_string());
+        assert_eq!(
+            get_text(&execute(&build, &ResponseLimits::default(), &contains)),
+            "task.review_docs\ntask.write_docs"
+        );
+    }
+
+    #[test]
+    fn test_list_without_type_counts_entities_per_type() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", TASKS)]);
+        let build = workspace.build().unwrap();
+
+        let result = execute(&build, &ResponseLimits::default(), &params(None));
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "person: 1\ntask: 5");
+
+        let mut json_params = params(None);
+        json_params.format = ResponseFormat::Json;
+        let json: serde_json::Value = serde_json::from_str(&get_text(&execute(
+            &build,
+            &ResponseLimits::default(),
+            &json_params,
+        )))
+        .unwrap();
+        assert_eq!(json, serde_json::json!({ "person": 1, "task": 5 }));
+    }
+
+    #[test]
+    fn test_list_truncates_at_response_limits() {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", TASKS)]);
+        let build = workspace.build().unwrap();
+        let limits = ResponseLimits {
+            max_entities: Some(2),
+            max_bytes: None,
+        };
+
+        let result = execute(&build, &limits, &params(Some("task")));
+        assert_eq!(
+            get_text(&result),
+            "task.deploy\ntask.fix_bug\n\n[truncated: 2 of 5 IDs shown — continue with cursor '2', \
+             or refine with id_pattern/prefix/contains, or set unlimited=true to get everything]"
+        );
+
+        let mut json_params = params(Some("task"));
+        json_params.format = ResponseFormat::Json;
+        let result = execute(&build, &limits, &json_params);
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["items"].as_array().unwrap().len(), 2);
+        assert_eq!(json["total"], 5);
+        assert_eq!(json["next_cursor"], "2");
+        assert_eq!(json["truncated"], true);
+
+        // A smaller requested limit isn't a truncation
+        json_params.limit = Some(1);
+        let result = execute(&build, &limits, &json_params);
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["truncated"], false);
+
+        // Asking for everything lifts the limits
+        let mut all_params = params(Some("task"));
+        all_params.unlimited = true;
+        let result = execute(&build, &limits, &all_params);
+        assert_eq!(get_text(&result).lines().count(), 5);
+    }
 }
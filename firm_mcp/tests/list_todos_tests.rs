@@ -0,0 +1,73 @@
+mod helpers;
+
+use firm_mcp::tools::ResponseFormat;
+use firm_mcp::tools::list_todos::{ListTodosParams, execute};
+use helpers::{create_workspace, get_text, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"// TODO: add the rest of the team
+person john {
+    // FIXME: wrong email
+    email = "john@example"
+}
+"#;
+
+    #[test]
+    fn test_list_todos_none_found() {
+        let (dir, workspace) =
+            create_workspace(&[("people.firm", "person john { name = \"John\" }\n")]);
+        let params = ListTodosParams {
+            markers: vec![],
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "No annotations found.");
+    }
+
+    #[test]
+    fn test_list_todos_reports_location_and_owner() {
+        let (dir, workspace) = create_workspace(&[("people/team.firm", SOURCE)]);
+        let params = ListTodosParams {
+            markers: vec![],
+            format: ResponseFormat::Text,
+        };
+
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Found 2 annotation(s)"));
+        assert!(text.contains("people/team.firm:1: TODO add the rest of the team"));
+        assert!(text.contains("people/team.firm:3: FIXME wrong email (person.john)"));
+    }
+
+    #[test]
+    fn test_list_todos_custom_markers_json() {
+        let (dir, workspace) = create_workspace(&[("people.firm", SOURCE)]);
+        let params = ListTodosParams {
+            markers: vec!["FIXME".to_string()],
+            format: ResponseFormat::Json,
+        };
+
+        let result = execute(&workspace, dir.path(), &params);
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "path": "people.firm",
+                "line": 3,
+                "marker": "FIXME",
+                "text": "wrong email",
+                "owner": "person.john",
+            }])
+        );
+    }
+}
@@ -0,0 +1,484 @@
+mod helpers;
+
+use std::fs;
+use std::time::Duration;
+
+use firm_lang::workspace::WorkspaceWatcher;
+use firm_mcp::FirmMcpServer;
+use helpers::{create_workspace, get_text, get_texts, is_error, is_success};
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, ErrorCode, ReadResourceRequestParam, ResourceContents,
+    ResourceUpdatedNotificationParam, SubscribeRequestParam,
+};
+use rmcp::service::{NotificationContext, RoleClient, RunningService, ServiceError};
+use rmcp::{ClientHandler, ErrorData as McpError, Peer, ServiceExt};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Client = RunningService<RoleClient, ()>;
+
+    const PEOPLE: &str = r#"schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+}
+
+person john {
+    name = "John"
+}
+
+person jane {
+    name = "Jane"
+    manager = person.john
+}
+
+query people {
+    query = "from person"
+    description = "Everyone"
+}
+"#;
+
+    /// Client that passes the resource notifications it gets on to the test.
+    struct NotificationRecorder {
+        sender: mpsc::UnboundedSender<String>,
+    }
+
+    impl ClientHandler for NotificationRecorder {
+        async fn on_resource_updated(
+            &self,
+            params: ResourceUpdatedNotificationParam,
+            _context: NotificationContext<RoleClient>,
+        ) {
+            let _ = self.sender.send(params.uri);
+        }
+
+        async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
+            let _ = self.sender.send("list_changed".to_string());
+        }
+    }
+
+    /// Helper to start a server on a temp workspace and connect a client to it.
+    ///
+    /// The server runs in-process on its own task, talking over an in-memory pipe.
+    async fn connect(files: &[(&str, &str)], read_only: bool) -> (TempDir, Client) {
+        connect_with(files, read_only, ()).await
+    }
+
+    /// Helper to connect a client with the given handler to a server on a temp workspace.
+    async fn connect_with<H: ClientHandler>(
+        files: &[(&str, &str)],
+        read_only: bool,
+        handler: H,
+    ) -> (TempDir, RunningService<RoleClient, H>) {
+        let (dir, _workspace) = create_workspace(files);
+        let server = FirmMcpServer::new(dir.path().to_path_buf())
+            .expect("Failed to create server")
+            .with_read_only(read_only);
+
+        let (server_transport, client_transport) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            if let Ok(service) = server.serve(server_transport).await {
+                let _ = service.waiting().await;
+            }
+        });
+        let client = handler
+            .serve(client_transport)
+            .await
+            .expect("Failed to connect");
+
+        (dir, client)
+    }
+
+    /// Helper to call a tool with JSON arguments.
+    async fn try_call(
+        client: &Peer<RoleClient>,
+        name: &'static str,
+        arguments: Value,
+    ) -> Result<CallToolResult, ServiceError> {
+        client
+            .call_tool(CallToolRequestParam {
+                name: name.into(),
+                arguments: arguments.as_object().cloned(),
+            })
+            .await
+    }
+
+    /// Helper to call a tool that's expected to answer with a result.
+    async fn call(
+        client: &Peer<RoleClient>,
+        name: &'static str,
+        arguments: Value,
+    ) -> CallToolResult {
+        try_call(client, name, arguments)
+            .await
+            .unwrap_or_else(|e| panic!("Calling '{}' failed: {}", name, e))
+    }
+
+    /// Helper to get the MCP error of a call that's rejected by the protocol.
+    async fn call_error(client: &Client, name: &'static str, arguments: Value) -> McpError {
+        match try_call(client, name, arguments).await {
+            Err(ServiceError::McpError(error)) => error,
+            other => panic!(
+                "Expected '{}' to fail with an MCP error, got {:?}",
+                name, other
+            ),
+        }
+    }
+
+    /// Helper to read the text of a resource.
+    async fn read_resource(client: &Client, uri: &str) -> Result<String, ServiceError> {
+        let result = client
+            .read_resource(ReadResourceRequestParam {
+                uri: uri.to_string(),
+            })
+            .await?;
+        let texts: Vec<String> = result
+            .contents
+            .into_iter()
+            .map(|contents| match contents {
+                ResourceContents::TextResourceContents { text, .. } => text,
+                _ => panic!("Expected text contents"),
+            })
+            .collect();
+        Ok(texts.join("\n"))
+    }
+
+    /// The names of the tools the client sees, sorted.
+    async fn tool_names(client: &Client) -> Vec<String> {
+        let mut names: Vec<String> = client
+            .list_all_tools()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_initialize_reports_capabilities() {
+        let (_dir, client) = connect(&[("people.firm", PEOPLE)], false).await;
+
+        let info = client.peer_info().expect("Expected server info");
+        assert!(info.capabilities.tools.is_some());
+        let resources = info.capabilities.resources.as_ref().unwrap();
+        assert_eq!(resources.subscribe, Some(true));
+        assert_eq!(resources.list_changed, Some(true));
+        assert!(
+            info.instructions
+                .as_ref()
+                .unwrap()
+                .contains("Use 'add_entity' to create new entities.")
+        );
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_every_tool_succeeds_through_the_router() {
+        let (dir, client) = connect(&[("people.firm", PEOPLE)], false).await;
+
+        // Reading tools first, then writing tools in an order where each one
+        // sees the changes of the ones before it.
+        let calls: Vec<(&'static str, Value)> = vec![
+            ("list", json!({ "type": "person" })),
+            ("get", json!({ "type": "person", "id": "john" })),
+            (
+                "get_many",
+                json!({ "ids": ["person.john", { "type": "person", "id": "jane" }] }),
+            ),
+            ("query", json!({ "query": "from person" })),
+            ("run_query", json!({ "name": "people" })),
+            ("stats", json!({ "type": "person" })),
+            ("related", json!({ "type": "person", "id": "john" })),
+            ("orphans", json!({})),
+            ("find_source", json!({ "type": "person", "id": "john" })),
+            ("read_source", json!({ "path": "people.firm" })),
+            ("validate", json!({ "path": "people.firm" })),
+            ("build", json!({})),
+            ("check_references", json!({})),
+            ("list_todos", json!({})),
+            ("dsl_reference", json!({ "topic": "dsl" })),
+            ("source_tree", json!({})),
+            ("search", json!({ "query": "jane" })),
+            ("search_source", json!({ "query": "John" })),
+            ("list_changes", json!({})),
+            (
+                "add_schema",
+                json!({
+                    "name": "project",
+                    "fields": [{ "name": "title", "type": "string", "required": true }],
+                    "to_file": "projects.firm"
+                }),
+            ),
+            (
+                "add_entity",
+                json!({
+                    "type": "person",
+                    "id": "bob",
+                    "fields": { "name": "Bob", "manager": "person.john" }
+                }),
+            ),
+            (
+                "add_entities",
+                json!({
+                    "entities": [{
+                        "type": "project",
+                        "id": "launch",
+                        "fields": { "title": "Launch" }
+                    }],
+                    "to_file": "projects.firm"
+                }),
+            ),
+            (
+                "update_entity",
+                json!({ "type": "person", "id": "bob", "set": { "name": "Robert" } }),
+            ),
+            (
+                "move_entity",
+                json!({ "type": "person", "id": "bob", "to_file": "team/bob.firm" }),
+            ),
+            (
+                "replace_source",
+                json!({
+                    "path": "people.firm",
+                    "old_string": "name = \"Jane\"",
+                    "new_string": "name = \"Janet\""
+                }),
+            ),
+            (
+                "write_source",
+                json!({ "path": "notes.firm", "content": "person ann {\n    name = \"Ann\"\n}\n" }),
+            ),
+            (
+                "delete_entity",
+                json!({ "type": "project", "id": "launch" }),
+            ),
+            ("delete_source", json!({ "path": "notes.firm" })),
+            ("undo_last_change", json!({})),
+        ];
+
+        // The calls cover every tool the client is offered
+        let mut called: Vec<String> = calls.iter().map(|(name, _)| name.to_string()).collect();
+        called.sort();
+        assert_eq!(called, tool_names(&client).await);
+
+        for (name, arguments) in calls {
+            let result = call(&client, name, arguments).await;
+            assert!(
+                is_success(&result),
+                "Expected '{}' to succeed: {:?}",
+                name,
+                get_texts(&result)
+            );
+        }
+
+        // The writes went through to disk and into the server state
+        let people = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert!(people.contains("name = \"Janet\""));
+        let bob = fs::read_to_string(dir.path().join("team/bob.firm")).unwrap();
+        assert!(bob.contains("name = \"Robert\""));
+        // Undoing the deletion brought the file back
+        assert!(dir.path().join("notes.firm").exists());
+        let result = call(&client, "get", json!({ "type": "person", "id": "ann" })).await;
+        assert!(is_success(&result));
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_only_server_rejects_writing_tools() {
+        let (dir, client) = connect(&[("people.firm", PEOPLE)], true).await;
+
+        let names = tool_names(&client).await;
+        assert!(names.iter().any(|name| name == "get"));
+        assert!(!names.iter().any(|name| name == "write_source"));
+
+        let error = call_error(
+            &client,
+            "write_source",
+            json!({ "path": "notes.firm", "content": "person ann {\n    name = \"Ann\"\n}\n" }),
+        )
+        .await;
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert!(!dir.path().join("notes.firm").exists());
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments_are_protocol_errors() {
+        let (_dir, client) = connect(&[("people.firm", PEOPLE)], false).await;
+
+        // A missing required parameter fails deserialization in the wrapper
+        let error = call_error(&client, "get", json!({ "type": "person" })).await;
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert!(error.message.contains("id"));
+
+        // So does a parameter of the wrong type
+        let error = call_error(&client, "list", json!({ "limit": "ten" })).await;
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+        // And a tool that doesn't exist
+        let error = call_error(&client, "no_such_tool", json!({})).await;
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tool_failures_are_error_results() {
+        let (_dir, client) = connect(&[("people.firm", PEOPLE)], false).await;
+
+        // Failures of the tool itself come back as results the model can read
+        let result = call(&client, "get", json!({ "type": "person", "id": "nobody" })).await;
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("nobody"));
+
+        let result = call(&client, "query", json!({ "query": "from person |" })).await;
+        assert!(is_error(&result));
+
+        let result = call(&client, "read_source", json!({ "path": "../outside.firm" })).await;
+        assert!(is_error(&result));
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_write_is_rolled_back_and_undo_restores() {
+        let (dir, client) = connect(&[("people.firm", PEOPLE)], false).await;
+        let path = dir.path().join("people.firm");
+
+        // A write that breaks a reference is rolled back
+        let broken = PEOPLE.replace("person.john", "person.nobody");
+        let result = call(
+            &client,
+            "write_source",
+            json!({ "path": "people.firm", "content": broken }),
+        )
+        .await;
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("Changes have been rolled back."));
+        assert_eq!(fs::read_to_string(&path).unwrap(), PEOPLE);
+
+        // Nothing was recorded, so there's nothing to undo yet
+        let result = call(&client, "undo_last_change", json!({})).await;
+        assert!(is_error(&result));
+
+        // A valid write is rebuilt into the server state right away
+        let renamed = PEOPLE.replace("name = \"John\"", "name = \"Johnny\"");
+        let result = call(
+            &client,
+            "write_source",
+            json!({ "path": "people.firm", "content": renamed }),
+        )
+        .await;
+        assert!(is_success(&result));
+        let result = call(
+            &client,
+            "get",
+            json!({ "type": "person", "id": "john", "format": "json" }),
+        )
+        .await;
+        let json: Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["fields"]["name"], "Johnny");
+
+        // Undoing it restores the file and the state
+        let result = call(&client, "undo_last_change", json!({})).await;
+        assert!(is_success(&result));
+        assert_eq!(fs::read_to_string(&path).unwrap(), PEOPLE);
+        let result = call(
+            &client,
+            "get",
+            json!({ "type": "person", "id": "john", "format": "json" }),
+        )
+        .await;
+        let json: Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json["fields"]["name"], "John");
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_and_read_resources() {
+        let (_dir, client) = connect(&[("people.firm", PEOPLE)], false).await;
+
+        let uris: Vec<String> = client
+            .list_all_resources()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|resource| resource.uri.clone())
+            .collect();
+        for uri in [
+            "firm://source/people.firm",
+            "firm://schema/person",
+            "firm://reference/dsl",
+            "firm://query/people",
+        ] {
+            assert!(uris.iter().any(|listed| listed == uri), "Missing {}", uri);
+        }
+
+        let source = read_resource(&client, "firm://source/people.firm")
+            .await
+            .unwrap();
+        assert_eq!(source, PEOPLE);
+
+        let schema = read_resource(&client, "firm://schema/person")
+            .await
+            .unwrap();
+        assert!(schema.contains("schema person"));
+
+        let people = read_resource(&client, "firm://query/people").await.unwrap();
+        assert!(people.contains("person.jane"));
+        assert!(people.contains("person.john"));
+
+        // Unknown resources are protocol errors
+        match read_resource(&client, "firm://source/missing.firm").await {
+            Err(ServiceError::McpError(error)) => {
+                assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND)
+            }
+            other => panic!("Expected a resource error, got {:?}", other),
+        }
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_external_edit_is_notified_after_a_tool_call() {
+        let (sender, mut notifications) = mpsc::unbounded_channel();
+        let (dir, client) = connect_with(
+            &[("people.firm", PEOPLE)],
+            false,
+            NotificationRecorder { sender },
+        )
+        .await;
+        client
+            .subscribe(SubscribeRequestParam {
+                uri: "firm://source/people.firm".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // An editor saves the file outside the MCP session, and a tool call
+        // picks up the change before the notifier next checks for edits
+        let edited = format!("{}\nperson ann {{\n    name = \"Ann\"\n}}\n", PEOPLE);
+        fs::write(dir.path().join("people.firm"), edited).unwrap();
+        tokio::time::sleep(WorkspaceWatcher::DEFAULT_DEBOUNCE + Duration::from_millis(50)).await;
+        let result = call(&client, "list", json!({ "type": "person" })).await;
+        assert!(is_success(&result));
+
+        let uri = tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+            .await
+            .expect("Timed out waiting for a notification")
+            .unwrap();
+        assert_eq!(uri, "firm://source/people.firm");
+
+        client.cancel().await.unwrap();
+    }
+}
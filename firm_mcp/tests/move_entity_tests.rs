@@ -0,0 +1,161 @@
+mod helpers;
+
+use firm_core::EntityId;
+use firm_mcp::tools::move_entity::{
+    MoveEntityParams, MoveEntityResult, execute, rollback, validation_error_result,
+};
+use firm_mcp::tools::update_entity::ChangedFile;
+use helpers::{create_workspace, get_text};
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+"#;
+
+    const GENERATED: &str = r#"person john {
+    name = "John"
+}
+
+// Jane leads the team.
+// She joined in 2024.
+person jane {
+    name = "Jane"
+}
+
+/* Bob is a contractor */
+person bob {
+    name = "Bob"
+}
+"#;
+
+    fn params(id: &str, to_file: &str) -> MoveEntityParams {
+        MoveEntityParams {
+            r#type: "person".to_string(),
+            id: id.to_string(),
+            to_file: to_file.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_move_entity_to_new_file_with_its_comments() {
+        let (dir, workspace) = create_workspace(&[
+            ("schema.firm", SCHEMA),
+            ("generated/person.firm", GENERATED),
+        ]);
+
+        let result = execute(dir.path(), &workspace, &params("jane", "people/team.firm")).unwrap();
+        assert_eq!(result.entity_id.as_str(), "person.jane");
+        assert_eq!(result.from_paths, vec!["generated/person.firm"]);
+        assert_eq!(result.to_path, "people/team.firm");
+        assert!(result.created_new_file);
+
+        let source = fs::read_to_string(dir.path().join("generated/person.firm")).unwrap();
+        assert_eq!(
+            source,
+            "person john {\n    name = \"John\"\n}\n\n/* Bob is a contractor */\nperson bob {\n    name = \"Bob\"\n}\n"
+        );
+        let destination = fs::read_to_string(dir.path().join("people/team.firm")).unwrap();
+        assert_eq!(
+            destination,
+            "// Jane leads the team.\n// She joined in 2024.\nperson jane {\n    name = \"Jane\"\n}\n"
+        );
+
+        // Rolling back restores the source and removes the created file
+        assert!(rollback(dir.path(), &result));
+        let source = fs::read_to_string(dir.path().join("generated/person.firm")).unwrap();
+        assert_eq!(source, GENERATED);
+        assert!(!dir.path().join("people/team.firm").exists());
+    }
+
+    #[test]
+    fn test_move_entity_appends_to_existing_file() {
+        let (dir, workspace) = create_workspace(&[
+            ("schema.firm", SCHEMA),
+            ("generated/person.firm", GENERATED),
+            (
+                "people/team.firm",
+                "person alice {\n    name = \"Alice\"\n}",
+            ),
+        ]);
+
+        let result = execute(dir.path(), &workspace, &params("bob", "people/team.firm")).unwrap();
+        assert!(!result.created_new_file);
+        assert_eq!(result.changed_files.len(), 2);
+
+        let source = fs::read_to_string(dir.path().join("generated/person.firm")).unwrap();
+        assert!(source.ends_with("person jane {\n    name = \"Jane\"\n}\n"));
+        let destination = fs::read_to_string(dir.path().join("people/team.firm")).unwrap();
+        assert_eq!(
+            destination,
+            "person alice {\n    name = \"Alice\"\n}\n\n/* Bob is a contractor */\nperson bob {\n    name = \"Bob\"\n}\n"
+        );
+
+        // Rolling back restores both files
+        assert!(rollback(dir.path(), &result));
+        let source = fs::read_to_string(dir.path().join("generated/person.firm")).unwrap();
+        assert_eq!(source, GENERATED);
+        let destination = fs::read_to_string(dir.path().join("people/team.firm")).unwrap();
+        assert_eq!(destination, "person alice {\n    name = \"Alice\"\n}");
+    }
+
+    #[test]
+    fn test_move_entity_errors() {
+        let (dir, workspace) = create_workspace(&[
+            ("schema.firm", SCHEMA),
+            ("generated/person.firm", GENERATED),
+        ]);
+
+        let error = execute(dir.path(), &workspace, &params("nobody", "team.firm")).unwrap_err();
+        assert!(error.contains("not found"));
+
+        let error = execute(
+            dir.path(),
+            &workspace,
+            &params("john", "generated/person.firm"),
+        )
+        .unwrap_err();
+        assert!(error.contains("already in generated/person.firm"));
+
+        let error = execute(dir.path(), &workspace, &params("john", "team.txt")).unwrap_err();
+        assert!(error.contains(".firm"));
+
+        // Nothing was written
+        let source = fs::read_to_string(dir.path().join("generated/person.firm")).unwrap();
+        assert_eq!(source, GENERATED);
+    }
+
+    fn moved_between(from_paths: &[&str], created_new_file: bool) -> MoveEntityResult {
+        MoveEntityResult {
+            entity_id: EntityId::new("person.john"),
+            from_paths: from_paths.iter().map(|path| path.to_string()).collect(),
+            to_path: "people.firm".to_string(),
+            created_new_file,
+            changed_files: from_paths
+                .iter()
+                .map(|path| ChangedFile {
+                    path: path.to_string(),
+                    original_content: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_validation_error_result_counts_restored_files() {
+        let result = moved_between(&["a.firm", "b.firm"], true);
+        let text = get_text(&validation_error_result("broken", &result, true));
+        assert!(text.ends_with("All 3 files have been restored."));
+
+        let result = moved_between(&["a.firm"], false);
+        let text = get_text(&validation_error_result("broken", &result, true));
+        assert!(text.ends_with("The file has been restored."));
+
+        let text = get_text(&validation_error_result("broken", &result, false));
+        assert!(text.ends_with("Warning: Failed to restore the files."));
+    }
+}
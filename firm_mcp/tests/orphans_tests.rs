@@ -0,0 +1,59 @@
+mod helpers;
+
+use firm_core::graph::EntityGraph;
+use firm_mcp::tools::orphans::{OrphansParams, execute};
+use helpers::{create_workspace, get_text, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_graph() -> EntityGraph {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+}
+
+schema note {
+    field { name = "text" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+person bob { name = "Bob" manager = person.alice }
+person charlie { name = "Charlie" }
+note draft { text = "Draft" }
+"#,
+        )]);
+        let build = workspace.build().unwrap();
+
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities).unwrap();
+        graph.build();
+        graph
+    }
+
+    #[test]
+    fn test_orphans() {
+        let graph = create_graph();
+
+        let result = execute(&graph, &OrphansParams { r#type: None });
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "note.draft\nperson.charlie");
+
+        let params = OrphansParams {
+            r#type: Some("person".to_string()),
+        };
+        let result = execute(&graph, &params);
+        assert_eq!(get_text(&result), "person.charlie");
+
+        let params = OrphansParams {
+            r#type: Some("project".to_string()),
+        };
+        let result = execute(&graph, &params);
+        assert!(is_success(&result));
+        assert!(get_text(&result).contains("No disconnected entities"));
+    }
+}
@@ -1,8 +1,11 @@
 mod helpers;
 
 use firm_core::graph::EntityGraph;
+use firm_core::{CurrencyFormat, ValueFormat};
+use firm_lang::workspace::TimezoneConfig;
 use firm_mcp::tools::query::{QueryParams, execute};
-use helpers::{create_workspace, get_text, is_error, is_success};
+use firm_mcp::tools::{ResponseFormat, ResponseLimits};
+use helpers::{create_workspace, get_text, get_texts, is_error, is_success};
 
 #[cfg(test)]
 mod tests {
@@ -36,9 +39,19 @@ person charlie { name = "Charlie" }
 
         let params = QueryParams {
             query: "from person".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -62,9 +75,19 @@ person alice { name = "Alice" }
 
         let params = QueryParams {
             query: "from organization".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         // Unknown entity type should return an error with helpful info
         assert!(is_error(&result));
@@ -90,9 +113,19 @@ person charlie { name = "Charlie" }
 
         let params = QueryParams {
             query: "from person | where name == \"Bob\"".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -118,9 +151,19 @@ person charlie { name = "Charlie Smith" }
 
         let params = QueryParams {
             query: "from person | where name contains \"Smith\"".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -147,9 +190,19 @@ task task3 { title = "Third" completed = true }
 
         let params = QueryParams {
             query: "from task | where completed == false".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -176,9 +229,19 @@ person old { name = "Old" age = 60 }
 
         let params = QueryParams {
             query: "from person | where age > 30".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -200,9 +263,19 @@ schema person {
 
         let params = QueryParams {
             query: "this is not valid query syntax".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_error(&result));
     }
@@ -220,10 +293,374 @@ schema person {
 
         let params = QueryParams {
             query: "".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
 
         assert!(is_error(&result));
     }
+
+    #[test]
+    fn test_query_strict_rejects_unknown_field() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+"#,
+        )]);
+        let build = workspace.build().unwrap();
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+        graph.build();
+
+        let params = QueryParams {
+            query: "from person | order nmae".to_string(),
+            strict: true,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        let result = execute(
+            &graph,
+            &build.schemas,
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_error(&result));
+        let text = get_text(&result);
+        assert!(text.contains("nmae"));
+        assert!(text.contains("person"));
+
+        // The same query is accepted in lenient mode
+        let params = QueryParams {
+            query: "from person | order nmae".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        let result = execute(
+            &graph,
+            &build.schemas,
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_success(&result));
+    }
+
+    #[test]
+    fn test_query_date_only_conditions_use_configured_timezone() {
+        let (_dir, mut workspace) = create_workspace(&[(
+            "data.firm",
+            r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "due" type = "datetime" required = true }
+}
+
+task late { name = "Late" due = 2025-09-30 at 20:00 UTC }
+"#,
+        )]);
+        let build = workspace.build().unwrap();
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+        graph.build();
+
+        let params = QueryParams {
+            query: "from task | where due == 2025-10-01".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+
+        // Late on the 30th in UTC is already the 1st in UTC+9
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        assert!(get_text(&result).contains("No entities found"));
+
+        let timezone = TimezoneConfig {
+            default: Some("UTC+9".to_string()),
+            render: true,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &timezone,
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        assert!(is_success(&result));
+        assert!(get_text(&result).contains("2025-10-01 05:00:00 +09:00"));
+    }
+
+    #[test]
+    fn test_query_json() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "hours" type = "integer" required = true }
+    field { name = "status" type = "enum" allowed_values = ["open", "done"] required = false }
+}
+
+task write { name = "Write" hours = 3 status = enum"open" }
+task review { name = "Review" hours = 2 }
+"#,
+        )]);
+
+        let params = QueryParams {
+            query: "from task | where hours > 2".to_string(),
+            strict: false,
+            format: ResponseFormat::Json,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "id": "task.write",
+                "type": "task",
+                "fields": { "name": "Write", "hours": 3, "status": { "enum": "open" } },
+            }])
+        );
+
+        // No matches is an empty array rather than a message
+        let params = QueryParams {
+            query: "from task | where hours > 5".to_string(),
+            strict: false,
+            format: ResponseFormat::Json,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        assert_eq!(get_text(&result), "[]");
+
+        // Aggregations use their serialized form
+        let params = QueryParams {
+            query: "from task | count".to_string(),
+            strict: false,
+            format: ResponseFormat::Json,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json, serde_json::json!({ "Count": 2 }));
+    }
+
+    #[test]
+    fn test_query_truncates_at_response_limits() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+person bob { name = "Bob" }
+person charlie { name = "Charlie" }
+"#,
+        )]);
+        let limits = ResponseLimits {
+            max_entities: Some(2),
+            max_bytes: None,
+        };
+
+        let mut params = QueryParams {
+            query: "from person".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &limits,
+            &params,
+        );
+        let text = get_text(&result);
+        assert_eq!(text.matches("\n---\n").count(), 1);
+        assert!(text.ends_with(
+            "\n\n[truncated: 2 of 3 entities shown — refine with limit/where, \
+             or set unlimited=true to get everything]"
+        ));
+
+        // In JSON, the marker follows the array so it stays parseable
+        params.format = ResponseFormat::Json;
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &limits,
+            &params,
+        );
+        let texts = get_texts(&result);
+        assert_eq!(texts.len(), 2);
+        let json: serde_json::Value = serde_json::from_str(&texts[0]).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert!(texts[1].starts_with("[truncated: 2 of 3 entities shown"));
+
+        // Asking for everything lifts the limits
+        params.unlimited = true;
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &limits,
+            &params,
+        );
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_query_truncates_at_byte_limit() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema note {
+    field { name = "text" type = "string" required = true }
+}
+
+note first { text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit" }
+note second { text = "Sed do eiusmod tempor incididunt ut labore et dolore" }
+"#,
+        )]);
+        let limits = ResponseLimits {
+            max_entities: None,
+            max_bytes: Some(60),
+        };
+
+        let params = QueryParams {
+            query: "from note".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            &limits,
+            &params,
+        );
+
+        // The first entity is shown even though it's larger than the limit
+        let text = get_text(&result);
+        assert!(!text.contains("\n---\n"));
+        assert!(text.contains("[truncated: 1 of 2 entities shown"));
+    }
+
+    #[test]
+    fn test_query_text_uses_value_format() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema invoice {
+    field { name = "amount" type = "currency" required = true }
+}
+
+invoice first { amount = 100.5 USD }
+"#,
+        )]);
+        let value_format = ValueFormat {
+            currency: CurrencyFormat::Symbol,
+            date_format: None,
+        };
+
+        let mut params = QueryParams {
+            query: "from invoice".to_string(),
+            strict: false,
+            format: ResponseFormat::Text,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &value_format,
+            &ResponseLimits::default(),
+            &params,
+        );
+        assert!(get_text(&result).contains("Amount: $100.50"));
+
+        params.query = "from invoice | sum amount".to_string();
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &value_format,
+            &ResponseLimits::default(),
+            &params,
+        );
+        assert_eq!(get_text(&result), "$100.50");
+
+        // JSON keeps the raw values
+        params.format = ResponseFormat::Json;
+        let result = execute(
+            &graph,
+            &[],
+            &TimezoneConfig::default(),
+            &value_format,
+            &ResponseLimits::default(),
+            &params,
+        );
+        assert!(!get_text(&result).contains('$'));
+    }
 }
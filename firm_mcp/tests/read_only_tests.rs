@@ -0,0 +1,83 @@
+use firm_mcp::FirmMcpServer;
+use rmcp::ServerHandler;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The names of the tools a server offers, sorted.
+    fn tool_names(server: &FirmMcpServer) -> Vec<String> {
+        let mut names: Vec<String> = server
+            .tools()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_read_only_server_leaves_out_writing_tools() {
+        let dir = TempDir::new().unwrap();
+        let server = FirmMcpServer::new(dir.path().to_path_buf()).unwrap();
+        let read_only_server = FirmMcpServer::new_read_only(dir.path().to_path_buf()).unwrap();
+        assert!(!server.is_read_only());
+        assert!(read_only_server.is_read_only());
+
+        let all_tools = tool_names(&server);
+        let read_only_tools = tool_names(&read_only_server);
+        assert_ne!(all_tools, read_only_tools);
+
+        for writing_tool in [
+            "add_entities",
+            "add_entity",
+            "add_schema",
+            "delete_entity",
+            "delete_source",
+            "move_entity",
+            "replace_source",
+            "undo_last_change",
+            "update_entity",
+            "write_source",
+        ] {
+            assert!(all_tools.iter().any(|name| name == writing_tool));
+            assert!(!read_only_tools.iter().any(|name| name == writing_tool));
+        }
+        for reading_tool in [
+            "get",
+            "list",
+            "query",
+            "read_source",
+            "run_query",
+            "stats",
+            "validate",
+        ] {
+            assert!(read_only_tools.iter().any(|name| name == reading_tool));
+        }
+
+        // Every tool that's left says so to clients
+        assert!(read_only_server.tools().iter().all(|tool| {
+            tool.annotations.as_ref().and_then(|a| a.read_only_hint) == Some(true)
+        }));
+
+        // Switching back offers every tool again
+        let server = read_only_server.with_read_only(false);
+        assert_eq!(tool_names(&server), all_tools);
+    }
+
+    #[test]
+    fn test_read_only_server_instructions() {
+        let dir = TempDir::new().unwrap();
+
+        let server = FirmMcpServer::new(dir.path().to_path_buf()).unwrap();
+        let instructions = server.get_info().instructions.unwrap();
+        assert!(!instructions.contains("read-only"));
+        assert!(instructions.contains("Use 'add_entity' to create new entities."));
+
+        let server = server.with_read_only(true);
+        let instructions = server.get_info().instructions.unwrap();
+        assert!(instructions.contains("This server is in read-only mode"));
+        assert!(!instructions.contains("add_entity"));
+    }
+}
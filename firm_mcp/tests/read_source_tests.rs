@@ -2,8 +2,9 @@ mod helpers;
 
 use std::fs;
 
+use firm_lang::workspace::content_hash;
 use firm_mcp::tools::read_source::{ReadSourceParams, execute};
-use helpers::{create_workspace, get_text, is_error, is_success};
+use helpers::{create_workspace, get_text, get_texts, is_error, is_success};
 use tempfile::TempDir;
 
 #[cfg(test)]
@@ -26,6 +27,7 @@ person john {
 
         let params = ReadSourceParams {
             path: "data.firm".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -45,6 +47,7 @@ person john {
 
         let params = ReadSourceParams {
             path: "schemas/person.firm".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -72,6 +75,7 @@ task example {
 
         let params = ReadSourceParams {
             path: "tasks.firm".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -87,6 +91,7 @@ task example {
 
         let params = ReadSourceParams {
             path: "nonexistent.firm".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -102,6 +107,7 @@ task example {
 
         let params = ReadSourceParams {
             path: "readme.txt".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -116,6 +122,7 @@ task example {
         // Try to read a file outside the workspace using ../
         let params = ReadSourceParams {
             path: "../../../etc/passwd".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -130,6 +137,7 @@ task example {
         // Try to use an absolute path
         let params = ReadSourceParams {
             path: "/etc/passwd".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -145,6 +153,7 @@ task example {
 
         let params = ReadSourceParams {
             path: "empty.firm".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
@@ -162,10 +171,34 @@ task example {
 
         let params = ReadSourceParams {
             path: "a/b/c/d/deep.firm".to_string(),
+            include_hash: false,
         };
 
         let result = execute(dir.path(), &params);
 
         assert!(is_success(&result));
     }
+
+    #[test]
+    fn test_read_source_include_hash() {
+        let content =
+            "schema person {\n    field { name = \"name\" type = \"string\" required = true }\n}\n";
+        let (dir, _workspace) = create_workspace(&[("people.firm", content)]);
+
+        let params = ReadSourceParams {
+            path: "people.firm".to_string(),
+            include_hash: true,
+        };
+
+        let result = execute(dir.path(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_texts(&result),
+            vec![
+                content.to_string(),
+                format!("base_hash: {}", content_hash(content))
+            ]
+        );
+    }
 }
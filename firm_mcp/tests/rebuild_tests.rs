@@ -0,0 +1,41 @@
+mod helpers;
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use firm_mcp::FirmMcpServer;
+use helpers::create_workspace;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rebuild_only_reads_changed_files() {
+        let (dir, _workspace) = create_workspace(&[
+            ("people.firm", "person jane {\n    name = \"Jane\"\n}\n"),
+            ("orgs.firm", "organization acme {\n    name = \"Acme\"\n}\n"),
+            ("projects.firm", "project launch {\n    name = \"Launch\"\n}\n"),
+        ]);
+
+        // Count reads through an injected reader
+        let reads = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reads.clone();
+        let server = FirmMcpServer::new(dir.path().to_path_buf())
+            .unwrap()
+            .with_source_reader(move |path| {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                recorded.lock().unwrap().push(name);
+                fs::read_to_string(path)
+            });
+
+        let people = dir.path().join("people.firm");
+        fs::write(&people, "person jane {\n    name = \"Jane Doe\"\n}\n").unwrap();
+        server.rebuild_files(&[people]).await.unwrap();
+        assert_eq!(*reads.lock().unwrap(), vec!["people.firm".to_string()]);
+
+        // Nothing changed since, so nothing is read again
+        server.rebuild_files(&[]).await.unwrap();
+        assert_eq!(reads.lock().unwrap().len(), 1);
+    }
+}
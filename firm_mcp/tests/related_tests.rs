@@ -1,6 +1,7 @@
 mod helpers;
 
 use firm_core::graph::EntityGraph;
+use firm_mcp::tools::ResponseLimits;
 use firm_mcp::tools::related::{RelatedDirection, RelatedParams, execute};
 use helpers::{create_workspace, get_text, is_error, is_success};
 
@@ -40,9 +41,13 @@ person charlie { name = "Charlie" manager = person.alice }
             r#type: "person".to_string(),
             id: "alice".to_string(),
             direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -70,9 +75,13 @@ person bob { name = "Bob" manager = person.alice }
             r#type: "person".to_string(),
             id: "alice".to_string(),
             direction: Some(RelatedDirection::Incoming),
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -99,9 +108,13 @@ person bob { name = "Bob" manager = person.alice }
             r#type: "person".to_string(),
             id: "bob".to_string(),
             direction: Some(RelatedDirection::Outgoing),
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -126,9 +139,13 @@ person bob { name = "Bob" }
             r#type: "person".to_string(),
             id: "alice".to_string(),
             direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         assert!(get_text(&result).contains("No related entities"));
@@ -151,9 +168,13 @@ person alice { name = "Alice" }
             r#type: "person".to_string(),
             id: "nonexistent".to_string(),
             direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_error(&result));
         assert!(get_text(&result).contains("not found"));
@@ -184,9 +205,13 @@ person bob { name = "Bob" employer = organization.acme }
             r#type: "organization".to_string(),
             id: "acme".to_string(),
             direction: Some(RelatedDirection::Incoming),
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -216,9 +241,13 @@ person employee { name = "Employee" manager = person.manager }
             r#type: "person".to_string(),
             id: "vp".to_string(),
             direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -248,11 +277,253 @@ person bob { name = "Bob" manager = person.alice }
             r#type: "person".to_string(),
             id: "bob".to_string(),
             direction: Some(RelatedDirection::Incoming),
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
         };
 
-        let result = execute(&graph, &params);
+        let result = execute(&graph, &ResponseLimits::default(), &params);
 
         assert!(is_success(&result));
         assert!(get_text(&result).contains("No related entities"));
     }
+
+    #[test]
+    fn test_related_with_edges_explains_connections() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema project {
+    field { name = "name" type = "string" required = true }
+}
+
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "assignee" type = "reference" required = false }
+    field { name = "project" type = "reference" required = false }
+}
+
+person john { name = "John" }
+project apollo { name = "Apollo" }
+task launch { name = "Launch" assignee = person.john project = project.apollo }
+"#,
+        )]);
+
+        // John reaches Apollo through the task that references both
+        let params = RelatedParams {
+            r#type: "person".to_string(),
+            id: "john".to_string(),
+            direction: None,
+            degrees: Some(2),
+            related_type: None,
+            include_edges: true,
+            unlimited: false,
+        };
+
+        let result = execute(&graph, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("project.apollo"));
+        assert!(text.contains(
+            "(task.launch, assignee, person.john): task.launch.assignee -> person.john"
+        ));
+        assert!(text.contains(
+            "(project.apollo, project, task.launch): task.launch.project -> project.apollo"
+        ));
+    }
+
+    #[test]
+    fn test_related_returns_summaries() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema note {
+    field { name = "author" type = "reference" required = true }
+}
+
+person alice { name = "Alice" }
+note kickoff { author = person.alice }
+"#,
+        )]);
+
+        let params = RelatedParams {
+            r#type: "note".to_string(),
+            id: "kickoff".to_string(),
+            direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
+        };
+
+        let result = execute(&graph, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "person.alice (person): Alice");
+
+        let params = RelatedParams {
+            r#type: "person".to_string(),
+            id: "alice".to_string(),
+            direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
+        };
+
+        let result = execute(&graph, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "note.kickoff (note)");
+    }
+
+    #[test]
+    fn test_related_type_filter() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+schema project {
+    field { name = "name" type = "string" required = true }
+}
+
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "assignee" type = "reference" required = false }
+    field { name = "project" type = "reference" required = false }
+}
+
+person john { name = "John" }
+project apollo { name = "Apollo" }
+task launch { name = "Launch" assignee = person.john project = project.apollo }
+"#,
+        )]);
+
+        // The task is followed to reach the project, but not returned
+        let params = RelatedParams {
+            r#type: "person".to_string(),
+            id: "john".to_string(),
+            direction: None,
+            degrees: Some(2),
+            related_type: Some("project".to_string()),
+            include_edges: false,
+            unlimited: false,
+        };
+
+        let result = execute(&graph, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "project.apollo (project): Apollo");
+
+        let params = RelatedParams {
+            r#type: "person".to_string(),
+            id: "john".to_string(),
+            direction: None,
+            degrees: None,
+            related_type: Some("project".to_string()),
+            include_edges: false,
+            unlimited: false,
+        };
+
+        let result = execute(&graph, &ResponseLimits::default(), &params);
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_text(&result),
+            "No related entities of type 'project' found."
+        );
+    }
+
+    #[test]
+    fn test_related_entity_not_found_suggests_similar() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person alice { name = "Alice" }
+"#,
+        )]);
+
+        let params = RelatedParams {
+            r#type: "person".to_string(),
+            id: "alicee".to_string(),
+            direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: false,
+            unlimited: false,
+        };
+
+        let result = execute(&graph, &ResponseLimits::default(), &params);
+
+        assert!(is_error(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Entity 'alicee' with type 'person' not found."));
+        assert!(text.contains("person.alice"));
+        assert!(text.contains("Use list with type='person'"));
+    }
+
+    #[test]
+    fn test_related_truncates_at_response_limits() {
+        let graph = create_graph(&[(
+            "data.firm",
+            r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "manager" type = "reference" required = false }
+}
+
+person alice { name = "Alice" }
+person bob { name = "Bob" manager = person.alice }
+person charlie { name = "Charlie" manager = person.alice }
+"#,
+        )]);
+        let limits = ResponseLimits {
+            max_entities: Some(1),
+            max_bytes: None,
+        };
+
+        let mut params = RelatedParams {
+            r#type: "person".to_string(),
+            id: "alice".to_string(),
+            direction: None,
+            degrees: None,
+            related_type: None,
+            include_edges: true,
+            unlimited: false,
+        };
+        let result = execute(&graph, &limits, &params);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        let edges = text.split("Edges").nth(1).unwrap();
+        assert_eq!(edges.matches(", manager, ").count(), 1);
+        assert!(text.ends_with(
+            "[truncated: 1 of 2 entities shown — refine with related_type/direction/degrees, \
+             or set unlimited=true to get everything]"
+        ));
+
+        params.unlimited = true;
+        let result = execute(&graph, &limits, &params);
+        let text = get_text(&result);
+        assert!(text.contains("person.bob"));
+        assert!(text.contains("person.charlie"));
+        assert!(!text.contains("[truncated"));
+    }
 }
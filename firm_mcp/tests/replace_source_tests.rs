@@ -2,9 +2,10 @@ mod helpers;
 
 use std::fs;
 
+use firm_lang::workspace::content_hash;
 use firm_mcp::tools::replace_source::{ReplaceSourceParams, execute};
-use firm_mcp::tools::write_source::{WriteSourceParams, rollback, validate_and_write};
-use helpers::{create_workspace, is_error, is_success};
+use firm_mcp::tools::write_source::{WriteSourceParams, preview, rollback, validate_and_write};
+use helpers::{create_workspace, get_text, is_error, is_success};
 
 #[cfg(test)]
 mod tests {
@@ -16,7 +17,7 @@ mod tests {
             "data.firm",
             r#"schema task {
     field { name = "title" type = "string" required = true }
-    field { name = "status" type = "enum" required = true values = ["pending", "done"] }
+    field { name = "status" type = "enum" required = true allowed_values = ["pending", "done"] }
 }
 task my_task {
     title = "Fix the bug"
@@ -30,6 +31,8 @@ task my_task {
             new_string: r#"status = enum"done""#.to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -64,6 +67,8 @@ task my_task {
             new_string: "replacement".to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -92,6 +97,8 @@ task task_two {
             new_string: "name".to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -117,6 +124,8 @@ task task_two {
             new_string: "name".to_string(),
             replace_all: true,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -138,6 +147,8 @@ task task_two {
             new_string: "something".to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -163,6 +174,8 @@ task my_task {
             new_string: "".to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -190,6 +203,8 @@ task my_task {
             new_string: r#"title = "New Title""#.to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -217,6 +232,8 @@ task my_task {
             new_string: "else".to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -240,6 +257,8 @@ task my_task {
             new_string: "true".to_string(), // Remove the field's closing brace - creates syntax error
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -251,6 +270,8 @@ task my_task {
             path: "data.firm".to_string(),
             content: replace_result.new_content,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let write_result = validate_and_write(dir.path(), &write_params);
@@ -275,6 +296,8 @@ task my_task {
             new_string: r#"title = "New""#.to_string(),
             replace_all: false,
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -287,6 +310,8 @@ task my_task {
             path: "data.firm".to_string(),
             content: replace_result.new_content.clone(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let write_result = validate_and_write(dir.path(), &write_params);
@@ -341,6 +366,8 @@ task my_task {
             new_string: r#"title = "Modified""#.to_string(),
             replace_all: false,
             force: true, // Force the write even if validation fails
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -353,6 +380,8 @@ task my_task {
             path: "data.firm".to_string(),
             content: replace_result.new_content.clone(),
             force: true,
+            dry_run: false,
+            base_hash: None,
         };
 
         let write_result = validate_and_write(dir.path(), &write_params);
@@ -402,6 +431,8 @@ task my_task {
             new_string: "".to_string(), // Delete the person
             replace_all: false,
             force: true,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = execute(dir.path(), &params);
@@ -414,6 +445,8 @@ task my_task {
             path: "data.firm".to_string(),
             content: replace_result.new_content.clone(),
             force: true,
+            dry_run: false,
+            base_hash: None,
         };
 
         let write_result = validate_and_write(dir.path(), &write_params);
@@ -453,4 +486,85 @@ task my_task {
             firm_mcp::tools::replace_source::validation_error_result("validation failed", true);
         assert!(is_error(&result));
     }
+
+    #[test]
+    fn test_replace_source_dry_run_does_not_modify_file() {
+        let original = r#"schema task {
+    field { name = "title" type = "string" required = true }
+}
+
+task bug {
+    title = "Fix the bug"
+}
+"#;
+        let (dir, _workspace) = create_workspace(&[("data.firm", original)]);
+
+        let params = ReplaceSourceParams {
+            path: "data.firm".to_string(),
+            old_string: "Fix the bug".to_string(),
+            new_string: "Fix the other bug".to_string(),
+            replace_all: false,
+            force: false,
+            dry_run: true,
+            base_hash: None,
+        };
+
+        // The replacement is computed, then previewed instead of written
+        let replace_result = execute(dir.path(), &params).unwrap();
+        let result = preview(dir.path(), None, &params.path, &replace_result.new_content);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Workspace would be valid."));
+        assert!(
+            text.contains("\n-    title = \"Fix the bug\"\n+    title = \"Fix the other bug\"\n")
+        );
+
+        let content = fs::read_to_string(dir.path().join("data.firm")).unwrap();
+        assert_eq!(content, original);
+    }
+
+    #[test]
+    fn test_replace_source_stale_base_hash() {
+        let original = r#"schema task {
+    field { name = "title" type = "string" required = true }
+}
+
+task my_task {
+    title = "Fix the bug"
+}
+"#;
+        let (dir, _workspace) = create_workspace(&[("data.firm", original)]);
+        let base_hash = content_hash(original);
+
+        // Someone else edits the file after it was read
+        let edited = original.replace("Fix the bug", "Fix the bug today");
+        fs::write(dir.path().join("data.firm"), &edited).unwrap();
+
+        let params = ReplaceSourceParams {
+            path: "data.firm".to_string(),
+            old_string: "Fix the bug".to_string(),
+            new_string: "Fix the other bug".to_string(),
+            replace_all: false,
+            force: false,
+            dry_run: false,
+            base_hash: Some(base_hash),
+        };
+
+        let error = execute(dir.path(), &params).unwrap_err();
+        assert!(is_error(&error));
+        assert!(get_text(&error).starts_with("Conflict: data.firm changed since it was read"));
+
+        // The current hash is accepted
+        let params = ReplaceSourceParams {
+            base_hash: Some(content_hash(&edited)),
+            ..params
+        };
+        let replace_result = execute(dir.path(), &params).unwrap();
+        assert!(
+            replace_result
+                .new_content
+                .contains("Fix the other bug today")
+        );
+    }
 }
@@ -0,0 +1,51 @@
+mod helpers;
+
+use std::fs;
+use std::time::Duration;
+
+use firm_lang::workspace::WorkspaceWatcher;
+use firm_mcp::resources::{ResourceSubscriptions, query_uri, source_file_uri};
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper to create a workspace directory and start watching it.
+    fn watched_workspace() -> (TempDir, WorkspaceWatcher) {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("people.firm"), "person jane {}\n").unwrap();
+        fs::write(dir.path().join("orgs.firm"), "organization acme {}\n").unwrap();
+
+        let watcher = WorkspaceWatcher::new(dir.path())
+            .unwrap()
+            .with_debounce(Duration::from_millis(50));
+        (dir, watcher)
+    }
+
+    #[test]
+    fn test_unsubscribed_files_only_change_the_list() {
+        let (dir, mut watcher) = watched_workspace();
+        let mut subscriptions = ResourceSubscriptions::default();
+        subscriptions
+            .subscribe(&source_file_uri("people.firm"))
+            .unwrap();
+        subscriptions.unsubscribe(&source_file_uri("people.firm"));
+
+        fs::write(dir.path().join("projects.firm"), "project launch {}\n").unwrap();
+        let changes = watcher.wait(Duration::from_secs(5));
+
+        let notifications = subscriptions.notifications(dir.path(), &changes);
+        assert!(notifications.updated.is_empty());
+        assert!(notifications.list_changed);
+    }
+
+    #[test]
+    fn test_subscribe_rejects_unknown_uris() {
+        let mut subscriptions = ResourceSubscriptions::default();
+
+        assert!(subscriptions.subscribe("firm://nothing/here").is_err());
+        assert!(subscriptions.subscribe("firm://reference/dsl").is_ok());
+        assert!(subscriptions.subscribe(&query_uri("open_tasks")).is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+mod helpers;
+
+use firm_core::ValueFormat;
+use firm_core::graph::EntityGraph;
+use firm_lang::workspace::{TimezoneConfig, WorkspaceBuild};
+use firm_mcp::tools::run_query::{RunQueryParams, execute};
+use firm_mcp::tools::{ResponseFormat, ResponseLimits};
+use helpers::{create_workspace, get_text, is_error, is_success};
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACE: &str = r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "is_completed" type = "boolean" required = true }
+}
+
+task write_docs { name = "Write docs" is_completed = false }
+task ship_release { name = "Ship release" is_completed = true }
+
+query open_tasks {
+    query = "from task | where is_completed == false"
+    description = "Tasks still to do"
+}
+
+query all_tasks {
+    query = "from task"
+}
+"#;
+
+    /// Helper to build a workspace and its graph together.
+    fn build_workspace(files: &[(&str, &str)]) -> (TempDir, WorkspaceBuild, EntityGraph) {
+        let (dir, mut workspace) = create_workspace(files);
+        let build = workspace.build().unwrap();
+
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities.clone()).unwrap();
+        graph.build();
+        (dir, build, graph)
+    }
+
+    fn run(name: Option<&str>, format: ResponseFormat) -> (TempDir, rmcp::model::CallToolResult) {
+        let (dir, build, graph) = build_workspace(&[("tasks.firm", WORKSPACE)]);
+        let params = RunQueryParams {
+            name: name.map(str::to_string),
+            strict: false,
+            format,
+            unlimited: false,
+        };
+        let result = execute(
+            &graph,
+            &build,
+            &TimezoneConfig::default(),
+            &ValueFormat::default(),
+            dir.path(),
+            &ResponseLimits::default(),
+            &params,
+        );
+        (dir, result)
+    }
+
+    #[test]
+    fn test_run_query_by_name() {
+        let (_dir, result) = run(Some("open_tasks"), ResponseFormat::Text);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("write_docs"));
+        assert!(!text.contains("ship_release"));
+    }
+
+    #[test]
+    fn test_run_query_lists_saved_queries() {
+        let (_dir, result) = run(None, ResponseFormat::Text);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("open_tasks - Tasks still to do (tasks.firm:"));
+        assert!(text.contains("from task | where is_completed == false"));
+        // Sorted by name
+        assert!(text.find("all_tasks").unwrap() < text.find("open_tasks").unwrap());
+    }
+
+    #[test]
+    fn test_run_query_lists_saved_queries_as_json() {
+        let (_dir, result) = run(None, ResponseFormat::Json);
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        let queries = json.as_array().unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[1]["name"], "open_tasks");
+        assert_eq!(queries[1]["description"], "Tasks still to do");
+        assert_eq!(queries[1]["path"], "tasks.firm");
+        assert!(queries[0]["description"].is_null());
+    }
+
+    #[test]
+    fn test_run_query_unknown_name() {
+        let (_dir, result) = run(Some("closed_tasks"), ResponseFormat::Text);
+
+        assert!(is_error(&result));
+        let text = get_text(&result);
+        assert!(text.contains("No query named 'closed_tasks'"));
+        assert!(text.contains("all_tasks, open_tasks"));
+    }
+
+    #[test]
+    fn test_saved_queries_are_not_entities() {
+        let (_dir, build, _graph) = build_workspace(&[("tasks.firm", WORKSPACE)]);
+
+        assert_eq!(build.entities.len(), 2);
+        assert_eq!(build.queries.len(), 2);
+    }
+}
@@ -0,0 +1,164 @@
+mod helpers;
+
+use firm_core::Entity;
+use firm_mcp::tools::search::{SearchParams, execute, search_entities};
+use helpers::{create_workspace, get_text, is_error, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACE: &str = r#"
+schema organization {
+    field { name = "name" type = "string" required = true }
+    field { name = "industry" type = "enum" allowed_values = ["software", "retail"] required = false }
+}
+
+schema person {
+    field { name = "name" type = "string" required = true }
+    field { name = "notes" type = "string" required = false }
+    field { name = "aliases" type = "list" required = false }
+}
+
+organization acme {
+    name = "Acme Corp"
+    industry = enum"software"
+}
+
+organization globex {
+    name = "Globex"
+    industry = enum"retail"
+}
+
+person jane {
+    name = "Jane Doe"
+    notes = "Met at the ACME offsite"
+    aliases = ["JD", "Acme Jane"]
+}
+
+person john {
+    name = "John Doe"
+    aliases = ["Johnny"]
+}
+"#;
+
+    /// Helper to build the workspace and get its entities.
+    fn create_entities() -> Vec<Entity> {
+        let (_dir, mut workspace) = create_workspace(&[("data.firm", WORKSPACE)]);
+        workspace.build().unwrap().entities
+    }
+
+    fn params(query: &str) -> SearchParams {
+        SearchParams {
+            query: query.to_string(),
+            r#type: None,
+            include_lists: false,
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_search_groups_matches_by_entity() {
+        let entities = create_entities();
+
+        let result = execute(&entities, &params("acme"));
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_text(&result),
+            "organization.acme:\n  name: Acme Corp\n\nperson.jane:\n  notes: Met at the ACME offsite"
+        );
+    }
+
+    #[test]
+    fn test_search_enum_values() {
+        let entities = create_entities();
+
+        let result = execute(&entities, &params("RETAIL"));
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_text(&result),
+            "organization.globex:\n  industry: retail"
+        );
+    }
+
+    #[test]
+    fn test_search_list_items() {
+        let entities = create_entities();
+
+        let matches = search_entities(&entities, "johnny", None, false);
+        assert!(matches.is_empty());
+
+        let matches = search_entities(&entities, "johnny", None, true);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entity.id.as_str(), "person.john");
+        assert_eq!(matches[0].fields[0].field.as_str(), "aliases");
+        assert_eq!(matches[0].fields[0].snippet, "Johnny");
+
+        // Each field is listed once, even when several items match
+        let matches = search_entities(&entities, "acme", Some("person"), true);
+        assert_eq!(matches.len(), 1);
+        let fields: Vec<&str> = matches[0].fields.iter().map(|m| m.field.as_str()).collect();
+        assert_eq!(fields, vec!["notes", "aliases"]);
+    }
+
+    #[test]
+    fn test_search_by_type() {
+        let entities = create_entities();
+
+        let result = execute(
+            &entities,
+            &SearchParams {
+                r#type: Some("person".to_string()),
+                ..params("doe")
+            },
+        );
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("person.jane:\n  name: Jane Doe"));
+        assert!(text.contains("person.john:\n  name: John Doe"));
+        assert!(!text.contains("organization"));
+    }
+
+    #[test]
+    fn test_search_limit() {
+        let entities = create_entities();
+
+        let result = execute(
+            &entities,
+            &SearchParams {
+                limit: Some(1),
+                ..params("doe")
+            },
+        );
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.starts_with("person.jane:"));
+        assert!(!text.contains("person.john"));
+        assert!(text.ends_with(
+            "Showing 1 of 2 matching entities. Use a higher 'limit' or a 'type' to see more."
+        ));
+    }
+
+    #[test]
+    fn test_search_no_matches() {
+        let entities = create_entities();
+
+        let result = execute(&entities, &params("initech"));
+
+        assert!(is_success(&result));
+        assert_eq!(get_text(&result), "No entities match 'initech'.");
+    }
+
+    #[test]
+    fn test_search_empty_query() {
+        let entities = create_entities();
+
+        let result = execute(&entities, &params(""));
+
+        assert!(is_error(&result));
+    }
+}
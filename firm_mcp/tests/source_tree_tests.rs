@@ -1,6 +1,6 @@
 mod helpers;
 
-use firm_mcp::tools::source_tree::execute;
+use firm_mcp::tools::source_tree::{SourceTreeParams, execute};
 use helpers::{create_workspace, get_text, is_success};
 use tempfile::TempDir;
 
@@ -8,6 +8,7 @@ use tempfile::TempDir;
 mod tests {
     use super::*;
     use firm_lang::workspace::Workspace;
+    use firm_mcp::tools::response_format::ResponseFormat;
 
     #[test]
     fn test_source_tree_single_file_at_root() {
@@ -20,10 +21,13 @@ schema person {
 "#,
         )]);
 
-        let result = execute(&workspace, dir.path());
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
 
         assert!(is_success(&result));
-        assert_eq!(get_text(&result), "main.firm");
+        assert_eq!(
+            get_text(&result),
+            "main.firm (1 schema)\n  schema person (line 2)\n\nTotal: 1 file, 1 schema"
+        );
     }
 
     #[test]
@@ -55,7 +59,7 @@ person john {
             ),
         ]);
 
-        let result = execute(&workspace, dir.path());
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -89,7 +93,7 @@ person alice {
             ),
         ]);
 
-        let result = execute(&workspace, dir.path());
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -109,7 +113,7 @@ person alice {
             .load_directory(&dir.path().to_path_buf())
             .expect("Failed to load workspace");
 
-        let result = execute(&workspace, dir.path());
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
 
         assert!(is_success(&result));
         assert_eq!(get_text(&result), "No .firm source files found.");
@@ -136,7 +140,7 @@ schema account {
             ),
         ]);
 
-        let result = execute(&workspace, dir.path());
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
 
         assert!(is_success(&result));
         let text = get_text(&result);
@@ -155,14 +159,169 @@ schema account {
             ("m_middle.firm", "schema m { field { name = \"n\" type = \"string\" required = true } }"),
         ]);
 
-        let result = execute(&workspace, dir.path());
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
 
         assert!(is_success(&result));
         let text = get_text(&result);
         let lines: Vec<&str> = text.lines().collect();
 
-        assert_eq!(lines[0], "a_first.firm");
-        assert_eq!(lines[1], "m_middle.firm");
-        assert_eq!(lines[2], "z_last.firm");
+        assert_eq!(lines[0], "a_first.firm (1 schema)");
+        assert_eq!(lines[2], "m_middle.firm (1 schema)");
+        assert_eq!(lines[4], "z_last.firm (1 schema)");
+    }
+
+    const PEOPLE: &str = r#"
+schema person {
+    field { name = "name" type = "string" required = true }
+}
+"#;
+
+    fn nested_workspace() -> (TempDir, Workspace) {
+        create_workspace(&[
+            ("schemas.firm", PEOPLE),
+            (
+                "people/team.firm",
+                r#"
+person alice {
+    name = "Alice"
+}
+
+person bob {
+    name = "Bob"
+}
+"#,
+            ),
+            (
+                "people/contractors/2024/q1.firm",
+                r#"
+person carol {
+    name = "Carol"
+}
+"#,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_source_tree_outlines_definitions() {
+        let (dir, workspace) = nested_workspace();
+
+        let result = execute(&workspace, dir.path(), &SourceTreeParams::default());
+
+        assert!(is_success(&result));
+        assert_eq!(
+            get_text(&result),
+            "schemas.firm (1 schema)
+  schema person (line 2)
+people/ (1 file, 2 entities)
+  team.firm (2 entities)
+    person alice (line 2)
+    person bob (line 6)
+people/contractors/2024/ (1 file, 1 entity)
+  q1.firm (1 entity)
+    person carol (line 2)
+
+Total: 3 files, 1 schema, 3 entities"
+        );
+    }
+
+    #[test]
+    fn test_source_tree_path_prefix_and_depth() {
+        let (dir, workspace) = nested_workspace();
+
+        // Only the files under the prefix
+        let result = execute(
+            &workspace,
+            dir.path(),
+            &SourceTreeParams {
+                path_prefix: Some("people/".to_string()),
+                ..Default::default()
+            },
+        );
+        let text = get_text(&result);
+        assert!(!text.contains("schemas.firm"));
+        assert!(text.contains("person carol (line 2)"));
+        assert!(text.ends_with("Total: 2 files, 3 entities"));
+
+        // Deeper directories are summarized in their ancestor
+        let result = execute(
+            &workspace,
+            dir.path(),
+            &SourceTreeParams {
+                depth: Some(1),
+                ..Default::default()
+            },
+        );
+        let text = get_text(&result);
+        assert!(text.contains("people/ (2 files, 3 entities)"));
+        assert!(text.contains("  + 1 file, 1 entity in subdirectories"));
+        assert!(!text.contains("carol"));
+        assert!(text.ends_with("Total: 3 files, 1 schema, 3 entities"));
+
+        // Depth counts from the prefix
+        let result = execute(
+            &workspace,
+            dir.path(),
+            &SourceTreeParams {
+                path_prefix: Some("people".to_string()),
+                depth: Some(0),
+                ..Default::default()
+            },
+        );
+        let text = get_text(&result);
+        assert!(text.starts_with("people/ (2 files, 3 entities)\n  team.firm (2 entities)"));
+        assert!(text.contains("  + 1 file, 1 entity in subdirectories"));
+
+        let result = execute(
+            &workspace,
+            dir.path(),
+            &SourceTreeParams {
+                path_prefix: Some("archive".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            get_text(&result),
+            "No .firm source files found under 'archive'."
+        );
+    }
+
+    #[test]
+    fn test_source_tree_json() {
+        let (dir, workspace) = nested_workspace();
+
+        let result = execute(
+            &workspace,
+            dir.path(),
+            &SourceTreeParams {
+                depth: Some(1),
+                format: ResponseFormat::Json,
+                ..Default::default()
+            },
+        );
+
+        assert!(is_success(&result));
+        let json: serde_json::Value = serde_json::from_str(&get_text(&result)).unwrap();
+        assert_eq!(
+            json["totals"],
+            serde_json::json!({ "files": 3, "schemas": 1, "entities": 3 })
+        );
+        assert_eq!(json["directories"][0]["path"], "");
+        assert_eq!(
+            json["directories"][0]["files"][0]["definitions"][0],
+            serde_json::json!({ "kind": "schema", "name": "person", "line": 2 })
+        );
+
+        let people = &json["directories"][1];
+        assert_eq!(people["path"], "people");
+        assert_eq!(
+            people["files"][0]["definitions"][1],
+            serde_json::json!({ "kind": "entity", "type": "person", "id": "bob", "line": 6 })
+        );
+        assert_eq!(
+            people["hidden"],
+            serde_json::json!({ "files": 1, "schemas": 0, "entities": 1 })
+        );
+        assert_eq!(people["counts"]["files"], 2);
     }
 }
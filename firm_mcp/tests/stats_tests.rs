@@ -0,0 +1,204 @@
+mod helpers;
+
+use firm_core::graph::EntityGraph;
+use firm_lang::workspace::TimezoneConfig;
+use firm_mcp::tools::stats::{StatsParams, execute};
+use helpers::{create_workspace, get_text, get_texts, is_error, is_success};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = r#"
+schema task {
+    field { name = "name" type = "string" required = true }
+    field { name = "status" type = "string" required = false }
+    field { name = "hours" type = "integer" required = true }
+}
+
+task write { name = "Write" status = "open" hours = 3 }
+task review { name = "Review" status = "open" hours = 2 }
+task ship { name = "Ship" status = "done" hours = 4 }
+task plan { name = "Plan" hours = 1 }
+"#;
+
+    /// Helper to build workspace and graph together.
+    fn create_graph(files: &[(&str, &str)]) -> EntityGraph {
+        let (_dir, mut workspace) = create_workspace(files);
+        let build = workspace.build().unwrap();
+
+        let mut graph = EntityGraph::new();
+        graph.add_entities(build.entities).unwrap();
+        graph.build();
+        graph
+    }
+
+    fn params() -> StatsParams {
+        StatsParams {
+            query: None,
+            r#type: None,
+            r#where: None,
+            group_by: None,
+            aggregate: None,
+        }
+    }
+
+    /// Runs the stats tool, returning its summary and JSON data.
+    fn run(graph: &EntityGraph, params: StatsParams) -> (String, serde_json::Value) {
+        let result = execute(graph, &TimezoneConfig::default(), &params);
+        assert!(is_success(&result), "{:?}", result);
+        let texts = get_texts(&result);
+        assert_eq!(texts.len(), 2);
+        (texts[0].clone(), serde_json::from_str(&texts[1]).unwrap())
+    }
+
+    #[test]
+    fn test_stats_query() {
+        let graph = create_graph(&[("tasks.firm", TASKS)]);
+
+        let (summary, json) = run(
+            &graph,
+            StatsParams {
+                query: Some("from task | where hours > 1 | sum hours".to_string()),
+                ..params()
+            },
+        );
+        assert_eq!(summary, "sum hours of task: 9");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "query": "from task | where hours > 1 | sum hours",
+                "result": { "Sum": { "Integer": 9 } },
+            })
+        );
+
+        let (summary, _) = run(
+            &graph,
+            StatsParams {
+                query: Some("from task | average hours".to_string()),
+                ..params()
+            },
+        );
+        assert_eq!(summary, "average hours of task: 2.5");
+    }
+
+    #[test]
+    fn test_stats_shorthand_group_by() {
+        let graph = create_graph(&[("tasks.firm", TASKS)]);
+
+        let (summary, json) = run(
+            &graph,
+            StatsParams {
+                r#type: Some("task".to_string()),
+                group_by: Some("status".to_string()),
+                ..params()
+            },
+        );
+        assert_eq!(summary, "count of task by status: done 1, open 2, (none) 1");
+        assert_eq!(json["query"], "from task | group status | count");
+        assert_eq!(
+            json["result"],
+            serde_json::json!({
+                "Grouped": {
+                    "field": "status",
+                    "aggregate": "count",
+                    "groups": [
+                        ["done", { "Count": 1 }],
+                        ["open", { "Count": 2 }],
+                        [null, { "Count": 1 }],
+                    ],
+                }
+            })
+        );
+
+        // Filters and other aggregations compose into the query too
+        let (summary, json) = run(
+            &graph,
+            StatsParams {
+                r#type: Some("task".to_string()),
+                r#where: Some("hours >= 2".to_string()),
+                group_by: Some("status".to_string()),
+                aggregate: Some("sum hours".to_string()),
+                ..params()
+            },
+        );
+        assert_eq!(summary, "sum hours of task by status: done 4, open 5");
+        assert_eq!(
+            json["query"],
+            "from task | where hours >= 2 | group status | sum hours"
+        );
+    }
+
+    #[test]
+    fn test_stats_requires_aggregation() {
+        let graph = create_graph(&[("tasks.firm", TASKS)]);
+
+        let result = execute(
+            &graph,
+            &TimezoneConfig::default(),
+            &StatsParams {
+                query: Some("from task | where hours > 1".to_string()),
+                ..params()
+            },
+        );
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("doesn't end in an aggregation"));
+
+        let result = execute(
+            &graph,
+            &TimezoneConfig::default(),
+            &StatsParams {
+                query: Some("from task | select name".to_string()),
+                ..params()
+            },
+        );
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("not a numeric aggregation"));
+    }
+
+    #[test]
+    fn test_stats_invalid_params() {
+        let graph = create_graph(&[("tasks.firm", TASKS)]);
+        let timezone = TimezoneConfig::default();
+
+        let result = execute(&graph, &timezone, &params());
+        assert!(is_error(&result));
+        assert!(get_text(&result).starts_with("Provide a 'query'"));
+
+        let result = execute(
+            &graph,
+            &timezone,
+            &StatsParams {
+                query: Some("from task | count".to_string()),
+                r#type: Some("task".to_string()),
+                ..params()
+            },
+        );
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("either 'query' or 'type'"));
+
+        let result = execute(
+            &graph,
+            &timezone,
+            &StatsParams {
+                query: Some("from task | count".to_string()),
+                group_by: Some("status".to_string()),
+                ..params()
+            },
+        );
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("only apply with 'type'"));
+
+        let result = execute(
+            &graph,
+            &timezone,
+            &StatsParams {
+                r#type: Some("task".to_string()),
+                aggregate: Some("total hours".to_string()),
+                ..params()
+            },
+        );
+        assert!(is_error(&result));
+        assert!(get_text(&result).starts_with("Failed to parse query 'from task | total hours'"));
+    }
+}
@@ -0,0 +1,109 @@
+mod helpers;
+
+use firm_mcp::journal::ChangeJournal;
+use firm_mcp::tools::list_changes;
+use firm_mcp::tools::undo_last_change::{UndoLastChangeParams, execute, rollback, success_result};
+use helpers::{get_text, is_success};
+use std::fs;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a change to three files and records it in a journal.
+    fn setup() -> (TempDir, ChangeJournal) {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.firm"), "after").unwrap();
+        fs::create_dir_all(dir.path().join("people")).unwrap();
+        fs::write(dir.path().join("people/new.firm"), "created").unwrap();
+
+        let mut journal = ChangeJournal::new();
+        journal.record(
+            dir.path(),
+            "write_source",
+            "Wrote main.firm",
+            [
+                ("main.firm".to_string(), Some("before".to_string())),
+                ("people/new.firm".to_string(), None),
+                ("gone.firm".to_string(), Some("deleted".to_string())),
+            ],
+        );
+        (dir, journal)
+    }
+
+    fn read(dir: &TempDir, path: &str) -> Option<String> {
+        fs::read_to_string(dir.path().join(path)).ok()
+    }
+
+    #[test]
+    fn test_undo_last_change_restores_files() {
+        let (dir, journal) = setup();
+        let entry = journal.last().unwrap();
+
+        let restored = execute(dir.path(), entry, &UndoLastChangeParams { force: false }).unwrap();
+        assert_eq!(restored.len(), 3);
+        assert_eq!(read(&dir, "main.firm").as_deref(), Some("before"));
+        assert_eq!(read(&dir, "people/new.firm"), None);
+        assert_eq!(read(&dir, "gone.firm").as_deref(), Some("deleted"));
+
+        let result = success_result(entry);
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Undid change #1 by write_source: Wrote main.firm"));
+        assert!(text.contains("- main.firm (restored)"));
+        assert!(text.contains("- people/new.firm (removed)"));
+        assert!(text.contains("- gone.firm (recreated)"));
+
+        // Rolling back puts the change back
+        assert!(rollback(dir.path(), &restored));
+        assert_eq!(read(&dir, "main.firm").as_deref(), Some("after"));
+        assert_eq!(read(&dir, "people/new.firm").as_deref(), Some("created"));
+        assert_eq!(read(&dir, "gone.firm"), None);
+    }
+
+    #[test]
+    fn test_undo_last_change_keeps_later_edits() {
+        let (dir, journal) = setup();
+        let entry = journal.last().unwrap();
+        fs::write(dir.path().join("main.firm"), "edited").unwrap();
+
+        let error = execute(dir.path(), entry, &UndoLastChangeParams { force: false }).unwrap_err();
+        assert!(error.contains("main.firm was edited after 'write_source' changed it"));
+        assert_eq!(read(&dir, "main.firm").as_deref(), Some("edited"));
+        assert_eq!(read(&dir, "people/new.firm").as_deref(), Some("created"));
+
+        // Forced, the edits are undone too
+        execute(dir.path(), entry, &UndoLastChangeParams { force: true }).unwrap();
+        assert_eq!(read(&dir, "main.firm").as_deref(), Some("before"));
+    }
+
+    #[test]
+    fn test_list_changes() {
+        let result = list_changes::execute(&ChangeJournal::new());
+        assert!(get_text(&result).starts_with("No changes recorded"));
+
+        let (dir, mut journal) = setup();
+        fs::write(dir.path().join("main.firm"), "again").unwrap();
+        journal.record(
+            dir.path(),
+            "replace_source",
+            "Replaced 1 occurrence(s) in main.firm",
+            [("main.firm".to_string(), Some("after".to_string()))],
+        );
+
+        let result = list_changes::execute(&journal);
+        assert!(is_success(&result));
+        let lines: Vec<String> = get_text(&result).lines().map(String::from).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("2 change(s), most recent first"));
+        assert_eq!(
+            lines[1],
+            "#2 replace_source: Replaced 1 occurrence(s) in main.firm (main.firm)"
+        );
+        assert_eq!(
+            lines[2],
+            "#1 write_source: Wrote main.firm (main.firm, people/new.firm, gone.firm)"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+mod helpers;
+
+use firm_mcp::tools::update_entity::{UpdateEntityParams, execute, rollback};
+use helpers::create_workspace;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+schema task {
+    field { name = "title" type = "string" required = true }
+    field { name = "priority" type = "integer" required = false }
+    field { name = "status" type = "enum" allowed_values = ["Open", "Done"] required = false }
+    field { name = "notes" type = "string" required = false }
+}
+"#;
+
+    fn params(set: &[(&str, serde_json::Value)], unset: &[&str]) -> UpdateEntityParams {
+        UpdateEntityParams {
+            r#type: "task".to_string(),
+            id: "bug_fix".to_string(),
+            set: set
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect::<HashMap<_, _>>(),
+            unset: unset.iter().map(|name| name.to_string()).collect(),
+            list_item_types: None,
+            force: false,
+        }
+    }
+
+    #[test]
+    fn test_update_entity_edits_only_affected_fields() {
+        let tasks = r#"task bug_fix {
+    // Keep this comment
+    title = "Fix bug"
+    priority = 1
+    notes = "Old notes"
+}
+"#;
+        let (dir, mut workspace) =
+            create_workspace(&[("schema.firm", SCHEMA), ("tasks.firm", tasks)]);
+        let build = workspace.build().unwrap();
+
+        let params = params(
+            &[("priority", json!(3)), ("status", json!("done"))],
+            &["notes"],
+        );
+        let result = execute(dir.path(), &workspace, &build, &params).unwrap();
+
+        let content = fs::read_to_string(dir.path().join("tasks.firm")).unwrap();
+        assert_eq!(
+            content,
+            r#"task bug_fix {
+    // Keep this comment
+    title = "Fix bug"
+    priority = 3
+    status = enum"Done"
+}
+"#
+        );
+        assert_eq!(result.changed_files.len(), 1);
+        assert_eq!(result.changed_files[0].path, "tasks.firm");
+        assert_eq!(result.dsl, content.trim_end());
+
+        // Rolling back restores the original file
+        assert!(rollback(dir.path(), &result.changed_files));
+        let content = fs::read_to_string(dir.path().join("tasks.firm")).unwrap();
+        assert_eq!(content, tasks);
+    }
+
+    #[test]
+    fn test_update_entity_rejects_unknown_and_unset_fields() {
+        let (dir, mut workspace) = create_workspace(&[
+            ("schema.firm", SCHEMA),
+            ("tasks.firm", "task bug_fix {\n    title = \"Fix bug\"\n}\n"),
+        ]);
+        let build = workspace.build().unwrap();
+
+        let result = execute(
+            dir.path(),
+            &workspace,
+            &build,
+            &params(&[("estimate", json!(2))], &[]),
+        );
+        assert!(result.unwrap_err().contains("not found in schema"));
+
+        let result = execute(dir.path(), &workspace, &build, &params(&[], &["notes"]));
+        assert!(result.unwrap_err().contains("is not set"));
+
+        let result = execute(dir.path(), &workspace, &build, &params(&[], &[]));
+        assert!(result.unwrap_err().contains("Nothing to update"));
+
+        let mut missing = params(&[("priority", json!(2))], &[]);
+        missing.id = "nothing".to_string();
+        let result = execute(dir.path(), &workspace, &build, &missing);
+        assert!(result.unwrap_err().contains("not found"));
+
+        // Nothing was written
+        let content = fs::read_to_string(dir.path().join("tasks.firm")).unwrap();
+        assert_eq!(content, "task bug_fix {\n    title = \"Fix bug\"\n}\n");
+    }
+}
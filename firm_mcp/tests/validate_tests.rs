@@ -0,0 +1,113 @@
+mod helpers;
+
+use std::fs;
+
+use firm_mcp::tools::validate::{ValidateParams, execute};
+use helpers::{create_workspace, get_text, is_error, is_success};
+use serde_json::Value;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEOPLE: &str = r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john {
+    name = "John"
+}
+"#;
+
+    /// Helper to validate and parse the JSON result.
+    fn validate(dir: &std::path::Path, path: &str, content: Option<&str>) -> Value {
+        let params = ValidateParams {
+            path: path.to_string(),
+            content: content.map(str::to_string),
+        };
+        let result = execute(dir, None, &params);
+        assert!(is_success(&result));
+        serde_json::from_str(&get_text(&result)).unwrap()
+    }
+
+    #[test]
+    fn test_validate_file_on_disk() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let json = validate(dir.path(), "people.firm", None);
+
+        assert_eq!(json["path"], "people.firm");
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["errors"], 0);
+        assert!(json["diagnostics"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_content_reports_diagnostics_without_writing() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+        let draft = "person jane {\n    name = \"Jane\"\n    manager = person.nobody\n}\n";
+
+        let json = validate(dir.path(), "more/jane.firm", Some(draft));
+
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["errors"], 1);
+        let diagnostics = json["diagnostics"].as_array().unwrap();
+        let diagnostic = diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic["code"] == "broken-reference")
+            .expect("Expected a broken reference");
+        assert_eq!(diagnostic["file"], "more/jane.firm");
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["line"], 3);
+        assert_eq!(diagnostic["column"], 5);
+        assert!(diagnostic["message"].as_str().unwrap().contains("nobody"));
+
+        // Nothing is written
+        assert!(!dir.path().join("more/jane.firm").exists());
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(content, PEOPLE);
+    }
+
+    #[test]
+    fn test_validate_content_reports_syntax_errors_with_columns() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+        let draft = PEOPLE.replace("name = \"John\"", "name = ");
+
+        let json = validate(dir.path(), "people.firm", Some(&draft));
+
+        assert_eq!(json["valid"], false);
+        let diagnostics = json["diagnostics"].as_array().unwrap();
+        assert!(
+            diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic["code"] == "syntax-error")
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_file_without_content() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let params = ValidateParams {
+            path: "missing.firm".to_string(),
+            content: None,
+        };
+        let result = execute(dir.path(), None, &params);
+
+        assert!(is_error(&result));
+        assert!(get_text(&result).contains("File not found: missing.firm"));
+    }
+
+    #[test]
+    fn test_validate_rejects_paths_outside_the_workspace() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let params = ValidateParams {
+            path: "../outside.firm".to_string(),
+            content: Some("person jane {}\n".to_string()),
+        };
+        let result = execute(dir.path(), None, &params);
+
+        assert!(is_error(&result));
+    }
+}
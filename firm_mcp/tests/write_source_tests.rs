@@ -2,8 +2,9 @@ mod helpers;
 
 use std::fs;
 
+use firm_lang::workspace::content_hash;
 use firm_mcp::tools::write_source::{
-    WriteSourceParams, force_success_result, rollback, success_result, validate_and_write,
+    WriteSourceParams, force_success_result, preview, rollback, success_result, validate_and_write,
     validation_error_result,
 };
 use helpers::{create_workspace, get_text, is_error, is_success};
@@ -27,6 +28,8 @@ mod tests {
             path: "new.firm".to_string(),
             content: content.to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -56,6 +59,8 @@ mod tests {
             path: "existing.firm".to_string(),
             content: new_content.to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -85,6 +90,8 @@ mod tests {
             content: "schema deep { field { name = \"x\" type = \"string\" required = true } }"
                 .to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -101,6 +108,8 @@ mod tests {
             path: "bad.firm".to_string(),
             content: "this is not valid { syntax".to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -124,6 +133,8 @@ mod tests {
 "#
             .to_string(), // Missing closing brace
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -141,6 +152,8 @@ mod tests {
             path: "file.txt".to_string(),
             content: "schema test {}".to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -255,6 +268,8 @@ mod tests {
             path: "empty.firm".to_string(),
             content: "".to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         // Empty content should be valid (no syntax errors)
@@ -273,6 +288,8 @@ mod tests {
             path: "comments.firm".to_string(),
             content: "// This is just a comment\n// Another comment".to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
@@ -300,10 +317,124 @@ organization acme { name = "ACME" }
 "#
             .to_string(),
             force: false,
+            dry_run: false,
+            base_hash: None,
         };
 
         let result = validate_and_write(dir.path(), &params);
 
         assert!(result.is_ok());
     }
+
+    // ============== preview Tests ==============
+
+    const PEOPLE: &str = r#"schema person {
+    field { name = "name" type = "string" required = true }
+}
+
+person john {
+    name = "John"
+}
+"#;
+
+    #[test]
+    fn test_preview_valid_change_returns_diff() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+        let proposed = PEOPLE.replace("\"John\"", "\"Johnny\"");
+
+        let result = preview(dir.path(), None, "people.firm", &proposed);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("no changes were written"));
+        assert!(text.contains("Workspace would be valid."));
+        assert!(text.contains("--- a/people.firm\n+++ b/people.firm\n"));
+        assert!(text.contains("\n-    name = \"John\"\n+    name = \"Johnny\"\n"));
+
+        // The file is untouched
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(content, PEOPLE);
+    }
+
+    #[test]
+    fn test_preview_reports_validation_errors() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+        let proposed = PEOPLE.replace("    name = \"John\"\n", "");
+
+        let result = preview(dir.path(), None, "people.firm", &proposed);
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Validation would fail"));
+        assert!(text.contains("\n-    name = \"John\"\n"));
+
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(content, PEOPLE);
+    }
+
+    #[test]
+    fn test_preview_new_file_is_not_created() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let result = preview(
+            dir.path(),
+            None,
+            "more/people.firm",
+            "person jane {\n    name = \"Jane\"\n}\n",
+        );
+
+        assert!(is_success(&result));
+        let text = get_text(&result);
+        assert!(text.contains("Workspace would be valid."));
+        assert!(text.contains("--- /dev/null\n+++ b/more/people.firm\n"));
+        assert!(!dir.path().join("more").exists());
+    }
+
+    #[test]
+    fn test_preview_unchanged_content() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let result = preview(dir.path(), None, "people.firm", PEOPLE);
+
+        assert!(is_success(&result));
+        assert!(get_text(&result).contains("The content is unchanged."));
+    }
+
+    #[test]
+    fn test_preview_rejects_invalid_syntax() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+
+        let result = preview(dir.path(), None, "people.firm", "person john {");
+
+        assert!(is_error(&result));
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert_eq!(content, PEOPLE);
+    }
+
+    #[test]
+    fn test_validate_and_write_checks_base_hash() {
+        let (dir, _workspace) = create_workspace(&[("people.firm", PEOPLE)]);
+        let base_hash = content_hash(PEOPLE);
+
+        let params = WriteSourceParams {
+            path: "people.firm".to_string(),
+            content: PEOPLE.replace("\"John\"", "\"Johnny\""),
+            force: false,
+            dry_run: false,
+            base_hash: Some(base_hash.clone()),
+        };
+        assert!(validate_and_write(dir.path(), &params).is_ok());
+
+        // The file changed since the base hash was taken, so the next write is refused
+        let stale_params = WriteSourceParams {
+            content: PEOPLE.replace("\"John\"", "\"Jon\""),
+            ..params
+        };
+        let error = validate_and_write(dir.path(), &stale_params).unwrap_err();
+        assert!(is_error(&error));
+        assert!(get_text(&error).starts_with("Conflict: people.firm changed since it was read"));
+
+        let content = fs::read_to_string(dir.path().join("people.firm")).unwrap();
+        assert!(content.contains("\"Johnny\""));
+    }
 }